@@ -0,0 +1,12 @@
+fn main() {
+    // Regenerates the C header for the `ffi` feature
+    #[cfg(feature = "ffi")]
+    {
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        cbindgen::generate(&crate_dir)
+            .expect("Could not generate the C header")
+            .write_to_file(format!("{}/include/rs_ecc.h", crate_dir));
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+    }
+}
@@ -0,0 +1,116 @@
+#ifndef RS_ECC_H
+#define RS_ECC_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum RsEccError {
+  RS_ECC_ERROR_OK = 0,
+  RS_ECC_ERROR_NULL_POINTER = 1,
+  RS_ECC_ERROR_INVALID_CURVE = 2,
+  RS_ECC_ERROR_INVALID_KEY = 3,
+  RS_ECC_ERROR_INVALID_SIGNATURE = 4,
+  RS_ECC_ERROR_BUFFER_TOO_SMALL = 5,
+  RS_ECC_ERROR_PANIC = 6,
+} RsEccError;
+
+typedef struct RsEccCurve RsEccCurve;
+
+typedef struct RsEccBytes {
+  const uint8_t *ptr;
+  size_t len;
+} RsEccBytes;
+
+/**
+ * Creates a curve y² = x³ + ax + b over F_p with generator (gx, gy) of order
+ * q, all given as big-endian integers.
+ *
+ * # Safety
+ *
+ * Every `RsEccBytes` must point to `len` readable bytes and `out` must be
+ * a valid pointer. The handle written to `out` must be released with
+ * `rs_ecc_curve_free`.
+ */
+enum RsEccError rs_ecc_curve_new(struct RsEccBytes a,
+                                 struct RsEccBytes b,
+                                 struct RsEccBytes p,
+                                 struct RsEccBytes gx,
+                                 struct RsEccBytes gy,
+                                 struct RsEccBytes q,
+                                 struct RsEccCurve **out);
+
+/**
+ * # Safety
+ *
+ * `curve` must come from `rs_ecc_curve_new` and not have been freed, or be
+ * null.
+ */
+void rs_ecc_curve_free(struct RsEccCurve *curve);
+
+/**
+ * Writes a fresh private key, `scalar_len` bytes.
+ *
+ * # Safety
+ *
+ * `curve` must be a live handle, `out` must point to `*out_len` writable
+ * bytes and `out_len` must be a valid pointer.
+ */
+enum RsEccError rs_ecc_generate_private_key(const struct RsEccCurve *curve,
+                                            uint8_t *out,
+                                            size_t *out_len);
+
+/**
+ * Writes the compressed SEC1 public key of `priv_key`.
+ *
+ * # Safety
+ *
+ * `curve` must be a live handle, `priv_key` must point to `priv_key_len`
+ * readable bytes, `out` to `*out_len` writable bytes and `out_len` must be a
+ * valid pointer.
+ */
+enum RsEccError rs_ecc_public_key(const struct RsEccCurve *curve,
+                                  const uint8_t *priv_key,
+                                  size_t priv_key_len,
+                                  uint8_t *out,
+                                  size_t *out_len);
+
+/**
+ * Signs SHA-256(message) and writes r || s.
+ *
+ * # Safety
+ *
+ * `curve` must be a live handle, `priv_key` and `message` must point to
+ * their lengths in readable bytes, `out` to `*out_len` writable bytes and
+ * `out_len` must be a valid pointer.
+ */
+enum RsEccError rs_ecc_sign(const struct RsEccCurve *curve,
+                            const uint8_t *priv_key,
+                            size_t priv_key_len,
+                            const uint8_t *message,
+                            size_t message_len,
+                            uint8_t *out,
+                            size_t *out_len);
+
+/**
+ * Returns RS_ECC_ERROR_OK when `signature` is a valid signature of `message`
+ * under `pub_key`, RS_ECC_ERROR_INVALID_SIGNATURE otherwise.
+ *
+ * # Safety
+ *
+ * `curve` must be a live handle and every other pointer must point to its
+ * length in readable bytes.
+ */
+enum RsEccError rs_ecc_verify(const struct RsEccCurve *curve,
+                              const uint8_t *pub_key,
+                              size_t pub_key_len,
+                              const uint8_t *message,
+                              size_t message_len,
+                              const uint8_t *signature,
+                              size_t signature_len);
+
+#endif /* RS_ECC_H */
@@ -0,0 +1,294 @@
+// Cryptocurrency addresses of secp256k1 public keys:
+// - Bitcoin P2PKH: Base58Check of a version byte (0x00 on mainnet, 0x6f on
+//   testnet) and HASH160 of the compressed key
+// - Ethereum: the last 20 bytes of Keccak-256 of x || y, written in hex with
+//   the EIP-55 mixed case checksum
+// `segwit_address` writes witness programs in bech32 (version 0, BIP 173) or
+// bech32m (later versions, BIP 350).
+// Other curves have no addresses, so these return None for them.
+
+use alloc::{string::String, vec::Vec};
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::hash::{hash160, keccak256, sha256};
+use crate::ECDSA;
+
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const BECH32_ALPHABET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32_CONSTANT: u32 = 1;
+const BECH32M_CONSTANT: u32 = 0x2bc8_30a3;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Network {
+    Bitcoin,
+    BitcoinTestnet,
+    Ethereum,
+}
+
+impl ECDSA {
+    pub fn address(&self, pub_key: &Point, network: Network) -> Option<String> {
+        if !self.is_secp256k1() || !self.is_valid_pub_key(pub_key) {
+            return None;
+        }
+        match network {
+            Network::Bitcoin => Some(base58check(0x00, &hash160(&self.encode_point(pub_key)))),
+            Network::BitcoinTestnet => {
+                Some(base58check(0x6f, &hash160(&self.encode_point(pub_key))))
+            }
+            Network::Ethereum => {
+                let hash = keccak256(&self.encode_point_uncompressed(pub_key)[1..]);
+                Some(eip55(&hash[12..]))
+            }
+        }
+    }
+}
+
+pub fn base58check(version: u8, payload: &[u8]) -> String {
+    // version || payload || first 4 bytes of SHA-256(SHA-256(version || payload))
+    let mut data = [&[version], payload].concat();
+    let checksum = sha256(&sha256(&data));
+    data.extend(&checksum[..4]);
+    base58(&data)
+}
+
+pub fn base58(data: &[u8]) -> String {
+    // Each leading zero byte is a '1', the rest is the number in base 58
+    let zeros = data.iter().take_while(|b| **b == 0).count();
+    let digits = BigUint::from_bytes_be(data).to_radix_be(58);
+    let digits = if digits == [0] { &[][..] } else { &digits[..] };
+    let mut encoded: Vec<u8> = alloc::vec![b'1'; zeros];
+    encoded.extend(digits.iter().map(|d| BASE58_ALPHABET[*d as usize]));
+    String::from_utf8(encoded).unwrap()
+}
+
+pub fn bech32(hrp: &str, data: &[u8]) -> String {
+    // Plain bech32 of bytes, as BIP 173 and Nostr's NIP-19 use it
+    bech32_with(hrp, &to_base32(data), BECH32_CONSTANT)
+}
+
+pub fn segwit_address(hrp: &str, version: u8, program: &[u8]) -> String {
+    // hrp "bc" on mainnet, "tb" on testnet
+    let mut values = alloc::vec![version];
+    values.extend(to_base32(program));
+    let constant = if version == 0 {
+        BECH32_CONSTANT
+    } else {
+        BECH32M_CONSTANT
+    };
+    bech32_with(hrp, &values, constant)
+}
+
+pub fn bech32_decode(text: &str) -> Option<(String, Vec<u8>)> {
+    // The hrp and bytes of plain bech32 in a single case; None for a bad
+    // checksum or padding
+    if text.bytes().any(|c| c.is_ascii_lowercase()) && text.bytes().any(|c| c.is_ascii_uppercase())
+    {
+        return None;
+    }
+    let text = text.to_ascii_lowercase();
+    let (hrp, data) = text.rsplit_once('1')?;
+    if hrp.is_empty() || data.len() < 6 {
+        return None;
+    }
+    let values = data
+        .bytes()
+        .map(|c| {
+            BECH32_ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                .map(|i| i as u8)
+        })
+        .collect::<Option<Vec<u8>>>()?;
+    let mut checked = hrp_expand(hrp);
+    checked.extend(&values);
+    if bech32_polymod(&checked) != BECH32_CONSTANT {
+        return None;
+    }
+    let bytes = from_base32(&values[..values.len() - 6])?;
+    Some((hrp.into(), bytes))
+}
+
+fn bech32_with(hrp: &str, values: &[u8], constant: u32) -> String {
+    // hrp || '1' || values || six checksum characters
+    let mut checked = hrp_expand(hrp);
+    checked.extend(values);
+    checked.extend([0; 6]);
+    let checksum = bech32_polymod(&checked) ^ constant;
+    let mut encoded = String::from(hrp);
+    encoded.push('1');
+    let checksum = (0..6).map(|i| ((checksum >> (5 * (5 - i))) & 31) as u8);
+    for value in values.iter().copied().chain(checksum) {
+        encoded.push(BECH32_ALPHABET[value as usize] as char);
+    }
+    encoded
+}
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let mut checksum = 1u32;
+    for value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ff_ffff) << 5) ^ u32::from(*value);
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let mut expanded: Vec<u8> = hrp.bytes().map(|c| c >> 5).collect();
+    expanded.push(0);
+    expanded.extend(hrp.bytes().map(|c| c & 31));
+    expanded
+}
+
+fn to_base32(data: &[u8]) -> Vec<u8> {
+    // 8-bit groups to 5-bit groups, the last one padded with zero bits
+    let mut values = Vec::new();
+    let (mut acc, mut bits) = (0u32, 0);
+    for byte in data {
+        acc = (acc << 8) | u32::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            values.push(((acc >> bits) & 31) as u8);
+        }
+    }
+    if bits > 0 {
+        values.push(((acc << (5 - bits)) & 31) as u8);
+    }
+    values
+}
+
+fn from_base32(values: &[u8]) -> Option<Vec<u8>> {
+    // 5-bit groups back to bytes; the padding must be under 5 zero bits
+    let mut data = Vec::new();
+    let (mut acc, mut bits) = (0u32, 0);
+    for value in values {
+        acc = (acc << 5) | u32::from(*value);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            data.push((acc >> bits) as u8);
+        }
+    }
+    (bits < 5 && acc & ((1 << bits) - 1) == 0).then_some(data)
+}
+
+pub fn base58_decode(text: &str) -> Option<Vec<u8>> {
+    // None for a character outside the alphabet
+    let digits = text
+        .bytes()
+        .map(|c| {
+            BASE58_ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                .map(|i| i as u8)
+        })
+        .collect::<Option<Vec<u8>>>()?;
+    let zeros = digits.iter().take_while(|d| **d == 0).count();
+    let mut data: Vec<u8> = alloc::vec![0; zeros];
+    if zeros < digits.len() {
+        data.extend(BigUint::from_radix_be(&digits[zeros..], 58)?.to_bytes_be());
+    }
+    Some(data)
+}
+
+fn eip55(address: &[u8]) -> String {
+    // A hex letter is upper case when its nibble of Keccak-256(lower case hex) is >= 8
+    let lower = hex::encode(address);
+    let hash = keccak256(lower.as_bytes());
+    let checksummed: String = lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0xf;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    alloc::format!("0x{}", checksummed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curves;
+
+    #[test]
+    fn test_addresses() {
+        // The well known addresses of private key 1, the generator
+        let ecdsa = curves::secp256k1();
+        let pub_key = ecdsa.a_gen.clone();
+        assert_eq!(
+            ecdsa.address(&pub_key, Network::Bitcoin).unwrap(),
+            "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH"
+        );
+        assert_eq!(
+            ecdsa.address(&pub_key, Network::BitcoinTestnet).unwrap(),
+            "mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r"
+        );
+        assert_eq!(
+            ecdsa.address(&pub_key, Network::Ethereum).unwrap(),
+            "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+        );
+        let p256 = curves::p256();
+        assert_eq!(p256.address(&p256.a_gen, Network::Bitcoin), None);
+    }
+
+    #[test]
+    fn test_segwit_addresses() {
+        // BIP 173 and BIP 350 examples for the key of the generator
+        let ecdsa = curves::secp256k1();
+        let compressed = ecdsa.encode_point(&ecdsa.a_gen);
+        assert_eq!(
+            segwit_address("bc", 0, &hash160(&compressed)),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+        assert_eq!(
+            segwit_address("bc", 1, &compressed[1..]),
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0"
+        );
+    }
+
+    #[test]
+    fn test_bech32_round_trip() {
+        for data in [&b""[..], b"\x00", b"bech32 round trip"] {
+            let encoded = bech32("test", data);
+            assert_eq!(
+                bech32_decode(&encoded),
+                Some(("test".into(), data.to_vec()))
+            );
+            let upper = encoded.to_ascii_uppercase();
+            assert_eq!(bech32_decode(&upper), Some(("test".into(), data.to_vec())));
+        }
+        let encoded = bech32("test", b"data");
+        let mut flipped = encoded.clone().into_bytes();
+        flipped[6] = if flipped[6] == b'q' { b'p' } else { b'q' };
+        assert_eq!(bech32_decode(core::str::from_utf8(&flipped).unwrap()), None);
+        assert_eq!(bech32_decode(&encoded.replacen('t', "T", 1)), None);
+        // A bech32m checksum is not plain bech32
+        assert_eq!(
+            bech32_decode("bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_base58_leading_zeros() {
+        assert_eq!(base58(&[0, 0, 1]), "112");
+        assert_eq!(base58(&[0]), "1");
+        assert_eq!(base58(&[]), "");
+        for data in [&[][..], &[0, 0, 1], &[0xff; 33]] {
+            assert_eq!(base58_decode(&base58(data)).unwrap(), data);
+        }
+        assert_eq!(base58_decode("10l"), None);
+    }
+}
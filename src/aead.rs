@@ -0,0 +1,206 @@
+// ChaCha20-Poly1305 AEAD as specified in RFC 8439, used by the hybrid
+// encryption schemes. The output of `seal` is ciphertext || 16-byte tag.
+
+use alloc::{vec, vec::Vec};
+use num_bigint::BigUint;
+
+pub const KEY_LEN: usize = 32;
+pub const NONCE_LEN: usize = 12;
+pub const TAG_LEN: usize = 16;
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+fn chacha20_block(key: &[u8; KEY_LEN], counter: u32, nonce: &[u8; NONCE_LEN]) -> [u8; 64] {
+    // "expand 32-byte k" || key || counter || nonce, 20 rounds
+    let mut state = [0u32; 16];
+    state[..4].copy_from_slice(&[0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]);
+    for i in 0..8 {
+        state[4 + i] = u32::from_le_bytes(key[4 * i..4 * i + 4].try_into().unwrap());
+    }
+    state[12] = counter;
+    for i in 0..3 {
+        state[13 + i] = u32::from_le_bytes(nonce[4 * i..4 * i + 4].try_into().unwrap());
+    }
+
+    let mut working = state;
+    for _ in 0..10 {
+        quarter_round(&mut working, 0, 4, 8, 12);
+        quarter_round(&mut working, 1, 5, 9, 13);
+        quarter_round(&mut working, 2, 6, 10, 14);
+        quarter_round(&mut working, 3, 7, 11, 15);
+        quarter_round(&mut working, 0, 5, 10, 15);
+        quarter_round(&mut working, 1, 6, 11, 12);
+        quarter_round(&mut working, 2, 7, 8, 13);
+        quarter_round(&mut working, 3, 4, 9, 14);
+    }
+
+    let mut block = [0u8; 64];
+    for i in 0..16 {
+        let word = working[i].wrapping_add(state[i]);
+        block[4 * i..4 * i + 4].copy_from_slice(&word.to_le_bytes());
+    }
+    block
+}
+
+pub fn chacha20(
+    key: &[u8; KEY_LEN],
+    counter: u32,
+    nonce: &[u8; NONCE_LEN],
+    data: &[u8],
+) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    for (i, chunk) in data.chunks(64).enumerate() {
+        let block = chacha20_block(key, counter.wrapping_add(i as u32), nonce);
+        out.extend(chunk.iter().zip(block.iter()).map(|(d, k)| d ^ k));
+    }
+    out
+}
+
+pub fn poly1305(key: &[u8; 32], message: &[u8]) -> [u8; TAG_LEN] {
+    // acc = (acc + block) * r mod 2^130 - 5, tag = acc + s mod 2^128
+    let mut r_bytes: [u8; 16] = key[..16].try_into().unwrap();
+    for i in [3, 7, 11, 15] {
+        r_bytes[i] &= 0x0f;
+    }
+    for i in [4, 8, 12] {
+        r_bytes[i] &= 0xfc;
+    }
+    let r = BigUint::from_bytes_le(&r_bytes);
+    let s = BigUint::from_bytes_le(&key[16..]);
+    let p: BigUint = (BigUint::from(1u32) << 130) - BigUint::from(5u32);
+
+    let mut acc = BigUint::from(0u32);
+    for chunk in message.chunks(16) {
+        let mut block = chunk.to_vec();
+        block.push(0x01);
+        acc = ((acc + BigUint::from_bytes_le(&block)) * &r) % &p;
+    }
+    let acc: BigUint = (acc + s) % (BigUint::from(1u32) << 128);
+
+    let mut tag = [0u8; TAG_LEN];
+    let bytes = acc.to_bytes_le();
+    tag[..bytes.len()].copy_from_slice(&bytes);
+    tag
+}
+
+fn pad16(data: &mut Vec<u8>) {
+    let rem = data.len() % 16;
+    if rem != 0 {
+        data.extend(vec![0u8; 16 - rem]);
+    }
+}
+
+fn compute_tag(
+    key: &[u8; KEY_LEN],
+    nonce: &[u8; NONCE_LEN],
+    aad: &[u8],
+    ciphertext: &[u8],
+) -> [u8; TAG_LEN] {
+    // The one-time Poly1305 key is the first half of block 0
+    let otk: [u8; 32] = chacha20_block(key, 0, nonce)[..32].try_into().unwrap();
+
+    let mut mac_data = aad.to_vec();
+    pad16(&mut mac_data);
+    mac_data.extend(ciphertext);
+    pad16(&mut mac_data);
+    mac_data.extend((aad.len() as u64).to_le_bytes());
+    mac_data.extend((ciphertext.len() as u64).to_le_bytes());
+    poly1305(&otk, &mac_data)
+}
+
+pub fn seal(key: &[u8; KEY_LEN], nonce: &[u8; NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+    let mut ciphertext = chacha20(key, 1, nonce, plaintext);
+    let tag = compute_tag(key, nonce, aad, &ciphertext);
+    ciphertext.extend(tag);
+    ciphertext
+}
+
+pub fn open(
+    key: &[u8; KEY_LEN],
+    nonce: &[u8; NONCE_LEN],
+    aad: &[u8],
+    sealed: &[u8],
+) -> Option<Vec<u8>> {
+    if sealed.len() < TAG_LEN {
+        return None;
+    }
+    let (ciphertext, tag) = sealed.split_at(sealed.len() - TAG_LEN);
+    let expected = compute_tag(key, nonce, aad, ciphertext);
+    if !ct_eq(&expected, tag) {
+        return None;
+    }
+    Some(chacha20(key, 1, nonce, ciphertext))
+}
+
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    // Compares without an early exit on the first differing byte
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter()
+        .zip(b.iter())
+        .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+        == 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_poly1305() {
+        // RFC 8439, section 2.5.2
+        let key: [u8; 32] =
+            hex::decode("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let tag = poly1305(&key, b"Cryptographic Forum Research Group");
+        assert_eq!(hex::encode(tag), "a8061dc1305136c6c22b8baf0c0127a9");
+    }
+
+    #[test]
+    fn test_chacha20poly1305_seal() {
+        // RFC 8439, section 2.8.2
+        let key: [u8; 32] = (0x80u8..=0x9f).collect::<Vec<u8>>().try_into().unwrap();
+        let nonce: [u8; 12] = hex::decode("070000004041424344454647")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let aad = hex::decode("50515253c0c1c2c3c4c5c6c7").unwrap();
+        let plaintext = b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.";
+
+        let sealed = seal(&key, &nonce, &aad, plaintext);
+        let (ciphertext, tag) = sealed.split_at(plaintext.len());
+
+        assert_eq!(
+            hex::encode(&ciphertext[..16]),
+            "d31a8d34648e60db7b86afbc53ef7ec2"
+        );
+        assert_eq!(hex::encode(tag), "1ae10b594f09e26a7e902ecbd0600691");
+        assert_eq!(
+            open(&key, &nonce, &aad, &sealed).unwrap(),
+            plaintext.to_vec()
+        );
+    }
+
+    #[test]
+    fn test_open_tampered() {
+        let key = [7u8; KEY_LEN];
+        let nonce = [1u8; NONCE_LEN];
+        let mut sealed = seal(&key, &nonce, b"header", b"attack at dawn");
+
+        assert_eq!(open(&key, &nonce, b"other header", &sealed), None);
+        sealed[0] ^= 1;
+        assert_eq!(open(&key, &nonce, b"header", &sealed), None);
+    }
+}
@@ -0,0 +1,269 @@
+// ssh-agent protocol (draft-miller-ssh-agent), behind the `ssh-agent`
+// feature: `AgentClient` talks to a running agent, for instance through
+// SSH_AUTH_SOCK, and `Agent` serves this crate's keys over the same protocol
+// so ssh and git can sign with them. Keys are ecdsa-sha2-nistp256, which signs
+// SHA-256(data) with standard ECDSA; messages other than listing keys and
+// signing are answered with SSH_AGENT_FAILURE.
+
+use std::io::{self, Read, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::Arc;
+use std::thread;
+
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::hash::sha256;
+use crate::signer::Signature;
+use crate::ssh::{put_mpint, put_string, put_u32, Reader, KEY_TYPE};
+use crate::ECDSA;
+
+const SSH_AGENT_FAILURE: u8 = 5;
+const SSH_AGENTC_REQUEST_IDENTITIES: u8 = 11;
+const SSH_AGENT_IDENTITIES_ANSWER: u8 = 12;
+const SSH_AGENTC_SIGN_REQUEST: u8 = 13;
+const SSH_AGENT_SIGN_RESPONSE: u8 = 14;
+
+// Refuses messages larger than this, as OpenSSH does
+const MAX_MESSAGE_LEN: usize = 256 * 1024;
+
+pub struct Identity {
+    pub key_blob: Vec<u8>,
+    pub comment: String,
+}
+
+pub struct Agent {
+    ecdsa: Arc<ECDSA>,
+    // Private key, its key blob and comment
+    keys: Vec<(BigUint, Vec<u8>, String)>,
+}
+
+pub struct AgentClient<S: Read + Write> {
+    stream: S,
+}
+
+impl ECDSA {
+    pub fn ssh_verify(&self, key_blob: &[u8], data: &[u8], signature_blob: &[u8]) -> bool {
+        // string "ecdsa-sha2-nistp256" || string (mpint r || mpint s)
+        let Some(pub_key) = self.ssh_parse_key_blob(key_blob) else {
+            return false;
+        };
+        let mut reader = Reader(signature_blob);
+        if reader.string() != Some(KEY_TYPE) {
+            return false;
+        }
+        let Some(mut inner) = reader.string().map(Reader) else {
+            return false;
+        };
+        match (inner.mpint(), inner.mpint()) {
+            (Some(r), Some(s)) if inner.0.is_empty() && reader.0.is_empty() => {
+                self.verify_standard(&sha256(data), &pub_key, &Signature::new(r, s))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Agent {
+    pub fn new(ecdsa: Arc<ECDSA>) -> Option<Agent> {
+        // Only P-256 has an ssh key type here
+        if !ecdsa.is_p256() {
+            return None;
+        }
+        Some(Agent {
+            ecdsa,
+            keys: Vec::new(),
+        })
+    }
+
+    pub fn add_key(&mut self, priv_key: BigUint, comment: &str) {
+        assert!(
+            priv_key < self.ecdsa.q_order && priv_key != BigUint::from(0u32),
+            "Private Key must be in [1, q - 1]"
+        );
+        let key_blob = self
+            .ecdsa
+            .ssh_key_blob(&self.ecdsa.generate_pub_key(&priv_key));
+        self.keys.push((priv_key, key_blob, comment.to_string()));
+    }
+
+    pub fn handle(&self, request: &[u8]) -> Vec<u8> {
+        // One message payload in, one out, without the length prefix
+        self.respond(request)
+            .unwrap_or_else(|| vec![SSH_AGENT_FAILURE])
+    }
+
+    pub fn serve_stream(&self, mut stream: impl Read + Write) -> io::Result<()> {
+        // Answers requests until the peer hangs up
+        loop {
+            let request = match read_message(&mut stream) {
+                Ok(request) => request,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            write_message(&mut stream, &self.handle(&request))?;
+        }
+    }
+
+    pub fn serve(self: Arc<Self>, listener: UnixListener) -> io::Result<()> {
+        // One thread per connection
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let agent = self.clone();
+            thread::spawn(move || agent.serve_stream(stream));
+        }
+        Ok(())
+    }
+
+    fn respond(&self, request: &[u8]) -> Option<Vec<u8>> {
+        let (&kind, body) = request.split_first()?;
+        let mut response = Vec::new();
+        match kind {
+            SSH_AGENTC_REQUEST_IDENTITIES => {
+                response.push(SSH_AGENT_IDENTITIES_ANSWER);
+                put_u32(&mut response, self.keys.len() as u32);
+                for (_, key_blob, comment) in &self.keys {
+                    put_string(&mut response, key_blob);
+                    put_string(&mut response, comment.as_bytes());
+                }
+            }
+            SSH_AGENTC_SIGN_REQUEST => {
+                let mut reader = Reader(body);
+                let (key_blob, data, _flags) = (reader.string()?, reader.string()?, reader.u32()?);
+                let (priv_key, _, _) = self.keys.iter().find(|(_, blob, _)| blob == key_blob)?;
+                let signature =
+                    self.ecdsa
+                        .sign_standard(&mut crate::rng(), &sha256(data), priv_key);
+
+                let mut inner = Vec::new();
+                put_mpint(&mut inner, signature.r());
+                put_mpint(&mut inner, signature.s());
+                let mut blob = Vec::new();
+                put_string(&mut blob, KEY_TYPE);
+                put_string(&mut blob, &inner);
+                response.push(SSH_AGENT_SIGN_RESPONSE);
+                put_string(&mut response, &blob);
+            }
+            _ => return None,
+        }
+        Some(response)
+    }
+}
+
+impl AgentClient<UnixStream> {
+    pub fn connect_env() -> io::Result<AgentClient<UnixStream>> {
+        let path = std::env::var("SSH_AUTH_SOCK")
+            .map_err(|_| io::Error::new(io::ErrorKind::NotFound, "SSH_AUTH_SOCK is not set"))?;
+        Ok(AgentClient::new(UnixStream::connect(path)?))
+    }
+}
+
+impl<S: Read + Write> AgentClient<S> {
+    pub fn new(stream: S) -> AgentClient<S> {
+        AgentClient { stream }
+    }
+
+    pub fn list_identities(&mut self) -> io::Result<Vec<Identity>> {
+        let response = self.request(&[SSH_AGENTC_REQUEST_IDENTITIES])?;
+        let mut reader = Reader(&response);
+        let parse = |reader: &mut Reader| {
+            if reader.byte()? != SSH_AGENT_IDENTITIES_ANSWER {
+                return None;
+            }
+            (0..reader.u32()?)
+                .map(|_| {
+                    Some(Identity {
+                        key_blob: reader.string()?.to_vec(),
+                        comment: String::from_utf8_lossy(reader.string()?).into_owned(),
+                    })
+                })
+                .collect()
+        };
+        parse(&mut reader).ok_or_else(agent_failure)
+    }
+
+    pub fn sign(&mut self, key_blob: &[u8], data: &[u8]) -> io::Result<Vec<u8>> {
+        // Returns the ssh signature blob, see `ssh_verify`
+        let mut request = vec![SSH_AGENTC_SIGN_REQUEST];
+        put_string(&mut request, key_blob);
+        put_string(&mut request, data);
+        put_u32(&mut request, 0);
+        let response = self.request(&request)?;
+
+        let mut reader = Reader(&response);
+        match reader.byte() {
+            Some(SSH_AGENT_SIGN_RESPONSE) => {
+                Ok(reader.string().ok_or_else(agent_failure)?.to_vec())
+            }
+            _ => Err(agent_failure()),
+        }
+    }
+
+    fn request(&mut self, request: &[u8]) -> io::Result<Vec<u8>> {
+        write_message(&mut self.stream, request)?;
+        read_message(&mut self.stream)
+    }
+}
+
+fn read_message(stream: &mut impl Read) -> io::Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_MESSAGE_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Agent message is too long",
+        ));
+    }
+    let mut message = vec![0u8; len];
+    stream.read_exact(&mut message)?;
+    Ok(message)
+}
+
+fn write_message(stream: &mut impl Write, message: &[u8]) -> io::Result<()> {
+    let mut framed = Vec::with_capacity(4 + message.len());
+    put_string(&mut framed, message);
+    stream.write_all(&framed)?;
+    stream.flush()
+}
+
+fn agent_failure() -> io::Error {
+    io::Error::other("ssh-agent refused the request")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ec_generic::EllipticCurve;
+
+    use crate::curves::p256;
+
+    #[test]
+    fn test_agent_round_trip() {
+        let ecdsa = Arc::new(p256());
+        let mut agent = Agent::new(ecdsa.clone()).unwrap();
+        agent.add_key(BigUint::from(0xc0ffeeu32), "alice@example.com");
+
+        let (client_end, agent_end) = UnixStream::pair().unwrap();
+        let server = thread::spawn(move || agent.serve_stream(agent_end));
+        let mut client = AgentClient::new(client_end);
+
+        let identities = client.list_identities().unwrap();
+        assert_eq!(identities.len(), 1);
+        assert_eq!(identities[0].comment, "alice@example.com");
+        let key_blob = &identities[0].key_blob;
+        assert!(ecdsa.ssh_parse_key_blob(key_blob).is_some());
+
+        let signature = client.sign(key_blob, b"git commit").unwrap();
+        assert!(ecdsa.ssh_verify(key_blob, b"git commit", &signature));
+        assert!(!ecdsa.ssh_verify(key_blob, b"git commit --amend", &signature));
+
+        // Unknown keys are refused without closing the connection
+        let other = ecdsa.ssh_key_blob(&ecdsa.generate_pub_key(&BigUint::from(3u32)));
+        assert!(client.sign(&other, b"git commit").is_err());
+        assert_eq!(client.list_identities().unwrap().len(), 1);
+
+        drop(client);
+        server.join().unwrap().unwrap();
+    }
+}
@@ -0,0 +1,263 @@
+// Group structure of curves over small prime fields, for hand-built teaching
+// and test curves such as y² = x³ + 2x + 2 over F_17. Points are counted
+// either by trying every x, which takes p steps, or by baby-step giant-step:
+// the group order N lies in the Hasse interval [p + 1 - 2√p, p + 1 + 2√p], so
+// the multiples m with mP = O for a few points P, found in about p^(1/4)
+// steps each, pin N down. Orders of points come from the factored group order.
+// All of it assumes p fits in a u64 and is only practical for small p.
+
+use alloc::collections::BTreeMap;
+use alloc::{vec, vec::Vec};
+use ec_generic::{EllipticCurve, Point};
+use num_bigint::BigUint;
+
+use crate::arithmetic::sqrt_mod;
+
+const BSGS_POINTS: usize = 32;
+
+pub fn count_points_naive(curve: &EllipticCurve) -> u64 {
+    // 1 for O, plus 2 points for every x where x³ + ax + b is a non-zero
+    // square and 1 where it is zero
+    let p = field_size(curve);
+    let exponent = (p - 1) / 2;
+    let mut count = 1;
+    for x in 0..p {
+        let rhs = rhs(curve, x);
+        if rhs == 0 {
+            count += 1;
+        } else if pow_mod(rhs, exponent, p) == 1 {
+            count += 2;
+        }
+    }
+    count
+}
+
+pub fn count_points_bsgs(curve: &EllipticCurve) -> Option<u64> {
+    // Each point's order divides N; once the lcm L of the orders seen has a
+    // single multiple in the Hasse interval, that multiple is N. None if that
+    // has not happened after `BSGS_POINTS` points, which can be the case when
+    // the group is far from cyclic
+    let p = field_size(curve);
+    let root = p.isqrt() + 1;
+    let (low, high) = ((p + 1).saturating_sub(2 * root), p + 1 + 2 * root);
+    let mut lcm = 1u64;
+    for point in points(curve).take(BSGS_POINTS) {
+        let multiple = multiple_in_range(curve, &point, low, high)?;
+        let order = point_order(curve, &point, multiple);
+        lcm = lcm / gcd(lcm, order) * order;
+        let first = low.div_ceil(lcm) * lcm;
+        if first + lcm > high {
+            return Some(first);
+        }
+    }
+    None
+}
+
+pub fn point_order(curve: &EllipticCurve, point: &Point, group_order: u64) -> u64 {
+    // Strips prime factors f from N while (N / f) P = O still holds;
+    // `group_order` can be any multiple of the order
+    let mut order = group_order;
+    for factor in prime_factors(group_order) {
+        while order.is_multiple_of(factor) && mul(curve, point, order / factor) == Point::Identity {
+            order /= factor;
+        }
+    }
+    order
+}
+
+pub fn find_generator(curve: &EllipticCurve, group_order: u64) -> Option<Point> {
+    // A point of order N, which exists only when the group is cyclic
+    points(curve).find(|point| point_order(curve, point, group_order) == group_order)
+}
+
+pub fn find_point_of_order(curve: &EllipticCurve, group_order: u64, q: u64) -> Option<Point> {
+    // For a prime q dividing N = q^e h: Q = hP for the first P where that is
+    // not O, which has order q^i, then multiplied by q until the next step
+    // would give O
+    if q < 2 || !group_order.is_multiple_of(q) || prime_factors(q) != [q] {
+        return None;
+    }
+    let mut cofactor = group_order;
+    while cofactor.is_multiple_of(q) {
+        cofactor /= q;
+    }
+    let mut point = points(curve)
+        .map(|point| mul(curve, &point, cofactor))
+        .find(|point| *point != Point::Identity)?;
+    loop {
+        let next = mul(curve, &point, q);
+        if next == Point::Identity {
+            return Some(point);
+        }
+        point = next;
+    }
+}
+
+pub fn points(curve: &EllipticCurve) -> impl Iterator<Item = Point> + '_ {
+    // One point for every x on the curve, the one with the smaller y
+    (0..field_size(curve)).filter_map(move |x| {
+        let y = sqrt_mod(&BigUint::from(rhs(curve, x)), &curve.p)?;
+        let y = y.clone().min(&curve.p - &y) % &curve.p;
+        Some(Point::Coor(BigUint::from(x), y))
+    })
+}
+
+fn multiple_in_range(curve: &EllipticCurve, point: &Point, low: u64, high: u64) -> Option<u64> {
+    // The smallest m in [low, high] with mP = O: baby steps jP for j < w, giant
+    // steps (low + iw) P, and mP = O when jP = -(low + iw) P
+    let width = (high - low + 1).isqrt() + 1;
+    let mut baby_steps: BTreeMap<Vec<u8>, Vec<u64>> = BTreeMap::new();
+    let mut step = Point::Identity;
+    for j in 0..width {
+        baby_steps.entry(point_key(&step)).or_default().push(j);
+        step = curve.add(&step, point).unwrap();
+    }
+
+    let giant = mul(curve, point, width);
+    let mut current = mul(curve, point, low);
+    for i in 0..=width {
+        let candidates = baby_steps.get(&point_key(&negate(curve, &current)));
+        let base = low + i * width;
+        if let Some(m) = candidates
+            .into_iter()
+            .flatten()
+            .map(|j| base + j)
+            .find(|m| (low..=high).contains(m))
+        {
+            return Some(m);
+        }
+        current = curve.add(&current, &giant).unwrap();
+    }
+    None
+}
+
+fn mul(curve: &EllipticCurve, point: &Point, k: u64) -> Point {
+    if k == 0 || *point == Point::Identity {
+        return Point::Identity;
+    }
+    curve.scalar_mul(point, &BigUint::from(k)).unwrap()
+}
+
+fn negate(curve: &EllipticCurve, point: &Point) -> Point {
+    match point {
+        Point::Coor(x, y) => Point::Coor(x.clone(), (&curve.p - y) % &curve.p),
+        Point::Identity => Point::Identity,
+    }
+}
+
+fn point_key(point: &Point) -> Vec<u8> {
+    match point {
+        Point::Coor(x, y) => [x.to_bytes_be(), vec![0xff], y.to_bytes_be()].concat(),
+        Point::Identity => Vec::new(),
+    }
+}
+
+fn field_size(curve: &EllipticCurve) -> u64 {
+    u64::try_from(&curve.p).expect("The field is too large to analyse")
+}
+
+fn rhs(curve: &EllipticCurve, x: u64) -> u64 {
+    // x³ + ax + b mod p
+    let p = u128::from(field_size(curve));
+    let (a, b) = (
+        u64::try_from(&curve.a).unwrap(),
+        u64::try_from(&curve.b).unwrap(),
+    );
+    let x = u128::from(x);
+    ((x * x % p * x + u128::from(a) * x + u128::from(b)) % p) as u64
+}
+
+fn pow_mod(base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let modulus = u128::from(modulus);
+    let (mut base, mut result) = (u128::from(base) % modulus, 1u128);
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exponent >>= 1;
+    }
+    result as u64
+}
+
+pub fn prime_factors(mut n: u64) -> Vec<u64> {
+    // Distinct prime factors by trial division
+    let mut factors = Vec::new();
+    let mut f = 2;
+    while f * f <= n {
+        if n.is_multiple_of(f) {
+            factors.push(f);
+            while n.is_multiple_of(f) {
+                n /= f;
+            }
+        }
+        f += 1;
+    }
+    if n > 1 {
+        factors.push(n);
+    }
+    factors
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn curve(a: u32, b: u32, p: u32) -> EllipticCurve {
+        EllipticCurve {
+            a: BigUint::from(a),
+            b: BigUint::from(b),
+            p: BigUint::from(p),
+        }
+    }
+
+    #[test]
+    fn test_f17_curve() {
+        // The curve of the tests: 19 points, a prime, so every point but O
+        // generates the group, (5, 1) included
+        let curve = curve(2, 2, 17);
+        assert_eq!(count_points_naive(&curve), 19);
+        assert_eq!(count_points_bsgs(&curve), Some(19));
+        assert_eq!(points(&curve).count(), 9);
+        let generator = Point::Coor(BigUint::from(5u32), BigUint::from(1u32));
+        assert_eq!(point_order(&curve, &generator, 19), 19);
+        assert_eq!(point_order(&curve, &Point::Identity, 19), 1);
+        assert!(find_generator(&curve, 19).is_some());
+    }
+
+    #[test]
+    fn test_count_points_bsgs() {
+        for (a, b, p) in [(0, 7, 10007), (3, 5, 10009), (1, 1, 65521), (2, 3, 97)] {
+            let curve = curve(a, b, p);
+            let n = count_points_naive(&curve);
+            assert_eq!(count_points_bsgs(&curve), Some(n));
+            for point in points(&curve).take(10) {
+                let order = point_order(&curve, &point, n);
+                assert_eq!(n % order, 0);
+                assert_eq!(mul(&curve, &point, order), Point::Identity);
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_point_of_order() {
+        // y² = x³ + x + 1 over F_101 has 105 = 3 · 5 · 7 points
+        let curve = curve(1, 1, 101);
+        let n = count_points_naive(&curve);
+        assert_eq!(n, 105);
+        let point = find_point_of_order(&curve, n, 7).unwrap();
+        assert_eq!(point_order(&curve, &point, n), 7);
+        assert_eq!(find_point_of_order(&curve, n, 11), None);
+        assert_eq!(find_point_of_order(&curve, n, 15), None);
+        let generator = find_generator(&curve, n).unwrap();
+        assert_eq!(point_order(&curve, &generator, n), n);
+    }
+}
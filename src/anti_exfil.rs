@@ -0,0 +1,144 @@
+// Anti-exfiltration ECDSA (sign-to-contract), as used between a wallet host
+// and a hardware signer. The host commits to fresh randomness rho, the signer
+// commits to its nonce point R1 = kA, the host reveals rho and the signer must
+// sign with k' = k + H(R1, rho). The host checks that r = x(R1 + H(R1, rho)A),
+// so a malicious signer cannot choose the final nonce to leak its key, and a
+// malicious host learns nothing it could not get from the signature itself.
+
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::aead::ct_eq;
+use crate::hash::sha256;
+use crate::secret::SecretScalar;
+use crate::ECDSA;
+
+const DST_COMMIT: &[u8] = b"RS_ECC-anti-exfil-commit";
+const DST_TWEAK: &[u8] = b"RS_ECC-anti-exfil-tweak";
+
+impl ECDSA {
+    pub fn anti_exfil_host_commitment(&self, host_data: &[u8]) -> Vec<u8> {
+        // c = H(rho), sent to the signer before it picks its nonce
+        sha256(&[DST_COMMIT, host_data].concat())
+    }
+
+    pub fn anti_exfil_signer_commitment(&self, k_random: &BigUint) -> Point {
+        // R1 = kA, sent to the host before it reveals rho
+        assert!(k_random < &self.q_order, "'K' is Bigger than the Ec group");
+        self.mul_secret(&self.a_gen, &SecretScalar::new(k_random.clone()))
+    }
+
+    pub fn anti_exfil_sign(
+        &self,
+        hash: &BigUint,
+        priv_key: &BigUint,
+        k_random: &BigUint,
+        host_data: &[u8],
+        host_commitment: &[u8],
+    ) -> Option<(BigUint, BigUint)> {
+        // Refuses to sign if rho does not open the host commitment
+        if !ct_eq(&self.anti_exfil_host_commitment(host_data), host_commitment) {
+            return None;
+        }
+        let r1 = self.anti_exfil_signer_commitment(k_random);
+        let k = (k_random + self.anti_exfil_tweak(&r1, host_data)) % &self.q_order;
+        if k == BigUint::from(0u32) {
+            return None;
+        }
+        Some(self.sign(hash, priv_key, &k))
+    }
+
+    pub fn anti_exfil_host_verify(
+        &self,
+        hash: &BigUint,
+        pub_key: &Point,
+        signature: &(BigUint, BigUint),
+        signer_commitment: &Point,
+        host_data: &[u8],
+    ) -> bool {
+        // r = x(R1 + H(R1, rho)A) and the signature verifies
+        let (r, s) = signature;
+        let zero = BigUint::from(0u32);
+        if !self.elliptic_curve.is_on_curve(signer_commitment)
+            || r % &self.q_order == zero
+            || *s == zero
+            || s >= &self.q_order
+        {
+            return false;
+        }
+
+        let tweak = self.anti_exfil_tweak(signer_commitment, host_data);
+        let expected = self.add_points(signer_commitment, &self.mul_vartime(&self.a_gen, &tweak));
+        match expected {
+            Point::Coor(x, _) if &x == r => self.verification(hash, pub_key, signature),
+            _ => false,
+        }
+    }
+
+    fn anti_exfil_tweak(&self, r1: &Point, host_data: &[u8]) -> BigUint {
+        let data = [self.encode_point(r1).as_slice(), host_data].concat();
+        self.hash_to_scalar(DST_TWEAK, &data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_anti_exfil_sign_verify() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let hash = ecdsa.generate_hash_less_than("Bob -> 1 BTC -> Alice", &ecdsa.q_order);
+
+        let host_data = b"host randomness";
+        let host_commitment = ecdsa.anti_exfil_host_commitment(host_data);
+        let k_random = BigUint::from(18u32);
+        let signer_commitment = ecdsa.anti_exfil_signer_commitment(&k_random);
+
+        let signature = ecdsa
+            .anti_exfil_sign(&hash, &priv_key, &k_random, host_data, &host_commitment)
+            .unwrap();
+        assert!(ecdsa.anti_exfil_host_verify(
+            &hash,
+            &pub_key,
+            &signature,
+            &signer_commitment,
+            host_data
+        ));
+    }
+
+    #[test]
+    fn test_anti_exfil_rejects_untweaked_nonce() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let hash = ecdsa.generate_hash_less_than("Bob -> 1 BTC -> Alice", &ecdsa.q_order);
+
+        let host_data = b"host randomness";
+        let k_random = BigUint::from(18u32);
+        let signer_commitment = ecdsa.anti_exfil_signer_commitment(&k_random);
+
+        // A signer that ignores rho and signs with its own nonce is caught
+        let signature = ecdsa.sign(&hash, &priv_key, &k_random);
+        assert!(!ecdsa.anti_exfil_host_verify(
+            &hash,
+            &pub_key,
+            &signature,
+            &signer_commitment,
+            host_data
+        ));
+        assert!(ecdsa
+            .anti_exfil_sign(
+                &hash,
+                &priv_key,
+                &k_random,
+                b"other data",
+                &ecdsa.anti_exfil_host_commitment(host_data)
+            )
+            .is_none());
+    }
+}
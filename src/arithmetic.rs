@@ -0,0 +1,316 @@
+use alloc::{vec, vec::Vec};
+use ec_generic::{FiniteField, Point};
+use num_bigint::{BigInt, BigUint, RandBigInt};
+
+use crate::ECDSA;
+
+impl ECDSA {
+    pub fn add_points(&self, a: &Point, b: &Point) -> Point {
+        self.elliptic_curve.add(a, b).unwrap()
+    }
+
+    pub fn negate_point(&self, point: &Point) -> Point {
+        // -P(x, y) = P(x, -y mod p)
+        match point {
+            Point::Coor(x, y) => Point::Coor(
+                x.clone(),
+                FiniteField::inv_add(y, &self.elliptic_curve.p).unwrap(),
+            ),
+            Point::Identity => Point::Identity,
+        }
+    }
+
+    pub fn sub_points(&self, a: &Point, b: &Point) -> Point {
+        self.add_points(a, &self.negate_point(b))
+    }
+
+    pub fn y_for_x(&self, x: &BigUint) -> Option<BigUint> {
+        // y² = x³ + ax + b mod p
+        let p = &self.elliptic_curve.p;
+        let x3 = x.modpow(&BigUint::from(3u32), p);
+        let ax = (&self.elliptic_curve.a * x) % p;
+        let rhs = (x3 + ax + &self.elliptic_curve.b) % p;
+        sqrt_mod(&rhs, p)
+    }
+}
+
+pub fn sqrt_mod(a: &BigUint, p: &BigUint) -> Option<BigUint> {
+    // Tonelli-Shanks, with the p = 3 mod 4 shortcut a^((p + 1) / 4)
+    let zero = BigUint::from(0u32);
+    let one = BigUint::from(1u32);
+    let a = a % p;
+    if a == zero || *p == BigUint::from(2u32) {
+        return Some(a);
+    }
+
+    let p_minus_one = p - &one;
+    // Euler's criterion: a is a square iff a^((p - 1) / 2) = 1
+    if a.modpow(&(&p_minus_one >> 1), p) != one {
+        return None;
+    }
+    if p % 4u32 == BigUint::from(3u32) {
+        return Some(a.modpow(&((p + &one) >> 2), p));
+    }
+
+    // p - 1 = q * 2^s with q odd
+    let mut q = p_minus_one.clone();
+    let mut s = 0u64;
+    while !q.bit(0) {
+        q >>= 1;
+        s += 1;
+    }
+
+    let mut z = BigUint::from(2u32);
+    while z.modpow(&(&p_minus_one >> 1), p) != p_minus_one {
+        z += 1u32;
+    }
+
+    let mut m = s;
+    let mut c = z.modpow(&q, p);
+    let mut t = a.modpow(&q, p);
+    let mut r = a.modpow(&((&q + &one) >> 1), p);
+
+    while t != one {
+        let mut i = 0u64;
+        let mut t_pow = t.clone();
+        while t_pow != one {
+            t_pow = (&t_pow * &t_pow) % p;
+            i += 1;
+        }
+        let b = c.modpow(&(BigUint::from(1u32) << (m - i - 1)), p);
+        m = i;
+        c = (&b * &b) % p;
+        t = (t * &c) % p;
+        r = (r * &b) % p;
+    }
+    Some(r)
+}
+
+pub fn gcd(a: &BigUint, b: &BigUint) -> BigUint {
+    let (mut a, mut b) = (a.clone(), b.clone());
+    while b != BigUint::from(0u32) {
+        let r = &a % &b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+pub fn mod_inverse(a: &BigUint, m: &BigUint) -> Option<BigUint> {
+    // Extended Euclid, for moduli that are not prime
+    let (mut old_r, mut r) = (BigInt::from(a % m), BigInt::from(m.clone()));
+    let (mut old_s, mut s) = (BigInt::from(1u32), BigInt::from(0u32));
+    while r != BigInt::from(0u32) {
+        let quotient = &old_r / &r;
+        (old_r, r) = (r.clone(), old_r - &quotient * &r);
+        (old_s, s) = (s.clone(), old_s - &quotient * &s);
+    }
+    if old_r != BigInt::from(1u32) {
+        return None;
+    }
+    let m = BigInt::from(m.clone());
+    (((old_s % &m) + &m) % &m).to_biguint()
+}
+
+pub fn batch_inverse(values: &[BigUint], m: &BigUint) -> Option<Vec<BigUint>> {
+    // Montgomery's trick: one inversion of the product of all the values,
+    // then a⁻¹_i = (a_0 ... a_{i-1}) * (a_0 ... a_i)⁻¹ walking backwards,
+    // 3(n - 1) multiplications in all
+    let mut prefix = Vec::with_capacity(values.len());
+    let mut product = BigUint::from(1u32) % m;
+    for value in values {
+        prefix.push(product.clone());
+        product = (product * value) % m;
+    }
+    let mut inverse = mod_inverse(&product, m)?;
+    let mut inverses = vec![BigUint::from(0u32); values.len()];
+    for (i, value) in values.iter().enumerate().rev() {
+        inverses[i] = (&inverse * &prefix[i]) % m;
+        inverse = (inverse * value) % m;
+    }
+    Some(inverses)
+}
+
+pub fn is_probable_prime(n: &BigUint) -> bool {
+    // Miller-Rabin with 40 random bases
+    let one = BigUint::from(1u32);
+    let two = BigUint::from(2u32);
+    if *n < two {
+        return false;
+    }
+    for small in [2u32, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        if *n == BigUint::from(small) {
+            return true;
+        }
+        if n % small == BigUint::from(0u32) {
+            return false;
+        }
+    }
+
+    // n - 1 = d * 2^s with d odd
+    let n_minus_one = n - &one;
+    let mut d = n_minus_one.clone();
+    let mut s = 0u64;
+    while !d.bit(0) {
+        d >>= 1;
+        s += 1;
+    }
+
+    let mut rng = crate::rng();
+    'witness: for _ in 0..40 {
+        let a = rng.gen_biguint_range(&two, &n_minus_one);
+        let mut x = a.modpow(&d, n);
+        if x == one || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+pub fn generate_prime(bits: u64) -> BigUint {
+    // Random odd candidates with the top bit set until one passes Miller-Rabin
+    assert!(bits >= 8, "Primes must have at least 8 bits");
+    let mut rng = crate::rng();
+    loop {
+        let mut candidate = rng.gen_biguint(bits);
+        candidate.set_bit(bits - 1, true);
+        candidate.set_bit(0, true);
+        if is_probable_prime(&candidate) {
+            return candidate;
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Barrett {
+    modulus: BigUint,
+    mu: BigUint,
+    k: u64,
+}
+
+impl Barrett {
+    pub fn new(modulus: &BigUint) -> Barrett {
+        // mu = floor(4^k / m) with k the bit length of m
+        let k = modulus.bits();
+        assert!(k >= 2, "Barrett reduction needs a modulus of at least 2");
+        Barrett {
+            modulus: modulus.clone(),
+            mu: (BigUint::from(1u32) << (2 * k)) / modulus,
+            k,
+        }
+    }
+
+    pub fn reduce(&self, x: &BigUint) -> BigUint {
+        // x mod m for x < m², with shifts and multiplications only:
+        // q = ((x >> (k - 1)) * mu) >> (k + 1), r = x - q * m, r < 3m
+        assert!(
+            x.bits() <= 2 * self.k,
+            "Input is too large for Barrett reduction"
+        );
+        let q = ((x >> (self.k - 1)) * &self.mu) >> (self.k + 1);
+        let mut r = x - q * &self.modulus;
+        while r >= self.modulus {
+            r -= &self.modulus;
+        }
+        r
+    }
+
+    pub fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        self.reduce(&(a * b))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_sqrt_mod() {
+        // 17 = 1 mod 4 goes through Tonelli-Shanks, 19 = 3 mod 4 through the shortcut
+        for p in [17u32, 19u32] {
+            let p = BigUint::from(p);
+            for a in 0u32..17 {
+                let a = BigUint::from(a);
+                if let Some(r) = sqrt_mod(&a, &p) {
+                    assert_eq!((&r * &r) % &p, a, "sqrt should square back");
+                }
+            }
+        }
+        assert_eq!(sqrt_mod(&BigUint::from(3u32), &BigUint::from(17u32)), None);
+    }
+
+    #[test]
+    fn test_barrett() {
+        let m = BigUint::parse_bytes(
+            b"ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551",
+            16,
+        )
+        .unwrap();
+        let barrett = Barrett::new(&m);
+        let mut rng = crate::rng();
+        for _ in 0..100 {
+            let a = rng.gen_biguint_below(&m);
+            let b = rng.gen_biguint_below(&m);
+            assert_eq!(barrett.mul(&a, &b), (&a * &b) % &m);
+        }
+        let top = &m - BigUint::from(1u32);
+        assert_eq!(barrett.mul(&top, &top), BigUint::from(1u32));
+    }
+
+    #[test]
+    fn test_batch_inverse() {
+        let m = BigUint::from(19u32);
+        let values: Vec<BigUint> = (1u32..19).map(BigUint::from).collect();
+        let inverses = batch_inverse(&values, &m).unwrap();
+        for (value, inverse) in values.iter().zip(&inverses) {
+            assert_eq!(Some(inverse.clone()), mod_inverse(value, &m));
+        }
+        assert_eq!(batch_inverse(&[], &m), Some(vec![]));
+        // A single value without an inverse spoils the whole batch
+        let values = [BigUint::from(3u32), BigUint::from(4u32)];
+        assert_eq!(batch_inverse(&values, &BigUint::from(12u32)), None);
+    }
+
+    #[test]
+    fn test_mod_inverse_and_primes() {
+        let m = BigUint::from(20u32);
+        assert_eq!(
+            mod_inverse(&BigUint::from(3u32), &m),
+            Some(BigUint::from(7u32))
+        );
+        assert_eq!(mod_inverse(&BigUint::from(4u32), &m), None);
+        assert_eq!(
+            gcd(&BigUint::from(12u32), &BigUint::from(18u32)),
+            BigUint::from(6u32)
+        );
+
+        assert!(is_probable_prime(&BigUint::from(7919u32)));
+        assert!(!is_probable_prime(&BigUint::from(7917u32)));
+        // Carmichael number 252601 = 41 * 61 * 101 fools the Fermat test
+        assert!(!is_probable_prime(&BigUint::from(252601u32)));
+        let prime = generate_prime(64);
+        assert_eq!(prime.bits(), 64);
+        assert!(is_probable_prime(&prime));
+    }
+
+    #[test]
+    fn test_point_negation() {
+        let ecdsa = toy_curve();
+
+        let p = ecdsa.mul_vartime(&ecdsa.a_gen, &BigUint::from(3u32));
+        assert_eq!(ecdsa.sub_points(&p, &p), Point::Identity);
+        assert_eq!(ecdsa.mul_vartime(&p, &BigUint::from(0u32)), Point::Identity);
+        assert_eq!(
+            ecdsa.add_points(&p, &ecdsa.negate_point(&ecdsa.a_gen)),
+            ecdsa.mul_vartime(&ecdsa.a_gen, &BigUint::from(2u32))
+        );
+    }
+}
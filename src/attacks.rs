@@ -0,0 +1,7 @@
+// Attacks on misused ECDSA, for teaching: each one recovers a private key
+// from signatures that leak something they should not, and shows why the
+// signing code insists on uniformly random (or RFC 6979 derived) nonces.
+
+pub mod biased_nonce;
+#[cfg(feature = "danger-demos")]
+pub mod invalid_curve;
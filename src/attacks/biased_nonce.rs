@@ -0,0 +1,252 @@
+// Private key recovery from nonces with known or zero bits, as a hidden number
+// problem (Boneh-Venkatesan, Howgrave-Graham-Smart). Each signature gives
+// s k = h + r d mod q, so k = t d + u mod q with t = r s⁻¹ and u = h s⁻¹. When
+// every nonce is known up to an error 0 ≤ e < X = 2^unknown_bits, the vector
+// (e_1 - X/2, ..., e_m - X/2, d X / q, X) is unusually short in the lattice
+// spanned by the rows
+//
+//     q e_i                 for i = 1..m
+//     (t_1, ..., t_m, X / q, 0)
+//     (u_1 - known_1 - X/2, ..., u_m - known_m - X/2, 0, X)
+//
+// and LLL finds it once m (log q - unknown_bits) is comfortably above log q.
+// The rows are scaled by q to stay in integers, and the reduction is the
+// integral LLL of Cohen's "A Course in Computational Algebraic Number Theory"
+// (algorithm 2.6.7), exact but only fast for small dimensions.
+
+use alloc::{vec, vec::Vec};
+use ec_generic::Point;
+use num_bigint::{BigInt, BigUint, Sign};
+
+use crate::arithmetic::mod_inverse;
+use crate::ECDSA;
+
+// LLL parameter δ = 99/100
+const DELTA: (u32, u32) = (99, 100);
+
+pub struct NonceLeak {
+    pub hash: BigUint,
+    pub signature: (BigUint, BigUint),
+    // The nonce was known + e for some 0 ≤ e < 2^unknown_bits; zero for nonces
+    // that are merely short, the leaked top bits shifted into place otherwise
+    pub known: BigUint,
+}
+
+pub fn recover_key(
+    ecdsa: &ECDSA,
+    pub_key: &Point,
+    leaks: &[NonceLeak],
+    unknown_bits: u64,
+) -> Option<BigUint> {
+    // The key, checked against `pub_key`, or None if the lattice did not give it
+    let q = &ecdsa.q_order;
+    let m = leaks.len();
+    if m == 0 || unknown_bits >= q.bits() {
+        return None;
+    }
+    let x = BigUint::from(1u32) << unknown_bits;
+    let half_x = &x >> 1;
+
+    let big = |value: &BigUint| BigInt::from(value.clone());
+    let mut basis = vec![vec![BigInt::from(0); m + 2]; m + 2];
+    for (i, leak) in leaks.iter().enumerate() {
+        let (r, s) = &leak.signature;
+        let s_inv = mod_inverse(s, q)?;
+        let t = r * &s_inv % q;
+        let u = (&leak.hash * &s_inv + q + q - &leak.known % q - &half_x % q) % q;
+        basis[i][i] = big(&(q * q));
+        basis[m][i] = big(&(t * q));
+        basis[m + 1][i] = big(&(u * q));
+    }
+    basis[m][m] = big(&x);
+    basis[m + 1][m + 1] = big(&(&x * q));
+    lll(&mut basis);
+
+    // Rows with ±Xq in the last place are ±(target + multiples of the q
+    // rows), and their second to last entry is then ±d X mod q X
+    let last = big(&(&x * q));
+    basis.iter().find_map(|row| {
+        let sign = if row[m + 1] == last {
+            1i32
+        } else if row[m + 1] == -&last {
+            -1
+        } else {
+            return None;
+        };
+        let d = (&row[m] / big(&x) * BigInt::from(sign)) % big(q);
+        let d = (d + big(q)).to_biguint()? % q;
+        (d != BigUint::from(0u32) && ecdsa.generate_pub_key(&d) == *pub_key).then_some(d)
+    })
+}
+
+fn lll(basis: &mut [Vec<BigInt>]) {
+    // Cohen 2.6.7, on the rows of `basis`, which must be independent. Indices
+    // are 1-based as in the book: d[i] is the Gram determinant of the first i
+    // rows, d[0] = 1, and lambda[k][j] = d[j] μ_kj
+    let n = basis.len();
+    if n < 2 {
+        return;
+    }
+    let dot = |a: &[BigInt], b: &[BigInt]| -> BigInt { a.iter().zip(b).map(|(x, y)| x * y).sum() };
+    let zero = BigInt::from(0);
+    let mut d = vec![zero.clone(); n + 1];
+    let mut lambda = vec![vec![zero.clone(); n + 1]; n + 1];
+    d[0] = BigInt::from(1);
+    d[1] = dot(&basis[0], &basis[0]);
+
+    let (mut k, mut k_max) = (2, 1);
+    while k <= n {
+        if k > k_max {
+            k_max = k;
+            for j in 1..=k {
+                let mut u = dot(&basis[k - 1], &basis[j - 1]);
+                for i in 1..j {
+                    u = (&d[i] * &u - &lambda[k][i] * &lambda[j][i]) / &d[i - 1];
+                }
+                if j < k {
+                    lambda[k][j] = u;
+                } else {
+                    assert!(u != zero, "LLL needs independent rows");
+                    d[k] = u;
+                }
+            }
+        }
+
+        reduce(basis, &mut lambda, &d, k, k - 1);
+        let (delta_num, delta_den) = (BigInt::from(DELTA.0), BigInt::from(DELTA.1));
+        let lambda_k = &lambda[k][k - 1];
+        if &delta_den * &d[k] * &d[k - 2]
+            < delta_num * &d[k - 1] * &d[k - 1] - &delta_den * lambda_k * lambda_k
+        {
+            swap(basis, &mut lambda, &mut d, k, k_max);
+            k = (k - 1).max(2);
+        } else {
+            for l in (1..k - 1).rev() {
+                reduce(basis, &mut lambda, &d, k, l);
+            }
+            k += 1;
+        }
+    }
+}
+
+fn reduce(basis: &mut [Vec<BigInt>], lambda: &mut [Vec<BigInt>], d: &[BigInt], k: usize, l: usize) {
+    // Size reduction of row k against row l, |μ_kl| ≤ 1/2
+    let two = BigInt::from(2);
+    if lambda[k][l].magnitude() * 2u32 <= *d[l].magnitude() {
+        return;
+    }
+    let q = floor_div(&(&two * &lambda[k][l] + &d[l]), &(&two * &d[l]));
+    let row_l = basis[l - 1].clone();
+    for (a, b) in basis[k - 1].iter_mut().zip(&row_l) {
+        *a -= &q * b;
+    }
+    lambda[k][l] -= &q * &d[l];
+    let (upper, lower) = lambda.split_at_mut(k);
+    for (a, b) in lower[0][1..l].iter_mut().zip(&upper[l][1..l]) {
+        *a -= &q * b;
+    }
+}
+
+fn swap(
+    basis: &mut [Vec<BigInt>],
+    lambda: &mut [Vec<BigInt>],
+    d: &mut [BigInt],
+    k: usize,
+    k_max: usize,
+) {
+    basis.swap(k - 1, k - 2);
+    let (upper, lower) = lambda.split_at_mut(k);
+    for (a, b) in upper[k - 1][1..k - 1]
+        .iter_mut()
+        .zip(&mut lower[0][1..k - 1])
+    {
+        core::mem::swap(a, b);
+    }
+    let l = lambda[k][k - 1].clone();
+    let b = (&d[k - 2] * &d[k] + &l * &l) / &d[k - 1];
+    for row in &mut lambda[k + 1..=k_max] {
+        let t = row[k].clone();
+        row[k] = (&d[k] * &row[k - 1] - &l * &t) / &d[k - 1];
+        row[k - 1] = (&b * &t + &l * &row[k]) / &d[k];
+    }
+    d[k - 1] = b;
+}
+
+fn floor_div(a: &BigInt, b: &BigInt) -> BigInt {
+    // b > 0; BigInt division truncates towards zero
+    let q = a / b;
+    if a.sign() == Sign::Minus && &q * b != *a {
+        q - 1
+    } else {
+        q
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use num_bigint::RandBigInt;
+
+    use crate::curves::p256;
+
+    fn leaks(
+        ecdsa: &ECDSA,
+        priv_key: &BigUint,
+        count: usize,
+        unknown_bits: u64,
+        known_bits: u64,
+    ) -> Vec<NonceLeak> {
+        // Nonces below 2^(unknown_bits + known_bits), whose top known_bits
+        // bits leak
+        let mut rng = crate::rng();
+        (0..count)
+            .map(|_| {
+                let k = rng.gen_biguint(unknown_bits + known_bits) | BigUint::from(1u32);
+                let hash = rng.gen_biguint_below(&ecdsa.q_order);
+                NonceLeak {
+                    signature: ecdsa.sign(&hash, priv_key, &k),
+                    known: &k >> unknown_bits << unknown_bits,
+                    hash,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_short_nonces() {
+        // 64 zero bits in each nonce, six signatures
+        let ecdsa = p256();
+        let priv_key = ecdsa.generate_priv_key();
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let leaks = leaks(&ecdsa, &priv_key, 6, 192, 0);
+        assert_eq!(recover_key(&ecdsa, &pub_key, &leaks, 192), Some(priv_key));
+        // Too few signatures for the lattice to pin the key down
+        assert_eq!(recover_key(&ecdsa, &pub_key, &leaks[..2], 192), None);
+    }
+
+    #[test]
+    fn test_known_top_bits() {
+        // The top 96 bits of each of four full length nonces are known
+        let ecdsa = p256();
+        let priv_key = ecdsa.generate_priv_key();
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let leaks = leaks(&ecdsa, &priv_key, 4, 159, 96);
+        assert_eq!(recover_key(&ecdsa, &pub_key, &leaks, 159), Some(priv_key));
+    }
+
+    #[test]
+    fn test_lll() {
+        // The example on Wikipedia's LLL page, which reduces to (0, 1, 0),
+        // (1, 0, 1) and (-1, 0, 2)
+        let mut basis: Vec<Vec<BigInt>> = [[1, 1, 1], [-1, 0, 2], [3, 5, 6]]
+            .iter()
+            .map(|row| row.iter().map(|&v| BigInt::from(v)).collect())
+            .collect();
+        lll(&mut basis);
+        let norms: Vec<BigInt> = basis
+            .iter()
+            .map(|row| row.iter().map(|v| v * v).sum())
+            .collect();
+        assert_eq!(norms, [1, 2, 5].map(BigInt::from));
+    }
+}
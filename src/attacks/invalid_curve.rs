@@ -0,0 +1,203 @@
+// Invalid-curve attack (Biehl-Meyer-Müller, Antipa et al.) on an ECDH that
+// skips `check_pub_key`. The addition formulas never use b, so a victim that
+// multiplies any (x, y) by its key computes on y² = x³ + ax + b' for the b'
+// the point happens to satisfy. The attacker sends points of small prime order
+// ℓ on such curves, learns d mod ℓ up to sign from each answer by trying the ℓ
+// multiples, and puts d together by the Chinese remainder theorem. Finding the
+// small order points needs the group orders of the b' curves, so the demo only
+// runs on curves small enough for `analysis`. Compiled only with the
+// `danger-demos` feature, as `unchecked_diffie_hellman` is exactly the bug
+// `diffie_hellman` guards against.
+
+use alloc::{vec, vec::Vec};
+use ec_generic::{EllipticCurve, Point};
+use num_bigint::BigUint;
+
+use crate::analysis::{count_points_bsgs, find_point_of_order, prime_factors};
+use crate::arithmetic::mod_inverse;
+use crate::encoding::to_fixed_bytes;
+use crate::secret::SecretScalar;
+use crate::ECDSA;
+
+pub fn unchecked_diffie_hellman(
+    ecdsa: &ECDSA,
+    priv_key: &BigUint,
+    peer: &Point,
+) -> Option<Vec<u8>> {
+    // The vulnerable version: x(dB) straight from the peer's point, with no
+    // check that it is on the curve. None only for the identity
+    match ecdsa.mul_secret(peer, &SecretScalar::new(priv_key.clone())) {
+        Point::Coor(x, _) => Some(to_fixed_bytes(&x, ecdsa.field_len())),
+        Point::Identity => None,
+    }
+}
+
+pub fn recover_key(
+    ecdsa: &ECDSA,
+    pub_key: &Point,
+    max_prime: u64,
+    mut oracle: impl FnMut(&Point) -> Option<Vec<u8>>,
+) -> Option<BigUint> {
+    // `oracle` answers like `unchecked_diffie_hellman` with the victim's key;
+    // points of every prime order up to `max_prime` are sent until the primes
+    // multiply past q. Returns the key, checked against `pub_key`
+    let curve = &ecdsa.elliptic_curve;
+    let p = &curve.p;
+    let mut residues: Vec<(u64, u64)> = Vec::new();
+    let mut modulus = BigUint::from(1u32);
+
+    let mut b = BigUint::from(0u32);
+    while modulus <= ecdsa.q_order && &b < p {
+        let twist = EllipticCurve {
+            a: curve.a.clone(),
+            b: b.clone(),
+            p: p.clone(),
+        };
+        b += 1u32;
+        if twist.b == curve.b || is_singular(&twist) {
+            continue;
+        }
+        let Some(order) = count_points_bsgs(&twist) else {
+            continue;
+        };
+        for l in prime_factors(order) {
+            if l > max_prime || residues.iter().any(|&(used, _)| used == l) {
+                continue;
+            }
+            let Some(point) = find_point_of_order(&twist, order, l) else {
+                continue;
+            };
+            let answer = oracle(&point);
+            residues.push((l, discrete_log_x(ecdsa, &twist, &point, l, answer)?));
+            modulus *= l;
+        }
+    }
+    if modulus <= ecdsa.q_order {
+        return None;
+    }
+
+    // Each answer fixes d mod ℓ only up to sign; try the combinations
+    let signs = residues
+        .iter()
+        .filter(|&&(l, j)| j != 0 && 2 * j != l)
+        .count();
+    (0u64..1 << signs).find_map(|choice| {
+        let mut bit = 0;
+        let chosen: Vec<(u64, u64)> = residues
+            .iter()
+            .map(|&(l, j)| {
+                if j == 0 || 2 * j == l {
+                    return (l, j);
+                }
+                bit += 1;
+                if choice >> (bit - 1) & 1 == 1 {
+                    (l, l - j)
+                } else {
+                    (l, j)
+                }
+            })
+            .collect();
+        let d = crt(&chosen)? % &ecdsa.q_order;
+        (d != BigUint::from(0u32) && ecdsa.generate_pub_key(&d) == *pub_key).then_some(d)
+    })
+}
+
+fn discrete_log_x(
+    ecdsa: &ECDSA,
+    twist: &EllipticCurve,
+    point: &Point,
+    l: u64,
+    answer: Option<Vec<u8>>,
+) -> Option<u64> {
+    // The j in [0, ℓ / 2] with x(jP) = answer, None for the identity
+    let Some(answer) = answer else {
+        return Some(0);
+    };
+    let mut multiple = point.clone();
+    for j in 1..=l / 2 {
+        if let Point::Coor(x, _) = &multiple {
+            if to_fixed_bytes(x, ecdsa.field_len()) == answer {
+                return Some(j);
+            }
+        }
+        multiple = twist.add(&multiple, point).ok()?;
+    }
+    None
+}
+
+fn is_singular(curve: &EllipticCurve) -> bool {
+    // 4a³ + 27b² = 0 mod p
+    let p = &curve.p;
+    let discriminant = BigUint::from(4u32) * curve.a.modpow(&BigUint::from(3u32), p)
+        + BigUint::from(27u32) * &curve.b * &curve.b;
+    discriminant % p == BigUint::from(0u32)
+}
+
+fn crt(residues: &[(u64, u64)]) -> Option<BigUint> {
+    let mut value = BigUint::from(0u32);
+    let mut modulus = BigUint::from(1u32);
+    for &(l, j) in residues {
+        let l_big = BigUint::from(l);
+        // value + modulus * t = j mod ℓ
+        let inverse = mod_inverse(&(&modulus % &l_big), &l_big)?;
+        let t = (BigUint::from(j) + &l_big - &value % &l_big) * inverse % &l_big;
+        value += &modulus * t;
+        modulus *= l_big;
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::analysis::find_generator;
+    use crate::arithmetic::is_probable_prime;
+    use crate::ecdh::PubKeyError;
+
+    fn ecdsa() -> ECDSA {
+        // The first y² = x³ - 3x + b over F_65521 with a prime number of points
+        let p = 65521u32;
+        (1..)
+            .find_map(|b| {
+                let curve = EllipticCurve {
+                    a: BigUint::from(p - 3),
+                    b: BigUint::from(b as u32),
+                    p: BigUint::from(p),
+                };
+                let order = count_points_bsgs(&curve)?;
+                if !is_probable_prime(&BigUint::from(order)) {
+                    return None;
+                }
+                let a_gen = find_generator(&curve, order)?;
+                ECDSA::new(curve, a_gen, BigUint::from(order))
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_invalid_curve_attack() {
+        let ecdsa = ecdsa();
+        let (priv_key, pub_key) = ecdsa.generate_key_pair();
+
+        let mut queries = Vec::new();
+        let recovered = recover_key(&ecdsa, &pub_key, 50, |point| {
+            queries.push(point.clone());
+            unchecked_diffie_hellman(&ecdsa, &priv_key, point)
+        });
+        assert_eq!(recovered, Some(priv_key.clone()));
+
+        // Every query was off the curve, and the checked ECDH refuses them all
+        assert!(!queries.is_empty());
+        for point in &queries {
+            assert_eq!(ecdsa.check_pub_key(point), Err(PubKeyError::NotOnCurve));
+            assert!(ecdsa.diffie_hellman(&priv_key, point).is_none());
+        }
+    }
+
+    #[test]
+    fn test_crt() {
+        // 23 = 1 mod 2, 2 mod 3, 3 mod 5
+        assert_eq!(crt(&[(2, 1), (3, 2), (5, 3)]), Some(BigUint::from(23u32)));
+    }
+}
@@ -0,0 +1,425 @@
+// Keys held in AWS KMS, behind the `aws-kms` feature. `sign_async` hashes
+// the message with SHA-256 here and sends only the digest to the Sign action
+// (MessageType DIGEST, SigningAlgorithm ECDSA_SHA_256). KMS answers with a
+// DER signature, which is checked against the key from GetPublicKey before it
+// is handed out. KMS signs plain ECDSA over the digest, so its signatures
+// verify with `VerifyingKey`, `verify_standard(sha256(msg), ...)` and
+// `KeyPair::verify`, like the ones `SigningKey` and `KeyPair::sign` make.
+//
+// Requests go to the JSON API (X-Amz-Target TrentService.*) signed with
+// Signature Version 4. Credentials are passed in; reading them from the
+// environment, profiles or instance metadata is up to the caller.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ec_generic::Point;
+use serde::{Deserialize, Serialize};
+use signature::Error;
+
+use crate::hash::{hmac_sha256, sha256};
+use crate::pem;
+use crate::signer::{AsyncSigner, Signature};
+use crate::ECDSA;
+
+const CONTENT_TYPE: &str = "application/x-amz-json-1.1";
+
+#[derive(Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+pub struct AwsKmsSigner<'a> {
+    ecdsa: &'a ECDSA,
+    client: reqwest::Client,
+    endpoint: String,
+    region: String,
+    key_id: String,
+    credentials: AwsCredentials,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct SignRequest<'a> {
+    key_id: &'a str,
+    message: String,
+    message_type: &'a str,
+    signing_algorithm: &'a str,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SignResponse {
+    signature: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct GetPublicKeyRequest<'a> {
+    key_id: &'a str,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GetPublicKeyResponse {
+    public_key: String,
+    key_spec: String,
+}
+
+impl<'a> AwsKmsSigner<'a> {
+    pub fn new(
+        ecdsa: &'a ECDSA,
+        region: &str,
+        key_id: &str,
+        credentials: AwsCredentials,
+    ) -> AwsKmsSigner<'a> {
+        // `key_id` is a key ID, key ARN, alias name or alias ARN
+        AwsKmsSigner {
+            ecdsa,
+            client: reqwest::Client::new(),
+            endpoint: format!("https://kms.{}.amazonaws.com", region),
+            region: region.to_string(),
+            key_id: key_id.to_string(),
+            credentials,
+        }
+    }
+
+    pub fn with_endpoint(mut self, endpoint: &str) -> AwsKmsSigner<'a> {
+        // A VPC endpoint, FIPS endpoint or local stand-in
+        self.endpoint = endpoint.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub async fn public_key(&self) -> Result<Point, Error> {
+        // An error unless the KMS key is on this signer's curve
+        let request = GetPublicKeyRequest {
+            key_id: &self.key_id,
+        };
+        let response: GetPublicKeyResponse = self.call("GetPublicKey", &request).await?;
+        let expected = if self.ecdsa.is_p256() {
+            "ECC_NIST_P256"
+        } else if self.ecdsa.is_secp256k1() {
+            "ECC_SECG_P256K1"
+        } else {
+            return Err(Error::new());
+        };
+        let der = STANDARD
+            .decode(response.public_key)
+            .map_err(Error::from_source)?;
+        match pem::public_key_from_der(&der) {
+            Some((ecdsa, pub_key))
+                if response.key_spec == expected && ecdsa.same_parameters(self.ecdsa) =>
+            {
+                Ok(pub_key)
+            }
+            _ => Err(Error::new()),
+        }
+    }
+
+    async fn call<T: Serialize, R: for<'de> Deserialize<'de>>(
+        &self,
+        action: &str,
+        body: &T,
+    ) -> Result<R, Error> {
+        let body = serde_json::to_vec(body).map_err(Error::from_source)?;
+        let target = format!("TrentService.{}", action);
+        let amz_date = amz_date(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(Error::from_source)?
+                .as_secs(),
+        );
+        let host = self
+            .endpoint
+            .split_once("://")
+            .map_or(self.endpoint.as_str(), |(_, rest)| rest)
+            .split('/')
+            .next()
+            .unwrap_or_default();
+        let mut headers = vec![
+            ("content-type", CONTENT_TYPE),
+            ("host", host),
+            ("x-amz-date", amz_date.as_str()),
+            ("x-amz-target", target.as_str()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token", token));
+        }
+        let authorization = sigv4_authorization(
+            &self.credentials,
+            &self.region,
+            "kms",
+            "POST",
+            "/",
+            &headers,
+            &body,
+        );
+
+        let mut request = self
+            .client
+            .post(format!("{}/", self.endpoint))
+            .header("authorization", authorization)
+            .body(body);
+        for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+            request = request.header(*name, *value);
+        }
+        request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(Error::from_source)?
+            .json()
+            .await
+            .map_err(Error::from_source)
+    }
+}
+
+impl AsyncSigner for AwsKmsSigner<'_> {
+    async fn sign_async(&self, msg: &[u8]) -> Result<Signature, Error> {
+        let digest = sha256(msg);
+        let request = SignRequest {
+            key_id: &self.key_id,
+            message: STANDARD.encode(&digest),
+            message_type: "DIGEST",
+            signing_algorithm: "ECDSA_SHA_256",
+        };
+        let response: SignResponse = self.call("Sign", &request).await?;
+        let der = STANDARD
+            .decode(response.signature)
+            .map_err(Error::from_source)?;
+        let signature = Signature::from_der(&der).ok_or_else(Error::new)?;
+
+        let pub_key = self.public_key().await?;
+        if !self.ecdsa.verify_standard(&digest, &pub_key, &signature) {
+            return Err(Error::new());
+        }
+        Ok(signature)
+    }
+}
+
+fn sigv4_authorization(
+    credentials: &AwsCredentials,
+    region: &str,
+    service: &str,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+    body: &[u8],
+) -> String {
+    // AWS Signature Version 4 over the given headers, which must be
+    // lowercase, include host and x-amz-date and need no trimming; no query
+    let mut headers = headers.to_vec();
+    headers.sort();
+    let amz_date = headers
+        .iter()
+        .find(|(name, _)| *name == "x-amz-date")
+        .map(|(_, value)| *value)
+        .unwrap();
+    let date = &amz_date[..8];
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value))
+        .collect();
+    let signed_headers: Vec<&str> = headers.iter().map(|(name, _)| *name).collect();
+    let signed_headers = signed_headers.join(";");
+    let canonical_request = format!(
+        "{}\n{}\n\n{}\n{}\n{}",
+        method,
+        path,
+        canonical_headers,
+        signed_headers,
+        hex::encode(sha256(body))
+    );
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(sha256(canonical_request.as_bytes()))
+    );
+
+    let secret = format!("AWS4{}", credentials.secret_access_key);
+    let key = [date, region, service, "aws4_request"]
+        .iter()
+        .fold(secret.into_bytes(), |key, part| {
+            hmac_sha256(&key, part.as_bytes())
+        });
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id,
+        scope,
+        signed_headers,
+        hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()))
+    )
+}
+
+fn amz_date(unix_seconds: u64) -> String {
+    // YYYYMMDDTHHMMSSZ, from the civil calendar of the days since 1970
+    let (days, seconds) = (unix_seconds / 86400, unix_seconds % 86400);
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use num_bigint::BigUint;
+    use signature::Verifier;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use crate::curves::p256;
+    use crate::signer::VerifyingKey;
+
+    fn credentials() -> AwsCredentials {
+        AwsCredentials {
+            access_key_id: "AKIDEXAMPLE".into(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".into(),
+            session_token: None,
+        }
+    }
+
+    #[test]
+    fn test_sigv4() {
+        // get-vanilla from the AWS Signature Version 4 test suite
+        let authorization = sigv4_authorization(
+            &credentials(),
+            "us-east-1",
+            "service",
+            "GET",
+            "/",
+            &[
+                ("x-amz-date", "20150830T123600Z"),
+                ("host", "example.amazonaws.com"),
+            ],
+            b"",
+        );
+        assert_eq!(
+            authorization,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+        assert_eq!(amz_date(1440938160), "20150830T123600Z");
+        assert_eq!(amz_date(951782400), "20000229T000000Z");
+    }
+
+    fn serve(requests: usize) -> String {
+        // A stand-in for KMS holding one P-256 key, one request per
+        // connection; it only looks at the action and the signed headers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let ecdsa = p256();
+            let priv_key = BigUint::from(0xc0ffeeu32);
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut target = String::new();
+                let mut authorization = String::new();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end().to_string();
+                    if line.is_empty() {
+                        break;
+                    }
+                    let (name, value) = line.split_once(": ").unwrap_or(("", ""));
+                    match name.to_ascii_lowercase().as_str() {
+                        "x-amz-target" => target = value.to_string(),
+                        "authorization" => authorization = value.to_string(),
+                        "content-length" => content_length = value.parse().unwrap(),
+                        _ => {}
+                    }
+                }
+                let mut body = vec![0u8; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+                let signed = authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/")
+                    && authorization.contains("/kms/aws4_request")
+                    && authorization
+                        .contains("SignedHeaders=content-type;host;x-amz-date;x-amz-target,");
+                let json = match target.as_str() {
+                    _ if !signed || body["KeyId"] != "alias/release" => None,
+                    "TrentService.GetPublicKey" => {
+                        let pub_key = ecdsa.generate_pub_key(&priv_key);
+                        let der = ecdsa.public_key_to_der(&pub_key).unwrap();
+                        Some(serde_json::json!({
+                            "KeyId": "alias/release",
+                            "KeySpec": "ECC_NIST_P256",
+                            "PublicKey": STANDARD.encode(der),
+                        }))
+                    }
+                    "TrentService.Sign" if body["MessageType"] == "DIGEST" => {
+                        let digest = STANDARD.decode(body["Message"].as_str().unwrap()).unwrap();
+                        let signature = ecdsa.sign_standard(&mut crate::rng(), &digest, &priv_key);
+                        Some(serde_json::json!({
+                            "KeyId": "alias/release",
+                            "Signature": STANDARD.encode(signature.to_der()),
+                            "SigningAlgorithm": "ECDSA_SHA_256",
+                        }))
+                    }
+                    _ => None,
+                };
+                let reply = match json {
+                    Some(json) => {
+                        let json = json.to_string();
+                        format!(
+                            "HTTP/1.1 200 OK\r\ncontent-type: {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                            CONTENT_TYPE,
+                            json.len(),
+                            json
+                        )
+                    }
+                    None => {
+                        "HTTP/1.1 400 Bad Request\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                            .to_string()
+                    }
+                };
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_aws_kms_signer() {
+        let ecdsa = p256();
+        let signer = AwsKmsSigner::new(&ecdsa, "eu-west-1", "alias/release", credentials())
+            .with_endpoint(&serve(3));
+        let signature = signer.sign_async(b"release v1.2").await.unwrap();
+        let pub_key = signer.public_key().await.unwrap();
+        assert!(ecdsa.verify_standard(&sha256(b"release v1.2"), &pub_key, &signature));
+        let verifying_key = VerifyingKey::new(&ecdsa, pub_key).unwrap();
+        assert!(verifying_key.verify(b"release v1.2", &signature).is_ok());
+
+        // The key is on P-256, not the signer's curve
+        let secp256k1 = crate::curves::secp256k1();
+        let signer = AwsKmsSigner::new(&secp256k1, "eu-west-1", "alias/release", credentials())
+            .with_endpoint(&serve(1));
+        assert!(signer.public_key().await.is_err());
+    }
+}
@@ -0,0 +1,13 @@
+// Command line tool, see `run_cli` for the commands
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (mut stdin, mut stdout) = (std::io::stdin().lock(), std::io::stdout().lock());
+    match rs_ecc::run_cli(&args, &mut stdin, &mut stdout) {
+        Ok(status) => std::process::exit(status),
+        Err(error) => {
+            eprintln!("rs_ecc: {}", error);
+            std::process::exit(2);
+        }
+    }
+}
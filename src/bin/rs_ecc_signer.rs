@@ -0,0 +1,33 @@
+// Signing service: rs_ecc_signer <key store> <listen address>. Clients
+// authenticate with the bearer token in RS_ECC_SIGNER_TOKEN, and the audit
+// log goes to stdout, one JSON object per line.
+
+use std::io;
+use std::sync::Mutex;
+
+use rs_ecc::{router, serve, BearerToken, JsonLinesAudit, KeyStore};
+use tokio::net::TcpListener;
+
+#[tokio::main]
+async fn main() -> io::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 3 {
+        eprintln!("usage: rs_ecc_signer <key store> <listen address>");
+        std::process::exit(2);
+    }
+
+    let store = KeyStore::load(&args[1])?;
+    let token = std::env::var("RS_ECC_SIGNER_TOKEN").map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "RS_ECC_SIGNER_TOKEN is not set",
+        )
+    })?;
+    let listener = TcpListener::bind(&args[2]).await?;
+    let router = router(
+        store,
+        Box::new(BearerToken(token)),
+        Box::new(JsonLinesAudit(Mutex::new(io::stdout()))),
+    );
+    serve(listener, router).await
+}
@@ -0,0 +1,186 @@
+// BIP 340 Schnorr signatures on secp256k1, the ones Taproot and Nostr use.
+// Public keys are x-only, the 32 bytes of x standing for the point with even
+// y, so signing negates the private key d when dG has odd y, and the nonce k
+// when R = kG does. A signature is x(R) || s with
+//
+//   s = k + e * d mod n,  e = H_BIP0340/challenge(x(R) || x(P) || m)
+//
+// and the nonce comes from the key, the message and 32 bytes of auxiliary
+// randomness, so a bad random source leaks nothing: all-zero aux_rand still
+// gives a safe deterministic nonce. Messages are signed as they are, usually
+// a 32-byte hash.
+
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::encoding::to_fixed_bytes;
+use crate::hash::tagged_hash;
+use crate::secret::SecretScalar;
+use crate::ECDSA;
+
+impl ECDSA {
+    pub fn x_only(&self, point: &Point) -> Option<Vec<u8>> {
+        // None for the identity
+        match point {
+            Point::Coor(x, _) => Some(to_fixed_bytes(x, 32)),
+            Point::Identity => None,
+        }
+    }
+
+    pub fn lift_x(&self, x: &[u8]) -> Option<Point> {
+        // The point with this x and even y; None off secp256k1, for x not
+        // below p or not on the curve
+        let x = BigUint::from_bytes_be(x);
+        if !self.is_secp256k1() || x >= self.elliptic_curve.p {
+            return None;
+        }
+        let y = self.y_for_x(&x)?;
+        let y = if y.bit(0) {
+            &self.elliptic_curve.p - y
+        } else {
+            y
+        };
+        Some(Point::Coor(x, y))
+    }
+
+    pub fn bip340_sign(
+        &self,
+        message: &[u8],
+        priv_key: &BigUint,
+        aux_rand: &[u8; 32],
+    ) -> Option<Vec<u8>> {
+        // The 64-byte signature, checked before it is returned; None off
+        // secp256k1 or for a key not in [1, n - 1]
+        let n = &self.q_order;
+        if !self.is_secp256k1() || *priv_key == BigUint::from(0u32) || priv_key >= n {
+            return None;
+        }
+        let pub_key = self.generate_pub_key(priv_key);
+        let d = if has_even_y(&pub_key) {
+            priv_key.clone()
+        } else {
+            n - priv_key
+        };
+        let pub_x = self.x_only(&pub_key)?;
+
+        let mask = tagged_hash("BIP0340/aux", aux_rand);
+        let t: Vec<u8> = to_fixed_bytes(&d, 32)
+            .iter()
+            .zip(&mask)
+            .map(|(a, b)| a ^ b)
+            .collect();
+        let nonce = tagged_hash("BIP0340/nonce", &[&t[..], &pub_x, message].concat());
+        let k = BigUint::from_bytes_be(&nonce) % n;
+        if k == BigUint::from(0u32) {
+            return None;
+        }
+        let r_point = self.mul_secret(&self.a_gen, &SecretScalar::new(k.clone()));
+        let k = if has_even_y(&r_point) { k } else { n - k };
+        let r_x = self.x_only(&r_point)?;
+        let e = self.bip340_challenge(&r_x, &pub_x, message);
+        let s = (k + e * d) % n;
+
+        let signature = [r_x, to_fixed_bytes(&s, 32)].concat();
+        self.bip340_verify(message, &pub_x, &signature)
+            .then_some(signature)
+    }
+
+    pub fn bip340_verify(&self, message: &[u8], pub_key: &[u8], signature: &[u8]) -> bool {
+        // sG - eP = R with even y and x(R) = r, for the x-only key
+        if pub_key.len() != 32 || signature.len() != 64 {
+            return false;
+        }
+        let Some(pub_point) = self.lift_x(pub_key) else {
+            return false;
+        };
+        let r = BigUint::from_bytes_be(&signature[..32]);
+        let s = BigUint::from_bytes_be(&signature[32..]);
+        if r >= self.elliptic_curve.p || s >= self.q_order {
+            return false;
+        }
+        let e = self.bip340_challenge(&signature[..32], pub_key, message);
+        let r_point = self.sub_points(
+            &self.mul_vartime(&self.a_gen, &s),
+            &self.mul_vartime(&pub_point, &e),
+        );
+        match &r_point {
+            Point::Coor(x, _) => has_even_y(&r_point) && *x == r,
+            Point::Identity => false,
+        }
+    }
+
+    fn bip340_challenge(&self, r_x: &[u8], pub_x: &[u8], message: &[u8]) -> BigUint {
+        let e = tagged_hash("BIP0340/challenge", &[r_x, pub_x, message].concat());
+        BigUint::from_bytes_be(&e) % &self.q_order
+    }
+}
+
+pub(crate) fn has_even_y(point: &Point) -> bool {
+    matches!(point, Point::Coor(_, y) if !y.bit(0))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::{p256, secp256k1};
+
+    #[test]
+    fn test_bip340_vectors() {
+        // Vectors 0 and 1 of BIP 340
+        let ecdsa = secp256k1();
+        for (priv_key, pub_key, aux_rand, message, signature) in [
+            (
+                "03",
+                "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+                [0; 32],
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "e907831f80848d1069a5371b402410364bdf1c5f8307b0084c55f1ce2dca8215\
+                 25f66a4a85ea8b71e482a74f382d2ce5ebeee8fdb2172f477df4900d310536c0",
+            ),
+            (
+                "b7e151628aed2a6abf7158809cf4f3c762e7160f38b4da56a784d9045190cfef",
+                "dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659",
+                {
+                    let mut aux_rand = [0; 32];
+                    aux_rand[31] = 1;
+                    aux_rand
+                },
+                "243f6a8885a308d313198a2e03707344a4093822299f31d0082efa98ec4e6c89",
+                "6896bd60eeae296db48a229ff71dfe071bde413e6d43f917dc8dcf8c78de3341\
+                 8906d11ac976abccb20b091292bff4ea897efcb639ea871cfa95f6de339e4b0a",
+            ),
+        ] {
+            let priv_key = BigUint::parse_bytes(priv_key.as_bytes(), 16).unwrap();
+            let message = hex::decode(message).unwrap();
+            let pub_x = ecdsa.x_only(&ecdsa.generate_pub_key(&priv_key)).unwrap();
+            assert_eq!(hex::encode(&pub_x), pub_key);
+            let signed = ecdsa.bip340_sign(&message, &priv_key, &aux_rand).unwrap();
+            assert_eq!(hex::encode(&signed), signature);
+            assert!(ecdsa.bip340_verify(&message, &pub_x, &signed));
+
+            let mut tampered = signed.clone();
+            tampered[63] ^= 1;
+            assert!(!ecdsa.bip340_verify(&message, &pub_x, &tampered));
+        }
+    }
+
+    #[test]
+    fn test_bip340_odd_keys() {
+        // Keys and nonces with odd y are negated, and the x-only key stands
+        // for the even point; G has even y, -G odd
+        let ecdsa = secp256k1();
+        for priv_key in [BigUint::from(1u32), &ecdsa.q_order - 1u32] {
+            let pub_x = ecdsa.x_only(&ecdsa.generate_pub_key(&priv_key)).unwrap();
+            let signature = ecdsa.bip340_sign(b"message", &priv_key, &[7; 32]).unwrap();
+            assert!(ecdsa.bip340_verify(b"message", &pub_x, &signature));
+            assert!(!ecdsa.bip340_verify(b"massage", &pub_x, &signature));
+            assert!(has_even_y(&ecdsa.lift_x(&pub_x).unwrap()));
+        }
+        assert!(ecdsa.lift_x(&[0xff; 32]).is_none());
+        assert!(p256()
+            .bip340_sign(b"message", &BigUint::from(3u32), &[0; 32])
+            .is_none());
+    }
+}
@@ -0,0 +1,236 @@
+// Bitcoin signed messages, as `signmessage` and `verifymessage` in Bitcoin
+// Core and "Sign/verify message" in Electrum handle them. The message is
+// hashed with double SHA-256 after the magic prefix, both preceded by their
+// lengths as Bitcoin varints:
+//
+//   varint(24) || "Bitcoin Signed Message:\n" || varint(len) || message
+//
+// and signed on secp256k1 with low s. The signature travels in base64 as 65
+// bytes, a header byte and r || s; the header tells the verifier how to
+// recover the key and which address form it has (BIP 137):
+//
+//   27 + recovery id   P2PKH of the uncompressed key
+//   31 + recovery id   P2PKH of the compressed key
+//   35 + recovery id   P2SH-P2WPKH, 39 + recovery id P2WPKH (Trezor)
+//
+// Electrum signs for any address of a compressed key with 31 to 34, so a
+// compressed header is accepted for all three address forms of that key, on
+// mainnet and testnet alike.
+
+use alloc::{string::String, vec, vec::Vec};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::address::{base58check, segwit_address};
+use crate::hash::{hash160, sha256};
+use crate::ECDSA;
+
+const MAGIC: &[u8] = b"Bitcoin Signed Message:\n";
+
+impl ECDSA {
+    pub fn sign_bitcoin_message(
+        &self,
+        message: &[u8],
+        priv_key: &BigUint,
+        compressed: bool,
+    ) -> Option<String> {
+        // The base64 signature for the P2PKH address of the key in the given
+        // form; None off secp256k1
+        if !self.is_secp256k1() {
+            return None;
+        }
+        let digest = bitcoin_message_hash(message);
+        let signature = self.sign_standard(&mut crate::rng(), &digest, priv_key);
+        let signature = signature.normalize(self);
+        let pub_key = self.generate_pub_key(priv_key);
+        let recovery_id = (0..4).find(|&recovery_id| {
+            self.recover_public_key(&digest, &signature, recovery_id) == Some(pub_key.clone())
+        })?;
+        let header = 27 + recovery_id + if compressed { 4 } else { 0 };
+        let mut bytes = vec![header];
+        bytes.extend(self.encode_signature(&signature));
+        Some(STANDARD.encode(bytes))
+    }
+
+    pub fn recover_bitcoin_message_key(
+        &self,
+        message: &[u8],
+        signature: &str,
+    ) -> Option<(Point, bool)> {
+        // The signer's key and whether the header says it is compressed
+        if !self.is_secp256k1() {
+            return None;
+        }
+        let bytes = STANDARD.decode(signature.trim()).ok()?;
+        let (&header, signature) = bytes.split_first()?;
+        if signature.len() != 64 || !(27..=42).contains(&header) {
+            return None;
+        }
+        let signature = self.decode_signature(signature)?;
+        let digest = bitcoin_message_hash(message);
+        let pub_key = self.recover_public_key(&digest, &signature, (header - 27) & 3)?;
+        Some((pub_key, header >= 31))
+    }
+
+    pub fn verify_bitcoin_message(&self, address: &str, message: &[u8], signature: &str) -> bool {
+        // Whether the signature recovers a key with this address
+        let Some((pub_key, compressed)) = self.recover_bitcoin_message_key(message, signature)
+        else {
+            return false;
+        };
+        // Bech32 may come in upper case, base58 only as it is
+        let bech32 = address.to_ascii_lowercase();
+        self.bitcoin_addresses(&pub_key, compressed)
+            .iter()
+            .any(|candidate| {
+                if candidate.starts_with(['b', 't']) {
+                    *candidate == bech32
+                } else {
+                    candidate == address
+                }
+            })
+    }
+
+    fn bitcoin_addresses(&self, pub_key: &Point, compressed: bool) -> Vec<String> {
+        // P2PKH, and for a compressed key P2SH-P2WPKH and P2WPKH, on mainnet
+        // and testnet; bech32 in lower case
+        let key = if compressed {
+            self.encode_point(pub_key)
+        } else {
+            self.encode_point_uncompressed(pub_key)
+        };
+        let key_hash = hash160(&key);
+        let mut addresses = Vec::new();
+        for (p2pkh, p2sh, hrp) in [(0x00, 0x05, "bc"), (0x6f, 0xc4, "tb")] {
+            addresses.push(base58check(p2pkh, &key_hash));
+            if compressed {
+                let redeem_script = [&[0x00, 0x14], &key_hash[..]].concat();
+                addresses.push(base58check(p2sh, &hash160(&redeem_script)));
+                addresses.push(segwit_address(hrp, 0, &key_hash));
+            }
+        }
+        addresses
+    }
+}
+
+pub fn bitcoin_message_hash(message: &[u8]) -> Vec<u8> {
+    let mut data = varint(MAGIC.len() as u64);
+    data.extend(MAGIC);
+    data.extend(varint(message.len() as u64));
+    data.extend(message);
+    sha256(&sha256(&data))
+}
+
+pub(crate) fn varint(n: u64) -> Vec<u8> {
+    // Bitcoin's CompactSize: one byte below 0xfd, else a marker byte and the
+    // little endian value in 2, 4 or 8 bytes
+    match n {
+        0..=0xfc => vec![n as u8],
+        0xfd..=0xffff => [&[0xfd][..], &(n as u16).to_le_bytes()].concat(),
+        0x1_0000..=0xffff_ffff => [&[0xfe][..], &(n as u32).to_le_bytes()].concat(),
+        _ => [&[0xff][..], &n.to_le_bytes()].concat(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::{p256, secp256k1};
+
+    // Made with an independent implementation over the `cryptography`
+    // package's secp256k1, for private key 0xc0ffee
+    const MESSAGE: &[u8] = b"Hello, Bitcoin!";
+    const COMPRESSED: &str =
+        "IKbxF31MIBM5htbtx3rfV/V0SHR8VzqYK1BAA5M+MgMYWpmULM4qPWhLFCFdgjKAohu8zQGK/p6Zgg53xV7DGZs=";
+    const UNCOMPRESSED: &str =
+        "G+UQ3/uKf/jIu8sDHqmteNQ1j7JIPFkMdTyd6cF7zQ64QeIuZk9sLDfzMxjR4s12i3cugxX2VDnw2I5k9cqgFfk=";
+
+    #[test]
+    fn test_bitcoin_message_hash() {
+        assert_eq!(
+            hex::encode(bitcoin_message_hash(MESSAGE)),
+            "d679b914285eb193449e2d5ead826522d92bcf4cfcfe3dc24da415bfbdd89464"
+        );
+        assert_eq!(varint(0xfc), [0xfc]);
+        assert_eq!(varint(0xfd), [0xfd, 0xfd, 0x00]);
+        assert_eq!(varint(0x1_0000), [0xfe, 0x00, 0x00, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn test_verify_bitcoin_message() {
+        let ecdsa = secp256k1();
+        assert!(ecdsa.verify_bitcoin_message(
+            "1PkjVT2eq7sLQaad4sa3bsawdHdop5EPWj",
+            MESSAGE,
+            COMPRESSED
+        ));
+        assert!(ecdsa.verify_bitcoin_message(
+            "13tvYqdQQkBCxjgxV1gmzLiWK4bCXo52iB",
+            MESSAGE,
+            UNCOMPRESSED
+        ));
+        // The other form of the key, another message
+        assert!(!ecdsa.verify_bitcoin_message(
+            "13tvYqdQQkBCxjgxV1gmzLiWK4bCXo52iB",
+            MESSAGE,
+            COMPRESSED
+        ));
+        assert!(!ecdsa.verify_bitcoin_message(
+            "1PkjVT2eq7sLQaad4sa3bsawdHdop5EPWj",
+            b"Hello, Bitcoin?",
+            COMPRESSED
+        ));
+
+        // Electrum's compressed header for the segwit and testnet addresses
+        // of the key, and Trezor's P2WPKH header
+        for address in [
+            "bc1qlxvp7agw998t68qm76ek6t20gh320yrs8lhw7j",
+            "BC1QLXVP7AGW998T68QM76EK6T20GH320YRS8LHW7J",
+            "35qjJjW8GzXZUh2YgWptq6d1oiFZ4H1NYL",
+            "n4GgnW7de9JbBh4EnSYRRnoGVHEWhK7sq9",
+        ] {
+            assert!(ecdsa.verify_bitcoin_message(address, MESSAGE, COMPRESSED));
+        }
+        let mut bytes = STANDARD.decode(COMPRESSED).unwrap();
+        bytes[0] += 8;
+        let trezor = STANDARD.encode(&bytes);
+        assert!(ecdsa.verify_bitcoin_message(
+            "bc1qlxvp7agw998t68qm76ek6t20gh320yrs8lhw7j",
+            MESSAGE,
+            &trezor
+        ));
+        bytes[0] = 43;
+        assert!(ecdsa
+            .recover_bitcoin_message_key(MESSAGE, &STANDARD.encode(&bytes))
+            .is_none());
+    }
+
+    #[test]
+    fn test_sign_bitcoin_message() {
+        let ecdsa = secp256k1();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        for (compressed, address) in [
+            (true, "1PkjVT2eq7sLQaad4sa3bsawdHdop5EPWj"),
+            (false, "13tvYqdQQkBCxjgxV1gmzLiWK4bCXo52iB"),
+        ] {
+            let signature = ecdsa
+                .sign_bitcoin_message(MESSAGE, &priv_key, compressed)
+                .unwrap();
+            assert_eq!(
+                ecdsa.recover_bitcoin_message_key(MESSAGE, &signature),
+                Some((pub_key.clone(), compressed))
+            );
+            assert!(ecdsa.verify_bitcoin_message(address, MESSAGE, &signature));
+            let bytes = STANDARD.decode(&signature).unwrap();
+            let signature = ecdsa.decode_signature(&bytes[1..]).unwrap();
+            assert!(signature.is_low_s(&ecdsa));
+        }
+        assert!(p256()
+            .sign_bitcoin_message(MESSAGE, &priv_key, true)
+            .is_none());
+    }
+}
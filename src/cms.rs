@@ -0,0 +1,565 @@
+// CMS SignedData (RFC 5652) with ECDSA signers, the container behind S/MIME,
+// code signing and most document signing services. `cms_sign` wraps content
+// of type id-data in a ContentInfo with one SignerInfo carrying the signed
+// attributes contentType, messageDigest, signingTime when given and, with a
+// certificate, signingCertificateV2 (RFC 5035), whose ESSCertIDv2 binds the
+// signature to that certificate. The content is either inside (attached) or
+// left out for the caller to keep beside the signature (detached). SHA-256 is
+// used on the 256-bit curves and SHA-384 on P-384.
+//
+// The signer is named by issuer and serial number when a certificate is
+// given, by a subject key identifier (the leftmost 160 bits of the SHA-256 of
+// the public key, RFC 7093) otherwise. The signature covers the DER of the
+// signed attributes as a SET, which DER orders by encoding.
+//
+// `cms_verify` accepts any signer that verifies under the given key, with or
+// without signed attributes. When the SignedData carries certificates and a
+// signingCertificateV2, the certificate it names must hold that key; building
+// a chain to a trusted root is up to the caller.
+
+use alloc::string::String;
+use alloc::{format, vec, vec::Vec};
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::der::{self, INTEGER, OCTET_STRING, OID, SEQUENCE};
+use crate::oid::{
+    self, HashAlgorithm, CONTENT_TYPE, DATA, MESSAGE_DIGEST, SIGNED_DATA, SIGNING_CERTIFICATE_V2,
+    SIGNING_TIME,
+};
+use crate::pem;
+use crate::signer::Signature;
+use crate::ECDSA;
+
+pub(crate) const SET: u8 = 0x31;
+const UTC_TIME: u8 = 0x17;
+const GENERALIZED_TIME: u8 = 0x18;
+const CONTEXT_0: u8 = 0xa0;
+const DIRECTORY_NAME: u8 = 0xa4;
+const SUBJECT_KEY_IDENTIFIER: u8 = 0x80;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CmsOptions<'a> {
+    // DER certificate of the signer, sent along and named in the signed
+    // attributes
+    pub certificate: Option<&'a [u8]>,
+    pub detached: bool,
+    // GeneralizedTime such as 20261015093000Z
+    pub signing_time: Option<&'a str>,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct CmsSignedData {
+    // Dotted OID of the encapsulated content type
+    pub content_type: String,
+    pub content: Vec<u8>,
+    // GeneralizedTime, converted from UTCTime where the signer used that
+    pub signing_time: Option<String>,
+    // DER of every certificate the SignedData carries
+    pub certificates: Vec<Vec<u8>>,
+}
+
+pub(crate) struct Certificate<'a> {
+    // DER of each field, tag and length included
+    pub issuer: &'a [u8],
+    pub serial: &'a [u8],
+    pub public_key_info: &'a [u8],
+}
+
+struct SignedData<'a> {
+    content_type: &'a [u8],
+    content: Option<&'a [u8]>,
+    certificates: Vec<&'a [u8]>,
+    signer_infos: &'a [u8],
+}
+
+impl ECDSA {
+    pub fn cms_sign(
+        &self,
+        content: &[u8],
+        priv_key: &BigUint,
+        options: &CmsOptions,
+    ) -> Option<Vec<u8>> {
+        // DER ContentInfo; None off the named curves, for a bad signing time
+        // or a certificate for some other key
+        self.named_curve()?;
+        let hash = self.cms_hash();
+        let pub_key = self.generate_pub_key(priv_key);
+        let certificate = match options.certificate {
+            Some(der) => {
+                let certificate = read_certificate(der)?;
+                if self.certificate_key(&certificate) != Some(pub_key.clone()) {
+                    return None;
+                }
+                Some((der, certificate))
+            }
+            None => None,
+        };
+
+        let mut attributes = vec![
+            attribute(CONTENT_TYPE, &der::write(OID, DATA)),
+            attribute(
+                MESSAGE_DIGEST,
+                &der::write(OCTET_STRING, &hash.digest(content)),
+            ),
+        ];
+        if let Some(time) = options.signing_time {
+            attributes.push(attribute(SIGNING_TIME, &write_time(time)?));
+        }
+        if let Some((der, certificate)) = &certificate {
+            attributes.push(attribute(
+                SIGNING_CERTIFICATE_V2,
+                &signing_certificate_v2(der, certificate),
+            ));
+        }
+        attributes.sort();
+        let attributes = attributes.concat();
+        let digest = hash.digest(&der::write(SET, &attributes));
+        let signature = self.sign_standard(&mut crate::rng(), &digest, priv_key);
+
+        let (version, sid) = match &certificate {
+            Some((_, certificate)) => (
+                1u32,
+                der::write(SEQUENCE, &[certificate.issuer, certificate.serial].concat()),
+            ),
+            None => {
+                let spki = self.public_key_to_der(&pub_key)?;
+                let (_, point) = pem::public_key_bytes_from_der(&spki)?;
+                (3, der::write(SUBJECT_KEY_IDENTIFIER, &sha256_160(point)))
+            }
+        };
+        let signer_info = der::write(
+            SEQUENCE,
+            &[
+                der::integer(&BigUint::from(version)),
+                sid,
+                oid::hash_algorithm(hash),
+                der::write(CONTEXT_0, &attributes),
+                oid::signature_algorithm(hash),
+                der::write(OCTET_STRING, &signature.to_der()),
+            ]
+            .concat(),
+        );
+        let mut encapsulated = der::write(OID, DATA);
+        if !options.detached {
+            encapsulated.extend(der::write(CONTEXT_0, &der::write(OCTET_STRING, content)));
+        }
+        let mut signed_data = der::integer(&BigUint::from(version));
+        signed_data.extend(der::write(SET, &oid::hash_algorithm(hash)));
+        signed_data.extend(der::write(SEQUENCE, &encapsulated));
+        if let Some((der, _)) = certificate {
+            signed_data.extend(der::write(CONTEXT_0, der));
+        }
+        signed_data.extend(der::write(SET, &signer_info));
+        Some(der::write(
+            SEQUENCE,
+            &[
+                der::write(OID, SIGNED_DATA),
+                der::write(CONTEXT_0, &der::write(SEQUENCE, &signed_data)),
+            ]
+            .concat(),
+        ))
+    }
+
+    pub fn cms_verify(
+        &self,
+        cms: &[u8],
+        detached_content: Option<&[u8]>,
+        pub_key: &Point,
+    ) -> Option<CmsSignedData> {
+        // The content, from inside or `detached_content`, which must be given
+        // exactly when the content is not inside
+        let signed_data = read_signed_data(cms)?;
+        let content = match (signed_data.content, detached_content) {
+            (Some(content), None) | (None, Some(content)) => content,
+            _ => return None,
+        };
+        let mut signer_infos = signed_data.signer_infos;
+        while !signer_infos.is_empty() {
+            let (signer_info, rest) = der::read_tag(signer_infos, SEQUENCE)?;
+            if let Some(attributes) =
+                self.verify_signer_info(signer_info, &signed_data, content, pub_key)
+            {
+                let signing_time = attribute_value(attributes, SIGNING_TIME).and_then(read_time);
+                return Some(CmsSignedData {
+                    content_type: der::oid_to_string(signed_data.content_type)?,
+                    content: content.to_vec(),
+                    signing_time,
+                    certificates: signed_data
+                        .certificates
+                        .iter()
+                        .map(|c| c.to_vec())
+                        .collect(),
+                });
+            }
+            signer_infos = rest;
+        }
+        None
+    }
+
+    fn verify_signer_info<'a>(
+        &self,
+        signer_info: &'a [u8],
+        signed_data: &SignedData,
+        content: &[u8],
+        pub_key: &Point,
+    ) -> Option<&'a [u8]> {
+        // SignerInfo: version, sid, digestAlgorithm, [0] signedAttrs,
+        // signatureAlgorithm, signature, [1] unsignedAttrs; the signed
+        // attributes (empty when there are none) if the signature checks out
+        let (_version, rest) = der::read_integer(signer_info)?;
+        let (_sid, rest) = element(rest)?;
+        let (hash, rest) = oid::read_hash_algorithm(rest)?;
+        let (attributes, rest) = match der::read_tag(rest, CONTEXT_0) {
+            Some((attributes, rest)) => (Some(attributes), rest),
+            None => (None, rest),
+        };
+        let (signature_hash, rest) = oid::read_signature_algorithm(rest)?;
+        let (signature, _) = der::read_tag(rest, OCTET_STRING)?;
+        if signature_hash != hash {
+            return None;
+        }
+
+        let digest = match attributes {
+            Some(attributes) => {
+                let names_key = |value: &[u8]| {
+                    signed_data.certificates.is_empty()
+                        || signed_data.certificates.iter().any(|certificate| {
+                            names_certificate(value, certificate)
+                                && read_certificate(certificate)
+                                    .and_then(|certificate| self.certificate_key(&certificate))
+                                    .as_ref()
+                                    == Some(pub_key)
+                        })
+                };
+                if attribute_value(attributes, CONTENT_TYPE)?
+                    != der::write(OID, signed_data.content_type)
+                    || attribute_value(attributes, MESSAGE_DIGEST)?
+                        != der::write(OCTET_STRING, &hash.digest(content))
+                    || !attribute_value(attributes, SIGNING_CERTIFICATE_V2).is_none_or(names_key)
+                {
+                    return None;
+                }
+                hash.digest(&der::write(SET, attributes))
+            }
+            // Without attributes the content itself is signed, which is only
+            // allowed for id-data
+            None if signed_data.content_type == DATA => hash.digest(content),
+            None => return None,
+        };
+        let signature = Signature::from_der(signature)?;
+        self.verify_standard(&digest, pub_key, &signature)
+            .then_some(attributes.unwrap_or_default())
+    }
+
+    fn cms_hash(&self) -> HashAlgorithm {
+        if self.is_p384() {
+            HashAlgorithm::Sha384
+        } else {
+            HashAlgorithm::Sha256
+        }
+    }
+
+    fn certificate_key(&self, certificate: &Certificate) -> Option<Point> {
+        // The certificate's public key, if it is on this curve
+        let (ecdsa, pub_key) = pem::public_key_from_der(certificate.public_key_info)?;
+        ecdsa.same_parameters(self).then_some(pub_key)
+    }
+}
+
+pub(crate) fn read_certificate(certificate: &[u8]) -> Option<Certificate<'_>> {
+    // Certificate { tbsCertificate { [0] version, serialNumber, signature,
+    // issuer, validity, subject, subjectPublicKeyInfo, ... }, ... }
+    let (certificate, rest) = der::read_tag(certificate, SEQUENCE)?;
+    if !rest.is_empty() {
+        return None;
+    }
+    let (mut tbs, _) = der::read_tag(certificate, SEQUENCE)?;
+    if tbs.first() == Some(&CONTEXT_0) {
+        tbs = element(tbs)?.1;
+    }
+    let (serial, rest) = element(tbs)?;
+    let (_signature, rest) = element(rest)?;
+    let (issuer, rest) = element(rest)?;
+    let (_validity, rest) = element(rest)?;
+    let (_subject, rest) = element(rest)?;
+    let (public_key_info, _) = element(rest)?;
+    (serial[0] == INTEGER && issuer[0] == SEQUENCE && public_key_info[0] == SEQUENCE).then_some(
+        Certificate {
+            issuer,
+            serial,
+            public_key_info,
+        },
+    )
+}
+
+pub(crate) fn attribute_value<'a>(mut attributes: &'a [u8], oid: &[u8]) -> Option<&'a [u8]> {
+    // The DER of the single value of the attribute with this type
+    while !attributes.is_empty() {
+        let (attribute, rest) = der::read_tag(attributes, SEQUENCE)?;
+        let (attribute_type, values) = der::read_tag(attribute, OID)?;
+        if attribute_type == oid {
+            let (values, _) = der::read_tag(values, SET)?;
+            return Some(values);
+        }
+        attributes = rest;
+    }
+    None
+}
+
+fn read_signed_data(cms: &[u8]) -> Option<SignedData<'_>> {
+    // ContentInfo { signedData, [0] SignedData { version, digestAlgorithms,
+    // encapContentInfo, [0] certificates, [1] crls, signerInfos } }
+    let (content_info, rest) = der::read_tag(cms, SEQUENCE)?;
+    let (content_type, content) = der::read_tag(content_info, OID)?;
+    let (content, _) = der::read_tag(content, CONTEXT_0)?;
+    let (signed_data, _) = der::read_tag(content, SEQUENCE)?;
+    if !rest.is_empty() || content_type != SIGNED_DATA {
+        return None;
+    }
+    let (_version, rest) = der::read_integer(signed_data)?;
+    let (_digest_algorithms, rest) = der::read_tag(rest, SET)?;
+    let (encapsulated, mut rest) = der::read_tag(rest, SEQUENCE)?;
+    let (e_content_type, e_content) = der::read_tag(encapsulated, OID)?;
+    let e_content = match der::read_tag(e_content, CONTEXT_0) {
+        Some((e_content, _)) => Some(der::read_tag(e_content, OCTET_STRING)?.0),
+        None => None,
+    };
+
+    let mut certificates = Vec::new();
+    if let Some((mut choices, after)) = der::read_tag(rest, CONTEXT_0) {
+        // Only plain certificates; attribute certificates and others are
+        // skipped
+        while !choices.is_empty() {
+            let (choice, others) = element(choices)?;
+            if choice[0] == SEQUENCE {
+                certificates.push(choice);
+            }
+            choices = others;
+        }
+        rest = after;
+    }
+    if let Some((_crls, after)) = der::read_tag(rest, 0xa1) {
+        rest = after;
+    }
+    let (signer_infos, _) = der::read_tag(rest, SET)?;
+    Some(SignedData {
+        content_type: e_content_type,
+        content: e_content,
+        certificates,
+        signer_infos,
+    })
+}
+
+fn attribute(oid: &[u8], value: &[u8]) -> Vec<u8> {
+    der::write(
+        SEQUENCE,
+        &[der::write(OID, oid), der::write(SET, value)].concat(),
+    )
+}
+
+fn signing_certificate_v2(der: &[u8], certificate: &Certificate) -> Vec<u8> {
+    // SigningCertificateV2 { certs { ESSCertIDv2 { certHash, issuerSerial {
+    // GeneralNames { [4] issuer }, serialNumber } } } }, hashAlgorithm left
+    // at its default, SHA-256
+    let general_names = der::write(SEQUENCE, &der::write(DIRECTORY_NAME, certificate.issuer));
+    let issuer_serial = der::write(
+        SEQUENCE,
+        &[general_names, certificate.serial.to_vec()].concat(),
+    );
+    let ess_cert_id = der::write(
+        SEQUENCE,
+        &[
+            der::write(OCTET_STRING, &HashAlgorithm::Sha256.digest(der)),
+            issuer_serial,
+        ]
+        .concat(),
+    );
+    der::write(SEQUENCE, &der::write(SEQUENCE, &ess_cert_id))
+}
+
+fn names_certificate(signing_certificate: &[u8], certificate: &[u8]) -> bool {
+    // Whether the first ESSCertIDv2 has this certificate's hash
+    let ess_cert_id = der::read_tag(signing_certificate, SEQUENCE)
+        .and_then(|(certs, _)| der::read_tag(certs, SEQUENCE))
+        .and_then(|(certs, _)| der::read_tag(certs, SEQUENCE));
+    let Some((ess_cert_id, _)) = ess_cert_id else {
+        return false;
+    };
+    let (hash, rest) = match ess_cert_id.first() {
+        Some(&SEQUENCE) => match oid::read_hash_algorithm(ess_cert_id) {
+            Some(read) => read,
+            None => return false,
+        },
+        _ => (HashAlgorithm::Sha256, ess_cert_id),
+    };
+    der::read_tag(rest, OCTET_STRING)
+        .is_some_and(|(cert_hash, _)| cert_hash == hash.digest(certificate))
+}
+
+fn write_time(time: &str) -> Option<Vec<u8>> {
+    // UTCTime for 1950 to 2049 as RFC 5652 requires, GeneralizedTime
+    // otherwise
+    let valid =
+        time.len() == 15 && time.ends_with('Z') && time[..14].bytes().all(|b| b.is_ascii_digit());
+    if !valid {
+        return None;
+    }
+    let year: u32 = time[..4].parse().ok()?;
+    Some(if (1950..2050).contains(&year) {
+        der::write(UTC_TIME, &time.as_bytes()[2..])
+    } else {
+        der::write(GENERALIZED_TIME, time.as_bytes())
+    })
+}
+
+fn read_time(value: &[u8]) -> Option<String> {
+    let (tag, time, _) = der::read(value)?;
+    let time = core::str::from_utf8(time).ok()?;
+    match tag {
+        UTC_TIME if time.len() == 13 => {
+            let century = if time < "50" { "20" } else { "19" };
+            Some(format!("{}{}", century, time))
+        }
+        GENERALIZED_TIME => Some(time.into()),
+        _ => None,
+    }
+}
+
+fn element(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    // The next element with its tag and length, and what follows
+    let (_, _, rest) = der::read(bytes)?;
+    Some((&bytes[..bytes.len() - rest.len()], rest))
+}
+
+fn sha256_160(data: &[u8]) -> Vec<u8> {
+    HashAlgorithm::Sha256.digest(data)[..20].to_vec()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::{p256, p384};
+
+    // `openssl cms -sign -binary -md sha256 -cades` of the content, with and
+    // without -nodetach, by the key of a self-signed P-256 certificate
+    const CONTENT: &[u8] = include_bytes!("../testdata/cms_content.txt");
+    const ATTACHED: &[u8] = include_bytes!("../testdata/cms_attached.p7s");
+    const DETACHED: &[u8] = include_bytes!("../testdata/cms_detached.p7s");
+    const CERTIFICATE: &[u8] = include_bytes!("../testdata/cms_signer.der");
+    const KEY: &str = include_str!("../testdata/cms_signer_key.pem");
+
+    #[test]
+    fn test_openssl_signed_data() {
+        let ecdsa = p256();
+        let pub_key = ecdsa
+            .certificate_key(&read_certificate(CERTIFICATE).unwrap())
+            .unwrap();
+
+        let attached = ecdsa.cms_verify(ATTACHED, None, &pub_key).unwrap();
+        assert_eq!(attached.content, CONTENT);
+        assert_eq!(attached.content_type, "1.2.840.113549.1.7.1");
+        assert_eq!(attached.certificates, vec![CERTIFICATE.to_vec()]);
+        assert!(attached.signing_time.unwrap().starts_with("2026"));
+        assert!(ecdsa
+            .cms_verify(ATTACHED, Some(CONTENT), &pub_key)
+            .is_none());
+
+        let detached = ecdsa.cms_verify(DETACHED, Some(CONTENT), &pub_key).unwrap();
+        assert_eq!(detached.content, CONTENT);
+        assert!(ecdsa.cms_verify(DETACHED, None, &pub_key).is_none());
+        assert!(ecdsa
+            .cms_verify(DETACHED, Some(b"other"), &pub_key)
+            .is_none());
+        let other_key = ecdsa.generate_pub_key(&BigUint::from(7u32));
+        assert!(ecdsa
+            .cms_verify(DETACHED, Some(CONTENT), &other_key)
+            .is_none());
+    }
+
+    #[test]
+    fn test_cms_sign_verify() {
+        let ecdsa = p256();
+        let (_, priv_key) = pem::private_key_from_pem(KEY).unwrap();
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let options = CmsOptions {
+            certificate: Some(CERTIFICATE),
+            detached: false,
+            signing_time: Some("20261015093000Z"),
+        };
+        let cms = ecdsa.cms_sign(b"contract", &priv_key, &options).unwrap();
+        let signed = ecdsa.cms_verify(&cms, None, &pub_key).unwrap();
+        assert_eq!(signed.content, b"contract");
+        assert_eq!(signed.signing_time.as_deref(), Some("20261015093000Z"));
+        assert_eq!(signed.certificates, vec![CERTIFICATE.to_vec()]);
+
+        // Detached, without a certificate and after 2049, when signingTime
+        // is a GeneralizedTime
+        let options = CmsOptions {
+            detached: true,
+            signing_time: Some("20500101000000Z"),
+            ..Default::default()
+        };
+        let cms = ecdsa.cms_sign(b"contract", &priv_key, &options).unwrap();
+        let signed = ecdsa.cms_verify(&cms, Some(b"contract"), &pub_key).unwrap();
+        assert_eq!(signed.signing_time.as_deref(), Some("20500101000000Z"));
+        assert!(signed.certificates.is_empty());
+        assert!(ecdsa.cms_verify(&cms, None, &pub_key).is_none());
+
+        // Every byte of the signed attributes counts
+        let cms = ecdsa.cms_sign(b"contract", &priv_key, &options).unwrap();
+        let position = cms.windows(2).position(|w| w == [0x18, 0x0f]).unwrap();
+        let mut tampered = cms.clone();
+        tampered[position + 5] ^= 1;
+        assert!(ecdsa
+            .cms_verify(&tampered, Some(b"contract"), &pub_key)
+            .is_none());
+
+        // A certificate for another key, and a bad time
+        let other = BigUint::from(7u32);
+        let with_certificate = CmsOptions {
+            certificate: Some(CERTIFICATE),
+            ..Default::default()
+        };
+        assert!(ecdsa.cms_sign(b"x", &other, &with_certificate).is_none());
+        let bad_time = CmsOptions {
+            signing_time: Some("2026-10-15"),
+            ..Default::default()
+        };
+        assert!(ecdsa.cms_sign(b"x", &priv_key, &bad_time).is_none());
+    }
+
+    #[test]
+    fn test_cms_p384() {
+        let ecdsa = p384();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let cms = ecdsa
+            .cms_sign(b"contract", &priv_key, &CmsOptions::default())
+            .unwrap();
+        assert!(cms
+            .windows(oid::SHA384.len())
+            .any(|window| window == oid::SHA384));
+        assert!(ecdsa.cms_verify(&cms, None, &pub_key).is_some());
+    }
+
+    #[test]
+    fn test_signing_certificate_binding() {
+        // A SignedData whose signingCertificateV2 names a certificate that is
+        // not among those it carries
+        let ecdsa = p256();
+        let (_, priv_key) = pem::private_key_from_pem(KEY).unwrap();
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let options = CmsOptions {
+            certificate: Some(CERTIFICATE),
+            ..Default::default()
+        };
+        let cms = ecdsa.cms_sign(b"contract", &priv_key, &options).unwrap();
+        let hash = HashAlgorithm::Sha256.digest(CERTIFICATE);
+        let position = cms.windows(32).position(|w| w == hash).unwrap();
+        let mut renamed = cms.clone();
+        renamed[position] ^= 1;
+        assert!(ecdsa.cms_verify(&renamed, None, &pub_key).is_none());
+    }
+}
@@ -0,0 +1,230 @@
+// Precomputed signing context, in the style of libsecp256k1 contexts. Creating
+// one does the expensive setup once: a fixed 4-bit window table of multiples
+// of the generator, the Barrett constant for the group order and the curve
+// invariants used by every signature. The data sits behind an `Arc`, so a
+// clone is a reference count bump and one context can be shared by any number
+// of threads.
+//
+// `generate_key_pairs` provisions keys in bulk: the private keys come from the
+// caller's rng in order, so a seeded rng gives the same fixtures every run,
+// the public keys are read from the generator table (in parallel on the rayon
+// pool with the `parallel` feature) and all of them are brought back to affine
+// coordinates with a single shared inversion.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use ec_generic::Point;
+use num_bigint::{BigUint, RandBigInt};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use signature::rand_core::CryptoRngCore;
+
+use crate::arithmetic::Barrett;
+use crate::jacobian::JacobianPoint;
+use crate::precompute::PointTable;
+use crate::secret::invert_secret;
+use crate::ECDSA;
+
+#[derive(Clone)]
+pub struct SigningContext {
+    inner: Arc<ContextData>,
+}
+
+struct ContextData {
+    ecdsa: ECDSA,
+    generator_table: PointTable,
+    q_barrett: Barrett,
+    q_minus_two: BigUint,
+    field_len: usize,
+    scalar_len: usize,
+}
+
+impl SigningContext {
+    pub fn new(ecdsa: ECDSA) -> SigningContext {
+        let generator_table = ecdsa.point_table(&ecdsa.a_gen);
+        let data = ContextData {
+            q_barrett: Barrett::new(&ecdsa.q_order),
+            q_minus_two: &ecdsa.q_order - BigUint::from(2u32),
+            field_len: ecdsa.field_len(),
+            scalar_len: ecdsa.scalar_len(),
+            generator_table,
+            ecdsa,
+        };
+        SigningContext {
+            inner: Arc::new(data),
+        }
+    }
+
+    pub fn ecdsa(&self) -> &ECDSA {
+        &self.inner.ecdsa
+    }
+
+    pub fn field_len(&self) -> usize {
+        self.inner.field_len
+    }
+
+    pub fn scalar_len(&self) -> usize {
+        self.inner.scalar_len
+    }
+
+    pub fn mul_generator(&self, k: &BigUint) -> Point {
+        // One table lookup and addition per window, no doublings
+        self.inner.ecdsa.mul_table(&self.inner.generator_table, k)
+    }
+
+    pub fn generate_pub_key(&self, priv_key: &BigUint) -> Point {
+        assert!(
+            *priv_key != BigUint::from(0u32) && priv_key < &self.inner.ecdsa.q_order,
+            "Private Key must be in [1, q - 1]"
+        );
+        self.mul_generator(priv_key)
+    }
+
+    pub fn generate_key_pairs(
+        &self,
+        count: usize,
+        rng: &mut impl CryptoRngCore,
+    ) -> Vec<(BigUint, Point)> {
+        let ecdsa = &self.inner.ecdsa;
+        let priv_keys: Vec<BigUint> = (0..count)
+            .map(|_| rng.gen_biguint_range(&BigUint::from(1u32), &ecdsa.q_order))
+            .collect();
+        let pub_keys = ecdsa.batch_to_affine(&self.jacobian_pub_keys(&priv_keys));
+        priv_keys.into_iter().zip(pub_keys).collect()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    fn jacobian_pub_keys(&self, priv_keys: &[BigUint]) -> Vec<JacobianPoint> {
+        let data = &*self.inner;
+        priv_keys
+            .iter()
+            .map(|d| data.ecdsa.mul_table_jacobian(&data.generator_table, d))
+            .collect()
+    }
+
+    #[cfg(feature = "parallel")]
+    fn jacobian_pub_keys(&self, priv_keys: &[BigUint]) -> Vec<JacobianPoint> {
+        let data = &*self.inner;
+        priv_keys
+            .par_iter()
+            .map(|d| data.ecdsa.mul_table_jacobian(&data.generator_table, d))
+            .collect()
+    }
+
+    pub fn sign(
+        &self,
+        hash: &BigUint,
+        priv_key: &BigUint,
+        k_random: &BigUint,
+    ) -> Option<(BigUint, BigUint)> {
+        // Same equations as `ECDSA::sign`:
+        // R = kA -> r = x(R), s = (hash + d * r) * k⁻¹ mod q
+        let data = &*self.inner;
+        let q = &data.ecdsa.q_order;
+        if hash >= q || priv_key >= q || *k_random == BigUint::from(0u32) || k_random >= q {
+            return None;
+        }
+        let r = match self.mul_generator(k_random) {
+            Point::Coor(x, _) => x,
+            Point::Identity => return None,
+        };
+        let k_inv = invert_secret(k_random, q)?;
+        let s = data.q_barrett.mul(&(&r % q), priv_key) + hash;
+        let s = data.q_barrett.mul(&(s % q), &k_inv);
+        Some((r, s))
+    }
+
+    pub fn verify(&self, hash: &BigUint, pub_key: &Point, signature: &(BigUint, BigUint)) -> bool {
+        // P = u1 A + u2 B with u1 = hash * s⁻¹, u2 = r * s⁻¹, accept if x(P) = r
+        let data = &*self.inner;
+        let (r, s) = signature;
+        let q = &data.ecdsa.q_order;
+        let zero = BigUint::from(0u32);
+        if hash >= q
+            || !data.ecdsa.is_valid_pub_key(pub_key)
+            || r % q == zero
+            || *s == zero
+            || s >= q
+        {
+            return false;
+        }
+
+        let s_inv = s.modpow(&data.q_minus_two, q);
+        let u1 = data.q_barrett.mul(hash, &s_inv);
+        let u2 = data.q_barrett.mul(&(r % q), &s_inv);
+        let p = data.ecdsa.add_points(
+            &self.mul_generator(&u1),
+            &data.ecdsa.mul_vartime(pub_key, &u2),
+        );
+        matches!(p, Point::Coor(x, _) if x == *r)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_context_mul_generator() {
+        let context = SigningContext::new(toy_curve());
+        for k in 0u32..19 {
+            let k = BigUint::from(k);
+            assert_eq!(
+                context.mul_generator(&k),
+                context.ecdsa().mul_vartime(&context.ecdsa().a_gen, &k)
+            );
+        }
+    }
+
+    #[test]
+    fn test_context_sign_verify() {
+        let context = SigningContext::new(toy_curve());
+        let priv_key = BigUint::from(7u32);
+        let pub_key = context.generate_pub_key(&priv_key);
+        let hash = BigUint::from(10u32);
+        let k_random = BigUint::from(18u32);
+
+        let signature = context.sign(&hash, &priv_key, &k_random).unwrap();
+        assert_eq!(signature, context.ecdsa().sign(&hash, &priv_key, &k_random));
+        assert!(context.verify(&hash, &pub_key, &signature));
+        assert!(!context.verify(&BigUint::from(11u32), &pub_key, &signature));
+    }
+
+    #[test]
+    fn test_context_generate_key_pairs() {
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let context = SigningContext::new(toy_curve());
+        let key_pairs = context.generate_key_pairs(40, &mut StdRng::seed_from_u64(7));
+        assert_eq!(key_pairs.len(), 40);
+        for (priv_key, pub_key) in &key_pairs {
+            assert_eq!(*pub_key, context.ecdsa().generate_pub_key(priv_key));
+        }
+        let again = context.generate_key_pairs(40, &mut StdRng::seed_from_u64(7));
+        assert_eq!(again, key_pairs);
+        assert!(context
+            .generate_key_pairs(0, &mut StdRng::seed_from_u64(7))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_context_shared_across_threads() {
+        let context = SigningContext::new(toy_curve());
+        let priv_key = BigUint::from(7u32);
+        let pub_key = context.generate_pub_key(&priv_key);
+        let hash = BigUint::from(10u32);
+
+        let worker = context.clone();
+        let signature = std::thread::spawn(move || {
+            worker
+                .sign(&hash, &priv_key, &BigUint::from(18u32))
+                .unwrap()
+        })
+        .join()
+        .unwrap();
+        assert!(context.verify(&BigUint::from(10u32), &pub_key, &signature));
+    }
+}
@@ -0,0 +1,260 @@
+// `TryFrom` conversions from the forms keys and signatures arrive in, so code
+// gluing this crate to another API can write `VerifyingKey::try_from((&curve,
+// text))` whatever the text holds. Keys need their curve, hence the
+// `(&ECDSA, input)` pairs; an input naming its own curve (PEM, DER) must name
+// that one.
+//
+//   signatures      DER bytes; text is hex or base64 of DER
+//   verifying keys  SEC1 point, x || y or SubjectPublicKeyInfo DER; text is
+//                   PEM or hex of any of those
+//   signing keys    scalar_len bytes of big-endian scalar, or SEC1 or PKCS#8
+//                   DER; text is PEM or hex of any of those
+//
+// Compact r || s signatures depend on the curve's sizes, so they go through
+// `der_to_compact` and `compact_to_der` rather than a guess on the length.
+
+use alloc::vec::Vec;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use num_bigint::BigUint;
+use signature::Error;
+
+use crate::keypair::KeyPair;
+use crate::pem;
+use crate::signer::{DerSignatureRef, Signature, SigningKey, VerifyingKey};
+use crate::ECDSA;
+
+impl ECDSA {
+    pub fn der_to_compact(&self, der: &[u8]) -> Option<Vec<u8>> {
+        // None when r or s does not fit its half
+        let signature = Signature::from_der(der)?;
+        let fits = signature.r().bits() <= 8 * self.field_len() as u64
+            && signature.s().bits() <= 8 * self.scalar_len() as u64;
+        fits.then(|| self.encode_signature(&signature))
+    }
+
+    pub fn compact_to_der(&self, compact: &[u8]) -> Option<Vec<u8>> {
+        Some(self.decode_signature(compact)?.to_der())
+    }
+}
+
+impl TryFrom<&str> for Signature {
+    type Error = Error;
+
+    fn try_from(text: &str) -> Result<Signature, Error> {
+        let text = text.trim();
+        let der = hex::decode(text)
+            .or_else(|_| STANDARD.decode(text))
+            .map_err(|_| Error::new())?;
+        Signature::try_from(der.as_slice())
+    }
+}
+
+impl<'a> From<DerSignatureRef<'a>> for Signature {
+    fn from(der: DerSignatureRef<'a>) -> Signature {
+        der.to_signature()
+    }
+}
+
+impl<'a> TryFrom<(&'a ECDSA, &[u8])> for VerifyingKey<'a> {
+    type Error = Error;
+
+    fn try_from((ecdsa, bytes): (&'a ECDSA, &[u8])) -> Result<VerifyingKey<'a>, Error> {
+        // x || y may start with any byte, SEC1 points start with 0x02 to 0x04
+        let key = match bytes.first() {
+            _ if bytes.len() == 2 * ecdsa.field_len() => VerifyingKey::from_xy_bytes(ecdsa, bytes),
+            Some(&0x30) => pem::public_key_from_der(bytes)
+                .filter(|(parsed, _)| parsed.same_parameters(ecdsa))
+                .and_then(|(_, pub_key)| VerifyingKey::new(ecdsa, pub_key)),
+            _ => VerifyingKey::from_bytes(ecdsa, bytes),
+        };
+        key.ok_or_else(Error::new)
+    }
+}
+
+impl<'a> TryFrom<(&'a ECDSA, &str)> for VerifyingKey<'a> {
+    type Error = Error;
+
+    fn try_from((ecdsa, text): (&'a ECDSA, &str)) -> Result<VerifyingKey<'a>, Error> {
+        if is_pem(text) {
+            let (parsed, pub_key) = pem::public_key_from_pem(text).ok_or_else(Error::new)?;
+            if !parsed.same_parameters(ecdsa) {
+                return Err(Error::new());
+            }
+            return VerifyingKey::new(ecdsa, pub_key).ok_or_else(Error::new);
+        }
+        VerifyingKey::try_from((ecdsa, decode_hex(text)?.as_slice()))
+    }
+}
+
+impl<'a> TryFrom<(&'a ECDSA, &[u8])> for SigningKey<'a> {
+    type Error = Error;
+
+    fn try_from((ecdsa, bytes): (&'a ECDSA, &[u8])) -> Result<SigningKey<'a>, Error> {
+        // No DER private key is as short as a raw scalar
+        if bytes.len() == ecdsa.scalar_len() {
+            return SigningKey::from_bytes(ecdsa, bytes).ok_or_else(Error::new);
+        }
+        let parsed =
+            pem::private_key_from_der(bytes).or_else(|| pem::pkcs8_private_key_from_der(bytes));
+        signing_key(ecdsa, parsed)
+    }
+}
+
+impl<'a> TryFrom<(&'a ECDSA, &str)> for SigningKey<'a> {
+    type Error = Error;
+
+    fn try_from((ecdsa, text): (&'a ECDSA, &str)) -> Result<SigningKey<'a>, Error> {
+        if is_pem(text) {
+            return signing_key(ecdsa, pem::private_key_from_pem(text));
+        }
+        SigningKey::try_from((ecdsa, decode_hex(text)?.as_slice()))
+    }
+}
+
+impl<'a> TryFrom<(&'a ECDSA, &[u8])> for KeyPair<'a> {
+    type Error = Error;
+
+    fn try_from(input: (&'a ECDSA, &[u8])) -> Result<KeyPair<'a>, Error> {
+        Ok(SigningKey::try_from(input)?.into())
+    }
+}
+
+impl<'a> TryFrom<(&'a ECDSA, &str)> for KeyPair<'a> {
+    type Error = Error;
+
+    fn try_from(input: (&'a ECDSA, &str)) -> Result<KeyPair<'a>, Error> {
+        Ok(SigningKey::try_from(input)?.into())
+    }
+}
+
+impl<'a> From<SigningKey<'a>> for KeyPair<'a> {
+    fn from(signing_key: SigningKey<'a>) -> KeyPair<'a> {
+        let ecdsa = signing_key.verifying_key().ecdsa();
+        let priv_key = BigUint::from_bytes_be(&signing_key.to_bytes());
+        KeyPair::from_private(ecdsa, priv_key).unwrap()
+    }
+}
+
+impl<'a> From<&KeyPair<'a>> for SigningKey<'a> {
+    fn from(key_pair: &KeyPair<'a>) -> SigningKey<'a> {
+        key_pair.signing_key()
+    }
+}
+
+impl<'a> From<&KeyPair<'a>> for VerifyingKey<'a> {
+    fn from(key_pair: &KeyPair<'a>) -> VerifyingKey<'a> {
+        key_pair.verifying_key()
+    }
+}
+
+impl<'a> From<&SigningKey<'a>> for VerifyingKey<'a> {
+    fn from(signing_key: &SigningKey<'a>) -> VerifyingKey<'a> {
+        signing_key.verifying_key()
+    }
+}
+
+fn signing_key(ecdsa: &ECDSA, parsed: Option<(ECDSA, BigUint)>) -> Result<SigningKey<'_>, Error> {
+    // A key read from DER or PEM, if it is on `ecdsa` and in [1, q - 1]
+    match parsed {
+        Some((parsed, priv_key))
+            if parsed.same_parameters(ecdsa)
+                && priv_key != BigUint::from(0u32)
+                && priv_key < ecdsa.q_order =>
+        {
+            Ok(SigningKey::new(ecdsa, priv_key))
+        }
+        _ => Err(Error::new()),
+    }
+}
+
+fn is_pem(text: &str) -> bool {
+    text.contains("-----BEGIN ")
+}
+
+fn decode_hex(text: &str) -> Result<Vec<u8>, Error> {
+    // Surrounding whitespace and a 0x prefix are allowed
+    let text = text.trim();
+    let text = text.strip_prefix("0x").unwrap_or(text);
+    hex::decode(text).map_err(|_| Error::new())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::{p256, secp256k1};
+
+    #[test]
+    fn test_key_conversions() {
+        let ecdsa = p256();
+        let key_pair = KeyPair::generate(&ecdsa);
+        let verifying_key = VerifyingKey::from(&key_pair);
+        let expected = verifying_key.to_bytes();
+        let pem = ecdsa.public_key_to_pem(key_pair.public()).unwrap();
+        let der = ecdsa.public_key_to_der(key_pair.public()).unwrap();
+        let inputs: [&[u8]; 4] = [
+            &expected,
+            &verifying_key.to_uncompressed_bytes(),
+            &verifying_key.to_xy_bytes(),
+            &der,
+        ];
+        for bytes in inputs {
+            let key = VerifyingKey::try_from((&ecdsa, bytes)).unwrap();
+            assert_eq!(key.to_bytes(), expected);
+            let text = hex::encode(bytes);
+            let key = VerifyingKey::try_from((&ecdsa, text.as_str())).unwrap();
+            assert_eq!(key.to_bytes(), expected);
+        }
+        let key = VerifyingKey::try_from((&ecdsa, pem.as_str())).unwrap();
+        assert_eq!(key.to_bytes(), expected);
+
+        let signing_key = SigningKey::from(&key_pair);
+        let scalar = signing_key.to_bytes();
+        let pem = ecdsa.private_key_to_pem(key_pair.private()).unwrap();
+        let der = ecdsa.private_key_to_der(key_pair.private()).unwrap();
+        for text in [hex::encode(&scalar), hex::encode(&der), pem] {
+            let loaded = KeyPair::try_from((&ecdsa, text.as_str())).unwrap();
+            assert_eq!(loaded.private(), key_pair.private());
+        }
+        assert_eq!(
+            VerifyingKey::from(&signing_key).to_bytes(),
+            verifying_key.to_bytes()
+        );
+
+        // The encodings that name a curve must name this one
+        let other = secp256k1();
+        assert!(VerifyingKey::try_from((&other, der.as_slice())).is_err());
+        assert!(SigningKey::try_from((&other, der.as_slice())).is_err());
+        assert!(VerifyingKey::try_from((&ecdsa, "not hex")).is_err());
+        assert!(SigningKey::try_from((&ecdsa, &[0u8; 32][..])).is_err());
+    }
+
+    #[test]
+    fn test_signature_conversions() {
+        let ecdsa = secp256k1();
+        let key_pair = KeyPair::generate(&ecdsa);
+        let signature = key_pair.sign(b"message");
+        let der = signature.to_der();
+
+        let text = hex::encode(&der);
+        assert_eq!(
+            Signature::try_from(text.as_str()).ok(),
+            Some(signature.clone())
+        );
+        let base64 = STANDARD.encode(&der);
+        assert_eq!(
+            Signature::try_from(base64.as_str()).ok(),
+            Some(signature.clone())
+        );
+        let view = DerSignatureRef::parse(&der).unwrap();
+        assert_eq!(Signature::from(view), signature);
+
+        let compact = ecdsa.der_to_compact(&der).unwrap();
+        assert_eq!(compact, ecdsa.encode_signature(&signature));
+        assert_eq!(ecdsa.compact_to_der(&compact).unwrap(), der);
+        assert!(ecdsa.compact_to_der(&compact[1..]).is_none());
+        let oversized = Signature::new(BigUint::from(1u32) << 256, BigUint::from(1u32));
+        assert!(ecdsa.der_to_compact(&oversized.to_der()).is_none());
+    }
+}
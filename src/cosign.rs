@@ -0,0 +1,252 @@
+// Sigstore cosign blob signatures, behind the `cosign` feature, so release
+// artifacts signed here pass `cosign verify-blob`. Cosign signs the SHA-256 of
+// the artifact with plain ECDSA on P-256 and writes the DER signature in
+// base64. `cosign_sign` returns that signature in the bundle layout of
+// `cosign sign-blob --bundle`:
+//
+//   {"base64Signature": ..., "cert": base64 PEM, "rekorBundle": {
+//       "SignedEntryTimestamp": base64 DER,
+//       "Payload": {"body", "integratedTime", "logIndex", "logID"}}}
+//
+// with the public key as "cert"; a certificate for the key (from Fulcio or a
+// private CA) may take its place. `rekor_entry` is the hashedrekord entry to
+// upload to a Rekor transparency log, and the log's reply fills in
+// "rekorBundle". `verify_rekor_bundle` checks the log's signed entry timestamp
+// and that the entry is for this artifact and signature.
+//
+// Only the key is read out of a certificate; checking its chain and identity
+// and deciding to trust the key, like trusting the log's key, is up to the
+// caller.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ec_generic::Point;
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::cms::read_certificate;
+use crate::hash::sha256;
+use crate::pem;
+use crate::signer::Signature;
+use crate::ECDSA;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CosignBundle {
+    #[serde(rename = "base64Signature")]
+    pub base64_signature: String,
+    // Base64 of a PEM public key or certificate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cert: Option<String>,
+    #[serde(
+        rename = "rekorBundle",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub rekor_bundle: Option<RekorBundle>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RekorBundle {
+    #[serde(rename = "SignedEntryTimestamp")]
+    pub signed_entry_timestamp: String,
+    #[serde(rename = "Payload")]
+    pub payload: RekorPayload,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RekorPayload {
+    // Base64 of the entry as the log stored it
+    pub body: String,
+    #[serde(rename = "integratedTime")]
+    pub integrated_time: i64,
+    #[serde(rename = "logIndex")]
+    pub log_index: i64,
+    // Hex SHA-256 of the log's DER public key
+    #[serde(rename = "logID")]
+    pub log_id: String,
+}
+
+impl RekorPayload {
+    fn canonical(&self) -> String {
+        // The JSON the log signs: keys sorted, no whitespace
+        format!(
+            "{{\"body\":{},\"integratedTime\":{},\"logID\":{},\"logIndex\":{}}}",
+            serde_json::Value::from(self.body.as_str()),
+            self.integrated_time,
+            serde_json::Value::from(self.log_id.as_str()),
+            self.log_index
+        )
+    }
+}
+
+impl ECDSA {
+    pub fn cosign_sign(&self, artifact: &[u8], priv_key: &BigUint) -> Option<CosignBundle> {
+        // None off P-256
+        if !self.is_p256() {
+            return None;
+        }
+        let signature = self.sign_standard(&mut crate::rng(), &sha256(artifact), priv_key);
+        let pub_key = self.generate_pub_key(priv_key);
+        Some(CosignBundle {
+            base64_signature: STANDARD.encode(signature.to_der()),
+            cert: Some(STANDARD.encode(self.public_key_to_pem(&pub_key)?)),
+            rekor_bundle: None,
+        })
+    }
+
+    pub fn cosign_verify(&self, artifact: &[u8], base64_signature: &str, pub_key: &Point) -> bool {
+        // What `cosign verify-blob --key` checks
+        let signature = STANDARD
+            .decode(base64_signature.trim())
+            .ok()
+            .and_then(|der| Signature::from_der(&der));
+        match signature {
+            Some(signature) if self.is_p256() => {
+                self.verify_standard(&sha256(artifact), pub_key, &signature)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn cosign_bundle_key(&self, bundle: &CosignBundle) -> Option<Point> {
+        // The key in "cert", a PEM public key or the subject key of a PEM
+        // certificate, if it is on this curve
+        let text = String::from_utf8(STANDARD.decode(bundle.cert.as_ref()?).ok()?).ok()?;
+        let (label, der) = pem::pem_decode(&text)?;
+        let spki = match label.as_str() {
+            "PUBLIC KEY" => der.as_slice(),
+            "CERTIFICATE" => read_certificate(&der)?.public_key_info,
+            _ => return None,
+        };
+        let (ecdsa, pub_key) = pem::public_key_from_der(spki)?;
+        ecdsa.same_parameters(self).then_some(pub_key)
+    }
+
+    pub fn verify_rekor_bundle(
+        &self,
+        artifact: &[u8],
+        bundle: &CosignBundle,
+        log_key: &Point,
+    ) -> bool {
+        // The log's signature over the payload, its ID, and a hashedrekord
+        // body naming this artifact, signature and key
+        let Some(rekor) = &bundle.rekor_bundle else {
+            return false;
+        };
+        let log_id = self
+            .public_key_to_der(log_key)
+            .map(|der| hex::encode(sha256(&der)));
+        let timestamp = STANDARD
+            .decode(&rekor.signed_entry_timestamp)
+            .ok()
+            .and_then(|der| Signature::from_der(&der));
+        let signed = timestamp.is_some_and(|timestamp| {
+            let digest = sha256(rekor.payload.canonical().as_bytes());
+            self.verify_standard(&digest, log_key, &timestamp)
+        });
+        if !signed || log_id.as_deref() != Some(rekor.payload.log_id.as_str()) {
+            return false;
+        }
+
+        let body = STANDARD
+            .decode(&rekor.payload.body)
+            .ok()
+            .and_then(|body| serde_json::from_slice::<serde_json::Value>(&body).ok());
+        let Some(body) = body else {
+            return false;
+        };
+        let spec = &body["spec"];
+        let public_key = spec["signature"]["publicKey"]["content"].as_str();
+        body["kind"] == "hashedrekord"
+            && spec["data"]["hash"]["algorithm"] == "sha256"
+            && spec["data"]["hash"]["value"] == hex::encode(sha256(artifact))
+            && spec["signature"]["content"] == bundle.base64_signature.as_str()
+            && bundle
+                .cert
+                .as_deref()
+                .is_none_or(|cert| public_key == Some(cert))
+    }
+}
+
+pub fn rekor_entry(artifact: &[u8], bundle: &CosignBundle) -> Option<String> {
+    // The hashedrekord entry for POST /api/v1/log/entries; None without
+    // "cert", which the log needs to check the signature
+    let entry = serde_json::json!({
+        "apiVersion": "0.0.1",
+        "kind": "hashedrekord",
+        "spec": {
+            "data": {"hash": {"algorithm": "sha256", "value": hex::encode(sha256(artifact))}},
+            "signature": {
+                "content": bundle.base64_signature,
+                "publicKey": {"content": bundle.cert.as_ref()?},
+            },
+        },
+    });
+    Some(entry.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::{p256, secp256k1};
+
+    const ARTIFACT: &[u8] = include_bytes!("../testdata/cosign_artifact.txt");
+    const BUNDLE: &str = include_str!("../testdata/cosign_bundle.json");
+
+    #[test]
+    fn test_cosign_sign_verify() {
+        let ecdsa = p256();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let bundle = ecdsa.cosign_sign(b"artifact", &priv_key).unwrap();
+        assert!(ecdsa.cosign_verify(b"artifact", &bundle.base64_signature, &pub_key));
+        assert!(!ecdsa.cosign_verify(b"artifact2", &bundle.base64_signature, &pub_key));
+        assert_eq!(ecdsa.cosign_bundle_key(&bundle), Some(pub_key));
+
+        let json = serde_json::to_string(&bundle).unwrap();
+        assert!(!json.contains("rekorBundle"));
+        assert_eq!(serde_json::from_str::<CosignBundle>(&json).unwrap(), bundle);
+
+        let entry: serde_json::Value =
+            serde_json::from_str(&rekor_entry(b"artifact", &bundle).unwrap()).unwrap();
+        assert_eq!(
+            entry["spec"]["data"]["hash"]["value"],
+            hex::encode(sha256(b"artifact"))
+        );
+        let keyless = CosignBundle {
+            cert: None,
+            ..bundle
+        };
+        assert_eq!(rekor_entry(b"artifact", &keyless), None);
+
+        assert!(secp256k1().cosign_sign(b"artifact", &priv_key).is_none());
+    }
+
+    #[test]
+    fn test_openssl_cosign_bundle() {
+        // An artifact signed with `openssl dgst -sha256 -sign`, as cosign
+        // does, under a self-signed certificate, with a Rekor entry signed by
+        // a stand-in log key
+        const LOG_PUB: &str = "04752efd894c5d51d28d5945fbfd0685deb0b6b447971b89ac8628d37e8504517f\
+                               b5ca4e3b943265543a429501c6c7c9270d92f1e498353a09ee94aff26ddf5f2d";
+        let ecdsa = p256();
+        let log_key = ecdsa.decode_point(&hex::decode(LOG_PUB).unwrap()).unwrap();
+        let bundle: CosignBundle = serde_json::from_str(BUNDLE).unwrap();
+
+        let pub_key = ecdsa.cosign_bundle_key(&bundle).unwrap();
+        assert!(ecdsa.cosign_verify(ARTIFACT, &bundle.base64_signature, &pub_key));
+        assert!(ecdsa.verify_rekor_bundle(ARTIFACT, &bundle, &log_key));
+        let body = STANDARD
+            .decode(&bundle.rekor_bundle.as_ref().unwrap().payload.body)
+            .unwrap();
+        assert_eq!(rekor_entry(ARTIFACT, &bundle).unwrap().as_bytes(), body);
+
+        // Another artifact, log position or log key
+        assert!(!ecdsa.verify_rekor_bundle(b"other", &bundle, &pub_key));
+        assert!(!ecdsa.verify_rekor_bundle(ARTIFACT, &bundle, &pub_key));
+        let mut moved = bundle.clone();
+        moved.rekor_bundle.as_mut().unwrap().payload.log_index += 1;
+        assert!(!ecdsa.verify_rekor_bundle(ARTIFACT, &moved, &log_key));
+    }
+}
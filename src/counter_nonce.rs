@@ -0,0 +1,205 @@
+// Stateful nonce derivation for deterministic environments such as HSMs: k is
+// derived from the private key, the digest and a monotonic counter, k =
+// HKDF-SHA256(salt = DST, ikm = d, info = counter || digest) reduced into
+// [1, q - 1]. The counter is moved forward in a `CounterStore` before it is
+// used, and nothing is signed when the store cannot persist it. A snapshot
+// that rolls the counter back then replays a (counter, digest) pair at worst,
+// which gives the very same signature, never the same k for another message.
+
+use alloc::vec::Vec;
+use num_bigint::BigUint;
+
+use crate::encoding::to_fixed_bytes;
+use crate::group;
+use crate::hash::hkdf_sha256;
+use crate::signer::Signature;
+use crate::ECDSA;
+
+const DST_COUNTER_NONCE: &[u8] = b"RS_ECC-counter-nonce";
+
+// Persistent storage of the counter; `store` must only return true once the
+// value would survive a crash or restart
+pub trait CounterStore {
+    fn load(&mut self) -> Option<u64>;
+    fn store(&mut self, counter: u64) -> bool;
+}
+
+// Keeps the counter in a file, as decimal text, replaced atomically and
+// synced to disk on every advance; a missing file starts the count at 0
+#[cfg(feature = "std")]
+pub struct FileCounter {
+    path: std::path::PathBuf,
+}
+
+impl ECDSA {
+    pub fn sign_with_counter(
+        &self,
+        store: &mut impl CounterStore,
+        digest: &[u8],
+        priv_key: &BigUint,
+    ) -> Option<(Signature, u64)> {
+        // Standard ECDSA over the digest, with the counter value that gave
+        // its nonce; None if the counter could not be advanced
+        let q = &self.q_order;
+        let hash = self.bits_to_scalar(digest);
+        let zero = BigUint::from(0u32);
+        loop {
+            let counter = store.load()?.checked_add(1)?;
+            if !store.store(counter) {
+                return None;
+            }
+            let k = self.counter_nonce(digest, priv_key, counter);
+            let Some((r, s)) = group::sign(self, &hash, priv_key, &k) else {
+                continue;
+            };
+            if r != zero && &r < q && s != zero {
+                return Some((Signature::new(r, s), counter));
+            }
+        }
+    }
+
+    pub fn counter_nonce(&self, digest: &[u8], priv_key: &BigUint, counter: u64) -> BigUint {
+        // 128 extra bits make the bias of the reduction negligible
+        let len = self.scalar_len() + 16;
+        let info: Vec<u8> = [&counter.to_be_bytes()[..], digest].concat();
+        let okm = hkdf_sha256(
+            DST_COUNTER_NONCE,
+            &to_fixed_bytes(priv_key, self.scalar_len()),
+            &info,
+            len,
+        );
+        BigUint::from_bytes_be(&okm) % (&self.q_order - 1u32) + 1u32
+    }
+}
+
+#[cfg(feature = "std")]
+impl FileCounter {
+    pub fn new(path: impl Into<std::path::PathBuf>) -> FileCounter {
+        FileCounter { path: path.into() }
+    }
+}
+
+#[cfg(feature = "std")]
+impl CounterStore for FileCounter {
+    fn load(&mut self) -> Option<u64> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(text) => text.trim().parse().ok(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Some(0),
+            Err(_) => None,
+        }
+    }
+
+    fn store(&mut self, counter: u64) -> bool {
+        // Write a temporary file next to it, sync it, then rename over
+        use std::io::Write;
+        let temp = self.path.with_extension("tmp");
+        let written = std::fs::File::create(&temp).and_then(|mut file| {
+            file.write_all(counter.to_string().as_bytes())?;
+            file.sync_all()
+        });
+        written.is_ok() && std::fs::rename(&temp, &self.path).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::p256;
+    use crate::hash::sha256;
+
+    struct MemoryCounter {
+        value: u64,
+        writable: bool,
+    }
+
+    impl CounterStore for MemoryCounter {
+        fn load(&mut self) -> Option<u64> {
+            Some(self.value)
+        }
+
+        fn store(&mut self, counter: u64) -> bool {
+            if self.writable {
+                self.value = counter;
+            }
+            self.writable
+        }
+    }
+
+    #[test]
+    fn test_sign_with_counter() {
+        let ecdsa = p256();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let digest = sha256(b"firmware image");
+        let mut store = MemoryCounter {
+            value: 41,
+            writable: true,
+        };
+
+        let (first, counter) = ecdsa
+            .sign_with_counter(&mut store, &digest, &priv_key)
+            .unwrap();
+        assert_eq!((counter, store.value), (42, 42));
+        assert!(ecdsa.verify_standard(&digest, &pub_key, &first));
+
+        // Same digest, next counter: a new nonce
+        let (second, _) = ecdsa
+            .sign_with_counter(&mut store, &digest, &priv_key)
+            .unwrap();
+        assert_ne!(first.r(), second.r());
+
+        // Rolled back, as after restoring a snapshot: the same signature
+        store.value = 41;
+        let (replayed, _) = ecdsa
+            .sign_with_counter(&mut store, &digest, &priv_key)
+            .unwrap();
+        assert_eq!(replayed, first);
+    }
+
+    #[test]
+    fn test_counter_store_failures() {
+        let ecdsa = p256();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let digest = sha256(b"firmware image");
+        let mut read_only = MemoryCounter {
+            value: 7,
+            writable: false,
+        };
+        assert!(ecdsa
+            .sign_with_counter(&mut read_only, &digest, &priv_key)
+            .is_none());
+        let mut exhausted = MemoryCounter {
+            value: u64::MAX,
+            writable: true,
+        };
+        assert!(ecdsa
+            .sign_with_counter(&mut exhausted, &digest, &priv_key)
+            .is_none());
+    }
+
+    #[test]
+    fn test_counter_nonce() {
+        let ecdsa = p256();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let k = ecdsa.counter_nonce(b"digest", &priv_key, 1);
+        assert!(k > BigUint::from(0u32) && k < ecdsa.q_order);
+        assert_eq!(k, ecdsa.counter_nonce(b"digest", &priv_key, 1));
+        assert_ne!(k, ecdsa.counter_nonce(b"digest", &priv_key, 2));
+        assert_ne!(k, ecdsa.counter_nonce(b"digesu", &priv_key, 1));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_file_counter() {
+        let path = std::env::temp_dir().join(format!("rs_ecc_counter_{}", std::process::id()));
+        let mut store = FileCounter::new(&path);
+        assert_eq!(store.load(), Some(0));
+        assert!(store.store(5));
+        assert_eq!(FileCounter::new(&path).load(), Some(5));
+
+        std::fs::write(&path, "not a number").unwrap();
+        assert_eq!(store.load(), None);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,249 @@
+// Named curve presets. `p256()`, `p384()` and `secp256k1()` build the
+// parameters from their published hex constants; under `std` the `P256`,
+// `P384` and `SECP256K1` statics hold a `SigningContext` for each preset,
+// built on first use and then shared by every thread, so the constants are
+// parsed and the generator tables computed once per process. `NamedCurve`
+// names the presets where a value has to say which curve it belongs to without
+// carrying its parameters.
+
+use ec_generic::{EllipticCurve, Point};
+use num_bigint::BigUint;
+#[cfg(feature = "std")]
+use once_cell::sync::Lazy;
+
+#[cfg(feature = "std")]
+use crate::context::SigningContext;
+use crate::ECDSA;
+
+pub const P256_P: &str = "ffffffff00000001000000000000000000000000ffffffffffffffffffffffff";
+pub const P256_B: &str = "5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b";
+pub const P256_GX: &str = "6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296";
+pub const P256_GY: &str = "4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5";
+pub const P256_Q: &str = "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551";
+
+pub const P384_P: &str = "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeffffffff0000000000000000ffffffff";
+pub const P384_B: &str = "b3312fa7e23ee7e4988e056be3f82d19181d9c6efe8141120314088f5013875ac656398d8a2ed19d2a85c8edd3ec2aef";
+pub const P384_GX: &str = "aa87ca22be8b05378eb1c71ef320ad746e1d3b628ba79b9859f741e082542a385502f25dbf55296c3a545e3872760ab7";
+pub const P384_GY: &str = "3617de4a96262c6f5d9e98bf9292dc29f8f41dbd289a147ce9da3113b5f0b8c00a60b1ce1d7e819d7a431d7c90ea0e5f";
+pub const P384_Q: &str = "ffffffffffffffffffffffffffffffffffffffffffffffffc7634d81f4372ddf581a0db248b0a77aecec196accc52973";
+
+pub const SECP256K1_P: &str = "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f";
+pub const SECP256K1_GX: &str = "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+pub const SECP256K1_GY: &str = "483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
+pub const SECP256K1_Q: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+
+// DER contents of the named curve OIDs, 1.2.840.10045.3.1.7, 1.3.132.0.34
+// and 1.3.132.0.10
+pub const P256_OID: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+pub const P384_OID: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x22];
+pub const SECP256K1_OID: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x0a];
+
+#[cfg(feature = "std")]
+pub static P256: Lazy<SigningContext> = Lazy::new(|| SigningContext::new(p256()));
+
+#[cfg(feature = "std")]
+pub static P384: Lazy<SigningContext> = Lazy::new(|| SigningContext::new(p384()));
+
+#[cfg(feature = "std")]
+pub static SECP256K1: Lazy<SigningContext> = Lazy::new(|| SigningContext::new(secp256k1()));
+
+fn hex(value: &str) -> BigUint {
+    BigUint::parse_bytes(value.as_bytes(), 16).unwrap()
+}
+
+pub fn p256() -> ECDSA {
+    // a = p - 3
+    ECDSA {
+        elliptic_curve: EllipticCurve {
+            a: hex(P256_P) - BigUint::from(3u32),
+            b: hex(P256_B),
+            p: hex(P256_P),
+        },
+        a_gen: Point::Coor(hex(P256_GX), hex(P256_GY)),
+        q_order: hex(P256_Q),
+    }
+}
+
+pub fn p384() -> ECDSA {
+    // a = p - 3
+    ECDSA {
+        elliptic_curve: EllipticCurve {
+            a: hex(P384_P) - BigUint::from(3u32),
+            b: hex(P384_B),
+            p: hex(P384_P),
+        },
+        a_gen: Point::Coor(hex(P384_GX), hex(P384_GY)),
+        q_order: hex(P384_Q),
+    }
+}
+
+pub fn secp256k1() -> ECDSA {
+    // y² = x³ + 7
+    ECDSA {
+        elliptic_curve: EllipticCurve {
+            a: BigUint::from(0u32),
+            b: BigUint::from(7u32),
+            p: hex(SECP256K1_P),
+        },
+        a_gen: Point::Coor(hex(SECP256K1_GX), hex(SECP256K1_GY)),
+        q_order: hex(SECP256K1_Q),
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum NamedCurve {
+    P256,
+    P384,
+    Secp256k1,
+}
+
+impl NamedCurve {
+    pub(crate) fn ecdsa(self) -> ECDSA {
+        match self {
+            NamedCurve::P256 => p256(),
+            NamedCurve::P384 => p384(),
+            NamedCurve::Secp256k1 => secp256k1(),
+        }
+    }
+
+    // The shared static under `std`, a fresh build of the constants without
+    #[cfg(feature = "std")]
+    pub(crate) fn preset(self) -> &'static ECDSA {
+        match self {
+            NamedCurve::P256 => P256.ecdsa(),
+            NamedCurve::P384 => P384.ecdsa(),
+            NamedCurve::Secp256k1 => SECP256K1.ecdsa(),
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn preset(self) -> alloc::boxed::Box<ECDSA> {
+        alloc::boxed::Box::new(self.ecdsa())
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NamedCurve::P256 => "prime256v1",
+            NamedCurve::P384 => "secp384r1",
+            NamedCurve::Secp256k1 => "secp256k1",
+        }
+    }
+
+    pub fn oid(self) -> &'static [u8] {
+        match self {
+            NamedCurve::P256 => P256_OID,
+            NamedCurve::P384 => P384_OID,
+            NamedCurve::Secp256k1 => SECP256K1_OID,
+        }
+    }
+
+    pub fn from_oid(oid: &[u8]) -> Option<NamedCurve> {
+        match oid {
+            P256_OID => Some(NamedCurve::P256),
+            P384_OID => Some(NamedCurve::P384),
+            SECP256K1_OID => Some(NamedCurve::Secp256k1),
+            _ => None,
+        }
+    }
+}
+
+pub fn from_name(name: &str) -> Option<ECDSA> {
+    // The names OpenSSL and the SEC and NIST documents use
+    match name.to_ascii_lowercase().as_str() {
+        "p256" | "p-256" | "prime256v1" | "secp256r1" => Some(p256()),
+        "p384" | "p-384" | "secp384r1" => Some(p384()),
+        "secp256k1" => Some(secp256k1()),
+        _ => None,
+    }
+}
+
+pub fn from_oid(oid: &[u8]) -> Option<ECDSA> {
+    NamedCurve::from_oid(oid).map(NamedCurve::ecdsa)
+}
+
+impl ECDSA {
+    pub fn named_curve(&self) -> Option<NamedCurve> {
+        if self.is_p256() {
+            Some(NamedCurve::P256)
+        } else if self.is_p384() {
+            Some(NamedCurve::P384)
+        } else if self.is_secp256k1() {
+            Some(NamedCurve::Secp256k1)
+        } else {
+            None
+        }
+    }
+
+    pub fn curve_name(&self) -> Option<&'static str> {
+        self.named_curve().map(NamedCurve::name)
+    }
+
+    pub fn curve_oid(&self) -> Option<&'static [u8]> {
+        self.named_curve().map(NamedCurve::oid)
+    }
+
+    pub fn is_p256(&self) -> bool {
+        NamedCurve::P256.preset().same_parameters(self)
+    }
+
+    pub fn is_p384(&self) -> bool {
+        NamedCurve::P384.preset().same_parameters(self)
+    }
+
+    pub fn is_secp256k1(&self) -> bool {
+        NamedCurve::Secp256k1.preset().same_parameters(self)
+    }
+
+    pub(crate) fn same_parameters(&self, other: &ECDSA) -> bool {
+        self.elliptic_curve.a == other.elliptic_curve.a
+            && self.elliptic_curve.b == other.elliptic_curve.b
+            && self.elliptic_curve.p == other.elliptic_curve.p
+            && self.a_gen == other.a_gen
+            && self.q_order == other.q_order
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_named_curves() {
+        let p256 = p256();
+        let secp256k1 = secp256k1();
+        assert!(ECDSA::new(
+            p256.elliptic_curve.clone(),
+            p256.a_gen.clone(),
+            p256.q_order.clone()
+        )
+        .is_some());
+        assert!(p256.is_p256() && !p256.is_secp256k1());
+        assert!(secp256k1.is_secp256k1() && !secp256k1.is_p256());
+
+        assert!(from_name("P-256").unwrap().is_p256());
+        assert!(from_oid(SECP256K1_OID).unwrap().is_secp256k1());
+        assert_eq!(p256.curve_oid(), Some(P256_OID));
+        assert_eq!(secp256k1.named_curve(), Some(NamedCurve::Secp256k1));
+        assert_eq!(NamedCurve::from_oid(P256_OID), Some(NamedCurve::P256));
+        assert!(from_name("secp384r1").unwrap().is_p384());
+        assert_eq!(from_oid(P384_OID).unwrap().curve_name(), Some("secp384r1"));
+        assert!(p384().is_valid_pub_key(&p384().a_gen));
+        assert!(from_name("p521").is_none());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_named_curve_statics() {
+        // Both threads get the very same context
+        let context: &'static SigningContext = std::thread::spawn(|| &*P256).join().unwrap();
+        assert!(std::ptr::eq(context, &*P256));
+        assert!(P256.ecdsa().is_p256());
+        assert!(P384.ecdsa().is_p384() && std::ptr::eq(NamedCurve::P384.preset(), P384.ecdsa()));
+
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        assert_eq!(
+            SECP256K1.generate_pub_key(&priv_key),
+            SECP256K1.ecdsa().generate_pub_key(&priv_key)
+        );
+    }
+}
@@ -0,0 +1,203 @@
+// The small subset of DER this crate reads and writes: definite lengths,
+// positive INTEGERs and the universal tags of signatures and key formats.
+// Reading is strict, only the canonical encoding of a value is accepted, and
+// works in place, handing back slices of the input. The `_ber` readers relax
+// that for legacy encoders (some smartcards among them) that pad lengths and
+// integers: a long-form length may have leading zeros or fit the short form,
+// and an INTEGER may carry redundant 0x00 bytes. Values stay positive.
+
+use alloc::string::{String, ToString};
+use alloc::{vec, vec::Vec};
+use num_bigint::BigUint;
+
+pub const INTEGER: u8 = 0x02;
+pub const BIT_STRING: u8 = 0x03;
+pub const OCTET_STRING: u8 = 0x04;
+pub const OID: u8 = 0x06;
+pub const SEQUENCE: u8 = 0x30;
+
+pub fn write(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut der = vec![tag];
+    der.extend(length(contents.len()));
+    der.extend(contents);
+    der
+}
+
+pub fn integer(n: &BigUint) -> Vec<u8> {
+    // Minimal big-endian two's complement, so a leading 0x00 when the top bit
+    // is set
+    let mut bytes = n.to_bytes_be();
+    if bytes[0] & 0x80 != 0 {
+        bytes.insert(0, 0x00);
+    }
+    write(INTEGER, &bytes)
+}
+
+fn length(len: usize) -> Vec<u8> {
+    if len < 0x80 {
+        return vec![len as u8];
+    }
+    let bytes: Vec<u8> = len
+        .to_be_bytes()
+        .into_iter()
+        .skip_while(|b| *b == 0)
+        .collect();
+    let mut der = vec![0x80 | bytes.len() as u8];
+    der.extend(bytes);
+    der
+}
+
+pub fn read(bytes: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    // tag, contents, remaining input
+    read_length(bytes, true)
+}
+
+pub fn read_ber(bytes: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    // As `read`, with non-minimal definite lengths
+    read_length(bytes, false)
+}
+
+fn read_length(bytes: &[u8], minimal: bool) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = bytes.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    let (len, rest) = if first < 0x80 {
+        (first as usize, rest)
+    } else {
+        let count = (first & 0x7f) as usize;
+        if count == 0 || rest.len() < count {
+            return None;
+        }
+        let (len_bytes, rest) = rest.split_at(count);
+        let significant = len_bytes.iter().skip_while(|b| **b == 0).count();
+        if significant > core::mem::size_of::<usize>() {
+            return None;
+        }
+        let len = len_bytes
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+        if minimal && (len_bytes[0] == 0 || len < 0x80) {
+            return None;
+        }
+        (len, rest)
+    };
+    if rest.len() < len {
+        return None;
+    }
+    let (contents, rest) = rest.split_at(len);
+    Some((tag, contents, rest))
+}
+
+pub fn read_tag(bytes: &[u8], expected: u8) -> Option<(&[u8], &[u8])> {
+    // contents, remaining input
+    match read(bytes)? {
+        (tag, contents, rest) if tag == expected => Some((contents, rest)),
+        _ => None,
+    }
+}
+
+pub fn read_integer(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    // Positive and minimally encoded; returns the magnitude, stripped of the
+    // 0x00 that keeps a high top bit positive
+    let (contents, rest) = read_tag(bytes, INTEGER)?;
+    if contents.is_empty() || contents[0] & 0x80 != 0 {
+        return None;
+    }
+    if contents.len() > 1 && contents[0] == 0 && contents[1] & 0x80 == 0 {
+        return None;
+    }
+    match contents {
+        [0, magnitude @ ..] if !magnitude.is_empty() => Some((magnitude, rest)),
+        _ => Some((contents, rest)),
+    }
+}
+
+pub fn read_integer_ber(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    // Positive, with any number of leading 0x00 bytes and a definite length
+    // of any form; returns the magnitude without them, or one 0x00 for zero
+    match read_ber(bytes)? {
+        (INTEGER, contents, rest) if !contents.is_empty() && contents[0] & 0x80 == 0 => {
+            let zeros = contents.iter().take_while(|b| **b == 0).count();
+            Some((&contents[zeros.min(contents.len() - 1)..], rest))
+        }
+        _ => None,
+    }
+}
+
+pub fn oid_to_string(contents: &[u8]) -> Option<String> {
+    // Dotted form of an OID's contents; base 128 arcs, the first of which
+    // packs the first two as 40 a + b
+    let mut arcs = Vec::new();
+    let mut arc = 0u64;
+    for byte in contents {
+        if arc == 0 && *byte == 0x80 || arc >> 57 != 0 {
+            return None;
+        }
+        arc = (arc << 7) | u64::from(byte & 0x7f);
+        if byte & 0x80 == 0 {
+            if arcs.is_empty() {
+                let first = (arc / 40).min(2);
+                arcs.push(first);
+                arcs.push(arc - 40 * first);
+            } else {
+                arcs.push(arc);
+            }
+            arc = 0;
+        }
+    }
+    if arcs.is_empty() || contents.last()? & 0x80 != 0 {
+        return None;
+    }
+    let arcs: Vec<String> = arcs.iter().map(|arc| arc.to_string()).collect();
+    Some(arcs.join("."))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_der_lengths() {
+        let contents = vec![0xab; 300];
+        let der = write(OCTET_STRING, &contents);
+        assert_eq!(&der[..4], &[0x04, 0x82, 0x01, 0x2c]);
+        assert_eq!(read_tag(&der, OCTET_STRING), Some((&contents[..], &[][..])));
+        assert_eq!(read_tag(&der, SEQUENCE), None);
+
+        // A long form length for fewer than 128 bytes is not canonical
+        assert_eq!(read(&[0x04, 0x81, 0x01, 0xab]), None);
+        assert_eq!(read(&[0x04, 0x02, 0xab]), None);
+        let padded = [0x04, 0x83, 0x00, 0x00, 0x01, 0xab];
+        assert_eq!(
+            read_ber(&padded),
+            Some((OCTET_STRING, &[0xab][..], &[][..]))
+        );
+        assert_eq!(read_ber(&[0x04, 0x81, 0x01, 0xab]).unwrap().1, &[0xab]);
+        assert_eq!(read_ber(&[0x04, 0x80, 0xab, 0x00, 0x00]), None);
+
+        assert_eq!(read_integer(&[0x02, 0x02, 0x00, 0x01]), None);
+        assert_eq!(
+            read_integer_ber(&[0x02, 0x03, 0x00, 0x00, 0x01]).unwrap().0,
+            &[0x01]
+        );
+        assert_eq!(
+            read_integer_ber(&[0x02, 0x02, 0x00, 0x00]).unwrap().0,
+            &[0x00]
+        );
+        assert_eq!(read_integer_ber(&[0x02, 0x01, 0x81]), None);
+    }
+
+    #[test]
+    fn test_oid_to_string() {
+        let p256 = [0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+        assert_eq!(oid_to_string(&p256).unwrap(), "1.2.840.10045.3.1.7");
+        assert_eq!(
+            oid_to_string(&[0x2b, 0x81, 0x04, 0x00, 0x0a]).unwrap(),
+            "1.3.132.0.10"
+        );
+        // 2.999.3, the first arc past 80
+        assert_eq!(oid_to_string(&[0x88, 0x37, 0x03]).unwrap(), "2.999.3");
+        // Truncated, and padded with a leading 0x80
+        assert_eq!(oid_to_string(&[0x2a, 0x86]), None);
+        assert_eq!(oid_to_string(&[0x2a, 0x80, 0x01]), None);
+    }
+}
@@ -0,0 +1,199 @@
+// did:key identifiers (W3C CCG did:key method), which are public keys
+// written as DIDs. The key is prefixed with its multicodec type as an
+// unsigned varint and encoded in base58btc multibase ('z'):
+//
+//   did:key:z || base58(varint(codec) || key)
+//
+// with the codecs 0xe7 secp256k1-pub, 0x1200 p256-pub and 0x1201 p384-pub
+// over SEC1 compressed points, and 0xed ed25519-pub over the 32 raw bytes.
+// Ed25519 is not a curve this crate computes on, so those keys are only
+// carried through as bytes. Resolution reads the key back, checking that an
+// EC point lies on its curve; a "#fragment" naming the verification method
+// is ignored.
+
+use alloc::{format, string::String, vec::Vec};
+use ec_generic::Point;
+
+use crate::address::{base58, base58_decode};
+use crate::curves::NamedCurve;
+use crate::ECDSA;
+
+const SECP256K1_PUB: u64 = 0xe7;
+const P256_PUB: u64 = 0x1200;
+const P384_PUB: u64 = 0x1201;
+const ED25519_PUB: u64 = 0xed;
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum DidKey {
+    Ec { curve: NamedCurve, pub_key: Point },
+    Ed25519([u8; 32]),
+}
+
+impl ECDSA {
+    pub fn did_key(&self, pub_key: &Point) -> Option<String> {
+        // None off the named curves or for an invalid key
+        let codec = match self.named_curve()? {
+            NamedCurve::P256 => P256_PUB,
+            NamedCurve::P384 => P384_PUB,
+            NamedCurve::Secp256k1 => SECP256K1_PUB,
+        };
+        if !self.is_valid_pub_key(pub_key) {
+            return None;
+        }
+        Some(encode_did(codec, &self.encode_point(pub_key)))
+    }
+}
+
+pub fn did_key_ed25519(pub_key: &[u8; 32]) -> String {
+    encode_did(ED25519_PUB, pub_key)
+}
+
+pub fn resolve_did_key(did: &str) -> Option<DidKey> {
+    let did = did.split_once('#').map_or(did, |(did, _)| did);
+    let encoded = did.strip_prefix("did:key:z")?;
+    let bytes = base58_decode(encoded)?;
+    let (codec, key) = read_varint(&bytes)?;
+    let curve = match codec {
+        ED25519_PUB => return Some(DidKey::Ed25519(key.try_into().ok()?)),
+        P256_PUB => NamedCurve::P256,
+        P384_PUB => NamedCurve::P384,
+        SECP256K1_PUB => NamedCurve::Secp256k1,
+        _ => return None,
+    };
+    let ecdsa = curve.ecdsa();
+    // Compressed points only
+    if key.len() != ecdsa.field_len() + 1 {
+        return None;
+    }
+    let pub_key = ecdsa.decode_point(key)?;
+    ecdsa
+        .is_valid_pub_key(&pub_key)
+        .then_some(DidKey::Ec { curve, pub_key })
+}
+
+fn encode_did(codec: u64, key: &[u8]) -> String {
+    let mut bytes = write_varint(codec);
+    bytes.extend(key);
+    format!("did:key:z{}", base58(&bytes))
+}
+
+fn write_varint(mut n: u64) -> Vec<u8> {
+    // Multiformats unsigned varint: 7 bits at a time, low first, the top bit
+    // set on all bytes but the last
+    let mut bytes = Vec::new();
+    while n >= 0x80 {
+        bytes.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+    bytes
+}
+
+fn read_varint(bytes: &[u8]) -> Option<(u64, &[u8])> {
+    // At most 9 bytes, in the shortest form
+    let mut n = 0u64;
+    for (i, byte) in bytes.iter().enumerate().take(9) {
+        n |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            let minimal = i == 0 || *byte != 0;
+            return minimal.then_some((n, &bytes[i + 1..]));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::{p256, p384, secp256k1};
+
+    #[test]
+    fn test_did_key_generators() {
+        // The generators' DIDs, from an independent base58 encoder
+        let secp256k1 = secp256k1();
+        let did = secp256k1.did_key(&secp256k1.a_gen).unwrap();
+        assert_eq!(
+            did,
+            "did:key:zQ3shVc2UkAfJCdc1TR8E66J85h48P43r93q8jGPkPpjF9Ef9"
+        );
+        assert_eq!(
+            resolve_did_key(&did),
+            Some(DidKey::Ec {
+                curve: NamedCurve::Secp256k1,
+                pub_key: secp256k1.a_gen.clone()
+            })
+        );
+
+        let p256 = p256();
+        let did = p256.did_key(&p256.a_gen).unwrap();
+        assert_eq!(
+            did,
+            "did:key:zDnaepsL7AXenJkVYdkh5KuKsSU7Ykh7kyXaLLU7auN9FWSiZ"
+        );
+        let fragment = format!("{}#{}", did, &did[8..]);
+        assert_eq!(
+            resolve_did_key(&fragment),
+            Some(DidKey::Ec {
+                curve: NamedCurve::P256,
+                pub_key: p256.a_gen.clone()
+            })
+        );
+
+        let ed25519: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let did = did_key_ed25519(&ed25519);
+        assert_eq!(
+            did,
+            "did:key:z6MkeTGwHmLmuCmgg4ABYhzWVh6ZX7hTwWt8gguAretUfc9c"
+        );
+        assert_eq!(resolve_did_key(&did), Some(DidKey::Ed25519(ed25519)));
+    }
+
+    #[test]
+    fn test_did_key_prefixes() {
+        // The multicodec prefixes give each key type its familiar start
+        let ecdsa = p384();
+        let pub_key = ecdsa.generate_pub_key(&num_bigint::BigUint::from(0xc0ffeeu32));
+        let did = ecdsa.did_key(&pub_key).unwrap();
+        assert!(did.starts_with("did:key:z82"));
+        assert!(matches!(
+            resolve_did_key(&did),
+            Some(DidKey::Ec { curve: NamedCurve::P384, pub_key: key }) if key == pub_key
+        ));
+        assert!(did_key_ed25519(&[0xff; 32]).starts_with("did:key:z6Mk"));
+    }
+
+    #[test]
+    fn test_resolve_rejects() {
+        let secp256k1 = secp256k1();
+        let did = secp256k1.did_key(&secp256k1.a_gen).unwrap();
+        assert_eq!(
+            resolve_did_key(&did.replace("did:key:z", "did:key:f")),
+            None
+        );
+        assert_eq!(resolve_did_key(&did.replace("did:key:", "did:web:")), None);
+
+        // An x that is not on the curve, an unknown codec, a non-minimal
+        // varint and an uncompressed point
+        let mut bytes = write_varint(SECP256K1_PUB);
+        bytes.push(0x02);
+        bytes.extend([0; 31]);
+        bytes.push(7);
+        assert_eq!(
+            resolve_did_key(&format!("did:key:z{}", base58(&bytes))),
+            None
+        );
+        assert_eq!(resolve_did_key(&encode_did(0x1205, &[2; 33])), None);
+        assert_eq!(read_varint(&[0xed, 0x00]), None);
+        let uncompressed = secp256k1.encode_point_uncompressed(&secp256k1.a_gen);
+        assert_eq!(
+            resolve_did_key(&encode_did(SECP256K1_PUB, &uncompressed)),
+            None
+        );
+
+        assert_eq!(
+            read_varint(&write_varint(P384_PUB)),
+            Some((P384_PUB, &[][..]))
+        );
+    }
+}
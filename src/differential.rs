@@ -0,0 +1,75 @@
+// Differential mode, behind the `differential` feature: every signature made
+// through `SigningKey` is checked again by the `k256` crate, and a mismatch in
+// either the public key or the verification result is returned as an error
+// instead of the signature. Only secp256k1 has a reference implementation;
+// on other curves the check passes through. The reference sees the same
+// digest as `sign_standard`, and a high s is flipped to q - s, which verifies
+// for exactly the same signatures, since k256 rejects high s.
+
+use core::cmp::min;
+
+use k256::ecdsa::signature::hazmat::PrehashVerifier;
+use k256::ecdsa::{Signature as ReferenceSignature, SigningKey as ReferenceKey};
+use num_bigint::BigUint;
+use signature::Error;
+
+use crate::encoding::to_fixed_bytes;
+use crate::signer::Signature;
+use crate::ECDSA;
+
+impl ECDSA {
+    pub fn differential_check(
+        &self,
+        digest: &[u8],
+        priv_key: &BigUint,
+        signature: &Signature,
+    ) -> Result<(), Error> {
+        if !self.is_secp256k1() {
+            return Ok(());
+        }
+
+        let reference_key =
+            ReferenceKey::from_slice(&to_fixed_bytes(priv_key, 32)).map_err(|_| Error::new())?;
+        let reference_pub_key = reference_key.verifying_key();
+        let pub_key = self.encode_point(&self.generate_pub_key(priv_key));
+        if reference_pub_key.to_encoded_point(true).as_bytes() != pub_key.as_slice() {
+            return Err(Error::new());
+        }
+
+        let s = min(signature.s().clone(), &self.q_order - signature.s());
+        let reference_signature = ReferenceSignature::from_scalars(
+            *k256::FieldBytes::from_slice(&to_fixed_bytes(signature.r(), 32)),
+            *k256::FieldBytes::from_slice(&to_fixed_bytes(&s, 32)),
+        )
+        .map_err(|_| Error::new())?;
+        reference_pub_key.verify_prehash(digest, &reference_signature)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use signature::Signer;
+
+    use crate::curves::secp256k1;
+    use crate::signer::SigningKey;
+
+    #[test]
+    fn test_differential_sign() {
+        let ecdsa = secp256k1();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let signing_key = SigningKey::new(&ecdsa, priv_key.clone());
+        let signature = signing_key.try_sign(b"Bob -> 1 BTC -> Alice").unwrap();
+
+        let digest = crate::hash::sha256(b"Bob -> 1 BTC -> Alice");
+        assert!(ecdsa
+            .differential_check(&digest, &priv_key, &signature)
+            .is_ok());
+
+        // A signature for another digest diverges from the reference
+        let digest = crate::hash::sha256(b"Bob -> 2 BTC -> Alice");
+        assert!(ecdsa
+            .differential_check(&digest, &priv_key, &signature)
+            .is_err());
+    }
+}
@@ -0,0 +1,157 @@
+// Distributed key generation in the style of Pedersen's DKG: every party deals
+// a random secret with Feldman VSS, proves knowledge of its constant term so no
+// one can cancel out the others' contributions, and ends up with the sum of
+// the shares it received. The group key is the sum of the C_0 commitments and
+// nobody ever learns the matching private key.
+
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::proof_of_knowledge::KnowledgeProof;
+use crate::vss::Share;
+use crate::ECDSA;
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct DkgDealing {
+    dealer: u32,
+    commitments: Vec<Point>,
+    proof: KnowledgeProof,
+}
+
+impl ECDSA {
+    pub fn dkg_deal(
+        &self,
+        dealer: u32,
+        threshold: usize,
+        parties: u32,
+    ) -> (DkgDealing, Vec<Share>) {
+        // Broadcast the dealing, send shares[i - 1] privately to party i
+        let secret = self.generate_random_number_less_than(&self.q_order);
+        let (shares, commitments) = self.vss_deal(&secret, threshold, parties);
+
+        let k_random = self.generate_random_number_less_than(&self.q_order);
+        let proof = self.prove_knowledge(&secret, &dkg_context(dealer), &k_random);
+        let dealing = DkgDealing {
+            dealer,
+            commitments,
+            proof,
+        };
+        (dealing, shares)
+    }
+
+    pub fn dkg_verify_dealing(&self, dealing: &DkgDealing, threshold: usize) -> bool {
+        dealing.commitments.len() == threshold
+            && self.verify_knowledge(
+                &dealing.commitments[0],
+                &dkg_context(dealing.dealer),
+                &dealing.proof,
+            )
+    }
+
+    pub fn dkg_finalize(
+        &self,
+        index: u32,
+        threshold: usize,
+        dealings: &[DkgDealing],
+        shares: &[Share],
+    ) -> Option<(Share, Point)> {
+        // shares[k] is the share party `index` received from dealings[k].
+        // x_i = sum s_i, Y = sum C_0. A dealer counted twice would add its
+        // secret twice, so every dealer must appear once
+        let repeated = dealings.iter().enumerate().any(|(k, dealing)| {
+            dealings[..k]
+                .iter()
+                .any(|other| other.dealer == dealing.dealer)
+        });
+        if dealings.len() != shares.len() || repeated {
+            return None;
+        }
+
+        let mut value = BigUint::from(0u32);
+        let mut group_key = Point::Identity;
+        for (dealing, share) in dealings.iter().zip(shares) {
+            if share.index != index
+                || !self.dkg_verify_dealing(dealing, threshold)
+                || !self.vss_verify_share(share, &dealing.commitments)
+            {
+                return None;
+            }
+            value = (value + &share.value) % &self.q_order;
+            group_key = self.add_points(&group_key, &dealing.commitments[0]);
+        }
+        Some((Share { index, value }, group_key))
+    }
+
+    pub fn dkg_public_share(&self, index: u32, dealings: &[DkgDealing]) -> Point {
+        // x_i A, so anyone can check a party's contribution in threshold protocols
+        dealings.iter().fold(Point::Identity, |acc, dealing| {
+            self.add_points(&acc, &self.vss_public_share(index, &dealing.commitments))
+        })
+    }
+}
+
+fn dkg_context(dealer: u32) -> Vec<u8> {
+    [b"RS_ECC-DKG".as_slice(), &dealer.to_be_bytes()].concat()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_dkg() {
+        let ecdsa = toy_curve();
+        let (threshold, parties) = (2, 3u32);
+        let (dealings, all_shares): (Vec<_>, Vec<_>) = (1..=parties)
+            .map(|dealer| ecdsa.dkg_deal(dealer, threshold, parties))
+            .unzip();
+
+        let mut final_shares = Vec::new();
+        let mut group_keys = Vec::new();
+        for index in 1..=parties {
+            let received: Vec<Share> = all_shares
+                .iter()
+                .map(|shares| shares[index as usize - 1].clone())
+                .collect();
+            let (share, group_key) = ecdsa
+                .dkg_finalize(index, threshold, &dealings, &received)
+                .unwrap();
+            assert_eq!(
+                ecdsa.mul_vartime(&ecdsa.a_gen, &share.value),
+                ecdsa.dkg_public_share(index, &dealings)
+            );
+            final_shares.push(share);
+            group_keys.push(group_key);
+        }
+
+        assert!(group_keys.iter().all(|key| *key == group_keys[0]));
+        let group_priv = ecdsa.vss_reconstruct(&final_shares[1..]).unwrap();
+        assert_eq!(ecdsa.mul_vartime(&ecdsa.a_gen, &group_priv), group_keys[0]);
+    }
+
+    #[test]
+    fn test_dkg_rejects_bad_share() {
+        let ecdsa = toy_curve();
+        let (dealing, shares) = ecdsa.dkg_deal(1, 2, 3);
+
+        let tampered = Share {
+            index: 2,
+            value: (&shares[1].value + 1u32) % &ecdsa.q_order,
+        };
+        let dealings = [dealing];
+        assert!(ecdsa.dkg_finalize(2, 2, &dealings, &shares[1..2]).is_some());
+        assert!(ecdsa.dkg_finalize(2, 2, &dealings, &[tampered]).is_none());
+    }
+
+    #[test]
+    fn test_dkg_rejects_duplicate_dealer() {
+        let ecdsa = toy_curve();
+        let (dealing, shares) = ecdsa.dkg_deal(1, 2, 3);
+
+        let dealings = [dealing.clone(), dealing];
+        let received = [shares[1].clone(), shares[1].clone()];
+        assert!(ecdsa.dkg_finalize(2, 2, &dealings, &received).is_none());
+    }
+}
@@ -0,0 +1,118 @@
+// Chaum-Pedersen proof of discrete-log equality: given bases G, H and points
+// A = xG, B = xH, prove that log_G(A) = log_H(B) without revealing x. This is
+// the building block behind verifiable OPRFs and verifiable ECDH.
+
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::secret::SecretScalar;
+use crate::ECDSA;
+
+const DST: &[u8] = b"RS_ECC-DLEQ";
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct DleqProof {
+    c: BigUint,
+    s: BigUint,
+}
+
+impl ECDSA {
+    pub fn dleq_prove(
+        &self,
+        secret: &BigUint,
+        g: &Point,
+        h: &Point,
+        k_random: &BigUint,
+    ) -> DleqProof {
+        // A = xG, B = xH, R1 = kG, R2 = kH
+        // c = H(G, H, A, B, R1, R2), s = k + c * x mod q
+        assert!(secret < &self.q_order, "Secret is Bigger than the Ec group");
+        assert!(k_random < &self.q_order, "'K' is Bigger than the Ec group");
+
+        let (x, k) = (
+            SecretScalar::new(secret.clone()),
+            SecretScalar::new(k_random.clone()),
+        );
+        let a = self.mul_secret(g, &x);
+        let b = self.mul_secret(h, &x);
+        let r1 = self.mul_secret(g, &k);
+        let r2 = self.mul_secret(h, &k);
+
+        let c = self.dleq_challenge(&[g, h, &a, &b, &r1, &r2]);
+        let s = (k_random + &c * secret) % &self.q_order;
+        DleqProof { c, s }
+    }
+
+    pub fn dleq_verify(
+        &self,
+        g: &Point,
+        a: &Point,
+        h: &Point,
+        b: &Point,
+        proof: &DleqProof,
+    ) -> bool {
+        // R1 = sG - cA, R2 = sH - cB, accept if c = H(G, H, A, B, R1, R2)
+        for point in [g, a, h, b] {
+            if *point == Point::Identity || !self.elliptic_curve.is_on_curve(point) {
+                return false;
+            }
+        }
+        if proof.c >= self.q_order || proof.s >= self.q_order {
+            return false;
+        }
+
+        let r1 = self.sub_points(
+            &self.mul_vartime(g, &proof.s),
+            &self.mul_vartime(a, &proof.c),
+        );
+        let r2 = self.sub_points(
+            &self.mul_vartime(h, &proof.s),
+            &self.mul_vartime(b, &proof.c),
+        );
+        self.dleq_challenge(&[g, h, a, b, &r1, &r2]) == proof.c
+    }
+
+    fn dleq_challenge(&self, points: &[&Point]) -> BigUint {
+        let mut data = Vec::new();
+        for point in points {
+            data.extend(self.encode_point(point));
+        }
+        self.hash_to_scalar(DST, &data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_dleq_prove_verify() {
+        let ecdsa = toy_curve();
+        let g = ecdsa.a_gen.clone();
+        let h = ecdsa.hash_to_curve(b"DLEQ test", b"H");
+        let secret = BigUint::from(7u32);
+        let a = ecdsa.mul_vartime(&g, &secret);
+        let b = ecdsa.mul_vartime(&h, &secret);
+
+        let k_random = ecdsa.generate_random_number_less_than(&ecdsa.q_order);
+        let proof = ecdsa.dleq_prove(&secret, &g, &h, &k_random);
+
+        assert!(ecdsa.dleq_verify(&g, &a, &h, &b, &proof));
+    }
+
+    #[test]
+    fn test_dleq_verify_different_logs() {
+        let ecdsa = toy_curve();
+        let g = ecdsa.a_gen.clone();
+        let h = ecdsa.hash_to_curve(b"DLEQ test", b"H");
+        let a = ecdsa.mul_vartime(&g, &BigUint::from(7u32));
+        let b = ecdsa.mul_vartime(&h, &BigUint::from(8u32));
+
+        let k_random = BigUint::from(5u32);
+        let proof = ecdsa.dleq_prove(&BigUint::from(7u32), &g, &h, &k_random);
+
+        assert!(!ecdsa.dleq_verify(&g, &a, &h, &b, &proof));
+    }
+}
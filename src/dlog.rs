@@ -0,0 +1,222 @@
+// Discrete logarithms d with dA = B, for curves small enough to solve: baby-step
+// giant-step takes about 2√q group operations and √q stored points, Pollard's
+// rho about 1.25√q operations in constant memory, so q ≤ 2^48 or so is the
+// practical limit either way (and why 256-bit curves are out of reach). Both
+// give up after `max_steps` group operations and call `progress` with the
+// number done so far every `PROGRESS_STEPS`.
+
+use alloc::collections::BTreeMap;
+use alloc::{vec, vec::Vec};
+use ec_generic::Point;
+use num_bigint::{BigUint, RandBigInt};
+
+use crate::arithmetic::mod_inverse;
+use crate::ECDSA;
+
+pub const PROGRESS_STEPS: u64 = 1 << 10;
+
+// Number of precomputed multipliers in the r-adding walk, as Teske suggests
+const RHO_PARTITIONS: usize = 20;
+
+impl ECDSA {
+    pub fn dlog_bsgs(
+        &self,
+        point: &Point,
+        max_steps: u64,
+        progress: &mut dyn FnMut(u64),
+    ) -> Option<BigUint> {
+        // d = im + j with m = ⌈√q⌉: store jA for j < m, then walk B - imA
+        // until it meets one of them
+        let m = self.q_order.sqrt() + 1u32;
+        let mut steps = Steps::new(max_steps, progress);
+        let mut baby_steps = BTreeMap::new();
+        let mut current = Point::Identity;
+        let mut j = BigUint::from(0u32);
+        while j < m {
+            if let Point::Coor(x, y) = &current {
+                baby_steps
+                    .entry((x.clone(), y.clone()))
+                    .or_insert(j.clone());
+            } else {
+                baby_steps.entry(Default::default()).or_insert(j.clone());
+            }
+            current = self.add_points(&current, &self.a_gen);
+            j += 1u32;
+            steps.take()?;
+        }
+
+        let giant = self.negate_point(&self.mul_vartime(&self.a_gen, &m));
+        let mut current = point.clone();
+        let mut i = BigUint::from(0u32);
+        while i <= m {
+            let key = match &current {
+                Point::Coor(x, y) => (x.clone(), y.clone()),
+                Point::Identity => Default::default(),
+            };
+            if let Some(j) = baby_steps.get(&key) {
+                let d = (&i * &m + j) % &self.q_order;
+                return (self.mul_vartime(&self.a_gen, &d) == *point).then_some(d);
+            }
+            current = self.add_points(&current, &giant);
+            i += 1u32;
+            steps.take()?;
+        }
+        None
+    }
+
+    pub fn dlog_rho(
+        &self,
+        point: &Point,
+        max_steps: u64,
+        progress: &mut dyn FnMut(u64),
+    ) -> Option<BigUint> {
+        // Walks X = aA + bB, adding one of RHO_PARTITIONS random combinations
+        // picked by x(X), until Brent's cycle search finds aA + bB = a'A + b'B,
+        // so d = (a' - a) / (b - b'). A collision with b = b' starts a new walk
+        let q = &self.q_order;
+        if *point == Point::Identity {
+            return Some(BigUint::from(0u32));
+        }
+        let mut rng = crate::rng();
+        let mut steps = Steps::new(max_steps, progress);
+        loop {
+            let multipliers: Vec<(BigUint, BigUint, Point)> = (0..RHO_PARTITIONS)
+                .map(|_| {
+                    let (a, b) = (rng.gen_biguint_below(q), rng.gen_biguint_below(q));
+                    let combination = self.combine(&a, &b, point);
+                    (a, b, combination)
+                })
+                .collect();
+            let step = |(x, a, b): &(Point, BigUint, BigUint)| {
+                let index = match x {
+                    Point::Coor(x, _) => (x % RHO_PARTITIONS as u32)
+                        .iter_u32_digits()
+                        .next()
+                        .unwrap_or(0) as usize,
+                    Point::Identity => 0,
+                };
+                let (a_i, b_i, m_i) = &multipliers[index];
+                (self.add_points(x, m_i), (a + a_i) % q, (b + b_i) % q)
+            };
+
+            let (a, b) = (rng.gen_biguint_below(q), rng.gen_biguint_below(q));
+            let mut tortoise = (self.combine(&a, &b, point), a, b);
+            let mut hare = step(&tortoise);
+            let (mut power, mut length) = (1u64, 1u64);
+            steps.take()?;
+            while hare.0 != tortoise.0 {
+                if power == length {
+                    tortoise = hare.clone();
+                    power *= 2;
+                    length = 0;
+                }
+                hare = step(&hare);
+                length += 1;
+                steps.take()?;
+            }
+
+            let (a_diff, b_diff) = (
+                (&hare.1 + q - &tortoise.1) % q,
+                (&tortoise.2 + q - &hare.2) % q,
+            );
+            if let Some(b_inv) = mod_inverse(&b_diff, q) {
+                let d = a_diff * b_inv % q;
+                if self.mul_vartime(&self.a_gen, &d) == *point {
+                    return Some(d);
+                }
+            }
+        }
+    }
+
+    fn combine(&self, a: &BigUint, b: &BigUint, point: &Point) -> Point {
+        // aA + bB
+        self.add_points(
+            &self.mul_vartime(&self.a_gen, a),
+            &self.mul_vartime(point, b),
+        )
+    }
+}
+
+struct Steps<'a> {
+    done: u64,
+    max_steps: u64,
+    progress: &'a mut dyn FnMut(u64),
+}
+
+impl Steps<'_> {
+    fn new(max_steps: u64, progress: &mut dyn FnMut(u64)) -> Steps<'_> {
+        Steps {
+            done: 0,
+            max_steps,
+            progress,
+        }
+    }
+
+    fn take(&mut self) -> Option<()> {
+        // None once the work bound is used up
+        self.done += 1;
+        if self.done.is_multiple_of(PROGRESS_STEPS) {
+            (self.progress)(self.done);
+        }
+        (self.done <= self.max_steps).then_some(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+    use ec_generic::EllipticCurve;
+
+    use crate::analysis::count_points_bsgs;
+
+    fn medium_ecdsa() -> ECDSA {
+        // y² = x³ + x + 14 over F_(2^20 - 3), with a prime number of points
+        ECDSA {
+            elliptic_curve: EllipticCurve {
+                a: BigUint::from(1u32),
+                b: BigUint::from(14u32),
+                p: BigUint::from((1u32 << 20) - 3),
+            },
+            a_gen: Point::Coor(BigUint::from(1u32), BigUint::from(4u32)),
+            q_order: BigUint::from(1048193u32),
+        }
+    }
+
+    #[test]
+    fn test_dlog_small_curve() {
+        let ecdsa = toy_curve();
+        for d in 0u32..19 {
+            let d = BigUint::from(d);
+            let point = ecdsa.mul_vartime(&ecdsa.a_gen, &d);
+            assert_eq!(ecdsa.dlog_bsgs(&point, 100, &mut |_| {}), Some(d.clone()));
+            assert_eq!(ecdsa.dlog_rho(&point, 1000, &mut |_| {}), Some(d));
+        }
+    }
+
+    #[test]
+    fn test_dlog_medium_curve() {
+        let ecdsa = medium_ecdsa();
+        assert_eq!(count_points_bsgs(&ecdsa.elliptic_curve), Some(1048193));
+        let (priv_key, pub_key) = ecdsa.generate_key_pair();
+
+        let mut reports = Vec::new();
+        let d = ecdsa.dlog_bsgs(&pub_key, 1 << 20, &mut |steps| reports.push(steps));
+        assert_eq!(d, Some(priv_key.clone()));
+        assert!(!reports.is_empty());
+        assert!(reports.windows(2).all(|w| w[1] == w[0] + PROGRESS_STEPS));
+
+        assert_eq!(
+            ecdsa.dlog_rho(&pub_key, 1 << 20, &mut |_| {}),
+            Some(priv_key)
+        );
+    }
+
+    #[test]
+    fn test_dlog_work_bound() {
+        let ecdsa = medium_ecdsa();
+        let pub_key = ecdsa.generate_pub_key(&(&ecdsa.q_order - 1u32));
+        assert_eq!(ecdsa.dlog_bsgs(&pub_key, 100, &mut |_| {}), None);
+        assert_eq!(ecdsa.dlog_rho(&pub_key, 100, &mut |_| {}), None);
+    }
+}
@@ -0,0 +1,557 @@
+// DNSSEC signatures with ECDSA (RFC 6605): algorithm 13, P-256 with SHA-256,
+// and 14, P-384 with SHA-384. A DNSKEY holds the public key as x || y and an
+// RRSIG the signature as r || s, each half the curve's size. What is signed
+// (RFC 4034 §3.1.8.1, §6) is the RRSIG RDATA up to the signer's name followed
+// by the RRset in canonical form: owner names lowercased and uncompressed,
+// the TTL set to the RRSIG's original TTL, duplicates dropped and records
+// sorted by their RDATA. An owner with more labels than the RRSIG counts was
+// expanded from a wildcard and is signed as "*." and its closest labels.
+//
+// Records carry their RDATA in wire form, which must already be canonical:
+// names inside RDATA (NS, CNAME, SOA, MX and the like) lowercased and not
+// compressed. Names are given in presentation form without escapes.
+// `dnssec_verify` checks the signature and the RRSIG's fields against the
+// RRset; whether the validity period covers the current time is a separate
+// `Rrsig::is_current`.
+
+use alloc::string::{String, ToString};
+use alloc::{format, vec, vec::Vec};
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::hash::sha256;
+use crate::oid::HashAlgorithm;
+use crate::ECDSA;
+
+pub const ECDSAP256SHA256: u8 = 13;
+pub const ECDSAP384SHA384: u8 = 14;
+
+pub const CLASS_IN: u16 = 1;
+pub const TYPE_A: u16 = 1;
+pub const TYPE_DS: u16 = 43;
+pub const TYPE_RRSIG: u16 = 46;
+pub const TYPE_DNSKEY: u16 = 48;
+
+// DNSKEY flags: a zone key, and one also meant as a secure entry point (KSK)
+pub const ZONE_KEY: u16 = 0x0100;
+pub const SECURE_ENTRY_POINT: u16 = 0x0001;
+
+// DS digest types (RFC 4509, RFC 6605)
+pub const DIGEST_SHA256: u8 = 2;
+pub const DIGEST_SHA384: u8 = 4;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    pub name: String,
+    pub rr_type: u16,
+    pub class: u16,
+    pub ttl: u32,
+    pub rdata: Vec<u8>,
+}
+
+impl Record {
+    pub fn new(name: &str, rr_type: u16, ttl: u32, rdata: &[u8]) -> Record {
+        // A record in class IN
+        Record {
+            name: name.to_string(),
+            rr_type,
+            class: CLASS_IN,
+            ttl,
+            rdata: rdata.to_vec(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rrsig {
+    pub type_covered: u16,
+    pub algorithm: u8,
+    pub labels: u8,
+    pub original_ttl: u32,
+    pub expiration: u32,
+    pub inception: u32,
+    pub key_tag: u16,
+    pub signer_name: String,
+    pub signature: Vec<u8>,
+}
+
+impl Rrsig {
+    pub fn to_rdata(&self) -> Option<Vec<u8>> {
+        let mut rdata = self.signed_prefix()?;
+        rdata.extend(&self.signature);
+        Some(rdata)
+    }
+
+    pub fn from_rdata(rdata: &[u8]) -> Option<Rrsig> {
+        if rdata.len() < 18 {
+            return None;
+        }
+        let (signer_name, end) = read_name(rdata, 18)?;
+        Some(Rrsig {
+            type_covered: u16::from_be_bytes([rdata[0], rdata[1]]),
+            algorithm: rdata[2],
+            labels: rdata[3],
+            original_ttl: u32::from_be_bytes(rdata[4..8].try_into().ok()?),
+            expiration: u32::from_be_bytes(rdata[8..12].try_into().ok()?),
+            inception: u32::from_be_bytes(rdata[12..16].try_into().ok()?),
+            key_tag: u16::from_be_bytes([rdata[16], rdata[17]]),
+            signer_name,
+            signature: rdata[end..].to_vec(),
+        })
+    }
+
+    pub fn is_current(&self, now: u32) -> bool {
+        // inception <= now <= expiration in serial number arithmetic
+        // (RFC 1982), so the period may span the 2106 wrap of 32-bit time
+        let after = |a: u32, b: u32| a.wrapping_sub(b) as i32 >= 0;
+        after(now, self.inception) && after(self.expiration, now)
+    }
+
+    fn signed_prefix(&self) -> Option<Vec<u8>> {
+        // The RDATA up to and including the signer's name
+        let mut prefix = Vec::new();
+        prefix.extend(self.type_covered.to_be_bytes());
+        prefix.push(self.algorithm);
+        prefix.push(self.labels);
+        prefix.extend(self.original_ttl.to_be_bytes());
+        prefix.extend(self.expiration.to_be_bytes());
+        prefix.extend(self.inception.to_be_bytes());
+        prefix.extend(self.key_tag.to_be_bytes());
+        prefix.extend(canonical_name(&self.signer_name)?);
+        Some(prefix)
+    }
+}
+
+pub fn canonical_name(name: &str) -> Option<Vec<u8>> {
+    // Lowercased wire form, ending with the root label; None for an empty
+    // label or a name over 255 bytes
+    let mut wire = Vec::new();
+    for label in labels(name) {
+        if label.is_empty() || label.len() > 63 {
+            return None;
+        }
+        wire.push(label.len() as u8);
+        wire.extend(label.bytes().map(|b| b.to_ascii_lowercase()));
+    }
+    wire.push(0);
+    (wire.len() <= 255).then_some(wire)
+}
+
+pub fn label_count(name: &str) -> u8 {
+    // As the RRSIG Labels field counts: no root, no leading wildcard
+    let labels: Vec<&str> = labels(name).collect();
+    let wildcard = labels.first() == Some(&"*");
+    (labels.len() - wildcard as usize) as u8
+}
+
+pub fn key_tag(dnskey_rdata: &[u8]) -> u16 {
+    // RFC 4034 Appendix B
+    let mut sum: u32 = 0;
+    for (i, byte) in dnskey_rdata.iter().enumerate() {
+        sum += if i % 2 == 0 {
+            (*byte as u32) << 8
+        } else {
+            *byte as u32
+        };
+    }
+    sum += (sum >> 16) & 0xffff;
+    (sum & 0xffff) as u16
+}
+
+pub fn ds_rdata(owner: &str, dnskey_rdata: &[u8], digest_type: u8) -> Option<Vec<u8>> {
+    // The DS record a parent zone publishes for this DNSKEY
+    let algorithm = *dnskey_rdata.get(3)?;
+    let data = [canonical_name(owner)?, dnskey_rdata.to_vec()].concat();
+    let digest = match digest_type {
+        DIGEST_SHA256 => sha256(&data),
+        DIGEST_SHA384 => HashAlgorithm::Sha384.digest(&data),
+        _ => return None,
+    };
+    let mut rdata = key_tag(dnskey_rdata).to_be_bytes().to_vec();
+    rdata.extend([algorithm, digest_type]);
+    rdata.extend(digest);
+    Some(rdata)
+}
+
+pub fn rrsig_signed_data(rrsig: &Rrsig, records: &[Record]) -> Option<Vec<u8>> {
+    // None unless the records form one RRset of the covered type with at
+    // least as many labels in the owner name as the RRSIG counts
+    let first = records.first()?;
+    let owner = canonical_name(&first.name)?;
+    let same_set = records.iter().all(|record| {
+        canonical_name(&record.name).as_ref() == Some(&owner)
+            && record.rr_type == rrsig.type_covered
+            && record.class == first.class
+    });
+    let owner_labels = label_count(&first.name);
+    if !same_set || rrsig.labels > owner_labels {
+        return None;
+    }
+    let owner = if rrsig.labels < owner_labels {
+        let closest: Vec<&str> = labels(&first.name).collect();
+        let closest = closest[closest.len() - rrsig.labels as usize..].join(".");
+        canonical_name(&format!("*.{}", closest))?
+    } else {
+        owner
+    };
+
+    let mut rdatas: Vec<&[u8]> = records.iter().map(|record| &record.rdata[..]).collect();
+    rdatas.sort();
+    rdatas.dedup();
+    let mut data = rrsig.signed_prefix()?;
+    for rdata in rdatas {
+        let length = u16::try_from(rdata.len()).ok()?;
+        data.extend(&owner);
+        data.extend(rrsig.type_covered.to_be_bytes());
+        data.extend(first.class.to_be_bytes());
+        data.extend(rrsig.original_ttl.to_be_bytes());
+        data.extend(length.to_be_bytes());
+        data.extend(rdata);
+    }
+    Some(data)
+}
+
+impl ECDSA {
+    pub fn dnssec_algorithm(&self) -> Option<u8> {
+        if self.is_p256() {
+            Some(ECDSAP256SHA256)
+        } else if self.is_p384() {
+            Some(ECDSAP384SHA384)
+        } else {
+            None
+        }
+    }
+
+    pub fn dnskey_rdata(&self, flags: u16, pub_key: &Point) -> Option<Vec<u8>> {
+        // Flags, protocol 3, algorithm and x || y; None off P-256 and P-384
+        let algorithm = self.dnssec_algorithm()?;
+        let Point::Coor(x, y) = pub_key else {
+            return None;
+        };
+        let mut rdata = flags.to_be_bytes().to_vec();
+        rdata.extend([3, algorithm]);
+        rdata.extend(to_fixed(x, self.field_len()));
+        rdata.extend(to_fixed(y, self.field_len()));
+        Some(rdata)
+    }
+
+    pub fn public_key_from_dnskey(&self, dnskey_rdata: &[u8]) -> Option<Point> {
+        // The key, if the DNSKEY is for this curve's algorithm and x || y is
+        // a valid uncompressed point, coordinates below p included
+        let algorithm = self.dnssec_algorithm()?;
+        let n = self.field_len();
+        if dnskey_rdata.len() != 4 + 2 * n || dnskey_rdata[2] != 3 || dnskey_rdata[3] != algorithm {
+            return None;
+        }
+        self.parse_pub_key(&[&[0x04], &dnskey_rdata[4..]].concat())
+            .ok()
+    }
+
+    pub fn dnssec_sign(
+        &self,
+        records: &[Record],
+        signer_name: &str,
+        key_tag: u16,
+        inception: u32,
+        expiration: u32,
+        priv_key: &BigUint,
+    ) -> Option<Rrsig> {
+        // An RRSIG over the RRset, with the labels, type and original TTL
+        // taken from its records
+        let first = records.first()?;
+        let mut rrsig = Rrsig {
+            type_covered: first.rr_type,
+            algorithm: self.dnssec_algorithm()?,
+            labels: label_count(&first.name),
+            original_ttl: first.ttl,
+            expiration,
+            inception,
+            key_tag,
+            signer_name: signer_name.to_string(),
+            signature: Vec::new(),
+        };
+        if !is_within(&first.name, signer_name) {
+            return None;
+        }
+        let digest = self.dnssec_digest(&rrsig_signed_data(&rrsig, records)?)?;
+        let signature = self.sign_standard(&mut crate::rng(), &digest, priv_key);
+        rrsig.signature = self.encode_signature(&signature);
+        Some(rrsig)
+    }
+
+    pub fn dnssec_verify(&self, records: &[Record], rrsig: &Rrsig, pub_key: &Point) -> bool {
+        // The signature and RFC 4035 §5.3.1: the algorithm is this curve's
+        // and the signer's name is the owner or one of its ancestors
+        let Some(first) = records.first() else {
+            return false;
+        };
+        if self.dnssec_algorithm() != Some(rrsig.algorithm)
+            || !is_within(&first.name, &rrsig.signer_name)
+        {
+            return false;
+        }
+        let digest = rrsig_signed_data(rrsig, records).and_then(|data| self.dnssec_digest(&data));
+        let signature = self.decode_signature(&rrsig.signature);
+        match (digest, signature) {
+            (Some(digest), Some(signature)) => self.verify_standard(&digest, pub_key, &signature),
+            _ => false,
+        }
+    }
+
+    fn dnssec_digest(&self, data: &[u8]) -> Option<Vec<u8>> {
+        match self.dnssec_algorithm()? {
+            ECDSAP256SHA256 => Some(sha256(data)),
+            _ => Some(HashAlgorithm::Sha384.digest(data)),
+        }
+    }
+}
+
+fn labels(name: &str) -> impl Iterator<Item = &str> {
+    // The labels left to right; "." and "" are the root
+    let name = name.strip_suffix('.').unwrap_or(name);
+    name.split('.').filter(move |_| !name.is_empty())
+}
+
+fn is_within(name: &str, zone: &str) -> bool {
+    // Whether `name` is `zone` or below it, ignoring case
+    let (Some(name), Some(zone)) = (canonical_name(name), canonical_name(zone)) else {
+        return false;
+    };
+    let mut offset = 0;
+    while name.len() - offset > zone.len() {
+        offset += name[offset] as usize + 1;
+    }
+    name[offset..] == zone[..]
+}
+
+fn read_name(rdata: &[u8], mut offset: usize) -> Option<(String, usize)> {
+    // An uncompressed wire name, in presentation form with a trailing dot
+    let mut labels = vec![];
+    loop {
+        let len = *rdata.get(offset)? as usize;
+        offset += 1;
+        if len == 0 {
+            break;
+        }
+        if len > 63 {
+            return None;
+        }
+        labels.push(core::str::from_utf8(rdata.get(offset..offset + len)?).ok()?);
+        offset += len;
+    }
+    Some((format!("{}.", labels.join(".")), offset))
+}
+
+fn to_fixed(n: &BigUint, len: usize) -> Vec<u8> {
+    let bytes = n.to_bytes_be();
+    let mut out = vec![0u8; len.saturating_sub(bytes.len())];
+    out.extend(bytes);
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+
+    use crate::curves::{p256, p384, secp256k1};
+
+    // RFC 6605 §6: www.example.net. 3600 IN A 192.0.2.1 signed by the zone
+    // key of example.net.
+    const P256_PRIV: &str = "GU6SnQ/Ou+xC5RumuIUIuJZteXT2z0O/ok1s38Et6mQ=";
+    const P256_DNSKEY: &str = "GojIhhXUN/u4v54ZQqGSnyhWJwaubCvTmeexv7bR6edb\
+                               krSqQpF64cYbcB7wNcP+e+MAnLr+Wi9xMWyQLc8NAA==";
+    const P256_DS: &str = "b4c8c1fe2e7477127b27115656ad6256f424625bf5c1e2770ce6d6e37df61d17";
+    const P256_RRSIG: &str = "qx6wLYqmh+l9oCKTN6qIc+bw6ya+KJ8oMz0YP107epXA\
+                              yGmt+3SNruPFKG7tZoLBLlUzGGus7ZwmwWep666VCw==";
+    const P384_PRIV: &str = "WURgWHCcYIYUPWgeLmiPY2DJJk02vgrmTfitxgqcL4vwW7BOrbawVmVe0d9V94SR";
+    const P384_DNSKEY: &str = "xKYaNhWdGOfJ+nPrL8/arkwf2EY3MDJ+SErKivBVSum1\
+                               w/egsXvSADtNJhyem5RCOpgQ6K8X1DRSEkrbYQ+OB+v8\
+                               /uX45NBwY8rp65F6Glur8I/mlVNgF6W/qTI37m40";
+    const P384_DS: &str = "72d7b62976ce06438e9c0bf319013cf801f09ecc84b8d7e9\
+                           495f27e305c6a9b0563a9b5f4d288405c3008a946df983d6";
+
+    fn www() -> Vec<Record> {
+        vec![Record::new(
+            "www.example.net.",
+            TYPE_A,
+            3600,
+            &[192, 0, 2, 1],
+        )]
+    }
+
+    fn zone_key(ecdsa: &ECDSA, priv_key: &str) -> (BigUint, Vec<u8>) {
+        let priv_key = BigUint::from_bytes_be(&STANDARD.decode(priv_key).unwrap());
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let dnskey = ecdsa
+            .dnskey_rdata(ZONE_KEY | SECURE_ENTRY_POINT, &pub_key)
+            .unwrap();
+        (priv_key, dnskey)
+    }
+
+    #[test]
+    fn test_rfc6605_p256() {
+        let ecdsa = p256();
+        let (priv_key, dnskey) = zone_key(&ecdsa, P256_PRIV);
+        assert_eq!(dnskey[4..], STANDARD.decode(P256_DNSKEY).unwrap());
+        assert_eq!(key_tag(&dnskey), 55648);
+        let ds = ds_rdata("example.net.", &dnskey, DIGEST_SHA256).unwrap();
+        assert_eq!(ds[..4], [0xd9, 0x60, 13, 2]);
+        assert_eq!(hex::encode(&ds[4..]), P256_DS);
+
+        let pub_key = ecdsa.public_key_from_dnskey(&dnskey).unwrap();
+        let rrsig = Rrsig {
+            type_covered: TYPE_A,
+            algorithm: ECDSAP256SHA256,
+            labels: 3,
+            original_ttl: 3600,
+            expiration: 1284026679, // 20100909100439
+            inception: 1281607479,  // 20100812100439
+            key_tag: 55648,
+            signer_name: "example.net.".into(),
+            signature: STANDARD.decode(P256_RRSIG).unwrap(),
+        };
+        assert!(ecdsa.dnssec_verify(&www(), &rrsig, &pub_key));
+        assert_eq!(
+            Rrsig::from_rdata(&rrsig.to_rdata().unwrap()),
+            Some(rrsig.clone())
+        );
+
+        let signed = ecdsa
+            .dnssec_sign(
+                &www(),
+                "example.net.",
+                55648,
+                1281607479,
+                1284026679,
+                &priv_key,
+            )
+            .unwrap();
+        assert_eq!(
+            Rrsig {
+                signature: rrsig.signature.clone(),
+                ..signed.clone()
+            },
+            rrsig
+        );
+        assert!(ecdsa.dnssec_verify(&www(), &signed, &pub_key));
+
+        let other = vec![Record::new(
+            "www.example.net.",
+            TYPE_A,
+            3600,
+            &[192, 0, 2, 2],
+        )];
+        assert!(!ecdsa.dnssec_verify(&other, &rrsig, &pub_key));
+        let outside = Rrsig {
+            signer_name: "example.com.".into(),
+            ..rrsig.clone()
+        };
+        assert!(!ecdsa.dnssec_verify(&www(), &outside, &pub_key));
+    }
+
+    #[test]
+    fn test_rfc6605_p384() {
+        let ecdsa = p384();
+        let (priv_key, dnskey) = zone_key(&ecdsa, P384_PRIV);
+        assert_eq!(dnskey[4..], STANDARD.decode(P384_DNSKEY).unwrap());
+        assert_eq!(key_tag(&dnskey), 10771);
+        let ds = ds_rdata("example.net.", &dnskey, DIGEST_SHA384).unwrap();
+        assert_eq!(hex::encode(&ds[4..]), P384_DS);
+
+        let pub_key = ecdsa.public_key_from_dnskey(&dnskey).unwrap();
+        let rrsig = ecdsa
+            .dnssec_sign(
+                &www(),
+                "example.net.",
+                10771,
+                1281608425,
+                1284027625,
+                &priv_key,
+            )
+            .unwrap();
+        assert_eq!(rrsig.algorithm, ECDSAP384SHA384);
+        assert_eq!(rrsig.signature.len(), 96);
+        assert!(ecdsa.dnssec_verify(&www(), &rrsig, &pub_key));
+
+        // A P-256 key or DNSKEY is not an algorithm 14 one
+        assert!(!p256().dnssec_verify(&www(), &rrsig, &pub_key));
+        assert!(ecdsa
+            .public_key_from_dnskey(&zone_key(&p256(), P256_PRIV).1)
+            .is_none());
+        assert!(secp256k1().dnssec_algorithm().is_none());
+
+        // Coordinates at or above p are rejected, not reduced
+        let oversized = [&dnskey[..4], &[0xff; 96]].concat();
+        assert!(ecdsa.public_key_from_dnskey(&oversized).is_none());
+    }
+
+    #[test]
+    fn test_canonical_rrset() {
+        let ecdsa = p256();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let rrset = [
+            Record::new("Mail.Example.NET", TYPE_A, 300, &[192, 0, 2, 7]),
+            Record::new("mail.example.net.", TYPE_A, 300, &[192, 0, 2, 1]),
+        ];
+        let rrsig = ecdsa
+            .dnssec_sign(&rrset, "example.net.", 1, 0, u32::MAX, &priv_key)
+            .unwrap();
+
+        // Order, case, duplicates and the TTLs served are not signed
+        let reordered = [
+            Record::new("mail.example.net.", TYPE_A, 17, &[192, 0, 2, 1]),
+            Record::new("mail.example.net.", TYPE_A, 17, &[192, 0, 2, 7]),
+            Record::new("MAIL.example.net.", TYPE_A, 17, &[192, 0, 2, 1]),
+        ];
+        assert_eq!(
+            rrsig_signed_data(&rrsig, &rrset),
+            rrsig_signed_data(&rrsig, &reordered)
+        );
+        assert!(ecdsa.dnssec_verify(&reordered, &rrsig, &pub_key));
+        let mixed = [
+            rrset[0].clone(),
+            Record::new("example.net.", TYPE_A, 300, &[1; 4]),
+        ];
+        assert!(rrsig_signed_data(&rrsig, &mixed).is_none());
+
+        // A wildcard answer verifies under the wildcard's RRSIG
+        let wildcard = [Record::new("*.example.net.", TYPE_A, 300, &[192, 0, 2, 9])];
+        let rrsig = ecdsa
+            .dnssec_sign(&wildcard, "example.net.", 1, 0, u32::MAX, &priv_key)
+            .unwrap();
+        assert_eq!(rrsig.labels, 2);
+        let expanded = [Record::new(
+            "a.b.example.net.",
+            TYPE_A,
+            300,
+            &[192, 0, 2, 9],
+        )];
+        assert!(ecdsa.dnssec_verify(&expanded, &rrsig, &pub_key));
+
+        assert_eq!(label_count("."), 0);
+        assert_eq!(label_count("*.example.net"), 2);
+        assert!(canonical_name("a..example.").is_none());
+    }
+
+    #[test]
+    fn test_rrsig_validity() {
+        let rrsig = Rrsig {
+            type_covered: TYPE_DNSKEY,
+            algorithm: ECDSAP256SHA256,
+            labels: 2,
+            original_ttl: 3600,
+            expiration: 0x0000_1000,
+            inception: 0xffff_f000,
+            key_tag: 1,
+            signer_name: "example.net.".into(),
+            signature: vec![],
+        };
+        assert!(rrsig.is_current(0xffff_ffff));
+        assert!(rrsig.is_current(0x0000_0800));
+        assert!(!rrsig.is_current(0x0000_2000));
+        assert!(!rrsig.is_current(0xffff_e000));
+    }
+}
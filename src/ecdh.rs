@@ -0,0 +1,251 @@
+// Elliptic curve Diffie-Hellman: both parties compute S = d_1 * B_2 = d_2 * B_1.
+// The peer key must lie in the subgroup of order q, which rules out small
+// subgroup points on curves with a cofactor, and on the curve itself, since the
+// addition formulas never use b and would happily compute dB on whichever curve
+// y² = x³ + ax + b' the point lies on (see `attacks::invalid_curve`). The raw
+// x(S) is never handed out, only keys derived from it with HKDF-SHA256.
+//
+// Public keys arriving as bytes go through `parse_pub_key`, which runs the same
+// checks and also refuses encodings that are not canonical: coordinates of p
+// or more, which would name the same point as their value mod p, and hybrid
+// encodings, unless the caller opts in with `parse_pub_key_allow_hybrid`.
+
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::encoding::{to_fixed_bytes, Sec1Point};
+use crate::hash::hkdf_sha256;
+use crate::secret::SecretScalar;
+use crate::ECDSA;
+
+pub struct SharedSecret {
+    x: Vec<u8>,
+}
+
+// Why `check_pub_key` or `parse_pub_key` refused a key
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum PubKeyError {
+    // Not a SEC1 point of this curve's length, or a hybrid point whose prefix
+    // disagrees with the parity of y
+    Malformed,
+    HybridEncoding,
+    CoordinateOutOfRange,
+    Identity,
+    NotOnCurve,
+    NotInSubgroup,
+}
+
+impl ECDSA {
+    pub fn is_valid_pub_key(&self, pub_key: &Point) -> bool {
+        self.check_pub_key(pub_key).is_ok()
+    }
+
+    pub fn check_pub_key(&self, pub_key: &Point) -> Result<(), PubKeyError> {
+        // B != O, B on the curve and qB = O
+        if *pub_key == Point::Identity {
+            Err(PubKeyError::Identity)
+        } else if !self.elliptic_curve.is_on_curve(pub_key) {
+            Err(PubKeyError::NotOnCurve)
+        } else if self.mul_vartime(pub_key, &self.q_order) != Point::Identity {
+            Err(PubKeyError::NotInSubgroup)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn parse_pub_key(&self, bytes: &[u8]) -> Result<Point, PubKeyError> {
+        self.parse_pub_key_with(bytes, false)
+    }
+
+    pub fn parse_pub_key_allow_hybrid(&self, bytes: &[u8]) -> Result<Point, PubKeyError> {
+        self.parse_pub_key_with(bytes, true)
+    }
+
+    fn parse_pub_key_with(&self, bytes: &[u8], allow_hybrid: bool) -> Result<Point, PubKeyError> {
+        let p = &self.elliptic_curve.p;
+        let coordinate = |bytes: &[u8]| {
+            let value = BigUint::from_bytes_be(bytes);
+            if &value < p {
+                Ok(value)
+            } else {
+                Err(PubKeyError::CoordinateOutOfRange)
+            }
+        };
+        let point = match Sec1Point::parse(bytes, self.field_len()) {
+            None => return Err(PubKeyError::Malformed),
+            Some(Sec1Point::Identity) => return Err(PubKeyError::Identity),
+            Some(Sec1Point::Hybrid { .. }) if !allow_hybrid => {
+                return Err(PubKeyError::HybridEncoding)
+            }
+            Some(Sec1Point::Hybrid { x, y, y_is_odd }) => {
+                let y = coordinate(y)?;
+                if y.bit(0) != y_is_odd {
+                    return Err(PubKeyError::Malformed);
+                }
+                Point::Coor(coordinate(x)?, y)
+            }
+            Some(Sec1Point::Uncompressed { x, y }) => Point::Coor(coordinate(x)?, coordinate(y)?),
+            Some(Sec1Point::Compressed { x, .. }) => {
+                // With x < p, only a missing square root can fail below
+                coordinate(x)?;
+                self.decode_point(bytes).ok_or(PubKeyError::NotOnCurve)?
+            }
+        };
+        self.check_pub_key(&point)?;
+        Ok(point)
+    }
+
+    pub fn diffie_hellman(&self, priv_key: &BigUint, pub_key: &Point) -> Option<SharedSecret> {
+        // S = dB, keeping only the x coordinate
+        assert!(
+            priv_key < &self.q_order,
+            "Private Key is Bigger than the Ec group"
+        );
+        if !self.is_valid_pub_key(pub_key) {
+            return None;
+        }
+
+        match self.mul_secret(pub_key, &SecretScalar::new(priv_key.clone())) {
+            Point::Coor(x, _) => Some(SharedSecret {
+                x: to_fixed_bytes(&x, self.field_len()),
+            }),
+            Point::Identity => None,
+        }
+    }
+}
+
+impl SharedSecret {
+    pub(crate) fn from_bytes(x: Vec<u8>) -> SharedSecret {
+        // A DH output computed elsewhere, such as by X25519
+        SharedSecret { x }
+    }
+
+    pub fn derive_key(&self, salt: &[u8], info: &[u8], len: usize) -> Option<Vec<u8>> {
+        // None past the HKDF-SHA256 limit of 255 blocks
+        (len <= 255 * 32).then(|| hkdf_sha256(salt, &self.x, info, len))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+    use ec_generic::EllipticCurve;
+
+    use crate::analysis::find_point_of_order;
+
+    #[test]
+    fn test_diffie_hellman() {
+        let ecdsa = toy_curve();
+        let (alice_priv, alice_pub) = ecdsa.generate_key_pair();
+        let (bob_priv, bob_pub) = ecdsa.generate_key_pair();
+
+        let alice_secret = ecdsa.diffie_hellman(&alice_priv, &bob_pub).unwrap();
+        let bob_secret = ecdsa.diffie_hellman(&bob_priv, &alice_pub).unwrap();
+
+        assert_eq!(
+            alice_secret.derive_key(b"salt", b"session", 32),
+            bob_secret.derive_key(b"salt", b"session", 32)
+        );
+        assert_ne!(
+            alice_secret.derive_key(b"salt", b"session", 32),
+            alice_secret.derive_key(b"salt", b"other session", 32)
+        );
+        assert_eq!(
+            alice_secret
+                .derive_key(b"salt", b"session", 255 * 32)
+                .map(|key| key.len()),
+            Some(8160)
+        );
+        assert!(alice_secret.derive_key(b"salt", b"session", 8161).is_none());
+    }
+
+    #[test]
+    fn test_diffie_hellman_invalid_pub_key() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+
+        let off_curve = Point::Coor(BigUint::from(5u32), BigUint::from(2u32));
+        assert!(ecdsa.diffie_hellman(&priv_key, &off_curve).is_none());
+        assert!(ecdsa.diffie_hellman(&priv_key, &Point::Identity).is_none());
+        assert_eq!(
+            ecdsa.check_pub_key(&off_curve),
+            Err(PubKeyError::NotOnCurve)
+        );
+        assert_eq!(
+            ecdsa.check_pub_key(&Point::Identity),
+            Err(PubKeyError::Identity)
+        );
+        assert_eq!(ecdsa.check_pub_key(&ecdsa.a_gen), Ok(()));
+    }
+
+    #[test]
+    fn test_parse_pub_key() {
+        let ecdsa = toy_curve();
+        assert_eq!(ecdsa.parse_pub_key(&[0x04, 5, 1]), Ok(ecdsa.a_gen.clone()));
+        assert_eq!(ecdsa.parse_pub_key(&[0x03, 5]), Ok(ecdsa.a_gen.clone()));
+        assert_eq!(ecdsa.parse_pub_key(&[0x00]), Err(PubKeyError::Identity));
+        assert_eq!(ecdsa.parse_pub_key(&[0x04, 5]), Err(PubKeyError::Malformed));
+        assert_eq!(
+            ecdsa.parse_pub_key(&[0x05, 5, 1]),
+            Err(PubKeyError::Malformed)
+        );
+        assert_eq!(
+            ecdsa.parse_pub_key(&[0x04, 5, 2]),
+            Err(PubKeyError::NotOnCurve)
+        );
+        // x + p and y + p fit in the byte but are not canonical
+        assert_eq!(
+            ecdsa.parse_pub_key(&[0x04, 22, 1]),
+            Err(PubKeyError::CoordinateOutOfRange)
+        );
+        assert_eq!(
+            ecdsa.parse_pub_key(&[0x04, 5, 18]),
+            Err(PubKeyError::CoordinateOutOfRange)
+        );
+        assert_eq!(
+            ecdsa.parse_pub_key(&[0x03, 22]),
+            Err(PubKeyError::CoordinateOutOfRange)
+        );
+
+        // Hybrid only on request, and with the right parity
+        assert_eq!(
+            ecdsa.parse_pub_key(&[0x07, 5, 1]),
+            Err(PubKeyError::HybridEncoding)
+        );
+        assert_eq!(
+            ecdsa.parse_pub_key_allow_hybrid(&[0x07, 5, 1]),
+            Ok(ecdsa.a_gen.clone())
+        );
+        assert_eq!(
+            ecdsa.parse_pub_key_allow_hybrid(&[0x06, 5, 1]),
+            Err(PubKeyError::Malformed)
+        );
+    }
+
+    #[test]
+    fn test_check_pub_key_subgroup() {
+        // y² = x³ + x + 1 over F_101 has 105 = 3 · 5 · 7 points; with the
+        // subgroup of order 7, a point of order 3 is on the curve but outside it
+        let elliptic_curve = EllipticCurve {
+            a: BigUint::from(1u32),
+            b: BigUint::from(1u32),
+            p: BigUint::from(101u32),
+        };
+        let a_gen = find_point_of_order(&elliptic_curve, 105, 7).unwrap();
+        let order_3 = find_point_of_order(&elliptic_curve, 105, 3).unwrap();
+        let ecdsa = ECDSA::new(elliptic_curve, a_gen, BigUint::from(7u32)).unwrap();
+        assert_eq!(
+            ecdsa.check_pub_key(&order_3),
+            Err(PubKeyError::NotInSubgroup)
+        );
+        assert_eq!(
+            ecdsa.parse_pub_key(&ecdsa.encode_point(&order_3)),
+            Err(PubKeyError::NotInSubgroup)
+        );
+        assert!(ecdsa
+            .diffie_hellman(&BigUint::from(3u32), &order_3)
+            .is_none());
+    }
+}
@@ -0,0 +1,102 @@
+// ECIES hybrid encryption to a public key B = dA: an ephemeral key R = kA,
+// the ECDH shared secret x(kB) = x(dR), HKDF-SHA256 for the AEAD key and nonce,
+// and ChaCha20-Poly1305 for the payload. The wire format is
+// version || R (compressed) || ciphertext || tag, with version || R as AAD.
+
+use alloc::{vec, vec::Vec};
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::aead::{self, KEY_LEN, NONCE_LEN, TAG_LEN};
+use crate::ecdh::SharedSecret;
+use crate::secret::SecretScalar;
+use crate::ECDSA;
+
+pub const VERSION: u8 = 0x01;
+
+impl ECDSA {
+    pub fn ecies_encrypt(&self, pub_key: &Point, plaintext: &[u8], k_random: &BigUint) -> Vec<u8> {
+        // R = kA, S = kB
+        assert!(k_random < &self.q_order, "'K' is Bigger than the Ec group");
+        let shared = self
+            .diffie_hellman(k_random, pub_key)
+            .expect("Public Key is not a valid point of the curve");
+
+        let r_point = self.mul_secret(&self.a_gen, &SecretScalar::new(k_random.clone()));
+        let mut header = vec![VERSION];
+        header.extend(self.encode_point(&r_point));
+
+        let (key, nonce) = self.ecies_derive_keys(&header, &shared);
+        let sealed = aead::seal(&key, &nonce, &header, plaintext);
+        [header, sealed].concat()
+    }
+
+    pub fn ecies_decrypt(&self, priv_key: &BigUint, bytes: &[u8]) -> Option<Vec<u8>> {
+        // S = dR
+        let header_len = 2 + self.field_len();
+        if bytes.len() < header_len + TAG_LEN || bytes[0] != VERSION {
+            return None;
+        }
+        let (header, sealed) = bytes.split_at(header_len);
+        let r_point = self.decode_point(&header[1..])?;
+        let shared = self.diffie_hellman(priv_key, &r_point)?;
+        let (key, nonce) = self.ecies_derive_keys(header, &shared);
+        aead::open(&key, &nonce, header, sealed)
+    }
+
+    fn ecies_derive_keys(
+        &self,
+        header: &[u8],
+        shared: &SharedSecret,
+    ) -> ([u8; KEY_LEN], [u8; NONCE_LEN]) {
+        // key || nonce = HKDF(salt = "", IKM = x(S), info = "ECIES" || version || R)
+        let info = [b"ECIES", header].concat();
+        let okm = shared.derive_key(&[], &info, KEY_LEN + NONCE_LEN).unwrap();
+        (
+            okm[..KEY_LEN].try_into().unwrap(),
+            okm[KEY_LEN..].try_into().unwrap(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+
+        let k_random = ecdsa.generate_random_number_less_than(&ecdsa.q_order);
+        let ciphertext = ecdsa.ecies_encrypt(&pub_key, b"Bob -> 1 BTC -> Alice", &k_random);
+
+        assert_eq!(ciphertext[0], VERSION);
+        assert_eq!(
+            ecdsa.ecies_decrypt(&priv_key, &ciphertext),
+            Some(b"Bob -> 1 BTC -> Alice".to_vec())
+        );
+        assert_eq!(ecdsa.ecies_decrypt(&BigUint::from(8u32), &ciphertext), None);
+    }
+
+    #[test]
+    fn test_decrypt_tampered() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let ciphertext = ecdsa.ecies_encrypt(&pub_key, b"attack at dawn", &BigUint::from(3u32));
+
+        let mut wrong_version = ciphertext.clone();
+        wrong_version[0] = 0x02;
+        assert_eq!(ecdsa.ecies_decrypt(&priv_key, &wrong_version), None);
+
+        let mut wrong_body = ciphertext.clone();
+        let last = wrong_body.len() - 1;
+        wrong_body[last] ^= 1;
+        assert_eq!(ecdsa.ecies_decrypt(&priv_key, &wrong_body), None);
+
+        assert_eq!(ecdsa.ecies_decrypt(&priv_key, &ciphertext[..4]), None);
+    }
+}
@@ -0,0 +1,145 @@
+// EC ElGamal encryption to a public key B = dA. Points are encrypted as
+// (rA, M + rB); small scalars use the exponential variant M = mA, which makes
+// ciphertexts additively homomorphic at the cost of a discrete log search on
+// decryption, so it is only meant for small values such as vote counts.
+
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::secret::SecretScalar;
+use crate::ECDSA;
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Ciphertext {
+    c1: Point,
+    c2: Point,
+}
+
+impl ECDSA {
+    pub fn elgamal_encrypt_point(
+        &self,
+        pub_key: &Point,
+        message: &Point,
+        k_random: &BigUint,
+    ) -> Ciphertext {
+        // C1 = kA, C2 = M + kB
+        assert!(k_random < &self.q_order, "'K' is Bigger than the Ec group");
+        assert!(
+            self.elliptic_curve.is_on_curve(pub_key) && *pub_key != Point::Identity,
+            "Public Key is not a valid point of the curve"
+        );
+
+        let k = SecretScalar::new(k_random.clone());
+        let c1 = self.mul_secret(&self.a_gen, &k);
+        let c2 = self.add_points(message, &self.mul_secret(pub_key, &k));
+        Ciphertext { c1, c2 }
+    }
+
+    pub fn elgamal_decrypt_point(&self, priv_key: &BigUint, ciphertext: &Ciphertext) -> Point {
+        // M = C2 - dC1
+        let d = SecretScalar::new(priv_key.clone());
+        self.sub_points(&ciphertext.c2, &self.mul_secret(&ciphertext.c1, &d))
+    }
+
+    pub fn elgamal_encrypt(
+        &self,
+        pub_key: &Point,
+        message: &BigUint,
+        k_random: &BigUint,
+    ) -> Ciphertext {
+        let m_point = self.mul_secret(&self.a_gen, &SecretScalar::new(message.clone()));
+        self.elgamal_encrypt_point(pub_key, &m_point, k_random)
+    }
+
+    pub fn elgamal_decrypt(
+        &self,
+        priv_key: &BigUint,
+        ciphertext: &Ciphertext,
+        max: &BigUint,
+    ) -> Option<BigUint> {
+        // Recovers m from mA by trying every m in [0, max]
+        let m_point = self.elgamal_decrypt_point(priv_key, ciphertext);
+        let mut m = BigUint::from(0u32);
+        let mut candidate = Point::Identity;
+        while &m <= max {
+            if candidate == m_point {
+                return Some(m);
+            }
+            candidate = self.add_points(&candidate, &self.a_gen);
+            m += 1u32;
+        }
+        None
+    }
+
+    pub fn elgamal_rerandomize(
+        &self,
+        pub_key: &Point,
+        ciphertext: &Ciphertext,
+        k_random: &BigUint,
+    ) -> Ciphertext {
+        // (C1 + kA, C2 + kB) decrypts to the same message but is unlinkable to the original
+        let zero = self.elgamal_encrypt_point(pub_key, &Point::Identity, k_random);
+        ciphertext.add(self, &zero)
+    }
+}
+
+impl Ciphertext {
+    pub fn add(&self, ecdsa: &ECDSA, other: &Ciphertext) -> Ciphertext {
+        // Enc(m1) + Enc(m2) = Enc(m1 + m2)
+        Ciphertext {
+            c1: ecdsa.add_points(&self.c1, &other.c1),
+            c2: ecdsa.add_points(&self.c2, &other.c2),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+
+        let k_random = ecdsa.generate_random_number_less_than(&ecdsa.q_order);
+        let ciphertext = ecdsa.elgamal_encrypt(&pub_key, &BigUint::from(4u32), &k_random);
+        let message = ecdsa.elgamal_decrypt(&priv_key, &ciphertext, &BigUint::from(10u32));
+
+        assert_eq!(message, Some(BigUint::from(4u32)));
+    }
+
+    #[test]
+    fn test_homomorphic_addition() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+
+        let vote1 = ecdsa.elgamal_encrypt(&pub_key, &BigUint::from(1u32), &BigUint::from(3u32));
+        let vote2 = ecdsa.elgamal_encrypt(&pub_key, &BigUint::from(1u32), &BigUint::from(9u32));
+        let vote3 = ecdsa.elgamal_encrypt(&pub_key, &BigUint::from(0u32), &BigUint::from(14u32));
+        let tally = vote1.add(&ecdsa, &vote2).add(&ecdsa, &vote3);
+
+        let result = ecdsa.elgamal_decrypt(&priv_key, &tally, &BigUint::from(3u32));
+        assert_eq!(result, Some(BigUint::from(2u32)));
+    }
+
+    #[test]
+    fn test_rerandomize() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+
+        let ciphertext =
+            ecdsa.elgamal_encrypt(&pub_key, &BigUint::from(4u32), &BigUint::from(3u32));
+        let rerandomized = ecdsa.elgamal_rerandomize(&pub_key, &ciphertext, &BigUint::from(5u32));
+
+        assert_ne!(rerandomized, ciphertext);
+        assert_eq!(
+            ecdsa.elgamal_decrypt(&priv_key, &rerandomized, &BigUint::from(10u32)),
+            Some(BigUint::from(4u32))
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use alloc::{format, string::String, vec, vec::Vec};
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::ECDSA;
+
+// A SEC1 point parsed in place, with the coordinates borrowed from the input.
+// Only the layout is checked; `decode_point` checks the point itself. Hybrid
+// points (0x06 or 0x07, the parity of y || x || y) are recognised so that
+// parsers can name them, but `decode_point` does not accept them
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Sec1Point<'a> {
+    Identity,
+    Compressed {
+        x: &'a [u8],
+        y_is_odd: bool,
+    },
+    Uncompressed {
+        x: &'a [u8],
+        y: &'a [u8],
+    },
+    Hybrid {
+        x: &'a [u8],
+        y: &'a [u8],
+        y_is_odd: bool,
+    },
+}
+
+// Ways to print a point, to compare against other libraries' output:
+// "(x, y)" in decimal or in 0x-prefixed hex padded to the field size, the
+// compressed SEC1 encoding in hex, or x alone in padded hex, as BIP 340 keys
+// are written. The identity prints as "identity", or "00" in SEC1
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum PointFormat {
+    AffineDecimal,
+    AffineHex,
+    CompressedHex,
+    XOnlyHex,
+}
+
+impl<'a> Sec1Point<'a> {
+    pub fn parse(bytes: &'a [u8], field_len: usize) -> Option<Sec1Point<'a>> {
+        match bytes.split_first()? {
+            (0x00, []) => Some(Sec1Point::Identity),
+            (0x02 | 0x03, x) if x.len() == field_len => Some(Sec1Point::Compressed {
+                x,
+                y_is_odd: bytes[0] == 0x03,
+            }),
+            (0x04, xy) if xy.len() == 2 * field_len => {
+                let (x, y) = xy.split_at(field_len);
+                Some(Sec1Point::Uncompressed { x, y })
+            }
+            (0x06 | 0x07, xy) if xy.len() == 2 * field_len => {
+                let (x, y) = xy.split_at(field_len);
+                Some(Sec1Point::Hybrid {
+                    x,
+                    y,
+                    y_is_odd: bytes[0] == 0x07,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl ECDSA {
+    pub fn field_len(&self) -> usize {
+        self.elliptic_curve.p.bits().div_ceil(8) as usize
+    }
+
+    pub fn scalar_len(&self) -> usize {
+        self.q_order.bits().div_ceil(8) as usize
+    }
+
+    pub fn encode_point(&self, point: &Point) -> Vec<u8> {
+        // Compressed SEC1 form: 0x02 (even y) or 0x03 (odd y) || x,
+        // and a single 0x00 byte for the identity
+        match point {
+            Point::Coor(x, y) => {
+                let mut bytes = vec![if y.bit(0) { 0x03 } else { 0x02 }];
+                bytes.extend(to_fixed_bytes(x, self.field_len()));
+                bytes
+            }
+            Point::Identity => vec![0x00],
+        }
+    }
+
+    pub fn encode_point_uncompressed(&self, point: &Point) -> Vec<u8> {
+        // Uncompressed SEC1 form: 0x04 || x || y
+        match point {
+            Point::Coor(x, y) => {
+                let mut bytes = vec![0x04];
+                bytes.extend(to_fixed_bytes(x, self.field_len()));
+                bytes.extend(to_fixed_bytes(y, self.field_len()));
+                bytes
+            }
+            Point::Identity => vec![0x00],
+        }
+    }
+
+    pub fn format_as(&self, point: &Point, format: PointFormat) -> String {
+        let hex = |n: &BigUint| hex::encode(to_fixed_bytes(n, self.field_len()));
+        match (point, format) {
+            (_, PointFormat::CompressedHex) => hex::encode(self.encode_point(point)),
+            (Point::Identity, _) => String::from("identity"),
+            (Point::Coor(x, y), PointFormat::AffineDecimal) => format!("({}, {})", x, y),
+            (Point::Coor(x, y), PointFormat::AffineHex) => {
+                format!("(0x{}, 0x{})", hex(x), hex(y))
+            }
+            (Point::Coor(x, _), PointFormat::XOnlyHex) => hex(x),
+        }
+    }
+
+    pub fn decode_point(&self, bytes: &[u8]) -> Option<Point> {
+        let p = &self.elliptic_curve.p;
+        match Sec1Point::parse(bytes, self.field_len())? {
+            Sec1Point::Identity => Some(Point::Identity),
+            Sec1Point::Uncompressed { x, y } => {
+                let point = Point::Coor(BigUint::from_bytes_be(x), BigUint::from_bytes_be(y));
+                let in_field = matches!(&point, Point::Coor(x, y) if x < p && y < p);
+                if !in_field || !self.elliptic_curve.is_on_curve(&point) {
+                    return None;
+                }
+                Some(point)
+            }
+            Sec1Point::Compressed { x, y_is_odd } => {
+                let x = BigUint::from_bytes_be(x);
+                if &x >= p {
+                    return None;
+                }
+                let y = self.y_for_x(&x)?;
+                let y = if y.bit(0) == y_is_odd { y } else { (p - y) % p };
+                Some(Point::Coor(x, y))
+            }
+            Sec1Point::Hybrid { .. } => None,
+        }
+    }
+}
+
+pub fn to_fixed_bytes(n: &BigUint, len: usize) -> Vec<u8> {
+    // Big-endian, left padded with zeros up to `len` bytes
+    let bytes = n.to_bytes_be();
+    assert!(bytes.len() <= len, "Integer does not fit in {} bytes", len);
+    let mut out = vec![0u8; len - bytes.len()];
+    out.extend(bytes);
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_encode_decode_point() {
+        let ecdsa = toy_curve();
+
+        for k in 1u32..20 {
+            let point = ecdsa.mul_vartime(&ecdsa.a_gen, &BigUint::from(k));
+            let bytes = ecdsa.encode_point(&point);
+            assert_eq!(ecdsa.decode_point(&bytes), Some(point.clone()));
+            let bytes = ecdsa.encode_point_uncompressed(&point);
+            assert_eq!(ecdsa.decode_point(&bytes), Some(point));
+        }
+        assert_eq!(ecdsa.decode_point(&[0x04, 0x05, 0x02]), None);
+        assert_eq!(ecdsa.decode_point(&[0x04, 0x05]), None);
+
+        let bytes = [0x03, 0x05];
+        assert_eq!(
+            Sec1Point::parse(&bytes, 1),
+            Some(Sec1Point::Compressed {
+                x: &bytes[1..],
+                y_is_odd: true
+            })
+        );
+        assert_eq!(Sec1Point::parse(&[0x00], 1), Some(Sec1Point::Identity));
+        assert_eq!(Sec1Point::parse(&[0x00, 0x00], 1), None);
+        assert_eq!(Sec1Point::parse(&[0x05, 0x05], 1), None);
+        let bytes = [0x06, 0x05, 0x01];
+        assert!(matches!(
+            Sec1Point::parse(&bytes, 1),
+            Some(Sec1Point::Hybrid {
+                y_is_odd: false,
+                ..
+            })
+        ));
+        assert_eq!(ecdsa.decode_point(&bytes), None);
+    }
+
+    #[test]
+    fn test_format_as() {
+        let ecdsa = crate::curves::p256();
+        let gx = "6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296";
+        let gy = "4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5";
+        let g = &ecdsa.a_gen;
+        assert_eq!(
+            ecdsa.format_as(g, PointFormat::AffineHex),
+            format!("(0x{}, 0x{})", gx, gy)
+        );
+        assert_eq!(
+            ecdsa.format_as(g, PointFormat::CompressedHex),
+            format!("03{}", gx)
+        );
+        assert_eq!(ecdsa.format_as(g, PointFormat::XOnlyHex), gx);
+        assert!(ecdsa.format_as(g, PointFormat::AffineDecimal).starts_with(
+            "(48439561293906451759052585252797914202762949526041747995844080717082404635286, "
+        ));
+
+        // Leading zero bytes are kept
+        let small = Point::Coor(BigUint::from(1u32), BigUint::from(2u32));
+        assert_eq!(
+            ecdsa.format_as(&small, PointFormat::XOnlyHex),
+            format!("{:0>64}", "1")
+        );
+        assert_eq!(
+            ecdsa.format_as(&Point::Identity, PointFormat::CompressedHex),
+            "00"
+        );
+        assert_eq!(
+            ecdsa.format_as(&Point::Identity, PointFormat::AffineDecimal),
+            "identity"
+        );
+    }
+}
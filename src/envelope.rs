@@ -0,0 +1,376 @@
+// Signed messages that carry their own metadata, so what is signed is never a
+// bare hash: the payload itself or its SHA-256 and, for each signer, the JOSE
+// algorithm name, the key id (SHA-256 of the DER SubjectPublicKeyInfo, what
+// `rs_ecc fingerprint` prints) and the creation time in Unix seconds. A
+// message holds any number of independent signatures over the same payload,
+// for approvals that need several officers. Each is standard ECDSA over
+// SHA-256 of the canonical encoding
+//
+//   DST || byte kind || string payload || string algorithm || string key id
+//      || uint64 created
+//
+// with SSH style length prefixed strings, kind being 0 for an inline payload
+// and 1 for a digest. `to_bytes` writes the payload, the number of signers
+// as a uint32, then each signer's fields and DER signature, without the DST.
+// A `VerificationPolicy` accepts a message once m of its n trusted keys have
+// valid signatures on it; signatures from other keys count for nothing.
+
+use alloc::{string::String, vec::Vec};
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::hash::sha256;
+use crate::signer::Signature;
+use crate::ssh::{put_string, put_u32, put_u64, Reader};
+use crate::ECDSA;
+
+const DST_ENVELOPE: &[u8] = b"RS_ECC-envelope-v1";
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Payload {
+    Inline(Vec<u8>),
+    // SHA-256 of a payload kept elsewhere
+    Digest(Vec<u8>),
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct SignerInfo {
+    pub algorithm: String,
+    pub key_id: Vec<u8>,
+    pub created: u64,
+    pub signature: Signature,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct SignedMessage {
+    pub payload: Payload,
+    pub signers: Vec<SignerInfo>,
+}
+
+pub struct VerificationPolicy<'a> {
+    keys: Vec<(&'a ECDSA, Point)>,
+    threshold: usize,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct PolicyOutcome {
+    // Indices of the trusted keys with a valid signature, in the policy's order
+    pub matched: Vec<usize>,
+    pub satisfied: bool,
+}
+
+impl ECDSA {
+    pub fn key_id(&self, pub_key: &Point) -> Option<Vec<u8>> {
+        Some(sha256(&self.public_key_to_der(pub_key)?))
+    }
+
+    pub fn sign_message(
+        &self,
+        payload: Payload,
+        created: u64,
+        priv_key: &BigUint,
+    ) -> Option<SignedMessage> {
+        // None on curves without a JOSE name or a DER encoding
+        let mut message = SignedMessage {
+            payload,
+            signers: Vec::new(),
+        };
+        self.add_signature(&mut message, created, priv_key)
+            .then_some(message)
+    }
+
+    pub fn add_signature(
+        &self,
+        message: &mut SignedMessage,
+        created: u64,
+        priv_key: &BigUint,
+    ) -> bool {
+        // A countersignature; false if this key already signed the message
+        let Some(algorithm) = self.jws_algorithm().map(String::from) else {
+            return false;
+        };
+        let Some(key_id) = self.key_id(&self.generate_pub_key(priv_key)) else {
+            return false;
+        };
+        if message.signer(&key_id).is_some() {
+            return false;
+        }
+        let tbs = to_be_signed(&message.payload, &algorithm, &key_id, created);
+        let signature = self.sign_standard(&mut crate::rng(), &sha256(&tbs), priv_key);
+        message.signers.push(SignerInfo {
+            algorithm,
+            key_id,
+            created,
+            signature,
+        });
+        true
+    }
+
+    pub fn verify_message(&self, message: &SignedMessage, pub_key: &Point) -> bool {
+        // This key signed the message: its signer entry names this curve and
+        // key, and the signature covers the payload and that metadata
+        self.key_id(pub_key)
+            .and_then(|key_id| message.signer(&key_id))
+            .is_some_and(|signer| self.verify_signer(&message.payload, signer, pub_key))
+    }
+
+    fn verify_signer(&self, payload: &Payload, signer: &SignerInfo, pub_key: &Point) -> bool {
+        let tbs = to_be_signed(payload, &signer.algorithm, &signer.key_id, signer.created);
+        Some(signer.algorithm.as_str()) == self.jws_algorithm()
+            && self.key_id(pub_key).as_ref() == Some(&signer.key_id)
+            && self.verify_standard(&sha256(&tbs), pub_key, &signer.signature)
+    }
+}
+
+impl Payload {
+    pub fn digest_of(payload: &[u8]) -> Payload {
+        Payload::Digest(sha256(payload))
+    }
+
+    pub fn matches(&self, payload: &[u8]) -> bool {
+        match self {
+            Payload::Inline(inline) => inline == payload,
+            Payload::Digest(digest) => *digest == sha256(payload),
+        }
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        let (kind, bytes) = match self {
+            Payload::Inline(bytes) => (0, bytes),
+            Payload::Digest(digest) => (1, digest),
+        };
+        out.push(kind);
+        put_string(out, bytes);
+    }
+
+    fn decode(reader: &mut Reader) -> Option<Payload> {
+        match (reader.byte()?, reader.string()?) {
+            (0, bytes) => Some(Payload::Inline(bytes.to_vec())),
+            (1, digest) if digest.len() == 32 => Some(Payload::Digest(digest.to_vec())),
+            _ => None,
+        }
+    }
+}
+
+impl SignedMessage {
+    pub fn signer(&self, key_id: &[u8]) -> Option<&SignerInfo> {
+        self.signers.iter().find(|signer| signer.key_id == key_id)
+    }
+
+    pub fn verify_all(&self, keys: &[(&ECDSA, &Point)]) -> bool {
+        // Every listed key signed, and every signature is from a listed key
+        // and valid
+        let verified = |signer: &SignerInfo| {
+            keys.iter()
+                .any(|(ecdsa, pub_key)| ecdsa.verify_signer(&self.payload, signer, pub_key))
+        };
+        !self.signers.is_empty()
+            && self.signers.iter().all(verified)
+            && keys
+                .iter()
+                .all(|(ecdsa, pub_key)| ecdsa.verify_message(self, pub_key))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.payload.encode(&mut bytes);
+        put_u32(&mut bytes, self.signers.len() as u32);
+        for signer in &self.signers {
+            put_string(&mut bytes, signer.algorithm.as_bytes());
+            put_string(&mut bytes, &signer.key_id);
+            put_u64(&mut bytes, signer.created);
+            put_string(&mut bytes, &signer.signature.to_der());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<SignedMessage> {
+        let mut reader = Reader(bytes);
+        let payload = Payload::decode(&mut reader)?;
+        let signers = (0..reader.u32()?)
+            .map(|_| {
+                Some(SignerInfo {
+                    algorithm: String::from_utf8(reader.string()?.to_vec()).ok()?,
+                    key_id: reader.string()?.to_vec(),
+                    created: reader.u64()?,
+                    signature: Signature::from_der(reader.string()?)?,
+                })
+            })
+            .collect::<Option<Vec<SignerInfo>>>()?;
+        reader
+            .0
+            .is_empty()
+            .then_some(SignedMessage { payload, signers })
+    }
+}
+
+impl<'a> VerificationPolicy<'a> {
+    pub fn new(keys: Vec<(&'a ECDSA, Point)>, threshold: usize) -> Option<VerificationPolicy<'a>> {
+        // 1 <= m <= n, and no key listed twice, which would let one signer
+        // count for two
+        let mut key_ids = Vec::with_capacity(keys.len());
+        for (ecdsa, pub_key) in &keys {
+            let key_id = ecdsa.key_id(pub_key)?;
+            if key_ids.contains(&key_id) {
+                return None;
+            }
+            key_ids.push(key_id);
+        }
+        (1..=keys.len())
+            .contains(&threshold)
+            .then_some(VerificationPolicy { keys, threshold })
+    }
+
+    pub fn evaluate(&self, message: &SignedMessage) -> PolicyOutcome {
+        let matched: Vec<usize> = self
+            .keys
+            .iter()
+            .enumerate()
+            .filter(|(_, (ecdsa, pub_key))| ecdsa.verify_message(message, pub_key))
+            .map(|(index, _)| index)
+            .collect();
+        PolicyOutcome {
+            satisfied: matched.len() >= self.threshold,
+            matched,
+        }
+    }
+}
+
+pub(crate) fn to_be_signed(
+    payload: &Payload,
+    algorithm: &str,
+    key_id: &[u8],
+    created: u64,
+) -> Vec<u8> {
+    let mut bytes = DST_ENVELOPE.to_vec();
+    payload.encode(&mut bytes);
+    put_string(&mut bytes, algorithm.as_bytes());
+    put_string(&mut bytes, key_id);
+    put_u64(&mut bytes, created);
+    bytes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::NamedCurve;
+
+    #[test]
+    fn test_sign_message() {
+        let ecdsa = NamedCurve::P256.preset();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let message = ecdsa
+            .sign_message(
+                Payload::Inline(b"release 1.2".to_vec()),
+                1_760_000_000,
+                &priv_key,
+            )
+            .unwrap();
+        assert_eq!(message.signers[0].algorithm, "ES256");
+        assert!(ecdsa.verify_message(&message, &pub_key));
+        assert!(message.payload.matches(b"release 1.2"));
+
+        let decoded = SignedMessage::from_bytes(&message.to_bytes()).unwrap();
+        assert_eq!(decoded, message);
+        assert!(SignedMessage::from_bytes(&[message.to_bytes(), vec![0]].concat()).is_none());
+
+        // Every piece of metadata is covered by the signature
+        let mut later = message.clone();
+        later.signers[0].created += 1;
+        assert!(!ecdsa.verify_message(&later, &pub_key));
+        let mut relabeled = message.clone();
+        relabeled.signers[0].algorithm = "ES256K".into();
+        assert!(!ecdsa.verify_message(&relabeled, &pub_key));
+        let other_pub = ecdsa.generate_pub_key(&BigUint::from(3u32));
+        assert!(!ecdsa.verify_message(&message, &other_pub));
+    }
+
+    #[test]
+    fn test_sign_digest_payload() {
+        let ecdsa = NamedCurve::Secp256k1.preset();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let image = vec![0x5a; 4096];
+        let message = ecdsa
+            .sign_message(Payload::digest_of(&image), 1_760_000_000, &priv_key)
+            .unwrap();
+        assert_eq!(message.signers[0].algorithm, "ES256K");
+        assert!(ecdsa.verify_message(&message, &pub_key));
+        assert!(message.payload.matches(&image));
+        assert!(!message.payload.matches(&image[1..]));
+
+        // An inline payload equal to the digest is a different message
+        let Payload::Digest(digest) = message.payload.clone() else {
+            unreachable!()
+        };
+        let inline = SignedMessage {
+            payload: Payload::Inline(digest),
+            ..message
+        };
+        assert!(!ecdsa.verify_message(&inline, &pub_key));
+    }
+
+    #[test]
+    fn test_countersignatures() {
+        // Two officers on different curves approve the same release
+        let (p256, secp256k1) = (NamedCurve::P256.ecdsa(), NamedCurve::Secp256k1.ecdsa());
+        let (alice, bob) = (BigUint::from(0xa11ceu32), BigUint::from(0xb0bu32));
+        let alice_pub = p256.generate_pub_key(&alice);
+        let bob_pub = secp256k1.generate_pub_key(&bob);
+
+        let mut message = p256
+            .sign_message(Payload::digest_of(b"release 1.2"), 1_760_000_000, &alice)
+            .unwrap();
+        assert!(!message.verify_all(&[(&p256, &alice_pub), (&secp256k1, &bob_pub)]));
+        assert!(secp256k1.add_signature(&mut message, 1_760_003_600, &bob));
+        assert!(!p256.add_signature(&mut message, 1_760_003_600, &alice));
+        assert_eq!(message.signers.len(), 2);
+
+        let message = SignedMessage::from_bytes(&message.to_bytes()).unwrap();
+        assert!(message.verify_all(&[(&p256, &alice_pub), (&secp256k1, &bob_pub)]));
+        assert!(secp256k1.verify_message(&message, &bob_pub));
+        // Bob's signature is not accounted for
+        assert!(!message.verify_all(&[(&p256, &alice_pub)]));
+
+        let mut forged = message.clone();
+        forged.signers[1].signature = forged.signers[0].signature.clone();
+        assert!(!forged.verify_all(&[(&p256, &alice_pub), (&secp256k1, &bob_pub)]));
+        assert!(p256.verify_message(&forged, &alice_pub));
+    }
+
+    #[test]
+    fn test_verification_policy() {
+        // 2-of-3 officers; Carol's key is not trusted
+        let ecdsa = NamedCurve::P256.preset();
+        let officers: Vec<BigUint> = [0xa11ceu32, 0xb0b, 0xd0d].map(BigUint::from).to_vec();
+        let trusted = officers
+            .iter()
+            .map(|key| (&*ecdsa, ecdsa.generate_pub_key(key)))
+            .collect::<Vec<_>>();
+        let policy = VerificationPolicy::new(trusted.clone(), 2).unwrap();
+
+        let payload = Payload::digest_of(b"release 1.2");
+        let carol = BigUint::from(0xca201u32);
+        let mut message = ecdsa.sign_message(payload, 1_760_000_000, &carol).unwrap();
+        assert!(ecdsa.add_signature(&mut message, 1_760_000_000, &officers[2]));
+        let outcome = policy.evaluate(&message);
+        assert_eq!(outcome.matched, vec![2]);
+        assert!(!outcome.satisfied);
+
+        assert!(ecdsa.add_signature(&mut message, 1_760_000_000, &officers[0]));
+        assert_eq!(
+            policy.evaluate(&message),
+            PolicyOutcome {
+                matched: vec![0, 2],
+                satisfied: true
+            }
+        );
+
+        assert!(VerificationPolicy::new(trusted.clone(), 0).is_none());
+        assert!(VerificationPolicy::new(trusted.clone(), 4).is_none());
+        let twice = vec![trusted[0].clone(), trusted[0].clone()];
+        assert!(VerificationPolicy::new(twice, 2).is_none());
+    }
+}
@@ -0,0 +1,203 @@
+// Ethereum signed messages (EIP-191 version 0x45), what `personal_sign` in
+// MetaMask and `signMessage` in ethers and web3 produce. The message is
+// hashed with Keccak-256 after a prefix holding its length in decimal:
+//
+//   "\x19Ethereum Signed Message:\n" || len || message
+//
+// and signed on secp256k1 with low s. The signature is 65 bytes, r || s || v,
+// in 0x-prefixed hex, where v is 27 + the recovery id. Some wallets and
+// hardware signers write the bare recovery id 0 or 1 instead, which
+// verification accepts as well.
+//
+// EIP-2098 packs the same signature into 64 bytes, r || yParityAndS, with the
+// recovery id in the top bit of s; low s leaves that bit free. Verification
+// takes either length.
+
+use alloc::{format, string::String, vec::Vec};
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::address::Network;
+use crate::hash::keccak256;
+use crate::signer::Signature;
+use crate::ECDSA;
+
+const PREFIX: &[u8] = b"\x19Ethereum Signed Message:\n";
+
+impl ECDSA {
+    pub fn personal_sign(&self, message: &[u8], priv_key: &BigUint) -> Option<String> {
+        // None off secp256k1
+        if !self.is_secp256k1() {
+            return None;
+        }
+        let digest = eip191_hash(message);
+        let signature = self.sign_standard(&mut crate::rng(), &digest, priv_key);
+        let signature = signature.normalize(self);
+        let pub_key = self.generate_pub_key(priv_key);
+        let recovery_id = (0..2).find(|&recovery_id| {
+            self.recover_public_key(&digest, &signature, recovery_id) == Some(pub_key.clone())
+        })?;
+        let mut bytes = self.encode_signature(&signature);
+        bytes.push(27 + recovery_id);
+        Some(format!("0x{}", hex::encode(bytes)))
+    }
+
+    pub fn recover_personal_sign(&self, message: &[u8], signature: &str) -> Option<Point> {
+        // v of 27 or 28, or 0 or 1; r and s as any valid signature, high s
+        // included, as ecrecover takes them
+        if !self.is_secp256k1() {
+            return None;
+        }
+        let hex_signature = signature.strip_prefix("0x").unwrap_or(signature);
+        let bytes = hex::decode(hex_signature).ok()?;
+        let (signature, recovery_id) = match bytes.len() {
+            64 => self.decode_eip2098(&bytes)?,
+            65 => {
+                let recovery_id = match bytes[64] {
+                    v @ (0 | 1) => v,
+                    v @ (27 | 28) => v - 27,
+                    _ => return None,
+                };
+                (self.decode_signature(&bytes[..64])?, recovery_id)
+            }
+            _ => return None,
+        };
+        self.recover_public_key(&eip191_hash(message), &signature, recovery_id)
+    }
+
+    pub fn verify_personal_sign(&self, address: &str, message: &[u8], signature: &str) -> bool {
+        // The address in any case; an EIP-55 checksum is not checked
+        self.recover_personal_sign(message, signature)
+            .and_then(|pub_key| self.address(&pub_key, Network::Ethereum))
+            .is_some_and(|signer| signer.eq_ignore_ascii_case(address))
+    }
+
+    pub fn encode_eip2098(&self, signature: &Signature, recovery_id: u8) -> Option<Vec<u8>> {
+        // r || yParityAndS; None off secp256k1, for high s or a recovery id
+        // other than 0 or 1
+        if !self.is_secp256k1() || !signature.is_low_s(self) || recovery_id > 1 {
+            return None;
+        }
+        let mut bytes = self.encode_signature(signature);
+        bytes[32] |= recovery_id << 7;
+        Some(bytes)
+    }
+
+    pub fn decode_eip2098(&self, bytes: &[u8]) -> Option<(Signature, u8)> {
+        // The signature and its recovery id
+        if !self.is_secp256k1() || bytes.len() != 64 {
+            return None;
+        }
+        let mut bytes = bytes.to_vec();
+        let recovery_id = bytes[32] >> 7;
+        bytes[32] &= 0x7f;
+        Some((self.decode_signature(&bytes)?, recovery_id))
+    }
+}
+
+pub fn eip191_hash(message: &[u8]) -> Vec<u8> {
+    let mut data = PREFIX.to_vec();
+    data.extend(format!("{}", message.len()).as_bytes());
+    data.extend(message);
+    keccak256(&data)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::{p256, secp256k1};
+
+    // The example of web3.js's `web3.eth.accounts.sign`
+    const PRIV_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    const ADDRESS: &str = "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23";
+    const SIGNATURE: &str = "0xb91467e570a6466aa9e9876cbcd013baba02900b8979d43fe208a4a4f339f5fd\
+                             6007e74cd82e037b800186422fc2da167c747ef045e5d18a5f5d4300f8e1a0291c";
+
+    #[test]
+    fn test_eip191_hash() {
+        assert_eq!(
+            hex::encode(eip191_hash(b"Some data")),
+            "1da44b586eb0729ff70a73c326926f6ed5a25f5b056e7f47fbc6e58d86871655"
+        );
+    }
+
+    #[test]
+    fn test_verify_personal_sign() {
+        let ecdsa = secp256k1();
+        let priv_key = BigUint::parse_bytes(PRIV_KEY.as_bytes(), 16).unwrap();
+        assert_eq!(
+            ecdsa.address(&ecdsa.generate_pub_key(&priv_key), Network::Ethereum),
+            Some(ADDRESS.into())
+        );
+        assert!(ecdsa.verify_personal_sign(ADDRESS, b"Some data", SIGNATURE));
+        assert!(ecdsa.verify_personal_sign(&ADDRESS.to_lowercase(), b"Some data", SIGNATURE));
+        assert!(!ecdsa.verify_personal_sign(ADDRESS, b"Some data!", SIGNATURE));
+
+        // v as the bare recovery id, and out of range
+        let bare = format!("{}01", &SIGNATURE[..130]);
+        assert!(ecdsa.verify_personal_sign(ADDRESS, b"Some data", &bare));
+        let bad_v = format!("{}1d", &SIGNATURE[..130]);
+        assert_eq!(ecdsa.recover_personal_sign(b"Some data", &bad_v), None);
+    }
+
+    #[test]
+    fn test_eip2098() {
+        // The examples of EIP-2098, signed with ethers' `signMessage`
+        let ecdsa = secp256k1();
+        let priv_key = BigUint::from_bytes_be(&[0x12, 0x34, 0x56, 0x78, 0x90].repeat(7)[..32]);
+        let address = ecdsa
+            .address(&ecdsa.generate_pub_key(&priv_key), Network::Ethereum)
+            .unwrap();
+        for (message, r, s, v, y_parity_and_s) in [
+            (
+                &b"Hello World"[..],
+                "68a020a209d3d56c46f38cc50a33f704f4a9a10a59377f8dd762ac66910e9b90",
+                "7e865ad05c4035ab5792787d4a0297a43617ae897930a6fe4d822b8faea52064",
+                "1b",
+                "7e865ad05c4035ab5792787d4a0297a43617ae897930a6fe4d822b8faea52064",
+            ),
+            (
+                &b"It's a small(er) world"[..],
+                "9328da16089fcba9bececa81663203989f2df5fe1faa6291a45381c81bd17f76",
+                "139c6d6b623b42da56557e5e734a43dc83345ddfadec52cbe24d0cc64f550793",
+                "1c",
+                "939c6d6b623b42da56557e5e734a43dc83345ddfadec52cbe24d0cc64f550793",
+            ),
+        ] {
+            let full = format!("{}{}{}", r, s, v);
+            let compact = format!("{}{}", r, y_parity_and_s);
+            assert!(ecdsa.verify_personal_sign(&address, message, &full));
+            assert!(ecdsa.verify_personal_sign(&address, message, &compact));
+
+            let bytes = hex::decode(&full).unwrap();
+            let signature = ecdsa.decode_signature(&bytes[..64]).unwrap();
+            let encoded = ecdsa.encode_eip2098(&signature, bytes[64] - 27).unwrap();
+            assert_eq!(hex::encode(&encoded), compact);
+            assert_eq!(
+                ecdsa.decode_eip2098(&encoded),
+                Some((signature, bytes[64] - 27))
+            );
+        }
+
+        // High s has no room for the bit
+        let signature = Signature::new(BigUint::from(1u32), &ecdsa.q_order - 1u32);
+        assert_eq!(ecdsa.encode_eip2098(&signature, 0), None);
+    }
+
+    #[test]
+    fn test_personal_sign() {
+        let ecdsa = secp256k1();
+        let priv_key = BigUint::parse_bytes(PRIV_KEY.as_bytes(), 16).unwrap();
+        let signature = ecdsa.personal_sign(b"Some data", &priv_key).unwrap();
+        assert_eq!(signature.len(), 132);
+        assert!(signature.ends_with("1b") || signature.ends_with("1c"));
+        assert!(ecdsa.verify_personal_sign(ADDRESS, b"Some data", &signature));
+        let bytes = hex::decode(&signature[2..]).unwrap();
+        assert!(ecdsa
+            .decode_signature(&bytes[..64])
+            .unwrap()
+            .is_low_s(&ecdsa));
+        assert!(p256().personal_sign(b"Some data", &priv_key).is_none());
+    }
+}
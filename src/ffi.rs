@@ -0,0 +1,353 @@
+// C ABI, built with `--features ffi`, which also regenerates include/rs_ecc.h
+// with cbindgen. A curve is an opaque `RsEccCurve` handle created by
+// `rs_ecc_curve_new` and released with `rs_ecc_curve_free`. Inputs are
+// pointer/length pairs and outputs are written to caller buffers: `*out_len`
+// holds the capacity on entry and the written length on return, or the
+// required length together with RS_ECC_ERROR_BUFFER_TOO_SMALL. Keys and
+// signatures use the same byte formats as the wasm bindings. Panics never
+// cross the boundary, they are reported as RS_ECC_ERROR_PANIC.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+use ec_generic::{EllipticCurve, Point};
+use num_bigint::BigUint;
+use signature::{Signer, Verifier};
+
+use crate::encoding::to_fixed_bytes;
+use crate::signer::{SigningKey, VerifyingKey};
+use crate::ECDSA;
+
+pub struct RsEccCurve {
+    ecdsa: ECDSA,
+}
+
+#[repr(C)]
+pub struct RsEccBytes {
+    pub ptr: *const u8,
+    pub len: usize,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum RsEccError {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidCurve = 2,
+    InvalidKey = 3,
+    InvalidSignature = 4,
+    BufferTooSmall = 5,
+    Panic = 6,
+}
+
+/// Creates a curve y² = x³ + ax + b over F_p with generator (gx, gy) of order
+/// q, all given as big-endian integers.
+///
+/// # Safety
+///
+/// Every `RsEccBytes` must point to `len` readable bytes and `out` must be
+/// a valid pointer. The handle written to `out` must be released with
+/// `rs_ecc_curve_free`.
+#[no_mangle]
+pub unsafe extern "C" fn rs_ecc_curve_new(
+    a: RsEccBytes,
+    b: RsEccBytes,
+    p: RsEccBytes,
+    gx: RsEccBytes,
+    gy: RsEccBytes,
+    q: RsEccBytes,
+    out: *mut *mut RsEccCurve,
+) -> RsEccError {
+    guard(|| {
+        if out.is_null() {
+            return RsEccError::NullPointer;
+        }
+        let params = [a, b, p, gx, gy, q].map(|bytes| input(bytes.ptr, bytes.len));
+        let [Some(a), Some(b), Some(p), Some(gx), Some(gy), Some(q)] = params else {
+            return RsEccError::NullPointer;
+        };
+        let ecdsa = ECDSA::new(
+            EllipticCurve {
+                a: BigUint::from_bytes_be(a),
+                b: BigUint::from_bytes_be(b),
+                p: BigUint::from_bytes_be(p),
+            },
+            Point::Coor(BigUint::from_bytes_be(gx), BigUint::from_bytes_be(gy)),
+            BigUint::from_bytes_be(q),
+        );
+        match ecdsa {
+            Some(ecdsa) => {
+                *out = Box::into_raw(Box::new(RsEccCurve { ecdsa }));
+                RsEccError::Ok
+            }
+            None => RsEccError::InvalidCurve,
+        }
+    })
+}
+
+/// # Safety
+///
+/// `curve` must come from `rs_ecc_curve_new` and not have been freed, or be
+/// null.
+#[no_mangle]
+pub unsafe extern "C" fn rs_ecc_curve_free(curve: *mut RsEccCurve) {
+    if !curve.is_null() {
+        drop(Box::from_raw(curve));
+    }
+}
+
+/// Writes a fresh private key, `scalar_len` bytes.
+///
+/// # Safety
+///
+/// `curve` must be a live handle, `out` must point to `*out_len` writable
+/// bytes and `out_len` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn rs_ecc_generate_private_key(
+    curve: *const RsEccCurve,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> RsEccError {
+    guard(|| {
+        let Some(curve) = curve.as_ref() else {
+            return RsEccError::NullPointer;
+        };
+        let ecdsa = &curve.ecdsa;
+        output(
+            &to_fixed_bytes(&ecdsa.generate_priv_key(), ecdsa.scalar_len()),
+            out,
+            out_len,
+        )
+    })
+}
+
+/// Writes the compressed SEC1 public key of `priv_key`.
+///
+/// # Safety
+///
+/// `curve` must be a live handle, `priv_key` must point to `priv_key_len`
+/// readable bytes, `out` to `*out_len` writable bytes and `out_len` must be a
+/// valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn rs_ecc_public_key(
+    curve: *const RsEccCurve,
+    priv_key: *const u8,
+    priv_key_len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> RsEccError {
+    guard(|| {
+        let (Some(curve), Some(priv_key)) = (curve.as_ref(), input(priv_key, priv_key_len)) else {
+            return RsEccError::NullPointer;
+        };
+        match SigningKey::from_bytes(&curve.ecdsa, priv_key) {
+            Some(key) => output(&key.verifying_key().to_bytes(), out, out_len),
+            None => RsEccError::InvalidKey,
+        }
+    })
+}
+
+/// Signs SHA-256(message) and writes r || s.
+///
+/// # Safety
+///
+/// `curve` must be a live handle, `priv_key` and `message` must point to
+/// their lengths in readable bytes, `out` to `*out_len` writable bytes and
+/// `out_len` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn rs_ecc_sign(
+    curve: *const RsEccCurve,
+    priv_key: *const u8,
+    priv_key_len: usize,
+    message: *const u8,
+    message_len: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> RsEccError {
+    guard(|| {
+        let (Some(curve), Some(priv_key), Some(message)) = (
+            curve.as_ref(),
+            input(priv_key, priv_key_len),
+            input(message, message_len),
+        ) else {
+            return RsEccError::NullPointer;
+        };
+        match SigningKey::from_bytes(&curve.ecdsa, priv_key) {
+            Some(key) => output(
+                &curve.ecdsa.encode_signature(&key.sign(message)),
+                out,
+                out_len,
+            ),
+            None => RsEccError::InvalidKey,
+        }
+    })
+}
+
+/// Returns RS_ECC_ERROR_OK when `signature` is a valid signature of `message`
+/// under `pub_key`, RS_ECC_ERROR_INVALID_SIGNATURE otherwise.
+///
+/// # Safety
+///
+/// `curve` must be a live handle and every other pointer must point to its
+/// length in readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rs_ecc_verify(
+    curve: *const RsEccCurve,
+    pub_key: *const u8,
+    pub_key_len: usize,
+    message: *const u8,
+    message_len: usize,
+    signature: *const u8,
+    signature_len: usize,
+) -> RsEccError {
+    guard(|| {
+        let (Some(curve), Some(pub_key), Some(message), Some(signature)) = (
+            curve.as_ref(),
+            input(pub_key, pub_key_len),
+            input(message, message_len),
+            input(signature, signature_len),
+        ) else {
+            return RsEccError::NullPointer;
+        };
+        let Some(key) = VerifyingKey::from_bytes(&curve.ecdsa, pub_key) else {
+            return RsEccError::InvalidKey;
+        };
+        match curve.ecdsa.decode_signature(signature) {
+            Some(signature) if key.verify(message, &signature).is_ok() => RsEccError::Ok,
+            _ => RsEccError::InvalidSignature,
+        }
+    })
+}
+
+fn guard(f: impl FnOnce() -> RsEccError) -> RsEccError {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(RsEccError::Panic)
+}
+
+unsafe fn input<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    // A null pointer is only accepted for an empty input
+    if data.is_null() {
+        return if len == 0 { Some(&[]) } else { None };
+    }
+    Some(slice::from_raw_parts(data, len))
+}
+
+unsafe fn output(bytes: &[u8], out: *mut u8, out_len: *mut usize) -> RsEccError {
+    if out_len.is_null() {
+        return RsEccError::NullPointer;
+    }
+    if *out_len < bytes.len() {
+        *out_len = bytes.len();
+        return RsEccError::BufferTooSmall;
+    }
+    if out.is_null() {
+        return RsEccError::NullPointer;
+    }
+    ptr::copy_nonoverlapping(bytes.as_ptr(), out, bytes.len());
+    *out_len = bytes.len();
+    RsEccError::Ok
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bytes(data: &[u8]) -> RsEccBytes {
+        RsEccBytes {
+            ptr: data.as_ptr(),
+            len: data.len(),
+        }
+    }
+
+    #[test]
+    fn test_ffi_sign_verify() {
+        unsafe {
+            let mut curve = ptr::null_mut();
+            let status = rs_ecc_curve_new(
+                bytes(&[2]),
+                bytes(&[2]),
+                bytes(&[17]),
+                bytes(&[5]),
+                bytes(&[1]),
+                bytes(&[19]),
+                &mut curve,
+            );
+            assert_eq!(status, RsEccError::Ok);
+
+            let (mut priv_key, mut priv_key_len) = ([0u8; 8], 8);
+            let status =
+                rs_ecc_generate_private_key(curve, priv_key.as_mut_ptr(), &mut priv_key_len);
+            assert_eq!(status, RsEccError::Ok);
+            assert_eq!(priv_key_len, 1);
+
+            let (mut pub_key, mut pub_key_len) = ([0u8; 8], 8);
+            let status = rs_ecc_public_key(
+                curve,
+                priv_key.as_ptr(),
+                priv_key_len,
+                pub_key.as_mut_ptr(),
+                &mut pub_key_len,
+            );
+            assert_eq!(status, RsEccError::Ok);
+
+            let message = b"Bob -> 1 BTC -> Alice";
+            let (mut signature, mut signature_len) = ([0u8; 8], 1);
+            let sign = |out: *mut u8, out_len: &mut usize| {
+                rs_ecc_sign(
+                    curve,
+                    priv_key.as_ptr(),
+                    priv_key_len,
+                    message.as_ptr(),
+                    message.len(),
+                    out,
+                    out_len,
+                )
+            };
+            assert_eq!(
+                sign(signature.as_mut_ptr(), &mut signature_len),
+                RsEccError::BufferTooSmall
+            );
+            assert_eq!(signature_len, 2);
+            assert_eq!(
+                sign(signature.as_mut_ptr(), &mut signature_len),
+                RsEccError::Ok
+            );
+
+            let status = rs_ecc_verify(
+                curve,
+                pub_key.as_ptr(),
+                pub_key_len,
+                message.as_ptr(),
+                message.len(),
+                signature.as_ptr(),
+                signature_len,
+            );
+            assert_eq!(status, RsEccError::Ok);
+
+            rs_ecc_curve_free(curve);
+        }
+    }
+
+    #[test]
+    fn test_ffi_rejects_bad_input() {
+        unsafe {
+            let mut curve = ptr::null_mut();
+            // (5, 2) is not on the curve
+            let status = rs_ecc_curve_new(
+                bytes(&[2]),
+                bytes(&[2]),
+                bytes(&[17]),
+                bytes(&[5]),
+                bytes(&[2]),
+                bytes(&[19]),
+                &mut curve,
+            );
+            assert_eq!(status, RsEccError::InvalidCurve);
+            assert!(curve.is_null());
+
+            let mut len = 8;
+            let status = rs_ecc_generate_private_key(ptr::null(), ptr::null_mut(), &mut len);
+            assert_eq!(status, RsEccError::NullPointer);
+        }
+    }
+}
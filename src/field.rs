@@ -0,0 +1,287 @@
+// Fixed-width arithmetic on integers held as little-endian u64 limbs, four
+// for 256 bits and six for 384, for the field and scalar routines that do not
+// go through `BigUint`. The portable code makes each limb product one u128
+// multiplication. With the `simd` feature, products and squares are taken by
+// vector code when the CPU reports it at run time: AVX2 on x86_64 and NEON on
+// aarch64. Both split the limbs into 32-bit digits, multiply a digit of one
+// operand by four (AVX2) or two (NEON) digits of the other per instruction,
+// and add the low and high halves of the 64-bit products into separate
+// column sums, which cannot overflow at these widths; one scalar pass then
+// propagates the carries. Every CPU without them takes the portable code.
+
+pub const MAX_LIMBS: usize = 6;
+
+// A product of two N-limb integers, in the low 2N limbs
+pub type Wide = [u64; 2 * MAX_LIMBS];
+
+pub fn mul_wide<const N: usize>(a: &[u64; N], b: &[u64; N]) -> Wide {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if std::is_x86_feature_detected!("avx2") {
+        // Safe: the CPU supports the feature the function was compiled for
+        return unsafe { mul_wide_avx2(a, b) };
+    }
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    if std::arch::is_aarch64_feature_detected!("neon") {
+        return unsafe { mul_wide_neon(a, b) };
+    }
+    mul_wide_portable(a, b)
+}
+
+pub fn square_wide<const N: usize>(a: &[u64; N]) -> Wide {
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    if std::is_x86_feature_detected!("avx2") {
+        return unsafe { mul_wide_avx2(a, a) };
+    }
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    if std::arch::is_aarch64_feature_detected!("neon") {
+        return unsafe { mul_wide_neon(a, a) };
+    }
+    square_wide_portable(a)
+}
+
+#[inline(always)]
+pub fn mac(acc: u64, a: u64, b: u64, carry: u64) -> (u64, u64) {
+    // acc + a * b + carry, which never overflows 128 bits
+    let wide = acc as u128 + (a as u128) * (b as u128) + carry as u128;
+    (wide as u64, (wide >> 64) as u64)
+}
+
+fn mul_wide_portable<const N: usize>(a: &[u64; N], b: &[u64; N]) -> Wide {
+    // Schoolbook, one row of partial products per limb of a
+    let mut result = [0u64; 2 * MAX_LIMBS];
+    for i in 0..N {
+        let mut carry = 0;
+        for j in 0..N {
+            (result[i + j], carry) = mac(result[i + j], a[i], b[j], carry);
+        }
+        result[i + N] = carry;
+    }
+    result
+}
+
+fn square_wide_portable<const N: usize>(a: &[u64; N]) -> Wide {
+    // The cross products a_i a_j (i < j) once, doubled, then the squares
+    // a_i² added on the diagonal: N(N + 1)/2 multiplications instead of N²
+    let mut result = [0u64; 2 * MAX_LIMBS];
+    for i in 0..N.saturating_sub(1) {
+        let mut carry = 0;
+        for j in (i + 1)..N {
+            (result[i + j], carry) = mac(result[i + j], a[i], a[j], carry);
+        }
+        result[i + N] = carry;
+    }
+    let mut top = 0;
+    for limb in result[..2 * N].iter_mut() {
+        let doubled = (*limb << 1) | top;
+        top = *limb >> 63;
+        *limb = doubled;
+    }
+    let mut carry = 0;
+    for i in 0..N {
+        let (low, high) = mac(result[2 * i], a[i], a[i], carry);
+        result[2 * i] = low;
+        let sum = result[2 * i + 1] as u128 + high as u128;
+        result[2 * i + 1] = sum as u64;
+        carry = (sum >> 64) as u64;
+    }
+    result
+}
+
+// Column sums of the vector paths: room for 4 * MAX_LIMBS digit columns plus
+// the lanes a last, partly used vector writes past them
+#[cfg(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64")))]
+const COLUMNS: usize = 4 * MAX_LIMBS + 4;
+
+#[cfg(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn digits<const N: usize>(a: &[u64; N]) -> [u32; 2 * MAX_LIMBS + 4] {
+    // 32-bit digits, zero past 2N so whole vectors can be loaded
+    let mut digits = [0u32; 2 * MAX_LIMBS + 4];
+    for (i, limb) in a.iter().enumerate() {
+        digits[2 * i] = *limb as u32;
+        digits[2 * i + 1] = (limb >> 32) as u32;
+    }
+    digits
+}
+
+#[cfg(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn fold_columns<const N: usize>(low: &[u64; COLUMNS], high: &[u64; COLUMNS]) -> Wide {
+    // Column k holds the low halves of the digit products of weight 2^(32k)
+    // and the high halves of those of weight 2^(32(k - 1)); each is below
+    // 2N * 2^32, so one pass with a u64 carry resolves them into digits
+    let mut result = [0u64; 2 * MAX_LIMBS];
+    let mut carry = 0u64;
+    for k in 0..4 * N {
+        let column = low[k] + if k > 0 { high[k - 1] } else { 0 } + carry;
+        result[k / 2] |= (column & 0xffff_ffff) << (32 * (k % 2));
+        carry = column >> 32;
+    }
+    result
+}
+
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[target_feature(enable = "avx2")]
+unsafe fn mul_wide_avx2<const N: usize>(a: &[u64; N], b: &[u64; N]) -> Wide {
+    use core::arch::x86_64::*;
+
+    let (a, b) = (digits(a), digits(b));
+    let mut low = [0u64; COLUMNS];
+    let mut high = [0u64; COLUMNS];
+    let mask = _mm256_set1_epi64x(0xffff_ffff);
+    for (i, digit) in a[..2 * N].iter().enumerate() {
+        let digit = _mm256_set1_epi64x(*digit as i64);
+        for j in (0..2 * N).step_by(4) {
+            // Four digits of b, one per 64-bit lane, times the digit of a
+            let lanes = _mm256_set_epi64x(
+                b[j + 3] as i64,
+                b[j + 2] as i64,
+                b[j + 1] as i64,
+                b[j] as i64,
+            );
+            let products = _mm256_mul_epu32(digit, lanes);
+            let low_sums = low.as_mut_ptr().add(i + j) as *mut __m256i;
+            let high_sums = high.as_mut_ptr().add(i + j) as *mut __m256i;
+            _mm256_storeu_si256(
+                low_sums,
+                _mm256_add_epi64(
+                    _mm256_loadu_si256(low_sums),
+                    _mm256_and_si256(products, mask),
+                ),
+            );
+            _mm256_storeu_si256(
+                high_sums,
+                _mm256_add_epi64(
+                    _mm256_loadu_si256(high_sums),
+                    _mm256_srli_epi64(products, 32),
+                ),
+            );
+        }
+    }
+    fold_columns::<N>(&low, &high)
+}
+
+#[cfg(all(feature = "simd", target_arch = "aarch64"))]
+#[target_feature(enable = "neon")]
+unsafe fn mul_wide_neon<const N: usize>(a: &[u64; N], b: &[u64; N]) -> Wide {
+    use core::arch::aarch64::*;
+
+    let (a, b) = (digits(a), digits(b));
+    let mut low = [0u64; COLUMNS];
+    let mut high = [0u64; COLUMNS];
+    let mask = vdupq_n_u64(0xffff_ffff);
+    for (i, digit) in a[..2 * N].iter().enumerate() {
+        let digit = vdup_n_u32(*digit);
+        for j in (0..2 * N).step_by(2) {
+            // Two digits of b times the digit of a, widened to 64 bits
+            let products = vmull_u32(digit, vld1_u32(b.as_ptr().add(j)));
+            let low_sums = low.as_mut_ptr().add(i + j);
+            let high_sums = high.as_mut_ptr().add(i + j);
+            vst1q_u64(
+                low_sums,
+                vaddq_u64(vld1q_u64(low_sums), vandq_u64(products, mask)),
+            );
+            vst1q_u64(
+                high_sums,
+                vaddq_u64(vld1q_u64(high_sums), vshrq_n_u64::<32>(products)),
+            );
+        }
+    }
+    fold_columns::<N>(&low, &high)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use num_bigint::{BigUint, RandBigInt};
+
+    fn to_biguint(limbs: &[u64]) -> BigUint {
+        limbs
+            .iter()
+            .rev()
+            .fold(BigUint::from(0u32), |acc, limb| (acc << 64) + limb)
+    }
+
+    fn to_limbs<const N: usize>(value: &BigUint) -> [u64; N] {
+        let mut limbs = [0u64; N];
+        for (limb, digit) in limbs.iter_mut().zip(value.iter_u64_digits()) {
+            *limb = digit;
+        }
+        limbs
+    }
+
+    fn check<const N: usize>(a: &BigUint, b: &BigUint) {
+        let (a, b) = (to_limbs::<N>(a), to_limbs::<N>(b));
+        let product = mul_wide(&a, &b);
+        assert_eq!(to_biguint(&product), to_biguint(&a) * to_biguint(&b));
+        // Whichever path `mul_wide` and `square_wide` took, the same limbs
+        // as the portable code
+        assert_eq!(product, mul_wide_portable(&a, &b));
+        assert_eq!(square_wide(&a), mul_wide_portable(&a, &a));
+        assert_eq!(square_wide_portable(&a), mul_wide_portable(&a, &a));
+    }
+
+    #[test]
+    fn test_mul_square_wide() {
+        let mut rng = crate::rng();
+        let bound = BigUint::from(1u32) << 384;
+        let mut inputs = vec![BigUint::from(0u32), &bound - BigUint::from(1u32)];
+        inputs.extend((0..50).map(|_| rng.gen_biguint_below(&bound)));
+
+        for a in &inputs {
+            for b in inputs.iter().take(5) {
+                check::<6>(a, b);
+                // The low four limbs alone
+                check::<4>(a, b);
+            }
+        }
+    }
+
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[test]
+    fn test_mul_wide_avx2() {
+        // Run directly, so a CPU that has AVX2 always tests it
+        if !std::is_x86_feature_detected!("avx2") {
+            return;
+        }
+        let mut rng = crate::rng();
+        let bound = BigUint::from(1u32) << 384;
+        let max = to_limbs::<6>(&(&bound - BigUint::from(1u32)));
+        assert_eq!(
+            unsafe { mul_wide_avx2(&max, &max) },
+            mul_wide_portable(&max, &max)
+        );
+        for _ in 0..200 {
+            let a = to_limbs::<6>(&rng.gen_biguint_below(&bound));
+            let b = to_limbs::<6>(&rng.gen_biguint_below(&bound));
+            assert_eq!(unsafe { mul_wide_avx2(&a, &b) }, mul_wide_portable(&a, &b));
+            let (a, b) = (resize4(&a), resize4(&b));
+            assert_eq!(unsafe { mul_wide_avx2(&a, &b) }, mul_wide_portable(&a, &b));
+        }
+    }
+
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    #[test]
+    fn test_mul_wide_neon() {
+        if !std::arch::is_aarch64_feature_detected!("neon") {
+            return;
+        }
+        let mut rng = crate::rng();
+        let bound = BigUint::from(1u32) << 384;
+        let max = to_limbs::<6>(&(&bound - BigUint::from(1u32)));
+        assert_eq!(
+            unsafe { mul_wide_neon(&max, &max) },
+            mul_wide_portable(&max, &max)
+        );
+        for _ in 0..200 {
+            let a = to_limbs::<6>(&rng.gen_biguint_below(&bound));
+            let b = to_limbs::<6>(&rng.gen_biguint_below(&bound));
+            assert_eq!(unsafe { mul_wide_neon(&a, &b) }, mul_wide_portable(&a, &b));
+            let (a, b) = (resize4(&a), resize4(&b));
+            assert_eq!(unsafe { mul_wide_neon(&a, &b) }, mul_wide_portable(&a, &b));
+        }
+    }
+
+    #[cfg(all(feature = "simd", any(target_arch = "x86_64", target_arch = "aarch64")))]
+    fn resize4(a: &[u64; 6]) -> [u64; 4] {
+        [a[0], a[1], a[2], a[3]]
+    }
+}
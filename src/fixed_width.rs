@@ -0,0 +1,976 @@
+// Allocation-free signing and verification for 256-bit and 384-bit curves of
+// prime order, such as P-256, secp256k1 and P-384. The backend is generic over
+// the number of u64 limbs: `FixedCurve` is the four-limb instance and
+// `FixedCurve384` the six-limb one. `new` converts the parameters once
+// (that part allocates); after that scalars, coordinates and digests live in
+// fixed-size stack arrays of u64 limbs, so `sign_digest` and `verify_digest`
+// never touch the heap. Products go through `field::mul_wide`, squares
+// through `field::square_wide`, and both are reduced with Montgomery's REDC.
+// Coordinates stay in Montgomery form, aR mod p, for the whole of the point
+// arithmetic and are converted only on the way in (`decode_point`, the
+// generator) and out (`to_affine`), so every field product is a single REDC. The secp256k1 prime, p = 2^256 - 2^32 - 977,
+// skips Montgomery form altogether: 2^256 = 2^32 + 977 mod p, so the high
+// half of a product folds into the low half with two small multiplications
+// and one conditional subtraction. The P-256 prime, 2^256 - 2^224 + 2^192 +
+// 2^96 - 1, does the same with the Solinas reduction of FIPS 186: signed sums
+// of the 32-bit words of the product, and so does the P-384 prime, 2^384 -
+// 2^128 - 2^96 + 2^32 - 1, on six limbs. Signing is plain ECDSA as in SEC1,
+// r = x(R) mod q and the digest taken by bits2int, and `sign_standard` and
+// `verify_standard` run through here on every curve either instance accepts.
+// Signatures are r || s and public keys 0x04 || x || y, the same bytes as
+// `encode_signature` and `encode_point_uncompressed` give for these curves.
+
+use alloc::vec;
+use num_bigint::BigUint;
+
+use crate::encoding::to_fixed_bytes;
+use crate::field::{mac, mul_wide, square_wide, Wide};
+use crate::signer::Signature;
+use crate::ECDSA;
+
+const WINDOW_BITS: usize = 4;
+const SECP256K1_P: [u64; 4] = [
+    0xffff_fffe_ffff_fc2f,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+];
+// 2^256 mod the secp256k1 prime
+const SECP256K1_C: u64 = 0x1_0000_03d1;
+const P256_P: [u64; 4] = [
+    0xffff_ffff_ffff_ffff,
+    0x0000_0000_ffff_ffff,
+    0x0000_0000_0000_0000,
+    0xffff_ffff_0000_0001,
+];
+// 2^256 mod the P-256 prime, 2^224 - 2^192 - 2^96 + 1
+const P256_C: [u64; 4] = [
+    0x0000_0000_0000_0001,
+    0xffff_ffff_0000_0000,
+    0xffff_ffff_ffff_ffff,
+    0x0000_0000_ffff_fffe,
+];
+const P384_P: [u64; 6] = [
+    0x0000_0000_ffff_ffff,
+    0xffff_ffff_0000_0000,
+    0xffff_ffff_ffff_fffe,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+];
+// 2^384 mod the P-384 prime, 2^128 + 2^96 - 2^32 + 1
+const P384_C: [u64; 6] = [
+    0xffff_ffff_0000_0001,
+    0x0000_0000_ffff_ffff,
+    0x0000_0000_0000_0001,
+    0,
+    0,
+    0,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Reduction {
+    // REDC, for any odd modulus
+    Montgomery,
+    // Values kept as they are (R = 1), products folded at 2^256
+    Secp256k1,
+    // R = 1 as well, products reduced by Solinas' word sums
+    P256,
+    P384,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Modulus<const N: usize> {
+    m: [u64; N],
+    // -m⁻¹ mod 2^64
+    m_inv: u64,
+    // R² mod m, R = 2^(64N)
+    r2: [u64; N],
+    // R mod m, 1 in Montgomery form
+    one: [u64; N],
+    reduction: Reduction,
+}
+
+// Jacobian coordinates in Montgomery form, Z = 0 for the identity
+#[derive(Clone, Copy, Debug)]
+struct FixedPoint<const N: usize> {
+    x: [u64; N],
+    y: [u64; N],
+    z: [u64; N],
+}
+
+#[derive(Clone, Debug)]
+pub struct FixedWidthCurve<const N: usize> {
+    p: Modulus<N>,
+    q: Modulus<N>,
+    // a and b in Montgomery form mod p
+    a: [u64; N],
+    b: [u64; N],
+    generator: FixedPoint<N>,
+}
+
+pub type FixedCurve = FixedWidthCurve<4>;
+pub type FixedCurve384 = FixedWidthCurve<6>;
+
+impl FixedCurve {
+    pub fn public_key(&self, priv_key: &[u8; 32]) -> Option<[u8; 65]> {
+        let mut out = [0u8; 65];
+        self.public_key_into(priv_key, &mut out)?;
+        Some(out)
+    }
+
+    pub fn sign_digest(
+        &self,
+        digest: &[u8; 32],
+        priv_key: &[u8; 32],
+        k_random: &[u8; 32],
+    ) -> Option<[u8; 64]> {
+        let mut out = [0u8; 64];
+        self.sign_into(digest, priv_key, k_random, &mut out)?;
+        Some(out)
+    }
+
+    pub fn verify_digest(
+        &self,
+        digest: &[u8; 32],
+        pub_key: &[u8; 65],
+        signature: &[u8; 64],
+    ) -> bool {
+        self.verify_bytes(digest, pub_key, signature)
+    }
+}
+
+impl FixedCurve384 {
+    pub fn public_key(&self, priv_key: &[u8; 48]) -> Option<[u8; 97]> {
+        let mut out = [0u8; 97];
+        self.public_key_into(priv_key, &mut out)?;
+        Some(out)
+    }
+
+    pub fn sign_digest(
+        &self,
+        digest: &[u8; 48],
+        priv_key: &[u8; 48],
+        k_random: &[u8; 48],
+    ) -> Option<[u8; 96]> {
+        let mut out = [0u8; 96];
+        self.sign_into(digest, priv_key, k_random, &mut out)?;
+        Some(out)
+    }
+
+    pub fn verify_digest(
+        &self,
+        digest: &[u8; 48],
+        pub_key: &[u8; 97],
+        signature: &[u8; 96],
+    ) -> bool {
+        self.verify_bytes(digest, pub_key, signature)
+    }
+}
+
+impl<const N: usize> FixedWidthCurve<N> {
+    pub fn new(ecdsa: &ECDSA) -> Option<FixedWidthCurve<N>> {
+        // p and q must both be 64N-bit numbers, and q > (p + 1 + 2√p) / 2,
+        // which by Hasse's bound leaves no room for a cofactor
+        let p = &ecdsa.elliptic_curve.p;
+        let q = &ecdsa.q_order;
+        let bits = 64 * N as u64;
+        let hasse = p + BigUint::from(1u32) + BigUint::from(2u32) * p.sqrt();
+        if p.bits() != bits || q.bits() != bits || q * BigUint::from(2u32) <= hasse {
+            return None;
+        }
+        let (gx, gy) = match &ecdsa.a_gen {
+            ec_generic::Point::Coor(x, y) => (x, y),
+            ec_generic::Point::Identity => return None,
+        };
+        let field = Modulus::new(p);
+        Some(FixedWidthCurve {
+            q: Modulus::new(q),
+            a: field.mont_in(&to_limbs(&ecdsa.elliptic_curve.a)),
+            b: field.mont_in(&to_limbs(&ecdsa.elliptic_curve.b)),
+            generator: FixedPoint {
+                x: field.mont_in(&to_limbs(gx)),
+                y: field.mont_in(&to_limbs(gy)),
+                z: field.one,
+            },
+            p: field,
+        })
+    }
+
+    fn public_key_into(&self, priv_key: &[u8], out: &mut [u8]) -> Option<()> {
+        // 0x04 || x || y into out, 16N + 1 bytes
+        let d = from_bytes(priv_key);
+        if d == [0; N] || !less_than(&d, &self.q.m) {
+            return None;
+        }
+        let (x, y) = self.to_affine(&self.mul(&self.generator, &d))?;
+        out[0] = 0x04;
+        write_bytes(&x, &mut out[1..=8 * N]);
+        write_bytes(&y, &mut out[8 * N + 1..]);
+        Some(())
+    }
+
+    fn sign_into(
+        &self,
+        digest: &[u8],
+        priv_key: &[u8],
+        k_random: &[u8],
+        out: &mut [u8],
+    ) -> Option<()> {
+        // R = kA -> r = x(R) mod q, s = (hash + d * r) * k⁻¹ mod q, and
+        // r || s into out, 16N bytes
+        let q = &self.q;
+        let d = from_bytes(priv_key);
+        let k = from_bytes(k_random);
+        if d == [0; N] || !less_than(&d, &q.m) || k == [0; N] || !less_than(&k, &q.m) {
+            return None;
+        }
+        let hash = self.bits_to_scalar(digest);
+        let (x, _) = self.to_affine(&self.mul(&self.generator, &k))?;
+        let r = reduce_once(&x, &q.m);
+        let s = q.mul(&q.add(&hash, &q.mul(&d, &r)), &q.inv(&k));
+        if r == [0; N] || s == [0; N] {
+            return None;
+        }
+        write_bytes(&r, &mut out[..8 * N]);
+        write_bytes(&s, &mut out[8 * N..]);
+        Some(())
+    }
+
+    fn verify_bytes(&self, digest: &[u8], pub_key: &[u8], signature: &[u8]) -> bool {
+        // P = u1 A + u2 B with u1 = hash * s⁻¹, u2 = r * s⁻¹, accept if
+        // x(P) mod q = r
+        let q = &self.q;
+        if signature.len() != 16 * N {
+            return false;
+        }
+        let pub_key = match self.decode_point(pub_key) {
+            Some(point) => point,
+            None => return false,
+        };
+        let r = from_bytes(&signature[..8 * N]);
+        let s = from_bytes(&signature[8 * N..]);
+        if r == [0; N] || !less_than(&r, &q.m) || s == [0; N] || !less_than(&s, &q.m) {
+            return false;
+        }
+
+        let hash = self.bits_to_scalar(digest);
+        let s_inv = q.inv(&s);
+        let u1 = q.mul(&hash, &s_inv);
+        let u2 = q.mul(&r, &s_inv);
+        let point = self.add(&self.mul(&self.generator, &u1), &self.mul(&pub_key, &u2));
+        matches!(self.to_affine(&point), Some((x, _)) if reduce_once(&x, &q.m) == r)
+    }
+
+    fn bits_to_scalar(&self, digest: &[u8]) -> [u64; N] {
+        // bits2int keeps all 64N bits of a digest that long, and with q above
+        // 2^(64N - 1) one subtraction reduces it
+        reduce_once(&from_bytes(digest), &self.q.m)
+    }
+
+    fn decode_point(&self, bytes: &[u8]) -> Option<FixedPoint<N>> {
+        // 0x04 || x || y with y² = x³ + ax + b
+        let p = &self.p;
+        if bytes.len() != 16 * N + 1 || bytes[0] != 0x04 {
+            return None;
+        }
+        let x = from_bytes(&bytes[1..=8 * N]);
+        let y = from_bytes(&bytes[8 * N + 1..]);
+        if !less_than(&x, &p.m) || !less_than(&y, &p.m) {
+            return None;
+        }
+        let (x, y) = (p.mont_in(&x), p.mont_in(&y));
+        let rhs = p.add(
+            &p.mont_mul(&p.add(&p.mont_square(&x), &self.a), &x),
+            &self.b,
+        );
+        if p.mont_square(&y) != rhs {
+            return None;
+        }
+        Some(FixedPoint { x, y, z: p.one })
+    }
+
+    fn identity() -> FixedPoint<N> {
+        FixedPoint {
+            x: small(1),
+            y: small(1),
+            z: [0; N],
+        }
+    }
+
+    fn double(&self, point: &FixedPoint<N>) -> FixedPoint<N> {
+        // S = 4XY², M = 3X² + aZ⁴
+        // X' = M² - 2S, Y' = M(S - X') - 8Y⁴, Z' = 2YZ
+        let p = &self.p;
+        if point.z == [0; N] || point.y == [0; N] {
+            return Self::identity();
+        }
+        let yy = p.mont_square(&point.y);
+        let zz = p.mont_square(&point.z);
+        let xx = p.mont_square(&point.x);
+        let s = p.double(&p.double(&p.mont_mul(&point.x, &yy)));
+        let m = p.add(
+            &p.add(&p.double(&xx), &xx),
+            &p.mont_mul(&self.a, &p.mont_square(&zz)),
+        );
+        let x = p.sub(&p.mont_square(&m), &p.double(&s));
+        let yyyy8 = p.double(&p.double(&p.double(&p.mont_square(&yy))));
+        let y = p.sub(&p.mont_mul(&m, &p.sub(&s, &x)), &yyyy8);
+        let z = p.mont_mul(&p.double(&point.y), &point.z);
+        FixedPoint { x, y, z }
+    }
+
+    fn add(&self, a: &FixedPoint<N>, b: &FixedPoint<N>) -> FixedPoint<N> {
+        // U1 = X1 Z2², U2 = X2 Z1², S1 = Y1 Z2³, S2 = Y2 Z1³, H = U2 - U1, R = S2 - S1
+        // X3 = R² - H³ - 2 U1 H², Y3 = R(U1 H² - X3) - S1 H³, Z3 = H Z1 Z2
+        let p = &self.p;
+        if a.z == [0; N] {
+            return *b;
+        }
+        if b.z == [0; N] {
+            return *a;
+        }
+        let z1z1 = p.mont_square(&a.z);
+        let z2z2 = p.mont_square(&b.z);
+        let u1 = p.mont_mul(&a.x, &z2z2);
+        let u2 = p.mont_mul(&b.x, &z1z1);
+        let s1 = p.mont_mul(&a.y, &p.mont_mul(&b.z, &z2z2));
+        let s2 = p.mont_mul(&b.y, &p.mont_mul(&a.z, &z1z1));
+        if u1 == u2 {
+            if s1 == s2 {
+                return self.double(a);
+            }
+            return Self::identity();
+        }
+        let h = p.sub(&u2, &u1);
+        let r = p.sub(&s2, &s1);
+        let hh = p.mont_square(&h);
+        let hhh = p.mont_mul(&hh, &h);
+        let v = p.mont_mul(&u1, &hh);
+        let x = p.sub(&p.sub(&p.mont_square(&r), &hhh), &p.double(&v));
+        let y = p.sub(&p.mont_mul(&r, &p.sub(&v, &x)), &p.mont_mul(&s1, &hhh));
+        let z = p.mont_mul(&h, &p.mont_mul(&a.z, &b.z));
+        FixedPoint { x, y, z }
+    }
+
+    fn mul(&self, point: &FixedPoint<N>, k: &[u64; N]) -> FixedPoint<N> {
+        // Fixed 4-bit windows from the top, table entries picked by masking
+        // in every entry, as `mul_point_ct`, unless `vartime-tables` is on
+        let mut table = [Self::identity(); 1 << WINDOW_BITS];
+        for j in 1..table.len() {
+            table[j] = self.add(&table[j - 1], point);
+        }
+        let mut result = Self::identity();
+        for limb in k.iter().rev() {
+            for shift in (0..64 / WINDOW_BITS).rev() {
+                for _ in 0..WINDOW_BITS {
+                    result = self.double(&result);
+                }
+                let digit = ((limb >> (shift * WINDOW_BITS)) & 0x0f) as usize;
+                result = self.add(&result, &lookup(&table, digit));
+            }
+        }
+        result
+    }
+
+    fn to_affine(&self, point: &FixedPoint<N>) -> Option<([u64; N], [u64; N])> {
+        // x = X / Z², y = Y / Z³
+        let p = &self.p;
+        if point.z == [0; N] {
+            return None;
+        }
+        let z_inv = p.inv_mont(&point.z);
+        let z_inv2 = p.mont_square(&z_inv);
+        Some((
+            p.mont_out(&p.mont_mul(&point.x, &z_inv2)),
+            p.mont_out(&p.mont_mul(&point.y, &p.mont_mul(&z_inv2, &z_inv))),
+        ))
+    }
+}
+
+impl<const N: usize> FixedWidthCurve<N> {
+    fn sign_biguint(
+        &self,
+        k_random: &mut impl FnMut() -> BigUint,
+        hash: &BigUint,
+        priv_key: &BigUint,
+    ) -> Signature {
+        // hash and priv_key below q
+        let len = 8 * N;
+        let (hash, priv_key) = (to_fixed_bytes(hash, len), to_fixed_bytes(priv_key, len));
+        let mut out = vec![0u8; 2 * len];
+        loop {
+            // Only r = 0 or s = 0 fail from here on, so draw another k
+            let k = to_fixed_bytes(&k_random(), len);
+            if self.sign_into(&hash, &priv_key, &k, &mut out).is_some() {
+                return Signature::new(
+                    BigUint::from_bytes_be(&out[..len]),
+                    BigUint::from_bytes_be(&out[len..]),
+                );
+            }
+        }
+    }
+
+    fn verify_biguint(
+        &self,
+        ecdsa: &ECDSA,
+        hash: &BigUint,
+        pub_key: &ec_generic::Point,
+        signature: &Signature,
+    ) -> bool {
+        // hash, r and s below q and the key valid, so every encoding has
+        // the lengths `verify_bytes` expects
+        self.verify_bytes(
+            &to_fixed_bytes(hash, 8 * N),
+            &ecdsa.encode_point_uncompressed(pub_key),
+            &ecdsa.encode_signature(signature),
+        )
+    }
+}
+
+impl ECDSA {
+    pub(crate) fn sign_fixed(
+        &self,
+        mut k_random: impl FnMut() -> BigUint,
+        hash: &BigUint,
+        priv_key: &BigUint,
+    ) -> Option<Signature> {
+        // None off the curves the fixed-width backend takes, or for a key
+        // out of range, so the caller can fall back to the generic path
+        if *priv_key == BigUint::from(0u32) || priv_key >= &self.q_order {
+            return None;
+        }
+        if let Some(curve) = FixedCurve::new(self) {
+            return Some(curve.sign_biguint(&mut k_random, hash, priv_key));
+        }
+        let curve = FixedCurve384::new(self)?;
+        Some(curve.sign_biguint(&mut k_random, hash, priv_key))
+    }
+
+    pub(crate) fn verify_fixed(
+        &self,
+        hash: &BigUint,
+        pub_key: &ec_generic::Point,
+        signature: &Signature,
+    ) -> Option<bool> {
+        // None off the curves the fixed-width backend takes
+        if let Some(curve) = FixedCurve::new(self) {
+            return Some(curve.verify_biguint(self, hash, pub_key, signature));
+        }
+        let curve = FixedCurve384::new(self)?;
+        Some(curve.verify_biguint(self, hash, pub_key, signature))
+    }
+}
+
+impl<const N: usize> Modulus<N> {
+    fn new(m: &BigUint) -> Modulus<N> {
+        // m odd, so that m⁻¹ mod 2^64 exists: Newton's iteration
+        // x <- x(2 - mx) doubles the correct low bits every step
+        let m_limbs = to_limbs(m);
+        let special = match &m_limbs[..] {
+            _ if m.bits() != 64 * N as u64 => None,
+            limbs if limbs == SECP256K1_P => Some(Reduction::Secp256k1),
+            limbs if limbs == P256_P => Some(Reduction::P256),
+            limbs if limbs == P384_P => Some(Reduction::P384),
+            _ => None,
+        };
+        if let Some(reduction) = special {
+            // With R = 1, mont_in and mont_out leave values unchanged
+            return Modulus {
+                m: m_limbs,
+                m_inv: 0,
+                r2: small(1),
+                one: small(1),
+                reduction,
+            };
+        }
+        let mut inv = 1u64;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(m_limbs[0].wrapping_mul(inv)));
+        }
+        let bits = 64 * N;
+        Modulus {
+            m: m_limbs,
+            m_inv: inv.wrapping_neg(),
+            r2: to_limbs(&((BigUint::from(1u32) << (2 * bits)) % m)),
+            one: to_limbs(&((BigUint::from(1u32) << bits) % m)),
+            reduction: Reduction::Montgomery,
+        }
+    }
+
+    fn mont_in(&self, a: &[u64; N]) -> [u64; N] {
+        // a R² R⁻¹ = aR
+        self.mont_mul(a, &self.r2)
+    }
+
+    fn mont_out(&self, a: &[u64; N]) -> [u64; N] {
+        // aR R⁻¹ = a
+        self.mont_mul(a, &small(1))
+    }
+
+    fn double(&self, a: &[u64; N]) -> [u64; N] {
+        self.add(a, a)
+    }
+
+    fn add(&self, a: &[u64; N], b: &[u64; N]) -> [u64; N] {
+        let (sum, carry) = add_carry(a, b);
+        if carry || !less_than(&sum, &self.m) {
+            return sub_borrow(&sum, &self.m).0;
+        }
+        sum
+    }
+
+    fn sub(&self, a: &[u64; N], b: &[u64; N]) -> [u64; N] {
+        let (difference, borrow) = sub_borrow(a, b);
+        if borrow {
+            return add_carry(&difference, &self.m).0;
+        }
+        difference
+    }
+
+    fn mont_mul(&self, a: &[u64; N], b: &[u64; N]) -> [u64; N] {
+        self.redc(mul_wide(a, b))
+    }
+
+    fn mont_square(&self, a: &[u64; N]) -> [u64; N] {
+        self.redc(square_wide(a))
+    }
+
+    fn redc(&self, mut t: Wide) -> [u64; N] {
+        // REDC: t R⁻¹ mod m, adding multiples of m that clear the low limbs
+        // one at a time
+        match self.reduction {
+            Reduction::Secp256k1 => return resize(&reduce_secp256k1(&t)),
+            Reduction::P256 => return resize(&reduce_p256(&t)),
+            Reduction::P384 => return resize(&reduce_p384(&t)),
+            Reduction::Montgomery => {}
+        }
+        let mut top = 0u64;
+        for i in 0..N {
+            let u = t[i].wrapping_mul(self.m_inv);
+            let mut carry = 0;
+            for j in 0..N {
+                (t[i + j], carry) = mac(t[i + j], u, self.m[j], carry);
+            }
+            for limb in t[i + N..2 * N].iter_mut() {
+                let (sum, overflow) = limb.overflowing_add(carry);
+                *limb = sum;
+                carry = overflow as u64;
+            }
+            top += carry;
+        }
+        // Without a branch: the inverse of k goes through here
+        let result: [u64; N] = core::array::from_fn(|i| t[N + i]);
+        let (difference, borrow) = sub_borrow(&result, &self.m);
+        let keep = ((top == 0) & borrow) as u64;
+        select(keep.wrapping_neg(), &result, &difference)
+    }
+
+    fn mul(&self, a: &[u64; N], b: &[u64; N]) -> [u64; N] {
+        // For operands outside Montgomery form: (aR) b R⁻¹ = a b
+        self.mont_mul(&self.mont_in(a), b)
+    }
+
+    fn inv(&self, a: &[u64; N]) -> [u64; N] {
+        self.mont_out(&self.inv_mont(&self.mont_in(a)))
+    }
+
+    fn inv_mont(&self, a: &[u64; N]) -> [u64; N] {
+        // (aR)⁻¹ as a^(m - 2) R, m prime, square and multiply over every bit
+        let exponent = sub_borrow(&self.m, &small(2)).0;
+        let mut result = self.one;
+        for limb in exponent.iter().rev() {
+            for bit in (0..64).rev() {
+                result = self.mont_square(&result);
+                if (limb >> bit) & 1 == 1 {
+                    result = self.mont_mul(&result, a);
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(not(feature = "vartime-tables"))]
+fn lookup<const N: usize>(table: &[FixedPoint<N>], index: usize) -> FixedPoint<N> {
+    ct_select(table, index)
+}
+
+#[cfg(feature = "vartime-tables")]
+fn lookup<const N: usize>(table: &[FixedPoint<N>], index: usize) -> FixedPoint<N> {
+    table[index]
+}
+
+#[cfg_attr(feature = "vartime-tables", allow(dead_code))]
+fn ct_select<const N: usize>(table: &[FixedPoint<N>], index: usize) -> FixedPoint<N> {
+    // Every entry is read; mask is all ones for the wanted one only
+    let mut out = FixedPoint {
+        x: [0; N],
+        y: [0; N],
+        z: [0; N],
+    };
+    for (j, entry) in table.iter().enumerate() {
+        let diff = (j ^ index) as u64;
+        let mask = ((diff | diff.wrapping_neg()) >> 63).wrapping_sub(1);
+        for i in 0..N {
+            out.x[i] |= mask & entry.x[i];
+            out.y[i] |= mask & entry.y[i];
+            out.z[i] |= mask & entry.z[i];
+        }
+    }
+    out
+}
+
+fn reduce_secp256k1(t: &Wide) -> [u64; 4] {
+    // t = hi 2^256 + lo = lo + hi c with c = 2^32 + 977, under 2^290; the
+    // top limb folds in the same way, and a last carry out is worth c again
+    let mut folded = [0; 4];
+    let mut carry = 0;
+    for i in 0..4 {
+        (folded[i], carry) = mac(t[i], t[i + 4], SECP256K1_C, carry);
+    }
+    let (low, high) = mac(0, carry, SECP256K1_C, 0);
+    let (sum, overflow) = add_carry(&folded, &[low, high, 0, 0]);
+    let (sum, _) = add_carry(&sum, &[overflow as u64 * SECP256K1_C, 0, 0, 0]);
+    // sum < 2^256 < 2p
+    let (difference, borrow) = sub_borrow(&sum, &SECP256K1_P);
+    select((borrow as u64).wrapping_neg(), &sum, &difference)
+}
+
+fn reduce_p256(t: &Wide) -> [u64; 4] {
+    // t as 32-bit words c0..c15: s1 + 2 s2 + 2 s3 + s4 + s5 - d1 - d2 - d3 - d4
+    // (FIPS 186-4, D.2.3), summed word by word in i64
+    let c: [i64; 16] = core::array::from_fn(|k| (t[k / 2] >> (32 * (k % 2))) as u32 as i64);
+    let sums = [
+        c[0] + c[8] + c[9] - c[11] - c[12] - c[13] - c[14],
+        c[1] + c[9] + c[10] - c[12] - c[13] - c[14] - c[15],
+        c[2] + c[10] + c[11] - c[13] - c[14] - c[15],
+        c[3] + 2 * c[11] + 2 * c[12] + c[13] - c[15] - c[8] - c[9],
+        c[4] + 2 * c[12] + 2 * c[13] + c[14] - c[9] - c[10],
+        c[5] + 2 * c[13] + 2 * c[14] + c[15] - c[10] - c[11],
+        c[6] + 3 * c[14] + 2 * c[15] + c[13] - c[8] - c[9],
+        c[7] + 3 * c[15] + c[8] - c[10] - c[11] - c[12] - c[13],
+    ];
+    solinas_fold(sums, &P256_C, &P256_P)
+}
+
+fn reduce_p384(t: &Wide) -> [u64; 6] {
+    // t as 32-bit words c0..c23: t + 2 s1 + s2 + s3 + s4 + s5 + s6 - d1 - d2
+    // - d3 (FIPS 186-4, D.2.4), summed word by word in i64
+    let c: [i64; 24] = core::array::from_fn(|k| (t[k / 2] >> (32 * (k % 2))) as u32 as i64);
+    let sums = [
+        c[0] + c[12] + c[20] + c[21] - c[23],
+        c[1] + c[13] + c[22] + c[23] - c[12] - c[20],
+        c[2] + c[14] + c[23] - c[13] - c[21],
+        c[3] + c[12] + c[15] + c[20] + c[21] - c[14] - c[22] - c[23],
+        c[4] + c[12] + c[13] + c[16] + c[20] + 2 * c[21] + c[22] - c[15] - 2 * c[23],
+        c[5] + c[13] + c[14] + c[17] + c[21] + 2 * c[22] + c[23] - c[16],
+        c[6] + c[14] + c[15] + c[18] + c[22] + 2 * c[23] - c[17],
+        c[7] + c[15] + c[16] + c[19] + c[23] - c[18],
+        c[8] + c[16] + c[17] + c[20] - c[19],
+        c[9] + c[17] + c[18] + c[21] - c[20],
+        c[10] + c[18] + c[19] + c[22] - c[21],
+        c[11] + c[19] + c[20] + c[23] - c[22],
+    ];
+    solinas_fold(sums, &P384_C, &P384_P)
+}
+
+fn solinas_fold<const W: usize, const N: usize>(
+    sums: [i64; W],
+    c: &[u64; N],
+    p: &[u64; N],
+) -> [u64; N] {
+    // Carries the signed word sums through, W = 2N words, then folds the
+    // small signed carry out of the top with 2^(64N) = c mod p twice, which
+    // leaves 0 <= value < 2^(64N) < 2p
+    let mut words = [0u64; W];
+    let mut carry = 0i64;
+    for (word, sum) in words.iter_mut().zip(sums) {
+        let value = sum + carry;
+        *word = value as u64 & 0xffff_ffff;
+        carry = value >> 32;
+    }
+    let mut value: [u64; N] = core::array::from_fn(|i| words[2 * i] | (words[2 * i + 1] << 32));
+    for _ in 0..2 {
+        let mut wide_carry = 0i128;
+        for i in 0..N {
+            let wide = value[i] as i128 + carry as i128 * c[i] as i128 + wide_carry;
+            value[i] = wide as u64;
+            wide_carry = wide >> 64;
+        }
+        carry = wide_carry as i64;
+    }
+    let (difference, borrow) = sub_borrow(&value, p);
+    select((borrow as u64).wrapping_neg(), &value, &difference)
+}
+
+fn select<const N: usize>(mask: u64, a: &[u64; N], b: &[u64; N]) -> [u64; N] {
+    // a where mask is all ones, b where it is zero
+    core::array::from_fn(|i| (a[i] & mask) | (b[i] & !mask))
+}
+
+fn add_carry<const N: usize>(a: &[u64; N], b: &[u64; N]) -> ([u64; N], bool) {
+    let mut out = [0; N];
+    let mut carry = false;
+    for i in 0..N {
+        let (sum, overflow1) = a[i].overflowing_add(b[i]);
+        let (sum, overflow2) = sum.overflowing_add(carry as u64);
+        out[i] = sum;
+        carry = overflow1 || overflow2;
+    }
+    (out, carry)
+}
+
+fn sub_borrow<const N: usize>(a: &[u64; N], b: &[u64; N]) -> ([u64; N], bool) {
+    let mut out = [0; N];
+    let mut borrow = false;
+    for i in 0..N {
+        let (difference, underflow1) = a[i].overflowing_sub(b[i]);
+        let (difference, underflow2) = difference.overflowing_sub(borrow as u64);
+        out[i] = difference;
+        borrow = underflow1 || underflow2;
+    }
+    (out, borrow)
+}
+
+fn less_than<const N: usize>(a: &[u64; N], b: &[u64; N]) -> bool {
+    sub_borrow(a, b).1
+}
+
+fn reduce_once<const N: usize>(a: &[u64; N], m: &[u64; N]) -> [u64; N] {
+    if less_than(a, m) {
+        return *a;
+    }
+    sub_borrow(a, m).0
+}
+
+fn small<const N: usize>(value: u64) -> [u64; N] {
+    let mut limbs = [0; N];
+    limbs[0] = value;
+    limbs
+}
+
+fn resize<const M: usize, const N: usize>(limbs: &[u64; M]) -> [u64; N] {
+    // The special reductions only run with M = N; this just restates the type
+    core::array::from_fn(|i| limbs[i])
+}
+
+fn from_bytes<const N: usize>(bytes: &[u8]) -> [u64; N] {
+    // 8N big-endian bytes to little-endian limbs
+    let mut limbs = [0; N];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.rchunks(8)) {
+        *limb = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    limbs
+}
+
+fn write_bytes<const N: usize>(limbs: &[u64; N], out: &mut [u8]) {
+    // Little-endian limbs to 8N big-endian bytes
+    for (limb, chunk) in limbs.iter().zip(out.rchunks_mut(8)) {
+        chunk.copy_from_slice(&limb.to_be_bytes());
+    }
+}
+
+fn to_limbs<const N: usize>(value: &BigUint) -> [u64; N] {
+    let mut limbs = [0; N];
+    for (limb, digit) in limbs.iter_mut().zip(value.iter_u64_digits()) {
+        *limb = digit;
+    }
+    limbs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+    use sha2::{Digest, Sha256};
+
+    use crate::curves;
+
+    fn bytes<const L: usize>(value: &BigUint) -> [u8; L] {
+        to_fixed_bytes(value, L).try_into().unwrap()
+    }
+
+    fn wide(limbs: &[u64]) -> Wide {
+        let mut wide = [0; 12];
+        wide[..limbs.len()].copy_from_slice(limbs);
+        wide
+    }
+
+    fn to_biguint(limbs: &[u64]) -> BigUint {
+        limbs
+            .iter()
+            .rev()
+            .fold(BigUint::from(0u32), |acc, &limb| (acc << 64) + limb)
+    }
+
+    #[test]
+    fn test_fixed_curve_matches_biguint() {
+        for ecdsa in [curves::p256(), curves::secp256k1()] {
+            let curve = FixedCurve::new(&ecdsa).unwrap();
+            let priv_key = BigUint::from(0xc0ffeeu32);
+            let k_random = BigUint::from(0xdecafu32);
+            let digest: [u8; 32] = Sha256::digest(b"Bob -> 1 BTC -> Alice").into();
+
+            let pub_key = curve.public_key(&bytes(&priv_key)).unwrap();
+            let expected = ecdsa.encode_point_uncompressed(&ecdsa.generate_pub_key(&priv_key));
+            assert_eq!(pub_key.to_vec(), expected);
+
+            let signature = curve
+                .sign_digest(&digest, &bytes(&priv_key), &bytes(&k_random))
+                .unwrap();
+            let hash = ecdsa.bits_to_scalar(&digest);
+            let (r, s) = ecdsa.sign(&hash, &priv_key, &k_random);
+            assert_eq!(
+                signature.to_vec(),
+                ecdsa.encode_signature(&Signature::new(r % &ecdsa.q_order, s))
+            );
+
+            assert!(curve.verify_digest(&digest, &pub_key, &signature));
+            let other: [u8; 32] = Sha256::digest(b"Bob -> 2 BTC -> Alice").into();
+            assert!(!curve.verify_digest(&other, &pub_key, &signature));
+        }
+    }
+
+    #[test]
+    fn test_fixed_curve_384_matches_biguint() {
+        let ecdsa = curves::p384();
+        assert!(FixedCurve::new(&ecdsa).is_none());
+        let curve = FixedCurve384::new(&ecdsa).unwrap();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let k_random = BigUint::from(0xdecafu32);
+        let digest: [u8; 48] = sha2::Sha384::digest(b"Bob -> 1 BTC -> Alice").into();
+
+        let pub_key = curve.public_key(&bytes(&priv_key)).unwrap();
+        let expected = ecdsa.encode_point_uncompressed(&ecdsa.generate_pub_key(&priv_key));
+        assert_eq!(pub_key.to_vec(), expected);
+
+        let signature = curve
+            .sign_digest(&digest, &bytes(&priv_key), &bytes(&k_random))
+            .unwrap();
+        let hash = ecdsa.bits_to_scalar(&digest);
+        let (r, s) = ecdsa.sign(&hash, &priv_key, &k_random);
+        assert_eq!(
+            signature.to_vec(),
+            ecdsa.encode_signature(&Signature::new(r % &ecdsa.q_order, s))
+        );
+        assert!(curve.verify_digest(&digest, &pub_key, &signature));
+        let mut other = digest;
+        other[0] ^= 1;
+        assert!(!curve.verify_digest(&other, &pub_key, &signature));
+    }
+
+    #[test]
+    fn test_secp256k1_reduction() {
+        // Against BigUint, including the largest products and inputs at p
+        let p = curves::secp256k1().elliptic_curve.p;
+        let field = Modulus::<4>::new(&p);
+        assert_eq!(field.reduction, Reduction::Secp256k1);
+        let max: BigUint = (BigUint::from(1u32) << 256) - 1u32;
+        let values = [
+            BigUint::from(0u32),
+            BigUint::from(1u32),
+            &p - 1u32,
+            p.clone(),
+            max.clone(),
+            BigUint::from(SECP256K1_C) << 200,
+            BigUint::from_bytes_be(&Sha256::digest(b"secp256k1")),
+        ];
+        for a in &values {
+            for b in &values {
+                let product = field.mont_mul(&to_limbs(a), &to_limbs(b));
+                assert_eq!(product, to_limbs(&(a * b % &p)));
+            }
+        }
+        assert_eq!(
+            reduce_secp256k1(&wide(&[u64::MAX; 8])),
+            to_limbs(&(((BigUint::from(1u32) << 512) - 1u32) % &p))
+        );
+    }
+
+    #[test]
+    fn test_p256_reduction() {
+        let p = curves::p256().elliptic_curve.p;
+        let field = Modulus::<4>::new(&p);
+        assert_eq!(field.reduction, Reduction::P256);
+        let max: BigUint = (BigUint::from(1u32) << 256) - 1u32;
+        let values = [
+            BigUint::from(0u32),
+            BigUint::from(1u32),
+            &p - 1u32,
+            p.clone(),
+            max,
+            BigUint::from(0xffff_ffffu32) << 224,
+            BigUint::from_bytes_be(&Sha256::digest(b"P-256")),
+        ];
+        for a in &values {
+            for b in &values {
+                let product = field.mont_mul(&to_limbs(a), &to_limbs(b));
+                assert_eq!(product, to_limbs(&(a * b % &p)));
+            }
+        }
+        // Word patterns that drive the signed sums to both extremes
+        for limbs in [
+            [u64::MAX; 8],
+            [0, 0, 0, 0, u64::MAX, 0, 0, 0],
+            [0, 0, 0, 0, 0, u64::MAX, 0, u64::MAX],
+        ] {
+            let expected = to_biguint(&limbs) % &p;
+            assert_eq!(reduce_p256(&wide(&limbs)), to_limbs(&expected));
+        }
+    }
+
+    #[test]
+    fn test_p384_reduction() {
+        let p = curves::p384().elliptic_curve.p;
+        let field = Modulus::<6>::new(&p);
+        assert_eq!(field.reduction, Reduction::P384);
+        let max: BigUint = (BigUint::from(1u32) << 384) - 1u32;
+        let values = [
+            BigUint::from(0u32),
+            BigUint::from(1u32),
+            &p - 1u32,
+            p.clone(),
+            max,
+            BigUint::from(0xffff_ffffu32) << 352,
+            BigUint::from_bytes_be(&sha2::Sha384::digest(b"P-384")),
+        ];
+        for a in &values {
+            for b in &values {
+                let product = field.mont_mul(&to_limbs(a), &to_limbs(b));
+                assert_eq!(product, to_limbs(&(a * b % &p)));
+            }
+        }
+        // The words that enter the sums with negative and with doubled
+        // coefficients, set alone and all together
+        let c20_to_c23 = 0xffff_ffff_ffff_ffff;
+        for limbs in [
+            [u64::MAX; 12],
+            [0, 0, 0, 0, 0, 0, u64::MAX, 0, 0, 0, 0, 0],
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, c20_to_c23, c20_to_c23],
+            [
+                u64::MAX,
+                u64::MAX,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                u64::MAX << 32,
+            ],
+        ] {
+            let expected = to_biguint(&limbs) % &p;
+            assert_eq!(reduce_p384(&limbs), to_limbs(&expected));
+        }
+    }
+
+    #[test]
+    fn test_fixed_curve_rejects_small_curves() {
+        let ecdsa = toy_curve();
+        assert!(FixedCurve::new(&ecdsa).is_none());
+    }
+}
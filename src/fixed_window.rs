@@ -0,0 +1,126 @@
+// Fixed-window scalar multiplication with a constant-time table lookup. The
+// scalar is read in 4-bit windows from the top, always as many windows as
+// the group order has, and every window does four doublings and one
+// addition. The table entry for a window is picked by reading all 16 entries
+// and masking in the wanted one, so neither the memory access pattern nor the
+// number of operations depends on the scalar. The `BigUint` arithmetic below
+// the lookup is still variable time; the fixed-width backend closes that gap.
+//
+// Every secret-indexed table in the crate (here, `FixedCurve` and
+// `PointTable`) goes through such a scan. The scan exists for CPUs with data
+// caches, where an indexed load leaves a trace another process can time. An
+// in-order core without a data cache, as in many microcontrollers, loads every
+// address in the same time, and there the `vartime-tables` feature swaps each
+// scan for a direct load. Leave it off anywhere else.
+
+use alloc::{vec, vec::Vec};
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::encoding::to_fixed_bytes;
+use crate::jacobian::JacobianPoint;
+use crate::ECDSA;
+
+const WINDOW_BITS: usize = 4;
+
+impl ECDSA {
+    pub fn mul_point_ct(&self, point: &Point, k: &BigUint) -> Point {
+        // table[j] = jP for j in 0..16, kept as fixed-size encodings so that
+        // every entry is read in full on every lookup
+        let field_len = self.field_len();
+        let base = self.to_jacobian(point);
+        let mut table = vec![self.to_jacobian(&Point::Identity)];
+        for j in 1..(1 << WINDOW_BITS) {
+            table.push(self.jacobian_add(&table[j - 1], &base));
+        }
+        let table: Vec<Vec<u8>> = table
+            .iter()
+            .map(|entry| entry.to_bytes(field_len))
+            .collect();
+
+        let k = to_fixed_bytes(&(k % &self.q_order), self.scalar_len());
+        let mut result = self.to_jacobian(&Point::Identity);
+        for byte in k {
+            for digit in [byte >> 4, byte & 0x0f] {
+                for _ in 0..WINDOW_BITS {
+                    result = self.jacobian_double(&result);
+                }
+                let entry = JacobianPoint::from_bytes(&lookup(&table, digit as usize)).unwrap();
+                result = self.jacobian_add(&result, &entry);
+            }
+        }
+        self.batch_to_affine(&[result]).pop().unwrap()
+    }
+}
+
+#[cfg(not(feature = "vartime-tables"))]
+pub fn lookup(table: &[Vec<u8>], index: usize) -> Vec<u8> {
+    ct_select(table, index)
+}
+
+#[cfg(feature = "vartime-tables")]
+pub fn lookup(table: &[Vec<u8>], index: usize) -> Vec<u8> {
+    table[index].clone()
+}
+
+#[cfg_attr(feature = "vartime-tables", allow(dead_code))]
+fn ct_select(table: &[Vec<u8>], index: usize) -> Vec<u8> {
+    // out = table[index], touching every entry: mask is 0xff for the wanted
+    // entry and 0x00 for the others, computed without a branch
+    let mut out = vec![0u8; table[0].len()];
+    for (j, entry) in table.iter().enumerate() {
+        let diff = (j ^ index) as u64;
+        let mask = ((diff | diff.wrapping_neg()) >> 63) as u8;
+        let mask = mask.wrapping_sub(1);
+        for (out, byte) in out.iter_mut().zip(entry) {
+            *out |= mask & byte;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_mul_point_ct() {
+        let ecdsa = toy_curve();
+        let point = ecdsa.mul_vartime(&ecdsa.a_gen, &BigUint::from(3u32));
+        for k in 0u32..40 {
+            let k = BigUint::from(k);
+            assert_eq!(
+                ecdsa.mul_point_ct(&point, &k),
+                ecdsa.mul_vartime(&point, &k)
+            );
+        }
+        assert_eq!(
+            ecdsa.mul_point_ct(&Point::Identity, &BigUint::from(5u32)),
+            Point::Identity
+        );
+    }
+
+    #[test]
+    fn test_mul_point_ct_p256() {
+        let ecdsa = crate::curves::p256();
+        let k = BigUint::parse_bytes(
+            b"c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721",
+            16,
+        )
+        .unwrap();
+        assert_eq!(
+            ecdsa.mul_point_ct(&ecdsa.a_gen, &k),
+            ecdsa.mul_vartime(&ecdsa.a_gen, &k)
+        );
+    }
+
+    #[test]
+    fn test_ct_select() {
+        let table: Vec<Vec<u8>> = (0u8..16).map(|j| vec![j, 0xff - j]).collect();
+        for index in 0..16 {
+            assert_eq!(ct_select(&table, index), table[index]);
+            assert_eq!(lookup(&table, index), table[index]);
+        }
+    }
+}
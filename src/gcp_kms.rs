@@ -0,0 +1,229 @@
+// Keys held in Google Cloud KMS, behind the `gcp-kms` feature. A signer names
+// one crypto key version (projects/*/locations/*/keyRings/*/cryptoKeys/*/
+// cryptoKeyVersions/*) of algorithm EC_SIGN_P256_SHA256 or
+// EC_SIGN_SECP256K1_SHA256. `sign_async` hashes the message with SHA-256
+// here, sends the digest to asymmetricSign and checks the DER signature it
+// gets back against the key from getPublicKey. As with AWS KMS these are
+// plain ECDSA signatures over the digest, for `VerifyingKey`,
+// `verify_standard` and `KeyPair::verify`.
+//
+// Requests carry an OAuth 2.0 access token; obtaining and refreshing it
+// (service account, workload identity, metadata server) is up to the caller.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ec_generic::Point;
+use serde::{Deserialize, Serialize};
+use signature::Error;
+
+use crate::hash::sha256;
+use crate::pem;
+use crate::signer::{AsyncSigner, Signature};
+use crate::ECDSA;
+
+pub struct GcpKmsSigner<'a> {
+    ecdsa: &'a ECDSA,
+    client: reqwest::Client,
+    endpoint: String,
+    key_version: String,
+    access_token: String,
+}
+
+#[derive(Serialize)]
+struct AsymmetricSignRequest {
+    digest: Digest,
+}
+
+#[derive(Serialize)]
+struct Digest {
+    sha256: String,
+}
+
+#[derive(Deserialize)]
+struct AsymmetricSignResponse {
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct PublicKeyResponse {
+    pem: String,
+    algorithm: String,
+}
+
+impl<'a> GcpKmsSigner<'a> {
+    pub fn new(ecdsa: &'a ECDSA, key_version: &str, access_token: &str) -> GcpKmsSigner<'a> {
+        GcpKmsSigner {
+            ecdsa,
+            client: reqwest::Client::new(),
+            endpoint: "https://cloudkms.googleapis.com".into(),
+            key_version: key_version.trim_matches('/').to_string(),
+            access_token: access_token.to_string(),
+        }
+    }
+
+    pub fn with_endpoint(mut self, endpoint: &str) -> GcpKmsSigner<'a> {
+        self.endpoint = endpoint.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub async fn public_key(&self) -> Result<Point, Error> {
+        // An error unless the key version is on this signer's curve
+        let url = format!("{}/v1/{}/publicKey", self.endpoint, self.key_version);
+        let response: PublicKeyResponse = self.send(self.client.get(url)).await?;
+        let expected = if self.ecdsa.is_p256() {
+            "EC_SIGN_P256_SHA256"
+        } else if self.ecdsa.is_secp256k1() {
+            "EC_SIGN_SECP256K1_SHA256"
+        } else {
+            return Err(Error::new());
+        };
+        match pem::public_key_from_pem(&response.pem) {
+            Some((ecdsa, pub_key))
+                if response.algorithm == expected && ecdsa.same_parameters(self.ecdsa) =>
+            {
+                Ok(pub_key)
+            }
+            _ => Err(Error::new()),
+        }
+    }
+
+    async fn send<T: for<'de> Deserialize<'de>>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, Error> {
+        request
+            .bearer_auth(&self.access_token)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(Error::from_source)?
+            .json()
+            .await
+            .map_err(Error::from_source)
+    }
+}
+
+impl AsyncSigner for GcpKmsSigner<'_> {
+    async fn sign_async(&self, msg: &[u8]) -> Result<Signature, Error> {
+        let digest = sha256(msg);
+        let url = format!("{}/v1/{}:asymmetricSign", self.endpoint, self.key_version);
+        let body = AsymmetricSignRequest {
+            digest: Digest {
+                sha256: STANDARD.encode(&digest),
+            },
+        };
+        let response: AsymmetricSignResponse = self.send(self.client.post(url).json(&body)).await?;
+        let der = STANDARD
+            .decode(response.signature)
+            .map_err(Error::from_source)?;
+        let signature = Signature::from_der(&der).ok_or_else(Error::new)?;
+
+        let pub_key = self.public_key().await?;
+        if !self.ecdsa.verify_standard(&digest, &pub_key, &signature) {
+            return Err(Error::new());
+        }
+        Ok(signature)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use num_bigint::BigUint;
+    use signature::Verifier;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use crate::curves::secp256k1;
+    use crate::signer::VerifyingKey;
+
+    const KEY_VERSION: &str =
+        "projects/p/locations/global/keyRings/release/cryptoKeys/signing/cryptoKeyVersions/1";
+
+    fn serve(requests: usize) -> String {
+        // A stand-in for Cloud KMS holding one secp256k1 key version, one
+        // request per connection
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let ecdsa = secp256k1();
+            let priv_key = BigUint::from(0xc0ffeeu32);
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                let mut authorized = false;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end().to_ascii_lowercase();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(length) = line.strip_prefix("content-length: ") {
+                        content_length = length.parse().unwrap();
+                    }
+                    authorized |= line == "authorization: bearer ya29.token";
+                }
+                let mut body = vec![0u8; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let public_key = format!("GET /v1/{}/publicKey ", KEY_VERSION);
+                let sign = format!("POST /v1/{}:asymmetricSign ", KEY_VERSION);
+                let json = if !authorized {
+                    None
+                } else if request_line.starts_with(&public_key) {
+                    let pub_key = ecdsa.generate_pub_key(&priv_key);
+                    Some(serde_json::json!({
+                        "pem": ecdsa.public_key_to_pem(&pub_key).unwrap(),
+                        "algorithm": "EC_SIGN_SECP256K1_SHA256",
+                        "name": KEY_VERSION,
+                    }))
+                } else if request_line.starts_with(&sign) {
+                    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                    let digest = STANDARD
+                        .decode(body["digest"]["sha256"].as_str().unwrap())
+                        .unwrap();
+                    let signature = ecdsa.sign_standard(&mut crate::rng(), &digest, &priv_key);
+                    Some(serde_json::json!({
+                        "signature": STANDARD.encode(signature.to_der()),
+                        "name": KEY_VERSION,
+                    }))
+                } else {
+                    None
+                };
+                let reply = match json {
+                    Some(json) => {
+                        let json = json.to_string();
+                        format!(
+                            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                            json.len(),
+                            json
+                        )
+                    }
+                    None => "HTTP/1.1 401 Unauthorized\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                        .to_string(),
+                };
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_gcp_kms_signer() {
+        let ecdsa = secp256k1();
+        let signer = GcpKmsSigner::new(&ecdsa, KEY_VERSION, "ya29.token").with_endpoint(&serve(3));
+        let signature = signer.sign_async(b"release v1.2").await.unwrap();
+        let pub_key = signer.public_key().await.unwrap();
+        assert!(ecdsa.verify_standard(&sha256(b"release v1.2"), &pub_key, &signature));
+        let verifying_key = VerifyingKey::new(&ecdsa, pub_key).unwrap();
+        assert!(verifying_key.verify(b"release v1.2", &signature).is_ok());
+
+        let signer = GcpKmsSigner::new(&ecdsa, KEY_VERSION, "expired").with_endpoint(&serve(1));
+        assert!(signer.sign_async(b"release v1.2").await.is_err());
+    }
+}
@@ -0,0 +1,248 @@
+// Generators for property tests and fuzzing of code that handles this crate's
+// keys and signatures: `arbitrary::Arbitrary` implementations behind the
+// `arbitrary` feature and proptest strategies behind `proptest`. Both build
+// valid material, a key pair on a named curve with a message and its standard
+// ECDSA signature over SHA-256, everything derived from a 32-byte seed so a
+// failing case reproduces; `SignatureMutation` then breaks a valid signature
+// in one structured way, giving inputs every verifier must reject.
+
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::{BigUint, RandBigInt};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+pub use crate::curves::NamedCurve;
+use crate::hash::sha256;
+use crate::signer::Signature;
+use crate::ECDSA;
+
+#[derive(Clone, Debug)]
+pub struct SignatureCase {
+    pub curve: NamedCurve,
+    pub priv_key: BigUint,
+    pub pub_key: Point,
+    pub message: Vec<u8>,
+    pub signature: Signature,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum SignatureMutation {
+    ZeroR,
+    ZeroS,
+    // r + q and s + q, the same values mod q but out of range
+    ROverflow,
+    SOverflow,
+    SwapRS,
+    // Bit i of r || s, 0 being the top bit of r
+    FlipSignatureBit(u16),
+    // Bit i of the message, modulo its length; the message gets a byte if it
+    // was empty
+    FlipMessageBit(u16),
+}
+
+impl SignatureCase {
+    pub fn generate(curve: NamedCurve, seed: [u8; 32], message: Vec<u8>) -> SignatureCase {
+        // Key and nonce both drawn from a generator seeded with `seed`
+        let ecdsa = curve.ecdsa();
+        let mut rng = StdRng::from_seed(seed);
+        let priv_key = rng.gen_biguint_range(&BigUint::from(1u32), &ecdsa.q_order);
+        let signature = ecdsa.sign_standard(&mut rng, &sha256(&message), &priv_key);
+        SignatureCase {
+            curve,
+            pub_key: ecdsa.generate_pub_key(&priv_key),
+            priv_key,
+            message,
+            signature,
+        }
+    }
+
+    pub fn verify(&self) -> bool {
+        self.curve
+            .ecdsa()
+            .verify_standard(&sha256(&self.message), &self.pub_key, &self.signature)
+    }
+
+    pub fn mutated(&self, mutation: SignatureMutation) -> SignatureCase {
+        let q = self.curve.ecdsa().q_order;
+        let (r, s) = (self.signature.r().clone(), self.signature.s().clone());
+        let zero = BigUint::from(0u32);
+        let mut message = self.message.clone();
+        let (r, s) = match mutation {
+            SignatureMutation::ZeroR => (zero, s),
+            SignatureMutation::ZeroS => (r, zero),
+            SignatureMutation::ROverflow => (r + &q, s),
+            SignatureMutation::SOverflow => (r, s + &q),
+            SignatureMutation::SwapRS => (s, r),
+            SignatureMutation::FlipSignatureBit(i) => {
+                let i = u64::from(i) % 512;
+                let (mut r, mut s) = (r, s);
+                let (value, bit) = if i < 256 {
+                    (&mut r, 255 - i)
+                } else {
+                    (&mut s, 511 - i)
+                };
+                value.set_bit(bit, !value.bit(bit));
+                (r, s)
+            }
+            SignatureMutation::FlipMessageBit(i) => {
+                if message.is_empty() {
+                    message.push(0);
+                }
+                let i = usize::from(i) % (message.len() * 8);
+                message[i / 8] ^= 0x80 >> (i % 8);
+                (r, s)
+            }
+        };
+        SignatureCase {
+            message,
+            signature: Signature::new(r, s),
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls {
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    use super::*;
+
+    impl<'a> Arbitrary<'a> for NamedCurve {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<NamedCurve> {
+            Ok(*u.choose(&[NamedCurve::P256, NamedCurve::Secp256k1])?)
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for SignatureCase {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<SignatureCase> {
+            Ok(SignatureCase::generate(
+                u.arbitrary()?,
+                u.arbitrary()?,
+                u.arbitrary()?,
+            ))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for SignatureMutation {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<SignatureMutation> {
+            Ok(match u.int_in_range(0..=6)? {
+                0 => SignatureMutation::ZeroR,
+                1 => SignatureMutation::ZeroS,
+                2 => SignatureMutation::ROverflow,
+                3 => SignatureMutation::SOverflow,
+                4 => SignatureMutation::SwapRS,
+                5 => SignatureMutation::FlipSignatureBit(u.arbitrary()?),
+                _ => SignatureMutation::FlipMessageBit(u.arbitrary()?),
+            })
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for Signature {
+        // Unstructured r and s of up to 32 bytes each, mostly invalid
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Signature> {
+            let mut scalar = || -> Result<BigUint> {
+                let len = u.int_in_range(0..=32)?;
+                Ok(BigUint::from_bytes_be(u.bytes(len)?))
+            };
+            Ok(Signature::new(scalar()?, scalar()?))
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+pub mod strategies {
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    use super::*;
+
+    pub fn named_curve() -> impl Strategy<Value = NamedCurve> {
+        prop_oneof![Just(NamedCurve::P256), Just(NamedCurve::Secp256k1)]
+    }
+
+    pub fn signature_case() -> impl Strategy<Value = SignatureCase> {
+        (named_curve(), any::<[u8; 32]>(), vec(any::<u8>(), 0..256))
+            .prop_map(|(curve, seed, message)| SignatureCase::generate(curve, seed, message))
+    }
+
+    pub fn signature_mutation() -> impl Strategy<Value = SignatureMutation> {
+        prop_oneof![
+            Just(SignatureMutation::ZeroR),
+            Just(SignatureMutation::ZeroS),
+            Just(SignatureMutation::ROverflow),
+            Just(SignatureMutation::SOverflow),
+            Just(SignatureMutation::SwapRS),
+            any::<u16>().prop_map(SignatureMutation::FlipSignatureBit),
+            any::<u16>().prop_map(SignatureMutation::FlipMessageBit),
+        ]
+    }
+
+    pub fn invalid_signature_case() -> impl Strategy<Value = SignatureCase> {
+        (signature_case(), signature_mutation())
+            .prop_map(|(signed, mutation)| signed.mutated(mutation))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_signature_case() {
+        let signed = SignatureCase::generate(NamedCurve::Secp256k1, [7; 32], b"hello".to_vec());
+        assert!(signed.verify());
+        let again = SignatureCase::generate(NamedCurve::Secp256k1, [7; 32], b"hello".to_vec());
+        assert_eq!(signed.signature, again.signature);
+
+        for mutation in [
+            SignatureMutation::ZeroS,
+            SignatureMutation::ROverflow,
+            SignatureMutation::SwapRS,
+            SignatureMutation::FlipSignatureBit(300),
+            SignatureMutation::FlipMessageBit(9),
+        ] {
+            assert!(!signed.mutated(mutation).verify(), "{mutation:?}");
+        }
+        let empty = SignatureCase::generate(NamedCurve::P256, [1; 32], Vec::new());
+        assert_eq!(
+            empty.mutated(SignatureMutation::FlipMessageBit(3)).message,
+            [0x10]
+        );
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let bytes: Vec<u8> = (0u8..=255).collect();
+        let mut u = Unstructured::new(&bytes);
+        let signed = SignatureCase::arbitrary(&mut u).unwrap();
+        assert!(signed.verify());
+        let mutation = SignatureMutation::arbitrary(&mut u).unwrap();
+        assert!(!signed.mutated(mutation).verify());
+        assert!(Signature::arbitrary(&mut u).is_ok());
+    }
+
+    #[cfg(feature = "proptest")]
+    mod properties {
+        use proptest::prelude::*;
+
+        use super::super::strategies::*;
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(4))]
+
+            #[test]
+            fn valid_signatures_verify(signed in signature_case()) {
+                prop_assert!(signed.verify());
+            }
+
+            #[test]
+            fn mutated_signatures_fail(signed in invalid_signature_case()) {
+                prop_assert!(!signed.verify());
+            }
+        }
+    }
+}
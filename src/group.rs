@@ -0,0 +1,168 @@
+// Group abstraction behind the ECDSA equations. `sign` and `verify` only need
+// a cyclic group of prime order q with a generator, and a way to turn an
+// element into the integer r, so any backend implementing `Group` (another
+// curve arithmetic, an externally defined curve, even a Schnorr subgroup of
+// Z_p^*, which turns ECDSA back into DSA) reuses the same signing logic.
+// Scalars stay plain `BigUint`s reduced mod q.
+
+use ec_generic::{FiniteField, Point};
+use num_bigint::BigUint;
+
+use crate::secret::{invert_secret, SecretScalar};
+use crate::ECDSA;
+
+pub trait Group {
+    type Element: Clone + PartialEq;
+
+    fn generator(&self) -> Self::Element;
+    fn order(&self) -> &BigUint;
+    fn identity(&self) -> Self::Element;
+    fn add(&self, a: &Self::Element, b: &Self::Element) -> Self::Element;
+    // Variable time, for public scalars
+    fn mul(&self, a: &Self::Element, k: &BigUint) -> Self::Element;
+    // For secret scalars, in constant time. There is deliberately no default
+    // falling back to `mul`, so no backend ends up running secrets through
+    // the variable-time path without saying so
+    fn mul_secret(&self, a: &Self::Element, k: &SecretScalar) -> Self::Element;
+    fn contains(&self, a: &Self::Element) -> bool;
+    // The integer ECDSA takes r from, the affine x coordinate on curves
+    fn x_coordinate(&self, a: &Self::Element) -> Option<BigUint>;
+}
+
+impl Group for ECDSA {
+    type Element = Point;
+
+    fn generator(&self) -> Point {
+        self.a_gen.clone()
+    }
+
+    fn order(&self) -> &BigUint {
+        &self.q_order
+    }
+
+    fn identity(&self) -> Point {
+        Point::Identity
+    }
+
+    fn add(&self, a: &Point, b: &Point) -> Point {
+        self.add_points(a, b)
+    }
+
+    fn mul(&self, a: &Point, k: &BigUint) -> Point {
+        self.mul_vartime(a, k)
+    }
+
+    fn mul_secret(&self, a: &Point, k: &SecretScalar) -> Point {
+        ECDSA::mul_secret(self, a, k)
+    }
+
+    fn contains(&self, a: &Point) -> bool {
+        self.is_valid_pub_key(a)
+    }
+
+    fn x_coordinate(&self, a: &Point) -> Option<BigUint> {
+        match a {
+            Point::Coor(x, _) => Some(x.clone()),
+            Point::Identity => None,
+        }
+    }
+}
+
+pub fn sign<G: Group>(
+    group: &G,
+    hash: &BigUint,
+    priv_key: &BigUint,
+    k_random: &BigUint,
+) -> Option<(BigUint, BigUint)> {
+    // R = kG -> r = x(R), s = (hash + d * r) * k⁻¹ mod q
+    // r itself is not reduced, as `verify` compares it against x(P)
+    let q = group.order();
+    let k_secret = SecretScalar::new(k_random.clone());
+    let r = group.x_coordinate(&group.mul_secret(&group.generator(), &k_secret))?;
+    let s = FiniteField::mult(&(&r % q), priv_key, q).unwrap();
+    let s = FiniteField::add(&s, hash, q).unwrap();
+    let k_inv = invert_secret(k_random, q)?;
+    Some((r, FiniteField::mult(&s, &k_inv, q).unwrap()))
+}
+
+pub fn verify<G: Group>(
+    group: &G,
+    hash: &BigUint,
+    pub_key: &G::Element,
+    signature: &(BigUint, BigUint),
+) -> bool {
+    // P = u1 G + u2 B with u1 = hash * s⁻¹, u2 = r * s⁻¹, accept if x(P) = r
+    let (r, s) = signature;
+    let q = group.order();
+    let zero = BigUint::from(0u32);
+    if !group.contains(pub_key) || r % q == zero || *s == zero || s >= q {
+        return false;
+    }
+
+    let s_inv = FiniteField::inv_mult_prime(s, q).unwrap();
+    let u1 = FiniteField::mult(&s_inv, hash, q).unwrap();
+    let u2 = FiniteField::mult(&s_inv, &(r % q), q).unwrap();
+    let p = group.add(
+        &group.mul(&group.generator(), &u1),
+        &group.mul(pub_key, &u2),
+    );
+    group.x_coordinate(&p).as_ref() == Some(r)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // The subgroup of order 11 of Z_23^*, generated by 4
+    struct Dsa;
+
+    impl Group for Dsa {
+        type Element = BigUint;
+
+        fn generator(&self) -> BigUint {
+            BigUint::from(4u32)
+        }
+
+        fn order(&self) -> &BigUint {
+            static Q: std::sync::OnceLock<BigUint> = std::sync::OnceLock::new();
+            Q.get_or_init(|| BigUint::from(11u32))
+        }
+
+        fn identity(&self) -> BigUint {
+            BigUint::from(1u32)
+        }
+
+        fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+            (a * b) % 23u32
+        }
+
+        fn mul(&self, a: &BigUint, k: &BigUint) -> BigUint {
+            a.modpow(k, &BigUint::from(23u32))
+        }
+
+        fn mul_secret(&self, a: &BigUint, k: &SecretScalar) -> BigUint {
+            // A toy group with nothing to protect, so plain modpow
+            self.mul(a, k.expose_secret())
+        }
+
+        fn contains(&self, a: &BigUint) -> bool {
+            *a != BigUint::from(1u32) && self.mul(a, self.order()) == BigUint::from(1u32)
+        }
+
+        fn x_coordinate(&self, a: &BigUint) -> Option<BigUint> {
+            Some(a.clone())
+        }
+    }
+
+    #[test]
+    fn test_sign_verify_other_group() {
+        let group = Dsa;
+        let priv_key = BigUint::from(7u32);
+        let pub_key = group.mul(&group.generator(), &priv_key);
+        let hash = BigUint::from(5u32);
+
+        let signature = sign(&group, &hash, &priv_key, &BigUint::from(3u32)).unwrap();
+        assert!(verify(&group, &hash, &pub_key, &signature));
+        assert!(!verify(&group, &BigUint::from(6u32), &pub_key, &signature));
+    }
+}
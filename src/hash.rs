@@ -0,0 +1,281 @@
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::BigUint;
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+use crate::ECDSA;
+
+pub fn sha256(data: &[u8]) -> Vec<u8> {
+    Sha256::digest(data).to_vec()
+}
+
+pub fn hash160(data: &[u8]) -> Vec<u8> {
+    // RIPEMD-160(SHA-256(data)), Bitcoin's key hash
+    Ripemd160::digest(Sha256::digest(data)).to_vec()
+}
+
+pub fn keccak256(data: &[u8]) -> Vec<u8> {
+    // The original Keccak padding Ethereum uses, not the SHA3-256 of FIPS 202
+    Keccak256::digest(data).to_vec()
+}
+
+pub fn tagged_hash(tag: &str, data: &[u8]) -> Vec<u8> {
+    // SHA-256(SHA-256(tag) || SHA-256(tag) || data), BIP 340's domain
+    // separation
+    let tag_hash = sha256(tag.as_bytes());
+    sha256(&[&tag_hash[..], &tag_hash, data].concat())
+}
+
+pub fn frame_context(context: &[u8], message: &[u8]) -> Option<Vec<u8>> {
+    // len(context) || context || message, as Ed25519ctx prefixes the message;
+    // the context is mandatory and 1 to 255 bytes long
+    let len = u8::try_from(context.len()).ok().filter(|&len| len > 0)?;
+    Some([&[len], context, message].concat())
+}
+
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    // HMAC(K, m) = H((K' ^ opad) || H((K' ^ ipad) || m)), RFC 2104
+    let mut block_key = if key.len() > 64 {
+        sha256(key)
+    } else {
+        key.to_vec()
+    };
+    block_key.resize(64, 0);
+
+    let ipad: Vec<u8> = block_key.iter().map(|b| b ^ 0x36).collect();
+    let opad: Vec<u8> = block_key.iter().map(|b| b ^ 0x5c).collect();
+    let inner = sha256(&[ipad.as_slice(), data].concat());
+    sha256(&[opad, inner].concat())
+}
+
+pub fn hkdf_sha256(salt: &[u8], ikm: &[u8], info: &[u8], len: usize) -> Vec<u8> {
+    // RFC 5869: PRK = HMAC(salt, IKM), T(i) = HMAC(PRK, T(i - 1) || info || i)
+    assert!(len <= 255 * 32, "HKDF output is too long");
+    let prk = hmac_sha256(salt, ikm);
+
+    let mut okm = Vec::with_capacity(len);
+    let mut t = Vec::new();
+    for counter in 1..=len.div_ceil(32) as u8 {
+        t = hmac_sha256(&prk, &[t.as_slice(), info, &[counter]].concat());
+        okm.extend(&t);
+    }
+    okm.truncate(len);
+    okm
+}
+
+pub fn sha1(data: &[u8]) -> Vec<u8> {
+    // FIPS 180-4; broken for signatures, kept for OpenPGP v4 fingerprints
+    let mut v: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0x00);
+    }
+    message.extend((data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for j in 0..16 {
+            w[j] = u32::from_be_bytes(block[4 * j..4 * j + 4].try_into().unwrap());
+        }
+        for j in 16..80 {
+            w[j] = (w[j - 3] ^ w[j - 8] ^ w[j - 14] ^ w[j - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = v;
+        for (j, wj) in w.iter().enumerate() {
+            let (k, f) = match j {
+                0..=19 => (0x5a827999u32, (b & c) | (!b & d)),
+                20..=39 => (0x6ed9eba1, b ^ c ^ d),
+                40..=59 => (0x8f1bbcdc, (b & c) | (b & d) | (c & d)),
+                _ => (0xca62c1d6, b ^ c ^ d),
+            };
+            let t = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*wj);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = t;
+        }
+        for (vi, x) in v.iter_mut().zip([a, b, c, d, e]) {
+            *vi = vi.wrapping_add(x);
+        }
+    }
+    v.iter().flat_map(|x| x.to_be_bytes()).collect()
+}
+
+pub fn sm3(data: &[u8]) -> Vec<u8> {
+    // GM/T 0004-2012, Merkle-Damgard with SHA-256 style padding
+    let mut v: [u32; 8] = [
+        0x7380166f, 0x4914b2b9, 0x172442d7, 0xda8a0600, 0xa96f30bc, 0x163138aa, 0xe38dee4d,
+        0xb0fb0e4e,
+    ];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0x00);
+    }
+    message.extend((data.len() as u64 * 8).to_be_bytes());
+
+    let p0 = |x: u32| x ^ x.rotate_left(9) ^ x.rotate_left(17);
+    let p1 = |x: u32| x ^ x.rotate_left(15) ^ x.rotate_left(23);
+    for block in message.chunks(64) {
+        let mut w = [0u32; 68];
+        for j in 0..16 {
+            w[j] = u32::from_be_bytes(block[4 * j..4 * j + 4].try_into().unwrap());
+        }
+        for j in 16..68 {
+            w[j] = p1(w[j - 16] ^ w[j - 9] ^ w[j - 3].rotate_left(15))
+                ^ w[j - 13].rotate_left(7)
+                ^ w[j - 6];
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = v;
+        for j in 0..64 {
+            let (t, ff, gg) = if j < 16 {
+                (0x79cc4519u32, a ^ b ^ c, e ^ f ^ g)
+            } else {
+                (
+                    0x7a879d8au32,
+                    (a & b) | (a & c) | (b & c),
+                    (e & f) | (!e & g),
+                )
+            };
+            let ss1 = a
+                .rotate_left(12)
+                .wrapping_add(e)
+                .wrapping_add(t.rotate_left(j as u32 % 32))
+                .rotate_left(7);
+            let ss2 = ss1 ^ a.rotate_left(12);
+            let tt1 = ff
+                .wrapping_add(d)
+                .wrapping_add(ss2)
+                .wrapping_add(w[j] ^ w[j + 4]);
+            let tt2 = gg.wrapping_add(h).wrapping_add(ss1).wrapping_add(w[j]);
+            d = c;
+            c = b.rotate_left(9);
+            b = a;
+            a = tt1;
+            h = g;
+            g = f.rotate_left(19);
+            f = e;
+            e = p0(tt2);
+        }
+        for (vi, x) in v.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *vi ^= x;
+        }
+    }
+    v.iter().flat_map(|x| x.to_be_bytes()).collect()
+}
+
+impl ECDSA {
+    pub fn hash_to_scalar(&self, dst: &[u8], data: &[u8]) -> BigUint {
+        // H(dst || data) mod q
+        let hash = sha256(&[dst, data].concat());
+        BigUint::from_bytes_be(&hash) % &self.q_order
+    }
+
+    pub fn hash_to_curve(&self, dst: &[u8], data: &[u8]) -> Point {
+        // Try-and-increment after RFC 9381, section 5.4.1.1, though x is reduced
+        // rather than rejected: x = H(dst || data || ctr) mod p
+        // until x lands on the curve, taking the even root for y. The curves used
+        // here have cofactor 1 so the result is already in the group of order q.
+        let p = &self.elliptic_curve.p;
+        for ctr in 0..=u8::MAX {
+            let hash = sha256(&[dst, data, &[ctr]].concat());
+            let x = BigUint::from_bytes_be(&hash) % p;
+            if let Some(y) = self.y_for_x(&x) {
+                let y = if y.bit(0) { (p - y) % p } else { y };
+                return Point::Coor(x, y);
+            }
+        }
+        panic!("Could not hash to a point of the curve");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_hash160_keccak256() {
+        assert_eq!(
+            hex::encode(hash160(b"")),
+            "b472a266d0bd89c13706a4132ccfb16f7c3b9fcb"
+        );
+        assert_eq!(
+            hex::encode(keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+    }
+
+    #[test]
+    fn test_hmac_sha256() {
+        // RFC 4231, test case 2
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(
+            hex::encode(mac),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_hkdf_sha256() {
+        // RFC 5869, test case 1
+        let ikm = [0x0bu8; 22];
+        let salt = hex::decode("000102030405060708090a0b0c").unwrap();
+        let info = hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap();
+        let okm = hkdf_sha256(&salt, &ikm, &info, 42);
+        assert_eq!(
+            hex::encode(okm),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+    }
+
+    #[test]
+    fn test_sha1() {
+        // FIPS 180 examples, one and two blocks
+        assert_eq!(
+            hex::encode(sha1(b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hex::encode(sha1(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+    }
+
+    #[test]
+    fn test_sm3() {
+        // GM/T 0004-2012, appendix A
+        assert_eq!(
+            hex::encode(sm3(b"abc")),
+            "66c7f0f462eeedd9d1f2d46bdc10e4e24167c4875cf2f7a2297da02b8f4ba8e0"
+        );
+        assert_eq!(
+            hex::encode(sm3(&b"abcd".repeat(16))),
+            "debe9ff92275b8a138604889c18e5a4d6fdb70e5387e5765293dcba39c0c5732"
+        );
+    }
+
+    #[test]
+    fn test_hash_to_curve() {
+        let ecdsa = toy_curve();
+
+        let h1 = ecdsa.hash_to_curve(b"test", b"Bob -> 1 BTC -> Alice");
+        let h2 = ecdsa.hash_to_curve(b"test", b"Bob -> 1 BTC -> Alice");
+        assert_eq!(h1, h2, "Hashing should be deterministic");
+        assert!(ecdsa.elliptic_curve.is_on_curve(&h1));
+        assert_eq!(ecdsa.mul_vartime(&h1, &ecdsa.q_order), Point::Identity);
+    }
+}
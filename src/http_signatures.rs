@@ -0,0 +1,492 @@
+// HTTP Message Signatures (RFC 9421) with ecdsa-p256-sha256. The signer picks
+// the components to cover, derived ones such as "@method" and "@path" and
+// header fields by their lowercased name, and the signature base lists each
+// as `"name": value` followed by the "@signature-params" line, which repeats
+// the Signature-Input member so the parameters are signed too. The signature
+// is r || s over SHA-256 of the base, sent as a byte sequence in the
+// Signature header under the same label as its Signature-Input.
+//
+// Only the structured field syntax these two headers use is read: a
+// dictionary of inner lists of strings with integer and string parameters,
+// and of byte sequences. Component parameters (";sf", ";key", ";req") are
+// not supported. `http_verify` checks the signature and hands back the
+// parameters; whether `created`, `expires` and `keyid` are acceptable is the
+// caller's policy.
+
+use alloc::string::{String, ToString};
+use alloc::{format, vec::Vec};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::encoding::to_fixed_bytes;
+use crate::hash::sha256;
+use crate::signer::Signature;
+use crate::ECDSA;
+
+pub const HTTP_SIG_ALGORITHM: &str = "ecdsa-p256-sha256";
+
+#[derive(Clone, Debug, Default)]
+pub struct HttpMessage {
+    method: String,
+    target_uri: String,
+    status: Option<u16>,
+    headers: Vec<(String, String)>,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct SignatureParams {
+    pub components: Vec<String>,
+    pub created: Option<u64>,
+    pub expires: Option<u64>,
+    pub nonce: Option<String>,
+    pub alg: Option<String>,
+    pub keyid: Option<String>,
+    pub tag: Option<String>,
+}
+
+impl HttpMessage {
+    pub fn request(method: &str, target_uri: &str) -> HttpMessage {
+        HttpMessage {
+            method: method.into(),
+            target_uri: target_uri.into(),
+            ..HttpMessage::default()
+        }
+    }
+
+    pub fn response(status: u16) -> HttpMessage {
+        HttpMessage {
+            status: Some(status),
+            ..HttpMessage::default()
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> HttpMessage {
+        // Repeated names are kept in order, and joined when covered
+        self.headers.push((name.to_ascii_lowercase(), value.into()));
+        self
+    }
+
+    fn component(&self, name: &str) -> Option<String> {
+        let (scheme, rest) = self.target_uri.split_once("://").unwrap_or(("", ""));
+        let rest = rest.split('#').next().unwrap_or_default();
+        let (authority, target) = rest.find('/').map_or((rest, ""), |i| rest.split_at(i));
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let path = if path.is_empty() { "/" } else { path };
+        let request = !self.method.is_empty();
+        match name {
+            "@method" if request => Some(self.method.clone()),
+            "@target-uri" if request => Some(self.target_uri.clone()),
+            "@authority" if request => Some(normalize_authority(scheme, authority)),
+            "@scheme" if request => Some(scheme.to_ascii_lowercase()),
+            "@request-target" if request => Some(target.into()),
+            "@path" if request => Some(path.into()),
+            "@query" if request => Some(format!("?{}", query)),
+            "@status" => self.status.map(|status| format!("{:03}", status)),
+            _ if name.starts_with('@') => None,
+            _ => {
+                let values: Vec<&str> = self
+                    .headers
+                    .iter()
+                    .filter(|(header, _)| header == name)
+                    .map(|(_, value)| value.trim())
+                    .collect();
+                (!values.is_empty()).then(|| values.join(", "))
+            }
+        }
+    }
+}
+
+impl SignatureParams {
+    pub fn new(components: &[&str]) -> SignatureParams {
+        SignatureParams {
+            components: components.iter().map(|name| name.to_string()).collect(),
+            ..SignatureParams::default()
+        }
+    }
+
+    pub fn serialize(&self) -> String {
+        // The inner list of a Signature-Input member, parameters in a fixed
+        // order
+        let names: Vec<String> = self.components.iter().map(|name| quote(name)).collect();
+        let mut text = format!("({})", names.join(" "));
+        if let Some(created) = self.created {
+            text.push_str(&format!(";created={}", created));
+        }
+        if let Some(expires) = self.expires {
+            text.push_str(&format!(";expires={}", expires));
+        }
+        let strings = [
+            ("nonce", &self.nonce),
+            ("alg", &self.alg),
+            ("keyid", &self.keyid),
+            ("tag", &self.tag),
+        ];
+        for (key, value) in strings {
+            if let Some(value) = value {
+                text.push_str(&format!(";{}={}", key, quote(value)));
+            }
+        }
+        text
+    }
+
+    pub fn parse(text: &str) -> Option<SignatureParams> {
+        let inner = text.trim().strip_prefix('(')?;
+        let close = find_unquoted(inner, ')')?;
+        let (items, parameters) = (&inner[..close], &inner[close + 1..]);
+        if !(parameters.is_empty() || parameters.starts_with(';')) {
+            return None;
+        }
+        let mut params = SignatureParams::default();
+        let mut rest = items.trim_start();
+        while !rest.is_empty() {
+            let (name, after) = unquote(rest)?;
+            // A component parameter, or no space between items
+            if !(after.is_empty() || after.starts_with(' ')) {
+                return None;
+            }
+            params.components.push(name);
+            rest = after.trim_start();
+        }
+        for parameter in split_unquoted(parameters, ';').into_iter().skip(1) {
+            let (key, value) = parameter.split_once('=')?;
+            let integer = || value.parse::<u64>().ok().filter(|_| value.len() <= 15);
+            let string = || {
+                unquote(value)
+                    .filter(|(_, rest)| rest.is_empty())
+                    .map(|(s, _)| s)
+            };
+            match key {
+                "created" => params.created = Some(integer()?),
+                "expires" => params.expires = Some(integer()?),
+                "nonce" => params.nonce = Some(string()?),
+                "alg" => params.alg = Some(string()?),
+                "keyid" => params.keyid = Some(string()?),
+                "tag" => params.tag = Some(string()?),
+                _ => return None,
+            }
+        }
+        Some(params)
+    }
+}
+
+pub fn signature_base(message: &HttpMessage, serialized_params: &str) -> Option<String> {
+    // None when a covered component is missing, repeated or not a valid name
+    let params = SignatureParams::parse(serialized_params)?;
+    let mut base = String::new();
+    for (i, name) in params.components.iter().enumerate() {
+        let valid = !name.is_empty()
+            && name
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b"@-_.".contains(&b));
+        if !valid || params.components[..i].contains(name) || name == "@signature-params" {
+            return None;
+        }
+        base.push_str(&format!("{}: {}\n", quote(name), message.component(name)?));
+    }
+    base.push_str(&format!("\"@signature-params\": {}", serialized_params));
+    Some(base)
+}
+
+impl ECDSA {
+    pub fn http_sign(
+        &self,
+        message: &HttpMessage,
+        label: &str,
+        params: &SignatureParams,
+        priv_key: &BigUint,
+    ) -> Option<(String, String)> {
+        // The Signature-Input and Signature header values; None off P-256,
+        // for an `alg` other than ecdsa-p256-sha256 or a bad label
+        if !self.is_p256() || !is_key(label) || !allowed_alg(params) {
+            return None;
+        }
+        let serialized = params.serialize();
+        let base = signature_base(message, &serialized)?;
+        let signature = self.sign_standard(&mut crate::rng(), &sha256(base.as_bytes()), priv_key);
+        let mut bytes = to_fixed_bytes(signature.r(), self.scalar_len());
+        bytes.extend(to_fixed_bytes(signature.s(), self.scalar_len()));
+        Some((
+            format!("{}={}", label, serialized),
+            format!("{}=:{}:", label, STANDARD.encode(bytes)),
+        ))
+    }
+
+    pub fn http_verify(
+        &self,
+        message: &HttpMessage,
+        signature_input: &str,
+        signature: &str,
+        label: &str,
+        pub_key: &Point,
+    ) -> Option<SignatureParams> {
+        // The parameters of the signature under `label`, if it checks out;
+        // the base repeats the member exactly as it was received
+        if !self.is_p256() {
+            return None;
+        }
+        let serialized = dictionary_member(signature_input, label)?;
+        let params = SignatureParams::parse(serialized)?;
+        if !allowed_alg(&params) {
+            return None;
+        }
+        let bytes = dictionary_member(signature, label)?
+            .strip_prefix(':')?
+            .strip_suffix(':')?;
+        let bytes = STANDARD.decode(bytes).ok()?;
+        if bytes.len() != 2 * self.scalar_len() {
+            return None;
+        }
+        let (r, s) = bytes.split_at(self.scalar_len());
+        let signature = Signature::new(BigUint::from_bytes_be(r), BigUint::from_bytes_be(s));
+        let base = signature_base(message, serialized)?;
+        self.verify_standard(&sha256(base.as_bytes()), pub_key, &signature)
+            .then_some(params)
+    }
+}
+
+fn allowed_alg(params: &SignatureParams) -> bool {
+    params
+        .alg
+        .as_deref()
+        .is_none_or(|alg| alg == HTTP_SIG_ALGORITHM)
+}
+
+fn normalize_authority(scheme: &str, authority: &str) -> String {
+    // Lowercase, without the default port of the scheme
+    let authority = authority.rsplit('@').next().unwrap_or_default();
+    let authority = authority.to_ascii_lowercase();
+    let default_port = match scheme.to_ascii_lowercase().as_str() {
+        "http" => ":80",
+        "https" => ":443",
+        _ => return authority,
+    };
+    authority
+        .strip_suffix(default_port)
+        .map_or(authority.clone(), String::from)
+}
+
+fn is_key(label: &str) -> bool {
+    // A structured field dictionary key
+    let mut bytes = label.bytes();
+    bytes
+        .next()
+        .is_some_and(|b| b.is_ascii_lowercase() || b == b'*')
+        && bytes.all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b"_-.*".contains(&b))
+}
+
+fn dictionary_member<'a>(header: &'a str, label: &str) -> Option<&'a str> {
+    split_unquoted(header, ',')
+        .into_iter()
+        .filter_map(|member| member.trim().split_once('='))
+        .find(|(key, _)| *key == label)
+        .map(|(_, value)| value)
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn unquote(text: &str) -> Option<(String, &str)> {
+    // A structured field string at the start of `text`, and what follows it
+    let mut chars = text.strip_prefix('"')?.char_indices();
+    let mut value = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &text[i + 2..])),
+            '\\' => match chars.next()? {
+                (_, escaped @ ('"' | '\\')) => value.push(escaped),
+                _ => return None,
+            },
+            ' '..='~' => value.push(c),
+            _ => return None,
+        }
+    }
+    None
+}
+
+fn find_unquoted(text: &str, target: char) -> Option<usize> {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            _ if c == target && !in_string => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn split_unquoted(mut text: &str, separator: char) -> Vec<&str> {
+    // Splits at separators outside strings and parentheses
+    let mut parts = Vec::new();
+    loop {
+        let mut depth = 0u32;
+        let mut end = None;
+        let mut in_string = false;
+        let mut escaped = false;
+        for (i, c) in text.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if in_string => escaped = true,
+                '"' => in_string = !in_string,
+                '(' if !in_string => depth += 1,
+                ')' if !in_string => depth = depth.saturating_sub(1),
+                _ if c == separator && !in_string && depth == 0 => {
+                    end = Some(i);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        match end {
+            Some(i) => {
+                parts.push(&text[..i]);
+                text = &text[i + 1..];
+            }
+            None => {
+                parts.push(text);
+                return parts;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use p256::ecdsa::signature::Verifier;
+
+    use crate::curves;
+
+    // The request of RFC 9421, appendix B.2
+    fn request() -> HttpMessage {
+        HttpMessage::request("POST", "https://example.com/foo?param=Value&Pet=dog")
+            .header("Host", "example.com")
+            .header("Date", "Tue, 20 Apr 2021 02:07:55 GMT")
+            .header("Content-Type", "application/json")
+            .header(
+                "Content-Digest",
+                "sha-512=:WZDPaVn/7XgHaAy8pmojAkGWoRx2UFChF41A2svX+TaPm+AbwAgBWnrIiYllu7BNNyealdVLvRwEmTHWXvJwew==:",
+            )
+            .header("Content-Length", "18")
+    }
+
+    #[test]
+    fn test_signature_base() {
+        // Section 2.5 of the RFC
+        let mut params = SignatureParams::new(&[
+            "@method",
+            "@authority",
+            "@path",
+            "content-digest",
+            "content-length",
+            "content-type",
+        ]);
+        params.created = Some(1618884473);
+        params.keyid = Some("test-key-rsa-pss".into());
+        let expected = concat!(
+            "\"@method\": POST\n",
+            "\"@authority\": example.com\n",
+            "\"@path\": /foo\n",
+            "\"content-digest\": sha-512=:WZDPaVn/7XgHaAy8pmojAkGWoRx2UFChF41A2svX+TaPm+",
+            "AbwAgBWnrIiYllu7BNNyealdVLvRwEmTHWXvJwew==:\n",
+            "\"content-length\": 18\n",
+            "\"content-type\": application/json\n",
+            "\"@signature-params\": (\"@method\" \"@authority\" \"@path\" \"content-digest\" ",
+            "\"content-length\" \"content-type\");created=1618884473;keyid=\"test-key-rsa-pss\"",
+        );
+        let serialized = params.serialize();
+        assert_eq!(signature_base(&request(), &serialized).unwrap(), expected);
+        assert_eq!(SignatureParams::parse(&serialized), Some(params));
+
+        let message = request();
+        assert_eq!(message.component("@query").unwrap(), "?param=Value&Pet=dog");
+        assert_eq!(
+            message.component("@request-target").unwrap(),
+            "/foo?param=Value&Pet=dog"
+        );
+        assert_eq!(message.component("@scheme").unwrap(), "https");
+        assert_eq!(message.component("@status"), None);
+        assert_eq!(
+            HttpMessage::response(200).component("@status").unwrap(),
+            "200"
+        );
+        let authority =
+            HttpMessage::request("GET", "https://Example.COM:443/").component("@authority");
+        assert_eq!(authority.unwrap(), "example.com");
+
+        // Missing and repeated components
+        assert!(signature_base(&request(), "(\"x-missing\")").is_none());
+        assert!(signature_base(&request(), "(\"@path\" \"@path\")").is_none());
+        assert!(signature_base(&request(), "(\"@path\";req)").is_none());
+        assert!(SignatureParams::parse("(\"@path\")x;created=1").is_none());
+    }
+
+    #[test]
+    fn test_http_sign_verify() {
+        let ecdsa = curves::p256();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let mut params = SignatureParams::new(&["@method", "@path", "content-digest"]);
+        params.created = Some(1618884473);
+        params.alg = Some(HTTP_SIG_ALGORITHM.into());
+        params.keyid = Some("test-key-ecc-p256".into());
+        let (input, signature) = ecdsa
+            .http_sign(&request(), "sig1", &params, &priv_key)
+            .unwrap();
+        assert!(input.starts_with("sig1=(\"@method\" \"@path\" \"content-digest\");created="));
+
+        // Alongside another signature, in either header order
+        let input = format!("proxy=(\"@path\");keyid=\"a, b\", {}", input);
+        let signature = format!("proxy=:AAAA:, {}", signature);
+        let verified = ecdsa.http_verify(&request(), &input, &signature, "sig1", &pub_key);
+        assert_eq!(verified, Some(params.clone()));
+        assert!(ecdsa
+            .http_verify(&request(), &input, &signature, "proxy", &pub_key)
+            .is_none());
+
+        let tampered = request().header("Content-Digest", "sha-256=:AAAA:");
+        assert!(ecdsa
+            .http_verify(&tampered, &input, &signature, "sig1", &pub_key)
+            .is_none());
+        let other = HttpMessage::request("POST", "https://example.com/bar");
+        assert!(ecdsa
+            .http_verify(&other, &input, &signature, "sig1", &pub_key)
+            .is_none());
+
+        params.alg = Some("rsa-pss-sha512".into());
+        assert!(ecdsa
+            .http_sign(&request(), "sig1", &params, &priv_key)
+            .is_none());
+        assert!(curves::secp256k1()
+            .http_sign(&request(), "sig1", &SignatureParams::new(&[]), &priv_key)
+            .is_none());
+    }
+
+    #[test]
+    fn test_http_signature_p256_interop() {
+        let ecdsa = curves::p256();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let params = SignatureParams::new(&["@method", "@authority"]);
+        let (input, signature) = ecdsa
+            .http_sign(&request(), "sig1", &params, &priv_key)
+            .unwrap();
+        let base = signature_base(&request(), input.strip_prefix("sig1=").unwrap()).unwrap();
+        let bytes = signature
+            .strip_prefix("sig1=:")
+            .unwrap()
+            .strip_suffix(':')
+            .unwrap();
+        let signature =
+            p256::ecdsa::Signature::from_slice(&STANDARD.decode(bytes).unwrap()).unwrap();
+
+        let encoded = ecdsa.encode_point_uncompressed(&ecdsa.generate_pub_key(&priv_key));
+        let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(&encoded).unwrap();
+        assert!(key.verify(base.as_bytes(), &signature).is_ok());
+    }
+}
@@ -0,0 +1,167 @@
+// Jacobian coordinates, (X, Y, Z) standing for the affine point
+// (X / Z², Y / Z³), with Z = 0 for the identity. Additions and doublings need
+// no field inversion, so long runs of them (precomputed tables) stay in
+// Jacobian form and are brought back to affine all at once with
+// `batch_to_affine`, which shares one inversion across every point.
+
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::arithmetic::batch_inverse;
+use crate::encoding::to_fixed_bytes;
+use crate::ECDSA;
+
+#[derive(Clone, Debug)]
+pub struct JacobianPoint {
+    x: BigUint,
+    y: BigUint,
+    z: BigUint,
+}
+
+impl JacobianPoint {
+    pub fn is_identity(&self) -> bool {
+        self.z == BigUint::from(0u32)
+    }
+
+    pub fn to_bytes(&self, field_len: usize) -> Vec<u8> {
+        // X || Y || Z, each big-endian in `field_len` bytes
+        let mut bytes = to_fixed_bytes(&self.x, field_len);
+        bytes.extend(to_fixed_bytes(&self.y, field_len));
+        bytes.extend(to_fixed_bytes(&self.z, field_len));
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<JacobianPoint> {
+        if !bytes.len().is_multiple_of(3) {
+            return None;
+        }
+        let mut coordinates = bytes.chunks(bytes.len() / 3).map(BigUint::from_bytes_be);
+        Some(JacobianPoint {
+            x: coordinates.next()?,
+            y: coordinates.next()?,
+            z: coordinates.next()?,
+        })
+    }
+}
+
+impl ECDSA {
+    pub fn to_jacobian(&self, point: &Point) -> JacobianPoint {
+        match point {
+            Point::Coor(x, y) => JacobianPoint {
+                x: x.clone(),
+                y: y.clone(),
+                z: BigUint::from(1u32),
+            },
+            Point::Identity => JacobianPoint {
+                x: BigUint::from(1u32),
+                y: BigUint::from(1u32),
+                z: BigUint::from(0u32),
+            },
+        }
+    }
+
+    pub fn jacobian_double(&self, point: &JacobianPoint) -> JacobianPoint {
+        // S = 4XY², M = 3X² + aZ⁴
+        // X' = M² - 2S, Y' = M(S - X') - 8Y⁴, Z' = 2YZ
+        let p = &self.elliptic_curve.p;
+        if point.is_identity() || point.y == BigUint::from(0u32) {
+            return self.to_jacobian(&Point::Identity);
+        }
+        let yy = (&point.y * &point.y) % p;
+        let zz = (&point.z * &point.z) % p;
+        let s = (BigUint::from(4u32) * &point.x * &yy) % p;
+        let m =
+            (BigUint::from(3u32) * &point.x * &point.x + &self.elliptic_curve.a * &zz * &zz) % p;
+        let x = (&m * &m + p * 2u32 - &s * 2u32) % p;
+        let y = (&m * ((&s + p - &x) % p) + p * 8u32 - (BigUint::from(8u32) * &yy * &yy) % p) % p;
+        let z = (BigUint::from(2u32) * &point.y * &point.z) % p;
+        JacobianPoint { x, y, z }
+    }
+
+    pub fn jacobian_add(&self, a: &JacobianPoint, b: &JacobianPoint) -> JacobianPoint {
+        // U1 = X1 Z2², U2 = X2 Z1², S1 = Y1 Z2³, S2 = Y2 Z1³, H = U2 - U1, R = S2 - S1
+        // X3 = R² - H³ - 2 U1 H², Y3 = R(U1 H² - X3) - S1 H³, Z3 = H Z1 Z2
+        let p = &self.elliptic_curve.p;
+        if a.is_identity() {
+            return b.clone();
+        }
+        if b.is_identity() {
+            return a.clone();
+        }
+        let z1z1 = (&a.z * &a.z) % p;
+        let z2z2 = (&b.z * &b.z) % p;
+        let u1 = (&a.x * &z2z2) % p;
+        let u2 = (&b.x * &z1z1) % p;
+        let s1 = (&a.y * &b.z * &z2z2) % p;
+        let s2 = (&b.y * &a.z * &z1z1) % p;
+        if u1 == u2 {
+            if s1 == s2 {
+                return self.jacobian_double(a);
+            }
+            return self.to_jacobian(&Point::Identity);
+        }
+        let h = (&u2 + p - &u1) % p;
+        let r = (&s2 + p - &s1) % p;
+        let hh = (&h * &h) % p;
+        let hhh = (&hh * &h) % p;
+        let v = (&u1 * &hh) % p;
+        let x = (&r * &r + p * 3u32 - &hhh - &v * 2u32) % p;
+        let y = (&r * ((&v + p - &x) % p) + p - (&s1 * &hhh) % p) % p;
+        let z = (&h * &a.z * &b.z) % p;
+        JacobianPoint { x, y, z }
+    }
+
+    pub fn batch_to_affine(&self, points: &[JacobianPoint]) -> Vec<Point> {
+        // x = X / Z², y = Y / Z³, with one inversion for all the Z
+        let p = &self.elliptic_curve.p;
+        let finite: Vec<BigUint> = points
+            .iter()
+            .filter(|point| !point.is_identity())
+            .map(|point| point.z.clone())
+            .collect();
+        let mut z_inverses = batch_inverse(&finite, p)
+            .expect("Z is invertible mod a prime p")
+            .into_iter();
+        points
+            .iter()
+            .map(|point| {
+                if point.is_identity() {
+                    return Point::Identity;
+                }
+                let z_inv = z_inverses.next().unwrap();
+                let z_inv2 = (&z_inv * &z_inv) % p;
+                let z_inv3 = (&z_inv2 * &z_inv) % p;
+                Point::Coor((&point.x * z_inv2) % p, (&point.y * z_inv3) % p)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_jacobian_multiples() {
+        // kA by repeated Jacobian additions and doublings, for k in 0..=19,
+        // which takes in doubling, adding P to -P and the identity
+        let ecdsa = toy_curve();
+        let generator = ecdsa.to_jacobian(&ecdsa.a_gen);
+        let mut multiples = vec![ecdsa.to_jacobian(&Point::Identity)];
+        for k in 1..=19usize {
+            let next = if k % 2 == 0 {
+                ecdsa.jacobian_double(&multiples[k / 2])
+            } else {
+                ecdsa.jacobian_add(&multiples[k - 1], &generator)
+            };
+            multiples.push(next);
+        }
+
+        let affine = ecdsa.batch_to_affine(&multiples);
+        for (k, point) in affine.iter().enumerate() {
+            assert_eq!(*point, ecdsa.mul_vartime(&ecdsa.a_gen, &BigUint::from(k)));
+        }
+    }
+}
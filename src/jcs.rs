@@ -0,0 +1,201 @@
+// Signatures over JSON values canonicalized with the JSON Canonicalization
+// Scheme (RFC 8785), behind the `jcs` feature, so services that serialize the
+// same value with different key order, whitespace or number spelling agree
+// on the bytes signed. Object members are sorted by the UTF-16 code units of
+// their names, strings keep every character but the ones JSON must escape,
+// and numbers are printed the way ECMAScript prints doubles. The signature is
+// standard ECDSA over SHA-256 of the canonical text. The feature turns on
+// serde_json's correctly rounded float parsing, which RFC 8785 relies on.
+
+use core::fmt::Write;
+
+use ec_generic::Point;
+use num_bigint::BigUint;
+use serde_json::Value;
+use signature::rand_core::CryptoRngCore;
+
+use crate::hash::sha256;
+use crate::signer::Signature;
+use crate::ECDSA;
+
+pub fn canonicalize_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value);
+    out
+}
+
+impl ECDSA {
+    pub fn sign_json(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        value: &Value,
+        priv_key: &BigUint,
+    ) -> Signature {
+        let digest = sha256(canonicalize_json(value).as_bytes());
+        self.sign_standard(rng, &digest, priv_key)
+    }
+
+    pub fn verify_json(&self, value: &Value, pub_key: &Point, signature: &Signature) -> bool {
+        let digest = sha256(canonicalize_json(value).as_bytes());
+        self.verify_standard(&digest, pub_key, signature)
+    }
+}
+
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => out.push_str(&number(n.as_f64().unwrap_or(f64::NAN))),
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item);
+            }
+            out.push(']');
+        }
+        Value::Object(members) => {
+            let mut members: Vec<(&String, &Value)> = members.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (name, item)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, name);
+                out.push(':');
+                write_value(out, item);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn number(value: f64) -> String {
+    // ECMAScript Number::toString: the shortest digits that round trip, d
+    // with k digits and value = 0.d × 10^n, then plain notation for
+    // -6 < n ≤ 21 and exponent notation otherwise
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    let sign = if value < 0.0 { "-" } else { "" };
+    let exponential = format!("{:e}", value.abs());
+    let (mantissa, exponent) = exponential.split_once('e').unwrap();
+    let digits: String = mantissa.chars().filter(|&c| c != '.').collect();
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().unwrap() + 1;
+
+    let body = if k <= n && n <= 21 {
+        digits + &"0".repeat((n - k) as usize)
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{digits}", "0".repeat(-n as usize))
+    } else {
+        let fraction = if k > 1 {
+            format!(".{}", &digits[1..])
+        } else {
+            String::new()
+        };
+        format!("{}{fraction}e{:+}", &digits[..1], n - 1)
+    };
+    format!("{sign}{body}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use serde_json::json;
+
+    use crate::curves::p256;
+
+    #[test]
+    fn test_canonicalize_rfc8785_example() {
+        // Section 3.2.2
+        let value: Value = serde_json::from_str(
+            r#"{
+                "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+                "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+                "literals": [null, true, false]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            canonicalize_json(&value),
+            r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_sorting() {
+        // Section 3.2.3: UTF-16 order puts the emoji's surrogates before U+FB33
+        let value: Value = serde_json::from_str(
+            r#"{"\u20ac": 1, "\r": 2, "\ufb33": 3, "1": 4, "\ud83d\ude00": 5, "\u0080": 6, "\u00f6": 7}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            canonicalize_json(&value),
+            "{\"\\r\":2,\"1\":4,\"\u{80}\":6,\"ö\":7,\"€\":1,\"😀\":5,\"\u{fb33}\":3}"
+        );
+    }
+
+    #[test]
+    fn test_number() {
+        // Appendix B
+        for (value, expected) in [
+            (0.0, "0"),
+            (-0.0, "0"),
+            (5e-324, "5e-324"),
+            (1.7976931348623157e308, "1.7976931348623157e+308"),
+            (9007199254740992.0, "9007199254740992"),
+            (295147905179352830000.0, "295147905179352830000"),
+            (1e21, "1e+21"),
+            (1e-7, "1e-7"),
+            (0.000001, "0.000001"),
+            (-1.5, "-1.5"),
+            (123.456, "123.456"),
+        ] {
+            assert_eq!(number(value), expected);
+        }
+    }
+
+    #[test]
+    fn test_sign_json() {
+        let ecdsa = p256();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let value = json!({ "amount": 1.50, "to": "alice", "from": "bob" });
+        let signature = ecdsa.sign_json(&mut StdRng::seed_from_u64(1), &value, &priv_key);
+
+        // Another service's serialization of the same value
+        let reordered: Value =
+            serde_json::from_str("{ \"from\": \"bob\", \"to\": \"alice\", \"amount\": 15e-1 }")
+                .unwrap();
+        assert!(ecdsa.verify_json(&reordered, &pub_key, &signature));
+        let changed = json!({ "amount": 1.51, "to": "alice", "from": "bob" });
+        assert!(!ecdsa.verify_json(&changed, &pub_key, &signature));
+    }
+}
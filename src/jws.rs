@@ -0,0 +1,117 @@
+// JSON Web Signatures (RFC 7515) in the compact serialization, for the curves
+// with a JOSE algorithm: ES256 on P-256 (RFC 7518) and ES256K on secp256k1
+// (RFC 8812). The signing input is base64url(header) "." base64url(payload),
+// hashed with SHA-256 and signed with standard ECDSA, and the signature is
+// r || s as fixed width big endian integers rather than DER. Header and
+// payload are taken as bytes; building and reading the JSON is up to the
+// caller, who should also check the header's "alg" against `jws_algorithm`.
+
+use alloc::{format, string::String, vec::Vec};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::encoding::to_fixed_bytes;
+use crate::hash::sha256;
+use crate::signer::Signature;
+use crate::ECDSA;
+
+impl ECDSA {
+    pub fn jws_algorithm(&self) -> Option<&'static str> {
+        if self.is_p256() {
+            Some("ES256")
+        } else if self.is_secp256k1() {
+            Some("ES256K")
+        } else {
+            None
+        }
+    }
+
+    pub fn jws_sign(&self, header: &[u8], payload: &[u8], priv_key: &BigUint) -> Option<String> {
+        self.jws_algorithm()?;
+        let input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header),
+            URL_SAFE_NO_PAD.encode(payload)
+        );
+        let signature = self.sign_standard(&mut crate::rng(), &sha256(input.as_bytes()), priv_key);
+        let mut bytes = to_fixed_bytes(signature.r(), self.scalar_len());
+        bytes.extend(to_fixed_bytes(signature.s(), self.scalar_len()));
+        Some(format!("{}.{}", input, URL_SAFE_NO_PAD.encode(bytes)))
+    }
+
+    pub fn jws_verify(&self, token: &str, pub_key: &Point) -> Option<(Vec<u8>, Vec<u8>)> {
+        // The decoded header and payload, if the signature checks out
+        self.jws_algorithm()?;
+        let (input, signature) = token.rsplit_once('.')?;
+        let (header, payload) = input.split_once('.')?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        if signature.len() != 2 * self.scalar_len() {
+            return None;
+        }
+        let (r, s) = signature.split_at(self.scalar_len());
+        let signature = Signature::new(BigUint::from_bytes_be(r), BigUint::from_bytes_be(s));
+        if !self.verify_standard(&sha256(input.as_bytes()), pub_key, &signature) {
+            return None;
+        }
+        Some((
+            URL_SAFE_NO_PAD.decode(header).ok()?,
+            URL_SAFE_NO_PAD.decode(payload).ok()?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use p256::ecdsa::signature::Verifier;
+
+    use crate::curves;
+
+    const HEADER: &[u8] = br#"{"alg":"ES256","typ":"JWT"}"#;
+    const CLAIMS: &[u8] = br#"{"sub":"ci","exp":4102444800}"#;
+
+    #[test]
+    fn test_jws_sign_verify() {
+        let ecdsa = curves::NamedCurve::P256.preset();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let token = ecdsa.jws_sign(HEADER, CLAIMS, &priv_key).unwrap();
+        assert_eq!(token.split('.').count(), 3);
+        assert_eq!(
+            ecdsa.jws_verify(&token, &pub_key),
+            Some((HEADER.to_vec(), CLAIMS.to_vec()))
+        );
+
+        // Another payload under the same signature
+        let (_, signature) = token.rsplit_once('.').unwrap();
+        let forged = format!(
+            "{}.{}.{}",
+            URL_SAFE_NO_PAD.encode(HEADER),
+            URL_SAFE_NO_PAD.encode(br#"{"sub":"admin"}"#),
+            signature
+        );
+        assert_eq!(ecdsa.jws_verify(&forged, &pub_key), None);
+        assert_eq!(ecdsa.jws_verify("a.b", &pub_key), None);
+    }
+
+    #[test]
+    fn test_jws_p256_interop() {
+        // The p256 crate checks the same r || s signature over the signing input
+        let ecdsa = curves::NamedCurve::P256.preset();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let token = ecdsa.jws_sign(HEADER, CLAIMS, &priv_key).unwrap();
+        let (input, signature) = token.rsplit_once('.').unwrap();
+        let signature = URL_SAFE_NO_PAD.decode(signature).unwrap();
+        let signature = p256::ecdsa::Signature::from_slice(&signature).unwrap();
+
+        let encoded = ecdsa.encode_point_uncompressed(&ecdsa.generate_pub_key(&priv_key));
+        let key = p256::ecdsa::VerifyingKey::from_sec1_bytes(&encoded).unwrap();
+        assert!(key.verify(input.as_bytes(), &signature).is_ok());
+        assert_eq!(
+            curves::NamedCurve::Secp256k1.preset().jws_algorithm(),
+            Some("ES256K")
+        );
+    }
+}
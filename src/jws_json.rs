@@ -0,0 +1,396 @@
+// The JWS JSON serializations (RFC 7515 §7.2), behind the `jws-json` feature,
+// for documents that carry several signatures or headers that are not signed.
+// Each signature has its own protected header, kept exactly as the base64url
+// text that was signed, and an optional unprotected "header" object; the two
+// must not share a name, and together they form the signature's JOSE header.
+// The signing input is protected "." payload as in the compact form, so a
+// signature moves between serializations unchanged. The general syntax lists
+// signatures under "signatures"; the flattened one holds a single signature's
+// members at the top level.
+//
+// Verification follows §4.1.11 for "crit": it must be protected, a non-empty
+// list of extension names present in the header, none defined by RFC 7515 or
+// RFC 7518, and every name must be one the caller says it understands.
+// Signatures under other algorithms are left to other code; `header` helps
+// pick the one meant for a key, by "kid" for instance.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ec_generic::Point;
+use num_bigint::BigUint;
+use serde_json::{json, Map, Value};
+
+use crate::encoding::to_fixed_bytes;
+use crate::hash::sha256;
+use crate::signer::Signature;
+use crate::ECDSA;
+
+// Header parameters registered by RFC 7515 and RFC 7518, which "crit" may
+// not list
+const REGISTERED: &[&str] = &[
+    "alg", "jku", "jwk", "kid", "x5u", "x5c", "x5t", "x5t#S256", "typ", "cty", "crit", "enc",
+    "zip", "epk", "apu", "apv", "iv", "tag", "p2s", "p2c",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct JwsSignature {
+    // Base64url of the protected header, empty when there is none
+    pub protected: String,
+    pub header: Option<Map<String, Value>>,
+    // Base64url of r || s
+    pub signature: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct JwsJson {
+    // Base64url of the payload
+    pub payload: String,
+    pub signatures: Vec<JwsSignature>,
+}
+
+impl JwsJson {
+    pub fn new(payload: &[u8]) -> JwsJson {
+        JwsJson {
+            payload: URL_SAFE_NO_PAD.encode(payload),
+            signatures: Vec::new(),
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<JwsJson> {
+        // Either syntax; a flattened JWS has "signature" at the top level
+        let value: Value = serde_json::from_str(text).ok()?;
+        let object = value.as_object()?;
+        let payload = object.get("payload")?.as_str()?.to_string();
+        let signatures = match (object.get("signatures"), object.get("signature")) {
+            (Some(Value::Array(entries)), None) if !entries.is_empty() => entries
+                .iter()
+                .map(|entry| read_signature(entry.as_object()?))
+                .collect::<Option<Vec<_>>>()?,
+            (None, Some(_)) => vec![read_signature(object)?],
+            _ => return None,
+        };
+        Some(JwsJson {
+            payload,
+            signatures,
+        })
+    }
+
+    pub fn to_general(&self) -> String {
+        let signatures: Vec<Value> = self.signatures.iter().map(write_signature).collect();
+        json!({"payload": self.payload, "signatures": signatures}).to_string()
+    }
+
+    pub fn to_flattened(&self) -> Option<String> {
+        // None unless there is exactly one signature
+        let [signature] = self.signatures.as_slice() else {
+            return None;
+        };
+        let mut object = write_signature(signature);
+        object["payload"] = Value::from(self.payload.as_str());
+        Some(object.to_string())
+    }
+
+    pub fn payload(&self) -> Option<Vec<u8>> {
+        URL_SAFE_NO_PAD.decode(&self.payload).ok()
+    }
+
+    pub fn header(&self, index: usize) -> Option<Map<String, Value>> {
+        // The JOSE header of a signature: its protected and unprotected
+        // members, None if they overlap or the protected part is not an object
+        let entry = self.signatures.get(index)?;
+        let mut header = protected_header(&entry.protected)?;
+        for (name, value) in entry.header.iter().flatten() {
+            if header.insert(name.clone(), value.clone()).is_some() {
+                return None;
+            }
+        }
+        Some(header)
+    }
+}
+
+impl ECDSA {
+    pub fn jws_json_sign(
+        &self,
+        jws: &mut JwsJson,
+        protected: &Map<String, Value>,
+        header: Option<Map<String, Value>>,
+        priv_key: &BigUint,
+    ) -> Option<()> {
+        // Adds a signature; None off the JOSE curves, or unless the protected
+        // header names this curve's algorithm and shares no name with the
+        // unprotected one
+        let algorithm = self.jws_algorithm()?;
+        if protected.get("alg").and_then(Value::as_str) != Some(algorithm)
+            || header
+                .iter()
+                .flatten()
+                .any(|(name, _)| protected.contains_key(name))
+        {
+            return None;
+        }
+        let protected = URL_SAFE_NO_PAD.encode(Value::from(protected.clone()).to_string());
+        let input = format!("{}.{}", protected, jws.payload);
+        let signature = self.sign_standard(&mut crate::rng(), &sha256(input.as_bytes()), priv_key);
+        let mut bytes = to_fixed_bytes(signature.r(), self.scalar_len());
+        bytes.extend(to_fixed_bytes(signature.s(), self.scalar_len()));
+        jws.signatures.push(JwsSignature {
+            protected,
+            header,
+            signature: URL_SAFE_NO_PAD.encode(bytes),
+        });
+        Some(())
+    }
+
+    pub fn jws_json_verify(
+        &self,
+        jws: &JwsJson,
+        index: usize,
+        pub_key: &Point,
+        understood: &[&str],
+    ) -> bool {
+        // Whether signature `index` is this curve's algorithm, valid under
+        // `pub_key`, with a "crit" naming only extensions in `understood`
+        let Some(header) = jws.header(index) else {
+            return false;
+        };
+        let entry = &jws.signatures[index];
+        let crit_ok = match (header.get("crit"), protected_header(&entry.protected)) {
+            (None, _) => true,
+            (Some(crit), Some(protected)) if protected.contains_key("crit") => {
+                is_valid_crit(crit, &header, understood)
+            }
+            _ => false,
+        };
+        let algorithm = header.get("alg").and_then(Value::as_str);
+        if !crit_ok || algorithm.is_none() || algorithm != self.jws_algorithm() {
+            return false;
+        }
+        let signature = URL_SAFE_NO_PAD.decode(&entry.signature).ok();
+        let Some(signature) = signature.filter(|bytes| bytes.len() == 2 * self.scalar_len()) else {
+            return false;
+        };
+        let (r, s) = signature.split_at(self.scalar_len());
+        let signature = Signature::new(BigUint::from_bytes_be(r), BigUint::from_bytes_be(s));
+        let input = format!("{}.{}", entry.protected, jws.payload);
+        self.verify_standard(&sha256(input.as_bytes()), pub_key, &signature)
+    }
+}
+
+fn read_signature(object: &Map<String, Value>) -> Option<JwsSignature> {
+    // At least one of "protected" and "header" must be there (§7.2.1)
+    let protected = match object.get("protected") {
+        Some(protected) => protected.as_str()?.to_string(),
+        None => String::new(),
+    };
+    let header = match object.get("header") {
+        Some(header) => Some(header.as_object()?.clone()),
+        None => None,
+    };
+    if protected.is_empty() && header.is_none() {
+        return None;
+    }
+    Some(JwsSignature {
+        protected,
+        header,
+        signature: object.get("signature")?.as_str()?.to_string(),
+    })
+}
+
+fn write_signature(signature: &JwsSignature) -> Value {
+    let mut object = Map::new();
+    if !signature.protected.is_empty() {
+        object.insert("protected".into(), signature.protected.as_str().into());
+    }
+    if let Some(header) = &signature.header {
+        object.insert("header".into(), header.clone().into());
+    }
+    object.insert("signature".into(), signature.signature.as_str().into());
+    object.into()
+}
+
+fn protected_header(protected: &str) -> Option<Map<String, Value>> {
+    // An empty protected header is an empty object
+    if protected.is_empty() {
+        return Some(Map::new());
+    }
+    let bytes = URL_SAFE_NO_PAD.decode(protected).ok()?;
+    match serde_json::from_slice(&bytes).ok()? {
+        Value::Object(header) => Some(header),
+        _ => None,
+    }
+}
+
+fn is_valid_crit(crit: &Value, header: &Map<String, Value>, understood: &[&str]) -> bool {
+    let Some(names) = crit.as_array().filter(|names| !names.is_empty()) else {
+        return false;
+    };
+    names.iter().all(|name| {
+        name.as_str().is_some_and(|name| {
+            !REGISTERED.contains(&name) && header.contains_key(name) && understood.contains(&name)
+        })
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::{p256, secp256k1};
+
+    // RFC 7515 Appendix A.3's P-256 key and the payload of its examples
+    const X: &str = "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU";
+    const Y: &str = "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0";
+    const PAYLOAD: &str = "eyJpc3MiOiJqb2UiLA0KICJleHAiOjEzMDA4MTkzODAsDQogImh0dHA6Ly9leGFtcGxlLmNvbS9pc19yb290Ijp0cnVlfQ";
+    const ES256_SIGNATURE: &str =
+        "DtEhU3ljbEg8L38VWAfUAqOyKAM6-Xx-F4GawxaepmXFCgfTjDxw5djxLa8ISlSApmWQxfKTUJqPP3-Kg6NU1Q";
+    const KID: &str = "e9bc097a-ce51-4036-9562-d2ade882db0d";
+
+    fn rfc_key() -> Point {
+        let coordinate = |text| BigUint::from_bytes_be(&URL_SAFE_NO_PAD.decode(text).unwrap());
+        Point::Coor(coordinate(X), coordinate(Y))
+    }
+
+    fn object(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_rfc7515_general_and_flattened() {
+        // A.6 lists an RS256 signature, cut short here since this crate does
+        // not check it, before the ES256 one of A.3; A.7 is that ES256
+        // signature flattened
+        let general = json!({
+            "payload": PAYLOAD,
+            "signatures": [
+                {"protected": "eyJhbGciOiJSUzI1NiJ9", "header": {"kid": "2010-12-29"},
+                 "signature": "cC4hiUPoj9Eetdgtv3hF80EGrhuB"},
+                {"protected": "eyJhbGciOiJFUzI1NiJ9", "header": {"kid": KID},
+                 "signature": ES256_SIGNATURE},
+            ],
+        });
+        let flattened = json!({
+            "payload": PAYLOAD,
+            "protected": "eyJhbGciOiJFUzI1NiJ9",
+            "header": {"kid": KID},
+            "signature": ES256_SIGNATURE,
+        });
+        let ecdsa = p256();
+        let pub_key = rfc_key();
+
+        let jws = JwsJson::parse(&general.to_string()).unwrap();
+        assert_eq!(jws.signatures.len(), 2);
+        assert!(!ecdsa.jws_json_verify(&jws, 0, &pub_key, &[]));
+        assert!(ecdsa.jws_json_verify(&jws, 1, &pub_key, &[]));
+        assert_eq!(jws.header(1).unwrap()["kid"], KID);
+        assert!(jws.payload().unwrap().starts_with(b"{\"iss\":\"joe\""));
+        assert_eq!(JwsJson::parse(&jws.to_general()), Some(jws.clone()));
+        assert_eq!(jws.to_flattened(), None);
+
+        let single = JwsJson::parse(&flattened.to_string()).unwrap();
+        assert!(ecdsa.jws_json_verify(&single, 0, &pub_key, &[]));
+        assert_eq!(
+            serde_json::from_str::<Value>(&single.to_flattened().unwrap()).unwrap(),
+            flattened
+        );
+        assert_eq!(single.signatures[0], jws.signatures[1]);
+    }
+
+    #[test]
+    fn test_multiple_signatures() {
+        let p256 = p256();
+        let k256 = secp256k1();
+        let (priv_a, priv_b) = (BigUint::from(0xc0ffeeu32), BigUint::from(0xdecafu32));
+        let (pub_a, pub_b) = (
+            p256.generate_pub_key(&priv_a),
+            k256.generate_pub_key(&priv_b),
+        );
+
+        let mut jws = JwsJson::new(b"quarterly report");
+        let protected = object(json!({"alg": "ES256"}));
+        let header = object(json!({"kid": "finance"}));
+        p256.jws_json_sign(&mut jws, &protected, Some(header), &priv_a)
+            .unwrap();
+        let protected = object(json!({"alg": "ES256K", "kid": "audit"}));
+        k256.jws_json_sign(&mut jws, &protected, None, &priv_b)
+            .unwrap();
+
+        let jws = JwsJson::parse(&jws.to_general()).unwrap();
+        assert_eq!(jws.payload().unwrap(), b"quarterly report");
+        assert!(p256.jws_json_verify(&jws, 0, &pub_a, &[]));
+        assert!(k256.jws_json_verify(&jws, 1, &pub_b, &[]));
+        assert!(!p256.jws_json_verify(&jws, 1, &pub_a, &[]));
+        assert!(!p256.jws_json_verify(&jws, 2, &pub_a, &[]));
+
+        // Unprotected headers are not signed, but may not shadow protected ones
+        let mut changed = jws.clone();
+        changed.signatures[0].header = Some(object(json!({"kid": "other"})));
+        assert!(p256.jws_json_verify(&changed, 0, &pub_a, &[]));
+        changed.signatures[0].header = Some(object(json!({"alg": "none"})));
+        assert!(!p256.jws_json_verify(&changed, 0, &pub_a, &[]));
+        let mut tampered = jws.clone();
+        tampered.payload = URL_SAFE_NO_PAD.encode(b"quarterly report, revised");
+        assert!(!p256.jws_json_verify(&tampered, 0, &pub_a, &[]));
+
+        let shadowing = object(json!({"alg": "ES256"}));
+        let mut jws = JwsJson::new(b"x");
+        assert!(p256
+            .jws_json_sign(&mut jws, &protected, None, &priv_a)
+            .is_none());
+        assert!(p256
+            .jws_json_sign(&mut jws, &shadowing, Some(shadowing.clone()), &priv_a)
+            .is_none());
+    }
+
+    #[test]
+    fn test_crit() {
+        // The §4.1.11 example: "exp" is critical, so only a verifier that
+        // knows it may accept the signature
+        let ecdsa = p256();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let sign = |protected: Value, header: Option<Value>| {
+            let mut jws = JwsJson::new(b"payload");
+            ecdsa
+                .jws_json_sign(&mut jws, &object(protected), header.map(object), &priv_key)
+                .unwrap();
+            jws
+        };
+
+        let jws = sign(
+            json!({"alg": "ES256", "crit": ["exp"], "exp": 1363284000}),
+            None,
+        );
+        assert!(ecdsa.jws_json_verify(&jws, 0, &pub_key, &["exp"]));
+        assert!(!ecdsa.jws_json_verify(&jws, 0, &pub_key, &[]));
+
+        for (protected, header) in [
+            // Not protected
+            (
+                json!({"alg": "ES256", "exp": 1363284000}),
+                Some(json!({"crit": ["exp"]})),
+            ),
+            // Names a registered parameter, one that is absent, or none
+            (json!({"alg": "ES256", "crit": ["alg"]}), None),
+            (json!({"alg": "ES256", "crit": ["exp"]}), None),
+            (json!({"alg": "ES256", "crit": []}), None),
+            (json!({"alg": "ES256", "crit": "exp", "exp": 1}), None),
+        ] {
+            let jws = sign(protected, header);
+            assert!(!ecdsa.jws_json_verify(&jws, 0, &pub_key, &["exp", "alg"]));
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects() {
+        for text in [
+            r#"{"signatures": [{"protected": "eyJhbGciOiJFUzI1NiJ9", "signature": ""}]}"#,
+            r#"{"payload": "", "signatures": []}"#,
+            r#"{"payload": "", "signatures": [{"signature": ""}]}"#,
+            r#"{"payload": "", "signature": "", "signatures": []}"#,
+            r#"{"payload": "", "header": [], "signature": ""}"#,
+            r#"["payload"]"#,
+        ] {
+            assert_eq!(JwsJson::parse(text), None, "{}", text);
+        }
+    }
+}
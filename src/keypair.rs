@@ -0,0 +1,163 @@
+// A private key bundled with its public key and curve, for the common case of
+// generating or loading a key and signing with it:
+//
+//   let p256 = curves::p256();
+//   let key_pair = KeyPair::generate(&p256);
+//   let signature = key_pair.sign(b"message");
+//
+// Signatures are standard ECDSA over SHA-256, as `sign_standard` and
+// `SigningKey` make them, so they verify with `VerifyingKey`,
+// `verify_standard` and other libraries.
+//
+// `from_seed` regenerates the same key from a stored seed: candidates
+// HKDF-SHA256(salt = DST, ikm = seed, info = q || counter), cut to the bit
+// length of q, are drawn until one lands in [1, q - 1]. Including q keeps the
+// keys of one seed on different curves unrelated.
+
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::encoding::to_fixed_bytes;
+use crate::hash::{hkdf_sha256, sha256};
+use crate::signer::{Signature, SigningKey, VerifyingKey};
+use crate::ECDSA;
+
+const DST_KEY_FROM_SEED: &[u8] = b"RS_ECC-key-from-seed";
+const MIN_SEED_LEN: usize = 16;
+
+#[derive(Clone)]
+pub struct KeyPair<'a> {
+    ecdsa: &'a ECDSA,
+    priv_key: BigUint,
+    pub_key: Point,
+}
+
+impl<'a> KeyPair<'a> {
+    pub fn generate(ecdsa: &'a ECDSA) -> KeyPair<'a> {
+        let (priv_key, pub_key) = ecdsa.generate_key_pair();
+        KeyPair {
+            ecdsa,
+            priv_key,
+            pub_key,
+        }
+    }
+
+    pub fn from_private(ecdsa: &'a ECDSA, priv_key: BigUint) -> Option<KeyPair<'a>> {
+        // None outside [1, q - 1]
+        if priv_key == BigUint::from(0u32) || priv_key >= ecdsa.q_order {
+            return None;
+        }
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        Some(KeyPair {
+            ecdsa,
+            priv_key,
+            pub_key,
+        })
+    }
+
+    pub fn from_seed(ecdsa: &'a ECDSA, seed: &[u8]) -> Option<KeyPair<'a>> {
+        // None for seeds under 128 bits
+        if seed.len() < MIN_SEED_LEN {
+            return None;
+        }
+        let q = &ecdsa.q_order;
+        let len = ecdsa.scalar_len();
+        let excess_bits = 8 * len as u64 - q.bits();
+        for counter in 0u32.. {
+            let info: Vec<u8> = [to_fixed_bytes(q, len), counter.to_be_bytes().to_vec()].concat();
+            let candidate =
+                BigUint::from_bytes_be(&hkdf_sha256(DST_KEY_FROM_SEED, seed, &info, len));
+            if let Some(key_pair) = KeyPair::from_private(ecdsa, candidate >> excess_bits) {
+                return Some(key_pair);
+            }
+        }
+        None
+    }
+
+    pub fn public(&self) -> &Point {
+        &self.pub_key
+    }
+
+    pub fn private(&self) -> &BigUint {
+        &self.priv_key
+    }
+
+    pub fn ecdsa(&self) -> &'a ECDSA {
+        self.ecdsa
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        self.ecdsa
+            .sign_standard(&mut crate::rng(), &sha256(message), &self.priv_key)
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &Signature) -> bool {
+        self.ecdsa
+            .verify_standard(&sha256(message), &self.pub_key, signature)
+    }
+
+    pub fn signing_key(&self) -> SigningKey<'a> {
+        SigningKey::new(self.ecdsa, self.priv_key.clone())
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey<'a> {
+        VerifyingKey::new(self.ecdsa, self.pub_key.clone()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+    use signature::{Signer, Verifier};
+
+    use crate::curves::p256;
+
+    #[test]
+    fn test_key_pair() {
+        let p256 = p256();
+        let key_pair = KeyPair::generate(&p256);
+        let signature = key_pair.sign(b"message");
+        assert!(key_pair.verify(b"message", &signature));
+        assert!(p256.verify_standard(&sha256(b"message"), key_pair.public(), &signature));
+        assert!(!key_pair.verify(b"massage", &signature));
+
+        // The signature trait keys make and check the same signatures
+        assert!(key_pair
+            .verifying_key()
+            .verify(b"message", &signature)
+            .is_ok());
+        let signature = key_pair.signing_key().sign(b"message");
+        assert!(key_pair.verify(b"message", &signature));
+
+        let loaded = KeyPair::from_private(&p256, key_pair.private().clone()).unwrap();
+        assert_eq!(loaded.public(), key_pair.public());
+        assert_eq!(loaded.verifying_key().pub_key(), key_pair.public());
+        assert!(KeyPair::from_private(&p256, BigUint::from(0u32)).is_none());
+        assert!(KeyPair::from_private(&p256, p256.q_order.clone()).is_none());
+    }
+
+    #[test]
+    fn test_key_pair_from_seed() {
+        let p256 = p256();
+        let seed = [0x5e; 32];
+        let key_pair = KeyPair::from_seed(&p256, &seed).unwrap();
+        assert_eq!(
+            KeyPair::from_seed(&p256, &seed).unwrap().private(),
+            key_pair.private()
+        );
+        assert_ne!(
+            KeyPair::from_seed(&p256, &[0x5f; 32]).unwrap().private(),
+            key_pair.private()
+        );
+        assert!(KeyPair::from_seed(&p256, &seed[..15]).is_none());
+
+        // On a curve of order 19 most candidates are rejected
+        let tiny = toy_curve();
+        for byte in 0u8..32 {
+            let key_pair = KeyPair::from_seed(&tiny, &[byte; 16]).unwrap();
+            assert!(*key_pair.private() < tiny.q_order);
+        }
+    }
+}
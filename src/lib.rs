@@ -1,17 +1,245 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![allow(dead_code, unused_imports)]
 
-use ec_generic::{EllipticCurve, FiniteField, Point};
-use num_bigint::{BigUint, RandBigInt};
-use rand::{self, Rng};
-use sha256::{digest, try_digest};
-
-struct ECDSA {
+// Without the default `std` feature the crate only needs `alloc`, and draws
+// randomness from the OS generator through getrandom instead of thread_rng.
+// ec-generic itself still links std, so embedded targets also need a no_std
+// build of that dependency.
+extern crate alloc;
+
+use ec_generic::FiniteField;
+use num_bigint::RandBigInt;
+use rand::{self, CryptoRng, Rng, RngCore};
+
+mod address;
+mod aead;
+#[cfg(feature = "ssh-agent")]
+mod agent;
+mod analysis;
+mod anti_exfil;
+mod arithmetic;
+mod attacks;
+#[cfg(feature = "aws-kms")]
+mod aws_kms;
+mod bip340;
+mod bitcoin_message;
+#[cfg(feature = "cli")]
+mod cli;
+mod cms;
+mod context;
+mod convert;
+#[cfg(feature = "cosign")]
+mod cosign;
+mod counter_nonce;
+pub mod curves;
+mod der;
+mod did_key;
+#[cfg(feature = "differential")]
+mod differential;
+mod dkg;
+mod dleq;
+mod dlog;
+pub mod dnssec;
+mod ecdh;
+mod ecies;
+mod elgamal;
+mod encoding;
+mod envelope;
+mod ethereum_message;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod field;
+mod fixed_width;
+mod fixed_window;
+#[cfg(feature = "gcp-kms")]
+mod gcp_kms;
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+mod generators;
+pub mod group;
+mod hash;
+mod http_signatures;
+mod jacobian;
+#[cfg(feature = "jcs")]
+mod jcs;
+mod jws;
+#[cfg(feature = "jws-json")]
+mod jws_json;
+mod keypair;
+mod malleability;
+mod merkle;
+mod msm;
+mod noise;
+#[cfg(feature = "nostr")]
+mod nostr;
+pub mod oid;
+pub mod openpgp;
+mod oprf;
+mod paillier;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod paseto;
+mod pedersen;
+mod pem;
+mod plot;
+mod point;
+mod precompute;
+mod proof_of_knowledge;
+mod purpose;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "qr")]
+mod qr;
+mod recovery;
+#[cfg(any(feature = "remote-signer", feature = "server"))]
+mod remote_api;
+#[cfg(feature = "remote-signer")]
+mod remote_signer;
+mod scalar;
+mod schnorr;
+mod secret;
+mod signer;
+#[cfg(feature = "server")]
+mod signing_server;
+mod sm2;
+mod spake2;
+mod ssh;
+#[cfg(feature = "std")]
+mod stream;
+mod taproot;
+#[cfg(feature = "testvectors")]
+mod testvectors;
+#[cfg(feature = "timestamp")]
+mod timestamp;
+#[cfg(feature = "timing")]
+mod timing;
+#[cfg(feature = "rustls")]
+mod tls;
+#[cfg(feature = "trace")]
+mod trace;
+mod two_party_ecdsa;
+#[cfg(feature = "vault")]
+mod vault_transit;
+mod vrf;
+mod vss;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "webauthn")]
+mod webauthn;
+mod x25519;
+
+#[cfg(feature = "aws-kms")]
+pub use aws_kms::{AwsCredentials, AwsKmsSigner};
+#[cfg(feature = "cli")]
+pub use cli::run_cli;
+pub use cms::{CmsOptions, CmsSignedData};
+pub use context::SigningContext;
+#[cfg(feature = "cosign")]
+pub use cosign::{rekor_entry, CosignBundle, RekorBundle, RekorPayload};
+pub use counter_nonce::CounterStore;
+#[cfg(feature = "std")]
+pub use counter_nonce::FileCounter;
+pub use did_key::{did_key_ed25519, resolve_did_key, DidKey};
+pub use dkg::DkgDealing;
+pub use dleq::DleqProof;
+pub use ec_generic::{EllipticCurve, Point};
+pub use ecdh::{PubKeyError, SharedSecret};
+pub use elgamal::Ciphertext;
+pub use encoding::{PointFormat, Sec1Point};
+pub use envelope::{Payload, PolicyOutcome, SignedMessage, SignerInfo, VerificationPolicy};
+pub use fixed_width::{FixedCurve, FixedCurve384, FixedWidthCurve};
+#[cfg(feature = "gcp-kms")]
+pub use gcp_kms::GcpKmsSigner;
+#[cfg(feature = "proptest")]
+pub use generators::strategies;
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+pub use generators::{NamedCurve, SignatureCase, SignatureMutation};
+pub use group::Group;
+pub use http_signatures::{signature_base, HttpMessage, SignatureParams, HTTP_SIG_ALGORITHM};
+#[cfg(feature = "jcs")]
+pub use jcs::canonicalize_json;
+#[cfg(feature = "jws-json")]
+pub use jws_json::{JwsJson, JwsSignature};
+pub use keypair::KeyPair;
+pub use merkle::{merkle_root, verify_inclusion, BatchSignature, InclusionProof};
+pub use noise::{CipherState, HandshakePattern, HandshakeState, NoiseDh};
+#[cfg(feature = "nostr")]
+pub use nostr::{nsec, priv_key_from_nsec, NostrEvent};
+pub use num_bigint::BigUint;
+pub use oprf::OprfMode;
+pub use paillier::{ModulusProof, PaillierPrivateKey, PaillierPublicKey};
+pub use pedersen::Commitment;
+pub use point::CurvePoint;
+pub use precompute::{PointTable, PrecomputedVerifyingKey};
+pub use proof_of_knowledge::KnowledgeProof;
+pub use purpose::{Purpose, PurposeKeyPair, PurposeVerifyingKey};
+#[cfg(feature = "remote-signer")]
+pub use remote_signer::RemoteSigner;
+pub use scalar::Scalar;
+pub use secret::SecretScalar;
+pub use signature;
+pub use signer::{AsyncSigner, DerMode, DerSignatureRef, Signature, SigningKey, VerifyingKey};
+#[cfg(feature = "server")]
+pub use signing_server::{
+    router, serve, AuditEvent, AuditLog, Authenticator, BearerToken, JsonLinesAudit, KeyStore,
+    Operation,
+};
+pub use sm2::{Sm2KeyExchange, Sm2SessionKey};
+pub use spake2::{Spake2, Spake2Keys};
+#[cfg(feature = "std")]
+pub use stream::VerifyingStream;
+pub use taproot::{tap_leaf_hash, TapTree, TAPSCRIPT_LEAF_VERSION};
+#[cfg(feature = "testvectors")]
+pub use testvectors::{run_wycheproof, CaseResult, Expected, TestGroup, WycheproofReport};
+#[cfg(feature = "timestamp")]
+pub use timestamp::{
+    request_timestamp, timestamp_info, timestamp_request, timestamp_token, TimestampInfo,
+    TsaTransport,
+};
+#[cfg(feature = "rustls")]
+pub use tls::TlsSigningKey;
+pub use two_party_ecdsa::{
+    KeyEncryptionProof, KeyGenMessage1, KeyGenMessage2, Party1, Party2, SignMessage1, SignMessage2,
+};
+#[cfg(feature = "vault")]
+pub use vault_transit::VaultTransitSigner;
+pub use vss::Share;
+#[cfg(feature = "webauthn")]
+pub use webauthn::{AssertionOptions, AuthenticatorData};
+
+#[allow(clippy::upper_case_acronyms)]
+pub struct ECDSA {
     elliptic_curve: EllipticCurve,
     a_gen: Point,
     q_order: BigUint,
 }
 
 impl ECDSA {
+    pub fn new(elliptic_curve: EllipticCurve, a_gen: Point, q_order: BigUint) -> Option<ECDSA> {
+        // Coefficients reduced mod p and A a point of order q on the curve,
+        // with p and q prime: inverses mod q and square roots mod p assume
+        // it, and signing would loop forever on an even q
+        let p = &elliptic_curve.p;
+        let in_field = matches!(&a_gen, Point::Coor(x, y) if x < p && y < p);
+        if p < &BigUint::from(3u32)
+            || &elliptic_curve.a >= p
+            || &elliptic_curve.b >= p
+            || !in_field
+            || !arithmetic::is_probable_prime(p)
+            || !arithmetic::is_probable_prime(&q_order)
+        {
+            return None;
+        }
+        let ecdsa = ECDSA {
+            elliptic_curve,
+            a_gen,
+            q_order,
+        };
+        if !ecdsa.is_valid_pub_key(&ecdsa.a_gen) {
+            return None;
+        }
+        Some(ecdsa)
+    }
+
     pub fn generate_key_pair(&self) -> (BigUint, Point) {
         // Generates: d, B, where B = dA
         let priv_key = self.generate_priv_key();
@@ -24,14 +252,12 @@ impl ECDSA {
     }
 
     pub fn generate_pub_key(&self, priv_key: &BigUint) -> Point {
-        self.elliptic_curve
-            .scalar_mul(&self.a_gen, priv_key)
-            .unwrap()
+        self.mul_secret(&self.a_gen, &secret::SecretScalar::new(priv_key.clone()))
     }
 
     pub fn generate_random_number_less_than(&self, max: &BigUint) -> BigUint {
-        let mut rng = rand::thread_rng();
-        rng.gen_biguint_range(&BigUint::from(1u32), &max)
+        let mut rng = rng();
+        rng.gen_biguint_range(&BigUint::from(1u32), max)
     }
 
     pub fn sign(
@@ -50,19 +276,8 @@ impl ECDSA {
         );
         assert!(k_random < &self.q_order, "'K' is Bigger than the Ec group");
 
-        let r_point = self
-            .elliptic_curve
-            .scalar_mul(&self.a_gen, k_random)
-            .unwrap();
-
-        if let Point::Coor(r, _) = r_point {
-            let s = FiniteField::mult(&r, priv_key, &self.q_order).unwrap();
-            let s = FiniteField::add(&s, hash, &self.q_order).unwrap();
-            let k_inv = FiniteField::inv_mult_prime(&k_random, &self.q_order).unwrap();
-            let s = FiniteField::mult(&s, &k_inv, &self.q_order).unwrap();
-            return (r, s);
-        }
-        panic!("The random point R should not be the identity");
+        group::sign(self, hash, priv_key, k_random)
+            .expect("The random point R should not be the identity")
     }
 
     pub fn verification(
@@ -76,27 +291,39 @@ impl ECDSA {
             "Hash is bigger than the order of the EC group"
         );
 
-        let (r, s) = signature;
-        let s_inv = FiniteField::inv_mult_prime(&s, &self.q_order).unwrap();
-        let u1 = FiniteField::mult(&s_inv, hash, &self.q_order).unwrap();
-        let u2 = FiniteField::mult(&s_inv, r, &self.q_order).unwrap();
-        let u1a = self.elliptic_curve.scalar_mul(&self.a_gen, &u1).unwrap();
-        let u2b = self.elliptic_curve.scalar_mul(&pub_key, &u2).unwrap();
-        let p = self.elliptic_curve.add(&u1a, &u2b).unwrap();
-
-        if let Point::Coor(xp, _) = p {
-            return &xp == r;
-        }
-        panic!("Point P = u1 + u2 cannot be the identity")
+        group::verify(self, hash, pub_key, signature)
     }
 
     pub fn generate_hash_less_than(&self, message: &str, max: &BigUint) -> BigUint {
-        let digest = digest(message);
-        let hash_bytes = hex::decode(digest).expect("Could not convert hash to Vec<u8>");
+        let hash_bytes = hash::sha256(message.as_bytes());
         let hash = BigUint::from_bytes_be(&hash_bytes)
             .modpow(&BigUint::from(1u32), &(max - BigUint::from(1u32)));
-        let hash = hash + BigUint::from(1u32);
-        hash
+        hash + BigUint::from(1u32)
+    }
+}
+
+#[cfg(feature = "std")]
+fn rng() -> impl RngCore + CryptoRng {
+    rand::thread_rng()
+}
+
+#[cfg(not(feature = "std"))]
+fn rng() -> impl RngCore + CryptoRng {
+    rand::rngs::OsRng
+}
+
+#[cfg(test)]
+pub(crate) fn toy_curve() -> ECDSA {
+    // y² = x³ + 2x + 2 over F_17, generator (5, 1) of prime order 19, small
+    // enough for the unit tests to reason about by hand
+    ECDSA {
+        elliptic_curve: EllipticCurve {
+            a: BigUint::from(2u32),
+            b: BigUint::from(2u32),
+            p: BigUint::from(17u32),
+        },
+        a_gen: Point::Coor(BigUint::from(5u32), BigUint::from(1u32)),
+        q_order: BigUint::from(19u32),
     }
 }
 
@@ -128,7 +355,7 @@ mod test {
         let k_random = BigUint::from(18u32);
 
         let message = "Bob -> 1 BTC -> Alice";
-        let hash = ecdsa.generate_hash_less_than(&message, &ecdsa.q_order);
+        let hash = ecdsa.generate_hash_less_than(message, &ecdsa.q_order);
 
         let signature = ecdsa.sign(&hash, &priv_key, &k_random);
         let verify_result = ecdsa.verification(&hash, &pub_key, &signature);
@@ -158,11 +385,11 @@ mod test {
         let k_random = BigUint::from(18u32);
 
         let message = "Bob -> 1 BTC -> Alice";
-        let hash = ecdsa.generate_hash_less_than(&message, &ecdsa.q_order);
+        let hash = ecdsa.generate_hash_less_than(message, &ecdsa.q_order);
         let signature = ecdsa.sign(&hash, &priv_key, &k_random);
 
         let message = "Bob -> 2 BTC -> Alice";
-        let hash = ecdsa.generate_hash_less_than(&message, &ecdsa.q_order);
+        let hash = ecdsa.generate_hash_less_than(message, &ecdsa.q_order);
         let verify_result = ecdsa.verification(&hash, &pub_key, &signature);
 
         assert!(!verify_result, "Verification should fail");
@@ -190,7 +417,7 @@ mod test {
         let k_random = BigUint::from(4u32);
 
         let message = "Bob -> 1 BTC -> Alice";
-        let hash = ecdsa.generate_hash_less_than(&message, &ecdsa.q_order);
+        let hash = ecdsa.generate_hash_less_than(message, &ecdsa.q_order);
         let signature = ecdsa.sign(&hash, &priv_key, &k_random);
         let (r, s) = signature;
         let tempered_siganture = (
@@ -202,4 +429,18 @@ mod test {
 
         assert!(!verify_result, "Verification should fail");
     }
+
+    #[test]
+    fn test_new_rejects_composite_orders() {
+        let curve = |p: u32| EllipticCurve {
+            a: BigUint::from(2u32),
+            b: BigUint::from(2u32),
+            p: BigUint::from(p),
+        };
+        let a_gen = Point::Coor(BigUint::from(5u32), BigUint::from(1u32));
+        assert!(ECDSA::new(curve(17), a_gen.clone(), BigUint::from(19u32)).is_some());
+        // 38 A = O as well, but signing mod 38 never finds an inverse of k
+        assert!(ECDSA::new(curve(17), a_gen.clone(), BigUint::from(38u32)).is_none());
+        assert!(ECDSA::new(curve(21), a_gen, BigUint::from(19u32)).is_none());
+    }
 }
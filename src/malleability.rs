@@ -0,0 +1,153 @@
+// Signature malleability at the boundary of consensus-critical code. Anyone
+// can turn a valid (r, s) into a second valid signature (r, q - s) over the
+// same message, and lenient DER readers accept many byte strings for one
+// (r, s). Systems that identify transactions by their signed bytes must keep
+// a single form: low s (s <= q/2, as Bitcoin's BIP 62/146 require) in the
+// canonical DER encoding that `Signature::from_der` reads.
+//
+// `malleated_der` lists encodings a strict boundary has to turn away: the
+// high-s twin and BER variants of the same (r, s) with non-minimal lengths,
+// sign padding or trailing bytes. It is meant for testing those boundaries.
+
+use alloc::{vec, vec::Vec};
+use num_bigint::BigUint;
+
+use crate::der;
+use crate::signer::{DerSignatureRef, Signature};
+use crate::ECDSA;
+
+impl Signature {
+    pub fn is_low_s(&self, ecdsa: &ECDSA) -> bool {
+        // s <= q/2; says nothing about r and s being in range
+        self.s() <= &(&ecdsa.q_order >> 1)
+    }
+
+    pub fn normalize(&self, ecdsa: &ECDSA) -> Signature {
+        // The low-s twin, which verifies wherever this one does; unchanged
+        // when s is already low or not below q
+        let q = &ecdsa.q_order;
+        if self.is_low_s(ecdsa) || self.s() >= q {
+            return self.clone();
+        }
+        Signature::new(self.r().clone(), q - self.s())
+    }
+
+    pub fn is_canonical_der(bytes: &[u8]) -> bool {
+        // Exactly the bytes `to_der` writes for the values inside
+        DerSignatureRef::parse(bytes).is_some_and(|sig| sig.to_signature().to_der() == bytes)
+    }
+
+    pub fn malleated_der(&self, ecdsa: &ECDSA) -> Vec<Vec<u8>> {
+        // None of these is canonical low-s DER, though each is this signature
+        // or its twin to a lenient reader
+        let q = &ecdsa.q_order;
+        let zero = BigUint::from(0u32);
+        let mut variants = Vec::new();
+        if *self.s() != zero && self.s() < q {
+            let twin = Signature::new(self.r().clone(), q - self.s());
+            let high = if self.is_low_s(ecdsa) {
+                twin
+            } else {
+                self.clone()
+            };
+            variants.push(high.to_der());
+        }
+
+        let (r, s) = (der::integer(self.r()), der::integer(self.s()));
+        let sequence = |body: &[u8]| der::write(der::SEQUENCE, body);
+        let body = [r.as_slice(), &s].concat();
+        // Sign padding: a 0x00 before a value whose top bit is clear
+        variants.push(sequence(&[padded_integer(self.r()), s.clone()].concat()));
+        variants.push(sequence(&[r.clone(), padded_integer(self.s())].concat()));
+        // Long-form length where the short form fits
+        if body.len() < 0x80 {
+            let mut long = vec![der::SEQUENCE, 0x81, body.len() as u8];
+            long.extend(&body);
+            variants.push(long);
+        }
+        // BER indefinite length, closed by an end-of-contents marker
+        let mut indefinite = vec![der::SEQUENCE, 0x80];
+        indefinite.extend(&body);
+        indefinite.extend([0x00, 0x00]);
+        variants.push(indefinite);
+        // Trailing data after the SEQUENCE
+        let mut trailing = sequence(&body);
+        trailing.push(0x00);
+        variants.push(trailing);
+        variants
+    }
+}
+
+fn padded_integer(n: &BigUint) -> Vec<u8> {
+    // `der::integer` with one more leading 0x00 than needed
+    let mut der = der::integer(n);
+    let len = der[1] as usize;
+    der.insert(2, 0x00);
+    der[1] = (len + 1) as u8;
+    der
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::secp256k1;
+    use crate::hash::sha256;
+    use crate::signer::DerMode;
+
+    #[test]
+    fn test_normalize() {
+        let ecdsa = secp256k1();
+        let q = &ecdsa.q_order;
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let digest = sha256(b"message");
+        let signature = ecdsa.sign_standard(&mut crate::rng(), &digest, &priv_key);
+
+        let low = signature.normalize(&ecdsa);
+        let high = Signature::new(low.r().clone(), q - low.s());
+        assert!(low.is_low_s(&ecdsa));
+        assert!(!high.is_low_s(&ecdsa));
+        assert_eq!(high.normalize(&ecdsa), low);
+        assert_eq!(low.normalize(&ecdsa), low);
+        assert!(ecdsa.verify_standard(&digest, &pub_key, &low));
+        assert!(ecdsa.verify_standard(&digest, &pub_key, &high));
+
+        // q/2 is the largest low s
+        let half = Signature::new(BigUint::from(1u32), q >> 1);
+        assert!(half.is_low_s(&ecdsa));
+        assert!(!Signature::new(BigUint::from(1u32), (q >> 1) + 1u32).is_low_s(&ecdsa));
+    }
+
+    #[test]
+    fn test_malleated_der() {
+        let ecdsa = secp256k1();
+        let digest = sha256(b"message");
+        let signature = ecdsa
+            .sign_standard(&mut crate::rng(), &digest, &BigUint::from(7u32))
+            .normalize(&ecdsa);
+        let der = signature.to_der();
+        assert!(Signature::is_canonical_der(&der));
+        assert!(!Signature::is_canonical_der(&der[..der.len() - 1]));
+
+        let variants = signature.malleated_der(&ecdsa);
+        assert_eq!(variants.len(), 6);
+        let twin = Signature::from_der(&variants[0]).unwrap();
+        assert_eq!(twin.normalize(&ecdsa), signature);
+        for variant in &variants[1..] {
+            assert!(!Signature::is_canonical_der(variant));
+            assert!(Signature::from_der(variant).is_none());
+        }
+
+        // BER readers take all but the trailing byte, as the same signature
+        let lenient = |bytes: &[u8]| Signature::from_der_with(bytes, DerMode::LenientBer);
+        for variant in &variants[1..5] {
+            assert_eq!(lenient(variant), Some(signature.clone()));
+        }
+        assert_eq!(lenient(&variants[5]), None);
+        let pub_key = ecdsa.generate_pub_key(&BigUint::from(7u32));
+        assert!(ecdsa.verify_der(&digest, &pub_key, &variants[1], DerMode::LenientBer));
+        assert!(!ecdsa.verify_der(&digest, &pub_key, &variants[1], DerMode::Strict));
+        assert!(ecdsa.verify_der(&digest, &pub_key, &der, DerMode::Strict));
+    }
+}
@@ -0,0 +1,238 @@
+// Batch signing through a Merkle tree: one ECDSA signature over the root of a
+// tree of N messages, and for each message an inclusion proof of about log2(N)
+// hashes that ties it to that root. The tree is the one of RFC 6962
+// (Certificate Transparency): leaves are SHA-256(0x00 || m), nodes
+// SHA-256(0x01 || left || right), and a tree of n leaves splits at the largest
+// power of two below n, so no leaf is ever duplicated. The signed digest is
+// SHA-256(DST || size || root), binding the tree size the proofs depend on.
+
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::BigUint;
+use signature::rand_core::CryptoRngCore;
+
+use crate::hash::sha256;
+use crate::signer::Signature;
+use crate::ECDSA;
+
+const DST_BATCH: &[u8] = b"RS_ECC-Merkle-batch";
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct BatchSignature {
+    pub size: u64,
+    pub root: Vec<u8>,
+    pub signature: Signature,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct InclusionProof {
+    pub index: u64,
+    // Sibling hashes from the leaf up to the root
+    pub path: Vec<Vec<u8>>,
+}
+
+pub fn merkle_root(messages: &[&[u8]]) -> Option<Vec<u8>> {
+    if messages.is_empty() {
+        return None;
+    }
+    let leaves: Vec<Vec<u8>> = messages.iter().map(|m| leaf_hash(m)).collect();
+    Some(subtree_root(&leaves))
+}
+
+impl ECDSA {
+    pub fn sign_batch(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        messages: &[&[u8]],
+        priv_key: &BigUint,
+    ) -> Option<(BatchSignature, Vec<InclusionProof>)> {
+        let root = merkle_root(messages)?;
+        let size = messages.len() as u64;
+        let signature = self.sign_standard(rng, &batch_digest(size, &root), priv_key);
+
+        let leaves: Vec<Vec<u8>> = messages.iter().map(|m| leaf_hash(m)).collect();
+        let proofs = (0..leaves.len())
+            .map(|index| InclusionProof {
+                index: index as u64,
+                path: audit_path(index, &leaves),
+            })
+            .collect();
+        Some((
+            BatchSignature {
+                size,
+                root,
+                signature,
+            },
+            proofs,
+        ))
+    }
+
+    pub fn verify_batch_signature(&self, batch: &BatchSignature, pub_key: &Point) -> bool {
+        self.verify_standard(
+            &batch_digest(batch.size, &batch.root),
+            pub_key,
+            &batch.signature,
+        )
+    }
+
+    pub fn verify_batch_member(
+        &self,
+        message: &[u8],
+        proof: &InclusionProof,
+        batch: &BatchSignature,
+        pub_key: &Point,
+    ) -> bool {
+        verify_inclusion(message, proof, batch.size, &batch.root)
+            && self.verify_batch_signature(batch, pub_key)
+    }
+}
+
+pub fn verify_inclusion(message: &[u8], proof: &InclusionProof, size: u64, root: &[u8]) -> bool {
+    // RFC 9162 section 2.1.3.2
+    if proof.index >= size {
+        return false;
+    }
+    let (mut f_n, mut s_n) = (proof.index, size - 1);
+    let mut hash = leaf_hash(message);
+    for sibling in &proof.path {
+        if s_n == 0 {
+            return false;
+        }
+        if f_n & 1 == 1 || f_n == s_n {
+            hash = node_hash(sibling, &hash);
+            while f_n & 1 == 0 && f_n != 0 {
+                f_n >>= 1;
+                s_n >>= 1;
+            }
+        } else {
+            hash = node_hash(&hash, sibling);
+        }
+        f_n >>= 1;
+        s_n >>= 1;
+    }
+    s_n == 0 && hash == root
+}
+
+fn batch_digest(size: u64, root: &[u8]) -> Vec<u8> {
+    sha256(&[DST_BATCH, &size.to_be_bytes(), root].concat())
+}
+
+fn leaf_hash(message: &[u8]) -> Vec<u8> {
+    sha256(&[&[0x00], message].concat())
+}
+
+fn node_hash(left: &[u8], right: &[u8]) -> Vec<u8> {
+    sha256(&[&[0x01], left, right].concat())
+}
+
+fn split(n: usize) -> usize {
+    // Largest power of two strictly below n, n > 1
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+fn subtree_root(leaves: &[Vec<u8>]) -> Vec<u8> {
+    if leaves.len() == 1 {
+        return leaves[0].clone();
+    }
+    let k = split(leaves.len());
+    node_hash(&subtree_root(&leaves[..k]), &subtree_root(&leaves[k..]))
+}
+
+fn audit_path(index: usize, leaves: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    if leaves.len() == 1 {
+        return Vec::new();
+    }
+    let k = split(leaves.len());
+    let (mut path, sibling) = if index < k {
+        (audit_path(index, &leaves[..k]), subtree_root(&leaves[k..]))
+    } else {
+        (
+            audit_path(index - k, &leaves[k..]),
+            subtree_root(&leaves[..k]),
+        )
+    };
+    path.push(sibling);
+    path
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::curves::p256;
+
+    #[test]
+    fn test_merkle_root() {
+        // Two and three leaves written out by hand
+        let (a, b, c) = (&b"a"[..], &b"b"[..], &b"c"[..]);
+        assert_eq!(merkle_root(&[]), None);
+        assert_eq!(merkle_root(&[a]), Some(leaf_hash(a)));
+        assert_eq!(
+            merkle_root(&[a, b, c]),
+            Some(node_hash(
+                &node_hash(&leaf_hash(a), &leaf_hash(b)),
+                &leaf_hash(c)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_inclusion_proofs() {
+        // Every index of every size up to 9, odd sizes included
+        for size in 1..10usize {
+            let messages: Vec<Vec<u8>> = (0..size).map(|i| vec![i as u8; i + 1]).collect();
+            let messages: Vec<&[u8]> = messages.iter().map(Vec::as_slice).collect();
+            let leaves: Vec<Vec<u8>> = messages.iter().map(|m| leaf_hash(m)).collect();
+            let root = merkle_root(&messages).unwrap();
+
+            for (index, message) in messages.iter().enumerate() {
+                let proof = InclusionProof {
+                    index: index as u64,
+                    path: audit_path(index, &leaves),
+                };
+                assert!(verify_inclusion(message, &proof, size as u64, &root));
+                assert!(!verify_inclusion(b"other", &proof, size as u64, &root));
+                if size > 1 {
+                    let moved = InclusionProof {
+                        index: ((index + 1) % size) as u64,
+                        ..proof.clone()
+                    };
+                    assert!(!verify_inclusion(message, &moved, size as u64, &root));
+                    let truncated = InclusionProof {
+                        index: index as u64,
+                        path: proof.path[1..].to_vec(),
+                    };
+                    assert!(!verify_inclusion(message, &truncated, size as u64, &root));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sign_batch() {
+        let ecdsa = p256();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let messages: [&[u8]; 5] = [b"log 1", b"log 2", b"log 3", b"log 4", b"log 5"];
+
+        let (batch, proofs) = ecdsa
+            .sign_batch(&mut StdRng::seed_from_u64(1), &messages, &priv_key)
+            .unwrap();
+        assert_eq!(batch.size, 5);
+        assert!(ecdsa.verify_batch_member(b"log 4", &proofs[3], &batch, &pub_key));
+        assert!(!ecdsa.verify_batch_member(b"log 4", &proofs[2], &batch, &pub_key));
+
+        // Claiming another size breaks the signature, not only the proof
+        let resized = BatchSignature {
+            size: 6,
+            ..batch.clone()
+        };
+        assert!(!ecdsa.verify_batch_signature(&resized, &pub_key));
+        assert!(ecdsa
+            .sign_batch(&mut StdRng::seed_from_u64(1), &[], &priv_key)
+            .is_none());
+    }
+}
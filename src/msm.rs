@@ -0,0 +1,86 @@
+// Multi-scalar multiplication, sum k_i P_i, by Pippenger's bucket method.
+// Scalars are cut into c-bit windows; in each window every point is added
+// once into the bucket of its digit, and the buckets are folded with a running
+// sum, so a window costs about n + 2^(c+1) additions instead of n scalar
+// multiplications. The window width grows with the number of inputs.
+
+use alloc::{vec, vec::Vec};
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::ECDSA;
+
+impl ECDSA {
+    pub fn multi_scalar_mul(&self, points: &[Point], scalars: &[BigUint]) -> Point {
+        assert_eq!(
+            points.len(),
+            scalars.len(),
+            "Need as many scalars as points"
+        );
+        let scalars: Vec<BigUint> = scalars.iter().map(|k| k % &self.q_order).collect();
+        let c = msm_window_bits(points.len());
+        let windows = self.q_order.bits().div_ceil(c as u64);
+
+        let mut result = Point::Identity;
+        for window in (0..windows).rev() {
+            for _ in 0..c {
+                result = self.add_points(&result, &result);
+            }
+
+            let mut buckets = vec![Point::Identity; (1 << c) - 1];
+            for (point, k) in points.iter().zip(&scalars) {
+                let digit = (0..c).fold(0usize, |digit, bit| {
+                    digit | (usize::from(k.bit(window * c as u64 + bit as u64)) << bit)
+                });
+                if digit != 0 {
+                    buckets[digit - 1] = self.add_points(&buckets[digit - 1], point);
+                }
+            }
+
+            // sum_j j * bucket_j, as the sum of the running sums from the top
+            let mut running = Point::Identity;
+            let mut window_sum = Point::Identity;
+            for bucket in buckets.iter().rev() {
+                running = self.add_points(&running, bucket);
+                window_sum = self.add_points(&window_sum, &running);
+            }
+            result = self.add_points(&result, &window_sum);
+        }
+        result
+    }
+}
+
+fn msm_window_bits(len: usize) -> usize {
+    // About ln(n) + 2 bits, ln(n) ≈ 0.69 log2(n)
+    if len < 4 {
+        1
+    } else {
+        (len.ilog2() as usize) * 69 / 100 + 2
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_multi_scalar_mul() {
+        let ecdsa = toy_curve();
+        for len in [0u32, 1, 3, 8, 40] {
+            let points: Vec<Point> = (0..len)
+                .map(|i| ecdsa.mul_vartime(&ecdsa.a_gen, &BigUint::from(i * 7 + 1)))
+                .collect();
+            // Includes zero and scalars above q
+            let scalars: Vec<BigUint> = (0..len).map(|i| BigUint::from(i * 5 % 23)).collect();
+
+            let expected = points
+                .iter()
+                .zip(&scalars)
+                .fold(Point::Identity, |acc, (point, k)| {
+                    ecdsa.add_points(&acc, &ecdsa.mul_vartime(point, k))
+                });
+            assert_eq!(ecdsa.multi_scalar_mul(&points, &scalars), expected);
+        }
+    }
+}
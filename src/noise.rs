@@ -0,0 +1,620 @@
+// Noise protocol framework (revision 34) handshakes with ChaCha20-Poly1305 for
+// the cipher and SHA-256 for the hash and HKDF. Two DH functions are offered:
+// `25519`, X25519 with 32-byte keys, and `P256`, ECDH on P-256 with keys sent
+// compressed, so DHLEN = 33. Keys are handled as bytes in the suite's format.
+// Neither the X25519 ladder nor the P-256 arithmetic under it is constant
+// time. Only the XX (mutual, keys exchanged in the handshake) and IK
+// (initiator knows the responder key in advance) patterns are supported.
+
+use alloc::{format, vec, vec::Vec};
+use num_bigint::BigUint;
+use rand::RngCore;
+
+use crate::aead::{self, KEY_LEN, NONCE_LEN, TAG_LEN};
+use crate::curves;
+use crate::ecdh::SharedSecret;
+use crate::encoding::to_fixed_bytes;
+use crate::hash::{hkdf_sha256, sha256};
+use crate::x25519::{x25519_base, x25519_diffie_hellman};
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum HandshakePattern {
+    XX,
+    IK,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum NoiseDh {
+    X25519,
+    P256,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum Token {
+    E,
+    S,
+    EE,
+    ES,
+    SE,
+    SS,
+}
+
+impl HandshakePattern {
+    fn name(&self) -> &'static str {
+        match self {
+            HandshakePattern::XX => "XX",
+            HandshakePattern::IK => "IK",
+        }
+    }
+
+    fn messages(&self) -> Vec<Vec<Token>> {
+        use Token::*;
+        match self {
+            // -> e
+            // <- e, ee, s, es
+            // -> s, se
+            HandshakePattern::XX => vec![vec![E], vec![E, EE, S, ES], vec![S, SE]],
+            // <- s
+            // ...
+            // -> e, es, s, ss
+            // <- e, ee, se
+            HandshakePattern::IK => vec![vec![E, ES, S, SS], vec![E, EE, SE]],
+        }
+    }
+}
+
+impl NoiseDh {
+    fn name(&self) -> &'static str {
+        match self {
+            NoiseDh::X25519 => "25519",
+            NoiseDh::P256 => "P256",
+        }
+    }
+
+    fn dh_len(&self) -> usize {
+        match self {
+            NoiseDh::X25519 => 32,
+            NoiseDh::P256 => 33,
+        }
+    }
+
+    pub fn generate_priv_key(&self) -> Vec<u8> {
+        // Any 32 bytes for X25519, which clamps them; a scalar in [1, q) for P-256
+        match self {
+            NoiseDh::X25519 => {
+                let mut priv_key = vec![0u8; 32];
+                crate::rng().fill_bytes(&mut priv_key);
+                priv_key
+            }
+            NoiseDh::P256 => {
+                to_fixed_bytes(&curves::NamedCurve::P256.preset().generate_key_pair().0, 32)
+            }
+        }
+    }
+
+    pub fn public_key(&self, priv_key: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            NoiseDh::X25519 => Some(x25519_base(priv_key.try_into().ok()?).to_vec()),
+            NoiseDh::P256 => {
+                let ecdsa = curves::NamedCurve::P256.preset();
+                let d = BigUint::from_bytes_be(priv_key);
+                if priv_key.len() != 32 || d == BigUint::from(0u32) || d >= ecdsa.q_order {
+                    return None;
+                }
+                Some(ecdsa.encode_point(&ecdsa.generate_pub_key(&d)))
+            }
+        }
+    }
+
+    fn is_public_key(&self, pub_key: &[u8]) -> bool {
+        match self {
+            NoiseDh::X25519 => pub_key.len() == 32,
+            NoiseDh::P256 => curves::NamedCurve::P256
+                .preset()
+                .parse_pub_key(pub_key)
+                .is_ok(),
+        }
+    }
+
+    fn dh(&self, priv_key: &[u8], pub_key: &[u8]) -> Option<SharedSecret> {
+        match self {
+            NoiseDh::X25519 => {
+                x25519_diffie_hellman(priv_key.try_into().ok()?, pub_key.try_into().ok()?)
+            }
+            NoiseDh::P256 => {
+                let ecdsa = curves::NamedCurve::P256.preset();
+                let pub_key = ecdsa.parse_pub_key(pub_key).ok()?;
+                ecdsa.diffie_hellman(&BigUint::from_bytes_be(priv_key), &pub_key)
+            }
+        }
+    }
+}
+
+pub struct CipherState {
+    k: Option<[u8; KEY_LEN]>,
+    n: u64,
+}
+
+impl CipherState {
+    fn new(k: Option<[u8; KEY_LEN]>) -> CipherState {
+        CipherState { k, n: 0 }
+    }
+
+    fn nonce(&self) -> [u8; NONCE_LEN] {
+        // 32 bits of zeros || little-endian 64-bit counter
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce[4..].copy_from_slice(&self.n.to_le_bytes());
+        nonce
+    }
+
+    pub fn encrypt_with_ad(&mut self, ad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        match self.k {
+            Some(k) => {
+                assert!(self.n < u64::MAX, "Noise nonce is exhausted");
+                let ciphertext = aead::seal(&k, &self.nonce(), ad, plaintext);
+                self.n += 1;
+                ciphertext
+            }
+            None => plaintext.to_vec(),
+        }
+    }
+
+    pub fn decrypt_with_ad(&mut self, ad: &[u8], ciphertext: &[u8]) -> Option<Vec<u8>> {
+        match self.k {
+            Some(k) => {
+                if self.n == u64::MAX {
+                    return None;
+                }
+                let plaintext = aead::open(&k, &self.nonce(), ad, ciphertext)?;
+                self.n += 1;
+                Some(plaintext)
+            }
+            None => Some(ciphertext.to_vec()),
+        }
+    }
+}
+
+struct SymmetricState {
+    ck: Vec<u8>,
+    h: Vec<u8>,
+    cipher: CipherState,
+}
+
+impl SymmetricState {
+    fn new(protocol_name: &[u8]) -> SymmetricState {
+        // h = protocol_name padded to HASHLEN, or its hash if it is longer
+        let h = if protocol_name.len() <= 32 {
+            let mut h = protocol_name.to_vec();
+            h.resize(32, 0);
+            h
+        } else {
+            sha256(protocol_name)
+        };
+        SymmetricState {
+            ck: h.clone(),
+            h,
+            cipher: CipherState::new(None),
+        }
+    }
+
+    fn mix_key(&mut self, shared: &SharedSecret) {
+        // ck, k = HKDF(ck, DH output)
+        let okm = shared.derive_key(&self.ck, &[], 64).unwrap();
+        self.ck = okm[..32].to_vec();
+        self.cipher = CipherState::new(Some(okm[32..].try_into().unwrap()));
+    }
+
+    fn mix_hash(&mut self, data: &[u8]) {
+        self.h = sha256(&[self.h.as_slice(), data].concat());
+    }
+
+    fn encrypt_and_hash(&mut self, plaintext: &[u8]) -> Vec<u8> {
+        let ciphertext = self.cipher.encrypt_with_ad(&self.h, plaintext);
+        self.mix_hash(&ciphertext);
+        ciphertext
+    }
+
+    fn decrypt_and_hash(&mut self, ciphertext: &[u8]) -> Option<Vec<u8>> {
+        let plaintext = self.cipher.decrypt_with_ad(&self.h, ciphertext)?;
+        self.mix_hash(ciphertext);
+        Some(plaintext)
+    }
+
+    fn split(&self) -> (CipherState, CipherState) {
+        let okm = hkdf_sha256(&self.ck, &[], &[], 64);
+        (
+            CipherState::new(Some(okm[..32].try_into().unwrap())),
+            CipherState::new(Some(okm[32..].try_into().unwrap())),
+        )
+    }
+}
+
+pub struct HandshakeState {
+    symmetric: SymmetricState,
+    dh: NoiseDh,
+    initiator: bool,
+    s: (Vec<u8>, Vec<u8>),
+    e: Option<(Vec<u8>, Vec<u8>)>,
+    rs: Option<Vec<u8>>,
+    re: Option<Vec<u8>>,
+    messages: Vec<Vec<Token>>,
+    index: usize,
+}
+
+impl HandshakeState {
+    pub fn new(
+        dh: NoiseDh,
+        pattern: HandshakePattern,
+        initiator: bool,
+        prologue: &[u8],
+        static_key: &[u8],
+        remote_static: Option<&[u8]>,
+    ) -> HandshakeState {
+        // For IK the initiator must already know the responder static key,
+        // which both sides mix into h as a pre-message
+        let name = format!("Noise_{}_{}_ChaChaPoly_SHA256", pattern.name(), dh.name());
+        let mut symmetric = SymmetricState::new(name.as_bytes());
+        symmetric.mix_hash(prologue);
+
+        let s_pub = dh
+            .public_key(static_key)
+            .expect("Not a private key of the DH function");
+        let s = (static_key.to_vec(), s_pub);
+        if pattern == HandshakePattern::IK {
+            let responder_static = if initiator {
+                remote_static.expect("IK needs the responder static key")
+            } else {
+                &s.1
+            };
+            symmetric.mix_hash(responder_static);
+        }
+
+        HandshakeState {
+            symmetric,
+            dh,
+            initiator,
+            s,
+            e: None,
+            rs: remote_static.map(<[u8]>::to_vec),
+            re: None,
+            messages: pattern.messages(),
+            index: 0,
+        }
+    }
+
+    #[cfg(test)]
+    fn set_ephemeral(&mut self, priv_key: &[u8]) {
+        // Fixed ephemeral keys, for test vectors only
+        let e_pub = self.dh.public_key(priv_key).unwrap();
+        self.e = Some((priv_key.to_vec(), e_pub));
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.index == self.messages.len()
+    }
+
+    pub fn remote_static(&self) -> Option<&[u8]> {
+        self.rs.as_deref()
+    }
+
+    pub fn handshake_hash(&self) -> &[u8] {
+        &self.symmetric.h
+    }
+
+    pub fn split(&self) -> (CipherState, CipherState) {
+        // (initiator -> responder, responder -> initiator)
+        assert!(self.is_finished(), "Handshake is not finished");
+        self.symmetric.split()
+    }
+
+    fn is_our_turn(&self) -> bool {
+        self.index.is_multiple_of(2) == self.initiator
+    }
+
+    pub fn write_message(&mut self, payload: &[u8]) -> Vec<u8> {
+        assert!(
+            !self.is_finished() && self.is_our_turn(),
+            "Not our turn to write a handshake message"
+        );
+
+        let mut message = Vec::new();
+        for token in self.messages[self.index].clone() {
+            match token {
+                Token::E => {
+                    // Each side writes e once, so a key already set is a preset one
+                    let e = self.e.take().unwrap_or_else(|| {
+                        let e_priv = self.dh.generate_priv_key();
+                        let e_pub = self.dh.public_key(&e_priv).unwrap();
+                        (e_priv, e_pub)
+                    });
+                    self.symmetric.mix_hash(&e.1);
+                    message.extend(&e.1);
+                    self.e = Some(e);
+                }
+                Token::S => {
+                    let s_pub = self.s.1.clone();
+                    message.extend(self.symmetric.encrypt_and_hash(&s_pub));
+                }
+                _ => {
+                    let shared = self
+                        .dh(token)
+                        .expect("Remote key is not a valid public key of the DH function");
+                    self.symmetric.mix_key(&shared);
+                }
+            }
+        }
+        message.extend(self.symmetric.encrypt_and_hash(payload));
+        self.index += 1;
+        message
+    }
+
+    pub fn read_message(&mut self, message: &[u8]) -> Option<Vec<u8>> {
+        assert!(
+            !self.is_finished() && !self.is_our_turn(),
+            "Not our turn to read a handshake message"
+        );
+
+        let dh_len = self.dh.dh_len();
+        let mut rest = message;
+        for token in self.messages[self.index].clone() {
+            match token {
+                Token::E => {
+                    if rest.len() < dh_len {
+                        return None;
+                    }
+                    let (re, tail) = rest.split_at(dh_len);
+                    if !self.dh.is_public_key(re) {
+                        return None;
+                    }
+                    self.re = Some(re.to_vec());
+                    self.symmetric.mix_hash(re);
+                    rest = tail;
+                }
+                Token::S => {
+                    let len = if self.symmetric.cipher.k.is_some() {
+                        dh_len + TAG_LEN
+                    } else {
+                        dh_len
+                    };
+                    if rest.len() < len {
+                        return None;
+                    }
+                    let (rs, tail) = rest.split_at(len);
+                    let rs = self.symmetric.decrypt_and_hash(rs)?;
+                    if !self.dh.is_public_key(&rs) {
+                        return None;
+                    }
+                    self.rs = Some(rs);
+                    rest = tail;
+                }
+                _ => {
+                    let shared = self.dh(token)?;
+                    self.symmetric.mix_key(&shared);
+                }
+            }
+        }
+        let payload = self.symmetric.decrypt_and_hash(rest)?;
+        self.index += 1;
+        Some(payload)
+    }
+
+    fn dh(&self, token: Token) -> Option<SharedSecret> {
+        // es and se name the initiator key first, so the responder swaps them
+        let (local_ephemeral, remote_ephemeral) = match (token, self.initiator) {
+            (Token::EE, _) => (true, true),
+            (Token::SS, _) => (false, false),
+            (Token::ES, true) | (Token::SE, false) => (true, false),
+            (Token::SE, true) | (Token::ES, false) => (false, true),
+            _ => unreachable!("Not a DH token"),
+        };
+
+        let priv_key = if local_ephemeral {
+            &self.e.as_ref()?.0
+        } else {
+            &self.s.0
+        };
+        let pub_key = if remote_ephemeral {
+            self.re.as_ref()?
+        } else {
+            self.rs.as_ref()?
+        };
+        self.dh.dh(priv_key, pub_key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key(dh: NoiseDh, seed: u8) -> Vec<u8> {
+        // Small valid private keys for either suite
+        let mut priv_key = vec![0u8; 32];
+        priv_key[31] = seed;
+        assert!(dh.public_key(&priv_key).is_some());
+        priv_key
+    }
+
+    #[test]
+    fn test_xx_handshake() {
+        let dh = NoiseDh::P256;
+        let alice_priv = key(dh, 7);
+        let bob_priv = key(dh, 11);
+
+        let mut alice =
+            HandshakeState::new(dh, HandshakePattern::XX, true, b"demo", &alice_priv, None);
+        let mut bob =
+            HandshakeState::new(dh, HandshakePattern::XX, false, b"demo", &bob_priv, None);
+
+        let msg1 = alice.write_message(b"hello");
+        assert_eq!(bob.read_message(&msg1), Some(b"hello".to_vec()));
+        let msg2 = bob.write_message(b"");
+        assert_eq!(alice.read_message(&msg2), Some(vec![]));
+        let msg3 = alice.write_message(b"");
+        assert_eq!(bob.read_message(&msg3), Some(vec![]));
+
+        assert!(alice.is_finished() && bob.is_finished());
+        assert_eq!(alice.handshake_hash(), bob.handshake_hash());
+        assert_eq!(alice.remote_static(), dh.public_key(&bob_priv).as_deref());
+        assert_eq!(bob.remote_static(), dh.public_key(&alice_priv).as_deref());
+
+        let (mut alice_send, mut alice_recv) = alice.split();
+        let (mut bob_recv, mut bob_send) = bob.split();
+        let ciphertext = alice_send.encrypt_with_ad(&[], b"Bob -> 1 BTC -> Alice");
+        assert_eq!(
+            bob_recv.decrypt_with_ad(&[], &ciphertext),
+            Some(b"Bob -> 1 BTC -> Alice".to_vec())
+        );
+        let ciphertext = bob_send.encrypt_with_ad(&[], b"ack");
+        assert_eq!(
+            alice_recv.decrypt_with_ad(&[], &ciphertext),
+            Some(b"ack".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_xx_25519_vector() {
+        // Noise_XX_25519_ChaChaPoly_SHA256 with the inputs of the cacophony
+        // test vectors; the outputs were cross-checked against an independent
+        // implementation
+        let dh = NoiseDh::X25519;
+        let prologue = hex::decode("4a6f686e2047616c74").unwrap();
+        let init_static =
+            hex::decode("e61ef9919cde45dd5f82166404bd08e38bceb5dfdfded0a34c8df7ed542214d1")
+                .unwrap();
+        let init_ephemeral =
+            hex::decode("893e28b9dc6ca8d611ab664754b8ceb7bac5117349a4439a6b0569da977c464a")
+                .unwrap();
+        let resp_static =
+            hex::decode("4a3acbfdb163dec651dfa3194dece676d437029c62a408b4c5ea9114246e4893")
+                .unwrap();
+        let resp_ephemeral =
+            hex::decode("bbdb4cdbd309f1a1f2e1456967fe288cadd6f712d65dc7b7793d5e63da6b375b")
+                .unwrap();
+
+        let mut init = HandshakeState::new(
+            dh,
+            HandshakePattern::XX,
+            true,
+            &prologue,
+            &init_static,
+            None,
+        );
+        let mut resp = HandshakeState::new(
+            dh,
+            HandshakePattern::XX,
+            false,
+            &prologue,
+            &resp_static,
+            None,
+        );
+        init.set_ephemeral(&init_ephemeral);
+        resp.set_ephemeral(&resp_ephemeral);
+
+        let payloads = [
+            "4c756477696720766f6e204d69736573",
+            "4d757272617920526f746862617264",
+            "462e20412e20486179656b",
+            "4361726c204d656e676572",
+            "4a65616e2d426170746973746520536179",
+            "457567656e2042f6686d20766f6e2042617765726b",
+        ]
+        .map(|payload| hex::decode(payload).unwrap());
+
+        let msg1 = init.write_message(&payloads[0]);
+        assert_eq!(
+            hex::encode(&msg1),
+            "ca35def5ae56cec33dc2036731ab14896bc4c75dbb07a61f879f8e3afa4c79444c756477696720766f6e204d69736573"
+        );
+        assert_eq!(resp.read_message(&msg1).as_ref(), Some(&payloads[0]));
+        let msg2 = resp.write_message(&payloads[1]);
+        assert_eq!(
+            hex::encode(&msg2),
+            "95ebc60d2b1fa672c1f46a8aa265ef51bfe38e7ccb39ec5be34069f14480884381cbad1f276e038c48378ffce2b65285e08d6b68aaa3629a5a8639392490e5b9bd5269c2f1e4f488ed8831161f19b7815528f8982ffe09be9b5c412f8a0db50f8814c7194e83f23dbd8d162c9326ad"
+        );
+        assert_eq!(init.read_message(&msg2).as_ref(), Some(&payloads[1]));
+        let msg3 = init.write_message(&payloads[2]);
+        assert_eq!(
+            hex::encode(&msg3),
+            "c7195ffacac1307ff99046f219750fc47693e23c3cb08b89c2af808b444850a80ae475b9df0f169ae80a89be0865b57f58c9fea0d4ec82a286427402f113e4b6ae769a1d95941d49b25030"
+        );
+        assert_eq!(resp.read_message(&msg3).as_ref(), Some(&payloads[2]));
+        assert_eq!(
+            hex::encode(init.handshake_hash()),
+            "c8e5f64e846193be2a834104c2a009868d6c9f3bd3c186299888b488b2f1f58e"
+        );
+        assert_eq!(init.handshake_hash(), resp.handshake_hash());
+
+        let (mut init_send, mut init_recv) = init.split();
+        let (mut resp_recv, mut resp_send) = resp.split();
+        let transport = [
+            "3744e25d623542b0576724d2c54efc70916e296af7ecd4fd05336c",
+            "9f722dd57ef7e065a07d2e406c12ad9e274c3bd41bef1f237b430aa839fc1431a4",
+            "f2d5b3016dbf0f4094776127ca2c0bb1b6345f1c8ed114d14394cc6da3291f22d0a37cdcc3",
+        ];
+        let ciphertext = init_send.encrypt_with_ad(&[], &payloads[3]);
+        assert_eq!(hex::encode(&ciphertext), transport[0]);
+        assert_eq!(
+            resp_recv.decrypt_with_ad(&[], &ciphertext).as_ref(),
+            Some(&payloads[3])
+        );
+        let ciphertext = resp_send.encrypt_with_ad(&[], &payloads[4]);
+        assert_eq!(hex::encode(&ciphertext), transport[1]);
+        assert_eq!(
+            init_recv.decrypt_with_ad(&[], &ciphertext).as_ref(),
+            Some(&payloads[4])
+        );
+        let ciphertext = init_send.encrypt_with_ad(&[], &payloads[5]);
+        assert_eq!(hex::encode(&ciphertext), transport[2]);
+    }
+
+    #[test]
+    fn test_ik_handshake() {
+        for dh in [NoiseDh::X25519, NoiseDh::P256] {
+            let alice_priv = key(dh, 7);
+            let bob_priv = key(dh, 11);
+            let bob_pub = dh.public_key(&bob_priv).unwrap();
+
+            let mut alice = HandshakeState::new(
+                dh,
+                HandshakePattern::IK,
+                true,
+                b"",
+                &alice_priv,
+                Some(&bob_pub),
+            );
+            let mut bob =
+                HandshakeState::new(dh, HandshakePattern::IK, false, b"", &bob_priv, None);
+
+            let msg1 = alice.write_message(b"early data");
+            assert_eq!(bob.read_message(&msg1), Some(b"early data".to_vec()));
+            let msg2 = bob.write_message(b"");
+            assert_eq!(alice.read_message(&msg2), Some(vec![]));
+
+            assert!(alice.is_finished() && bob.is_finished());
+            assert_eq!(alice.handshake_hash(), bob.handshake_hash());
+            assert_eq!(bob.remote_static(), dh.public_key(&alice_priv).as_deref());
+        }
+    }
+
+    #[test]
+    fn test_handshake_tampered() {
+        let dh = NoiseDh::P256;
+        let alice_priv = key(dh, 7);
+        let bob_priv = key(dh, 11);
+        let bob_pub = dh.public_key(&bob_priv).unwrap();
+
+        let mut alice = HandshakeState::new(
+            dh,
+            HandshakePattern::IK,
+            true,
+            b"demo",
+            &alice_priv,
+            Some(&bob_pub),
+        );
+        let mut bob =
+            HandshakeState::new(dh, HandshakePattern::IK, false, b"other", &bob_priv, None);
+
+        let msg1 = alice.write_message(b"early data");
+        assert_eq!(bob.read_message(&msg1), None);
+    }
+}
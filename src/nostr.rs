@@ -0,0 +1,196 @@
+// Nostr events (NIP-01) and key encodings (NIP-19), behind the `nostr`
+// feature. An event's id is the SHA-256 of the JSON array
+//
+//   [0, pubkey, created_at, kind, tags, content]
+//
+// written without whitespace, with non-ASCII text as UTF-8 rather than \u
+// escapes, and its "sig" is a BIP 340 signature of the id under the x-only
+// "pubkey", all three in lower case hex. Relays reject events whose id does
+// not match their content, so `nostr_verify` recomputes it before checking
+// the signature. Keys are shown to users in bech32, public keys as "npub"
+// and private keys as "nsec".
+
+use ec_generic::Point;
+use num_bigint::BigUint;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::address::{bech32, bech32_decode};
+use crate::encoding::to_fixed_bytes;
+use crate::hash::sha256;
+use crate::ECDSA;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NostrEvent {
+    pub id: String,
+    pub pubkey: String,
+    // Unix time in seconds
+    pub created_at: u64,
+    pub kind: u32,
+    pub tags: Vec<Vec<String>>,
+    pub content: String,
+    pub sig: String,
+}
+
+impl NostrEvent {
+    pub fn compute_id(&self) -> String {
+        // Hex SHA-256 of the serialization, whatever "id" holds now
+        let serialized = serde_json::json!([
+            0,
+            self.pubkey,
+            self.created_at,
+            self.kind,
+            self.tags,
+            self.content
+        ]);
+        hex::encode(sha256(serialized.to_string().as_bytes()))
+    }
+}
+
+impl ECDSA {
+    pub fn nostr_sign(
+        &self,
+        priv_key: &BigUint,
+        created_at: u64,
+        kind: u32,
+        tags: Vec<Vec<String>>,
+        content: &str,
+    ) -> Option<NostrEvent> {
+        // None off secp256k1
+        let pubkey = self.x_only(&self.generate_pub_key(priv_key))?;
+        let mut event = NostrEvent {
+            id: String::new(),
+            pubkey: hex::encode(pubkey),
+            created_at,
+            kind,
+            tags,
+            content: content.into(),
+            sig: String::new(),
+        };
+        event.id = event.compute_id();
+        let mut aux_rand = [0; 32];
+        crate::rng().fill_bytes(&mut aux_rand);
+        let sig = self.bip340_sign(&hex::decode(&event.id).ok()?, priv_key, &aux_rand)?;
+        event.sig = hex::encode(sig);
+        Some(event)
+    }
+
+    pub fn nostr_verify(&self, event: &NostrEvent) -> bool {
+        // The id, in lower case as relays expect, and the signature over it
+        let decode = |text: &str| {
+            let lower = text.bytes().all(|c| !c.is_ascii_uppercase());
+            hex::decode(text).ok().filter(|_| lower)
+        };
+        match (decode(&event.id), decode(&event.pubkey), decode(&event.sig)) {
+            (Some(id), Some(pubkey), Some(sig)) => {
+                event.id == event.compute_id() && self.bip340_verify(&id, &pubkey, &sig)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn npub(&self, pub_key: &Point) -> Option<String> {
+        Some(bech32("npub", &self.x_only(pub_key)?))
+    }
+
+    pub fn pub_key_from_npub(&self, npub: &str) -> Option<Point> {
+        // The even-y point of the x-only key
+        match bech32_decode(npub)? {
+            (hrp, pub_x) if hrp == "npub" && pub_x.len() == 32 => self.lift_x(&pub_x),
+            _ => None,
+        }
+    }
+}
+
+pub fn nsec(priv_key: &BigUint) -> String {
+    bech32("nsec", &to_fixed_bytes(priv_key, 32))
+}
+
+pub fn priv_key_from_nsec(nsec: &str) -> Option<BigUint> {
+    match bech32_decode(nsec)? {
+        (hrp, priv_key) if hrp == "nsec" && priv_key.len() == 32 => {
+            Some(BigUint::from_bytes_be(&priv_key))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::{p256, secp256k1};
+
+    // The keys of NIP-19's examples
+    const NPUB: &str = "npub10elfcs4fr0l0r8af98jlmgdh9c8tcxjvz9qkw038js35mp4dma8qzvjptg";
+    const NSEC: &str = "nsec1vl029mgpspedva04g90vltkh6fvh240zqtv9k0t9af8935ke9laqsnlfe5";
+    // Signed by the NSEC key with a Python implementation of NIP-01
+    const EVENT: &str = include_str!("../testdata/nostr_event.json");
+
+    #[test]
+    fn test_nip19_keys() {
+        let ecdsa = secp256k1();
+        let priv_key = priv_key_from_nsec(NSEC).unwrap();
+        assert_eq!(
+            hex::encode(to_fixed_bytes(&priv_key, 32)),
+            "67dea2ed018072d675f5415ecfaed7d2597555e202d85b3d65ea4e58d2d92ffa"
+        );
+        assert_eq!(nsec(&priv_key), NSEC);
+
+        let pub_key = ecdsa.pub_key_from_npub(NPUB).unwrap();
+        assert_eq!(
+            hex::encode(ecdsa.x_only(&pub_key).unwrap()),
+            "7e7e9c42a91bfef19fa929e5fda1b72e0ebc1a4c1141673e2794234d86addf4e"
+        );
+        assert_eq!(ecdsa.npub(&pub_key).unwrap(), NPUB);
+        let derived = ecdsa.generate_pub_key(&priv_key);
+        assert_eq!(ecdsa.npub(&derived).unwrap(), NPUB);
+
+        // Each prefix only for its own kind of key
+        assert!(ecdsa.pub_key_from_npub(NSEC).is_none());
+        assert!(priv_key_from_nsec(NPUB).is_none());
+    }
+
+    #[test]
+    fn test_verify_event() {
+        let ecdsa = secp256k1();
+        let event: NostrEvent = serde_json::from_str(EVENT).unwrap();
+        assert_eq!(event.compute_id(), event.id);
+        assert!(ecdsa.nostr_verify(&event));
+
+        // Content, tags and id all count, and so does the case of the hex
+        let mut edited = event.clone();
+        edited.content.push('!');
+        assert!(!ecdsa.nostr_verify(&edited));
+        let mut retagged = event.clone();
+        retagged.tags.pop();
+        assert!(!ecdsa.nostr_verify(&retagged));
+        let mut upper = event.clone();
+        upper.sig = upper.sig.to_uppercase();
+        assert!(!ecdsa.nostr_verify(&upper));
+        let mut reid = event;
+        reid.id = reid.compute_id();
+        reid.sig.replace_range(..2, "00");
+        assert!(!ecdsa.nostr_verify(&reid));
+    }
+
+    #[test]
+    fn test_sign_event() {
+        let ecdsa = secp256k1();
+        let priv_key = priv_key_from_nsec(NSEC).unwrap();
+        let tags = vec![vec!["t".into(), "rust".into()]];
+        let event = ecdsa
+            .nostr_sign(&priv_key, 1_700_000_000, 1, tags, "gm")
+            .unwrap();
+        assert!(ecdsa.nostr_verify(&event));
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(serde_json::from_str::<NostrEvent>(&json).unwrap(), event);
+        assert_eq!(
+            ecdsa.pub_key_from_npub(NPUB).map(|key| ecdsa.x_only(&key)),
+            Some(hex::decode(&event.pubkey).ok())
+        );
+        assert!(p256()
+            .nostr_sign(&priv_key, 0, 1, Vec::new(), "gm")
+            .is_none());
+    }
+}
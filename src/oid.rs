@@ -0,0 +1,267 @@
+// Object identifiers for X.509 and CMS code built on this crate, so it can
+// name curves, hashes, signature algorithms and CMS content types and
+// attributes without spelling out byte strings. Constants are the DER
+// contents of each OID, without tag and length, the form `der::OID` wraps and
+// `der::read_tag` returns. The tables map them to `NamedCurve` and
+// `HashAlgorithm` and to their registered names.
+//
+// Parameters follow the RFCs: ecPublicKey carries the curve OID (RFC 5480),
+// ecdsa-with-SHA2 identifiers carry none (RFC 5758), and hash identifiers are
+// written with an explicit NULL, which readers must also accept absent (RFC
+// 5754).
+
+use alloc::vec::Vec;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+
+use crate::curves::{self, NamedCurve};
+use crate::der;
+
+// 1.2.840.10045.2.1
+pub const EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+pub const PRIME256V1: &[u8] = curves::P256_OID;
+pub const SECP384R1: &[u8] = curves::P384_OID;
+pub const SECP256K1: &[u8] = curves::SECP256K1_OID;
+// 2.16.840.1.101.3.4.2.{1,2,3}
+pub const SHA256: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+pub const SHA384: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02];
+pub const SHA512: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03];
+// 1.2.840.10045.4.3.{2,3,4}
+pub const ECDSA_WITH_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+pub const ECDSA_WITH_SHA384: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x03];
+pub const ECDSA_WITH_SHA512: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x04];
+// CMS content types, 1.2.840.113549.1.7.{1,2}
+pub const DATA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x01];
+pub const SIGNED_DATA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02];
+// CMS attributes, 1.2.840.113549.1.9.{3,4,5} and 1.2.840.113549.1.9.16.2.47
+pub const CONTENT_TYPE: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x03];
+pub const MESSAGE_DIGEST: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x04];
+pub const SIGNING_TIME: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x05];
+pub const SIGNING_CERTIFICATE_V2: &[u8] = &[
+    0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x10, 0x02, 0x2f,
+];
+
+const NULL: u8 = 0x05;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+// (hash, hash OID, ecdsa-with-hash OID, name)
+const HASHES: [(HashAlgorithm, &[u8], &[u8], &str); 3] = [
+    (HashAlgorithm::Sha256, SHA256, ECDSA_WITH_SHA256, "sha256"),
+    (HashAlgorithm::Sha384, SHA384, ECDSA_WITH_SHA384, "sha384"),
+    (HashAlgorithm::Sha512, SHA512, ECDSA_WITH_SHA512, "sha512"),
+];
+
+const NAMES: [(&[u8], &str); 16] = [
+    (EC_PUBLIC_KEY, "ecPublicKey"),
+    (PRIME256V1, "prime256v1"),
+    (SECP384R1, "secp384r1"),
+    (SECP256K1, "secp256k1"),
+    (SHA256, "sha256"),
+    (SHA384, "sha384"),
+    (SHA512, "sha512"),
+    (ECDSA_WITH_SHA256, "ecdsa-with-SHA256"),
+    (ECDSA_WITH_SHA384, "ecdsa-with-SHA384"),
+    (ECDSA_WITH_SHA512, "ecdsa-with-SHA512"),
+    (DATA, "data"),
+    (SIGNED_DATA, "signedData"),
+    (CONTENT_TYPE, "contentType"),
+    (MESSAGE_DIGEST, "messageDigest"),
+    (SIGNING_TIME, "signingTime"),
+    (SIGNING_CERTIFICATE_V2, "signingCertificateV2"),
+];
+
+impl HashAlgorithm {
+    pub fn oid(self) -> &'static [u8] {
+        self.entry().1
+    }
+
+    pub fn ecdsa_oid(self) -> &'static [u8] {
+        // ecdsa-with-SHAxxx for signatures over this hash
+        self.entry().2
+    }
+
+    pub fn name(self) -> &'static str {
+        self.entry().3
+    }
+
+    pub fn from_oid(oid: &[u8]) -> Option<HashAlgorithm> {
+        HASHES
+            .iter()
+            .find(|entry| entry.1 == oid)
+            .map(|entry| entry.0)
+    }
+
+    pub fn from_ecdsa_oid(oid: &[u8]) -> Option<HashAlgorithm> {
+        HASHES
+            .iter()
+            .find(|entry| entry.2 == oid)
+            .map(|entry| entry.0)
+    }
+
+    pub fn output_len(self) -> usize {
+        match self {
+            HashAlgorithm::Sha256 => 32,
+            HashAlgorithm::Sha384 => 48,
+            HashAlgorithm::Sha512 => 64,
+        }
+    }
+
+    pub fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Sha256 => Sha256::digest(data).to_vec(),
+            HashAlgorithm::Sha384 => Sha384::digest(data).to_vec(),
+            HashAlgorithm::Sha512 => Sha512::digest(data).to_vec(),
+        }
+    }
+
+    fn entry(self) -> &'static (HashAlgorithm, &'static [u8], &'static [u8], &'static str) {
+        HASHES.iter().find(|entry| entry.0 == self).unwrap()
+    }
+}
+
+pub fn name(oid: &[u8]) -> Option<&'static str> {
+    // The registered name of any OID above
+    NAMES
+        .iter()
+        .find(|entry| entry.0 == oid)
+        .map(|entry| entry.1)
+}
+
+pub fn ec_public_key_algorithm(curve: NamedCurve) -> Vec<u8> {
+    // AlgorithmIdentifier { ecPublicKey, namedCurve }
+    algorithm_identifier(EC_PUBLIC_KEY, Some(&der::write(der::OID, curve.oid())))
+}
+
+pub fn hash_algorithm(hash: HashAlgorithm) -> Vec<u8> {
+    algorithm_identifier(hash.oid(), Some(&der::write(NULL, &[])))
+}
+
+pub fn signature_algorithm(hash: HashAlgorithm) -> Vec<u8> {
+    algorithm_identifier(hash.ecdsa_oid(), None)
+}
+
+pub fn algorithm_identifier(oid: &[u8], parameters: Option<&[u8]>) -> Vec<u8> {
+    // SEQUENCE { OID, parameters }, the parameters already DER encoded
+    let mut body = der::write(der::OID, oid);
+    body.extend(parameters.unwrap_or_default());
+    der::write(der::SEQUENCE, &body)
+}
+
+pub fn read_algorithm_identifier(bytes: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
+    // OID, DER of the parameters (empty when absent), remaining input
+    let (body, rest) = der::read_tag(bytes, der::SEQUENCE)?;
+    let (oid, parameters) = der::read_tag(body, der::OID)?;
+    Some((oid, parameters, rest))
+}
+
+pub fn read_hash_algorithm(bytes: &[u8]) -> Option<(HashAlgorithm, &[u8])> {
+    // NULL or absent parameters
+    let (oid, parameters, rest) = read_algorithm_identifier(bytes)?;
+    if !parameters.is_empty() && parameters != [NULL, 0x00] {
+        return None;
+    }
+    Some((HashAlgorithm::from_oid(oid)?, rest))
+}
+
+pub fn read_signature_algorithm(bytes: &[u8]) -> Option<(HashAlgorithm, &[u8])> {
+    // ecdsa-with-SHAxxx, no parameters
+    match read_algorithm_identifier(bytes)? {
+        (oid, [], rest) => Some((HashAlgorithm::from_ecdsa_oid(oid)?, rest)),
+        _ => None,
+    }
+}
+
+pub fn read_ec_public_key_algorithm(bytes: &[u8]) -> Option<(NamedCurve, &[u8])> {
+    let (oid, parameters, rest) = read_algorithm_identifier(bytes)?;
+    let (curve, trailing) = der::read_tag(parameters, der::OID)?;
+    if oid != EC_PUBLIC_KEY || !trailing.is_empty() {
+        return None;
+    }
+    Some((NamedCurve::from_oid(curve)?, rest))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_oid_registry() {
+        let dotted = [
+            (EC_PUBLIC_KEY, "1.2.840.10045.2.1"),
+            (PRIME256V1, "1.2.840.10045.3.1.7"),
+            (SECP384R1, "1.3.132.0.34"),
+            (SECP256K1, "1.3.132.0.10"),
+            (SHA256, "2.16.840.1.101.3.4.2.1"),
+            (SHA384, "2.16.840.1.101.3.4.2.2"),
+            (SHA512, "2.16.840.1.101.3.4.2.3"),
+            (ECDSA_WITH_SHA256, "1.2.840.10045.4.3.2"),
+            (ECDSA_WITH_SHA384, "1.2.840.10045.4.3.3"),
+            (ECDSA_WITH_SHA512, "1.2.840.10045.4.3.4"),
+            (DATA, "1.2.840.113549.1.7.1"),
+            (SIGNED_DATA, "1.2.840.113549.1.7.2"),
+            (CONTENT_TYPE, "1.2.840.113549.1.9.3"),
+            (MESSAGE_DIGEST, "1.2.840.113549.1.9.4"),
+            (SIGNING_TIME, "1.2.840.113549.1.9.5"),
+            (SIGNING_CERTIFICATE_V2, "1.2.840.113549.1.9.16.2.47"),
+        ];
+        for (oid, expected) in dotted {
+            assert_eq!(der::oid_to_string(oid).unwrap(), expected);
+            assert!(name(oid).is_some());
+        }
+        assert_eq!(name(ECDSA_WITH_SHA384), Some("ecdsa-with-SHA384"));
+        assert_eq!(name(&[0x2a, 0x03]), None);
+
+        for hash in [
+            HashAlgorithm::Sha256,
+            HashAlgorithm::Sha384,
+            HashAlgorithm::Sha512,
+        ] {
+            assert_eq!(HashAlgorithm::from_oid(hash.oid()), Some(hash));
+            assert_eq!(HashAlgorithm::from_ecdsa_oid(hash.ecdsa_oid()), Some(hash));
+            assert_eq!(hash.digest(b"abc").len(), hash.output_len());
+        }
+        assert_eq!(HashAlgorithm::from_oid(ECDSA_WITH_SHA256), None);
+    }
+
+    #[test]
+    fn test_algorithm_identifiers() {
+        // The encodings X.509 certificates carry
+        assert_eq!(
+            hex::encode(signature_algorithm(HashAlgorithm::Sha256)),
+            "300a06082a8648ce3d040302"
+        );
+        assert_eq!(
+            hex::encode(hash_algorithm(HashAlgorithm::Sha256)),
+            "300d06096086480165030402010500"
+        );
+        let spki = ec_public_key_algorithm(NamedCurve::P256);
+        assert_eq!(
+            hex::encode(&spki),
+            "301306072a8648ce3d020106082a8648ce3d030107"
+        );
+
+        assert_eq!(
+            read_ec_public_key_algorithm(&spki),
+            Some((NamedCurve::P256, &[][..]))
+        );
+        let sha384 = signature_algorithm(HashAlgorithm::Sha384);
+        assert_eq!(
+            read_signature_algorithm(&sha384).map(|(hash, _)| hash),
+            Some(HashAlgorithm::Sha384)
+        );
+        assert_eq!(read_hash_algorithm(&sha384), None);
+        // Hash parameters may be left out
+        let absent = algorithm_identifier(SHA512, None);
+        assert_eq!(
+            read_hash_algorithm(&absent).map(|(hash, _)| hash),
+            Some(HashAlgorithm::Sha512)
+        );
+        let with_null = algorithm_identifier(ECDSA_WITH_SHA256, Some(&[NULL, 0x00]));
+        assert_eq!(read_signature_algorithm(&with_null), None);
+    }
+}
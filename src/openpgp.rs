@@ -0,0 +1,809 @@
+// OpenPGP v4 keys and signatures (RFC 4880, with ECDSA from RFC 6637 and
+// EdDSA from RFC 9580's EdDSALegacy), enough to make detached signatures of
+// files and git commits that GnuPG checks, and to read the keys and
+// signatures GnuPG writes.
+//
+// A key packet is the version, creation time, algorithm, the curve OID and
+// the point as an MPI, a 2-byte bit count followed by the big endian value:
+// SEC1 uncompressed for ECDSA, 0x40 || key for Ed25519. Its fingerprint is
+// SHA-1(0x99 || 2-byte length || key packet body), and the key id the last 8
+// bytes of that. A signature packet hashes the signed data followed by
+//
+//   4 || type || algorithm || hash || hashed subpackets || 0x04 0xff || len
+//
+// and carries the first 2 bytes of that hash and r and s as MPIs. Subpackets
+// hold the creation time, the issuer's fingerprint and the like; only the
+// hashed ones are covered by the signature. A certification, as a key's
+// self-signature binding its user id, hashes the key and user id instead of
+// data. Ed25519 is not a curve this crate computes on, so EdDSA keys and
+// signatures are read and fingerprinted but not made or checked.
+//
+// Packets are written in the new format, and read in either. Text
+// signatures hash the data with CRLF line endings; binary signatures, which
+// git asks for, hash it as it is.
+
+use alloc::{format, string::String, vec, vec::Vec};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::curves::NamedCurve;
+use crate::hash::sha1;
+use crate::oid::HashAlgorithm;
+use crate::signer::Signature;
+use crate::ECDSA;
+
+// Packet tags
+pub const SIGNATURE_PACKET: u8 = 2;
+pub const PUBLIC_KEY_PACKET: u8 = 6;
+pub const USER_ID_PACKET: u8 = 13;
+
+// Signature types
+pub const BINARY_SIGNATURE: u8 = 0x00;
+pub const TEXT_SIGNATURE: u8 = 0x01;
+pub const POSITIVE_CERTIFICATION: u8 = 0x13;
+
+// Subpacket types
+pub const SIGNATURE_CREATION_TIME: u8 = 2;
+pub const ISSUER: u8 = 16;
+pub const KEY_FLAGS: u8 = 27;
+pub const ISSUER_FINGERPRINT: u8 = 33;
+
+const ECDSA_ALGORITHM: u8 = 19;
+const EDDSA_ALGORITHM: u8 = 22;
+const SHA256_ALGORITHM: u8 = 8;
+const SHA384_ALGORITHM: u8 = 9;
+const SHA512_ALGORITHM: u8 = 10;
+// 1.3.6.1.4.1.11591.15.1
+const ED25519_OID: &[u8] = &[0x2b, 0x06, 0x01, 0x04, 0x01, 0xda, 0x47, 0x0f, 0x01];
+// Certify other keys, sign data
+const CERTIFY_AND_SIGN: u8 = 0x03;
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum OpenPgpKeyMaterial {
+    Ecdsa { curve: NamedCurve, pub_key: Point },
+    Ed25519([u8; 32]),
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct OpenPgpKey {
+    // Unix time in seconds, part of the fingerprint
+    pub created: u32,
+    pub material: OpenPgpKeyMaterial,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Subpacket {
+    pub kind: u8,
+    pub critical: bool,
+    pub data: Vec<u8>,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct OpenPgpSignature {
+    pub signature_type: u8,
+    pub algorithm: u8,
+    pub hash_algorithm: u8,
+    pub hashed: Vec<Subpacket>,
+    pub unhashed: Vec<Subpacket>,
+    pub hash_prefix: [u8; 2],
+    // r and s, big endian without leading zeros
+    pub r: Vec<u8>,
+    pub s: Vec<u8>,
+}
+
+impl OpenPgpKey {
+    pub fn body(&self) -> Vec<u8> {
+        let mut body = vec![4];
+        body.extend(self.created.to_be_bytes());
+        let (algorithm, oid, point) = match &self.material {
+            OpenPgpKeyMaterial::Ecdsa { curve, pub_key } => (
+                ECDSA_ALGORITHM,
+                curve.oid(),
+                curve.ecdsa().encode_point_uncompressed(pub_key),
+            ),
+            OpenPgpKeyMaterial::Ed25519(key) => {
+                (EDDSA_ALGORITHM, ED25519_OID, [&[0x40], &key[..]].concat())
+            }
+        };
+        body.push(algorithm);
+        body.push(oid.len() as u8);
+        body.extend(oid);
+        body.extend(mpi(&point));
+        body
+    }
+
+    pub fn from_body(body: &[u8]) -> Option<OpenPgpKey> {
+        // Version 4 ECDSA keys on the named curves and EdDSA keys on Ed25519
+        let (header, rest) = split(body, 6)?;
+        let (oid_len, rest) = rest.split_first()?;
+        let (oid, rest) = split(rest, usize::from(*oid_len))?;
+        let (point, rest) = read_mpi(rest)?;
+        if header[0] != 4 || !rest.is_empty() {
+            return None;
+        }
+        let created = u32::from_be_bytes(header[1..5].try_into().ok()?);
+        let material = match header[5] {
+            ECDSA_ALGORITHM => {
+                let curve = NamedCurve::from_oid(oid)?;
+                let ecdsa = curve.ecdsa();
+                // Uncompressed points only
+                let pub_key = ecdsa.decode_point(point)?;
+                if point.first() != Some(&0x04) || !ecdsa.is_valid_pub_key(&pub_key) {
+                    return None;
+                }
+                OpenPgpKeyMaterial::Ecdsa { curve, pub_key }
+            }
+            EDDSA_ALGORITHM if oid == ED25519_OID => {
+                OpenPgpKeyMaterial::Ed25519(point.strip_prefix(&[0x40])?.try_into().ok()?)
+            }
+            _ => return None,
+        };
+        Some(OpenPgpKey { created, material })
+    }
+
+    pub fn to_packet(&self) -> Vec<u8> {
+        write_packet(PUBLIC_KEY_PACKET, &self.body())
+    }
+
+    pub fn fingerprint(&self) -> Vec<u8> {
+        sha1(&self.hashed_form())
+    }
+
+    pub fn key_id(&self) -> Vec<u8> {
+        self.fingerprint()[12..].to_vec()
+    }
+
+    fn hashed_form(&self) -> Vec<u8> {
+        // 0x99 || 2-byte length || body, as fingerprints and certifications
+        // hash a key
+        let body = self.body();
+        let mut hashed = vec![0x99];
+        hashed.extend((body.len() as u16).to_be_bytes());
+        hashed.extend(body);
+        hashed
+    }
+}
+
+impl OpenPgpSignature {
+    pub fn from_body(body: &[u8]) -> Option<OpenPgpSignature> {
+        // Version 4 signatures with two MPIs, as ECDSA and EdDSA make
+        let (header, rest) = split(body, 4)?;
+        let (hashed, rest) = read_subpackets(rest)?;
+        let (unhashed, rest) = read_subpackets(rest)?;
+        let (hash_prefix, rest) = split(rest, 2)?;
+        let (r, rest) = read_mpi(rest)?;
+        let (s, rest) = read_mpi(rest)?;
+        if header[0] != 4 || !rest.is_empty() {
+            return None;
+        }
+        Some(OpenPgpSignature {
+            signature_type: header[1],
+            algorithm: header[2],
+            hash_algorithm: header[3],
+            hashed,
+            unhashed,
+            hash_prefix: hash_prefix.try_into().ok()?,
+            r: r.to_vec(),
+            s: s.to_vec(),
+        })
+    }
+
+    pub fn body(&self) -> Vec<u8> {
+        let mut body = self.hashed_part();
+        body.extend(write_subpackets(&self.unhashed));
+        body.extend(self.hash_prefix);
+        body.extend(mpi(&self.r));
+        body.extend(mpi(&self.s));
+        body
+    }
+
+    pub fn to_packet(&self) -> Vec<u8> {
+        write_packet(SIGNATURE_PACKET, &self.body())
+    }
+
+    pub fn creation_time(&self) -> Option<u32> {
+        let data = self.hashed_subpacket(SIGNATURE_CREATION_TIME)?;
+        Some(u32::from_be_bytes(data.try_into().ok()?))
+    }
+
+    pub fn issuer_fingerprint(&self) -> Option<&[u8]> {
+        // The v4 fingerprint, without the version byte in front
+        self.hashed_subpacket(ISSUER_FINGERPRINT)?
+            .strip_prefix(&[4])
+    }
+
+    fn hashed_subpacket(&self, kind: u8) -> Option<&[u8]> {
+        self.hashed
+            .iter()
+            .find(|subpacket| subpacket.kind == kind)
+            .map(|subpacket| &subpacket.data[..])
+    }
+
+    fn hashed_part(&self) -> Vec<u8> {
+        let mut hashed = vec![4, self.signature_type, self.algorithm, self.hash_algorithm];
+        hashed.extend(write_subpackets(&self.hashed));
+        hashed
+    }
+
+    fn digest(&self, signed: &[u8]) -> Option<Vec<u8>> {
+        let hash = match self.hash_algorithm {
+            SHA256_ALGORITHM => HashAlgorithm::Sha256,
+            SHA384_ALGORITHM => HashAlgorithm::Sha384,
+            SHA512_ALGORITHM => HashAlgorithm::Sha512,
+            _ => return None,
+        };
+        let hashed = self.hashed_part();
+        let mut data = signed.to_vec();
+        data.extend(&hashed);
+        data.extend([0x04, 0xff]);
+        data.extend((hashed.len() as u32).to_be_bytes());
+        Some(hash.digest(&data))
+    }
+}
+
+impl ECDSA {
+    pub fn openpgp_key(&self, pub_key: &Point, created: u32) -> Option<OpenPgpKey> {
+        // None off the named curves or for an invalid key
+        let curve = self.named_curve()?;
+        self.is_valid_pub_key(pub_key).then(|| OpenPgpKey {
+            created,
+            material: OpenPgpKeyMaterial::Ecdsa {
+                curve,
+                pub_key: pub_key.clone(),
+            },
+        })
+    }
+
+    pub fn openpgp_sign(
+        &self,
+        data: &[u8],
+        priv_key: &BigUint,
+        key: &OpenPgpKey,
+        signature_type: u8,
+        signed_at: u32,
+    ) -> Option<OpenPgpSignature> {
+        // A binary or text signature of the data; None unless `key` is the
+        // public key of `priv_key` on this curve
+        if signature_type != BINARY_SIGNATURE && signature_type != TEXT_SIGNATURE {
+            return None;
+        }
+        let signed = signed_data(signature_type, data);
+        self.openpgp_signature(
+            &signed,
+            priv_key,
+            key,
+            signature_type,
+            signed_at,
+            Vec::new(),
+        )
+    }
+
+    pub fn openpgp_verify(
+        &self,
+        data: &[u8],
+        signature: &OpenPgpSignature,
+        key: &OpenPgpKey,
+    ) -> bool {
+        // Whether `key` made this binary or text signature of the data
+        let signature_type = signature.signature_type;
+        (signature_type == BINARY_SIGNATURE || signature_type == TEXT_SIGNATURE)
+            && self.openpgp_check(&signed_data(signature_type, data), signature, key)
+    }
+
+    pub fn openpgp_certify(
+        &self,
+        key: &OpenPgpKey,
+        user_id: &str,
+        priv_key: &BigUint,
+        signed_at: u32,
+    ) -> Option<OpenPgpSignature> {
+        // The self-signature binding a user id to the key, with flags for
+        // certifying and signing, as GnuPG needs to import a key
+        let flags = Subpacket {
+            kind: KEY_FLAGS,
+            critical: false,
+            data: vec![CERTIFY_AND_SIGN],
+        };
+        let signed = certified_data(key, user_id);
+        self.openpgp_signature(
+            &signed,
+            priv_key,
+            key,
+            POSITIVE_CERTIFICATION,
+            signed_at,
+            vec![flags],
+        )
+    }
+
+    pub fn openpgp_verify_certification(
+        &self,
+        key: &OpenPgpKey,
+        user_id: &str,
+        signature: &OpenPgpSignature,
+    ) -> bool {
+        // Any of the four certification types
+        (0x10..=POSITIVE_CERTIFICATION).contains(&signature.signature_type)
+            && self.openpgp_check(&certified_data(key, user_id), signature, key)
+    }
+
+    pub fn openpgp_certificate(
+        &self,
+        priv_key: &BigUint,
+        created: u32,
+        user_id: &str,
+    ) -> Option<Vec<u8>> {
+        // The key, user id and self-signature packets that `gpg --import`
+        // takes, certified at the key's creation time
+        let key = self.openpgp_key(&self.generate_pub_key(priv_key), created)?;
+        let certification = self.openpgp_certify(&key, user_id, priv_key, created)?;
+        let mut packets = key.to_packet();
+        packets.extend(write_packet(USER_ID_PACKET, user_id.as_bytes()));
+        packets.extend(certification.to_packet());
+        Some(packets)
+    }
+
+    fn openpgp_signature(
+        &self,
+        signed: &[u8],
+        priv_key: &BigUint,
+        key: &OpenPgpKey,
+        signature_type: u8,
+        signed_at: u32,
+        extra: Vec<Subpacket>,
+    ) -> Option<OpenPgpSignature> {
+        let pub_key = self.generate_pub_key(priv_key);
+        if self.openpgp_key(&pub_key, key.created).as_ref() != Some(key) {
+            return None;
+        }
+        let hash_algorithm = if self.is_p384() {
+            SHA384_ALGORITHM
+        } else {
+            SHA256_ALGORITHM
+        };
+        let mut hashed = vec![
+            Subpacket {
+                kind: ISSUER_FINGERPRINT,
+                critical: false,
+                data: [&[4], &key.fingerprint()[..]].concat(),
+            },
+            Subpacket {
+                kind: SIGNATURE_CREATION_TIME,
+                critical: false,
+                data: signed_at.to_be_bytes().to_vec(),
+            },
+        ];
+        hashed.extend(extra);
+        let mut signature = OpenPgpSignature {
+            signature_type,
+            algorithm: ECDSA_ALGORITHM,
+            hash_algorithm,
+            hashed,
+            unhashed: vec![Subpacket {
+                kind: ISSUER,
+                critical: false,
+                data: key.key_id(),
+            }],
+            hash_prefix: [0; 2],
+            r: Vec::new(),
+            s: Vec::new(),
+        };
+        let digest = signature.digest(signed)?;
+        let signed = self.sign_standard(&mut crate::rng(), &digest, priv_key);
+        signature.hash_prefix = [digest[0], digest[1]];
+        signature.r = signed.r().to_bytes_be();
+        signature.s = signed.s().to_bytes_be();
+        Some(signature)
+    }
+
+    fn openpgp_check(&self, signed: &[u8], signature: &OpenPgpSignature, key: &OpenPgpKey) -> bool {
+        // An ECDSA signature by a key on this curve, naming that key if it
+        // names any, with no critical hashed subpacket left unread
+        let OpenPgpKeyMaterial::Ecdsa { curve, pub_key } = &key.material else {
+            return false;
+        };
+        let known = [
+            SIGNATURE_CREATION_TIME,
+            ISSUER,
+            KEY_FLAGS,
+            ISSUER_FINGERPRINT,
+        ];
+        if self.named_curve() != Some(*curve)
+            || signature.algorithm != ECDSA_ALGORITHM
+            || signature
+                .hashed
+                .iter()
+                .any(|subpacket| subpacket.critical && !known.contains(&subpacket.kind))
+            || signature
+                .issuer_fingerprint()
+                .is_some_and(|fingerprint| fingerprint != key.fingerprint())
+        {
+            return false;
+        }
+        let Some(digest) = signature.digest(signed) else {
+            return false;
+        };
+        let rs = Signature::new(
+            BigUint::from_bytes_be(&signature.r),
+            BigUint::from_bytes_be(&signature.s),
+        );
+        digest[..2] == signature.hash_prefix && self.verify_standard(&digest, pub_key, &rs)
+    }
+}
+
+pub fn write_packet(tag: u8, body: &[u8]) -> Vec<u8> {
+    // New format header: 0xc0 | tag, then the length
+    let mut packet = vec![0xc0 | tag];
+    packet.extend(write_length(body.len()));
+    packet.extend(body);
+    packet
+}
+
+pub fn read_packets(mut bytes: &[u8]) -> Option<Vec<(u8, &[u8])>> {
+    // Tag and body of each packet, in either format; partial and
+    // indeterminate lengths are not supported
+    let mut packets = Vec::new();
+    while let Some((&ctb, rest)) = bytes.split_first() {
+        if ctb & 0x80 == 0 {
+            return None;
+        }
+        let (tag, len, rest) = if ctb & 0x40 != 0 {
+            let (len, rest) = read_length(rest)?;
+            (ctb & 0x3f, len, rest)
+        } else {
+            let size = match ctb & 0x03 {
+                0 => 1,
+                1 => 2,
+                2 => 4,
+                _ => return None,
+            };
+            let (len, rest) = split(rest, size)?;
+            let len = len.iter().fold(0, |n, byte| (n << 8) | usize::from(*byte));
+            ((ctb >> 2) & 0x0f, len, rest)
+        };
+        let (body, rest) = split(rest, len)?;
+        packets.push((tag, body));
+        bytes = rest;
+    }
+    Some(packets)
+}
+
+pub fn openpgp_armor(kind: &str, data: &[u8]) -> String {
+    // kind is "SIGNATURE" or "PUBLIC KEY BLOCK"; the CRC-24 checksum follows
+    // the base64 lines
+    let encoded = STANDARD.encode(data);
+    let mut armored = format!("-----BEGIN PGP {}-----\n\n", kind);
+    for line in encoded.as_bytes().chunks(64) {
+        armored.push_str(core::str::from_utf8(line).unwrap());
+        armored.push('\n');
+    }
+    let crc = crc24(data).to_be_bytes();
+    armored.push_str(&format!("={}\n", STANDARD.encode(&crc[1..])));
+    armored.push_str(&format!("-----END PGP {}-----\n", kind));
+    armored
+}
+
+pub fn openpgp_dearmor(text: &str) -> Option<(String, Vec<u8>)> {
+    // The kind and contents of the first armored block, its headers skipped
+    // and its checksum, when there is one, checked
+    let mut lines = text.lines().map(str::trim_end);
+    let kind =
+        lines.find_map(|line| line.strip_prefix("-----BEGIN PGP ")?.strip_suffix("-----"))?;
+    let end = format!("-----END PGP {}-----", kind);
+    lines.by_ref().take_while(|line| !line.is_empty()).count();
+    let mut encoded = String::new();
+    let mut checksum = None;
+    for line in lines {
+        if line == end {
+            let data = STANDARD.decode(&encoded).ok()?;
+            if let Some(checksum) = checksum {
+                let crc = crc24(&data).to_be_bytes();
+                if STANDARD.decode(checksum).ok()? != crc[1..] {
+                    return None;
+                }
+            }
+            return Some((kind.into(), data));
+        }
+        match line.strip_prefix('=') {
+            Some(crc) => checksum = Some(crc),
+            None => encoded.push_str(line),
+        }
+    }
+    None
+}
+
+fn signed_data(signature_type: u8, data: &[u8]) -> Vec<u8> {
+    // Text is hashed with CRLF line endings
+    if signature_type != TEXT_SIGNATURE {
+        return data.to_vec();
+    }
+    let mut canonical = Vec::with_capacity(data.len());
+    for (i, byte) in data.iter().enumerate() {
+        if *byte == b'\n' && (i == 0 || data[i - 1] != b'\r') {
+            canonical.push(b'\r');
+        }
+        canonical.push(*byte);
+    }
+    canonical
+}
+
+fn certified_data(key: &OpenPgpKey, user_id: &str) -> Vec<u8> {
+    // key || 0xb4 || 4-byte length || user id
+    let mut data = key.hashed_form();
+    data.push(0xb4);
+    data.extend((user_id.len() as u32).to_be_bytes());
+    data.extend(user_id.as_bytes());
+    data
+}
+
+fn mpi(value: &[u8]) -> Vec<u8> {
+    // Bit count and the value without leading zero bytes
+    let start = value
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(value.len());
+    let value = &value[start..];
+    let bits = value.first().map_or(0, |top| {
+        8 * (value.len() - 1) + (8 - top.leading_zeros() as usize)
+    });
+    let mut encoded = (bits as u16).to_be_bytes().to_vec();
+    encoded.extend(value);
+    encoded
+}
+
+fn read_mpi(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    // The bit count has to match the value's top byte
+    let (bits, rest) = split(bytes, 2)?;
+    let bits = usize::from(u16::from_be_bytes([bits[0], bits[1]]));
+    let (value, rest) = split(rest, bits.div_ceil(8))?;
+    match value.first() {
+        Some(top) if 8 - top.leading_zeros() as usize != (bits - 1) % 8 + 1 => None,
+        _ => Some((value, rest)),
+    }
+}
+
+fn write_length(len: usize) -> Vec<u8> {
+    // One, two or five bytes, as packets and subpackets share
+    match len {
+        0..=191 => vec![len as u8],
+        192..=8383 => {
+            let len = len - 192;
+            vec![(len >> 8) as u8 + 192, len as u8]
+        }
+        _ => [&[0xff], &(len as u32).to_be_bytes()[..]].concat(),
+    }
+}
+
+fn read_length(bytes: &[u8]) -> Option<(usize, &[u8])> {
+    let (&first, rest) = bytes.split_first()?;
+    match first {
+        0..=191 => Some((usize::from(first), rest)),
+        192..=223 => {
+            let (&second, rest) = rest.split_first()?;
+            Some((
+                (usize::from(first - 192) << 8) + usize::from(second) + 192,
+                rest,
+            ))
+        }
+        255 => {
+            let (len, rest) = split(rest, 4)?;
+            Some((u32::from_be_bytes(len.try_into().ok()?) as usize, rest))
+        }
+        _ => None,
+    }
+}
+
+fn write_subpackets(subpackets: &[Subpacket]) -> Vec<u8> {
+    // 2-byte length of the area, then length, type and data of each
+    let mut area = Vec::new();
+    for subpacket in subpackets {
+        area.extend(write_length(subpacket.data.len() + 1));
+        area.push(subpacket.kind | if subpacket.critical { 0x80 } else { 0 });
+        area.extend(&subpacket.data);
+    }
+    let mut encoded = (area.len() as u16).to_be_bytes().to_vec();
+    encoded.extend(area);
+    encoded
+}
+
+fn read_subpackets(bytes: &[u8]) -> Option<(Vec<Subpacket>, &[u8])> {
+    let (len, rest) = split(bytes, 2)?;
+    let (mut area, rest) = split(rest, usize::from(u16::from_be_bytes([len[0], len[1]])))?;
+    let mut subpackets = Vec::new();
+    while !area.is_empty() {
+        let (len, after) = read_length(area)?;
+        let (subpacket, after) = split(after, len)?;
+        let (kind, data) = subpacket.split_first()?;
+        subpackets.push(Subpacket {
+            kind: kind & 0x7f,
+            critical: kind & 0x80 != 0,
+            data: data.to_vec(),
+        });
+        area = after;
+    }
+    Some((subpackets, rest))
+}
+
+fn crc24(data: &[u8]) -> u32 {
+    // RFC 4880, 6.1
+    let mut crc = 0xb704ceu32;
+    for byte in data {
+        crc ^= u32::from(*byte) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x1000000 != 0 {
+                crc ^= 0x1864cfb;
+            }
+        }
+    }
+    crc & 0xffffff
+}
+
+fn split(bytes: &[u8], len: usize) -> Option<(&[u8], &[u8])> {
+    (bytes.len() >= len).then(|| bytes.split_at(len))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::{p256, secp256k1};
+
+    // Made with GnuPG 2.2: a P-256 and an Ed25519 key created 2024-01-01,
+    // each with a detached binary signature of CONTENT made a day later
+    const P256_KEY: &str = include_str!("../testdata/openpgp_p256_key.asc");
+    const P256_SIGNATURE: &str = include_str!("../testdata/openpgp_p256_signature.asc");
+    const ED25519_KEY: &str = include_str!("../testdata/openpgp_ed25519_key.asc");
+    const ED25519_SIGNATURE: &str = include_str!("../testdata/openpgp_ed25519_signature.asc");
+    const CONTENT: &[u8] = include_bytes!("../testdata/openpgp_content.txt");
+
+    fn read_armored(armored: &str) -> Vec<(u8, Vec<u8>)> {
+        let (_, data) = openpgp_dearmor(armored).unwrap();
+        let packets = read_packets(&data).unwrap();
+        packets
+            .into_iter()
+            .map(|(tag, body)| (tag, body.to_vec()))
+            .collect()
+    }
+
+    fn signature(armored: &str) -> OpenPgpSignature {
+        match &read_armored(armored)[..] {
+            [(SIGNATURE_PACKET, body)] => OpenPgpSignature::from_body(body).unwrap(),
+            _ => panic!("not a single signature packet"),
+        }
+    }
+
+    #[test]
+    fn test_gnupg_keys() {
+        let packets = read_armored(P256_KEY);
+        assert_eq!(
+            packets.iter().map(|(tag, _)| *tag).collect::<Vec<_>>(),
+            [PUBLIC_KEY_PACKET, USER_ID_PACKET, SIGNATURE_PACKET]
+        );
+        let key = OpenPgpKey::from_body(&packets[0].1).unwrap();
+        assert_eq!(key.created, 1704067200);
+        assert_eq!(
+            hex::encode(key.fingerprint()),
+            "e4d6fd322d9611b56569d20b5d064100d264b110"
+        );
+        assert_eq!(hex::encode(key.key_id()), "5d064100d264b110");
+        assert_eq!(key.body(), packets[0].1);
+
+        // The self-signature, with GnuPG's preference subpackets
+        let user_id = core::str::from_utf8(&packets[1].1).unwrap();
+        assert_eq!(user_id, "RS ECC Test <test@example.com>");
+        let certification = OpenPgpSignature::from_body(&packets[2].1).unwrap();
+        assert_eq!(certification.body(), packets[2].1);
+        let ecdsa = p256();
+        assert!(ecdsa.openpgp_verify_certification(&key, user_id, &certification));
+        assert!(!ecdsa.openpgp_verify_certification(&key, "Mallory", &certification));
+
+        let packets = read_armored(ED25519_KEY);
+        let key = OpenPgpKey::from_body(&packets[0].1).unwrap();
+        assert!(matches!(key.material, OpenPgpKeyMaterial::Ed25519(_)));
+        assert_eq!(
+            hex::encode(key.fingerprint()),
+            "677e4de036608e5a8df0ae051f6abc4eb44b99a6"
+        );
+        assert_eq!(key.body(), packets[0].1);
+    }
+
+    #[test]
+    fn test_gnupg_signatures() {
+        let key = OpenPgpKey::from_body(&read_armored(P256_KEY)[0].1).unwrap();
+        let signed = signature(P256_SIGNATURE);
+        assert_eq!(signed.signature_type, BINARY_SIGNATURE);
+        assert_eq!(signed.creation_time(), Some(1704153600));
+        assert_eq!(signed.issuer_fingerprint(), Some(&key.fingerprint()[..]));
+        assert_eq!(signed.unhashed[0].kind, ISSUER);
+
+        let ecdsa = p256();
+        assert!(ecdsa.openpgp_verify(CONTENT, &signed, &key));
+        assert!(!ecdsa.openpgp_verify(b"Signed by someone else.\n", &signed, &key));
+        let mut retimed = signed.clone();
+        retimed.hashed[1].data[3] ^= 1;
+        assert!(!ecdsa.openpgp_verify(CONTENT, &retimed, &key));
+
+        // EdDSA signatures are read, but not checked
+        let ed25519_key = OpenPgpKey::from_body(&read_armored(ED25519_KEY)[0].1).unwrap();
+        let eddsa = signature(ED25519_SIGNATURE);
+        assert_eq!(eddsa.algorithm, EDDSA_ALGORITHM);
+        assert_eq!(
+            eddsa.issuer_fingerprint(),
+            Some(&ed25519_key.fingerprint()[..])
+        );
+        assert!(!ecdsa.openpgp_verify(CONTENT, &eddsa, &ed25519_key));
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let ecdsa = p256();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let created = 1_700_000_000;
+        let key = ecdsa
+            .openpgp_key(&ecdsa.generate_pub_key(&priv_key), created)
+            .unwrap();
+
+        // A text signature covers the text whatever its line endings
+        let signed = ecdsa
+            .openpgp_sign(
+                b"line\nline\n",
+                &priv_key,
+                &key,
+                TEXT_SIGNATURE,
+                created + 1,
+            )
+            .unwrap();
+        let armored = openpgp_armor("SIGNATURE", &signed.to_packet());
+        assert!(armored.starts_with("-----BEGIN PGP SIGNATURE-----\n\n"));
+        let read = signature(&armored);
+        assert_eq!(read, signed);
+        assert!(ecdsa.openpgp_verify(b"line\r\nline\r\n", &read, &key));
+        assert!(ecdsa.openpgp_verify(b"line\nline\n", &read, &key));
+        assert!(!ecdsa.openpgp_verify(b"line\nline", &read, &key));
+
+        // The certificate GnuPG imports, and no signing with another key
+        let certificate = ecdsa
+            .openpgp_certificate(&priv_key, created, "Test <test@example.com>")
+            .unwrap();
+        let packets = read_packets(&certificate).unwrap();
+        assert_eq!(OpenPgpKey::from_body(packets[0].1), Some(key.clone()));
+        let certification = OpenPgpSignature::from_body(packets[2].1).unwrap();
+        assert!(ecdsa.openpgp_verify_certification(
+            &key,
+            "Test <test@example.com>",
+            &certification
+        ));
+        assert!(ecdsa
+            .openpgp_sign(b"data", &BigUint::from(7u32), &key, BINARY_SIGNATURE, 0)
+            .is_none());
+        assert!(!secp256k1().openpgp_verify(b"line\nline\n", &read, &key));
+    }
+
+    #[test]
+    fn test_encodings() {
+        assert_eq!(mpi(&[0x00, 0x01]), [0x00, 0x01, 0x01]);
+        assert_eq!(mpi(&[0x40, 0xff]), [0x00, 0x0f, 0x40, 0xff]);
+        assert_eq!(
+            read_mpi(&[0x00, 0x0f, 0x40, 0xff]),
+            Some((&[0x40, 0xff][..], &[][..]))
+        );
+        assert_eq!(read_mpi(&[0x00, 0x10, 0x40, 0xff]), None);
+
+        // Each length form, in both directions
+        for len in [0, 191, 192, 8383, 8384, 70000] {
+            let body = vec![0x5a; len];
+            let packet = write_packet(USER_ID_PACKET, &body);
+            assert_eq!(
+                read_packets(&packet),
+                Some(vec![(USER_ID_PACKET, &body[..])])
+            );
+        }
+        assert_eq!(write_length(8383), [0xdf, 0xff]);
+
+        // A damaged checksum
+        let armored = P256_SIGNATURE.replace("=7st7", "=7st8");
+        assert_eq!(openpgp_dearmor(&armored), None);
+    }
+}
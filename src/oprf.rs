@@ -0,0 +1,216 @@
+// Oblivious PRF following RFC 9497 in the base (OPRF) and verifiable (VOPRF)
+// modes. The client blinds its input as rH(x), the server multiplies by its key
+// and the client unblinds, so the server never sees x and the client never sees
+// the key. In verifiable mode the server also proves with a DLEQ proof that it
+// used the key behind its public key. Hashing to the group uses the crate's
+// try-and-increment map instead of the RFC 9380 suites, so outputs are not
+// interoperable with other RFC 9497 implementations. Inputs are at most
+// 65535 bytes, the largest length the output hash can encode; longer ones
+// give None.
+
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::dleq::DleqProof;
+use crate::hash::sha256;
+use crate::secret::{invert_secret, SecretScalar};
+use crate::ECDSA;
+
+const SUITE: &[u8] = b"RS_ECC-SHA256";
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum OprfMode {
+    Base,
+    Verifiable,
+}
+
+impl ECDSA {
+    pub fn oprf_blind(&self, mode: OprfMode, input: &[u8], r_random: &BigUint) -> Option<Point> {
+        // blinded = r * H(input), r is kept by the client for finalize
+        assert!(
+            r_random < &self.q_order && *r_random != BigUint::from(0u32),
+            "Blind must be in [1, q - 1]"
+        );
+        u16::try_from(input.len()).ok()?;
+        let r = SecretScalar::new(r_random.clone());
+        Some(self.mul_secret(&self.oprf_hash_to_group(mode, input), &r))
+    }
+
+    pub fn oprf_blind_evaluate(&self, priv_key: &BigUint, blinded: &Point) -> Option<Point> {
+        // evaluated = sk * blinded
+        if !self.is_valid_pub_key(blinded) {
+            return None;
+        }
+        Some(self.mul_secret(blinded, &SecretScalar::new(priv_key.clone())))
+    }
+
+    pub fn voprf_blind_evaluate(
+        &self,
+        priv_key: &BigUint,
+        blinded: &Point,
+        k_random: &BigUint,
+    ) -> Option<(Point, DleqProof)> {
+        // Proves log_A(pk) = log_blinded(evaluated)
+        let evaluated = self.oprf_blind_evaluate(priv_key, blinded)?;
+        let proof = self.dleq_prove(priv_key, &self.a_gen, blinded, k_random);
+        Some((evaluated, proof))
+    }
+
+    pub fn oprf_finalize(
+        &self,
+        mode: OprfMode,
+        input: &[u8],
+        r_random: &BigUint,
+        evaluated: &Point,
+    ) -> Option<Vec<u8>> {
+        // N = r⁻¹ * evaluated, output = H(len || input || len || N || "Finalize")
+        if !self.is_valid_pub_key(evaluated) {
+            return None;
+        }
+        let r_inv = invert_secret(r_random, &self.q_order)?;
+        let unblinded = self.mul_secret(evaluated, &SecretScalar::new(r_inv));
+        self.oprf_output(mode, input, &unblinded)
+    }
+
+    pub fn voprf_finalize(
+        &self,
+        pub_key: &Point,
+        input: &[u8],
+        r_random: &BigUint,
+        blinded: &Point,
+        evaluated: &Point,
+        proof: &DleqProof,
+    ) -> Option<Vec<u8>> {
+        if !self.dleq_verify(&self.a_gen, pub_key, blinded, evaluated, proof) {
+            return None;
+        }
+        self.oprf_finalize(OprfMode::Verifiable, input, r_random, evaluated)
+    }
+
+    pub fn oprf_evaluate(
+        &self,
+        mode: OprfMode,
+        priv_key: &BigUint,
+        input: &[u8],
+    ) -> Option<Vec<u8>> {
+        // What the server computes on its own, without the blinding round trip
+        let d = SecretScalar::new(priv_key.clone());
+        let point = self.mul_secret(&self.oprf_hash_to_group(mode, input), &d);
+        self.oprf_output(mode, input, &point)
+    }
+
+    fn oprf_hash_to_group(&self, mode: OprfMode, input: &[u8]) -> Point {
+        let dst = [b"HashToGroup-".as_slice(), &oprf_context(mode)].concat();
+        self.hash_to_curve(&dst, input)
+    }
+
+    fn oprf_output(&self, mode: OprfMode, input: &[u8], point: &Point) -> Option<Vec<u8>> {
+        let element = self.encode_point(point);
+        let mut data = Vec::new();
+        data.extend(u16::try_from(input.len()).ok()?.to_be_bytes());
+        data.extend(input);
+        data.extend(u16::try_from(element.len()).ok()?.to_be_bytes());
+        data.extend(element);
+        data.extend(b"Finalize");
+        data.extend(oprf_context(mode));
+        Some(sha256(&data))
+    }
+}
+
+fn oprf_context(mode: OprfMode) -> Vec<u8> {
+    // "OPRFV1-" || mode || "-" || suite
+    let mode = match mode {
+        OprfMode::Base => 0x00,
+        OprfMode::Verifiable => 0x01,
+    };
+    [b"OPRFV1-".as_slice(), &[mode], b"-", SUITE].concat()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_oprf() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+
+        let r_random = ecdsa.generate_random_number_less_than(&ecdsa.q_order);
+        let blinded = ecdsa
+            .oprf_blind(OprfMode::Base, b"password123", &r_random)
+            .unwrap();
+        let evaluated = ecdsa.oprf_blind_evaluate(&priv_key, &blinded).unwrap();
+        let output = ecdsa
+            .oprf_finalize(OprfMode::Base, b"password123", &r_random, &evaluated)
+            .unwrap();
+
+        assert_eq!(
+            Some(output),
+            ecdsa.oprf_evaluate(OprfMode::Base, &priv_key, b"password123")
+        );
+    }
+
+    #[test]
+    fn test_oprf_input_length() {
+        // The length has to fit the two bytes of the output hash
+        let ecdsa = toy_curve();
+        let (priv_key, r_random) = (BigUint::from(7u32), BigUint::from(5u32));
+        let longest = vec![0x61; u16::MAX as usize];
+        let blinded = ecdsa
+            .oprf_blind(OprfMode::Base, &longest, &r_random)
+            .unwrap();
+        let evaluated = ecdsa.oprf_blind_evaluate(&priv_key, &blinded).unwrap();
+        assert_eq!(
+            ecdsa.oprf_finalize(OprfMode::Base, &longest, &r_random, &evaluated),
+            ecdsa.oprf_evaluate(OprfMode::Base, &priv_key, &longest)
+        );
+
+        let too_long = vec![0x61; u16::MAX as usize + 1];
+        assert!(ecdsa
+            .oprf_blind(OprfMode::Base, &too_long, &r_random)
+            .is_none());
+        assert!(ecdsa
+            .oprf_finalize(OprfMode::Base, &too_long, &r_random, &evaluated)
+            .is_none());
+        assert!(ecdsa
+            .oprf_evaluate(OprfMode::Base, &priv_key, &too_long)
+            .is_none());
+    }
+
+    #[test]
+    fn test_voprf() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+
+        let r_random = BigUint::from(5u32);
+        let blinded = ecdsa
+            .oprf_blind(OprfMode::Verifiable, b"token", &r_random)
+            .unwrap();
+        let (evaluated, proof) = ecdsa
+            .voprf_blind_evaluate(&priv_key, &blinded, &BigUint::from(11u32))
+            .unwrap();
+        let output = ecdsa
+            .voprf_finalize(&pub_key, b"token", &r_random, &blinded, &evaluated, &proof)
+            .unwrap();
+        assert_eq!(
+            Some(output),
+            ecdsa.oprf_evaluate(OprfMode::Verifiable, &priv_key, b"token")
+        );
+
+        // A server that evaluates with a different key is caught
+        let other_pub_key = ecdsa.generate_pub_key(&BigUint::from(3u32));
+        assert!(ecdsa
+            .voprf_finalize(
+                &other_pub_key,
+                b"token",
+                &r_random,
+                &blinded,
+                &evaluated,
+                &proof
+            )
+            .is_none());
+    }
+}
@@ -0,0 +1,225 @@
+// Paillier encryption with g = n + 1. Ciphertexts are additively homomorphic:
+// Enc(m1) * Enc(m2) = Enc(m1 + m2) and Enc(m)^k = Enc(k * m) mod n, which is
+// what the two-party ECDSA protocol uses to multiply by a secret it cannot see.
+//
+// The key holder can also prove that n is a well-formed modulus, gcd(n, φ(n))
+// = 1, which rules out the square factors a cheating party could use to learn
+// from what gets encrypted under n. This is the non-interactive proof of
+// Goldberg, Reyzin, Sagga and Baldimtsi (ACNS 2019): challenges ρ_i come from
+// hashing n and a context, and the prover answers with their n-th roots
+// σ_i = ρ_i^(n⁻¹ mod λ). x -> x^n permutes Z*_n exactly when gcd(n, φ(n)) = 1;
+// otherwise at most 1/p of the ρ_i have a root, p being the smallest prime of
+// gcd(n, φ(n)). The verifier rules out primes below 6370, so 11 rounds leave
+// a cheating prover less than 2^-128.
+
+use alloc::vec::Vec;
+use num_bigint::{BigUint, RandBigInt};
+
+use crate::arithmetic::{gcd, generate_prime, mod_inverse};
+use crate::hash::sha256;
+
+const DST_MODULUS: &[u8] = b"RS_ECC-paillier-modulus";
+const MODULUS_ROUNDS: usize = 11;
+const MODULUS_MIN_FACTOR: u32 = 6370;
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct PaillierPublicKey {
+    n: BigUint,
+    n_squared: BigUint,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct PaillierPrivateKey {
+    public_key: PaillierPublicKey,
+    lambda: BigUint,
+    mu: BigUint,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct ModulusProof {
+    sigma: Vec<BigUint>,
+}
+
+impl PaillierPrivateKey {
+    pub fn generate(bits: u64) -> PaillierPrivateKey {
+        // n = pq of exactly `bits` bits with p, q of bits / 2 each,
+        // λ = lcm(p - 1, q - 1), μ = λ⁻¹ mod n
+        let one = BigUint::from(1u32);
+        loop {
+            let p = generate_prime(bits / 2);
+            let q = generate_prime(bits - bits / 2);
+            let n = &p * &q;
+            if p == q || n.bits() != bits {
+                continue;
+            }
+            let (p1, q1) = (&p - &one, &q - &one);
+            let lambda = &p1 * &q1 / gcd(&p1, &q1);
+            if let Some(mu) = mod_inverse(&lambda, &n) {
+                let n_squared = &n * &n;
+                return PaillierPrivateKey {
+                    public_key: PaillierPublicKey { n, n_squared },
+                    lambda,
+                    mu,
+                };
+            }
+        }
+    }
+
+    pub fn public_key(&self) -> &PaillierPublicKey {
+        &self.public_key
+    }
+
+    pub fn prove_modulus(&self, context: &[u8]) -> ModulusProof {
+        // n⁻¹ mod λ exists since `generate` only keeps keys with gcd(n, λ) = 1
+        let n = &self.public_key.n;
+        let exponent = mod_inverse(n, &self.lambda).expect("n is not invertible mod λ");
+        let sigma = (0..MODULUS_ROUNDS)
+            .map(|round| modulus_challenge(n, context, round).modpow(&exponent, n))
+            .collect();
+        ModulusProof { sigma }
+    }
+
+    pub fn decrypt(&self, ciphertext: &BigUint) -> BigUint {
+        // m = L(c^λ mod n²) * μ mod n, L(x) = (x - 1) / n
+        let n = &self.public_key.n;
+        let x = ciphertext.modpow(&self.lambda, &self.public_key.n_squared);
+        let l = (x - BigUint::from(1u32)) / n;
+        (l * &self.mu) % n
+    }
+}
+
+impl PaillierPublicKey {
+    pub fn n(&self) -> &BigUint {
+        &self.n
+    }
+
+    pub fn encrypt(&self, message: &BigUint) -> BigUint {
+        let mut rng = crate::rng();
+        loop {
+            let r = rng.gen_biguint_range(&BigUint::from(1u32), &self.n);
+            if gcd(&r, &self.n) == BigUint::from(1u32) {
+                return self.encrypt_with(message, &r);
+            }
+        }
+    }
+
+    pub fn encrypt_with(&self, message: &BigUint, r_random: &BigUint) -> BigUint {
+        // c = (1 + n)^m * r^n = (1 + mn) * r^n mod n²
+        assert!(
+            message < &self.n,
+            "Message is bigger than the Paillier modulus"
+        );
+        let gm = (BigUint::from(1u32) + message * &self.n) % &self.n_squared;
+        (gm * r_random.modpow(&self.n, &self.n_squared)) % &self.n_squared
+    }
+
+    pub fn verify_modulus(&self, context: &[u8], proof: &ModulusProof) -> bool {
+        // No prime factor below the bound, and σ_i^n = ρ_i for every round
+        let n = &self.n;
+        let small_factor = (2..MODULUS_MIN_FACTOR)
+            .filter(|m| (2..*m).take_while(|d| d * d <= *m).all(|d| m % d != 0))
+            .any(|prime| n % prime == BigUint::from(0u32));
+        !small_factor
+            && proof.sigma.len() == MODULUS_ROUNDS
+            && proof.sigma.iter().enumerate().all(|(round, sigma)| {
+                sigma < n && sigma.modpow(n, n) == modulus_challenge(n, context, round)
+            })
+    }
+
+    pub fn is_ciphertext(&self, c: &BigUint) -> bool {
+        // An element of Z*_{n²}
+        c < &self.n_squared && gcd(c, &self.n) == BigUint::from(1u32)
+    }
+
+    pub fn add(&self, c1: &BigUint, c2: &BigUint) -> BigUint {
+        // Enc(m1 + m2)
+        (c1 * c2) % &self.n_squared
+    }
+
+    pub fn mul_scalar(&self, ciphertext: &BigUint, k: &BigUint) -> BigUint {
+        // Enc(k * m)
+        ciphertext.modpow(k, &self.n_squared)
+    }
+}
+
+fn modulus_challenge(n: &BigUint, context: &[u8], round: usize) -> BigUint {
+    // 128 bits more than n of SHA-256 output in counter mode, reduced mod n
+    let len = (n.bits() as usize + 128).div_ceil(8);
+    let mut prefix = DST_MODULUS.to_vec();
+    for piece in [context, &n.to_bytes_be()] {
+        prefix.extend((piece.len() as u64).to_be_bytes());
+        prefix.extend(piece);
+    }
+    prefix.extend((round as u32).to_be_bytes());
+    let mut bytes = Vec::with_capacity(len + 32);
+    let mut block = 0u32;
+    while bytes.len() < len {
+        bytes.extend(sha256(&[&prefix[..], &block.to_be_bytes()].concat()));
+        block += 1;
+    }
+    BigUint::from_bytes_be(&bytes[..len]) % n
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt_homomorphic() {
+        let priv_key = PaillierPrivateKey::generate(128);
+        let pub_key = priv_key.public_key();
+
+        let c1 = pub_key.encrypt(&BigUint::from(1234u32));
+        let c2 = pub_key.encrypt(&BigUint::from(4321u32));
+        assert_eq!(priv_key.decrypt(&c1), BigUint::from(1234u32));
+        assert_eq!(
+            priv_key.decrypt(&pub_key.add(&c1, &c2)),
+            BigUint::from(5555u32)
+        );
+        assert_eq!(
+            priv_key.decrypt(&pub_key.mul_scalar(&c1, &BigUint::from(3u32))),
+            BigUint::from(3702u32)
+        );
+    }
+
+    #[test]
+    fn test_modulus_proof() {
+        let priv_key = PaillierPrivateKey::generate(256);
+        let pub_key = priv_key.public_key();
+        let proof = priv_key.prove_modulus(b"session");
+        assert!(pub_key.verify_modulus(b"session", &proof));
+        assert!(!pub_key.verify_modulus(b"other session", &proof));
+        let mut short = proof.clone();
+        short.sigma.pop();
+        assert!(!pub_key.verify_modulus(b"session", &short));
+
+        // n = 7P still has gcd(n, φ(n)) = 1 when P is not 1 mod 7, so the
+        // roots exist, but the small factor is refused
+        let one = BigUint::from(1u32);
+        let large = loop {
+            let prime = generate_prime(128);
+            if &prime % 7u32 != one {
+                break prime;
+            }
+        };
+        let n = &large * 7u32;
+        let p1 = &large - &one;
+        let lambda = &p1 * 6u32 / gcd(&p1, &BigUint::from(6u32));
+        let small = PaillierPrivateKey {
+            public_key: PaillierPublicKey {
+                n_squared: &n * &n,
+                n,
+            },
+            mu: one,
+            lambda,
+        };
+        let proof = small.prove_modulus(b"session");
+        let n = small.public_key().n();
+        assert!(proof
+            .sigma
+            .iter()
+            .enumerate()
+            .all(|(round, sigma)| sigma.modpow(n, n) == modulus_challenge(n, b"session", round)));
+        assert!(!small.public_key().verify_modulus(b"session", &proof));
+    }
+}
@@ -0,0 +1,103 @@
+// Parallel bulk operations, behind the `parallel` feature. Verifications and
+// key generations are independent of each other, so they are spread over the
+// rayon thread pool as they are. A batch verification reports every failing
+// index rather than a bare false, so an indexer can drop the bad entries and
+// keep the rest.
+
+use ec_generic::Point;
+use num_bigint::BigUint;
+use rayon::prelude::*;
+
+use crate::ECDSA;
+
+pub struct BatchItem<'a> {
+    pub hash: &'a BigUint,
+    pub pub_key: &'a Point,
+    pub signature: &'a (BigUint, BigUint),
+}
+
+impl ECDSA {
+    pub fn verify_batch_parallel(&self, items: &[BatchItem]) -> Result<(), Vec<usize>> {
+        // Err holds the indices of the failing items, in increasing order
+        let failures: Vec<usize> = items
+            .par_iter()
+            .enumerate()
+            .filter(|(_, item)| {
+                item.hash >= &self.q_order
+                    || !self.verification(item.hash, item.pub_key, item.signature)
+            })
+            .map(|(index, _)| index)
+            .collect();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+
+    pub fn generate_key_pairs_parallel(&self, count: usize) -> Vec<(BigUint, Point)> {
+        // Every worker draws from its own thread-local rng
+        (0..count)
+            .into_par_iter()
+            .map(|_| self.generate_key_pair())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    fn batch<'a>(
+        keys: &'a [(BigUint, Point)],
+        hashes: &'a [BigUint],
+        signatures: &'a [(BigUint, BigUint)],
+    ) -> Vec<BatchItem<'a>> {
+        keys.iter()
+            .zip(hashes)
+            .zip(signatures)
+            .map(|(((_, pub_key), hash), signature)| BatchItem {
+                hash,
+                pub_key,
+                signature,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_batch_parallel() {
+        let ecdsa = toy_curve();
+        let keys: Vec<(BigUint, Point)> = (1u32..19)
+            .map(|d| (BigUint::from(d), ecdsa.generate_pub_key(&BigUint::from(d))))
+            .collect();
+        let hashes: Vec<BigUint> = (1u32..19).map(BigUint::from).collect();
+        let mut signatures: Vec<(BigUint, BigUint)> = keys
+            .iter()
+            .zip(&hashes)
+            .map(|((priv_key, _), hash)| ecdsa.sign(hash, priv_key, &BigUint::from(18u32)))
+            .collect();
+        assert_eq!(
+            ecdsa.verify_batch_parallel(&batch(&keys, &hashes, &signatures)),
+            Ok(())
+        );
+
+        // s = 0 never verifies
+        signatures[3].1 = BigUint::from(0u32);
+        signatures[11].1 = BigUint::from(0u32);
+        assert_eq!(
+            ecdsa.verify_batch_parallel(&batch(&keys, &hashes, &signatures)),
+            Err(vec![3, 11])
+        );
+    }
+
+    #[test]
+    fn test_generate_key_pairs_parallel() {
+        let ecdsa = toy_curve();
+        let pairs = ecdsa.generate_key_pairs_parallel(100);
+        assert_eq!(pairs.len(), 100);
+        for (priv_key, pub_key) in &pairs {
+            assert_eq!(*pub_key, ecdsa.generate_pub_key(priv_key));
+        }
+    }
+}
@@ -0,0 +1,209 @@
+// PASETO v3.public tokens: ECDSA on P-384 with SHA-384, a JWT without the
+// algorithm header to downgrade. A token is
+//
+//   v3.public. base64url(m || r || s) [. base64url(footer)]
+//
+// and the signature covers the pre-authentication encoding of the compressed
+// public key, the header, the message, the footer and the implicit assertion:
+//
+//   PAE(pk, "v3.public.", m, f, i)
+//
+// PAE writes the number of pieces and then each piece's length as 64-bit
+// little endian integers with the top bit clear, so no two lists of pieces
+// encode alike. The footer travels in the clear, as a key id usually;
+// the implicit assertion never travels at all, and both sides have to agree
+// on it. Signing draws a random k, which the specification allows in place
+// of RFC 6979 nonces.
+
+use alloc::{format, string::String, vec::Vec};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::aead::ct_eq;
+use crate::oid::HashAlgorithm;
+use crate::ECDSA;
+
+const HEADER: &str = "v3.public.";
+
+impl ECDSA {
+    pub fn paseto_v3_sign(
+        &self,
+        message: &[u8],
+        priv_key: &BigUint,
+        footer: &[u8],
+        implicit: &[u8],
+    ) -> Option<String> {
+        // None off P-384; an empty footer is left out of the token
+        let pub_key = self.generate_pub_key(priv_key);
+        let digest = self.paseto_v3_digest(&pub_key, message, footer, implicit)?;
+        let signature = self.sign_standard(&mut crate::rng(), &digest, priv_key);
+        let body = [message, &self.encode_signature(&signature)].concat();
+        let mut token = format!("{}{}", HEADER, URL_SAFE_NO_PAD.encode(body));
+        if !footer.is_empty() {
+            token.push('.');
+            token.push_str(&URL_SAFE_NO_PAD.encode(footer));
+        }
+        Some(token)
+    }
+
+    pub fn paseto_v3_verify(
+        &self,
+        token: &str,
+        pub_key: &Point,
+        footer: &[u8],
+        implicit: &[u8],
+    ) -> Option<Vec<u8>> {
+        // The message, if the token carries the expected footer and its
+        // signature checks out
+        let mut parts = token.strip_prefix(HEADER)?.split('.');
+        let body = URL_SAFE_NO_PAD.decode(parts.next()?).ok()?;
+        let token_footer = match parts.next() {
+            Some(encoded) => URL_SAFE_NO_PAD.decode(encoded).ok()?,
+            None => Vec::new(),
+        };
+        if parts.next().is_some() || !ct_eq(&token_footer, footer) {
+            return None;
+        }
+        let signature_len = self.field_len() + self.scalar_len();
+        let split = body.len().checked_sub(signature_len)?;
+        let (message, signature) = body.split_at(split);
+        let signature = self.decode_signature(signature)?;
+        let digest = self.paseto_v3_digest(pub_key, message, footer, implicit)?;
+        self.verify_standard(&digest, pub_key, &signature)
+            .then(|| message.to_vec())
+    }
+
+    fn paseto_v3_digest(
+        &self,
+        pub_key: &Point,
+        message: &[u8],
+        footer: &[u8],
+        implicit: &[u8],
+    ) -> Option<Vec<u8>> {
+        if !self.is_p384() || !self.is_valid_pub_key(pub_key) {
+            return None;
+        }
+        let pk = self.encode_point(pub_key);
+        let pieces = pae(&[&pk, HEADER.as_bytes(), message, footer, implicit]);
+        Some(HashAlgorithm::Sha384.digest(&pieces))
+    }
+}
+
+pub fn paseto_footer(token: &str) -> Option<Vec<u8>> {
+    // The footer of a v3.public token before it is verified, to pick the key
+    // it names; nothing in it can be trusted yet
+    let mut parts = token.strip_prefix(HEADER)?.split('.');
+    parts.next()?;
+    match (parts.next(), parts.next()) {
+        (Some(footer), None) => URL_SAFE_NO_PAD.decode(footer).ok(),
+        (None, _) => Some(Vec::new()),
+        _ => None,
+    }
+}
+
+pub fn pae(pieces: &[&[u8]]) -> Vec<u8> {
+    // Pre-authentication encoding: LE64(count) || LE64(len) || piece ...
+    let le64 = |n: usize| ((n as u64) & (u64::MAX >> 1)).to_le_bytes();
+    let mut encoded = le64(pieces.len()).to_vec();
+    for piece in pieces {
+        encoded.extend(le64(piece.len()));
+        encoded.extend(*piece);
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::{p256, p384};
+
+    // Signed by key 0xc0ffee with the Python `cryptography` package, with
+    // the implicit assertion below
+    const TOKEN: &str = include_str!("../testdata/paseto_v3_public.txt");
+    const IMPLICIT: &[u8] = br#"{"test-vector":"3-S-3"}"#;
+    const FOOTER: &[u8] = br#"{"kid":"rs-ecc-test"}"#;
+
+    #[test]
+    fn test_pae() {
+        // The examples of the PASETO specification
+        assert_eq!(pae(&[]), [0; 8]);
+        assert_eq!(hex::encode(pae(&[b""])), "01000000000000000000000000000000");
+        assert_eq!(
+            hex::encode(pae(&[b"test"])),
+            "0100000000000000040000000000000074657374"
+        );
+    }
+
+    #[test]
+    fn test_verify_token() {
+        let ecdsa = p384();
+        let pub_key = ecdsa.generate_pub_key(&BigUint::from(0xc0ffeeu32));
+        let token = TOKEN.trim();
+        assert_eq!(paseto_footer(token).unwrap(), FOOTER);
+        let message = ecdsa
+            .paseto_v3_verify(token, &pub_key, FOOTER, IMPLICIT)
+            .unwrap();
+        assert!(message.starts_with(br#"{"data":"this is a signed message""#));
+
+        // Rejected before any curve arithmetic
+        assert!(ecdsa
+            .paseto_v3_verify(token, &pub_key, b"", IMPLICIT)
+            .is_none());
+        assert!(ecdsa
+            .paseto_v3_verify(&token.replace("v3.", "v4."), &pub_key, FOOTER, IMPLICIT)
+            .is_none());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_verify_token_bindings() {
+        // The implicit assertion, the footer and the key are all signed; each
+        // case costs a full P-384 verification
+        let ecdsa = p384();
+        let pub_key = ecdsa.generate_pub_key(&BigUint::from(0xc0ffeeu32));
+        let token = TOKEN.trim();
+        assert!(ecdsa
+            .paseto_v3_verify(token, &pub_key, FOOTER, b"")
+            .is_none());
+        let (unfooted, _) = token.rsplit_once('.').unwrap();
+        assert!(ecdsa
+            .paseto_v3_verify(unfooted, &pub_key, b"", IMPLICIT)
+            .is_none());
+        let other = ecdsa.generate_pub_key(&BigUint::from(0xbeefu32));
+        assert!(ecdsa
+            .paseto_v3_verify(token, &other, FOOTER, IMPLICIT)
+            .is_none());
+
+        // A message edited under the same signature
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let signed = ecdsa
+            .paseto_v3_sign(b"{\"sub\":\"ci\"}", &priv_key, b"", b"")
+            .unwrap();
+        let mut edited = URL_SAFE_NO_PAD.decode(&signed[HEADER.len()..]).unwrap();
+        edited[8] ^= 1;
+        let edited = format!("{}{}", HEADER, URL_SAFE_NO_PAD.encode(edited));
+        assert!(ecdsa
+            .paseto_v3_verify(&edited, &pub_key, b"", b"")
+            .is_none());
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let ecdsa = p384();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let token = ecdsa
+            .paseto_v3_sign(b"{\"sub\":\"ci\"}", &priv_key, b"", b"")
+            .unwrap();
+        assert_eq!(token.matches('.').count(), 2);
+        assert_eq!(paseto_footer(&token), Some(Vec::new()));
+        assert_eq!(
+            ecdsa.paseto_v3_verify(&token, &pub_key, b"", b""),
+            Some(b"{\"sub\":\"ci\"}".to_vec())
+        );
+        assert!(p256().paseto_v3_sign(b"{}", &priv_key, b"", b"").is_none());
+    }
+}
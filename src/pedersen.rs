@@ -0,0 +1,81 @@
+// Pedersen commitments C = vA + rH. The second generator H comes from hashing
+// the generator A to the curve, so nobody knows log_A(H) and the commitment is
+// binding; the random blinding r makes it hiding.
+
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::secret::SecretScalar;
+use crate::ECDSA;
+
+const DST: &[u8] = b"RS_ECC-Pedersen-H";
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Commitment {
+    point: Point,
+}
+
+impl ECDSA {
+    pub fn pedersen_generator(&self) -> Point {
+        // H = hash_to_curve(A)
+        self.hash_to_curve(DST, &self.encode_point(&self.a_gen))
+    }
+}
+
+impl Commitment {
+    pub fn commit(ecdsa: &ECDSA, value: &BigUint, blinding: &BigUint) -> Commitment {
+        // C = vA + rH
+        let value = value % &ecdsa.q_order;
+        let blinding = blinding % &ecdsa.q_order;
+        let va = ecdsa.mul_secret(&ecdsa.a_gen, &SecretScalar::new(value));
+        let rh = ecdsa.mul_secret(&ecdsa.pedersen_generator(), &SecretScalar::new(blinding));
+        Commitment {
+            point: ecdsa.add_points(&va, &rh),
+        }
+    }
+
+    pub fn add(&self, ecdsa: &ECDSA, other: &Commitment) -> Commitment {
+        // C1 + C2 = (v1 + v2)A + (r1 + r2)H
+        Commitment {
+            point: ecdsa.add_points(&self.point, &other.point),
+        }
+    }
+
+    pub fn verify_opening(&self, ecdsa: &ECDSA, value: &BigUint, blinding: &BigUint) -> bool {
+        *self == Commitment::commit(ecdsa, value, blinding)
+    }
+
+    pub fn point(&self) -> &Point {
+        &self.point
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_commit_open() {
+        let ecdsa = toy_curve();
+        let value = BigUint::from(5u32);
+        let blinding = BigUint::from(12u32);
+
+        let commitment = Commitment::commit(&ecdsa, &value, &blinding);
+
+        assert!(commitment.verify_opening(&ecdsa, &value, &blinding));
+        assert!(!commitment.verify_opening(&ecdsa, &BigUint::from(6u32), &blinding));
+    }
+
+    #[test]
+    fn test_commitment_homomorphism() {
+        let ecdsa = toy_curve();
+        let c1 = Commitment::commit(&ecdsa, &BigUint::from(5u32), &BigUint::from(12u32));
+        let c2 = Commitment::commit(&ecdsa, &BigUint::from(3u32), &BigUint::from(10u32));
+
+        let sum = c1.add(&ecdsa, &c2);
+
+        // 12 + 10 = 3 mod 19
+        assert!(sum.verify_opening(&ecdsa, &BigUint::from(8u32), &BigUint::from(3u32)));
+    }
+}
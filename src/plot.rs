@@ -0,0 +1,195 @@
+// Scatter plots of the points of a curve over a small prime field, for
+// figures in course notes made from the same parameters the code signs with.
+// CSV gives one `x,y,step` row per affine point, then the points of an
+// optional walk again with their step numbers; SVG draws every point as a dot
+// on a p × p grid with y pointing up, and the walk as a numbered polyline.
+// Fields above `PLOT_MAX_FIELD` have too many points to draw.
+
+use alloc::string::{String, ToString};
+use alloc::{format, vec::Vec};
+use core::fmt::Write;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::ECDSA;
+
+pub const PLOT_MAX_FIELD: u32 = 4096;
+
+// Width and height of the SVG in pixels, whatever the field size
+const SVG_SIZE: u32 = 600;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum PlotFormat {
+    Csv,
+    Svg,
+}
+
+impl ECDSA {
+    pub fn plot_points(&self, walk: &[Point], format: PlotFormat) -> Option<String> {
+        // None for fields above PLOT_MAX_FIELD; O is left out of the walk
+        let p = u32::try_from(&self.elliptic_curve.p).ok()?;
+        if p > PLOT_MAX_FIELD {
+            return None;
+        }
+        let points = self.affine_points(p);
+        let walk: Vec<(u32, u32)> = walk.iter().filter_map(small_coordinates).collect();
+        Some(match format {
+            PlotFormat::Csv => csv(&points, &walk),
+            PlotFormat::Svg => svg(p, &points, &walk),
+        })
+    }
+
+    pub fn double_and_add_walk(&self, point: &Point, k: &BigUint) -> Vec<Point> {
+        // The points left to right double-and-add goes through on the way to
+        // kP: P, then 2Q and 2Q + P for the following bits of k
+        let mut walk = Vec::new();
+        if *k == BigUint::from(0u32) {
+            return walk;
+        }
+        let mut current = point.clone();
+        walk.push(current.clone());
+        for bit in (0..k.bits() - 1).rev() {
+            current = self.add_points(&current, &current);
+            walk.push(current.clone());
+            if k.bit(bit) {
+                current = self.add_points(&current, point);
+                walk.push(current.clone());
+            }
+        }
+        walk
+    }
+
+    fn affine_points(&self, p: u32) -> Vec<(u32, u32)> {
+        // Both square roots of x³ + ax + b for every x, in order of x then y
+        let mut points = Vec::new();
+        for x in 0..p {
+            let Some(y) = self.y_for_x(&BigUint::from(x)) else {
+                continue;
+            };
+            let y = u32::try_from(&y).unwrap();
+            let other = (p - y) % p;
+            points.push((x, y.min(other)));
+            if y != other {
+                points.push((x, y.max(other)));
+            }
+        }
+        points
+    }
+}
+
+fn small_coordinates(point: &Point) -> Option<(u32, u32)> {
+    match point {
+        Point::Coor(x, y) => Some((u32::try_from(x).ok()?, u32::try_from(y).ok()?)),
+        Point::Identity => None,
+    }
+}
+
+fn csv(points: &[(u32, u32)], walk: &[(u32, u32)]) -> String {
+    let mut out = "x,y,step\n".to_string();
+    for (x, y) in points {
+        writeln!(out, "{x},{y},").unwrap();
+    }
+    for (step, (x, y)) in walk.iter().enumerate() {
+        writeln!(out, "{x},{y},{step}").unwrap();
+    }
+    out
+}
+
+fn svg(p: u32, points: &[(u32, u32)], walk: &[(u32, u32)]) -> String {
+    // One unit per field element, with a unit of margin around the grid
+    let flip = |y: u32| p - 1 - y;
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{SVG_SIZE}\" height=\"{SVG_SIZE}\" \
+         viewBox=\"-1 -1 {size} {size}\">\n\
+         <rect x=\"-1\" y=\"-1\" width=\"{size}\" height=\"{size}\" fill=\"white\"/>\n\
+         <rect x=\"0\" y=\"0\" width=\"{side}\" height=\"{side}\" fill=\"none\" stroke=\"#ccc\" \
+         stroke-width=\"0.05\"/>\n",
+        size = p + 1,
+        side = p - 1,
+    );
+    for (x, y) in points {
+        writeln!(
+            out,
+            "<circle cx=\"{x}\" cy=\"{}\" r=\"0.3\" fill=\"black\"/>",
+            flip(*y)
+        )
+        .unwrap();
+    }
+    if !walk.is_empty() {
+        let path: Vec<String> = walk
+            .iter()
+            .map(|(x, y)| format!("{x},{}", flip(*y)))
+            .collect();
+        writeln!(
+            out,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"red\" stroke-width=\"0.1\"/>",
+            path.join(" ")
+        )
+        .unwrap();
+        for (step, (x, y)) in walk.iter().enumerate() {
+            writeln!(
+                out,
+                "<text x=\"{x}\" y=\"{}\" font-size=\"0.8\" fill=\"red\">{step}</text>",
+                flip(*y)
+            )
+            .unwrap();
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_double_and_add_walk() {
+        // 13 = 0b1101: A, 2A, 3A, 6A, 12A, 13A
+        let ecdsa = toy_curve();
+        let walk = ecdsa.double_and_add_walk(&ecdsa.a_gen, &BigUint::from(13u32));
+        let expected: Vec<Point> = [1u32, 2, 3, 6, 12, 13]
+            .iter()
+            .map(|&k| ecdsa.mul_vartime(&ecdsa.a_gen, &BigUint::from(k)))
+            .collect();
+        assert_eq!(walk, expected);
+        assert!(ecdsa
+            .double_and_add_walk(&ecdsa.a_gen, &BigUint::from(0u32))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_plot_csv() {
+        // 18 affine points besides O, each on the curve
+        let ecdsa = toy_curve();
+        let walk = ecdsa.double_and_add_walk(&ecdsa.a_gen, &BigUint::from(5u32));
+        let csv = ecdsa.plot_points(&walk, PlotFormat::Csv).unwrap();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], "x,y,step");
+        assert_eq!(rows.len(), 1 + 18 + walk.len());
+        assert_eq!(rows[1], "0,6,");
+        assert_eq!(rows[19], "5,1,0");
+        for row in &rows[1..] {
+            let fields: Vec<&str> = row.split(',').collect();
+            let point = Point::Coor(fields[0].parse().unwrap(), fields[1].parse().unwrap());
+            assert!(ecdsa.elliptic_curve.is_on_curve(&point));
+        }
+    }
+
+    #[test]
+    fn test_plot_svg() {
+        let ecdsa = toy_curve();
+        let walk = ecdsa.double_and_add_walk(&ecdsa.a_gen, &BigUint::from(5u32));
+        let svg = ecdsa.plot_points(&walk, PlotFormat::Svg).unwrap();
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<circle").count(), 18);
+        assert_eq!(svg.matches("<text").count(), walk.len());
+        // (5, 1) is drawn at row 17 - 1 - 1
+        assert!(svg.contains("<circle cx=\"5\" cy=\"15\""));
+
+        assert!(crate::curves::p256()
+            .plot_points(&[], PlotFormat::Svg)
+            .is_none());
+    }
+}
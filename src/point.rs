@@ -0,0 +1,181 @@
+// Checked point arithmetic for protocols built on this crate. A `CurvePoint`
+// is only ever the identity or a point of the prime order subgroup of its
+// curve: `new` and `from_bytes` check the point, and sums, doublings,
+// negations and multiples of such points stay in the subgroup, so none of the
+// operations below can reach the unwraps on invalid points inside
+// ec-generic. Combining points of two different curves gives None. The
+// identity is a value like any other, `CurvePoint::identity`, rather than an
+// error.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::encoding::PointFormat;
+use crate::secret::SecretScalar;
+use crate::ECDSA;
+
+#[derive(Clone)]
+pub struct CurvePoint<'a> {
+    ecdsa: &'a ECDSA,
+    point: Point,
+}
+
+impl<'a> CurvePoint<'a> {
+    pub fn new(ecdsa: &'a ECDSA, point: Point) -> Option<CurvePoint<'a>> {
+        // The identity, or on the curve with qP = O
+        let valid = point == Point::Identity || ecdsa.is_valid_pub_key(&point);
+        valid.then_some(CurvePoint { ecdsa, point })
+    }
+
+    pub fn identity(ecdsa: &'a ECDSA) -> CurvePoint<'a> {
+        CurvePoint {
+            ecdsa,
+            point: Point::Identity,
+        }
+    }
+
+    pub fn generator(ecdsa: &'a ECDSA) -> CurvePoint<'a> {
+        CurvePoint {
+            ecdsa,
+            point: ecdsa.a_gen.clone(),
+        }
+    }
+
+    pub fn from_bytes(ecdsa: &'a ECDSA, bytes: &[u8]) -> Option<CurvePoint<'a>> {
+        // Compressed or uncompressed SEC1, or 0x00 for the identity
+        CurvePoint::new(ecdsa, ecdsa.decode_point(bytes)?)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.ecdsa.encode_point(&self.point)
+    }
+
+    pub fn point(&self) -> &Point {
+        &self.point
+    }
+
+    pub fn coordinates(&self) -> Option<(&BigUint, &BigUint)> {
+        match &self.point {
+            Point::Coor(x, y) => Some((x, y)),
+            Point::Identity => None,
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.point == Point::Identity
+    }
+
+    pub fn add(&self, other: &CurvePoint) -> Option<CurvePoint<'a>> {
+        self.same_curve(other)?;
+        Some(self.with(self.ecdsa.add_points(&self.point, &other.point)))
+    }
+
+    pub fn sub(&self, other: &CurvePoint) -> Option<CurvePoint<'a>> {
+        self.same_curve(other)?;
+        Some(self.with(self.ecdsa.sub_points(&self.point, &other.point)))
+    }
+
+    pub fn double(&self) -> CurvePoint<'a> {
+        self.with(self.ecdsa.add_points(&self.point, &self.point))
+    }
+
+    pub fn negate(&self) -> CurvePoint<'a> {
+        self.with(self.ecdsa.negate_point(&self.point))
+    }
+
+    pub fn mul(&self, k: &SecretScalar) -> CurvePoint<'a> {
+        // Constant time, see `mul_secret`
+        self.with(self.ecdsa.mul_secret(&self.point, k))
+    }
+
+    pub fn mul_vartime(&self, k: &BigUint) -> CurvePoint<'a> {
+        // For public scalars only
+        self.with(self.ecdsa.mul_vartime(&self.point, k))
+    }
+
+    pub fn format_as(&self, format: PointFormat) -> String {
+        self.ecdsa.format_as(&self.point, format)
+    }
+
+    fn same_curve(&self, other: &CurvePoint) -> Option<()> {
+        let same =
+            core::ptr::eq(self.ecdsa, other.ecdsa) || self.ecdsa.same_parameters(other.ecdsa);
+        same.then_some(())
+    }
+
+    fn with(&self, point: Point) -> CurvePoint<'a> {
+        CurvePoint {
+            ecdsa: self.ecdsa,
+            point,
+        }
+    }
+}
+
+impl PartialEq for CurvePoint<'_> {
+    fn eq(&self, other: &CurvePoint) -> bool {
+        self.point == other.point && self.same_curve(other).is_some()
+    }
+}
+
+impl fmt::Debug for CurvePoint<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "CurvePoint({})",
+            self.format_as(PointFormat::CompressedHex)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::{p256, secp256k1};
+
+    #[test]
+    fn test_curve_point_arithmetic() {
+        let ecdsa = p256();
+        let g = CurvePoint::generator(&ecdsa);
+        let o = CurvePoint::identity(&ecdsa);
+
+        let two_g = g.double();
+        let three_g = two_g.add(&g).unwrap();
+        assert_eq!(three_g, g.mul_vartime(&BigUint::from(3u32)));
+        assert_eq!(three_g, g.mul(&SecretScalar::new(BigUint::from(3u32))));
+        assert_eq!(three_g.sub(&two_g).unwrap(), g);
+        assert!(g.add(&g.negate()).unwrap().is_identity());
+        assert_eq!(o.add(&g).unwrap(), g);
+        assert_eq!(o.negate(), o);
+        assert!(g.mul_vartime(&ecdsa.q_order).is_identity());
+
+        let bytes = three_g.to_bytes();
+        assert_eq!(CurvePoint::from_bytes(&ecdsa, &bytes).unwrap(), three_g);
+        assert_eq!(CurvePoint::from_bytes(&ecdsa, &[0x00]).unwrap(), o);
+        assert!(o.coordinates().is_none());
+    }
+
+    #[test]
+    fn test_curve_point_checks() {
+        let ecdsa = p256();
+        let (x, y) = CurvePoint::generator(&ecdsa)
+            .coordinates()
+            .map(|(x, y)| (x.clone(), y.clone()))
+            .unwrap();
+        assert!(CurvePoint::new(&ecdsa, Point::Coor(x.clone(), y.clone())).is_some());
+        assert!(CurvePoint::new(&ecdsa, Point::Coor(x, y + 1u32)).is_none());
+
+        // Points of two curves do not mix, even with the same coordinates
+        let other = secp256k1();
+        let g = CurvePoint::generator(&ecdsa);
+        let h = CurvePoint::generator(&other);
+        assert!(g.add(&h).is_none());
+        assert!(g.sub(&h).is_none());
+        assert_ne!(CurvePoint::identity(&ecdsa), CurvePoint::identity(&other));
+        assert_eq!(g.add(&CurvePoint::generator(&p256())), Some(g.double()));
+    }
+}
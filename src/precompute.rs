@@ -0,0 +1,170 @@
+// Fixed 4-bit window tables of multiples of a point, rows[i][j] =
+// j * 16^i * P, so kP costs one addition per window and no doublings. Worth it
+// when the same point is multiplied many times: the generator in
+// `SigningContext`, or the key of a signer whose signatures are checked over
+// and over, through `VerifyingKey::precompute`. The generator table is indexed
+// by secret nonces, so entries are kept as fixed-size encodings and read with
+// `fixed_window::lookup`, which scans the whole row, and every window adds its
+// entry, the identity for a zero digit included.
+
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::BigUint;
+use signature::{Error, Verifier};
+
+use crate::fixed_window::lookup;
+use crate::hash::sha256;
+use crate::jacobian::JacobianPoint;
+use crate::signer::{Signature, VerifyingKey};
+use crate::ECDSA;
+
+const WINDOW_BITS: u64 = 4;
+
+#[derive(Clone, Debug)]
+pub struct PointTable {
+    rows: Vec<Vec<Vec<u8>>>,
+}
+
+// A verifying key with its table, for verifying many signatures of one signer
+#[derive(Clone)]
+pub struct PrecomputedVerifyingKey<'a> {
+    key: VerifyingKey<'a>,
+    table: PointTable,
+}
+
+impl ECDSA {
+    pub fn point_table(&self, point: &Point) -> PointTable {
+        // Built in Jacobian coordinates and converted to affine in one batch,
+        // with a single field inversion
+        let windows = self.q_order.bits().div_ceil(WINDOW_BITS);
+        let mut multiples = Vec::new();
+        let mut base = self.to_jacobian(point);
+        for _ in 0..windows {
+            let mut multiple = base.clone();
+            for _ in 1..(1 << WINDOW_BITS) {
+                multiples.push(multiple.clone());
+                multiple = self.jacobian_add(&multiple, &base);
+            }
+            // After 15 additions `multiple` is 16 * base, the next window's base
+            base = multiple;
+        }
+        let field_len = self.field_len();
+        let encode = |point: &Point| self.to_jacobian(point).to_bytes(field_len);
+        let rows = self
+            .batch_to_affine(&multiples)
+            .chunks((1 << WINDOW_BITS) - 1)
+            .map(|row| {
+                let mut entries = Vec::from([encode(&Point::Identity)]);
+                entries.extend(row.iter().map(encode));
+                entries
+            })
+            .collect();
+        PointTable { rows }
+    }
+
+    pub fn mul_table(&self, table: &PointTable, k: &BigUint) -> Point {
+        self.batch_to_affine(&[self.mul_table_jacobian(table, k)])
+            .pop()
+            .unwrap()
+    }
+
+    pub fn mul_table_jacobian(&self, table: &PointTable, k: &BigUint) -> JacobianPoint {
+        // kP = Σ k_i * 16^i * P, left in Jacobian form for callers that
+        // convert many results at once
+        let k = k % &self.q_order;
+        let mut result = self.to_jacobian(&Point::Identity);
+        for (i, row) in table.rows.iter().enumerate() {
+            let digit = (0..WINDOW_BITS).fold(0usize, |digit, bit| {
+                digit | (usize::from(k.bit(i as u64 * WINDOW_BITS + bit)) << bit)
+            });
+            let entry = JacobianPoint::from_bytes(&lookup(row, digit)).unwrap();
+            result = self.jacobian_add(&result, &entry);
+        }
+        result
+    }
+
+    pub fn verify_precomputed(
+        &self,
+        digest: &[u8],
+        key: &PrecomputedVerifyingKey,
+        signature: &Signature,
+    ) -> bool {
+        // Same check as `verify_standard`, with u2 B read from the key's table
+        // P = u1 A + u2 B with u1 = z * s⁻¹, u2 = r * s⁻¹, accept if x(P) mod q = r
+        let q = &self.q_order;
+        let (r, s) = (signature.r(), signature.s());
+        let zero = BigUint::from(0u32);
+        if *r == zero || r >= q || *s == zero || s >= q {
+            return false;
+        }
+
+        let s_inv = s.modpow(&(q - BigUint::from(2u32)), q);
+        let u1 = (self.bits_to_scalar(digest) * &s_inv) % q;
+        let u2 = (r * &s_inv) % q;
+        let p = self.add_points(
+            &self.mul_vartime(&self.a_gen, &u1),
+            &self.mul_table(&key.table, &u2),
+        );
+        matches!(p, Point::Coor(x, _) if &(&x % q) == r)
+    }
+}
+
+impl<'a> VerifyingKey<'a> {
+    pub fn precompute(&self) -> PrecomputedVerifyingKey<'a> {
+        PrecomputedVerifyingKey {
+            key: self.clone(),
+            table: self.ecdsa().point_table(self.pub_key()),
+        }
+    }
+}
+
+impl<'a> PrecomputedVerifyingKey<'a> {
+    pub fn verifying_key(&self) -> &VerifyingKey<'a> {
+        &self.key
+    }
+}
+
+impl Verifier<Signature> for PrecomputedVerifyingKey<'_> {
+    fn verify(&self, msg: &[u8], signature: &Signature) -> Result<(), Error> {
+        let ecdsa = self.key.ecdsa();
+        if ecdsa.verify_precomputed(&sha256(msg), self, signature) {
+            return Ok(());
+        }
+        Err(Error::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+    use signature::Signer;
+
+    use crate::signer::SigningKey;
+
+    #[test]
+    fn test_mul_table() {
+        let ecdsa = toy_curve();
+        let point = ecdsa.mul_vartime(&ecdsa.a_gen, &BigUint::from(3u32));
+        let table = ecdsa.point_table(&point);
+        for k in 0u32..40 {
+            let k = BigUint::from(k);
+            assert_eq!(ecdsa.mul_table(&table, &k), ecdsa.mul_vartime(&point, &k));
+        }
+    }
+
+    #[test]
+    fn test_precomputed_verifying_key() {
+        let ecdsa = crate::curves::p256();
+        let signing_key = SigningKey::new(&ecdsa, BigUint::from(0xc0ffeeu32));
+        let verifying_key = signing_key.verifying_key();
+        let precomputed = verifying_key.precompute();
+
+        for message in [&b"log entry 1"[..], b"log entry 2"] {
+            let signature = signing_key.sign(message);
+            assert!(precomputed.verify(message, &signature).is_ok());
+            assert!(ecdsa.verify_standard(&sha256(message), verifying_key.pub_key(), &signature));
+            assert!(precomputed.verify(b"log entry 3", &signature).is_err());
+        }
+    }
+}
@@ -0,0 +1,116 @@
+// Non-interactive Schnorr proof of knowledge of the private key d behind
+// B = dA, made non-interactive with the Fiat-Shamir transform. The challenge
+// hashes a caller supplied context so a proof made for one protocol, session
+// or registration cannot be replayed in another.
+
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::secret::SecretScalar;
+use crate::ECDSA;
+
+const DST: &[u8] = b"RS_ECC-Schnorr-PoK";
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct KnowledgeProof {
+    c: BigUint,
+    s: BigUint,
+}
+
+impl ECDSA {
+    pub fn prove_knowledge(
+        &self,
+        priv_key: &BigUint,
+        context: &[u8],
+        k_random: &BigUint,
+    ) -> KnowledgeProof {
+        // R = kA, c = H(A, B, R, context)
+        // s = k + c * d mod q
+        assert!(
+            priv_key < &self.q_order,
+            "Private Key is Bigger than the Ec group"
+        );
+        assert!(k_random < &self.q_order, "'K' is Bigger than the Ec group");
+
+        let pub_key = self.generate_pub_key(priv_key);
+        let r_point = self.mul_secret(&self.a_gen, &SecretScalar::new(k_random.clone()));
+        let c = self.knowledge_challenge(&pub_key, &r_point, context);
+        let s = (k_random + &c * priv_key) % &self.q_order;
+        KnowledgeProof { c, s }
+    }
+
+    pub fn verify_knowledge(
+        &self,
+        pub_key: &Point,
+        context: &[u8],
+        proof: &KnowledgeProof,
+    ) -> bool {
+        // R = sA - cB, accept if c = H(A, B, R, context)
+        if *pub_key == Point::Identity
+            || !self.elliptic_curve.is_on_curve(pub_key)
+            || proof.c >= self.q_order
+            || proof.s >= self.q_order
+        {
+            return false;
+        }
+
+        let r_point = self.sub_points(
+            &self.mul_vartime(&self.a_gen, &proof.s),
+            &self.mul_vartime(pub_key, &proof.c),
+        );
+        self.knowledge_challenge(pub_key, &r_point, context) == proof.c
+    }
+
+    fn knowledge_challenge(&self, pub_key: &Point, r_point: &Point, context: &[u8]) -> BigUint {
+        let mut data = Vec::new();
+        for point in [&self.a_gen, pub_key, r_point] {
+            data.extend(self.encode_point(point));
+        }
+        data.extend((context.len() as u64).to_be_bytes());
+        data.extend(context);
+        self.hash_to_scalar(DST, &data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_prove_verify_knowledge() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+
+        let k_random = ecdsa.generate_random_number_less_than(&ecdsa.q_order);
+        let proof = ecdsa.prove_knowledge(&priv_key, b"register:alice", &k_random);
+
+        assert!(ecdsa.verify_knowledge(&pub_key, b"register:alice", &proof));
+    }
+
+    #[test]
+    fn test_verify_knowledge_wrong_context() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+
+        let k_random = BigUint::from(11u32);
+        let proof = ecdsa.prove_knowledge(&priv_key, b"register:alice", &k_random);
+
+        assert!(!ecdsa.verify_knowledge(&pub_key, b"register:bob", &proof));
+    }
+
+    #[test]
+    fn test_verify_knowledge_wrong_key() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+        let other_pub_key = ecdsa.generate_pub_key(&BigUint::from(3u32));
+
+        let k_random = BigUint::from(11u32);
+        let proof = ecdsa.prove_knowledge(&priv_key, b"register:alice", &k_random);
+
+        assert!(!ecdsa.verify_knowledge(&other_pub_key, b"register:alice", &proof));
+    }
+}
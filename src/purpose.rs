@@ -0,0 +1,157 @@
+// Keys bound to one purpose ("tls", "firmware", "tokens") when they are
+// created. Every signature hashes the purpose in ahead of the message,
+// SHA-256(DST || len(purpose) || purpose || m), and a verifying key checks
+// against its own purpose, never one given at verification time. So a token
+// signature does not verify as a firmware signature, even under the same key
+// pair, as long as the verifier holds the key as a firmware key.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use ec_generic::Point;
+
+use crate::hash::{frame_context, sha256};
+use crate::keypair::KeyPair;
+use crate::signer::Signature;
+use crate::ECDSA;
+
+const DST_PURPOSE: &[u8] = b"RS_ECC-purpose";
+
+// 1 to 255 printable ASCII characters
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Purpose(String);
+
+#[derive(Clone)]
+pub struct PurposeKeyPair<'a> {
+    key_pair: KeyPair<'a>,
+    purpose: Purpose,
+}
+
+#[derive(Clone)]
+pub struct PurposeVerifyingKey<'a> {
+    ecdsa: &'a ECDSA,
+    pub_key: Point,
+    purpose: Purpose,
+}
+
+impl Purpose {
+    pub fn new(name: &str) -> Option<Purpose> {
+        let printable = name.bytes().all(|byte| byte.is_ascii_graphic());
+        if name.is_empty() || name.len() > 255 || !printable {
+            return None;
+        }
+        Some(Purpose(String::from(name)))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    fn digest(&self, message: &[u8]) -> Vec<u8> {
+        let framed = frame_context(self.0.as_bytes(), message).unwrap();
+        sha256(&[DST_PURPOSE, &framed].concat())
+    }
+}
+
+impl<'a> KeyPair<'a> {
+    pub fn with_purpose(self, purpose: Purpose) -> PurposeKeyPair<'a> {
+        PurposeKeyPair {
+            key_pair: self,
+            purpose,
+        }
+    }
+}
+
+impl<'a> PurposeKeyPair<'a> {
+    pub fn purpose(&self) -> &Purpose {
+        &self.purpose
+    }
+
+    pub fn public(&self) -> &Point {
+        self.key_pair.public()
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        let ecdsa = self.key_pair.ecdsa();
+        ecdsa.sign_standard(
+            &mut crate::rng(),
+            &self.purpose.digest(message),
+            self.key_pair.private(),
+        )
+    }
+
+    pub fn verifying_key(&self) -> PurposeVerifyingKey<'a> {
+        PurposeVerifyingKey {
+            ecdsa: self.key_pair.ecdsa(),
+            pub_key: self.key_pair.public().clone(),
+            purpose: self.purpose.clone(),
+        }
+    }
+}
+
+impl<'a> PurposeVerifyingKey<'a> {
+    pub fn new(
+        ecdsa: &'a ECDSA,
+        pub_key: Point,
+        purpose: Purpose,
+    ) -> Option<PurposeVerifyingKey<'a>> {
+        if !ecdsa.is_valid_pub_key(&pub_key) {
+            return None;
+        }
+        Some(PurposeVerifyingKey {
+            ecdsa,
+            pub_key,
+            purpose,
+        })
+    }
+
+    pub fn purpose(&self) -> &Purpose {
+        &self.purpose
+    }
+
+    pub fn pub_key(&self) -> &Point {
+        &self.pub_key
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &Signature) -> bool {
+        self.ecdsa
+            .verify_standard(&self.purpose.digest(message), &self.pub_key, signature)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::p256;
+
+    #[test]
+    fn test_purpose_separation() {
+        let ecdsa = p256();
+        let key_pair = KeyPair::generate(&ecdsa);
+        let tokens = key_pair
+            .clone()
+            .with_purpose(Purpose::new("tokens").unwrap());
+        let signature = tokens.sign(b"image v1.2");
+        assert!(tokens.verifying_key().verify(b"image v1.2", &signature));
+
+        // Same key pair, held by the verifier as a firmware key
+        let firmware = PurposeVerifyingKey::new(
+            &ecdsa,
+            key_pair.public().clone(),
+            Purpose::new("firmware").unwrap(),
+        )
+        .unwrap();
+        assert!(!firmware.verify(b"image v1.2", &signature));
+        assert!(!key_pair.verify(b"image v1.2", &signature));
+    }
+
+    #[test]
+    fn test_purpose_names() {
+        assert_eq!(Purpose::new("tls").unwrap().as_str(), "tls");
+        assert!(Purpose::new("").is_none());
+        assert!(Purpose::new("two words").is_none());
+        assert!(Purpose::new("caf\u{e9}").is_none());
+        assert!(Purpose::new(&"x".repeat(256)).is_none());
+        assert!(Purpose::new(&"x".repeat(255)).is_some());
+    }
+}
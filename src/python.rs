@@ -0,0 +1,129 @@
+// Python bindings, built as an extension module with maturin (see
+// pyproject.toml) from the `python` feature. Same API as the wasm bindings:
+// a Curve from big-endian parameters, key pairs, and sign/verify over bytes,
+// with private keys as big-endian scalars of exactly `scalar_len` bytes,
+// public keys as compressed SEC1 points and signatures as r || s. Bad curves
+// and keys raise ValueError.
+
+use std::borrow::Cow;
+
+use ec_generic::{EllipticCurve, Point};
+use num_bigint::BigUint;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use signature::{Signer, Verifier};
+
+use crate::signer::{SigningKey, VerifyingKey};
+use crate::ECDSA;
+
+#[pyclass(module = "rs_ecc", frozen)]
+pub struct Curve {
+    ecdsa: ECDSA,
+}
+
+#[pyclass(module = "rs_ecc", frozen)]
+pub struct KeyPair {
+    private_key: Vec<u8>,
+    public_key: Vec<u8>,
+}
+
+#[pymethods]
+impl Curve {
+    #[new]
+    fn new(a: &[u8], b: &[u8], p: &[u8], gx: &[u8], gy: &[u8], q: &[u8]) -> PyResult<Curve> {
+        // y² = x³ + ax + b over F_p, generator (gx, gy) of order q
+        let ecdsa = ECDSA::new(
+            EllipticCurve {
+                a: BigUint::from_bytes_be(a),
+                b: BigUint::from_bytes_be(b),
+                p: BigUint::from_bytes_be(p),
+            },
+            Point::Coor(BigUint::from_bytes_be(gx), BigUint::from_bytes_be(gy)),
+            BigUint::from_bytes_be(q),
+        )
+        .ok_or_else(|| PyValueError::new_err("Invalid curve parameters"))?;
+        Ok(Curve { ecdsa })
+    }
+
+    fn generate_key_pair(&self) -> KeyPair {
+        let priv_key = self.ecdsa.generate_priv_key();
+        self.to_key_pair(&SigningKey::new(&self.ecdsa, priv_key))
+    }
+
+    fn key_pair(&self, private_key: &[u8]) -> PyResult<KeyPair> {
+        Ok(self.to_key_pair(&self.signing_key(private_key)?))
+    }
+
+    fn sign(&self, private_key: &[u8], message: &[u8]) -> PyResult<Cow<'static, [u8]>> {
+        let signature = self.signing_key(private_key)?.sign(message);
+        Ok(Cow::Owned(self.ecdsa.encode_signature(&signature)))
+    }
+
+    fn verify(&self, public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        let verifying_key = VerifyingKey::from_bytes(&self.ecdsa, public_key);
+        match (verifying_key, self.ecdsa.decode_signature(signature)) {
+            (Some(key), Some(signature)) => key.verify(message, &signature).is_ok(),
+            _ => false,
+        }
+    }
+}
+
+impl Curve {
+    fn signing_key(&self, private_key: &[u8]) -> PyResult<SigningKey<'_>> {
+        SigningKey::from_bytes(&self.ecdsa, private_key)
+            .ok_or_else(|| PyValueError::new_err("Private key must be in [1, q - 1]"))
+    }
+
+    fn to_key_pair(&self, signing_key: &SigningKey) -> KeyPair {
+        KeyPair {
+            private_key: signing_key.to_bytes(),
+            public_key: signing_key.verifying_key().to_bytes(),
+        }
+    }
+}
+
+#[pymethods]
+impl KeyPair {
+    #[getter]
+    fn private_key(&self) -> &[u8] {
+        &self.private_key
+    }
+
+    #[getter]
+    fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+}
+
+#[pymodule]
+fn rs_ecc(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Curve>()?;
+    module.add_class::<KeyPair>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_python_sign_verify() {
+        let curve = Curve::new(&[2], &[2], &[17], &[5], &[1], &[19]).unwrap();
+        let key_pair = curve.generate_key_pair();
+
+        let signature = curve
+            .sign(key_pair.private_key(), b"Bob -> 1 BTC -> Alice")
+            .unwrap();
+        assert!(curve.verify(key_pair.public_key(), b"Bob -> 1 BTC -> Alice", &signature));
+        let ecdsa = &curve.ecdsa;
+        assert!(ecdsa.verify_standard(
+            &crate::hash::sha256(b"Bob -> 1 BTC -> Alice"),
+            &ecdsa.parse_pub_key(key_pair.public_key()).unwrap(),
+            &ecdsa.decode_signature(&signature).unwrap()
+        ));
+
+        let restored = curve.key_pair(key_pair.private_key()).unwrap();
+        assert_eq!(restored.public_key(), key_pair.public_key());
+        assert!(curve.key_pair(&[0]).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+// QR codes of public keys, addresses and signature envelopes, behind the `qr`
+// feature, so they can cross an air gap through a camera. The terminal form
+// uses half block characters, two rows of modules per line, drawn light on
+// dark for the usual terminal colours; PNG and SVG draw every module as an
+// 8 pixel square. All three keep the 4 module quiet zone scanners need.
+
+use qrcode::render::{svg, unicode};
+use qrcode::{Color, EcLevel, QrCode};
+
+const MODULE_PIXELS: usize = 8;
+const QUIET_ZONE: usize = 4;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum QrFormat {
+    Terminal,
+    Png,
+    Svg,
+}
+
+pub fn render_qr(data: &[u8], format: QrFormat) -> Option<Vec<u8>> {
+    // None when the data does not fit in a QR code, about 2.3 kB at level M
+    let code = QrCode::with_error_correction_level(data, EcLevel::M).ok()?;
+    let rendered = match format {
+        QrFormat::Terminal => code
+            .render::<unicode::Dense1x2>()
+            .dark_color(unicode::Dense1x2::Light)
+            .light_color(unicode::Dense1x2::Dark)
+            .build()
+            .into_bytes(),
+        QrFormat::Svg => code
+            .render::<svg::Color>()
+            .module_dimensions(MODULE_PIXELS as u32, MODULE_PIXELS as u32)
+            .build()
+            .into_bytes(),
+        QrFormat::Png => png(&code),
+    };
+    Some(rendered)
+}
+
+fn png(code: &QrCode) -> Vec<u8> {
+    // 8-bit greyscale, black modules on white
+    let width = code.width();
+    let size = (width + 2 * QUIET_ZONE) * MODULE_PIXELS;
+    let colors = code.to_colors();
+    let mut pixels = vec![0xffu8; size * size];
+    for (i, pixel) in pixels.iter_mut().enumerate() {
+        let module_x = (i % size / MODULE_PIXELS).wrapping_sub(QUIET_ZONE);
+        let module_y = (i / size / MODULE_PIXELS).wrapping_sub(QUIET_ZONE);
+        if module_x < width
+            && module_y < width
+            && colors[module_y * width + module_x] == Color::Dark
+        {
+            *pixel = 0;
+        }
+    }
+
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, size as u32, size as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&pixels).unwrap();
+    writer.finish().unwrap();
+    bytes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ADDRESS: &[u8] = b"1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH";
+
+    #[test]
+    fn test_qr_png() {
+        let code = QrCode::with_error_correction_level(ADDRESS, EcLevel::M).unwrap();
+        let bytes = render_qr(ADDRESS, QrFormat::Png).unwrap();
+        let decoder = png::Decoder::new(&bytes[..]);
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut pixels).unwrap();
+
+        let size = (code.width() + 2 * QUIET_ZONE) * MODULE_PIXELS;
+        assert_eq!(reader.info().width as usize, size);
+        // Quiet zone, then the dark corner of the top left finder pattern
+        let corner = QUIET_ZONE * MODULE_PIXELS;
+        assert_eq!(pixels[(corner - 1) * size + corner - 1], 0xff);
+        assert_eq!(pixels[corner * size + corner], 0);
+        assert_eq!(
+            pixels[(corner + MODULE_PIXELS) * size + corner + MODULE_PIXELS],
+            0xff
+        );
+    }
+
+    #[test]
+    fn test_qr_text_formats() {
+        let terminal = String::from_utf8(render_qr(ADDRESS, QrFormat::Terminal).unwrap()).unwrap();
+        // Two module rows per line, quiet zone included
+        let code = QrCode::new(ADDRESS).unwrap();
+        assert_eq!(
+            terminal.lines().count(),
+            (code.width() + 2 * QUIET_ZONE).div_ceil(2)
+        );
+        let svg = String::from_utf8(render_qr(ADDRESS, QrFormat::Svg).unwrap()).unwrap();
+        assert!(svg.starts_with("<?xml") && svg.contains("<svg"));
+        assert_eq!(render_qr(&[0x5a; 4000], QrFormat::Png), None);
+    }
+}
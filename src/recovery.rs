@@ -0,0 +1,112 @@
+// Public key recovery from a standard ECDSA signature. R is not in the
+// signature, only r = x(R) mod q, so x(R) is one of r, r + q, ... below p,
+// and for each x there are two points, told apart by the parity of y. Every
+// such R gives the one key Q = r⁻¹(sR - zA) under which the signature
+// verifies. The recovery id numbers the choices, 2j + parity for x = r + jq,
+// as Bitcoin and Ethereum transmit it; `recover_candidates` tries them all for
+// formats that leave the id out, and the caller matches the candidates
+// against the keys it knows.
+
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::signer::Signature;
+use crate::ECDSA;
+
+impl ECDSA {
+    pub fn recover_public_key(
+        &self,
+        digest: &[u8],
+        signature: &Signature,
+        recovery_id: u8,
+    ) -> Option<Point> {
+        // None when the id names no point on the curve or a key that is not
+        // valid; digest as `verify_standard` reads it
+        let (r, s) = (signature.r(), signature.s());
+        let q = &self.q_order;
+        let p = &self.elliptic_curve.p;
+        let zero = BigUint::from(0u32);
+        if *r == zero || r >= q || *s == zero || s >= q {
+            return None;
+        }
+        let x = r + q * BigUint::from(recovery_id >> 1);
+        if &x >= p {
+            return None;
+        }
+        let y = self.y_for_x(&x)?;
+        let y_is_odd = recovery_id & 1 == 1;
+        let y = if y.bit(0) == y_is_odd { y } else { (p - y) % p };
+        let point_r = Point::Coor(x, y);
+
+        let z = self.bits_to_scalar(digest);
+        let r_inv = r.modpow(&(q - 2u32), q);
+        let s_r = self.mul_vartime(&point_r, s);
+        let z_a = self.mul_vartime(&self.a_gen, &z);
+        let pub_key = self.mul_vartime(&self.sub_points(&s_r, &z_a), &r_inv);
+        self.is_valid_pub_key(&pub_key).then_some(pub_key)
+    }
+
+    pub fn recover_candidates(&self, digest: &[u8], signature: &Signature) -> Vec<Point> {
+        // Every key the signature verifies under, in order of recovery id;
+        // at most four on curves where q is close to p
+        let q = &self.q_order;
+        let p = &self.elliptic_curve.p;
+        let max_j = (p - 1u32) / q;
+        let ids = 0..=u8::try_from(2u32 * max_j + 1u32).unwrap_or(u8::MAX);
+        let mut candidates: Vec<Point> = Vec::new();
+        for recovery_id in ids {
+            if let Some(pub_key) = self.recover_public_key(digest, signature, recovery_id) {
+                if !candidates.contains(&pub_key) {
+                    candidates.push(pub_key);
+                }
+            }
+        }
+        candidates
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    use crate::curves::secp256k1;
+    use crate::hash::sha256;
+
+    #[test]
+    fn test_recover_candidates() {
+        let ecdsa = toy_curve();
+        let digest = sha256(b"Bob -> 1 BTC -> Alice");
+        for d in 1u32..19 {
+            let priv_key = BigUint::from(d);
+            let pub_key = ecdsa.generate_pub_key(&priv_key);
+            let signature = ecdsa.sign_standard(&mut crate::rng(), &digest, &priv_key);
+            let candidates = ecdsa.recover_candidates(&digest, &signature);
+            assert!(candidates.contains(&pub_key));
+            assert!(candidates.len() <= 4);
+            for candidate in &candidates {
+                assert!(ecdsa.verify_standard(&digest, candidate, &signature));
+            }
+        }
+    }
+
+    #[test]
+    fn test_recover_public_key_secp256k1() {
+        let ecdsa = secp256k1();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let digest = sha256(b"message");
+        let signature = ecdsa.sign_standard(&mut crate::rng(), &digest, &priv_key);
+
+        // r + q is above p for all but a negligible share of r
+        let candidates = ecdsa.recover_candidates(&digest, &signature);
+        assert_eq!(candidates.len(), 2);
+        let recovery_id = candidates.iter().position(|key| *key == pub_key).unwrap();
+        assert_eq!(
+            ecdsa.recover_public_key(&digest, &signature, recovery_id as u8),
+            Some(pub_key)
+        );
+        assert_eq!(ecdsa.recover_public_key(&digest, &signature, 2), None);
+    }
+}
@@ -0,0 +1,19 @@
+// JSON bodies of the remote signing protocol, shared by the `remote-signer`
+// client and the `server` binary. Byte strings travel hex encoded.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct SignRequest {
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SignResponse {
+    pub signature: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PublicKeyResponse {
+    pub public_key: String,
+}
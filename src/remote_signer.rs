@@ -0,0 +1,287 @@
+// Client for keys held by a remote signing service, behind the
+// `remote-signer` feature. The service speaks JSON over HTTP:
+//
+//   GET  {base}/v1/keys/{key_id}/public-key -> {"public_key": hex SEC1 point}
+//   POST {base}/v1/keys/{key_id}/sign {"message": hex} -> {"signature": hex r || s}
+//
+// The message is sent whole and signed by the service with standard ECDSA
+// over SHA-256, as `SigningKey` signs it, so signatures verify with
+// `VerifyingKey` and `verify_standard` like local ones. The public key is
+// fetched once and pinned, and every returned signature is checked against it
+// before it is handed out, so a service that starts answering with another key
+// is caught. `new` trusts whichever key the service reports first; `pinned`
+// takes the key up front and refuses a service that reports any other. The
+// key id is percent-encoded as a single path segment.
+
+use ec_generic::Point;
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use signature::{Error, Verifier};
+
+use crate::remote_api::{PublicKeyResponse, SignRequest, SignResponse};
+use crate::signer::{AsyncSigner, Signature, VerifyingKey};
+use crate::ECDSA;
+
+pub struct RemoteSigner<'a> {
+    ecdsa: &'a ECDSA,
+    client: reqwest::Client,
+    base_url: String,
+    key_id: String,
+    token: Option<String>,
+    expected: Option<VerifyingKey<'a>>,
+    verifying_key: OnceCell<VerifyingKey<'a>>,
+}
+
+impl<'a> RemoteSigner<'a> {
+    pub fn new(ecdsa: &'a ECDSA, base_url: &str, key_id: &str) -> RemoteSigner<'a> {
+        RemoteSigner {
+            ecdsa,
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            key_id: key_id.to_string(),
+            token: None,
+            expected: None,
+            verifying_key: OnceCell::new(),
+        }
+    }
+
+    pub fn pinned(
+        ecdsa: &'a ECDSA,
+        base_url: &str,
+        key_id: &str,
+        pub_key: &Point,
+    ) -> Option<RemoteSigner<'a>> {
+        // None when `pub_key` is not a valid key on the curve
+        let expected = VerifyingKey::new(ecdsa, pub_key.clone())?;
+        Some(RemoteSigner {
+            expected: Some(expected),
+            ..RemoteSigner::new(ecdsa, base_url, key_id)
+        })
+    }
+
+    pub fn with_token(mut self, token: &str) -> RemoteSigner<'a> {
+        // Sent as `Authorization: Bearer <token>`
+        self.token = Some(token.to_string());
+        self
+    }
+
+    pub async fn verifying_key(&self) -> Result<VerifyingKey<'a>, Error> {
+        // The first key the service reports, kept for the signer's lifetime;
+        // an error if it is not the key given to `pinned`
+        if let Some(verifying_key) = self.verifying_key.get() {
+            return Ok(verifying_key.clone());
+        }
+        let url = self.key_url("public-key")?;
+        let response: PublicKeyResponse = self.send(self.client.get(url)).await?;
+        let bytes = hex::decode(response.public_key).map_err(Error::from_source)?;
+        let verifying_key = VerifyingKey::from_bytes(self.ecdsa, &bytes).ok_or_else(Error::new)?;
+        if self
+            .expected
+            .as_ref()
+            .is_some_and(|expected| *expected != verifying_key)
+        {
+            return Err(Error::new());
+        }
+        Ok(self.verifying_key.get_or_init(|| verifying_key).clone())
+    }
+
+    fn key_url(&self, action: &str) -> Result<reqwest::Url, Error> {
+        // {base}/v1/keys/{key_id}/{action}, the key id escaped so that it
+        // cannot name another path or add a query. Dot segments would still
+        // be resolved away, so they are refused.
+        if matches!(self.key_id.as_str(), "" | "." | "..") {
+            return Err(Error::new());
+        }
+        let mut url = reqwest::Url::parse(&self.base_url).map_err(Error::from_source)?;
+        url.path_segments_mut()
+            .map_err(|_| Error::new())?
+            .pop_if_empty()
+            .extend(["v1", "keys", &self.key_id, action]);
+        Ok(url)
+    }
+
+    async fn send<T: for<'de> Deserialize<'de>>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<T, Error> {
+        let request = match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        };
+        request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(Error::from_source)?
+            .json()
+            .await
+            .map_err(Error::from_source)
+    }
+}
+
+impl AsyncSigner for RemoteSigner<'_> {
+    async fn sign_async(&self, msg: &[u8]) -> Result<Signature, Error> {
+        let url = self.key_url("sign")?;
+        let body = SignRequest {
+            message: hex::encode(msg),
+        };
+        let response: SignResponse = self.send(self.client.post(url).json(&body)).await?;
+        let bytes = hex::decode(response.signature).map_err(Error::from_source)?;
+        let signature = self.ecdsa.decode_signature(&bytes).ok_or_else(Error::new)?;
+
+        self.verifying_key().await?.verify(msg, &signature)?;
+        Ok(signature)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+    use num_bigint::BigUint;
+    use signature::Signer;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use crate::signer::SigningKey;
+
+    fn serve(requests: usize, key_path: &'static str, rotate: bool) -> String {
+        // A bare HTTP/1.1 stand-in for the signing service, one request per
+        // connection, for the key at /v1/keys/{key_path}. With `rotate` it
+        // moves to another key once it has reported the first one.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let ecdsa = toy_curve();
+            let mut signing_key = SigningKey::new(&ecdsa, BigUint::from(7u32));
+            let public_key_line = format!("GET /v1/keys/{}/public-key ", key_path);
+            let sign_line = format!("POST /v1/keys/{}/sign ", key_path);
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                let mut authorized = false;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end().to_ascii_lowercase();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(length) = line.strip_prefix("content-length: ") {
+                        content_length = length.parse().unwrap();
+                    }
+                    authorized |= line == "authorization: bearer secret";
+                }
+                let mut body = vec![0u8; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let response = if !authorized {
+                    None
+                } else if request_line.starts_with(&public_key_line) {
+                    let public_key = hex::encode(signing_key.verifying_key().to_bytes());
+                    if rotate {
+                        signing_key = SigningKey::new(&ecdsa, BigUint::from(11u32));
+                    }
+                    serde_json::to_string(&PublicKeyResponse { public_key }).ok()
+                } else if request_line.starts_with(&sign_line) {
+                    let request: SignRequest = serde_json::from_slice(&body).unwrap();
+                    let signature = signing_key.sign(&hex::decode(request.message).unwrap());
+                    let signature = hex::encode(ecdsa.encode_signature(&signature));
+                    serde_json::to_string(&SignResponse { signature }).ok()
+                } else {
+                    None
+                };
+                let reply = match response {
+                    Some(json) => format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                        json.len(),
+                        json
+                    ),
+                    None => "HTTP/1.1 401 Unauthorized\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                        .to_string(),
+                };
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer() {
+        let ecdsa = toy_curve();
+        let base_url = serve(2, "alice", false);
+        let signer = RemoteSigner::new(&ecdsa, &base_url, "alice").with_token("secret");
+
+        let signature = signer.sign_async(b"Bob -> 1 BTC -> Alice").await.unwrap();
+        let verifying_key = signer.verifying_key().await.unwrap();
+        assert!(verifying_key
+            .verify(b"Bob -> 1 BTC -> Alice", &signature)
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_unauthorized() {
+        let ecdsa = toy_curve();
+        let base_url = serve(1, "alice", false);
+        let signer = RemoteSigner::new(&ecdsa, &base_url, "alice");
+        assert!(signer.sign_async(b"Bob -> 1 BTC -> Alice").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_pins_key() {
+        // The service switches keys after the first lookup; signatures by the
+        // new key do not verify against the pinned one
+        let ecdsa = toy_curve();
+        let base_url = serve(2, "alice", true);
+        let signer = RemoteSigner::new(&ecdsa, &base_url, "alice").with_token("secret");
+
+        let pinned = signer.verifying_key().await.unwrap();
+        assert!(signer.sign_async(b"Bob -> 1 BTC -> Alice").await.is_err());
+        assert_eq!(
+            signer.verifying_key().await.unwrap().to_bytes(),
+            pinned.to_bytes()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_expected_key() {
+        // The service holds the key 7 G; a signer expecting 11 G refuses it
+        let ecdsa = toy_curve();
+        let base_url = serve(2, "alice", false);
+        let pub_key = ecdsa.generate_pub_key(&BigUint::from(7u32));
+        let signer = RemoteSigner::pinned(&ecdsa, &base_url, "alice", &pub_key)
+            .unwrap()
+            .with_token("secret");
+        let signature = signer.sign_async(b"Bob -> 1 BTC -> Alice").await.unwrap();
+        assert!(ecdsa.verify_standard(
+            &crate::hash::sha256(b"Bob -> 1 BTC -> Alice"),
+            &pub_key,
+            &signature
+        ));
+
+        let base_url = serve(2, "alice", false);
+        let other_pub = ecdsa.generate_pub_key(&BigUint::from(11u32));
+        let signer = RemoteSigner::pinned(&ecdsa, &base_url, "alice", &other_pub)
+            .unwrap()
+            .with_token("secret");
+        assert!(signer.sign_async(b"Bob -> 1 BTC -> Alice").await.is_err());
+        assert!(signer.verifying_key().await.is_err());
+
+        assert!(RemoteSigner::pinned(&ecdsa, &base_url, "alice", &Point::Identity).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_escapes_key_id() {
+        let ecdsa = toy_curve();
+        let base_url = serve(2, "team%2Falice%3Fv=2", false);
+        let signer = RemoteSigner::new(&ecdsa, &base_url, "team/alice?v=2").with_token("secret");
+        assert!(signer.sign_async(b"Bob -> 1 BTC -> Alice").await.is_ok());
+
+        let signer = RemoteSigner::new(&ecdsa, &base_url, "..").with_token("secret");
+        assert!(signer.sign_async(b"Bob -> 1 BTC -> Alice").await.is_err());
+    }
+}
@@ -0,0 +1,180 @@
+// Integers mod q, the order of the group, for protocols that combine keys,
+// nonces and challenges (MuSig, adaptor signatures, secret sharing). A
+// `Scalar` is always reduced into [0, q - 1] and remembers its curve, so the
+// arithmetic never mixes two moduli and never needs an extra `% q`. Values are
+// treated as secret: equality compares fixed-length bytes without an early
+// exit, inversion goes through `invert_secret`, and Debug prints no digits. `to_bytes` and
+// `from_bytes` use big-endian strings of `scalar_len` bytes.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use num_bigint::BigUint;
+
+use crate::aead::ct_eq;
+use crate::encoding::to_fixed_bytes;
+use crate::secret::{invert_secret, SecretScalar};
+use crate::ECDSA;
+
+#[derive(Clone)]
+pub struct Scalar<'a> {
+    ecdsa: &'a ECDSA,
+    value: BigUint,
+}
+
+impl<'a> Scalar<'a> {
+    pub fn new(ecdsa: &'a ECDSA, value: &BigUint) -> Scalar<'a> {
+        Scalar {
+            ecdsa,
+            value: value % &ecdsa.q_order,
+        }
+    }
+
+    pub fn zero(ecdsa: &'a ECDSA) -> Scalar<'a> {
+        Scalar::new(ecdsa, &BigUint::from(0u32))
+    }
+
+    pub fn one(ecdsa: &'a ECDSA) -> Scalar<'a> {
+        Scalar::new(ecdsa, &BigUint::from(1u32))
+    }
+
+    pub fn random(ecdsa: &'a ECDSA) -> Scalar<'a> {
+        // Uniform in [1, q - 1]
+        Scalar {
+            ecdsa,
+            value: ecdsa.generate_priv_key(),
+        }
+    }
+
+    pub fn from_bytes(ecdsa: &'a ECDSA, bytes: &[u8]) -> Option<Scalar<'a>> {
+        // Exactly scalar_len bytes, None for a value of q or more
+        if bytes.len() != ecdsa.scalar_len() {
+            return None;
+        }
+        let value = BigUint::from_bytes_be(bytes);
+        (value < ecdsa.q_order).then_some(Scalar { ecdsa, value })
+    }
+
+    pub fn from_bytes_reduced(ecdsa: &'a ECDSA, bytes: &[u8]) -> Scalar<'a> {
+        // Any length, taken mod q; with 128 bits more than q the result is
+        // close enough to uniform for hash outputs
+        Scalar::new(ecdsa, &BigUint::from_bytes_be(bytes))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_fixed_bytes(&self.value, self.ecdsa.scalar_len())
+    }
+
+    pub fn to_biguint(&self) -> &BigUint {
+        &self.value
+    }
+
+    pub fn to_secret(&self) -> SecretScalar {
+        SecretScalar::new(self.value.clone())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.ct_eq_value(&BigUint::from(0u32))
+    }
+
+    pub fn add(&self, other: &Scalar) -> Option<Scalar<'a>> {
+        self.same_curve(other)?;
+        Some(self.with(&self.value + &other.value))
+    }
+
+    pub fn sub(&self, other: &Scalar) -> Option<Scalar<'a>> {
+        self.same_curve(other)?;
+        Some(self.with(&self.value + &self.ecdsa.q_order - &other.value))
+    }
+
+    pub fn mul(&self, other: &Scalar) -> Option<Scalar<'a>> {
+        self.same_curve(other)?;
+        Some(self.with(&self.value * &other.value))
+    }
+
+    pub fn negate(&self) -> Scalar<'a> {
+        self.with(&self.ecdsa.q_order - &self.value)
+    }
+
+    pub fn invert(&self) -> Option<Scalar<'a>> {
+        // None for zero, which has no inverse
+        Some(self.with(invert_secret(&self.value, &self.ecdsa.q_order)?))
+    }
+
+    fn same_curve(&self, other: &Scalar) -> Option<()> {
+        let same =
+            core::ptr::eq(self.ecdsa, other.ecdsa) || self.ecdsa.q_order == other.ecdsa.q_order;
+        same.then_some(())
+    }
+
+    fn with(&self, value: BigUint) -> Scalar<'a> {
+        Scalar::new(self.ecdsa, &value)
+    }
+
+    fn ct_eq_value(&self, value: &BigUint) -> bool {
+        let len = self.ecdsa.scalar_len();
+        ct_eq(&self.to_bytes(), &to_fixed_bytes(value, len))
+    }
+}
+
+impl PartialEq for Scalar<'_> {
+    fn eq(&self, other: &Scalar) -> bool {
+        self.same_curve(other).is_some() && self.ct_eq_value(&other.value)
+    }
+}
+
+impl fmt::Debug for Scalar<'_> {
+    // Never prints the value
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Scalar(..)")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    use crate::curves::p256;
+    use crate::point::CurvePoint;
+
+    #[test]
+    fn test_scalar_arithmetic() {
+        let ecdsa = toy_curve();
+        let s = |n: u32| Scalar::new(&ecdsa, &BigUint::from(n));
+        assert_eq!(s(12).add(&s(9)).unwrap(), s(2));
+        assert_eq!(s(3).sub(&s(5)).unwrap(), s(17));
+        assert_eq!(s(7).mul(&s(8)).unwrap(), s(18));
+        assert_eq!(s(4).negate(), s(15));
+        assert_eq!(s(0).negate(), s(0));
+        assert_eq!(s(25), s(6));
+        for n in 1..19 {
+            assert_eq!(s(n).mul(&s(n).invert().unwrap()).unwrap(), s(1));
+        }
+        assert!(s(19).is_zero());
+        assert!(Scalar::zero(&ecdsa).invert().is_none());
+        assert_eq!(format!("{:?}", s(7)), "Scalar(..)");
+    }
+
+    #[test]
+    fn test_scalar_bytes() {
+        let ecdsa = p256();
+        let x = Scalar::random(&ecdsa);
+        let bytes = x.to_bytes();
+        assert_eq!(bytes.len(), 32);
+        assert_eq!(Scalar::from_bytes(&ecdsa, &bytes).unwrap(), x);
+        assert!(Scalar::from_bytes(&ecdsa, &bytes[1..]).is_none());
+        let q = to_fixed_bytes(&ecdsa.q_order, 32);
+        assert!(Scalar::from_bytes(&ecdsa, &q).is_none());
+        assert!(Scalar::from_bytes_reduced(&ecdsa, &q).is_zero());
+
+        // (x + y) G = xG + yG
+        let y = Scalar::random(&ecdsa);
+        let g = CurvePoint::generator(&ecdsa);
+        let sum = g.mul(&x.add(&y).unwrap().to_secret());
+        assert_eq!(
+            sum,
+            g.mul(&x.to_secret()).add(&g.mul(&y.to_secret())).unwrap()
+        );
+    }
+}
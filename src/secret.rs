@@ -0,0 +1,187 @@
+// Constant-time and variable-time scalar multiplication, kept apart by type.
+// Secret scalars (private keys, nonces, ECDH keys) travel as `SecretScalar`,
+// and the only multiplication that takes one is `mul_secret`, the fixed-window
+// ladder with constant-time table lookups. `mul_vartime` takes a plain
+// `BigUint` and is for public scalars only, as in verification, where
+// the faster double-and-add leaks nothing. Getting a secret into the fast path
+// takes a visible call to `expose_secret`. Secret scalars are inverted the
+// same way, by `invert_secret`, a Fermat exponentiation whose sequence of
+// products is fixed by the public modulus alone.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::field::mac;
+use crate::ECDSA;
+
+#[derive(Clone, PartialEq)]
+pub struct SecretScalar(BigUint);
+
+impl SecretScalar {
+    pub fn new(k: BigUint) -> SecretScalar {
+        SecretScalar(k)
+    }
+
+    pub fn expose_secret(&self) -> &BigUint {
+        &self.0
+    }
+}
+
+impl fmt::Debug for SecretScalar {
+    // Never prints the scalar
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretScalar(..)")
+    }
+}
+
+impl ECDSA {
+    pub fn mul_secret(&self, point: &Point, k: &SecretScalar) -> Point {
+        self.mul_point_ct(point, &k.0)
+    }
+
+    pub fn mul_vartime(&self, point: &Point, k: &BigUint) -> Point {
+        // k * P by double-and-add, where k = 0 gives the identity instead of
+        // the `InvalidScalar` error returned by `scalar_mul`
+        if *k == BigUint::from(0u32) {
+            return Point::Identity;
+        }
+        self.elliptic_curve.scalar_mul(point, k).unwrap()
+    }
+}
+
+pub fn invert_secret(a: &BigUint, q: &BigUint) -> Option<BigUint> {
+    // a⁻¹ = a^(q - 2) mod q, q an odd prime. Montgomery products over as many
+    // limbs as q has, with a branch-free final subtraction, and the bits of
+    // the public exponent alone decide between squaring and multiplying, so
+    // the running time does not depend on a. None for a = 0 mod q.
+    if !q.bit(0) || q.bits() < 2 {
+        return None;
+    }
+    let n = q.iter_u64_digits().len();
+    let m = to_limbs(q, n);
+    let a = to_limbs(&(a % q), n);
+    if a.iter().fold(0, |acc, limb| acc | limb) == 0 {
+        return None;
+    }
+    // -q⁻¹ mod 2^64 by Newton's iteration, R = 2^(64n)
+    let mut m_inv = 1u64;
+    for _ in 0..6 {
+        m_inv = m_inv.wrapping_mul(2u64.wrapping_sub(m[0].wrapping_mul(m_inv)));
+    }
+    let m_inv = m_inv.wrapping_neg();
+    let r2 = to_limbs(&((BigUint::from(1u32) << (128 * n)) % q), n);
+    let a_mont = mont_mul(&a, &r2, &m, m_inv);
+
+    let exponent = q - 2u32;
+    let mut result = to_limbs(&((BigUint::from(1u32) << (64 * n)) % q), n);
+    for bit in (0..exponent.bits()).rev() {
+        result = mont_mul(&result, &result, &m, m_inv);
+        if exponent.bit(bit) {
+            result = mont_mul(&result, &a_mont, &m, m_inv);
+        }
+    }
+    let mut one = vec![0u64; n];
+    one[0] = 1;
+    let result = mont_mul(&result, &one, &m, m_inv);
+    Some(
+        result
+            .iter()
+            .rev()
+            .fold(BigUint::from(0u32), |acc, &limb| (acc << 64) + limb),
+    )
+}
+
+fn mont_mul(a: &[u64], b: &[u64], m: &[u64], m_inv: u64) -> Vec<u64> {
+    // a * b * R⁻¹ mod m, one limb of a and one REDC step per round (CIOS)
+    let n = m.len();
+    let mut t = vec![0u64; n + 2];
+    for &a_i in a {
+        let mut carry = 0;
+        for j in 0..n {
+            (t[j], carry) = mac(t[j], a_i, b[j], carry);
+        }
+        let (sum, overflow) = t[n].overflowing_add(carry);
+        t[n] = sum;
+        t[n + 1] = overflow as u64;
+
+        let u = t[0].wrapping_mul(m_inv);
+        let (_, mut carry) = mac(t[0], u, m[0], 0);
+        for j in 1..n {
+            (t[j - 1], carry) = mac(t[j], u, m[j], carry);
+        }
+        let (sum, overflow) = t[n].overflowing_add(carry);
+        t[n - 1] = sum;
+        t[n] = t[n + 1] + overflow as u64;
+    }
+    // t < 2m: subtract m, and keep t instead when that borrows out of the top
+    let mut difference = vec![0u64; n];
+    let mut borrow = 0u64;
+    for j in 0..n {
+        let (d, underflow1) = t[j].overflowing_sub(m[j]);
+        let (d, underflow2) = d.overflowing_sub(borrow);
+        difference[j] = d;
+        borrow = (underflow1 | underflow2) as u64;
+    }
+    let keep = (t[n].overflowing_sub(borrow).1 as u64).wrapping_neg();
+    (0..n)
+        .map(|j| (t[j] & keep) | (difference[j] & !keep))
+        .collect()
+}
+
+fn to_limbs(value: &BigUint, n: usize) -> Vec<u64> {
+    let mut limbs = vec![0u64; n];
+    for (limb, digit) in limbs.iter_mut().zip(value.iter_u64_digits()) {
+        *limb = digit;
+    }
+    limbs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_secret_and_vartime_paths_agree() {
+        let ecdsa = toy_curve();
+        for k in 0u32..19 {
+            let secret = SecretScalar::new(BigUint::from(k));
+            assert_eq!(
+                ecdsa.mul_secret(&ecdsa.a_gen, &secret),
+                ecdsa.mul_vartime(&ecdsa.a_gen, secret.expose_secret())
+            );
+        }
+        let secret = SecretScalar::new(BigUint::from(7u32));
+        assert_eq!(format!("{:?}", secret), "SecretScalar(..)");
+    }
+
+    #[test]
+    fn test_invert_secret() {
+        let q = BigUint::from(19u32);
+        for a in 1u32..19 {
+            let inverse = invert_secret(&BigUint::from(a), &q).unwrap();
+            assert_eq!(inverse * a % &q, BigUint::from(1u32));
+        }
+        assert_eq!(invert_secret(&BigUint::from(0u32), &q), None);
+        assert_eq!(invert_secret(&q, &q), None);
+        assert_eq!(
+            invert_secret(&BigUint::from(3u32), &BigUint::from(20u32)),
+            None
+        );
+
+        // Several limbs, and values reaching the top limb
+        let q = crate::curves::p256().q_order;
+        for a in [
+            BigUint::from(2u32),
+            &q - 1u32,
+            (BigUint::from(1u32) << 200) + 12345u32,
+        ] {
+            let inverse = invert_secret(&a, &q).unwrap();
+            assert_eq!(inverse, a.modpow(&(&q - 2u32), &q));
+        }
+    }
+}
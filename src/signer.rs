@@ -0,0 +1,688 @@
+// Key types implementing the RustCrypto `signature` traits, so code that is
+// generic over `Signer`/`Verifier` can use this crate's ECDSA. Messages are
+// hashed with SHA-256 and signed with `sign_standard`: bits2int of the digest
+// and r = x(R) mod q, as in SEC1 and FIPS 186, so signatures interoperate
+// with OpenSSL and the RustCrypto `p256` crate. They are not those of
+// `sign`/`verification`, which hash with `generate_hash_less_than` and keep
+// r = x(R) unreduced.
+//
+// Signatures and verifying keys can be kept in a `HashSet` or used as
+// `BTreeMap` keys. Equality compares without an early exit, keys by their
+// compressed SEC1 encoding and curve, signatures by r || s. Keys sort by that
+// encoding (then by curve parameters), signatures by r and then s.
+
+use alloc::{vec, vec::Vec};
+use core::cmp::Ordering;
+use core::future::Future;
+use core::hash::{Hash, Hasher};
+use ec_generic::Point;
+use num_bigint::{BigUint, RandBigInt};
+use signature::digest::Digest;
+use signature::rand_core::CryptoRngCore;
+use signature::{DigestSigner, DigestVerifier, Error, RandomizedSigner, Signer, Verifier};
+
+use crate::aead::ct_eq;
+use crate::der;
+use crate::encoding::to_fixed_bytes;
+use crate::group;
+use crate::hash::{frame_context, sha256};
+use crate::ECDSA;
+
+const DST_CONTEXT: &[u8] = b"RS_ECC-ECDSA-ctx";
+
+// Signing that may wait on something else, such as a remote signing service,
+// so local and remote keys can be used through the same abstraction
+pub trait AsyncSigner {
+    fn sign_async(&self, msg: &[u8]) -> impl Future<Output = Result<Signature, Error>> + Send;
+}
+
+#[derive(Clone, Debug)]
+pub struct Signature {
+    r: BigUint,
+    s: BigUint,
+}
+
+pub struct SigningKey<'a> {
+    ecdsa: &'a ECDSA,
+    priv_key: BigUint,
+}
+
+// How strictly a DER signature is read. `Strict` takes only the canonical
+// encoding, as consensus rules (BIP 66) demand; `LenientBer` also takes the
+// BER that older smartcards and HSMs emit: non-minimal lengths, an indefinite
+// length SEQUENCE and INTEGERs with redundant 0x00 padding
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum DerMode {
+    #[default]
+    Strict,
+    LenientBer,
+}
+
+// A DER signature parsed in place: r and s are the big-endian magnitudes
+// inside the input, without the sign padding byte
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct DerSignatureRef<'a> {
+    r: &'a [u8],
+    s: &'a [u8],
+}
+
+#[derive(Clone)]
+pub struct VerifyingKey<'a> {
+    ecdsa: &'a ECDSA,
+    pub_key: Point,
+}
+
+impl ECDSA {
+    pub fn encode_signature(&self, signature: &Signature) -> Vec<u8> {
+        // r || s, r padded to the field size since it is x(R) and not reduced
+        // mod q, s padded to the scalar size
+        let mut bytes = to_fixed_bytes(&signature.r, self.field_len());
+        bytes.extend(to_fixed_bytes(&signature.s, self.scalar_len()));
+        bytes
+    }
+
+    pub fn decode_signature(&self, bytes: &[u8]) -> Option<Signature> {
+        if bytes.len() != self.field_len() + self.scalar_len() {
+            return None;
+        }
+        let (r, s) = bytes.split_at(self.field_len());
+        Some(Signature {
+            r: BigUint::from_bytes_be(r),
+            s: BigUint::from_bytes_be(s),
+        })
+    }
+
+    pub fn sign_standard(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        digest: &[u8],
+        priv_key: &BigUint,
+    ) -> Signature {
+        // Plain ECDSA as in SEC1 and FIPS 186, which TLS and SSH peers run:
+        // z = leftmost bits of the digest, r = x(R) mod q. 256-bit curves go
+        // through the fixed-width backend
+        let q = &self.q_order;
+        let hash = self.bits_to_scalar(digest);
+        let mut k_random = || rng.gen_biguint_range(&BigUint::from(1u32), q);
+        if let Some(signature) = self.sign_fixed(&mut k_random, &hash, priv_key) {
+            return signature;
+        }
+        let zero = BigUint::from(0u32);
+        loop {
+            let Some((r, s)) = group::sign(self, &hash, priv_key, &k_random()) else {
+                continue;
+            };
+            if r != zero && &r < q && s != zero {
+                return Signature { r, s };
+            }
+        }
+    }
+
+    pub fn sign_with_context(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        context: &[u8],
+        message: &[u8],
+        priv_key: &BigUint,
+    ) -> Option<Signature> {
+        // `sign_standard` over SHA-256(DST || len(context) || context || m),
+        // so a signature made for one protocol never verifies in another. None
+        // unless the context is 1 to 255 bytes
+        let digest = self.context_digest(context, message)?;
+        Some(self.sign_standard(rng, &digest, priv_key))
+    }
+
+    pub fn verify_with_context(
+        &self,
+        context: &[u8],
+        message: &[u8],
+        pub_key: &Point,
+        signature: &Signature,
+    ) -> bool {
+        self.context_digest(context, message)
+            .is_some_and(|digest| self.verify_standard(&digest, pub_key, signature))
+    }
+
+    fn context_digest(&self, context: &[u8], message: &[u8]) -> Option<Vec<u8>> {
+        Some(sha256(
+            &[DST_CONTEXT, &frame_context(context, message)?].concat(),
+        ))
+    }
+
+    pub fn verify_standard(&self, digest: &[u8], pub_key: &Point, signature: &Signature) -> bool {
+        // x(u1A + u2B) mod q = r
+        let q = &self.q_order;
+        let zero = BigUint::from(0u32);
+        let (r, s) = (&signature.r, &signature.s);
+        if !self.is_valid_pub_key(pub_key) || *r == zero || r >= q || *s == zero || s >= q {
+            return false;
+        }
+        let hash = self.bits_to_scalar(digest);
+        if let Some(valid) = self.verify_fixed(&hash, pub_key, signature) {
+            return valid;
+        }
+        let s_inv = s.modpow(&(q - BigUint::from(2u32)), q);
+        let u1 = (&s_inv * hash) % q;
+        let u2 = (&s_inv * r) % q;
+        let p = self.add_points(
+            &self.mul_vartime(&self.a_gen, &u1),
+            &self.mul_vartime(pub_key, &u2),
+        );
+        matches!(p, Point::Coor(x, _) if &(&x % q) == r)
+    }
+
+    pub fn verify_der(&self, digest: &[u8], pub_key: &Point, der: &[u8], mode: DerMode) -> bool {
+        // `verify_standard` on a DER signature read in the given mode
+        Signature::from_der_with(der, mode)
+            .is_some_and(|signature| self.verify_standard(digest, pub_key, &signature))
+    }
+
+    pub fn bits_to_scalar(&self, digest: &[u8]) -> BigUint {
+        // bits2int followed by a reduction mod q
+        let z = BigUint::from_bytes_be(digest);
+        let excess = (digest.len() as u64 * 8).saturating_sub(self.q_order.bits());
+        (z >> excess) % &self.q_order
+    }
+
+    fn sign_checked(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        digest: &[u8],
+        priv_key: &BigUint,
+    ) -> Result<Signature, Error> {
+        // `sign_standard`, with the result checked against the reference
+        // implementation in differential mode
+        let signature = self.sign_standard(rng, digest, priv_key);
+        #[cfg(feature = "differential")]
+        self.differential_check(digest, priv_key, &signature)?;
+        Ok(signature)
+    }
+}
+
+impl Signature {
+    pub fn new(r: BigUint, s: BigUint) -> Signature {
+        Signature { r, s }
+    }
+
+    pub fn r(&self) -> &BigUint {
+        &self.r
+    }
+
+    pub fn s(&self) -> &BigUint {
+        &self.s
+    }
+
+    pub fn to_der(&self) -> Vec<u8> {
+        // SEQUENCE { INTEGER r, INTEGER s }, as in X.509 and TLS
+        let mut body = der::integer(&self.r);
+        body.extend(der::integer(&self.s));
+        der::write(der::SEQUENCE, &body)
+    }
+
+    pub fn from_der(bytes: &[u8]) -> Option<Signature> {
+        Some(DerSignatureRef::parse(bytes)?.to_signature())
+    }
+
+    pub fn from_der_with(bytes: &[u8], mode: DerMode) -> Option<Signature> {
+        Some(DerSignatureRef::parse_with(bytes, mode)?.to_signature())
+    }
+}
+
+impl PartialEq for Signature {
+    fn eq(&self, other: &Signature) -> bool {
+        // r || s with both values padded to the longest of the four
+        let len = [&self.r, &self.s, &other.r, &other.s]
+            .iter()
+            .map(|n| n.bits().div_ceil(8) as usize)
+            .max()
+            .unwrap();
+        let bytes = |signature: &Signature| {
+            [
+                to_fixed_bytes(&signature.r, len),
+                to_fixed_bytes(&signature.s, len),
+            ]
+            .concat()
+        };
+        ct_eq(&bytes(self), &bytes(other))
+    }
+}
+
+impl Eq for Signature {}
+
+impl Hash for Signature {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.r.hash(state);
+        self.s.hash(state);
+    }
+}
+
+impl PartialOrd for Signature {
+    fn partial_cmp(&self, other: &Signature) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Signature {
+    fn cmp(&self, other: &Signature) -> Ordering {
+        (&self.r, &self.s).cmp(&(&other.r, &other.s))
+    }
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Signature, Error> {
+        // DER, as `from_der`
+        Signature::from_der(bytes).ok_or_else(Error::new)
+    }
+}
+
+impl<'a> DerSignatureRef<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Option<DerSignatureRef<'a>> {
+        // Only the canonical encoding is accepted, checked in the same pass
+        // that finds r and s, without copying them out
+        let (tag, body, rest) = der::read(bytes)?;
+        if tag != der::SEQUENCE || !rest.is_empty() {
+            return None;
+        }
+        let (r, rest) = der::read_integer(body)?;
+        let (s, rest) = der::read_integer(rest)?;
+        if !rest.is_empty() {
+            return None;
+        }
+        Some(DerSignatureRef { r, s })
+    }
+
+    pub fn parse_with(bytes: &'a [u8], mode: DerMode) -> Option<DerSignatureRef<'a>> {
+        // Even lenient, nothing may follow the SEQUENCE and r and s must be
+        // non-negative INTEGERs
+        if mode == DerMode::Strict {
+            return DerSignatureRef::parse(bytes);
+        }
+        let body = match bytes {
+            // Indefinite length, closed by the end-of-contents octets
+            [der::SEQUENCE, 0x80, body @ .., 0x00, 0x00] => body,
+            _ => match der::read_ber(bytes)? {
+                (der::SEQUENCE, body, []) => body,
+                _ => return None,
+            },
+        };
+        let (r, rest) = der::read_integer_ber(body)?;
+        let (s, rest) = der::read_integer_ber(rest)?;
+        if !rest.is_empty() {
+            return None;
+        }
+        Some(DerSignatureRef { r, s })
+    }
+
+    pub fn r_bytes(&self) -> &'a [u8] {
+        self.r
+    }
+
+    pub fn s_bytes(&self) -> &'a [u8] {
+        self.s
+    }
+
+    pub fn to_signature(self) -> Signature {
+        Signature {
+            r: BigUint::from_bytes_be(self.r),
+            s: BigUint::from_bytes_be(self.s),
+        }
+    }
+}
+
+impl<'a> SigningKey<'a> {
+    pub fn new(ecdsa: &'a ECDSA, priv_key: BigUint) -> SigningKey<'a> {
+        assert!(
+            priv_key < ecdsa.q_order && priv_key != BigUint::from(0u32),
+            "Private Key must be in [1, q - 1]"
+        );
+        SigningKey { ecdsa, priv_key }
+    }
+
+    pub fn from_bytes(ecdsa: &'a ECDSA, bytes: &[u8]) -> Option<SigningKey<'a>> {
+        // Big-endian scalar of exactly `scalar_len` bytes, as `to_bytes`
+        // writes it (the JWK "d" member, Ethereum and libp2p keys), None
+        // instead of a panic outside [1, q - 1]
+        if bytes.len() != ecdsa.scalar_len() {
+            return None;
+        }
+        let priv_key = BigUint::from_bytes_be(bytes);
+        if priv_key == BigUint::from(0u32) || priv_key >= ecdsa.q_order {
+            return None;
+        }
+        Some(SigningKey { ecdsa, priv_key })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_fixed_bytes(&self.priv_key, self.ecdsa.scalar_len())
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey<'a> {
+        VerifyingKey {
+            ecdsa: self.ecdsa,
+            pub_key: self.ecdsa.generate_pub_key(&self.priv_key),
+        }
+    }
+}
+
+impl<'a> VerifyingKey<'a> {
+    pub fn new(ecdsa: &'a ECDSA, pub_key: Point) -> Option<VerifyingKey<'a>> {
+        if !ecdsa.is_valid_pub_key(&pub_key) {
+            return None;
+        }
+        Some(VerifyingKey { ecdsa, pub_key })
+    }
+
+    pub fn from_bytes(ecdsa: &'a ECDSA, bytes: &[u8]) -> Option<VerifyingKey<'a>> {
+        // Compressed or uncompressed SEC1 point, see `parse_pub_key`
+        let pub_key = ecdsa.parse_pub_key(bytes).ok()?;
+        Some(VerifyingKey { ecdsa, pub_key })
+    }
+
+    pub fn from_xy_bytes(ecdsa: &'a ECDSA, bytes: &[u8]) -> Option<VerifyingKey<'a>> {
+        // x || y with no prefix, as Ethereum hashes keys into addresses
+        if bytes.len() != 2 * ecdsa.field_len() {
+            return None;
+        }
+        VerifyingKey::from_bytes(ecdsa, &[&[0x04], bytes].concat())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.ecdsa.encode_point(&self.pub_key)
+    }
+
+    pub fn to_uncompressed_bytes(&self) -> Vec<u8> {
+        self.ecdsa.encode_point_uncompressed(&self.pub_key)
+    }
+
+    pub fn to_xy_bytes(&self) -> Vec<u8> {
+        self.to_uncompressed_bytes()[1..].to_vec()
+    }
+
+    pub fn pub_key(&self) -> &Point {
+        &self.pub_key
+    }
+
+    pub fn ecdsa(&self) -> &'a ECDSA {
+        self.ecdsa
+    }
+
+    fn curve_parameters(&self) -> [&BigUint; 4] {
+        let curve = &self.ecdsa.elliptic_curve;
+        [&curve.p, &curve.a, &curve.b, &self.ecdsa.q_order]
+    }
+}
+
+impl PartialEq for VerifyingKey<'_> {
+    fn eq(&self, other: &VerifyingKey) -> bool {
+        self.ecdsa.same_parameters(other.ecdsa) && ct_eq(&self.to_bytes(), &other.to_bytes())
+    }
+}
+
+impl Eq for VerifyingKey<'_> {}
+
+impl Hash for VerifyingKey<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_bytes().hash(state);
+    }
+}
+
+impl PartialOrd for VerifyingKey<'_> {
+    fn partial_cmp(&self, other: &VerifyingKey) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for VerifyingKey<'_> {
+    fn cmp(&self, other: &VerifyingKey) -> Ordering {
+        // The generator breaks the last ties between curves, as in
+        // `same_parameters`
+        self.to_bytes()
+            .cmp(&other.to_bytes())
+            .then_with(|| self.curve_parameters().cmp(&other.curve_parameters()))
+            .then_with(|| {
+                let generator = |key: &VerifyingKey| key.ecdsa.encode_point(&key.ecdsa.a_gen);
+                generator(self).cmp(&generator(other))
+            })
+    }
+}
+
+impl Signer<Signature> for SigningKey<'_> {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature, Error> {
+        self.try_sign_with_rng(&mut crate::rng(), msg)
+    }
+}
+
+impl AsyncSigner for SigningKey<'_> {
+    async fn sign_async(&self, msg: &[u8]) -> Result<Signature, Error> {
+        self.try_sign(msg)
+    }
+}
+
+impl RandomizedSigner<Signature> for SigningKey<'_> {
+    fn try_sign_with_rng(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        msg: &[u8],
+    ) -> Result<Signature, Error> {
+        self.ecdsa.sign_checked(rng, &sha256(msg), &self.priv_key)
+    }
+}
+
+impl<D: Digest> DigestSigner<D, Signature> for SigningKey<'_> {
+    fn try_sign_digest(&self, digest: D) -> Result<Signature, Error> {
+        self.ecdsa
+            .sign_checked(&mut crate::rng(), &digest.finalize(), &self.priv_key)
+    }
+}
+
+impl Verifier<Signature> for VerifyingKey<'_> {
+    fn verify(&self, msg: &[u8], signature: &Signature) -> Result<(), Error> {
+        if self
+            .ecdsa
+            .verify_standard(&sha256(msg), &self.pub_key, signature)
+        {
+            return Ok(());
+        }
+        Err(Error::new())
+    }
+}
+
+impl<D: Digest> DigestVerifier<D, Signature> for VerifyingKey<'_> {
+    fn verify_digest(&self, digest: D, signature: &Signature) -> Result<(), Error> {
+        if self
+            .ecdsa
+            .verify_standard(&digest.finalize(), &self.pub_key, signature)
+        {
+            return Ok(());
+        }
+        Err(Error::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_signer_verifier() {
+        let ecdsa = toy_curve();
+        let signing_key = SigningKey::new(&ecdsa, BigUint::from(7u32));
+        let verifying_key = signing_key.verifying_key();
+
+        let signature: Signature = signing_key.sign(b"Bob -> 1 BTC -> Alice");
+        assert!(verifying_key
+            .verify(b"Bob -> 1 BTC -> Alice", &signature)
+            .is_ok());
+
+        // Interoperates with `sign_standard`/`verify_standard`
+        let digest = sha256(b"Bob -> 1 BTC -> Alice");
+        assert!(signature.r() < &ecdsa.q_order);
+        assert!(ecdsa.verify_standard(&digest, verifying_key.pub_key(), &signature));
+        let signature = ecdsa.sign_standard(&mut crate::rng(), &digest, &BigUint::from(7u32));
+        assert!(verifying_key
+            .verify(b"Bob -> 1 BTC -> Alice", &signature)
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_async_signer() {
+        let ecdsa = toy_curve();
+        let signing_key = SigningKey::new(&ecdsa, BigUint::from(7u32));
+
+        let signature = signing_key
+            .sign_async(b"Bob -> 1 BTC -> Alice")
+            .await
+            .unwrap();
+        assert!(signing_key
+            .verifying_key()
+            .verify(b"Bob -> 1 BTC -> Alice", &signature)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_randomized_signer_tampered() {
+        let ecdsa = toy_curve();
+        let signing_key = SigningKey::new(&ecdsa, BigUint::from(7u32));
+        let verifying_key = signing_key.verifying_key();
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let signature = signing_key.sign_with_rng(&mut rng, b"Bob -> 1 BTC -> Alice");
+        assert!(verifying_key
+            .verify(b"Bob -> 2 BTC -> Alice", &signature)
+            .is_err());
+
+        let restored = VerifyingKey::from_bytes(&ecdsa, &verifying_key.to_bytes()).unwrap();
+        assert_eq!(restored.pub_key(), verifying_key.pub_key());
+        assert!(SigningKey::from_bytes(&ecdsa, &[19]).is_none());
+
+        let bytes = ecdsa.encode_signature(&signature);
+        assert_eq!(ecdsa.decode_signature(&bytes), Some(signature.clone()));
+        assert_eq!(ecdsa.decode_signature(&bytes[1..]), None);
+
+        let der = signature.to_der();
+        assert_eq!(Signature::from_der(&der), Some(signature.clone()));
+        assert_eq!(Signature::from_der(&der[..der.len() - 1]), None);
+        // 0x80 needs a leading zero byte to stay positive
+        let high = Signature::new(BigUint::from(0x80u32), BigUint::from(1u32));
+        assert_eq!(hex::encode(high.to_der()), "300702020080020101");
+
+        // The view points into the input, past the padding byte
+        let der = high.to_der();
+        let view = DerSignatureRef::parse(&der).unwrap();
+        assert_eq!(view.r_bytes(), &[0x80]);
+        assert!(core::ptr::eq(view.r_bytes(), &der[5..6]));
+        assert_eq!(view.to_signature(), high);
+        // Not minimal: a padding byte before a low top bit
+        assert_eq!(
+            DerSignatureRef::parse(&hex::decode("30070202000102020101").unwrap()),
+            None
+        );
+
+        let forged = Signature::new(signature.r().clone(), BigUint::from(0u32));
+        assert!(verifying_key
+            .verify(b"Bob -> 1 BTC -> Alice", &forged)
+            .is_err());
+    }
+
+    #[test]
+    fn test_raw_key_bytes() {
+        let ecdsa = crate::curves::secp256k1();
+        let priv_bytes = to_fixed_bytes(&BigUint::from(0xc0ffeeu32), 32);
+        let signing_key = SigningKey::from_bytes(&ecdsa, &priv_bytes).unwrap();
+        assert_eq!(signing_key.to_bytes(), priv_bytes);
+        assert!(SigningKey::from_bytes(&ecdsa, &priv_bytes[1..]).is_none());
+        assert!(SigningKey::from_bytes(&ecdsa, &[&[0], &priv_bytes[..]].concat()).is_none());
+        assert!(SigningKey::from_bytes(&ecdsa, &[0; 32]).is_none());
+        assert!(SigningKey::from_bytes(&ecdsa, &[0xff; 32]).is_none());
+
+        let verifying_key = signing_key.verifying_key();
+        let compressed = verifying_key.to_bytes();
+        let uncompressed = verifying_key.to_uncompressed_bytes();
+        let xy = verifying_key.to_xy_bytes();
+        assert_eq!(
+            (compressed.len(), uncompressed.len(), xy.len()),
+            (33, 65, 64)
+        );
+        for key in [
+            VerifyingKey::from_bytes(&ecdsa, &compressed),
+            VerifyingKey::from_bytes(&ecdsa, &uncompressed),
+            VerifyingKey::from_xy_bytes(&ecdsa, &xy),
+        ] {
+            assert_eq!(key.unwrap().pub_key(), verifying_key.pub_key());
+        }
+        assert!(VerifyingKey::from_xy_bytes(&ecdsa, &uncompressed).is_none());
+        assert!(VerifyingKey::from_xy_bytes(&ecdsa, &xy[1..]).is_none());
+        assert!(VerifyingKey::from_bytes(&ecdsa, &uncompressed[..64]).is_none());
+    }
+
+    #[test]
+    fn test_keys_and_signatures_as_map_keys() {
+        use alloc::collections::BTreeSet;
+        use std::collections::HashSet;
+
+        let ecdsa = toy_curve();
+        let keys: Vec<VerifyingKey> = [3u32, 7, 3, 11, 7]
+            .iter()
+            .map(|&d| SigningKey::new(&ecdsa, BigUint::from(d)).verifying_key())
+            .collect();
+        assert!(keys[0] == keys[2]);
+        assert!(keys[0] != keys[1]);
+        assert_eq!(keys.iter().collect::<HashSet<_>>().len(), 3);
+        let sorted: Vec<Vec<u8>> = keys
+            .iter()
+            .collect::<BTreeSet<_>>()
+            .iter()
+            .map(|key| key.to_bytes())
+            .collect();
+        assert_eq!(sorted.len(), 3);
+        assert!(sorted.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // Same point on another curve: a different key
+        let other = ECDSA {
+            a_gen: ecdsa.mul_vartime(&ecdsa.a_gen, &BigUint::from(2u32)),
+            ..toy_curve()
+        };
+        let moved = VerifyingKey::new(&other, keys[0].pub_key().clone()).unwrap();
+        assert!(moved != keys[0]);
+        assert_ne!(moved.cmp(&keys[0]), Ordering::Equal);
+
+        let one = Signature::new(BigUint::from(1u32), BigUint::from(0x1234u32));
+        let two = Signature::new(BigUint::from(2u32), BigUint::from(1u32));
+        assert_eq!(
+            one,
+            Signature::new(BigUint::from(1u32), BigUint::from(0x1234u32))
+        );
+        assert_ne!(
+            one,
+            Signature::new(BigUint::from(0x1234u32), BigUint::from(1u32))
+        );
+        assert!(one < two);
+        let set: HashSet<Signature> = [one.clone(), two, one].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_context_signatures() {
+        let ecdsa = crate::curves::p256();
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let signature = ecdsa
+            .sign_with_context(&mut rng, b"payments", b"pay 1", &priv_key)
+            .unwrap();
+        assert!(ecdsa.verify_with_context(b"payments", b"pay 1", &pub_key, &signature));
+        assert!(!ecdsa.verify_with_context(b"login", b"pay 1", &pub_key, &signature));
+        assert!(!ecdsa.verify_standard(&sha256(b"pay 1"), &pub_key, &signature));
+
+        assert!(ecdsa
+            .sign_with_context(&mut rng, b"", b"pay 1", &priv_key)
+            .is_none());
+        assert!(!ecdsa.verify_with_context(&[1; 256], b"pay 1", &pub_key, &signature));
+    }
+}
@@ -0,0 +1,360 @@
+// Remote signing service behind the `server` feature, the other end of
+// `RemoteSigner`. Keys come from a JSON key store:
+//
+//   {"curve": {"a": hex, "b": hex, "p": hex, "gx": hex, "gy": hex, "q": hex},
+//    "keys": {"<key id>": hex private key, ...}}
+//
+// Every request goes through an `Authenticator` before the key is looked up,
+// so unauthorized callers cannot probe which key ids exist, and every
+// decision is handed to an `AuditLog`, with the SHA-256 of the message rather
+// than the message itself.
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::extract::{Path, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use ec_generic::{EllipticCurve, Point};
+use num_bigint::BigUint;
+use serde::Deserialize;
+use signature::Signer;
+use tokio::net::TcpListener;
+
+use crate::aead::ct_eq;
+use crate::hash::sha256;
+use crate::remote_api::{PublicKeyResponse, SignRequest, SignResponse};
+use crate::signer::SigningKey;
+use crate::ECDSA;
+
+pub struct KeyStore {
+    ecdsa: ECDSA,
+    keys: BTreeMap<String, BigUint>,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Operation {
+    PublicKey,
+    Sign,
+}
+
+pub trait Authenticator: Send + Sync {
+    // `token` is the bearer token of the request, if any
+    fn authorize(&self, token: Option<&str>, key_id: &str, operation: Operation) -> bool;
+}
+
+pub struct AuditEvent<'a> {
+    pub key_id: &'a str,
+    pub operation: Operation,
+    pub allowed: bool,
+    pub message_hash: Option<Vec<u8>>,
+}
+
+pub trait AuditLog: Send + Sync {
+    fn record(&self, event: &AuditEvent);
+}
+
+// Accepts a single shared bearer token for every key
+pub struct BearerToken(pub String);
+
+// Writes one JSON object per event
+pub struct JsonLinesAudit<W: Write + Send>(pub Mutex<W>);
+
+struct ServerState {
+    store: KeyStore,
+    authenticator: Box<dyn Authenticator>,
+    audit: Box<dyn AuditLog>,
+}
+
+#[derive(Deserialize)]
+struct KeyStoreFile {
+    curve: CurveParams,
+    keys: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct CurveParams {
+    a: String,
+    b: String,
+    p: String,
+    gx: String,
+    gy: String,
+    q: String,
+}
+
+impl KeyStore {
+    pub fn from_json(json: &str) -> Option<KeyStore> {
+        let file: KeyStoreFile = serde_json::from_str(json).ok()?;
+        let hex = |value: &str| BigUint::parse_bytes(value.as_bytes(), 16);
+        let curve = &file.curve;
+        let ecdsa = ECDSA::new(
+            EllipticCurve {
+                a: hex(&curve.a)?,
+                b: hex(&curve.b)?,
+                p: hex(&curve.p)?,
+            },
+            Point::Coor(hex(&curve.gx)?, hex(&curve.gy)?),
+            hex(&curve.q)?,
+        )?;
+
+        let mut keys = BTreeMap::new();
+        for (key_id, priv_key) in file.keys {
+            let priv_key = hex(&priv_key)?;
+            if priv_key == BigUint::from(0u32) || priv_key >= ecdsa.q_order {
+                return None;
+            }
+            keys.insert(key_id, priv_key);
+        }
+        Some(KeyStore { ecdsa, keys })
+    }
+
+    pub fn load(path: &str) -> io::Result<KeyStore> {
+        KeyStore::from_json(&std::fs::read_to_string(path)?)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid key store"))
+    }
+
+    fn signing_key(&self, key_id: &str) -> Option<SigningKey<'_>> {
+        let priv_key = self.keys.get(key_id)?;
+        Some(SigningKey::new(&self.ecdsa, priv_key.clone()))
+    }
+}
+
+impl<F> Authenticator for F
+where
+    F: Fn(Option<&str>, &str, Operation) -> bool + Send + Sync,
+{
+    fn authorize(&self, token: Option<&str>, key_id: &str, operation: Operation) -> bool {
+        self(token, key_id, operation)
+    }
+}
+
+impl Authenticator for BearerToken {
+    fn authorize(&self, token: Option<&str>, _key_id: &str, _operation: Operation) -> bool {
+        token.is_some_and(|token| ct_eq(token.as_bytes(), self.0.as_bytes()))
+    }
+}
+
+impl<W: Write + Send> AuditLog for JsonLinesAudit<W> {
+    fn record(&self, event: &AuditEvent) {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let line = serde_json::json!({
+            "time": time,
+            "key_id": event.key_id,
+            "operation": format!("{:?}", event.operation),
+            "allowed": event.allowed,
+            "message_sha256": event.message_hash.as_ref().map(hex::encode),
+        });
+        // An audit trail that cannot be written is not silently skipped
+        let mut writer = self.0.lock().unwrap();
+        writeln!(writer, "{}", line).expect("Could not write the audit log");
+    }
+}
+
+pub fn router(
+    store: KeyStore,
+    authenticator: Box<dyn Authenticator>,
+    audit: Box<dyn AuditLog>,
+) -> Router {
+    let state = Arc::new(ServerState {
+        store,
+        authenticator,
+        audit,
+    });
+    Router::new()
+        .route("/v1/keys/:key_id/public-key", get(public_key))
+        .route("/v1/keys/:key_id/sign", post(sign))
+        .with_state(state)
+}
+
+pub async fn serve(listener: TcpListener, router: Router) -> io::Result<()> {
+    axum::serve(listener, router).await
+}
+
+async fn public_key(
+    State(state): State<Arc<ServerState>>,
+    Path(key_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<PublicKeyResponse>, StatusCode> {
+    state.check(&headers, &key_id, Operation::PublicKey, None)?;
+    let signing_key = state
+        .store
+        .signing_key(&key_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(PublicKeyResponse {
+        public_key: hex::encode(signing_key.verifying_key().to_bytes()),
+    }))
+}
+
+async fn sign(
+    State(state): State<Arc<ServerState>>,
+    Path(key_id): Path<String>,
+    headers: HeaderMap,
+    Json(request): Json<SignRequest>,
+) -> Result<Json<SignResponse>, StatusCode> {
+    let message = hex::decode(request.message).map_err(|_| StatusCode::BAD_REQUEST)?;
+    state.check(&headers, &key_id, Operation::Sign, Some(sha256(&message)))?;
+    let signing_key = state
+        .store
+        .signing_key(&key_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let signature = signing_key.sign(&message);
+    Ok(Json(SignResponse {
+        signature: hex::encode(state.store.ecdsa.encode_signature(&signature)),
+    }))
+}
+
+impl ServerState {
+    fn check(
+        &self,
+        headers: &HeaderMap,
+        key_id: &str,
+        operation: Operation,
+        message_hash: Option<Vec<u8>>,
+    ) -> Result<(), StatusCode> {
+        let token = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        let allowed = self.authenticator.authorize(token, key_id, operation);
+        self.audit.record(&AuditEvent {
+            key_id,
+            operation,
+            allowed,
+            message_hash,
+        });
+        if !allowed {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use signature::Verifier;
+    use tower::ServiceExt;
+
+    use crate::signer::VerifyingKey;
+
+    const STORE: &str = r#"{
+        "curve": {"a": "2", "b": "2", "p": "11", "gx": "5", "gy": "1", "q": "13"},
+        "keys": {"alice": "07"}
+    }"#;
+
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn request(method: &str, uri: &str, token: Option<&str>, body: String) -> Request<Body> {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json");
+        let request = match token {
+            Some(token) => request.header(header::AUTHORIZATION, format!("Bearer {}", token)),
+            None => request,
+        };
+        request.body(Body::from(body)).unwrap()
+    }
+
+    async fn json<T: for<'de> Deserialize<'de>>(response: axum::response::Response) -> T {
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_server_sign() {
+        let audit = Shared::default();
+        let router = router(
+            KeyStore::from_json(STORE).unwrap(),
+            Box::new(BearerToken("secret".to_string())),
+            Box::new(JsonLinesAudit(Mutex::new(audit.clone()))),
+        );
+
+        let uri = "/v1/keys/alice/public-key";
+        let response = router
+            .clone()
+            .oneshot(request("GET", uri, Some("secret"), String::new()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let public_key: PublicKeyResponse = json(response).await;
+
+        let body = serde_json::to_string(&SignRequest {
+            message: hex::encode(b"Bob -> 1 BTC -> Alice"),
+        })
+        .unwrap();
+        let uri = "/v1/keys/alice/sign";
+        let response = router
+            .oneshot(request("POST", uri, Some("secret"), body))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let signature: SignResponse = json(response).await;
+
+        let store = KeyStore::from_json(STORE).unwrap();
+        let ecdsa = &store.ecdsa;
+        let verifying_key =
+            VerifyingKey::from_bytes(ecdsa, &hex::decode(public_key.public_key).unwrap()).unwrap();
+        let signature = ecdsa
+            .decode_signature(&hex::decode(signature.signature).unwrap())
+            .unwrap();
+        assert!(verifying_key
+            .verify(b"Bob -> 1 BTC -> Alice", &signature)
+            .is_ok());
+
+        let log = String::from_utf8(audit.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(log.lines().count(), 2);
+        assert!(log.contains(&hex::encode(sha256(b"Bob -> 1 BTC -> Alice"))));
+    }
+
+    #[tokio::test]
+    async fn test_server_rejects_unauthorized() {
+        let audit = Shared::default();
+        let router = router(
+            KeyStore::from_json(STORE).unwrap(),
+            Box::new(|token: Option<&str>, key_id: &str, operation| {
+                token == Some("secret") && (key_id != "alice" || operation == Operation::PublicKey)
+            }),
+            Box::new(JsonLinesAudit(Mutex::new(audit.clone()))),
+        );
+
+        let body = serde_json::to_string(&SignRequest {
+            message: hex::encode(b"Bob -> 1 BTC -> Alice"),
+        })
+        .unwrap();
+        let response = router
+            .clone()
+            .oneshot(request("POST", "/v1/keys/alice/sign", Some("secret"), body))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let uri = "/v1/keys/bob/public-key";
+        let response = router
+            .oneshot(request("GET", uri, Some("secret"), String::new()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let log = String::from_utf8(audit.0.lock().unwrap().clone()).unwrap();
+        assert!(log.lines().next().unwrap().contains("\"allowed\":false"));
+    }
+}
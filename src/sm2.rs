@@ -0,0 +1,288 @@
+// SM2 public key encryption and key exchange (GM/T 0003-2012, parts 3 and 4)
+// over the configured curve, with SM3 as the hash and the SM3 based KDF.
+// Ciphertexts use the C1 || C3 || C2 layout of the 2012 revision. The curves
+// used here have cofactor 1, so the h multiplications of the standard vanish.
+
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::aead::ct_eq;
+use crate::encoding::to_fixed_bytes;
+use crate::hash::sm3;
+use crate::secret::SecretScalar;
+use crate::ECDSA;
+
+pub struct Sm2KeyExchange {
+    initiator: bool,
+    priv_key: BigUint,
+    id: Vec<u8>,
+    r_random: BigUint,
+    r_point: Point,
+}
+
+pub struct Sm2SessionKey {
+    key: Vec<u8>,
+    confirmation: Vec<u8>,
+    peer_confirmation: Vec<u8>,
+}
+
+impl ECDSA {
+    pub fn sm2_z(&self, id: &[u8], pub_key: &Point) -> Vec<u8> {
+        // Z = SM3(ENTL || ID || a || b || xG || yG || xP || yP)
+        assert!(id.len() < 8192, "SM2 user ID is too long");
+        let mut data = ((id.len() * 8) as u16).to_be_bytes().to_vec();
+        data.extend(id);
+        for n in [&self.elliptic_curve.a, &self.elliptic_curve.b] {
+            data.extend(to_fixed_bytes(n, self.field_len()));
+        }
+        data.extend(&self.encode_point_uncompressed(&self.a_gen)[1..]);
+        data.extend(&self.encode_point_uncompressed(pub_key)[1..]);
+        sm3(&data)
+    }
+
+    pub fn sm2_encrypt(
+        &self,
+        pub_key: &Point,
+        message: &[u8],
+        k_random: &BigUint,
+    ) -> Option<Vec<u8>> {
+        // C1 = kA, (x2, y2) = kB, t = KDF(x2 || y2)
+        // C2 = M ^ t, C3 = SM3(x2 || M || y2)
+        // None means t came out all zero and the caller has to pick a new k
+        assert!(k_random < &self.q_order, "'K' is Bigger than the Ec group");
+        assert!(
+            self.is_valid_pub_key(pub_key),
+            "Public Key is not a valid point of the curve"
+        );
+
+        let k = SecretScalar::new(k_random.clone());
+        let c1 = self.mul_secret(&self.a_gen, &k);
+        let (x2, y2) = self.sm2_coordinates(&self.mul_secret(pub_key, &k))?;
+        let t = sm2_kdf(&[x2.as_slice(), &y2].concat(), message.len());
+        if !message.is_empty() && t.iter().all(|&b| b == 0) {
+            return None;
+        }
+
+        let c2: Vec<u8> = message.iter().zip(&t).map(|(m, k)| m ^ k).collect();
+        let c3 = sm3(&[x2.as_slice(), message, &y2].concat());
+        Some([self.encode_point_uncompressed(&c1), c3, c2].concat())
+    }
+
+    pub fn sm2_decrypt(&self, priv_key: &BigUint, ciphertext: &[u8]) -> Option<Vec<u8>> {
+        // (x2, y2) = dC1, M = C2 ^ KDF(x2 || y2), check C3
+        let c1_len = 1 + 2 * self.field_len();
+        if ciphertext.len() < c1_len + 32 {
+            return None;
+        }
+        let (c1, rest) = ciphertext.split_at(c1_len);
+        let (c3, c2) = rest.split_at(32);
+        let c1 = self.decode_point(c1)?;
+        if c1 == Point::Identity {
+            return None;
+        }
+
+        let d = SecretScalar::new(priv_key.clone());
+        let (x2, y2) = self.sm2_coordinates(&self.mul_secret(&c1, &d))?;
+        let t = sm2_kdf(&[x2.as_slice(), &y2].concat(), c2.len());
+        if !c2.is_empty() && t.iter().all(|&b| b == 0) {
+            return None;
+        }
+
+        let message: Vec<u8> = c2.iter().zip(&t).map(|(c, k)| c ^ k).collect();
+        let u = sm3(&[x2.as_slice(), &message, &y2].concat());
+        if !ct_eq(&u, c3) {
+            return None;
+        }
+        Some(message)
+    }
+
+    pub fn sm2_exchange_start(
+        &self,
+        initiator: bool,
+        priv_key: &BigUint,
+        id: &[u8],
+    ) -> Sm2KeyExchange {
+        // R = rA is sent to the peer
+        assert!(
+            priv_key < &self.q_order,
+            "Private Key is Bigger than the Ec group"
+        );
+        let (r_random, r_point) = self.generate_key_pair();
+        Sm2KeyExchange {
+            initiator,
+            priv_key: priv_key.clone(),
+            id: id.to_vec(),
+            r_random,
+            r_point,
+        }
+    }
+
+    pub fn sm2_exchange_finish(
+        &self,
+        exchange: &Sm2KeyExchange,
+        peer_pub_key: &Point,
+        peer_id: &[u8],
+        peer_r_point: &Point,
+        key_len: usize,
+    ) -> Option<Sm2SessionKey> {
+        // t = d + x̄(R) * r mod q, V = t(P' + x̄(R') R')
+        // K = KDF(xV || yV || ZA || ZB)
+        if !self.is_valid_pub_key(peer_pub_key) || !self.is_valid_pub_key(peer_r_point) {
+            return None;
+        }
+
+        let q = &self.q_order;
+        let t = (&exchange.priv_key + self.sm2_x_bar(&exchange.r_point)? * &exchange.r_random) % q;
+        let peer = self.add_points(
+            peer_pub_key,
+            &self.mul_vartime(peer_r_point, &self.sm2_x_bar(peer_r_point)?),
+        );
+        let (xv, yv) = self.sm2_coordinates(&self.mul_secret(&peer, &SecretScalar::new(t)))?;
+
+        let own_z = self.sm2_z(&exchange.id, &self.generate_pub_key(&exchange.priv_key));
+        let peer_z = self.sm2_z(peer_id, peer_pub_key);
+        let own_r = self.encode_point_uncompressed(&exchange.r_point);
+        let peer_r = self.encode_point_uncompressed(peer_r_point);
+        let (za, zb, ra, rb) = if exchange.initiator {
+            (own_z, peer_z, own_r, peer_r)
+        } else {
+            (peer_z, own_z, peer_r, own_r)
+        };
+
+        let key = sm2_kdf(&[xv.as_slice(), &yv, &za, &zb].concat(), key_len);
+        // S = SM3(tag || yV || SM3(xV || ZA || ZB || x1 || y1 || x2 || y2)),
+        // with tag 0x02 from the responder and 0x03 from the initiator
+        let inner = sm3(&[xv.as_slice(), &za, &zb, &ra[1..], &rb[1..]].concat());
+        let tag = |t: u8| sm3(&[&[t], yv.as_slice(), &inner].concat());
+        let (confirmation, peer_confirmation) = if exchange.initiator {
+            (tag(0x03), tag(0x02))
+        } else {
+            (tag(0x02), tag(0x03))
+        };
+        Some(Sm2SessionKey {
+            key,
+            confirmation,
+            peer_confirmation,
+        })
+    }
+
+    fn sm2_x_bar(&self, point: &Point) -> Option<BigUint> {
+        // x̄ = 2^w + (x mod 2^w), w = ceil(ceil(log2(q)) / 2) - 1
+        let w = self.q_order.bits().div_ceil(2) - 1;
+        match point {
+            Point::Coor(x, _) => {
+                let two_w = BigUint::from(1u32) << w;
+                Some(&two_w + (x % &two_w))
+            }
+            Point::Identity => None,
+        }
+    }
+
+    fn sm2_coordinates(&self, point: &Point) -> Option<(Vec<u8>, Vec<u8>)> {
+        match point {
+            Point::Coor(x, y) => Some((
+                to_fixed_bytes(x, self.field_len()),
+                to_fixed_bytes(y, self.field_len()),
+            )),
+            Point::Identity => None,
+        }
+    }
+}
+
+impl Sm2KeyExchange {
+    pub fn r_point(&self) -> &Point {
+        &self.r_point
+    }
+}
+
+impl Sm2SessionKey {
+    pub fn key(&self) -> &[u8] {
+        &self.key
+    }
+
+    pub fn confirmation(&self) -> &[u8] {
+        &self.confirmation
+    }
+
+    pub fn verify_confirmation(&self, peer_confirmation: &[u8]) -> bool {
+        ct_eq(&self.peer_confirmation, peer_confirmation)
+    }
+}
+
+fn sm2_kdf(z: &[u8], len: usize) -> Vec<u8> {
+    // K = SM3(Z || 1) || SM3(Z || 2) || ..., truncated to `len` bytes
+    let mut out = Vec::with_capacity(len + 32);
+    let mut counter = 1u32;
+    while out.len() < len {
+        out.extend(sm3(&[z, &counter.to_be_bytes()].concat()));
+        counter += 1;
+    }
+    out.truncate(len);
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_sm2_encrypt_decrypt() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+
+        let k_random = BigUint::from(5u32);
+        let ciphertext = ecdsa
+            .sm2_encrypt(&pub_key, b"encryption standard", &k_random)
+            .unwrap();
+        assert_eq!(
+            ecdsa.sm2_decrypt(&priv_key, &ciphertext),
+            Some(b"encryption standard".to_vec())
+        );
+
+        let mut tampered = ciphertext.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert_eq!(ecdsa.sm2_decrypt(&priv_key, &tampered), None);
+    }
+
+    #[test]
+    fn test_sm2_key_exchange() {
+        let ecdsa = toy_curve();
+        let alice_priv = BigUint::from(7u32);
+        let bob_priv = BigUint::from(11u32);
+        let alice_pub = ecdsa.generate_pub_key(&alice_priv);
+        let bob_pub = ecdsa.generate_pub_key(&bob_priv);
+
+        // On a group of order 19 the shared point is the identity once in a
+        // while, and both sides then start over with new ephemeral keys
+        let (alice_key, bob_key) = (0..20)
+            .find_map(|_| {
+                let alice = ecdsa.sm2_exchange_start(true, &alice_priv, b"ALICE123@YAHOO.COM");
+                let bob = ecdsa.sm2_exchange_start(false, &bob_priv, b"BILL456@YAHOO.COM");
+                let bob_key = ecdsa.sm2_exchange_finish(
+                    &bob,
+                    &alice_pub,
+                    b"ALICE123@YAHOO.COM",
+                    alice.r_point(),
+                    16,
+                )?;
+                let alice_key = ecdsa.sm2_exchange_finish(
+                    &alice,
+                    &bob_pub,
+                    b"BILL456@YAHOO.COM",
+                    bob.r_point(),
+                    16,
+                )?;
+                Some((alice_key, bob_key))
+            })
+            .unwrap();
+
+        assert_eq!(alice_key.key(), bob_key.key());
+        assert!(alice_key.verify_confirmation(bob_key.confirmation()));
+        assert!(bob_key.verify_confirmation(alice_key.confirmation()));
+        assert!(!alice_key.verify_confirmation(alice_key.confirmation()));
+    }
+}
@@ -0,0 +1,207 @@
+// SPAKE2 (RFC 9382) password-authenticated key exchange. Both sides blind
+// their Diffie-Hellman share with w * M or w * N, where w is derived from the
+// password, so only someone who knows the password can unblind the peer share.
+// On P-256 M and N are the constants of the RFC; on other curves they are
+// hashed to the curve so nobody knows their discrete logs. The hash is SHA-256,
+// the KDF HKDF-SHA256 and the MAC HMAC-SHA256.
+
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::aead::ct_eq;
+use crate::encoding::to_fixed_bytes;
+use crate::hash::{hkdf_sha256, hmac_sha256, sha256};
+use crate::secret::SecretScalar;
+use crate::ECDSA;
+
+const P256_M: &str = "02886e2f97ace46e55ba9dd7242579f2993b64e16ef3dcab95afd497333d8fa12f";
+const P256_N: &str = "03d8bbd6c639c62937b04d997f38c3770719c629d7014d49a24b4f98baa1292b49";
+
+pub struct Spake2 {
+    initiator: bool,
+    id_a: Vec<u8>,
+    id_b: Vec<u8>,
+    w: BigUint,
+    x: BigUint,
+    message: Vec<u8>,
+}
+
+pub struct Spake2Keys {
+    shared_key: Vec<u8>,
+    confirmation: Vec<u8>,
+    peer_confirmation: Vec<u8>,
+}
+
+impl ECDSA {
+    pub fn spake2_points(&self) -> (Point, Point) {
+        if self.is_p256() {
+            let m = self.decode_point(&hex::decode(P256_M).unwrap()).unwrap();
+            let n = self.decode_point(&hex::decode(P256_N).unwrap()).unwrap();
+            return (m, n);
+        }
+        let gen = self.encode_point(&self.a_gen);
+        (
+            self.hash_to_curve(b"RS_ECC-SPAKE2-M", &gen),
+            self.hash_to_curve(b"RS_ECC-SPAKE2-N", &gen),
+        )
+    }
+
+    pub fn spake2_start(
+        &self,
+        initiator: bool,
+        password: &[u8],
+        id_a: &[u8],
+        id_b: &[u8],
+    ) -> Spake2 {
+        // w = H(password) mod q, pA = xA + wM for the initiator, pB = yA + wN
+        // for the responder. The password should already have gone through a
+        // memory-hard function such as scrypt or Argon2
+        let w = self.hash_to_scalar(b"RS_ECC-SPAKE2-w", password);
+        let (m, n) = self.spake2_points();
+        let (x, x_point) = self.generate_key_pair();
+        let blind = self.mul_secret(
+            if initiator { &m } else { &n },
+            &SecretScalar::new(w.clone()),
+        );
+        let message = self.encode_point_uncompressed(&self.add_points(&x_point, &blind));
+        Spake2 {
+            initiator,
+            id_a: id_a.to_vec(),
+            id_b: id_b.to_vec(),
+            w,
+            x,
+            message,
+        }
+    }
+
+    pub fn spake2_finish(
+        &self,
+        state: &Spake2,
+        peer_message: &[u8],
+        aad: &[u8],
+    ) -> Option<Spake2Keys> {
+        // K = x(pB - wN) = y(pA - wM)
+        // Ke || Ka = H(TT), KcA || KcB = KDF(Ka, "ConfirmationKeys" || AAD)
+        let peer = self.decode_point(peer_message)?;
+        if !self.is_valid_pub_key(&peer) {
+            return None;
+        }
+        let (m, n) = self.spake2_points();
+        let peer_blind = self.mul_secret(
+            if state.initiator { &n } else { &m },
+            &SecretScalar::new(state.w.clone()),
+        );
+        let k = self.mul_secret(
+            &self.sub_points(&peer, &peer_blind),
+            &SecretScalar::new(state.x.clone()),
+        );
+        if k == Point::Identity {
+            return None;
+        }
+
+        let (p_a, p_b) = if state.initiator {
+            (state.message.as_slice(), peer_message)
+        } else {
+            (peer_message, state.message.as_slice())
+        };
+        let mut tt = Vec::new();
+        for field in [
+            state.id_a.as_slice(),
+            &state.id_b,
+            p_a,
+            p_b,
+            &self.encode_point_uncompressed(&k),
+            &to_fixed_bytes(&state.w, self.scalar_len()),
+        ] {
+            tt.extend((field.len() as u64).to_le_bytes());
+            tt.extend(field);
+        }
+
+        let hash = sha256(&tt);
+        let (ke, ka) = hash.split_at(16);
+        let kc = hkdf_sha256(&[], ka, &[b"ConfirmationKeys", aad].concat(), 32);
+        let (kc_a, kc_b) = kc.split_at(16);
+        let (c_a, c_b) = (hmac_sha256(kc_a, &tt), hmac_sha256(kc_b, &tt));
+        let (confirmation, peer_confirmation) = if state.initiator {
+            (c_a, c_b)
+        } else {
+            (c_b, c_a)
+        };
+        Some(Spake2Keys {
+            shared_key: ke.to_vec(),
+            confirmation,
+            peer_confirmation,
+        })
+    }
+}
+
+impl Spake2 {
+    pub fn message(&self) -> &[u8] {
+        &self.message
+    }
+}
+
+impl Spake2Keys {
+    pub fn shared_key(&self) -> &[u8] {
+        &self.shared_key
+    }
+
+    pub fn confirmation(&self) -> &[u8] {
+        &self.confirmation
+    }
+
+    pub fn verify_confirmation(&self, peer_confirmation: &[u8]) -> bool {
+        ct_eq(&self.peer_confirmation, peer_confirmation)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    fn run(
+        ecdsa: &ECDSA,
+        password_a: &[u8],
+        password_b: &[u8],
+    ) -> Option<(Spake2Keys, Spake2Keys)> {
+        // On a group of order 19 K is the identity once in a while, in which
+        // case the exchange is simply run again
+        (0..20).find_map(|_| {
+            let alice = ecdsa.spake2_start(true, password_a, b"alice", b"bob");
+            let bob = ecdsa.spake2_start(false, password_b, b"alice", b"bob");
+            let alice_keys = ecdsa.spake2_finish(&alice, bob.message(), b"")?;
+            let bob_keys = ecdsa.spake2_finish(&bob, alice.message(), b"")?;
+            Some((alice_keys, bob_keys))
+        })
+    }
+
+    #[test]
+    fn test_spake2() {
+        let ecdsa = toy_curve();
+        let (alice, bob) = run(&ecdsa, b"hunter2", b"hunter2").unwrap();
+
+        assert_eq!(alice.shared_key(), bob.shared_key());
+        assert!(alice.verify_confirmation(bob.confirmation()));
+        assert!(bob.verify_confirmation(alice.confirmation()));
+    }
+
+    #[test]
+    fn test_spake2_wrong_password() {
+        let ecdsa = toy_curve();
+        let (alice, bob) = run(&ecdsa, b"hunter2", b"hunter3").unwrap();
+
+        assert!(!alice.verify_confirmation(bob.confirmation()));
+        assert!(!bob.verify_confirmation(alice.confirmation()));
+    }
+
+    #[test]
+    fn test_spake2_p256_points() {
+        let ecdsa = crate::curves::p256();
+
+        let (m, n) = ecdsa.spake2_points();
+        assert_eq!(hex::encode(ecdsa.encode_point(&m)), P256_M);
+        assert_eq!(hex::encode(ecdsa.encode_point(&n)), P256_N);
+    }
+}
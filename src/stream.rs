@@ -0,0 +1,87 @@
+// Verification of payloads too large to hold in memory. `VerifyingStream` is
+// an `io::Write` sink that feeds everything written to it into SHA-256, so it
+// can sit at the end of `io::copy` or be teed into while a download is saved,
+// and `finalize` gives the same verdict as `VerifyingKey::verify` would on the
+// whole message.
+
+use std::io::{self, Write};
+
+use sha2::{Digest, Sha256};
+use signature::{DigestVerifier, Error};
+
+use crate::signer::{Signature, VerifyingKey};
+
+pub struct VerifyingStream<'a> {
+    key: VerifyingKey<'a>,
+    signature: Signature,
+    hasher: Sha256,
+    len: u64,
+}
+
+impl<'a> VerifyingStream<'a> {
+    pub fn new(key: VerifyingKey<'a>, signature: Signature) -> VerifyingStream<'a> {
+        VerifyingStream {
+            key,
+            signature,
+            hasher: Sha256::new(),
+            len: 0,
+        }
+    }
+
+    pub fn bytes_written(&self) -> u64 {
+        self.len
+    }
+
+    pub fn finalize(self) -> Result<(), Error> {
+        self.key.verify_digest(self.hasher, &self.signature)
+    }
+}
+
+impl Write for VerifyingStream<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.hasher.update(buf);
+        self.len += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> VerifyingKey<'a> {
+    pub fn verifying_stream(&self, signature: Signature) -> VerifyingStream<'a> {
+        VerifyingStream::new(self.clone(), signature)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+    use num_bigint::BigUint;
+    use signature::Signer;
+
+    use crate::signer::SigningKey;
+    use crate::ECDSA;
+
+    #[test]
+    fn test_verifying_stream() {
+        let ecdsa = toy_curve();
+        let signing_key = SigningKey::new(&ecdsa, BigUint::from(7u32));
+        let verifying_key = signing_key.verifying_key();
+        let payload: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let signature = signing_key.sign(&payload);
+
+        // Written in uneven chunks, as a reader would hand them over
+        let mut stream = verifying_key.verifying_stream(signature.clone());
+        io::copy(&mut io::Cursor::new(&payload), &mut stream).unwrap();
+        assert_eq!(stream.bytes_written(), payload.len() as u64);
+        assert!(stream.finalize().is_ok());
+
+        // The verdict only comes at the end, so a payload cut short fails
+        let mut stream = verifying_key.verifying_stream(signature);
+        stream.write_all(&payload[..payload.len() - 1]).unwrap();
+        assert!(stream.finalize().is_err());
+    }
+}
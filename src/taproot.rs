@@ -0,0 +1,306 @@
+// Taproot outputs (BIP 341) over BIP 340 keys. An output commits to an
+// internal key P and optionally a tree of scripts, by tweaking the key:
+//
+//   t = H_TapTweak(x(P) || merkle root),  Q = P + tG
+//
+// with the root left out when there are no scripts (BIP 86). Leaves hash as
+// H_TapLeaf(version || compact size || script) and branches as
+// H_TapBranch of their two children in byte order, so a tree is given by
+// its shape alone. The output key Q is the witness program of a version 1
+// segwit address.
+//
+// A key path spend is a BIP 340 signature under Q by the tweaked private key
+// d' + t, where d' is d negated when P has odd y. A script path spend reveals
+// the script with a control block: the leaf version with the parity of Q in
+// its low bit, x(P), and the sibling hashes from the leaf up to the root.
+// Computing the transaction's signature hash is left to the caller; it is
+// the message signed here.
+
+use alloc::{boxed::Box, string::String, vec, vec::Vec};
+use num_bigint::BigUint;
+
+use crate::address::segwit_address;
+use crate::bip340::has_even_y;
+use crate::bitcoin_message::varint;
+use crate::hash::tagged_hash;
+use crate::secret::SecretScalar;
+use crate::ECDSA;
+
+// Leaf version of BIP 342 tapscript
+pub const TAPSCRIPT_LEAF_VERSION: u8 = 0xc0;
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum TapTree {
+    Leaf { version: u8, script: Vec<u8> },
+    Branch(Box<TapTree>, Box<TapTree>),
+}
+
+impl TapTree {
+    pub fn leaf(script: &[u8]) -> TapTree {
+        // A tapscript leaf
+        TapTree::Leaf {
+            version: TAPSCRIPT_LEAF_VERSION,
+            script: script.to_vec(),
+        }
+    }
+
+    pub fn branch(left: TapTree, right: TapTree) -> TapTree {
+        TapTree::Branch(Box::new(left), Box::new(right))
+    }
+
+    pub fn merkle_root(&self) -> Vec<u8> {
+        match self {
+            TapTree::Leaf { version, script } => tap_leaf_hash(*version, script),
+            TapTree::Branch(left, right) => {
+                tap_branch_hash(&left.merkle_root(), &right.merkle_root())
+            }
+        }
+    }
+
+    fn leaves(&self) -> Vec<(u8, Vec<Vec<u8>>)> {
+        // Leaf version and the path of sibling hashes up to the root, left to
+        // right
+        match self {
+            TapTree::Leaf { version, .. } => vec![(*version, Vec::new())],
+            TapTree::Branch(left, right) => {
+                let (left_hash, right_hash) = (left.merkle_root(), right.merkle_root());
+                let mut leaves = left.leaves();
+                for (_, path) in &mut leaves {
+                    path.push(right_hash.clone());
+                }
+                for (version, mut path) in right.leaves() {
+                    path.push(left_hash.clone());
+                    leaves.push((version, path));
+                }
+                leaves
+            }
+        }
+    }
+}
+
+impl ECDSA {
+    pub fn taproot_output_key(
+        &self,
+        internal_key: &[u8],
+        merkle_root: Option<&[u8]>,
+    ) -> Option<(Vec<u8>, bool)> {
+        // x(Q) and whether Q has odd y; None for an internal key that is not
+        // on secp256k1 or a tweak that is not a scalar
+        let internal_point = self.lift_x(internal_key)?;
+        let tweak = self.taproot_tweak(internal_key, merkle_root)?;
+        let output = self.add_points(&internal_point, &self.mul_vartime(&self.a_gen, &tweak));
+        Some((self.x_only(&output)?, !has_even_y(&output)))
+    }
+
+    pub fn taproot_address(
+        &self,
+        hrp: &str,
+        internal_key: &[u8],
+        merkle_root: Option<&[u8]>,
+    ) -> Option<String> {
+        // hrp "bc" on mainnet, "tb" on testnet
+        let (output_key, _) = self.taproot_output_key(internal_key, merkle_root)?;
+        Some(segwit_address(hrp, 1, &output_key))
+    }
+
+    pub fn taproot_tweak_priv_key(
+        &self,
+        priv_key: &BigUint,
+        merkle_root: Option<&[u8]>,
+    ) -> Option<BigUint> {
+        // The private key of Q
+        let n = &self.q_order;
+        let internal_point = self.mul_secret(&self.a_gen, &SecretScalar::new(priv_key.clone()));
+        let internal_key = self.x_only(&internal_point)?;
+        let d = if has_even_y(&internal_point) {
+            priv_key.clone()
+        } else {
+            n - priv_key
+        };
+        let tweaked = (d + self.taproot_tweak(&internal_key, merkle_root)?) % n;
+        (tweaked != BigUint::from(0u32)).then_some(tweaked)
+    }
+
+    pub fn taproot_sign_key_path(
+        &self,
+        sighash: &[u8],
+        priv_key: &BigUint,
+        merkle_root: Option<&[u8]>,
+        aux_rand: &[u8; 32],
+    ) -> Option<Vec<u8>> {
+        // A BIP 340 signature that `bip340_verify` checks under x(Q)
+        let tweaked = self.taproot_tweak_priv_key(priv_key, merkle_root)?;
+        self.bip340_sign(sighash, &tweaked, aux_rand)
+    }
+
+    pub fn taproot_control_block(
+        &self,
+        internal_key: &[u8],
+        tree: &TapTree,
+        leaf_index: usize,
+    ) -> Option<Vec<u8>> {
+        // For the leaf at this position, counted left to right
+        let leaves = tree.leaves();
+        let (version, path) = leaves.get(leaf_index)?;
+        let (_, odd) = self.taproot_output_key(internal_key, Some(&tree.merkle_root()))?;
+        let mut control_block = vec![version | u8::from(odd)];
+        control_block.extend(internal_key);
+        for hash in path {
+            control_block.extend(hash);
+        }
+        Some(control_block)
+    }
+
+    pub fn taproot_verify_script_path(
+        &self,
+        output_key: &[u8],
+        script: &[u8],
+        control_block: &[u8],
+    ) -> bool {
+        // Whether the control block proves the script is committed to in Q,
+        // as a script path spend is checked before the script runs
+        let Some(path) = control_block.get(33..) else {
+            return false;
+        };
+        if !path.len().is_multiple_of(32) || path.len() / 32 > 128 {
+            return false;
+        }
+        let version = control_block[0] & 0xfe;
+        let internal_key = &control_block[1..33];
+        let root = path
+            .chunks(32)
+            .fold(tap_leaf_hash(version, script), |hash, sibling| {
+                tap_branch_hash(&hash, sibling)
+            });
+        self.taproot_output_key(internal_key, Some(&root))
+            .is_some_and(|(key, odd)| key == output_key && odd == (control_block[0] & 1 == 1))
+    }
+
+    fn taproot_tweak(&self, internal_key: &[u8], merkle_root: Option<&[u8]>) -> Option<BigUint> {
+        let data = [internal_key, merkle_root.unwrap_or_default()].concat();
+        let tweak = BigUint::from_bytes_be(&tagged_hash("TapTweak", &data));
+        (tweak < self.q_order).then_some(tweak)
+    }
+}
+
+pub fn tap_leaf_hash(version: u8, script: &[u8]) -> Vec<u8> {
+    let mut data = vec![version];
+    data.extend(varint(script.len() as u64));
+    data.extend(script);
+    tagged_hash("TapLeaf", &data)
+}
+
+fn tap_branch_hash(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let (a, b) = if a <= b { (a, b) } else { (b, a) };
+    tagged_hash("TapBranch", &[a, b].concat())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::secp256k1;
+
+    fn key(hex: &str) -> Vec<u8> {
+        hex::decode(hex).unwrap()
+    }
+
+    #[test]
+    fn test_key_path_outputs() {
+        // BIP 86's first account key, and the first key only output of the
+        // BIP 341 wallet vectors
+        let ecdsa = secp256k1();
+        let internal = key("cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115");
+        let (output, _) = ecdsa.taproot_output_key(&internal, None).unwrap();
+        assert_eq!(
+            hex::encode(output),
+            "a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"
+        );
+        assert_eq!(
+            ecdsa.taproot_address("bc", &internal, None).unwrap(),
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+
+        let internal = key("d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d");
+        assert_eq!(
+            ecdsa.taproot_address("bc", &internal, None).unwrap(),
+            "bc1p2wsldez5mud2yam29q22wgfh9439spgduvct83k3pm50fcxa5dps59h4z5"
+        );
+    }
+
+    #[test]
+    fn test_script_path_output() {
+        // A single tapscript leaf from the BIP 341 wallet vectors
+        let ecdsa = secp256k1();
+        let internal = key("187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27");
+        let tree = TapTree::leaf(&key(
+            "20d85a959b0290bf19bb89ed43c916be835475d013da4b362117393e25a48229b8ac",
+        ));
+        let root = tree.merkle_root();
+        assert_eq!(
+            hex::encode(&root),
+            "5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21"
+        );
+        let (output, _) = ecdsa.taproot_output_key(&internal, Some(&root)).unwrap();
+        assert_eq!(
+            hex::encode(&output),
+            "147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3"
+        );
+        let control_block = ecdsa.taproot_control_block(&internal, &tree, 0).unwrap();
+        assert_eq!(
+            hex::encode(&control_block),
+            "c1187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27"
+        );
+        let TapTree::Leaf { script, .. } = &tree else {
+            unreachable!()
+        };
+        assert!(ecdsa.taproot_verify_script_path(&output, script, &control_block));
+    }
+
+    #[test]
+    fn test_script_tree() {
+        // Every leaf of an unbalanced tree proves its way to the output key,
+        // and to no other
+        let ecdsa = secp256k1();
+        let internal = ecdsa
+            .x_only(&ecdsa.generate_pub_key(&BigUint::from(0xc0ffeeu32)))
+            .unwrap();
+        let scripts: Vec<Vec<u8>> = (0u8..3).map(|i| vec![0x51 + i]).collect();
+        let tree = TapTree::branch(
+            TapTree::leaf(&scripts[0]),
+            TapTree::branch(TapTree::leaf(&scripts[1]), TapTree::leaf(&scripts[2])),
+        );
+        let (output, _) = ecdsa
+            .taproot_output_key(&internal, Some(&tree.merkle_root()))
+            .unwrap();
+        for (i, script) in scripts.iter().enumerate() {
+            let control_block = ecdsa.taproot_control_block(&internal, &tree, i).unwrap();
+            assert_eq!(control_block.len(), 33 + 32 * if i == 0 { 1 } else { 2 });
+            assert!(ecdsa.taproot_verify_script_path(&output, script, &control_block));
+            assert!(!ecdsa.taproot_verify_script_path(&output, &[0x50], &control_block));
+            let mut flipped = control_block.clone();
+            flipped[0] ^= 1;
+            assert!(!ecdsa.taproot_verify_script_path(&output, script, &flipped));
+        }
+        assert!(ecdsa.taproot_control_block(&internal, &tree, 3).is_none());
+    }
+
+    #[test]
+    fn test_key_path_spend() {
+        // Whatever the parity of P, the tweaked key signs for Q
+        let ecdsa = secp256k1();
+        let root = TapTree::leaf(&[0x51]).merkle_root();
+        for priv_key in [BigUint::from(1u32), &ecdsa.q_order - 1u32] {
+            let internal = ecdsa.x_only(&ecdsa.generate_pub_key(&priv_key)).unwrap();
+            for merkle_root in [None, Some(root.as_slice())] {
+                let (output, _) = ecdsa.taproot_output_key(&internal, merkle_root).unwrap();
+                let sighash = [0x5a; 32];
+                let signature = ecdsa
+                    .taproot_sign_key_path(&sighash, &priv_key, merkle_root, &[0; 32])
+                    .unwrap();
+                assert!(ecdsa.bip340_verify(&sighash, &output, &signature));
+                assert!(!ecdsa.bip340_verify(&sighash, &internal, &signature));
+            }
+        }
+    }
+}
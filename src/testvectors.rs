@@ -0,0 +1,243 @@
+// Harness for Google's Wycheproof ECDSA verification vectors, behind the
+// `testvectors` feature, so any backend implementing `signature::Verifier`
+// (this crate, an HSM, a library across the FFI) can be checked against the
+// same files. The JSON is parsed with serde_json; every test group's public
+// key goes to a factory that returns the verifier for it, or None to skip the
+// group, for instance for a curve or hash the backend does not support.
+// Signatures are handed to `S::try_from` exactly as they appear in the file,
+// DER for the ecdsa_*_test.json files and r || s for the _p1363 ones.
+
+use core::fmt;
+
+use serde_json::Value;
+use signature::Verifier;
+
+pub struct TestGroup<'a> {
+    pub curve: &'a str,
+    pub sha: &'a str,
+    // Uncompressed SEC1 point, 0x04 || x || y
+    pub public_key: &'a [u8],
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Expected {
+    Valid,
+    Invalid,
+    // Either outcome is fine, such as for weak but legal parameters
+    Acceptable,
+}
+
+#[derive(Clone, Debug)]
+pub struct CaseResult {
+    pub tc_id: u64,
+    pub comment: String,
+    pub flags: Vec<String>,
+    pub expected: Expected,
+    // None when the group was skipped
+    pub accepted: Option<bool>,
+}
+
+#[derive(Clone, Debug)]
+pub struct WycheproofReport {
+    pub algorithm: String,
+    pub cases: Vec<CaseResult>,
+}
+
+pub fn run_wycheproof<V, S>(
+    json: &str,
+    mut verifier_for: impl FnMut(&TestGroup) -> Option<V>,
+) -> Option<WycheproofReport>
+where
+    V: Verifier<S>,
+    S: for<'a> TryFrom<&'a [u8]>,
+{
+    // None if the file is not Wycheproof JSON; signatures S cannot parse
+    // count as rejected
+    let file: Value = serde_json::from_str(json).ok()?;
+    let mut cases = Vec::new();
+    for group in file["testGroups"].as_array()? {
+        let key = group.get("publicKey").or_else(|| group.get("key"))?;
+        let public_key = match key.get("uncompressed") {
+            Some(uncompressed) => hex::decode(uncompressed.as_str()?).ok()?,
+            None => uncompressed_point(key)?,
+        };
+        let verifier = verifier_for(&TestGroup {
+            curve: key["curve"].as_str()?,
+            sha: group["sha"].as_str()?,
+            public_key: &public_key,
+        });
+
+        for test in group["tests"].as_array()? {
+            let expected = match test["result"].as_str()? {
+                "valid" => Expected::Valid,
+                "invalid" => Expected::Invalid,
+                "acceptable" => Expected::Acceptable,
+                _ => return None,
+            };
+            let accepted = match &verifier {
+                Some(verifier) => {
+                    let msg = hex::decode(test["msg"].as_str()?).ok()?;
+                    let sig = hex::decode(test["sig"].as_str()?).ok()?;
+                    Some(S::try_from(&sig).is_ok_and(|sig| verifier.verify(&msg, &sig).is_ok()))
+                }
+                None => None,
+            };
+            cases.push(CaseResult {
+                tc_id: test["tcId"].as_u64()?,
+                comment: test["comment"].as_str().unwrap_or_default().to_string(),
+                flags: test["flags"]
+                    .as_array()
+                    .map(|flags| {
+                        flags
+                            .iter()
+                            .filter_map(|f| Some(f.as_str()?.to_string()))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                expected,
+                accepted,
+            });
+        }
+    }
+    Some(WycheproofReport {
+        algorithm: file["algorithm"].as_str().unwrap_or_default().to_string(),
+        cases,
+    })
+}
+
+fn uncompressed_point(key: &Value) -> Option<Vec<u8>> {
+    // Older files only give wx and wy, as big-endian integers that may carry
+    // a leading zero byte or be shorter than the field
+    let size = key["keySize"].as_u64()?.div_ceil(8) as usize;
+    let mut point = vec![0x04];
+    for coordinate in [&key["wx"], &key["wy"]] {
+        let bytes = hex::decode(coordinate.as_str()?).ok()?;
+        let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+        let bytes = &bytes[start..];
+        if bytes.len() > size {
+            return None;
+        }
+        point.resize(point.len() + size - bytes.len(), 0);
+        point.extend_from_slice(bytes);
+    }
+    Some(point)
+}
+
+impl CaseResult {
+    pub fn passed(&self) -> bool {
+        match (self.expected, self.accepted) {
+            (_, None) => false,
+            (Expected::Valid, Some(accepted)) => accepted,
+            (Expected::Invalid, Some(accepted)) => !accepted,
+            (Expected::Acceptable, Some(_)) => true,
+        }
+    }
+}
+
+impl WycheproofReport {
+    pub fn passed(&self) -> usize {
+        self.cases.iter().filter(|case| case.passed()).count()
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.cases
+            .iter()
+            .filter(|case| case.accepted.is_none())
+            .count()
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &CaseResult> {
+        self.cases
+            .iter()
+            .filter(|case| case.accepted.is_some() && !case.passed())
+    }
+}
+
+impl fmt::Display for WycheproofReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // One line per failure, then the totals
+        for case in self.failures() {
+            let outcome = if case.accepted == Some(true) {
+                "accepted"
+            } else {
+                "rejected"
+            };
+            writeln!(
+                f,
+                "tcId {}: expected {:?}, {outcome} ({})",
+                case.tc_id, case.expected, case.comment
+            )?;
+        }
+        write!(
+            f,
+            "{}: {} passed, {} failed, {} skipped",
+            self.algorithm,
+            self.passed(),
+            self.failures().count(),
+            self.skipped()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::curves::p256;
+    use crate::signer::{Signature, VerifyingKey};
+    use crate::ECDSA;
+
+    const VECTORS: &str = include_str!("../testdata/wycheproof_ecdsa_secp256r1_sha256.json");
+
+    fn verifier<'a>(ecdsa: &'a ECDSA, group: &TestGroup) -> Option<VerifyingKey<'a>> {
+        VerifyingKey::from_bytes(ecdsa, group.public_key)
+    }
+
+    #[test]
+    fn test_wycheproof_p256_sample() {
+        // The first 12 cases of the first group and the next four groups; the
+        // whole file takes minutes in a debug build, see the ignored test
+        let mut file: Value = serde_json::from_str(VECTORS).unwrap();
+        let groups = file["testGroups"].as_array_mut().unwrap();
+        groups.truncate(5);
+        groups[0]["tests"].as_array_mut().unwrap().truncate(12);
+        let ecdsa = p256();
+        let report =
+            run_wycheproof::<_, Signature>(&file.to_string(), |group| verifier(&ecdsa, group))
+                .unwrap();
+        assert_eq!(report.cases.len(), 17);
+        assert_eq!(report.failures().count(), 0, "{report}");
+        assert!(report
+            .cases
+            .iter()
+            .any(|case| case.expected == Expected::Invalid));
+    }
+
+    #[test]
+    #[ignore]
+    fn test_wycheproof_p256() {
+        let ecdsa = p256();
+        let report =
+            run_wycheproof::<_, Signature>(VECTORS, |group| verifier(&ecdsa, group)).unwrap();
+        assert_eq!(report.cases.len(), 386);
+        assert_eq!(report.failures().count(), 0, "{report}");
+        assert_eq!(report.skipped(), 0);
+    }
+
+    #[test]
+    fn test_wycheproof_other_backend() {
+        // The p256 crate through the same harness, and a factory that skips
+        // every group
+        let report = run_wycheproof::<_, p256::ecdsa::DerSignature>(VECTORS, |group| {
+            p256::ecdsa::VerifyingKey::from_sec1_bytes(group.public_key).ok()
+        })
+        .unwrap();
+        assert_eq!(report.passed(), 386, "{report}");
+
+        let report = run_wycheproof::<VerifyingKey, Signature>(VECTORS, |_| None).unwrap();
+        assert_eq!(report.skipped(), 386);
+        assert!(report
+            .to_string()
+            .ends_with("0 passed, 0 failed, 386 skipped"));
+        assert!(run_wycheproof::<VerifyingKey, Signature>("{}", |_| None).is_none());
+    }
+}
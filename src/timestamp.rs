@@ -0,0 +1,437 @@
+// RFC 3161 timestamps over signatures, behind the `timestamp` feature. A
+// timestamp authority (TSA) signs the SHA-256 of a signature's DER together
+// with the time, which shows the signature existed by then, for instance
+// before its key expired or was revoked. `timestamp_request` builds the
+// TimeStampReq, a `TsaTransport` carries it to the TSA (over HTTP, a POST of
+// application/timestamp-query) and `timestamp_token` takes the token, a CMS
+// SignedData, out of the TimeStampResp. `verify_timestamp` checks a token
+// against the signature and the TSA's public key: the message imprint, the
+// contentType and messageDigest attributes and the TSA's signature, which
+// must be ECDSA with SHA-256 on the same curve. Getting that key and deciding
+// to trust it, usually through the TSA's certificate, is up to the caller.
+
+use std::io;
+
+use ec_generic::Point;
+use num_bigint::BigUint;
+use rand::RngCore;
+
+use crate::cms::{attribute_value, SET};
+use crate::der::{self, INTEGER, OCTET_STRING, OID, SEQUENCE};
+use crate::hash::sha256;
+use crate::oid::{
+    CONTENT_TYPE, ECDSA_WITH_SHA256 as ECDSA_SHA256_OID, MESSAGE_DIGEST, SHA256 as SHA256_OID,
+    SIGNED_DATA,
+};
+use crate::signer::Signature;
+use crate::ECDSA;
+
+const BOOLEAN: u8 = 0x01;
+const NULL: u8 = 0x05;
+const GENERALIZED_TIME: u8 = 0x18;
+const CONTEXT_0: u8 = 0xa0;
+const CONTEXT_1: u8 = 0xa1;
+
+// 1.2.840.113549.1.9.16.1.4
+const TST_INFO_OID: &[u8] = &[
+    0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x10, 0x01, 0x04,
+];
+
+// Carries a DER TimeStampReq to a TSA and brings back its DER TimeStampResp
+pub trait TsaTransport {
+    fn send(&mut self, request: &[u8]) -> io::Result<Vec<u8>>;
+}
+
+impl<F: FnMut(&[u8]) -> io::Result<Vec<u8>>> TsaTransport for F {
+    fn send(&mut self, request: &[u8]) -> io::Result<Vec<u8>> {
+        self(request)
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct TimestampInfo {
+    // Dotted OID of the TSA policy
+    pub policy: String,
+    pub serial: BigUint,
+    // GeneralizedTime as the TSA wrote it, such as 20261015093000Z
+    pub gen_time: String,
+    pub nonce: Option<BigUint>,
+    // SHA-256 of the DER of the timestamped signature
+    pub imprint: Vec<u8>,
+}
+
+impl TimestampInfo {
+    pub fn covers(&self, signature: &Signature) -> bool {
+        self.imprint == sha256(&signature.to_der())
+    }
+}
+
+pub fn timestamp_request(signature: &Signature, nonce: Option<u64>) -> Vec<u8> {
+    // version 1, the imprint, the nonce if any and certReq, so the TSA sends
+    // its certificate along
+    let mut request = der::integer(&BigUint::from(1u32));
+    request.extend(message_imprint(&sha256(&signature.to_der())));
+    if let Some(nonce) = nonce {
+        request.extend(der::integer(&BigUint::from(nonce)));
+    }
+    request.extend(der::write(BOOLEAN, &[0xff]));
+    der::write(SEQUENCE, &request)
+}
+
+pub fn request_timestamp(
+    transport: &mut impl TsaTransport,
+    signature: &Signature,
+) -> io::Result<Vec<u8>> {
+    // Sends a request with a fresh nonce and returns the token once it is
+    // known to answer that request; the TSA's signature is not checked here
+    let nonce = crate::rng().next_u64();
+    let response = transport.send(&timestamp_request(signature, Some(nonce)))?;
+    let token = timestamp_token(&response)
+        .ok_or_else(|| invalid("The TSA refused the request or sent a malformed response"))?;
+    match timestamp_info(&token) {
+        Some(info) if info.covers(signature) && info.nonce == Some(BigUint::from(nonce)) => {
+            Ok(token)
+        }
+        _ => Err(invalid("The timestamp token does not answer the request")),
+    }
+}
+
+pub fn timestamp_token(response: &[u8]) -> Option<Vec<u8>> {
+    // TimeStampResp: PKIStatusInfo, then the token when the status is
+    // granted (0) or grantedWithMods (1)
+    let (response, _) = der::read_tag(response, SEQUENCE)?;
+    let (status_info, token) = der::read_tag(response, SEQUENCE)?;
+    let (status, _) = der::read_integer(status_info)?;
+    if !matches!(status, [0] | [1]) {
+        return None;
+    }
+    match der::read_tag(token, SEQUENCE)? {
+        (_, []) => Some(token.to_vec()),
+        _ => None,
+    }
+}
+
+pub fn timestamp_info(token: &[u8]) -> Option<TimestampInfo> {
+    // TSTInfo: version, policy, messageImprint, serialNumber, genTime,
+    // accuracy, ordering, nonce, ...
+    let (tst_info, _) = signed_data(token)?;
+    let (tst_info, _) = der::read_tag(tst_info, SEQUENCE)?;
+    let (_version, rest) = der::read_integer(tst_info)?;
+    let (policy, rest) = der::read_tag(rest, OID)?;
+    let (imprint, rest) = read_message_imprint(rest)?;
+    let (serial, rest) = der::read_integer(rest)?;
+    let (gen_time, mut rest) = der::read_tag(rest, GENERALIZED_TIME)?;
+    for tag in [SEQUENCE, BOOLEAN] {
+        if let Some((_, after)) = der::read_tag(rest, tag) {
+            rest = after;
+        }
+    }
+    let nonce = der::read_integer(rest).map(|(nonce, _)| BigUint::from_bytes_be(nonce));
+    Some(TimestampInfo {
+        policy: der::oid_to_string(policy)?,
+        serial: BigUint::from_bytes_be(serial),
+        gen_time: String::from_utf8(gen_time.to_vec()).ok()?,
+        nonce,
+        imprint: imprint.to_vec(),
+    })
+}
+
+impl ECDSA {
+    pub fn verify_timestamp(
+        &self,
+        token: &[u8],
+        signature: &Signature,
+        tsa_pub: &Point,
+    ) -> Option<TimestampInfo> {
+        // SignerInfo: version, sid, digestAlgorithm, [0] signedAttrs,
+        // signatureAlgorithm, signature; only the first signer is read
+        let info = timestamp_info(token).filter(|info| info.covers(signature))?;
+        let (tst_info, signer_infos) = signed_data(token)?;
+        let (signer_info, _) = der::read_tag(signer_infos, SEQUENCE)?;
+        let (_version, rest) = der::read_integer(signer_info)?;
+        let (_, _, rest) = der::read(rest)?;
+        let (digest_algorithm, rest) = der::read_tag(rest, SEQUENCE)?;
+        let (attributes, rest) = der::read_tag(rest, CONTEXT_0)?;
+        let (signature_algorithm, rest) = der::read_tag(rest, SEQUENCE)?;
+        let (tsa_signature, _) = der::read_tag(rest, OCTET_STRING)?;
+        if algorithm(digest_algorithm)? != SHA256_OID
+            || algorithm(signature_algorithm)? != ECDSA_SHA256_OID
+            || attribute_value(attributes, CONTENT_TYPE)? != der::write(OID, TST_INFO_OID)
+            || attribute_value(attributes, MESSAGE_DIGEST)?
+                != der::write(OCTET_STRING, &sha256(tst_info))
+        {
+            return None;
+        }
+
+        // The attributes are signed as a SET, not with their implicit [0] tag
+        let digest = sha256(&der::write(SET, attributes));
+        let tsa_signature = Signature::from_der(tsa_signature)?;
+        self.verify_standard(&digest, tsa_pub, &tsa_signature)
+            .then_some(info)
+    }
+}
+
+fn signed_data(token: &[u8]) -> Option<(&[u8], &[u8])> {
+    // The TSTInfo DER and the contents of the SignerInfos SET, out of
+    // ContentInfo { signedData, [0] SignedData { version, digestAlgorithms,
+    // encapContentInfo, [0] certificates, [1] crls, signerInfos } }
+    let (content_info, _) = der::read_tag(token, SEQUENCE)?;
+    let (content_type, rest) = der::read_tag(content_info, OID)?;
+    let (content, _) = der::read_tag(rest, CONTEXT_0)?;
+    let (signed_data, _) = der::read_tag(content, SEQUENCE)?;
+    let (_version, rest) = der::read_integer(signed_data)?;
+    let (_digest_algorithms, rest) = der::read_tag(rest, SET)?;
+    let (encapsulated, mut rest) = der::read_tag(rest, SEQUENCE)?;
+    let (e_content_type, e_content) = der::read_tag(encapsulated, OID)?;
+    if content_type != SIGNED_DATA || e_content_type != TST_INFO_OID {
+        return None;
+    }
+    let (e_content, _) = der::read_tag(e_content, CONTEXT_0)?;
+    let (tst_info, _) = der::read_tag(e_content, OCTET_STRING)?;
+    for tag in [CONTEXT_0, CONTEXT_1] {
+        if let Some((_, after)) = der::read_tag(rest, tag) {
+            rest = after;
+        }
+    }
+    let (signer_infos, _) = der::read_tag(rest, SET)?;
+    Some((tst_info, signer_infos))
+}
+
+fn message_imprint(hash: &[u8]) -> Vec<u8> {
+    let algorithm = [der::write(OID, SHA256_OID), der::write(NULL, &[])].concat();
+    der::write(
+        SEQUENCE,
+        &[
+            der::write(SEQUENCE, &algorithm),
+            der::write(OCTET_STRING, hash),
+        ]
+        .concat(),
+    )
+}
+
+fn read_message_imprint(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    // The SHA-256 hash and the remaining input
+    let (imprint, rest) = der::read_tag(bytes, SEQUENCE)?;
+    let (algorithm_id, hash) = der::read_tag(imprint, SEQUENCE)?;
+    let (hash, _) = der::read_tag(hash, OCTET_STRING)?;
+    (algorithm(algorithm_id)? == SHA256_OID && hash.len() == 32).then_some((hash, rest))
+}
+
+fn algorithm(algorithm_id: &[u8]) -> Option<&[u8]> {
+    // The OID of an AlgorithmIdentifier's contents, parameters ignored
+    der::read_tag(algorithm_id, OID).map(|(oid, _)| oid)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+pub(crate) fn test_tsa_response(
+    tsa: &ECDSA,
+    tsa_key: &BigUint,
+    request: &[u8],
+    gen_time: &str,
+) -> Vec<u8> {
+    // A granted TimeStampResp for a request, as a TSA with this key would
+    // answer it, policy 1.2.3.4 and serial number 42
+    let (request, _) = der::read_tag(request, SEQUENCE).unwrap();
+    let (_version, rest) = der::read_integer(request).unwrap();
+    let (imprint, rest) = read_message_imprint(rest).unwrap();
+    let nonce = der::read_tag(rest, INTEGER).map(|(_, after)| &rest[..rest.len() - after.len()]);
+
+    let tst_info = der::write(
+        SEQUENCE,
+        &[
+            der::integer(&BigUint::from(1u32)),
+            der::write(OID, &[0x2a, 0x03, 0x04]),
+            message_imprint(imprint),
+            der::integer(&BigUint::from(42u32)),
+            der::write(GENERALIZED_TIME, gen_time.as_bytes()),
+            nonce.unwrap_or_default().to_vec(),
+        ]
+        .concat(),
+    );
+    let attribute = |oid: &[u8], value: Vec<u8>| {
+        der::write(
+            SEQUENCE,
+            &[der::write(OID, oid), der::write(SET, &value)].concat(),
+        )
+    };
+    let attributes = [
+        attribute(CONTENT_TYPE, der::write(OID, TST_INFO_OID)),
+        attribute(MESSAGE_DIGEST, der::write(OCTET_STRING, &sha256(&tst_info))),
+    ]
+    .concat();
+    let signature = tsa.sign_standard(
+        &mut crate::rng(),
+        &sha256(&der::write(SET, &attributes)),
+        tsa_key,
+    );
+
+    let sha256_id = der::write(SEQUENCE, &der::write(OID, SHA256_OID));
+    let signer_info = der::write(
+        SEQUENCE,
+        &[
+            der::integer(&BigUint::from(3u32)),
+            // [0] subjectKeyIdentifier
+            der::write(0x80, &[0x01; 20]),
+            sha256_id.clone(),
+            der::write(CONTEXT_0, &attributes),
+            der::write(SEQUENCE, &der::write(OID, ECDSA_SHA256_OID)),
+            der::write(OCTET_STRING, &signature.to_der()),
+        ]
+        .concat(),
+    );
+    let signed_data = der::write(
+        SEQUENCE,
+        &[
+            der::integer(&BigUint::from(3u32)),
+            der::write(SET, &sha256_id),
+            der::write(
+                SEQUENCE,
+                &[
+                    der::write(OID, TST_INFO_OID),
+                    der::write(CONTEXT_0, &der::write(OCTET_STRING, &tst_info)),
+                ]
+                .concat(),
+            ),
+            der::write(SET, &signer_info),
+        ]
+        .concat(),
+    );
+    let token = der::write(
+        SEQUENCE,
+        &[
+            der::write(OID, SIGNED_DATA),
+            der::write(CONTEXT_0, &signed_data),
+        ]
+        .concat(),
+    );
+    let status = der::write(SEQUENCE, &der::integer(&BigUint::from(0u32)));
+    der::write(SEQUENCE, &[status, token].concat())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::p256;
+
+    #[test]
+    fn test_request_and_verify_timestamp() {
+        let ecdsa = p256();
+        let signature = Signature::new(BigUint::from(12345u32), BigUint::from(67890u32));
+        let tsa_key = BigUint::from(0x75a75a75u32);
+        let tsa_pub = ecdsa.generate_pub_key(&tsa_key);
+
+        let mut tsa = |request: &[u8]| {
+            Ok(test_tsa_response(
+                &ecdsa,
+                &tsa_key,
+                request,
+                "20261015093000Z",
+            ))
+        };
+        let token = request_timestamp(&mut tsa, &signature).unwrap();
+
+        let info = ecdsa
+            .verify_timestamp(&token, &signature, &tsa_pub)
+            .unwrap();
+        assert_eq!(info.gen_time, "20261015093000Z");
+        assert_eq!(info.policy, "1.2.3.4");
+        assert_eq!(info.serial, BigUint::from(42u32));
+        assert!(info.nonce.is_some());
+
+        // Another signature, another TSA key
+        let other = Signature::new(BigUint::from(12345u32), BigUint::from(67891u32));
+        assert!(ecdsa.verify_timestamp(&token, &other, &tsa_pub).is_none());
+        let other_pub = ecdsa.generate_pub_key(&BigUint::from(3u32));
+        assert!(ecdsa
+            .verify_timestamp(&token, &signature, &other_pub)
+            .is_none());
+    }
+
+    #[test]
+    fn test_request_timestamp_checks_response() {
+        let ecdsa = p256();
+        let signature = Signature::new(BigUint::from(1u32), BigUint::from(2u32));
+        let tsa_key = BigUint::from(5u32);
+
+        // Answering an older request, whose nonce differs
+        let old_request = timestamp_request(&signature, Some(7));
+        let mut replay = |_: &[u8]| {
+            Ok(test_tsa_response(
+                &ecdsa,
+                &tsa_key,
+                &old_request,
+                "20261015093000Z",
+            ))
+        };
+        assert!(request_timestamp(&mut replay, &signature).is_err());
+
+        // status rejection (2)
+        let mut refusing = |_: &[u8]| {
+            Ok(der::write(
+                SEQUENCE,
+                &der::write(SEQUENCE, &der::integer(&BigUint::from(2u32))),
+            ))
+        };
+        assert!(request_timestamp(&mut refusing, &signature).is_err());
+        let mut offline =
+            |_: &[u8]| Err(io::Error::new(io::ErrorKind::ConnectionRefused, "offline"));
+        assert!(request_timestamp(&mut offline, &signature).is_err());
+    }
+
+    #[test]
+    fn test_openssl_reply() {
+        // `openssl ts -reply` with a P-256 TSA key, answering a request for
+        // the DER signature (1, 2) built with `openssl ts -query -no_nonce`
+        const REPLY: &[u8] = include_bytes!("../testdata/openssl_ts_reply.tsr");
+        const TSA_PUB: &str = "0428e1dcf97a755b42e5d82b605c41de386fdf7f6266dc1d4e6169e14e8bae8\
+                               62faabd0868b8fee69a60e13937891646f887c335eac25f5606a6ad84aecba15d95";
+        let ecdsa = p256();
+        let tsa_pub = ecdsa.decode_point(&hex::decode(TSA_PUB).unwrap()).unwrap();
+        let signature = Signature::new(BigUint::from(1u32), BigUint::from(2u32));
+
+        let token = timestamp_token(REPLY).unwrap();
+        let info = ecdsa
+            .verify_timestamp(&token, &signature, &tsa_pub)
+            .unwrap();
+        assert_eq!(info.gen_time, "20261015044025Z");
+        assert_eq!(info.policy, "1.2.3.4.1");
+        assert_eq!(info.serial, BigUint::from(3u32));
+        assert_eq!(info.nonce, None);
+    }
+
+    #[test]
+    fn test_tampered_token() {
+        let ecdsa = p256();
+        let signature = Signature::new(BigUint::from(1u32), BigUint::from(2u32));
+        let tsa_key = BigUint::from(5u32);
+        let tsa_pub = ecdsa.generate_pub_key(&tsa_key);
+        let response = test_tsa_response(
+            &ecdsa,
+            &tsa_key,
+            &timestamp_request(&signature, None),
+            "20261015093000Z",
+        );
+        let token = timestamp_token(&response).unwrap();
+        assert!(timestamp_info(&token).unwrap().nonce.is_none());
+
+        // An earlier time in the TSTInfo no longer matches messageDigest
+        let position = token
+            .windows(15)
+            .position(|window| window == b"20261015093000Z")
+            .unwrap();
+        let mut backdated = token.clone();
+        backdated[position + 3] = b'5';
+        assert_eq!(
+            timestamp_info(&backdated).unwrap().gen_time,
+            "20251015093000Z"
+        );
+        assert!(ecdsa
+            .verify_timestamp(&backdated, &signature, &tsa_pub)
+            .is_none());
+    }
+}
@@ -0,0 +1,120 @@
+// rustls adapter, behind the `rustls` feature, so a TLS server can sign its
+// handshakes with a key held by this crate. Only P-256 keys are accepted, for
+// the ecdsa_secp256r1_sha256 scheme. Signatures are made with
+// `sign_standard`, the same standard ECDSA TLS peers run, and go out DER
+// encoded.
+
+use std::fmt;
+use std::sync::Arc;
+
+use num_bigint::BigUint;
+use rustls::sign::{Signer, SigningKey};
+use rustls::{SignatureAlgorithm, SignatureScheme};
+
+use crate::hash::sha256;
+use crate::ECDSA;
+
+pub struct TlsSigningKey {
+    ecdsa: Arc<ECDSA>,
+    priv_key: BigUint,
+}
+
+struct TlsSigner {
+    ecdsa: Arc<ECDSA>,
+    priv_key: BigUint,
+}
+
+impl TlsSigningKey {
+    pub fn new(ecdsa: Arc<ECDSA>, priv_key: BigUint) -> Option<TlsSigningKey> {
+        if !ecdsa.is_p256() || priv_key == BigUint::from(0u32) || priv_key >= ecdsa.q_order {
+            return None;
+        }
+        Some(TlsSigningKey { ecdsa, priv_key })
+    }
+}
+
+impl fmt::Debug for TlsSigningKey {
+    // Never prints the private key
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsSigningKey")
+            .field("scheme", &SignatureScheme::ECDSA_NISTP256_SHA256)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for TlsSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsSigner")
+            .field("scheme", &SignatureScheme::ECDSA_NISTP256_SHA256)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SigningKey for TlsSigningKey {
+    fn choose_scheme(&self, offered: &[SignatureScheme]) -> Option<Box<dyn Signer>> {
+        if !offered.contains(&SignatureScheme::ECDSA_NISTP256_SHA256) {
+            return None;
+        }
+        Some(Box::new(TlsSigner {
+            ecdsa: self.ecdsa.clone(),
+            priv_key: self.priv_key.clone(),
+        }))
+    }
+
+    fn algorithm(&self) -> SignatureAlgorithm {
+        SignatureAlgorithm::ECDSA
+    }
+}
+
+impl Signer for TlsSigner {
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, rustls::Error> {
+        let signature =
+            self.ecdsa
+                .sign_standard(&mut crate::rng(), &sha256(message), &self.priv_key);
+        Ok(signature.to_der())
+    }
+
+    fn scheme(&self) -> SignatureScheme {
+        SignatureScheme::ECDSA_NISTP256_SHA256
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+    use p256::ecdsa::signature::Verifier;
+    use p256::ecdsa::{DerSignature, VerifyingKey};
+
+    use crate::curves;
+
+    #[test]
+    fn test_tls_signing_key() {
+        let ecdsa = Arc::new(curves::p256());
+        let priv_key = BigUint::from(0xc0ffeeu32);
+        let pub_key = ecdsa.encode_point(&ecdsa.generate_pub_key(&priv_key));
+        let key = TlsSigningKey::new(ecdsa, priv_key).unwrap();
+
+        assert!(key.choose_scheme(&[SignatureScheme::ED25519]).is_none());
+        let signer = key
+            .choose_scheme(&[
+                SignatureScheme::ED25519,
+                SignatureScheme::ECDSA_NISTP256_SHA256,
+            ])
+            .unwrap();
+        let der = signer.sign(b"TLS 1.3, server CertificateVerify").unwrap();
+
+        // Checked by an independent P-256 implementation, as a TLS client would
+        let verifying_key = VerifyingKey::from_sec1_bytes(&pub_key).unwrap();
+        let signature = DerSignature::try_from(der.as_slice()).unwrap();
+        assert!(verifying_key
+            .verify(b"TLS 1.3, server CertificateVerify", &signature)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_tls_signing_key_rejects_other_curves() {
+        let ecdsa = toy_curve();
+        assert!(TlsSigningKey::new(Arc::new(ecdsa), BigUint::from(7u32)).is_none());
+    }
+}
@@ -0,0 +1,176 @@
+// Step by step signing and verification, behind the `trace` feature, for
+// learning the algorithm and for finding where an interop mismatch with another
+// library starts. `sign_traced` and `verify_traced` follow the equations of
+// `sign` and `verification`, handing every intermediate value to a callback
+// as a label and its hex encoding: scalars as fixed width big endian, points
+// in uncompressed SEC1 form. `TraceLog` collects them and prints one
+// "label: hex" line per step.
+
+use alloc::{format, string::String, string::ToString, vec::Vec};
+use core::fmt;
+use ec_generic::{FiniteField, Point};
+use num_bigint::BigUint;
+
+use crate::encoding::to_fixed_bytes;
+use crate::secret::{invert_secret, SecretScalar};
+use crate::ECDSA;
+
+#[derive(Default)]
+pub struct TraceLog {
+    pub steps: Vec<(&'static str, String)>,
+}
+
+impl TraceLog {
+    pub fn new() -> TraceLog {
+        TraceLog::default()
+    }
+
+    pub fn record(&mut self) -> impl FnMut(&'static str, String) + '_ {
+        |label, value| self.steps.push((label, value))
+    }
+
+    pub fn get(&self, label: &str) -> Option<&str> {
+        self.steps
+            .iter()
+            .find(|(step, _)| *step == label)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl fmt::Display for TraceLog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (label, value) in &self.steps {
+            writeln!(f, "{}: {}", label, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl ECDSA {
+    pub fn sign_traced(
+        &self,
+        hash: &BigUint,
+        priv_key: &BigUint,
+        k_random: &BigUint,
+        trace: &mut dyn FnMut(&'static str, String),
+    ) -> Option<(BigUint, BigUint)> {
+        // R = kA -> r = x(R), s = (hash + d * r) * k⁻¹ mod q; None where `sign`
+        // would panic
+        let q = &self.q_order;
+        if hash >= q || priv_key >= q || k_random >= q || *k_random == BigUint::from(0u32) {
+            return None;
+        }
+        trace("hash", self.scalar_hex(hash));
+        trace("k", self.scalar_hex(k_random));
+        let point_r = self.mul_secret(&self.a_gen, &SecretScalar::new(k_random.clone()));
+        trace("R = kA", self.point_hex(&point_r));
+        let Point::Coor(r, _) = point_r else {
+            return None;
+        };
+        trace("r = x(R)", self.scalar_hex(&r));
+        let k_inv = invert_secret(k_random, q)?;
+        trace("k^-1", self.scalar_hex(&k_inv));
+        let s = (hash + &r % q * priv_key) % q * &k_inv % q;
+        trace("s = (hash + d r) k^-1", self.scalar_hex(&s));
+        Some((r, s))
+    }
+
+    pub fn verify_traced(
+        &self,
+        hash: &BigUint,
+        pub_key: &Point,
+        signature: &(BigUint, BigUint),
+        trace: &mut dyn FnMut(&'static str, String),
+    ) -> bool {
+        // P = u1 A + u2 B with u1 = hash * s⁻¹, u2 = r * s⁻¹, accept if x(P) = r
+        let (r, s) = signature;
+        let q = &self.q_order;
+        let zero = BigUint::from(0u32);
+        trace("hash", self.scalar_hex(hash));
+        trace("r", self.scalar_hex(r));
+        trace("s", self.scalar_hex(s));
+        if hash >= q || !self.is_valid_pub_key(pub_key) || r % q == zero || *s == zero || s >= q {
+            trace("result", "rejected, a value is out of range".to_string());
+            return false;
+        }
+
+        let s_inv = FiniteField::inv_mult_prime(s, q).unwrap();
+        trace("w = s^-1", self.scalar_hex(&s_inv));
+        let u1 = hash * &s_inv % q;
+        trace("u1 = hash w", self.scalar_hex(&u1));
+        let u2 = r % q * &s_inv % q;
+        trace("u2 = r w", self.scalar_hex(&u2));
+        let u1_a = self.mul_vartime(&self.a_gen, &u1);
+        trace("u1 A", self.point_hex(&u1_a));
+        let u2_b = self.mul_vartime(pub_key, &u2);
+        trace("u2 B", self.point_hex(&u2_b));
+        let p = self.add_points(&u1_a, &u2_b);
+        trace("P = u1 A + u2 B", self.point_hex(&p));
+        let valid = matches!(&p, Point::Coor(x, _) if x == r);
+        let result = if valid {
+            "valid, x(P) = r"
+        } else {
+            "invalid, x(P) != r"
+        };
+        trace("result", result.to_string());
+        valid
+    }
+
+    fn scalar_hex(&self, n: &BigUint) -> String {
+        hex::encode(to_fixed_bytes(n, self.scalar_len()))
+    }
+
+    fn point_hex(&self, point: &Point) -> String {
+        hex::encode(self.encode_point_uncompressed(point))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_sign_traced() {
+        // 18A = (5, 16), so r = 5 and s = (10 + 7 * 5) * 18⁻¹ = 45 * 18 = 12 mod 19
+        let ecdsa = toy_curve();
+        let (hash, priv_key, k) = (
+            BigUint::from(10u32),
+            BigUint::from(7u32),
+            BigUint::from(18u32),
+        );
+        let mut log = TraceLog::new();
+        let signature = ecdsa
+            .sign_traced(&hash, &priv_key, &k, &mut log.record())
+            .unwrap();
+        assert_eq!(signature, ecdsa.sign(&hash, &priv_key, &k));
+        assert_eq!(
+            log.to_string(),
+            "hash: 0a\nk: 12\nR = kA: 040510\nr = x(R): 05\nk^-1: 12\ns = (hash + d r) k^-1: 0c\n"
+        );
+    }
+
+    #[test]
+    fn test_verify_traced() {
+        let ecdsa = toy_curve();
+        let (hash, priv_key, k) = (
+            BigUint::from(10u32),
+            BigUint::from(7u32),
+            BigUint::from(18u32),
+        );
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+        let signature = ecdsa.sign(&hash, &priv_key, &k);
+
+        let mut log = TraceLog::new();
+        assert!(ecdsa.verify_traced(&hash, &pub_key, &signature, &mut log.record()));
+        // The recovered point is R itself
+        assert_eq!(log.get("P = u1 A + u2 B"), Some("040510"));
+        assert_eq!(log.get("result"), Some("valid, x(P) = r"));
+
+        let mut log = TraceLog::new();
+        let other = BigUint::from(11u32);
+        assert!(!ecdsa.verify_traced(&other, &pub_key, &signature, &mut log.record()));
+        assert_eq!(log.get("result"), Some("invalid, x(P) != r"));
+        assert_eq!(log.steps.len(), 10);
+    }
+}
@@ -0,0 +1,456 @@
+// Two-party ECDSA after Lindell (CRYPTO 2017). The key is Q = x1 * x2 * A and
+// neither party ever holds x1 * x2. Party 1 owns a Paillier key and hands
+// party 2 c_key = Enc(x1); party 2 then folds its nonce and key share into that
+// ciphertext homomorphically, and party 1 decrypts the almost finished s.
+// Each message carries a Schnorr proof of knowledge for its public share,
+// and the first one also proves that c_key encrypts log_A(Q1):
+//
+//   a = Enc(α; β), B = αA with α < 2^(|q| + 208), e = H(sid, n, c_key, Q1, a, B)
+//   z = α + e * x1, w = β * r^e mod n
+//   accept if Enc(z; w) = a * c_key^e, zA = B + eQ1 and z < 2^(|q| + 209)
+//
+// with a 128-bit challenge and 80 bits of statistical slack. A plaintext
+// that is not log_A(Q1), or is far out of range, would need e * x1 mod n to
+// land below the bound for a challenge fixed only after a was sent. The
+// Paillier modulus has at least 2048 bits, and party 1 proves with
+// `prove_modulus` that gcd(n, φ(n)) = 1; that n has exactly two prime factors
+// is not proven. Every transcript is bound to a session id both parties
+// agree on beforehand, fresh for each key generation and each signature, so
+// no proof can be replayed into another session.
+
+use ec_generic::Point;
+use num_bigint::{BigUint, RandBigInt};
+
+use crate::arithmetic::gcd;
+use crate::hash::sha256;
+use crate::paillier::{ModulusProof, PaillierPrivateKey, PaillierPublicKey};
+use crate::proof_of_knowledge::KnowledgeProof;
+use crate::secret::{invert_secret, SecretScalar};
+use crate::ECDSA;
+
+const KEYGEN_1: &[u8] = b"RS_ECC-2P-ECDSA-keygen-1";
+const KEYGEN_2: &[u8] = b"RS_ECC-2P-ECDSA-keygen-2";
+const SIGN_1: &[u8] = b"RS_ECC-2P-ECDSA-sign-1";
+const SIGN_2: &[u8] = b"RS_ECC-2P-ECDSA-sign-2";
+const KEY_ENCRYPTION: &[u8] = b"RS_ECC-2P-ECDSA-key-encryption";
+const KEY_MODULUS: &[u8] = b"RS_ECC-2P-ECDSA-key-modulus";
+
+const MIN_PAILLIER_BITS: u64 = 2048;
+const CHALLENGE_BITS: u64 = 128;
+const SLACK_BITS: u64 = 80;
+
+pub struct Party1 {
+    x1: BigUint,
+    paillier: PaillierPrivateKey,
+    pub_key: Point,
+}
+
+pub struct Party2 {
+    x2: BigUint,
+    paillier: PaillierPublicKey,
+    c_key: BigUint,
+    pub_key: Point,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct KeyGenMessage1 {
+    q1: Point,
+    proof: KnowledgeProof,
+    paillier: PaillierPublicKey,
+    modulus_proof: ModulusProof,
+    c_key: BigUint,
+    key_proof: KeyEncryptionProof,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct KeyEncryptionProof {
+    a: BigUint,
+    b: Point,
+    z: BigUint,
+    w: BigUint,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct KeyGenMessage2 {
+    q2: Point,
+    proof: KnowledgeProof,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct SignMessage1 {
+    r1: Point,
+    proof: KnowledgeProof,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct SignMessage2 {
+    r2: Point,
+    proof: KnowledgeProof,
+    c3: BigUint,
+}
+
+impl ECDSA {
+    pub fn two_party_keygen_1(
+        &self,
+        session_id: &[u8],
+    ) -> (BigUint, PaillierPrivateKey, KeyGenMessage1) {
+        // Q1 = x1 A, c_key = Enc(x1; r) and the proof that it does
+        let (x1, q1) = self.generate_key_pair();
+        let proof = self.two_party_prove(&x1, KEYGEN_1, session_id);
+        let paillier = PaillierPrivateKey::generate(self.paillier_bits());
+        let modulus_proof = paillier.prove_modulus(&[KEY_MODULUS, session_id].concat());
+        let public_key = paillier.public_key();
+        let r = random_unit(public_key.n());
+        let c_key = public_key.encrypt_with(&x1, &r);
+        let key_proof = self.prove_key_encryption(session_id, public_key, &c_key, &q1, &x1, &r);
+        let message = KeyGenMessage1 {
+            q1,
+            proof,
+            paillier: public_key.clone(),
+            modulus_proof,
+            c_key,
+            key_proof,
+        };
+        (x1, paillier, message)
+    }
+
+    pub fn two_party_keygen_2(
+        &self,
+        session_id: &[u8],
+        message: &KeyGenMessage1,
+    ) -> Option<(Party2, KeyGenMessage2)> {
+        // Q2 = x2 A, Q = x2 Q1
+        if !self.verify_knowledge(
+            &message.q1,
+            &[KEYGEN_1, session_id].concat(),
+            &message.proof,
+        ) || !self.verify_key_encryption(session_id, message)
+        {
+            return None;
+        }
+        let (x2, q2) = self.generate_key_pair();
+        let proof = self.two_party_prove(&x2, KEYGEN_2, session_id);
+        let party = Party2 {
+            pub_key: self.mul_secret(&message.q1, &SecretScalar::new(x2.clone())),
+            x2,
+            paillier: message.paillier.clone(),
+            c_key: message.c_key.clone(),
+        };
+        Some((party, KeyGenMessage2 { q2, proof }))
+    }
+
+    pub fn two_party_keygen_finish(
+        &self,
+        session_id: &[u8],
+        x1: BigUint,
+        paillier: PaillierPrivateKey,
+        message: &KeyGenMessage2,
+    ) -> Option<Party1> {
+        // Q = x1 Q2
+        if !self.verify_knowledge(
+            &message.q2,
+            &[KEYGEN_2, session_id].concat(),
+            &message.proof,
+        ) {
+            return None;
+        }
+        Some(Party1 {
+            pub_key: self.mul_secret(&message.q2, &SecretScalar::new(x1.clone())),
+            x1,
+            paillier,
+        })
+    }
+
+    pub fn two_party_sign_1(&self, session_id: &[u8]) -> (BigUint, SignMessage1) {
+        // R1 = k1 A
+        let (k1, r1) = self.generate_key_pair();
+        let proof = self.two_party_prove(&k1, SIGN_1, session_id);
+        (k1, SignMessage1 { r1, proof })
+    }
+
+    pub fn two_party_sign_2(
+        &self,
+        session_id: &[u8],
+        party: &Party2,
+        hash: &BigUint,
+        message: &SignMessage1,
+    ) -> Option<SignMessage2> {
+        // R = k2 R1, r = x(R)
+        // c3 = Enc(ρq + k2⁻¹ * hash) + c_key * (k2⁻¹ * r * x2) with ρ < q²
+        assert!(hash < &self.q_order, "Hash is Bigger than the Ec group");
+        if !self.verify_knowledge(&message.r1, &[SIGN_1, session_id].concat(), &message.proof) {
+            return None;
+        }
+
+        let q = &self.q_order;
+        let (k2, r2) = self.generate_key_pair();
+        let proof = self.two_party_prove(&k2, SIGN_2, session_id);
+        let r = match self.mul_secret(&message.r1, &SecretScalar::new(k2.clone())) {
+            Point::Coor(x, _) if &x % q != BigUint::from(0u32) => x,
+            _ => return None,
+        };
+
+        let k2_inv = invert_secret(&k2, q)?;
+        let rho = self.generate_random_number_less_than(&(q * q));
+        let v = (&k2_inv * &r % q) * &party.x2 % q;
+        let c1 = party.paillier.encrypt(&(rho * q + (&k2_inv * hash) % q));
+        let c2 = party.paillier.mul_scalar(&party.c_key, &v);
+        let c3 = party.paillier.add(&c1, &c2);
+        Some(SignMessage2 { r2, proof, c3 })
+    }
+
+    pub fn two_party_sign_finish(
+        &self,
+        session_id: &[u8],
+        party: &Party1,
+        k1: &BigUint,
+        hash: &BigUint,
+        message: &SignMessage2,
+    ) -> Option<(BigUint, BigUint)> {
+        // R = k1 R2, s = k1⁻¹ * Dec(c3) mod q, normalised to the lower half
+        if !self.verify_knowledge(&message.r2, &[SIGN_2, session_id].concat(), &message.proof) {
+            return None;
+        }
+
+        let q = &self.q_order;
+        let r = match self.mul_secret(&message.r2, &SecretScalar::new(k1.clone())) {
+            Point::Coor(x, _) => x,
+            Point::Identity => return None,
+        };
+        let s_prime = party.paillier.decrypt(&message.c3) % q;
+        let k1_inv = invert_secret(k1, q)?;
+        let s = (k1_inv * s_prime) % q;
+        if s == BigUint::from(0u32) || &r % q == BigUint::from(0u32) {
+            return None;
+        }
+        let s = core::cmp::min(s.clone(), q - &s);
+
+        let signature = (r, s);
+        if self.verification(hash, &party.pub_key, &signature) {
+            return Some(signature);
+        }
+        None
+    }
+
+    fn two_party_prove(
+        &self,
+        secret: &BigUint,
+        context: &[u8],
+        session_id: &[u8],
+    ) -> KnowledgeProof {
+        // No context constant is a prefix of another, so context || session
+        // id is unambiguous
+        let k_random = self.generate_random_number_less_than(&self.q_order);
+        self.prove_knowledge(secret, &[context, session_id].concat(), &k_random)
+    }
+
+    fn paillier_bits(&self) -> u64 {
+        // Above the largest plaintext party 2 builds, ρq + k2⁻¹ * hash +
+        // v * Dec(c_key) with ρ < q² and v < q, for any plaintext the proof
+        // lets through
+        let bits = 4 * self.q_order.bits() + CHALLENGE_BITS + SLACK_BITS + 64;
+        bits.max(MIN_PAILLIER_BITS)
+    }
+
+    fn prove_key_encryption(
+        &self,
+        session_id: &[u8],
+        paillier: &PaillierPublicKey,
+        c_key: &BigUint,
+        q1: &Point,
+        x1: &BigUint,
+        r: &BigUint,
+    ) -> KeyEncryptionProof {
+        let n = paillier.n();
+        let bound = BigUint::from(1u32) << (self.q_order.bits() + CHALLENGE_BITS + SLACK_BITS);
+        let alpha = crate::rng().gen_biguint_below(&bound);
+        let beta = random_unit(n);
+        let a = paillier.encrypt_with(&alpha, &beta);
+        let b = self.mul_secret(&self.a_gen, &SecretScalar::new(alpha.clone()));
+        let e = self.key_encryption_challenge(session_id, paillier, c_key, q1, &a, &b);
+        KeyEncryptionProof {
+            z: alpha + &e * x1,
+            w: beta * r.modpow(&e, n) % n,
+            a,
+            b,
+        }
+    }
+
+    fn verify_key_encryption(&self, session_id: &[u8], message: &KeyGenMessage1) -> bool {
+        let (paillier, c_key, proof) = (&message.paillier, &message.c_key, &message.key_proof);
+        let n = paillier.n();
+        let bound = BigUint::from(1u32) << (self.q_order.bits() + CHALLENGE_BITS + SLACK_BITS + 1);
+        if n.bits() < self.paillier_bits()
+            || !paillier.verify_modulus(&[KEY_MODULUS, session_id].concat(), &message.modulus_proof)
+            || !paillier.is_ciphertext(c_key)
+            || !paillier.is_ciphertext(&proof.a)
+            || !self.elliptic_curve.is_on_curve(&proof.b)
+            || proof.z >= bound
+            || proof.w >= *n
+            || gcd(&proof.w, n) != BigUint::from(1u32)
+        {
+            return false;
+        }
+
+        let e = self.key_encryption_challenge(
+            session_id,
+            paillier,
+            c_key,
+            &message.q1,
+            &proof.a,
+            &proof.b,
+        );
+        let encrypted = paillier.add(&proof.a, &paillier.mul_scalar(c_key, &e));
+        let z_point = self.mul_vartime(&self.a_gen, &(&proof.z % &self.q_order));
+        let e_point = self.mul_vartime(&message.q1, &e);
+        paillier.encrypt_with(&proof.z, &proof.w) == encrypted
+            && z_point == self.add_points(&proof.b, &e_point)
+    }
+
+    fn key_encryption_challenge(
+        &self,
+        session_id: &[u8],
+        paillier: &PaillierPublicKey,
+        c_key: &BigUint,
+        q1: &Point,
+        a: &BigUint,
+        b: &Point,
+    ) -> BigUint {
+        // The first 128 bits of SHA-256 over the length-prefixed pieces
+        let mut data = KEY_ENCRYPTION.to_vec();
+        for piece in [
+            session_id.to_vec(),
+            paillier.n().to_bytes_be(),
+            c_key.to_bytes_be(),
+            self.encode_point(q1),
+            a.to_bytes_be(),
+            self.encode_point(b),
+        ] {
+            data.extend((piece.len() as u64).to_be_bytes());
+            data.extend(piece);
+        }
+        BigUint::from_bytes_be(&sha256(&data)[..CHALLENGE_BITS as usize / 8])
+    }
+}
+
+fn random_unit(n: &BigUint) -> BigUint {
+    // Uniform in [1, n) and coprime to n
+    let mut rng = crate::rng();
+    loop {
+        let r = rng.gen_biguint_range(&BigUint::from(1u32), n);
+        if gcd(&r, n) == BigUint::from(1u32) {
+            return r;
+        }
+    }
+}
+
+impl Party1 {
+    pub fn pub_key(&self) -> &Point {
+        &self.pub_key
+    }
+}
+
+impl Party2 {
+    pub fn pub_key(&self) -> &Point {
+        &self.pub_key
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    fn keygen(ecdsa: &ECDSA) -> (Party1, Party2) {
+        let (x1, paillier, msg1) = ecdsa.two_party_keygen_1(b"keygen");
+        let (party2, msg2) = ecdsa.two_party_keygen_2(b"keygen", &msg1).unwrap();
+        let party1 = ecdsa
+            .two_party_keygen_finish(b"keygen", x1, paillier, &msg2)
+            .unwrap();
+        (party1, party2)
+    }
+
+    #[test]
+    fn test_two_party_sign() {
+        let ecdsa = toy_curve();
+        let (party1, party2) = keygen(&ecdsa);
+        assert_eq!(party1.pub_key(), party2.pub_key());
+
+        let hash = ecdsa.generate_hash_less_than("Bob -> 1 BTC -> Alice", &ecdsa.q_order);
+        // On a group of order 19 a session aborts on r = 0 or s = 0 fairly
+        // often, in which case the parties simply start over with new nonces
+        let signature = (0..20u8)
+            .find_map(|i| {
+                let (k1, msg1) = ecdsa.two_party_sign_1(&[i]);
+                let msg2 = ecdsa.two_party_sign_2(&[i], &party2, &hash, &msg1)?;
+                ecdsa.two_party_sign_finish(&[i], &party1, &k1, &hash, &msg2)
+            })
+            .unwrap();
+
+        assert!(ecdsa.verification(&hash, party1.pub_key(), &signature));
+    }
+
+    #[test]
+    fn test_two_party_sign_rejects_bad_proof() {
+        let ecdsa = toy_curve();
+        let (_, party2) = keygen(&ecdsa);
+        let hash = ecdsa.generate_hash_less_than("Bob -> 1 BTC -> Alice", &ecdsa.q_order);
+
+        // R1 = 7A with a proof made for the nonce 3
+        let forged = SignMessage1 {
+            r1: ecdsa.generate_pub_key(&BigUint::from(7u32)),
+            proof: ecdsa.prove_knowledge(&BigUint::from(3u32), SIGN_1, &BigUint::from(11u32)),
+        };
+        assert!(ecdsa
+            .two_party_sign_2(b"sign", &party2, &hash, &forged)
+            .is_none());
+
+        // A sound proof from another session
+        let context = [SIGN_1, b"sign"].concat();
+        let sound = SignMessage1 {
+            r1: ecdsa.generate_pub_key(&BigUint::from(3u32)),
+            proof: ecdsa.prove_knowledge(&BigUint::from(3u32), &context, &BigUint::from(5u32)),
+        };
+        assert!(ecdsa.verify_knowledge(&sound.r1, &context, &sound.proof));
+        assert!(ecdsa
+            .two_party_sign_2(b"other", &party2, &hash, &sound)
+            .is_none());
+    }
+
+    #[test]
+    fn test_two_party_keygen_rejects_bad_key_encryption() {
+        let ecdsa = toy_curve();
+        let (x1, paillier_key, msg1) = ecdsa.two_party_keygen_1(b"keygen");
+        assert!(ecdsa.two_party_keygen_2(b"other", &msg1).is_none());
+
+        // c_key re-encrypted to x1 + 1 under the original proof
+        let paillier = &msg1.paillier;
+        let other_key = KeyGenMessage1 {
+            c_key: paillier.encrypt(&(&x1 + 1u32)),
+            ..msg1.clone()
+        };
+        assert!(ecdsa.two_party_keygen_2(b"keygen", &other_key).is_none());
+
+        // A modulus proof made for another session
+        let replayed = KeyGenMessage1 {
+            modulus_proof: paillier_key.prove_modulus(&[KEY_MODULUS, b"other"].concat()),
+            ..msg1.clone()
+        };
+        assert!(ecdsa.two_party_keygen_2(b"keygen", &replayed).is_none());
+
+        // An honest proof under a 1024-bit modulus
+        let small = PaillierPrivateKey::generate(1024);
+        let r = random_unit(small.public_key().n());
+        let c_key = small.public_key().encrypt_with(&x1, &r);
+        let key_proof =
+            ecdsa.prove_key_encryption(b"keygen", small.public_key(), &c_key, &msg1.q1, &x1, &r);
+        let small_key = KeyGenMessage1 {
+            paillier: small.public_key().clone(),
+            modulus_proof: small.prove_modulus(&[KEY_MODULUS, b"keygen"].concat()),
+            c_key,
+            key_proof,
+            ..msg1
+        };
+        assert!(ecdsa.two_party_keygen_2(b"keygen", &small_key).is_none());
+    }
+}
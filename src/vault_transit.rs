@@ -0,0 +1,403 @@
+// Keys held in HashiCorp Vault's transit secrets engine, behind the `vault`
+// feature. Vault only offers NIST curves, so the signer is for P-256 keys
+// (type ecdsa-p256). `sign_async` hashes the message with SHA-256 here and
+// sends the digest as a prehashed input with asn1 marshaling:
+//
+//   GET  {address}/v1/{mount}/keys/{name}    -> {"data": {"type", "latest_version",
+//                                                "keys": {"1": {"public_key": PEM}}}}
+//   POST {address}/v1/{mount}/sign/{name}    {"input": base64 digest, "prehashed",
+//        "hash_algorithm", "marshaling_algorithm", "key_version"}
+//        -> {"data": {"signature": "vault:v1:...", "key_version": 1}}
+//   POST {address}/v1/{mount}/verify/{name}  {"input", "signature", ...}
+//        -> {"data": {"valid": true}}
+//
+// Vault writes signatures as vault:v<key version>:<base64 DER>, read and
+// written by `Signature::from_vault` and `to_vault`. They are plain ECDSA over
+// the digest, for `VerifyingKey`, `verify_standard` and `KeyPair::verify`.
+// Signing uses the latest key version unless one is pinned with
+// `with_key_version`, and every signature is checked against the public key
+// of the version that made it.
+
+use std::collections::BTreeMap;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ec_generic::Point;
+use serde::{Deserialize, Serialize};
+use signature::Error;
+
+use crate::hash::sha256;
+use crate::pem;
+use crate::signer::{AsyncSigner, Signature};
+use crate::ECDSA;
+
+pub struct VaultTransitSigner<'a> {
+    ecdsa: &'a ECDSA,
+    client: reqwest::Client,
+    address: String,
+    mount: String,
+    key_name: String,
+    token: String,
+    namespace: Option<String>,
+    key_version: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct VaultResponse<T> {
+    data: T,
+}
+
+#[derive(Serialize)]
+struct SignRequest<'a> {
+    input: String,
+    prehashed: bool,
+    hash_algorithm: &'a str,
+    marshaling_algorithm: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key_version: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    signature: String,
+}
+
+#[derive(Serialize)]
+struct VerifyRequest<'a> {
+    input: String,
+    signature: String,
+    prehashed: bool,
+    hash_algorithm: &'a str,
+    marshaling_algorithm: &'a str,
+}
+
+#[derive(Deserialize)]
+struct VerifyResponse {
+    valid: bool,
+}
+
+#[derive(Deserialize)]
+struct KeyResponse {
+    #[serde(rename = "type")]
+    key_type: String,
+    latest_version: u32,
+    keys: BTreeMap<String, KeyVersion>,
+}
+
+#[derive(Deserialize)]
+struct KeyVersion {
+    public_key: String,
+}
+
+impl Signature {
+    pub fn to_vault(&self, key_version: u32) -> String {
+        format!("vault:v{}:{}", key_version, STANDARD.encode(self.to_der()))
+    }
+
+    pub fn from_vault(text: &str) -> Option<(u32, Signature)> {
+        // The key version and signature of a vault:v<n>:<base64 DER> string
+        let rest = text.trim().strip_prefix("vault:v")?;
+        let (version, der) = rest.split_once(':')?;
+        if version.is_empty() || !version.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let version = version.parse().ok().filter(|version| *version > 0)?;
+        let signature = Signature::from_der(&STANDARD.decode(der).ok()?)?;
+        Some((version, signature))
+    }
+}
+
+impl<'a> VaultTransitSigner<'a> {
+    pub fn new(
+        ecdsa: &'a ECDSA,
+        address: &str,
+        key_name: &str,
+        token: &str,
+    ) -> VaultTransitSigner<'a> {
+        VaultTransitSigner {
+            ecdsa,
+            client: reqwest::Client::new(),
+            address: address.trim_end_matches('/').to_string(),
+            mount: "transit".into(),
+            key_name: key_name.to_string(),
+            token: token.to_string(),
+            namespace: None,
+            key_version: None,
+        }
+    }
+
+    pub fn with_mount(mut self, mount: &str) -> VaultTransitSigner<'a> {
+        // Where the engine is mounted when not at transit/
+        self.mount = mount.trim_matches('/').to_string();
+        self
+    }
+
+    pub fn with_namespace(mut self, namespace: &str) -> VaultTransitSigner<'a> {
+        // Sent as X-Vault-Namespace (Vault Enterprise, HCP Vault)
+        self.namespace = Some(namespace.to_string());
+        self
+    }
+
+    pub fn with_key_version(mut self, key_version: u32) -> VaultTransitSigner<'a> {
+        // Sign with this version rather than the latest
+        self.key_version = Some(key_version);
+        self
+    }
+
+    pub async fn public_keys(&self) -> Result<Vec<(u32, Point)>, Error> {
+        // Every version of the key that Vault still holds, oldest first; an
+        // error unless the key is ecdsa-p256 and this signer is on P-256
+        let url = format!("{}/v1/{}/keys/{}", self.address, self.mount, self.key_name);
+        let key: KeyResponse = self.send(self.client.get(url)).await?;
+        if key.key_type != "ecdsa-p256" || !self.ecdsa.is_p256() {
+            return Err(Error::new());
+        }
+        let mut versions = Vec::new();
+        for (version, entry) in key.keys {
+            let version = version.parse().map_err(Error::from_source)?;
+            match pem::public_key_from_pem(&entry.public_key) {
+                Some((ecdsa, pub_key)) if ecdsa.same_parameters(self.ecdsa) => {
+                    versions.push((version, pub_key))
+                }
+                _ => return Err(Error::new()),
+            }
+        }
+        versions.sort_by_key(|(version, _)| *version);
+        match versions.last() {
+            Some((latest, _)) if *latest == key.latest_version => Ok(versions),
+            _ => Err(Error::new()),
+        }
+    }
+
+    pub async fn public_key(&self, key_version: u32) -> Result<Point, Error> {
+        self.public_keys()
+            .await?
+            .into_iter()
+            .find(|(version, _)| *version == key_version)
+            .map(|(_, pub_key)| pub_key)
+            .ok_or_else(Error::new)
+    }
+
+    pub async fn sign_versioned(&self, msg: &[u8]) -> Result<(u32, Signature), Error> {
+        // The signature together with the key version Vault used
+        let digest = sha256(msg);
+        let url = format!("{}/v1/{}/sign/{}", self.address, self.mount, self.key_name);
+        let request = SignRequest {
+            input: STANDARD.encode(&digest),
+            prehashed: true,
+            hash_algorithm: "sha2-256",
+            marshaling_algorithm: "asn1",
+            key_version: self.key_version,
+        };
+        let response: SignResponse = self.send(self.client.post(url).json(&request)).await?;
+        let (version, signature) =
+            Signature::from_vault(&response.signature).ok_or_else(Error::new)?;
+        if self.key_version.is_some_and(|pinned| pinned != version) {
+            return Err(Error::new());
+        }
+
+        let pub_key = self.public_key(version).await?;
+        if !self.ecdsa.verify_standard(&digest, &pub_key, &signature) {
+            return Err(Error::new());
+        }
+        Ok((version, signature))
+    }
+
+    pub async fn verify_remote(
+        &self,
+        msg: &[u8],
+        key_version: u32,
+        signature: &Signature,
+    ) -> Result<bool, Error> {
+        // Asks Vault itself, which also rejects versions below the key's
+        // min_decryption_version
+        let url = format!(
+            "{}/v1/{}/verify/{}",
+            self.address, self.mount, self.key_name
+        );
+        let request = VerifyRequest {
+            input: STANDARD.encode(sha256(msg)),
+            signature: signature.to_vault(key_version),
+            prehashed: true,
+            hash_algorithm: "sha2-256",
+            marshaling_algorithm: "asn1",
+        };
+        let response: VerifyResponse = self.send(self.client.post(url).json(&request)).await?;
+        Ok(response.valid)
+    }
+
+    async fn send<T: for<'de> Deserialize<'de>>(
+        &self,
+        mut request: reqwest::RequestBuilder,
+    ) -> Result<T, Error> {
+        request = request.header("X-Vault-Token", &self.token);
+        if let Some(namespace) = &self.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+        let response: VaultResponse<T> = request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(Error::from_source)?
+            .json()
+            .await
+            .map_err(Error::from_source)?;
+        Ok(response.data)
+    }
+}
+
+impl AsyncSigner for VaultTransitSigner<'_> {
+    async fn sign_async(&self, msg: &[u8]) -> Result<Signature, Error> {
+        Ok(self.sign_versioned(msg).await?.1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use num_bigint::BigUint;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use crate::curves::p256;
+
+    #[test]
+    fn test_vault_marshaling() {
+        let signature = Signature::new(BigUint::from(0x1234u32), BigUint::from(0x80u32));
+        let text = signature.to_vault(3);
+        assert_eq!(text, "vault:v3:MAgCAhI0AgIAgA==");
+        assert_eq!(Signature::from_vault(&text), Some((3, signature)));
+
+        for text in [
+            "vault:v0:MAgCAhI0AgIAgA==",
+            "vault:v:MAgCAhI0AgIAgA==",
+            "vault:v+3:MAgCAhI0AgIAgA==",
+            "vault:3:MAgCAhI0AgIAgA==",
+            "vault:v3:MAgCAhI0AgIAgA",
+            "vault:v3:MAkCAhI0AgIAgAA=",
+        ] {
+            assert_eq!(Signature::from_vault(text), None);
+        }
+    }
+
+    fn serve(requests: usize) -> String {
+        // A stand-in for Vault with one ecdsa-p256 key, "release", at two
+        // versions; one request per connection
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let ecdsa = p256();
+            let priv_keys = [BigUint::from(0xc0ffeeu32), BigUint::from(0xdecafu32)];
+            let pub_keys: Vec<Point> = priv_keys
+                .iter()
+                .map(|priv_key| ecdsa.generate_pub_key(priv_key))
+                .collect();
+            for stream in listener.incoming().take(requests) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                let mut authorized = false;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end().to_ascii_lowercase();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(length) = line.strip_prefix("content-length: ") {
+                        content_length = length.parse().unwrap();
+                    }
+                    authorized |= line == "x-vault-token: hvs.token";
+                }
+                let mut body = vec![0u8; content_length];
+                reader.read_exact(&mut body).unwrap();
+                let body: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+
+                let data = if !authorized {
+                    None
+                } else if request_line.starts_with("GET /v1/transit/keys/release ") {
+                    Some(serde_json::json!({
+                        "type": "ecdsa-p256",
+                        "latest_version": 2,
+                        "keys": {
+                            "1": {"public_key": ecdsa.public_key_to_pem(&pub_keys[0]).unwrap()},
+                            "2": {"public_key": ecdsa.public_key_to_pem(&pub_keys[1]).unwrap()},
+                        },
+                    }))
+                } else if request_line.starts_with("POST /v1/transit/sign/release ") {
+                    assert_eq!(body["prehashed"], true);
+                    assert_eq!(body["marshaling_algorithm"], "asn1");
+                    let version = body["key_version"].as_u64().unwrap_or(2) as u32;
+                    let digest = STANDARD.decode(body["input"].as_str().unwrap()).unwrap();
+                    let priv_key = &priv_keys[version as usize - 1];
+                    let signature = ecdsa.sign_standard(&mut crate::rng(), &digest, priv_key);
+                    Some(serde_json::json!({
+                        "signature": signature.to_vault(version),
+                        "key_version": version,
+                    }))
+                } else if request_line.starts_with("POST /v1/transit/verify/release ") {
+                    let digest = STANDARD.decode(body["input"].as_str().unwrap()).unwrap();
+                    let valid = Signature::from_vault(body["signature"].as_str().unwrap())
+                        .filter(|(version, _)| (1..=2).contains(version))
+                        .is_some_and(|(version, signature)| {
+                            let pub_key = &pub_keys[version as usize - 1];
+                            ecdsa.verify_standard(&digest, pub_key, &signature)
+                        });
+                    Some(serde_json::json!({"valid": valid}))
+                } else {
+                    None
+                };
+                let reply = match data {
+                    Some(data) => {
+                        let json = serde_json::json!({"data": data}).to_string();
+                        format!(
+                            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                            json.len(),
+                            json
+                        )
+                    }
+                    None => {
+                        "HTTP/1.1 403 Forbidden\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                            .to_string()
+                    }
+                };
+                stream.write_all(reply.as_bytes()).unwrap();
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_vault_transit_signer() {
+        let ecdsa = p256();
+        let digest = sha256(b"release v1.2");
+        let address = serve(8);
+        let signer = VaultTransitSigner::new(&ecdsa, &address, "release", "hvs.token");
+        let (version, signature) = signer.sign_versioned(b"release v1.2").await.unwrap();
+        assert_eq!(version, 2);
+        let keys = signer.public_keys().await.unwrap();
+        assert_eq!(keys.len(), 2);
+        assert!(ecdsa.verify_standard(&digest, &keys[1].1, &signature));
+        assert!(signer
+            .verify_remote(b"release v1.2", 2, &signature)
+            .await
+            .unwrap());
+        assert!(!signer
+            .verify_remote(b"release v1.3", 2, &signature)
+            .await
+            .unwrap());
+
+        // A pinned older version signs with that version's key
+        let pinned =
+            VaultTransitSigner::new(&ecdsa, &address, "release", "hvs.token").with_key_version(1);
+        let signature = pinned.sign_async(b"release v1.2").await.unwrap();
+        assert!(ecdsa.verify_standard(&digest, &keys[0].1, &signature));
+        assert!(!ecdsa.verify_standard(&digest, &keys[1].1, &signature));
+
+        let signer = VaultTransitSigner::new(&ecdsa, &address, "release", "expired");
+        assert!(signer.sign_async(b"release v1.2").await.is_err());
+    }
+}
@@ -0,0 +1,196 @@
+// An ECVRF in the style of RFC 9381's TAI suites over the configured curve:
+// hash-to-curve by try-and-increment, a Chaum-Pedersen style proof that
+// Gamma = x * H uses the same x as Y = x * A, and the VRF output
+// beta = Hash(suite || 0x03 || Gamma || 0x00). It is not one of the RFC's
+// suites (the full-width challenge and the hash-to-curve differ), so it runs
+// under a suite string of its own and does not interoperate with them.
+
+use alloc::vec::Vec;
+use ec_generic::Point;
+use num_bigint::BigUint;
+
+use crate::encoding::to_fixed_bytes;
+use crate::hash::{hkdf_sha256, sha256};
+use crate::secret::SecretScalar;
+use crate::ECDSA;
+
+const SUITE: &[u8] = b"RS_ECC-ECVRF-SHA256-TAI";
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct VrfProof {
+    gamma: Point,
+    c: BigUint,
+    s: BigUint,
+}
+
+impl ECDSA {
+    pub fn vrf_prove(&self, priv_key: &BigUint, alpha: &[u8]) -> VrfProof {
+        // H = hash_to_curve(Y, alpha), Gamma = x * H
+        // k = nonce(x, H), c = challenge(Y, H, Gamma, kA, kH)
+        // s = k + c * x mod q
+        assert!(
+            priv_key < &self.q_order,
+            "Private Key is Bigger than the Ec group"
+        );
+
+        let pub_key = self.generate_pub_key(priv_key);
+        let h = self.vrf_encode_to_curve(&pub_key, alpha);
+        let gamma = self.mul_secret(&h, &SecretScalar::new(priv_key.clone()));
+
+        let k = self.vrf_nonce(priv_key, &h);
+        let k_secret = SecretScalar::new(k.clone());
+        let u = self.mul_secret(&self.a_gen, &k_secret);
+        let v = self.mul_secret(&h, &k_secret);
+        let c = self.vrf_challenge(&[&pub_key, &h, &gamma, &u, &v]);
+
+        let s = (k + &c * priv_key) % &self.q_order;
+        VrfProof { gamma, c, s }
+    }
+
+    pub fn vrf_verify(&self, pub_key: &Point, alpha: &[u8], proof: &VrfProof) -> Option<Vec<u8>> {
+        // U = sA - cY, V = sH - c * Gamma
+        // the proof is valid if c = challenge(Y, H, Gamma, U, V)
+        if *pub_key == Point::Identity
+            || !self.elliptic_curve.is_on_curve(pub_key)
+            || !self.elliptic_curve.is_on_curve(&proof.gamma)
+            || proof.c >= self.q_order
+            || proof.s >= self.q_order
+        {
+            return None;
+        }
+
+        let h = self.vrf_encode_to_curve(pub_key, alpha);
+        let u = self.sub_points(
+            &self.mul_vartime(&self.a_gen, &proof.s),
+            &self.mul_vartime(pub_key, &proof.c),
+        );
+        let v = self.sub_points(
+            &self.mul_vartime(&h, &proof.s),
+            &self.mul_vartime(&proof.gamma, &proof.c),
+        );
+        let c = self.vrf_challenge(&[pub_key, &h, &proof.gamma, &u, &v]);
+
+        if c == proof.c {
+            return Some(self.vrf_proof_to_hash(proof));
+        }
+        None
+    }
+
+    pub fn vrf_proof_to_hash(&self, proof: &VrfProof) -> Vec<u8> {
+        let gamma = self.encode_point(&proof.gamma);
+        sha256(&[SUITE, &[0x03], gamma.as_slice(), &[0x00]].concat())
+    }
+
+    pub fn vrf_proof_to_bytes(&self, proof: &VrfProof) -> Vec<u8> {
+        // pi = Gamma || c || s
+        let mut bytes = self.encode_point(&proof.gamma);
+        bytes.extend(to_fixed_bytes(&proof.c, self.scalar_len()));
+        bytes.extend(to_fixed_bytes(&proof.s, self.scalar_len()));
+        bytes
+    }
+
+    pub fn vrf_proof_from_bytes(&self, bytes: &[u8]) -> Option<VrfProof> {
+        let n = self.scalar_len();
+        if bytes.len() < 2 * n {
+            return None;
+        }
+        let (gamma, scalars) = bytes.split_at(bytes.len() - 2 * n);
+        let gamma = self.decode_point(gamma)?;
+        let c = BigUint::from_bytes_be(&scalars[..n]);
+        let s = BigUint::from_bytes_be(&scalars[n..]);
+        if c >= self.q_order || s >= self.q_order {
+            return None;
+        }
+        Some(VrfProof { gamma, c, s })
+    }
+
+    fn vrf_encode_to_curve(&self, pub_key: &Point, alpha: &[u8]) -> Point {
+        let data = [self.encode_point(pub_key).as_slice(), alpha].concat();
+        self.hash_to_curve(&[SUITE, &[0x01]].concat(), &data)
+    }
+
+    fn vrf_nonce(&self, priv_key: &BigUint, h: &Point) -> BigUint {
+        // Deterministic nonce in [1, q - 1] derived from the secret key and H,
+        // with 128 bits more than q so the reduction leaves no usable bias
+        let okm = hkdf_sha256(
+            &[SUITE, &[0x04]].concat(),
+            &to_fixed_bytes(priv_key, self.scalar_len()),
+            &self.encode_point(h),
+            self.scalar_len() + 16,
+        );
+        BigUint::from_bytes_be(&okm) % (&self.q_order - 1u32) + 1u32
+    }
+
+    fn vrf_challenge(&self, points: &[&Point]) -> BigUint {
+        let mut data = Vec::new();
+        for point in points {
+            data.extend(self.encode_point(point));
+        }
+        data.push(0x00);
+        self.hash_to_scalar(&[SUITE, &[0x02]].concat(), &data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_vrf_prove_verify() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+
+        let alpha = b"round 42";
+        let proof = ecdsa.vrf_prove(&priv_key, alpha);
+        let beta = ecdsa.vrf_verify(&pub_key, alpha, &proof);
+
+        assert_eq!(beta, Some(ecdsa.vrf_proof_to_hash(&proof)));
+        assert_eq!(
+            proof,
+            ecdsa.vrf_prove(&priv_key, alpha),
+            "Proofs are deterministic"
+        );
+    }
+
+    #[test]
+    fn test_vrf_verify_wrong_input() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+
+        let proof = ecdsa.vrf_prove(&priv_key, b"round 42");
+        assert_eq!(ecdsa.vrf_verify(&pub_key, b"round 43", &proof), None);
+
+        let other_pub_key = ecdsa.generate_pub_key(&BigUint::from(8u32));
+        assert_eq!(ecdsa.vrf_verify(&other_pub_key, b"round 42", &proof), None);
+    }
+
+    #[test]
+    fn test_vrf_nonce_full_width() {
+        // On P-384 a nonce limited to 256 bits would leak the key through s
+        let ecdsa = crate::curves::p384();
+        let priv_key = BigUint::from(7u32);
+        let h = ecdsa.vrf_encode_to_curve(&ecdsa.generate_pub_key(&priv_key), b"round 42");
+        let nonces: Vec<BigUint> = (1u32..=8)
+            .map(|x| ecdsa.vrf_nonce(&BigUint::from(x), &h))
+            .collect();
+        assert!(nonces.iter().any(|k| k.bits() > 256));
+        assert!(nonces.iter().all(|k| k < &ecdsa.q_order));
+    }
+
+    #[test]
+    fn test_vrf_proof_bytes() {
+        let ecdsa = toy_curve();
+        let priv_key = BigUint::from(7u32);
+        let pub_key = ecdsa.generate_pub_key(&priv_key);
+
+        let proof = ecdsa.vrf_prove(&priv_key, b"round 42");
+        let bytes = ecdsa.vrf_proof_to_bytes(&proof);
+        let decoded = ecdsa.vrf_proof_from_bytes(&bytes).unwrap();
+
+        assert_eq!(decoded, proof);
+        assert!(ecdsa.vrf_verify(&pub_key, b"round 42", &decoded).is_some());
+    }
+}
@@ -0,0 +1,172 @@
+// Feldman verifiable secret sharing. The dealer splits a secret a_0 with a
+// random polynomial f(x) = a_0 + a_1 x + ... + a_{t-1} x^{t-1} mod q, hands
+// f(i) to party i and publishes C_j = a_j A, so every party can check its
+// share against the commitments. Any t shares recover a_0 by interpolation.
+
+use alloc::{vec, vec::Vec};
+use ec_generic::{FiniteField, Point};
+use num_bigint::BigUint;
+
+use crate::secret::SecretScalar;
+use crate::ECDSA;
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Share {
+    pub index: u32,
+    pub value: BigUint,
+}
+
+impl ECDSA {
+    pub fn vss_deal(
+        &self,
+        secret: &BigUint,
+        threshold: usize,
+        parties: u32,
+    ) -> (Vec<Share>, Vec<Point>) {
+        assert!(secret < &self.q_order, "Secret is Bigger than the Ec group");
+        assert!(
+            threshold >= 1 && threshold <= parties as usize,
+            "Threshold must be between 1 and the number of parties"
+        );
+        assert!(
+            BigUint::from(parties) < self.q_order,
+            "Too many parties for the Ec group"
+        );
+
+        let mut coefficients = vec![secret.clone()];
+        for _ in 1..threshold {
+            coefficients.push(self.generate_random_number_less_than(&self.q_order));
+        }
+        self.vss_deal_polynomial(&coefficients, parties)
+    }
+
+    pub fn vss_deal_polynomial(
+        &self,
+        coefficients: &[BigUint],
+        parties: u32,
+    ) -> (Vec<Share>, Vec<Point>) {
+        // s_i = f(i), C_j = a_j A
+        let shares = (1..=parties)
+            .map(|index| Share {
+                index,
+                value: self.eval_polynomial(coefficients, index),
+            })
+            .collect();
+        let commitments = coefficients
+            .iter()
+            .map(|a| self.mul_secret(&self.a_gen, &SecretScalar::new(a.clone())))
+            .collect();
+        (shares, commitments)
+    }
+
+    pub fn vss_verify_share(&self, share: &Share, commitments: &[Point]) -> bool {
+        // s_i A = sum C_j i^j
+        share.value < self.q_order
+            && self.mul_secret(&self.a_gen, &SecretScalar::new(share.value.clone()))
+                == self.vss_public_share(share.index, commitments)
+    }
+
+    pub fn vss_public_share(&self, index: u32, commitments: &[Point]) -> Point {
+        // f(i) A = sum i^j C_j, computed from the commitments alone
+        let x = BigUint::from(index);
+        let mut powers = Vec::new();
+        let mut power = BigUint::from(1u32);
+        for _ in commitments {
+            powers.push(power.clone());
+            power = (power * &x) % &self.q_order;
+        }
+        self.multi_scalar_mul(commitments, &powers)
+    }
+
+    pub fn vss_reconstruct(&self, shares: &[Share]) -> Option<BigUint> {
+        // a_0 = sum λ_i s_i mod q
+        let indices: Vec<u32> = shares.iter().map(|share| share.index).collect();
+        shares.iter().try_fold(BigUint::from(0u32), |acc, share| {
+            let lambda = self.lagrange_coefficient(share.index, &indices)?;
+            Some((acc + lambda * &share.value) % &self.q_order)
+        })
+    }
+
+    pub fn lagrange_coefficient(&self, index: u32, indices: &[u32]) -> Option<BigUint> {
+        // λ_i = prod_{j != i} j / (j - i) mod q. None unless i is among the
+        // indices and they are all nonzero and distinct mod q, as a repeated
+        // or zero index has no coefficient
+        let q = &self.q_order;
+        let reduced: Vec<BigUint> = indices.iter().map(|&j| BigUint::from(j) % q).collect();
+        let distinct = reduced
+            .iter()
+            .enumerate()
+            .all(|(k, j)| *j != BigUint::from(0u32) && !reduced[..k].contains(j));
+        if !distinct || !indices.contains(&index) {
+            return None;
+        }
+
+        let i = BigUint::from(index) % q;
+        let mut num = BigUint::from(1u32);
+        let mut den = BigUint::from(1u32);
+        for j in reduced.into_iter().filter(|j| *j != i) {
+            den = (den * ((&j + q - &i) % q)) % q;
+            num = (num * j) % q;
+        }
+        let den_inv = FiniteField::inv_mult_prime(&den, q).unwrap();
+        Some((num * den_inv) % q)
+    }
+
+    fn eval_polynomial(&self, coefficients: &[BigUint], index: u32) -> BigUint {
+        let x = BigUint::from(index);
+        coefficients
+            .iter()
+            .rev()
+            .fold(BigUint::from(0u32), |acc, a| (acc * &x + a) % &self.q_order)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::toy_curve;
+
+    #[test]
+    fn test_deal_verify_reconstruct() {
+        let ecdsa = toy_curve();
+        let secret = BigUint::from(7u32);
+        let (shares, commitments) = ecdsa.vss_deal(&secret, 3, 5);
+
+        for share in &shares {
+            assert!(ecdsa.vss_verify_share(share, &commitments));
+        }
+        assert_eq!(commitments[0], ecdsa.generate_pub_key(&secret));
+        assert_eq!(ecdsa.vss_reconstruct(&shares[..3]), Some(secret.clone()));
+        assert_eq!(ecdsa.vss_reconstruct(&shares[2..]), Some(secret));
+    }
+
+    #[test]
+    fn test_reconstruct_rejects_bad_indices() {
+        let ecdsa = toy_curve();
+        let (shares, _) = ecdsa.vss_deal(&BigUint::from(7u32), 2, 3);
+
+        let repeated = [shares[0].clone(), shares[1].clone(), shares[1].clone()];
+        assert_eq!(ecdsa.vss_reconstruct(&repeated), None);
+        let zero = Share {
+            index: 0,
+            value: shares[0].value.clone(),
+        };
+        assert_eq!(ecdsa.vss_reconstruct(&[zero, shares[1].clone()]), None);
+        // 20 is 1 mod 19, the same point of the polynomial as index 1
+        assert_eq!(ecdsa.lagrange_coefficient(1, &[1, 20]), None);
+        assert_eq!(ecdsa.lagrange_coefficient(3, &[1, 2]), None);
+    }
+
+    #[test]
+    fn test_verify_tampered_share() {
+        let ecdsa = toy_curve();
+        let coefficients = [7u32, 3, 11].map(BigUint::from);
+        let (shares, commitments) = ecdsa.vss_deal_polynomial(&coefficients, 4);
+
+        let tampered = Share {
+            index: shares[1].index,
+            value: (&shares[1].value + 1u32) % &ecdsa.q_order,
+        };
+        assert!(!ecdsa.vss_verify_share(&tampered, &commitments));
+    }
+}
@@ -0,0 +1,383 @@
+// WebAuthn assertions (Web Authentication Level 2, §7.2) signed with ES256,
+// behind the `webauthn` feature: what a relying party checks when a passkey
+// or security key signs in. The authenticator signs
+//
+//   authenticatorData || SHA-256(clientDataJSON)
+//
+// with ECDSA P-256 and SHA-256, DER encoded, under the credential key the
+// relying party stored at registration as a COSE_Key (RFC 9053 EC2: kty 2,
+// alg -7, crv 1, x and y). Before the signature, `webauthn_verify_assertion`
+// checks that the client data is a "webauthn.get" for the expected challenge
+// and origin outside a cross-origin iframe, that the authenticator data is
+// for the RP ID, with user presence and, when asked for, user verification,
+// and that the signature counter moved forward. A counter that did not
+// suggests a cloned authenticator, unless both counters are zero, which is
+// how authenticators without one report it. The caller stores the returned
+// counter for next time.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ec_generic::Point;
+use serde_json::Value;
+
+use crate::hash::sha256;
+use crate::signer::Signature;
+use crate::ECDSA;
+
+const USER_PRESENT: u8 = 0x01;
+const USER_VERIFIED: u8 = 0x04;
+const BACKUP_ELIGIBLE: u8 = 0x08;
+const BACKUP_STATE: u8 = 0x10;
+
+// COSE_Key labels and values
+const KTY: i64 = 1;
+const ALG: i64 = 3;
+const CRV: i64 = -1;
+const X: i64 = -2;
+const Y: i64 = -3;
+const KTY_EC2: i64 = 2;
+const ALG_ES256: i64 = -7;
+const CRV_P256: i64 = 1;
+
+#[derive(Clone, Copy, Debug)]
+pub struct AssertionOptions<'a> {
+    pub rp_id: &'a str,
+    // Scheme, host and port, as in "https://login.example.com"
+    pub origin: &'a str,
+    pub challenge: &'a [u8],
+    pub require_user_verification: bool,
+    // The counter stored after the last assertion with this credential
+    pub sign_count: u32,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct AuthenticatorData {
+    pub user_present: bool,
+    pub user_verified: bool,
+    pub backup_eligible: bool,
+    pub backed_up: bool,
+    pub sign_count: u32,
+}
+
+impl ECDSA {
+    pub fn cose_key(&self, pub_key: &Point) -> Option<Vec<u8>> {
+        // The EC2 COSE_Key of a P-256 key, in the canonical CBOR encoding
+        // authenticators use
+        if !self.is_p256() || !self.is_valid_pub_key(pub_key) {
+            return None;
+        }
+        let point = self.encode_point_uncompressed(pub_key);
+        let mut cose = vec![0xa5, 0x01, 0x02, 0x03, 0x26, 0x20, 0x01, 0x21, 0x58, 0x20];
+        cose.extend(&point[1..33]);
+        cose.extend([0x22, 0x58, 0x20]);
+        cose.extend(&point[33..]);
+        Some(cose)
+    }
+
+    pub fn public_key_from_cose(&self, cose_key: &[u8]) -> Option<Point> {
+        // None unless an ES256 key on P-256, "alg" being optional
+        let (major, entries, mut rest) = read_head(cose_key)?;
+        if major != 5 || !self.is_p256() {
+            return None;
+        }
+        let (mut kty, mut alg, mut crv, mut x, mut y) = (None, None, None, None, None);
+        for _ in 0..entries {
+            let (label, after) = read_item(rest)?;
+            let (value, after) = read_item(after)?;
+            let slot = match label {
+                Cbor::Int(KTY) => &mut kty,
+                Cbor::Int(ALG) => &mut alg,
+                Cbor::Int(CRV) => &mut crv,
+                Cbor::Int(X) => &mut x,
+                Cbor::Int(Y) => &mut y,
+                Cbor::Int(_) | Cbor::Bytes(_) => {
+                    rest = after;
+                    continue;
+                }
+            };
+            if slot.replace(value).is_some() {
+                return None;
+            }
+            rest = after;
+        }
+        if !rest.is_empty()
+            || kty != Some(Cbor::Int(KTY_EC2))
+            || alg.is_some_and(|alg| alg != Cbor::Int(ALG_ES256))
+            || crv != Some(Cbor::Int(CRV_P256))
+        {
+            return None;
+        }
+        match (x, y) {
+            (Some(Cbor::Bytes(x)), Some(Cbor::Bytes(y))) if x.len() == 32 && y.len() == 32 => {
+                self.decode_point(&[&[0x04], x, y].concat())
+            }
+            _ => None,
+        }
+    }
+
+    pub fn webauthn_verify_assertion(
+        &self,
+        cose_key: &[u8],
+        authenticator_data: &[u8],
+        client_data_json: &[u8],
+        signature: &[u8],
+        options: &AssertionOptions,
+    ) -> Option<AuthenticatorData> {
+        // The authenticator data of a valid assertion; the raw bytes of each
+        // part, as the browser's AuthenticatorAssertionResponse holds them
+        let pub_key = self.public_key_from_cose(cose_key)?;
+        let client_data: Value = serde_json::from_slice(client_data_json).ok()?;
+        let challenge = URL_SAFE_NO_PAD.encode(options.challenge);
+        if client_data["type"] != "webauthn.get"
+            || client_data["challenge"] != challenge.as_str()
+            || client_data["origin"] != options.origin
+            || client_data["crossOrigin"] == true
+        {
+            return None;
+        }
+
+        let data = read_authenticator_data(authenticator_data)?;
+        let forward = data.sign_count > options.sign_count
+            || (data.sign_count == 0 && options.sign_count == 0);
+        if authenticator_data[..32] != sha256(options.rp_id.as_bytes())
+            || !data.user_present
+            || (options.require_user_verification && !data.user_verified)
+            || !forward
+        {
+            return None;
+        }
+
+        let signed = [authenticator_data, &sha256(client_data_json)].concat();
+        let signature = Signature::from_der(signature)?;
+        self.verify_standard(&sha256(&signed), &pub_key, &signature)
+            .then_some(data)
+    }
+}
+
+fn read_authenticator_data(bytes: &[u8]) -> Option<AuthenticatorData> {
+    // rpIdHash (32) || flags (1) || signCount (4, big endian) || extensions
+    if bytes.len() < 37 {
+        return None;
+    }
+    let flags = bytes[32];
+    Some(AuthenticatorData {
+        user_present: flags & USER_PRESENT != 0,
+        user_verified: flags & USER_VERIFIED != 0,
+        backup_eligible: flags & BACKUP_ELIGIBLE != 0,
+        backed_up: flags & BACKUP_STATE != 0,
+        sign_count: u32::from_be_bytes(bytes[33..37].try_into().ok()?),
+    })
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum Cbor<'a> {
+    Int(i64),
+    Bytes(&'a [u8]),
+}
+
+fn read_item(bytes: &[u8]) -> Option<(Cbor<'_>, &[u8])> {
+    // Integers and byte strings, the only values an EC2 key needs
+    let (major, argument, rest) = read_head(bytes)?;
+    match major {
+        0 => Some((Cbor::Int(i64::try_from(argument).ok()?), rest)),
+        1 => Some((Cbor::Int(-1 - i64::try_from(argument).ok()?), rest)),
+        2 => {
+            let len = usize::try_from(argument).ok()?;
+            (rest.len() >= len).then(|| (Cbor::Bytes(&rest[..len]), &rest[len..]))
+        }
+        _ => None,
+    }
+}
+
+fn read_head(bytes: &[u8]) -> Option<(u8, u64, &[u8])> {
+    // Major type and argument; indefinite lengths are not allowed
+    let (&initial, rest) = bytes.split_first()?;
+    let len = match initial & 0x1f {
+        info @ 0..=23 => return Some((initial >> 5, u64::from(info), rest)),
+        24 => 1,
+        25 => 2,
+        26 => 4,
+        27 => 8,
+        _ => return None,
+    };
+    let argument = rest.get(..len)?;
+    let argument = argument
+        .iter()
+        .fold(0u64, |n, byte| (n << 8) | u64::from(*byte));
+    Some((initial >> 5, argument, &rest[len..]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::curves::NamedCurve;
+
+    // An assertion by a P-256 key, made with the Python `cryptography`
+    // package, for login.example.com with signature counter 42
+    const ASSERTION: &str = include_str!("../testdata/webauthn_assertion.json");
+
+    fn fixture() -> [Vec<u8>; 5] {
+        let json: Value = serde_json::from_str(ASSERTION).unwrap();
+        let field = |name: &str| {
+            URL_SAFE_NO_PAD
+                .decode(json[name].as_str().unwrap())
+                .unwrap()
+        };
+        [
+            "credentialPublicKey",
+            "authenticatorData",
+            "clientDataJSON",
+            "signature",
+            "challenge",
+        ]
+        .map(field)
+    }
+
+    #[test]
+    fn test_verify_assertion() {
+        let ecdsa = NamedCurve::P256.preset();
+        let [cose_key, authenticator_data, client_data, signature, challenge] = fixture();
+        let options = AssertionOptions {
+            rp_id: "login.example.com",
+            origin: "https://login.example.com",
+            challenge: &challenge,
+            require_user_verification: true,
+            sign_count: 41,
+        };
+        let verify = |options: &AssertionOptions| {
+            ecdsa.webauthn_verify_assertion(
+                &cose_key,
+                &authenticator_data,
+                &client_data,
+                &signature,
+                options,
+            )
+        };
+        assert_eq!(
+            verify(&options),
+            Some(AuthenticatorData {
+                user_present: true,
+                user_verified: true,
+                backup_eligible: false,
+                backed_up: false,
+                sign_count: 42,
+            })
+        );
+
+        // Another RP, origin or challenge, and a counter that went back
+        for options in [
+            AssertionOptions {
+                rp_id: "example.com",
+                ..options
+            },
+            AssertionOptions {
+                origin: "https://evil.example.com",
+                ..options
+            },
+            AssertionOptions {
+                challenge: b"another challenge",
+                ..options
+            },
+            AssertionOptions {
+                sign_count: 42,
+                ..options
+            },
+        ] {
+            assert_eq!(verify(&options), None);
+        }
+
+        // A changed byte anywhere in what was signed
+        let mut flags_cleared = authenticator_data.clone();
+        flags_cleared[32] &= !USER_VERIFIED;
+        let options = AssertionOptions {
+            require_user_verification: false,
+            ..options
+        };
+        assert!(ecdsa
+            .webauthn_verify_assertion(
+                &cose_key,
+                &flags_cleared,
+                &client_data,
+                &signature,
+                &options
+            )
+            .is_none());
+        assert!(verify(&options).is_some());
+    }
+
+    #[test]
+    fn test_cose_key() {
+        let ecdsa = NamedCurve::P256.preset();
+        let [cose_key, ..] = fixture();
+        let pub_key = ecdsa.public_key_from_cose(&cose_key).unwrap();
+        assert_eq!(ecdsa.cose_key(&pub_key).unwrap(), cose_key);
+
+        // Without the optional "alg", with ES384's, and off P-256
+        let without_alg = [&[0xa4, 0x01, 0x02][..], &cose_key[5..]].concat();
+        assert_eq!(
+            ecdsa.public_key_from_cose(&without_alg),
+            Some(pub_key.clone())
+        );
+        let mut es384 = cose_key.clone();
+        es384[4] = 0x38;
+        es384.insert(5, 0x22);
+        assert_eq!(ecdsa.public_key_from_cose(&es384), None);
+        assert_eq!(
+            NamedCurve::Secp256k1
+                .preset()
+                .public_key_from_cose(&cose_key),
+            None
+        );
+        assert_eq!(ecdsa.public_key_from_cose(&cose_key[..70]), None);
+    }
+
+    #[test]
+    fn test_counterless_authenticator() {
+        // Counters that stay at zero are allowed, a signed assertion made
+        // here for one
+        let ecdsa = NamedCurve::P256.preset();
+        let priv_key = num_bigint::BigUint::from(0xc0ffeeu32);
+        let cose_key = ecdsa.cose_key(&ecdsa.generate_pub_key(&priv_key)).unwrap();
+        let client_data = format!(
+            r#"{{"type":"webauthn.get","challenge":"{}","origin":"https://example.com"}}"#,
+            URL_SAFE_NO_PAD.encode(b"challenge")
+        );
+        let mut authenticator_data = sha256(b"example.com");
+        authenticator_data.extend([USER_PRESENT | BACKUP_ELIGIBLE | BACKUP_STATE, 0, 0, 0, 0]);
+        let signed = [&authenticator_data[..], &sha256(client_data.as_bytes())].concat();
+        let signature = ecdsa
+            .sign_standard(&mut crate::rng(), &sha256(&signed), &priv_key)
+            .to_der();
+        let options = AssertionOptions {
+            rp_id: "example.com",
+            origin: "https://example.com",
+            challenge: b"challenge",
+            require_user_verification: false,
+            sign_count: 0,
+        };
+        let data = ecdsa
+            .webauthn_verify_assertion(
+                &cose_key,
+                &authenticator_data,
+                client_data.as_bytes(),
+                &signature,
+                &options,
+            )
+            .unwrap();
+        assert!(data.backed_up && !data.user_verified);
+        let options = AssertionOptions {
+            require_user_verification: true,
+            ..options
+        };
+        assert!(ecdsa
+            .webauthn_verify_assertion(
+                &cose_key,
+                &authenticator_data,
+                client_data.as_bytes(),
+                &signature,
+                &options,
+            )
+            .is_none());
+    }
+}
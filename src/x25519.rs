@@ -0,0 +1,125 @@
+// X25519 (RFC 7748): Diffie-Hellman on the u-coordinates of Curve25519,
+// v² = u³ + 486662u² + u mod 2^255 - 19, with the Montgomery ladder. Scalars
+// are clamped, multiples of the cofactor 8 with bit 254 set, and inputs are 32
+// little-endian bytes with the top bit ignored. It exists for the `25519`
+// suite of the Noise handshakes; like everything else on `BigUint` here, the
+// ladder is not constant time, since the bignum arithmetic under it is not.
+
+use num_bigint::BigUint;
+
+use crate::ecdh::SharedSecret;
+
+// The u-coordinate of the base point
+const BASE_U: u8 = 9;
+// (486662 - 2) / 4
+const A24: u32 = 121665;
+
+pub fn x25519(scalar: &[u8; 32], u: &[u8; 32]) -> [u8; 32] {
+    // RFC 7748, section 5, with a plain branch for cswap
+    let p: BigUint = (BigUint::from(1u32) << 255) - 19u32;
+    let mut k = *scalar;
+    k[0] &= 248;
+    k[31] &= 127;
+    k[31] |= 64;
+    let k = BigUint::from_bytes_le(&k);
+    let mut u = *u;
+    u[31] &= 127;
+    let x1 = BigUint::from_bytes_le(&u) % &p;
+
+    let one = BigUint::from(1u32);
+    let (mut x2, mut z2) = (one.clone(), BigUint::from(0u32));
+    let (mut x3, mut z3) = (x1.clone(), one);
+    let mut swap = false;
+    for t in (0..255).rev() {
+        let bit = k.bit(t);
+        if swap != bit {
+            core::mem::swap(&mut x2, &mut x3);
+            core::mem::swap(&mut z2, &mut z3);
+        }
+        swap = bit;
+
+        let a = (&x2 + &z2) % &p;
+        let aa = &a * &a % &p;
+        let b = (&x2 + &p - &z2) % &p;
+        let bb = &b * &b % &p;
+        let e = (&aa + &p - &bb) % &p;
+        let c = (&x3 + &z3) % &p;
+        let d = (&x3 + &p - &z3) % &p;
+        let da = d * &a % &p;
+        let cb = c * &b % &p;
+        let sum = (&da + &cb) % &p;
+        let difference = (&da + &p - &cb) % &p;
+        x3 = &sum * &sum % &p;
+        z3 = &x1 * (&difference * &difference % &p) % &p;
+        z2 = &e * ((&aa + BigUint::from(A24) * &e) % &p) % &p;
+        x2 = aa * bb % &p;
+    }
+    if swap {
+        core::mem::swap(&mut x2, &mut x3);
+        core::mem::swap(&mut z2, &mut z3);
+    }
+
+    // x2 / z2, with z2⁻¹ = z2^(p - 2)
+    let u = x2 * z2.modpow(&(&p - 2u32), &p) % &p;
+    let mut out = [0u8; 32];
+    let bytes = u.to_bytes_le();
+    out[..bytes.len()].copy_from_slice(&bytes);
+    out
+}
+
+pub fn x25519_base(scalar: &[u8; 32]) -> [u8; 32] {
+    let mut base = [0u8; 32];
+    base[0] = BASE_U;
+    x25519(scalar, &base)
+}
+
+pub fn x25519_diffie_hellman(priv_key: &[u8; 32], pub_key: &[u8; 32]) -> Option<SharedSecret> {
+    // None for an all-zero output, which a peer key of small order forces
+    let shared = x25519(priv_key, pub_key);
+    (shared != [0; 32]).then(|| SharedSecret::from_bytes(shared.to_vec()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bytes32(hex: &str) -> [u8; 32] {
+        hex::decode(hex).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_x25519() {
+        // RFC 7748, section 5.2
+        let scalar = bytes32("a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4");
+        let u = bytes32("e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c");
+        assert_eq!(
+            hex::encode(x25519(&scalar, &u)),
+            "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552"
+        );
+    }
+
+    #[test]
+    fn test_x25519_diffie_hellman() {
+        // RFC 7748, section 6.1
+        let alice = bytes32("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+        let bob = bytes32("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+        let alice_pub = x25519_base(&alice);
+        let bob_pub = x25519_base(&bob);
+        assert_eq!(
+            hex::encode(alice_pub),
+            "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"
+        );
+        assert_eq!(
+            hex::encode(bob_pub),
+            "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"
+        );
+        assert_eq!(
+            hex::encode(x25519(&alice, &bob_pub)),
+            "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742"
+        );
+        assert_eq!(x25519(&bob, &alice_pub), x25519(&alice, &bob_pub));
+
+        // u = 0 has order 1 and forces the all-zero output
+        assert!(x25519_diffie_hellman(&alice, &[0; 32]).is_none());
+    }
+}
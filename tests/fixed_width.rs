@@ -0,0 +1,69 @@
+// The allocation-free claim of `FixedCurve` and `FixedCurve384`. A global
+// allocator replaces the one of the whole test binary, so it gets a binary
+// of its own.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use rs_ecc::{curves, FixedCurve, FixedCurve384};
+use sha2::{Digest, Sha256, Sha384};
+
+// Counts the allocations of the current thread only, so tests running
+// alongside do not disturb the count
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+fn bytes<const L: usize>(value: u32) -> [u8; L] {
+    let mut bytes = [0u8; L];
+    bytes[L - 4..].copy_from_slice(&value.to_be_bytes());
+    bytes
+}
+
+#[test]
+fn test_fixed_curve_does_not_allocate() {
+    for ecdsa in [curves::p256(), curves::secp256k1()] {
+        let curve = FixedCurve::new(&ecdsa).unwrap();
+        let priv_key = bytes(0xc0ffee);
+        let k_random = bytes(0xdecaf);
+        let pub_key = curve.public_key(&priv_key).unwrap();
+
+        let before = allocations();
+        let digest: [u8; 32] = Sha256::digest(b"Bob -> 1 BTC -> Alice").into();
+        let signature = curve.sign_digest(&digest, &priv_key, &k_random).unwrap();
+        let valid = curve.verify_digest(&digest, &pub_key, &signature);
+        assert_eq!(allocations(), before);
+        assert!(valid);
+    }
+
+    let curve = FixedCurve384::new(&curves::p384()).unwrap();
+    let (priv_key, k_random) = (bytes(0xc0ffee), bytes(0xdecaf));
+    let pub_key = curve.public_key(&priv_key).unwrap();
+
+    let before = allocations();
+    let digest: [u8; 48] = Sha384::digest(b"Bob -> 1 BTC -> Alice").into();
+    let signature = curve.sign_digest(&digest, &priv_key, &k_random).unwrap();
+    let valid = curve.verify_digest(&digest, &pub_key, &signature);
+    assert_eq!(allocations(), before);
+    assert!(valid);
+}
@@ -0,0 +1,26 @@
+// Nostr through the public API: the event type, key encodings and the
+// signing methods on `ECDSA`.
+#![cfg(feature = "nostr")]
+
+use rs_ecc::{curves, nsec, priv_key_from_nsec, NostrEvent};
+
+// NIP-19's example key and the event signed with it in the unit tests
+const NSEC: &str = "nsec1vl029mgpspedva04g90vltkh6fvh240zqtv9k0t9af8935ke9laqsnlfe5";
+const NPUB: &str = "npub10elfcs4fr0l0r8af98jlmgdh9c8tcxjvz9qkw038js35mp4dma8qzvjptg";
+const EVENT: &str = include_str!("../testdata/nostr_event.json");
+
+#[test]
+fn test_nostr_sign_verify() {
+    let secp256k1 = curves::secp256k1();
+    let priv_key = priv_key_from_nsec(NSEC).unwrap();
+    assert_eq!(nsec(&priv_key), NSEC);
+    let pub_key = secp256k1.pub_key_from_npub(NPUB).unwrap();
+    assert_eq!(secp256k1.npub(&pub_key).unwrap(), NPUB);
+
+    let event: NostrEvent = serde_json::from_str(EVENT).unwrap();
+    assert!(secp256k1.nostr_verify(&event));
+    let event = secp256k1
+        .nostr_sign(&priv_key, 1_700_000_000, 1, Vec::new(), "gm")
+        .unwrap();
+    assert!(secp256k1.nostr_verify(&event));
+}
@@ -0,0 +1,551 @@
+// The crate as a dependent sees it: everything here goes through the root
+// re-exports and the public modules (`curves`, `group`, and the protocol
+// constant tables such as `dnssec` and `openpgp`), never a private module path.
+
+use std::collections::{BTreeSet, HashSet};
+
+use rs_ecc::dnssec::{self, Record, Rrsig};
+use rs_ecc::openpgp::{self, OpenPgpKey, OpenPgpSignature};
+use rs_ecc::signature::{Signer, Verifier};
+#[cfg(feature = "std")]
+use rs_ecc::FileCounter;
+use rs_ecc::{
+    curves, did_key_ed25519, group, merkle_root, resolve_did_key, signature_base, tap_leaf_hash,
+    verify_inclusion, BatchSignature, BigUint, CipherState, Ciphertext, CmsOptions, CmsSignedData,
+    Commitment, CounterStore, CurvePoint, DidKey, DkgDealing, DleqProof, Group, HandshakePattern,
+    HandshakeState, HttpMessage, InclusionProof, KeyGenMessage1, KeyGenMessage2, KeyPair,
+    KnowledgeProof, NoiseDh, OprfMode, PaillierPrivateKey, Party1, Party2, Payload, PolicyOutcome,
+    Scalar, SecretScalar, Share, SignMessage1, SignMessage2, Signature, SignatureParams,
+    SignedMessage, SignerInfo, SigningKey, Sm2KeyExchange, Sm2SessionKey, Spake2, Spake2Keys,
+    TapTree, VerificationPolicy, VerifyingKey, ECDSA, HTTP_SIG_ALGORITHM, TAPSCRIPT_LEAF_VERSION,
+};
+
+#[test]
+fn test_key_pair_and_trait_keys() {
+    let p256: ECDSA = curves::p256();
+    let key_pair = KeyPair::generate(&p256);
+    let signature = key_pair.sign(b"message");
+    assert!(key_pair
+        .verifying_key()
+        .verify(b"message", &signature)
+        .is_ok());
+
+    let signing_key = SigningKey::from_bytes(&p256, &key_pair.signing_key().to_bytes()).unwrap();
+    let signature: Signature = signing_key.sign(b"message");
+    assert!(key_pair.verify(b"message", &signature));
+
+    // DER text and raw key bytes through `TryFrom`
+    let der = hex::encode(signature.to_der());
+    assert_eq!(Signature::try_from(der.as_str()).unwrap(), signature);
+    let bytes = key_pair.verifying_key().to_bytes();
+    let verifying_key = VerifyingKey::try_from((&p256, bytes.as_slice())).unwrap();
+
+    // Keys and signatures as set members
+    let keys: BTreeSet<VerifyingKey> = [verifying_key.clone(), key_pair.verifying_key()].into();
+    assert_eq!(keys.len(), 1);
+    let signatures: HashSet<Signature> = [signature.clone(), signature].into();
+    assert_eq!(signatures.len(), 1);
+}
+
+#[test]
+fn test_points_and_scalars() {
+    let secp256k1 = curves::secp256k1();
+    let two = Scalar::new(&secp256k1, &BigUint::from(2u32));
+    let generator = CurvePoint::generator(&secp256k1);
+    let doubled = generator.mul(&SecretScalar::new(two.to_biguint().clone()));
+    assert_eq!(doubled, generator.double());
+    assert_eq!(
+        CurvePoint::from_bytes(&secp256k1, &doubled.to_bytes()).unwrap(),
+        doubled
+    );
+}
+
+#[test]
+fn test_oid_registry() {
+    use rs_ecc::oid::{self, HashAlgorithm};
+
+    assert_eq!(
+        HashAlgorithm::from_ecdsa_oid(oid::ECDSA_WITH_SHA384),
+        Some(HashAlgorithm::Sha384)
+    );
+    assert_eq!(oid::name(oid::PRIME256V1), Some("prime256v1"));
+    assert!(curves::from_oid(oid::SECP256K1).is_some());
+}
+
+// The subgroup of order 11 of Z_23^*, generated by 4: DSA through the same
+// generic signing code, from a backend defined outside the crate
+struct Dsa(BigUint);
+
+impl Group for Dsa {
+    type Element = BigUint;
+
+    fn generator(&self) -> BigUint {
+        BigUint::from(4u32)
+    }
+
+    fn order(&self) -> &BigUint {
+        &self.0
+    }
+
+    fn identity(&self) -> BigUint {
+        BigUint::from(1u32)
+    }
+
+    fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a * b) % 23u32
+    }
+
+    fn mul(&self, a: &BigUint, k: &BigUint) -> BigUint {
+        a.modpow(k, &BigUint::from(23u32))
+    }
+
+    fn mul_secret(&self, a: &BigUint, k: &SecretScalar) -> BigUint {
+        self.mul(a, k.expose_secret())
+    }
+
+    fn contains(&self, a: &BigUint) -> bool {
+        *a != BigUint::from(1u32) && self.mul(a, &self.0) == BigUint::from(1u32)
+    }
+
+    fn x_coordinate(&self, a: &BigUint) -> Option<BigUint> {
+        Some(a.clone())
+    }
+}
+
+#[test]
+fn test_external_group() {
+    let dsa = Dsa(BigUint::from(11u32));
+    let priv_key = BigUint::from(7u32);
+    let pub_key = dsa.mul(&dsa.generator(), &priv_key);
+    let hash = BigUint::from(5u32);
+    let signature = group::sign(&dsa, &hash, &priv_key, &BigUint::from(3u32)).unwrap();
+    assert!(group::verify(&dsa, &hash, &pub_key, &signature));
+    assert!(!group::verify(
+        &dsa,
+        &BigUint::from(6u32),
+        &pub_key,
+        &signature
+    ));
+
+    // ECDSA itself is one of the backends
+    let p256 = curves::p256();
+    let generator = Group::generator(&p256);
+    assert!(p256.contains(&generator));
+}
+
+#[test]
+fn test_signed_message_policy() {
+    let ecdsa = curves::p256();
+    let (alice, bob) = (BigUint::from(0xa11ceu32), BigUint::from(0xb0bu32));
+    let trusted = vec![
+        (&ecdsa, ecdsa.generate_pub_key(&alice)),
+        (&ecdsa, ecdsa.generate_pub_key(&bob)),
+    ];
+    let mut message = ecdsa
+        .sign_message(Payload::digest_of(b"release"), 1_760_000_000, &alice)
+        .unwrap();
+    assert!(ecdsa.add_signature(&mut message, 1_760_000_000, &bob));
+    let message = SignedMessage::from_bytes(&message.to_bytes()).unwrap();
+    let signer: &SignerInfo = &message.signers[1];
+    assert_eq!(signer.algorithm, "ES256");
+
+    let policy = VerificationPolicy::new(trusted, 2).unwrap();
+    assert_eq!(
+        policy.evaluate(&message),
+        PolicyOutcome {
+            matched: vec![0, 1],
+            satisfied: true
+        }
+    );
+}
+
+#[test]
+fn test_voprf_round_trip() {
+    let ecdsa = curves::p256();
+    let priv_key = BigUint::from(0x5eedu32);
+    let pub_key = ecdsa.generate_pub_key(&priv_key);
+    let r_random = BigUint::from(0xb11du32);
+    let blinded = ecdsa
+        .oprf_blind(OprfMode::Verifiable, b"token", &r_random)
+        .unwrap();
+    let (evaluated, proof): (_, DleqProof) = ecdsa
+        .voprf_blind_evaluate(&priv_key, &blinded, &BigUint::from(0x1234u32))
+        .unwrap();
+    let output = ecdsa.voprf_finalize(&pub_key, b"token", &r_random, &blinded, &evaluated, &proof);
+    assert!(output.is_some());
+    assert_eq!(
+        output,
+        ecdsa.oprf_evaluate(OprfMode::Verifiable, &priv_key, b"token")
+    );
+}
+
+#[test]
+fn test_noise_xx() {
+    let dh = NoiseDh::X25519;
+    let (alice_priv, bob_priv) = (dh.generate_priv_key(), dh.generate_priv_key());
+    let mut alice = HandshakeState::new(dh, HandshakePattern::XX, true, b"", &alice_priv, None);
+    let mut bob = HandshakeState::new(dh, HandshakePattern::XX, false, b"", &bob_priv, None);
+
+    let msg = alice.write_message(b"");
+    assert_eq!(bob.read_message(&msg), Some(vec![]));
+    let msg = bob.write_message(b"");
+    assert_eq!(alice.read_message(&msg), Some(vec![]));
+    let msg = alice.write_message(b"hello");
+    assert_eq!(bob.read_message(&msg), Some(b"hello".to_vec()));
+    assert!(alice.is_finished() && bob.is_finished());
+    assert_eq!(bob.remote_static(), dh.public_key(&alice_priv).as_deref());
+
+    let (mut alice_send, _): (CipherState, CipherState) = alice.split();
+    let (mut bob_recv, _) = bob.split();
+    let ciphertext = alice_send.encrypt_with_ad(b"ad", b"transport");
+    assert_eq!(
+        bob_recv.decrypt_with_ad(b"ad", &ciphertext),
+        Some(b"transport".to_vec())
+    );
+}
+
+// A counter held in memory, standing in for an HSM's monotonic counter
+struct MemoryCounter(u64);
+
+impl CounterStore for MemoryCounter {
+    fn load(&mut self) -> Option<u64> {
+        Some(self.0)
+    }
+
+    fn store(&mut self, counter: u64) -> bool {
+        self.0 = counter;
+        true
+    }
+}
+
+#[test]
+fn test_counter_stores() {
+    let ecdsa = curves::p256();
+    let priv_key = BigUint::from(0xc0ffeeu32);
+    let pub_key = ecdsa.generate_pub_key(&priv_key);
+    let digest = [0x42; 32];
+
+    let mut memory = MemoryCounter(41);
+    let (signature, counter) = ecdsa
+        .sign_with_counter(&mut memory, &digest, &priv_key)
+        .unwrap();
+    assert_eq!(counter, 42);
+    assert!(ecdsa.verify_standard(&digest, &pub_key, &signature));
+
+    #[cfg(feature = "std")]
+    {
+        let path = std::env::temp_dir().join(format!("rs_ecc-counter-{}", std::process::id()));
+        let mut file = FileCounter::new(&path);
+        let (_, first) = ecdsa
+            .sign_with_counter(&mut file, &digest, &priv_key)
+            .unwrap();
+        let (_, second) = ecdsa
+            .sign_with_counter(&mut file, &digest, &priv_key)
+            .unwrap();
+        assert_eq!((first, second), (1, 2));
+        std::fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_dkg_and_shares() {
+    // 2-of-3: every party deals, each then combines the shares it received
+    let ecdsa = curves::secp256k1();
+    let (threshold, parties) = (2, 3u32);
+    let (dealings, dealt): (Vec<DkgDealing>, Vec<Vec<Share>>) = (1..=parties)
+        .map(|dealer| ecdsa.dkg_deal(dealer, threshold, parties))
+        .unzip();
+    let finals: Vec<(Share, _)> = (1..=parties)
+        .map(|index| {
+            let received: Vec<Share> = dealt
+                .iter()
+                .map(|shares| shares[index as usize - 1].clone())
+                .collect();
+            ecdsa
+                .dkg_finalize(index, threshold, &dealings, &received)
+                .unwrap()
+        })
+        .collect();
+    let group_key = finals[0].1.clone();
+    assert!(finals.iter().all(|(_, key)| *key == group_key));
+
+    let shares: Vec<Share> = finals.into_iter().map(|(share, _)| share).collect();
+    let group_priv = ecdsa.vss_reconstruct(&shares[..2]).unwrap();
+    assert_eq!(ecdsa.generate_pub_key(&group_priv), group_key);
+}
+
+#[test]
+fn test_two_party_ecdsa() {
+    // Party 1 holds the Paillier key, both end up with the same public key
+    let ecdsa = curves::secp256k1();
+    let (x1, paillier, msg1): (_, PaillierPrivateKey, KeyGenMessage1) =
+        ecdsa.two_party_keygen_1(b"keygen");
+    let (party2, msg2): (Party2, KeyGenMessage2) =
+        ecdsa.two_party_keygen_2(b"keygen", &msg1).unwrap();
+    let party1: Party1 = ecdsa
+        .two_party_keygen_finish(b"keygen", x1, paillier, &msg2)
+        .unwrap();
+    assert_eq!(party1.pub_key(), party2.pub_key());
+
+    let hash = BigUint::from(0x1234_5678u32);
+    let (k1, msg1): (_, SignMessage1) = ecdsa.two_party_sign_1(b"sign");
+    let msg2: SignMessage2 = ecdsa
+        .two_party_sign_2(b"sign", &party2, &hash, &msg1)
+        .unwrap();
+    let signature = ecdsa
+        .two_party_sign_finish(b"sign", &party1, &k1, &hash, &msg2)
+        .unwrap();
+    assert!(ecdsa.verification(&hash, party1.pub_key(), &signature));
+}
+
+#[test]
+fn test_sm2_key_exchange() {
+    let ecdsa = curves::p256();
+    let (alice_priv, bob_priv) = (BigUint::from(0xa11ceu32), BigUint::from(0xb0bu32));
+    let alice: Sm2KeyExchange = ecdsa.sm2_exchange_start(true, &alice_priv, b"alice");
+    let bob = ecdsa.sm2_exchange_start(false, &bob_priv, b"bob");
+    let alice_pub = ecdsa.generate_pub_key(&alice_priv);
+    let bob_pub = ecdsa.generate_pub_key(&bob_priv);
+    let alice_key: Sm2SessionKey = ecdsa
+        .sm2_exchange_finish(&alice, &bob_pub, b"bob", bob.r_point(), 16)
+        .unwrap();
+    let bob_key = ecdsa
+        .sm2_exchange_finish(&bob, &alice_pub, b"alice", alice.r_point(), 16)
+        .unwrap();
+    assert_eq!(alice_key.key(), bob_key.key());
+    assert!(alice_key.verify_confirmation(bob_key.confirmation()));
+    assert!(bob_key.verify_confirmation(alice_key.confirmation()));
+}
+
+#[test]
+fn test_spake2() {
+    let ecdsa = curves::p256();
+    let alice: Spake2 = ecdsa.spake2_start(true, b"hunter2", b"alice", b"bob");
+    let bob = ecdsa.spake2_start(false, b"hunter2", b"alice", b"bob");
+    let alice_keys: Spake2Keys = ecdsa.spake2_finish(&alice, bob.message(), b"").unwrap();
+    let bob_keys = ecdsa.spake2_finish(&bob, alice.message(), b"").unwrap();
+    assert_eq!(alice_keys.shared_key(), bob_keys.shared_key());
+    assert!(bob_keys.verify_confirmation(alice_keys.confirmation()));
+}
+
+#[test]
+fn test_batch_signature() {
+    let ecdsa = curves::p256();
+    let priv_key = BigUint::from(0xc0ffeeu32);
+    let pub_key = ecdsa.generate_pub_key(&priv_key);
+    let messages: [&[u8]; 3] = [b"one", b"two", b"three"];
+    let (batch, proofs): (BatchSignature, Vec<InclusionProof>) = ecdsa
+        .sign_batch(&mut rand::thread_rng(), &messages, &priv_key)
+        .unwrap();
+    assert_eq!(merkle_root(&messages), Some(batch.root.clone()));
+    assert!(ecdsa.verify_batch_signature(&batch, &pub_key));
+    assert!(verify_inclusion(
+        b"two",
+        &proofs[1],
+        batch.size,
+        &batch.root
+    ));
+    assert!(ecdsa.verify_batch_member(b"three", &proofs[2], &batch, &pub_key));
+    assert!(!ecdsa.verify_batch_member(b"three", &proofs[1], &batch, &pub_key));
+}
+
+#[test]
+fn test_dnssec() {
+    let ecdsa = curves::p256();
+    let priv_key = BigUint::from(0x20e5u32);
+    let pub_key = ecdsa.generate_pub_key(&priv_key);
+    let dnskey = ecdsa
+        .dnskey_rdata(dnssec::ZONE_KEY | dnssec::SECURE_ENTRY_POINT, &pub_key)
+        .unwrap();
+    let records = vec![Record::new(
+        "www.example.net.",
+        dnssec::TYPE_A,
+        3600,
+        &[192, 0, 2, 1],
+    )];
+    let rrsig: Rrsig = ecdsa
+        .dnssec_sign(
+            &records,
+            "example.net.",
+            dnssec::key_tag(&dnskey),
+            1_760_000_000,
+            1_762_592_000,
+            &priv_key,
+        )
+        .unwrap();
+    assert_eq!(rrsig.algorithm, dnssec::ECDSAP256SHA256);
+    let pub_key = ecdsa.public_key_from_dnskey(&dnskey).unwrap();
+    assert!(ecdsa.dnssec_verify(&records, &rrsig, &pub_key));
+    assert!(dnssec::ds_rdata("example.net.", &dnskey, dnssec::DIGEST_SHA256).is_some());
+}
+
+#[test]
+fn test_cms() {
+    let ecdsa = curves::p256();
+    let priv_key = BigUint::from(0xc0ffeeu32);
+    let pub_key = ecdsa.generate_pub_key(&priv_key);
+    let options = CmsOptions {
+        detached: true,
+        signing_time: Some("20261015093000Z"),
+        ..Default::default()
+    };
+    let cms = ecdsa.cms_sign(b"contract", &priv_key, &options).unwrap();
+    let signed: CmsSignedData = ecdsa.cms_verify(&cms, Some(b"contract"), &pub_key).unwrap();
+    assert_eq!(signed.content, b"contract");
+    assert_eq!(signed.signing_time.as_deref(), Some("20261015093000Z"));
+}
+
+#[test]
+fn test_openpgp() {
+    let ecdsa = curves::p256();
+    let priv_key = BigUint::from(0xc0ffeeu32);
+    let key: OpenPgpKey = ecdsa
+        .openpgp_key(&ecdsa.generate_pub_key(&priv_key), 1_760_000_000)
+        .unwrap();
+    let signature = ecdsa
+        .openpgp_sign(
+            b"release",
+            &priv_key,
+            &key,
+            openpgp::BINARY_SIGNATURE,
+            1_760_000_000,
+        )
+        .unwrap();
+    let armored = openpgp::openpgp_armor("SIGNATURE", &signature.to_packet());
+    let (kind, packet) = openpgp::openpgp_dearmor(&armored).unwrap();
+    assert_eq!(kind, "SIGNATURE");
+    let packets = openpgp::read_packets(&packet).unwrap();
+    assert_eq!(packets[0].0, openpgp::SIGNATURE_PACKET);
+    let signature = OpenPgpSignature::from_body(packets[0].1).unwrap();
+    assert_eq!(signature.issuer_fingerprint(), Some(&key.fingerprint()[..]));
+    assert!(ecdsa.openpgp_verify(b"release", &signature, &key));
+}
+
+#[test]
+fn test_taproot() {
+    let ecdsa = curves::secp256k1();
+    let priv_key = BigUint::from(0xc0ffeeu32);
+    let internal = ecdsa.x_only(&ecdsa.generate_pub_key(&priv_key)).unwrap();
+    let tree = TapTree::branch(TapTree::leaf(&[0x51]), TapTree::leaf(&[0x52]));
+    let TapTree::Branch(left, _) = &tree else {
+        unreachable!()
+    };
+    assert_eq!(
+        left.merkle_root(),
+        tap_leaf_hash(TAPSCRIPT_LEAF_VERSION, &[0x51])
+    );
+    let root = tree.merkle_root();
+    let (output, _) = ecdsa.taproot_output_key(&internal, Some(&root)).unwrap();
+
+    let sighash = [0x5a; 32];
+    let signature = ecdsa
+        .taproot_sign_key_path(&sighash, &priv_key, Some(&root), &[0; 32])
+        .unwrap();
+    assert!(ecdsa.bip340_verify(&sighash, &output, &signature));
+    let control_block = ecdsa.taproot_control_block(&internal, &tree, 1).unwrap();
+    assert!(ecdsa.taproot_verify_script_path(&output, &[0x52], &control_block));
+}
+
+#[test]
+fn test_did_key() {
+    let ecdsa = curves::p384();
+    let pub_key = ecdsa.generate_pub_key(&BigUint::from(0xc0ffeeu32));
+    let did = ecdsa.did_key(&pub_key).unwrap();
+    assert!(did.starts_with("did:key:z"));
+    assert_eq!(
+        resolve_did_key(&format!("{}#key-1", did)),
+        Some(DidKey::Ec {
+            curve: curves::NamedCurve::P384,
+            pub_key
+        })
+    );
+    let ed25519 = did_key_ed25519(&[7; 32]);
+    assert_eq!(resolve_did_key(&ed25519), Some(DidKey::Ed25519([7; 32])));
+}
+
+#[test]
+fn test_http_signatures() {
+    let ecdsa = curves::p256();
+    let priv_key = BigUint::from(0xc0ffeeu32);
+    let pub_key = ecdsa.generate_pub_key(&priv_key);
+    let message = HttpMessage::request("POST", "https://example.com/foo?param=Value")
+        .header("Content-Type", "application/json");
+    let mut params = SignatureParams::new(&["@method", "@path", "content-type"]);
+    params.created = Some(1_760_000_000);
+    params.alg = Some(HTTP_SIG_ALGORITHM.into());
+    assert!(signature_base(&message, &params.serialize())
+        .unwrap()
+        .starts_with("\"@method\": POST\n\"@path\": /foo\n"));
+
+    let (input, signature) = ecdsa
+        .http_sign(&message, "sig1", &params, &priv_key)
+        .unwrap();
+    assert_eq!(
+        ecdsa.http_verify(&message, &input, &signature, "sig1", &pub_key),
+        Some(params)
+    );
+}
+
+#[test]
+fn test_pedersen_commitments() {
+    let ecdsa = curves::secp256k1();
+    let (v1, r1) = (BigUint::from(3u32), BigUint::from(0x1111u32));
+    let (v2, r2) = (BigUint::from(4u32), BigUint::from(0x2222u32));
+    let sum =
+        Commitment::commit(&ecdsa, &v1, &r1).add(&ecdsa, &Commitment::commit(&ecdsa, &v2, &r2));
+    assert!(sum.verify_opening(&ecdsa, &(v1 + v2), &(r1 + r2)));
+    assert!(!sum.verify_opening(&ecdsa, &BigUint::from(8u32), &BigUint::from(0x3333u32)));
+}
+
+#[test]
+fn test_elgamal_tally() {
+    // Three encrypted votes, added up without decrypting any of them
+    let ecdsa = curves::p256();
+    let priv_key = BigUint::from(0xc0ffeeu32);
+    let pub_key = ecdsa.generate_pub_key(&priv_key);
+    let total: Ciphertext = [1u32, 0, 1]
+        .iter()
+        .enumerate()
+        .map(|(i, vote)| {
+            let k_random = BigUint::from(0x1000u32 + i as u32);
+            ecdsa.elgamal_encrypt(&pub_key, &BigUint::from(*vote), &k_random)
+        })
+        .reduce(|sum, vote| sum.add(&ecdsa, &vote))
+        .unwrap();
+    let total = ecdsa.elgamal_rerandomize(&pub_key, &total, &BigUint::from(0x77u32));
+    assert_eq!(
+        ecdsa.elgamal_decrypt(&priv_key, &total, &BigUint::from(10u32)),
+        Some(BigUint::from(2u32))
+    );
+}
+
+#[test]
+fn test_ecies() {
+    let ecdsa = curves::p256();
+    let priv_key = BigUint::from(0xc0ffeeu32);
+    let pub_key = ecdsa.generate_pub_key(&priv_key);
+    let ciphertext = ecdsa.ecies_encrypt(&pub_key, b"sealed", &BigUint::from(0x5eedu32));
+    assert_eq!(
+        ecdsa.ecies_decrypt(&priv_key, &ciphertext),
+        Some(b"sealed".to_vec())
+    );
+    assert_eq!(ecdsa.ecies_decrypt(&BigUint::from(3u32), &ciphertext), None);
+}
+
+#[test]
+fn test_proofs_of_knowledge() {
+    let ecdsa = curves::p256();
+    let priv_key = BigUint::from(0xc0ffeeu32);
+    let pub_key = ecdsa.generate_pub_key(&priv_key);
+    let proof: KnowledgeProof =
+        ecdsa.prove_knowledge(&priv_key, b"registration", &BigUint::from(0x1234u32));
+    assert!(ecdsa.verify_knowledge(&pub_key, b"registration", &proof));
+    assert!(!ecdsa.verify_knowledge(&pub_key, b"login", &proof));
+
+    // The same key behind B = dA and dH, for a second base H
+    let generator = Group::generator(&ecdsa);
+    let base = ecdsa.pedersen_generator();
+    let other = ecdsa.mul_vartime(&base, &priv_key);
+    let proof: DleqProof = ecdsa.dleq_prove(&priv_key, &generator, &base, &BigUint::from(0x99u32));
+    assert!(ecdsa.dleq_verify(&generator, &pub_key, &base, &other, &proof));
+}
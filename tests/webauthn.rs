@@ -0,0 +1,43 @@
+// WebAuthn assertion checks through the public API.
+#![cfg(feature = "webauthn")]
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rs_ecc::{curves, AssertionOptions};
+use serde_json::Value;
+
+// The P-256 assertion of the unit tests, for login.example.com with
+// signature counter 42
+const ASSERTION: &str = include_str!("../testdata/webauthn_assertion.json");
+
+#[test]
+fn test_webauthn_verify_assertion() {
+    let json: Value = serde_json::from_str(ASSERTION).unwrap();
+    let field = |name: &str| {
+        URL_SAFE_NO_PAD
+            .decode(json[name].as_str().unwrap())
+            .unwrap()
+    };
+    let challenge = field("challenge");
+    let options = AssertionOptions {
+        rp_id: "login.example.com",
+        origin: "https://login.example.com",
+        challenge: &challenge,
+        require_user_verification: true,
+        sign_count: 41,
+    };
+
+    let p256 = curves::p256();
+    let cose_key = field("credentialPublicKey");
+    assert!(p256.public_key_from_cose(&cose_key).is_some());
+    let data = p256
+        .webauthn_verify_assertion(
+            &cose_key,
+            &field("authenticatorData"),
+            &field("clientDataJSON"),
+            &field("signature"),
+            &options,
+        )
+        .unwrap();
+    assert_eq!(data.sign_count, 42);
+}